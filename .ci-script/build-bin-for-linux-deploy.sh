@@ -15,3 +15,4 @@ cargo install --target x86_64-unknown-linux-musl --path . --force
 cd ../docker/release
 cp $HOME/.cargo/bin/cita-cli ./
 tar -zcf cita-cli-x86_64-musl-tls-"$TRAVIS_TAG".tar.gz cita-cli
+../../.ci-script/checksum-release.sh cita-cli-x86_64-musl-tls-"$TRAVIS_TAG".tar.gz ./cita-cli
@@ -1,5 +1,6 @@
 #!/bin/bash
 cd ./cita-cli
-cargo install --no-default-features --features openssl --path . --force
+cargo install --no-default-features --features openssl,tx-v0,tx-v1,tx-v2 --path . --force
 cd ../docker/release
 tar -zcf cita-cli-x86_64-mac-osx-tls-"$TRAVIS_TAG".tar.gz $HOME/.cargo/bin/cita-cli
+../../.ci-script/checksum-release.sh cita-cli-x86_64-mac-osx-tls-"$TRAVIS_TAG".tar.gz $HOME/.cargo/bin/cita-cli
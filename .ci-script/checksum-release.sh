@@ -0,0 +1,14 @@
+#!/bin/bash
+# Write the sha256 of the archive of a release to `<archive>.sha256`, signed into
+# `<archive>.sha256.sig` when $RELEASE_SIGNING_KEY is set, which `cita-cli update` checks
+# Usage: checksum-release.sh <archive> <cita-cli>
+set -e
+if command -v sha256sum > /dev/null; then
+  sha256sum "$1" > "$1".sha256
+else
+  shasum -a 256 "$1" > "$1".sha256
+fi
+if [ -n "$RELEASE_SIGNING_KEY" ]; then
+  # The key goes through the environment, an argument would show in the process list
+  CITA_CLI_PRIVATE_KEY="$RELEASE_SIGNING_KEY" "$2" key sign-message --message "$(cat "$1".sha256)" > "$1".sha256.sig
+fi
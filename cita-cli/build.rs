@@ -26,7 +26,7 @@ fn main() {
 
 pub fn get_commit_describe() -> Option<String> {
     std::process::Command::new("git")
-        .args(&["describe", "--dirty", "--tags"])
+        .args(["describe", "--dirty", "--tags"])
         .output()
         .ok()
         .and_then(|r| {
@@ -44,7 +44,7 @@ pub fn get_commit_describe() -> Option<String> {
 pub fn get_commit_date() -> Option<String> {
     std::process::Command::new("git")
         .env("TZ", "UTC")
-        .args(&["log", "-1", "--date=short-local", "--pretty=format:%cd"])
+        .args(["log", "-1", "--date=short-local", "--pretty=format:%cd"])
         .output()
         .ok()
         .and_then(|r| {
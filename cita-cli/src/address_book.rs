@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use cita_tool::{remove_0x, to_checksum_address, Address};
+
+use crate::profile;
+
+/// The long names of the args that take an address, names in the address book are accepted there
+const ADDRESS_ARGS: [&str; 7] = [
+    "address",
+    "account",
+    "contract",
+    "from",
+    "to",
+    "permission",
+    "admin",
+];
+
+/// Local name -> address mapping, stored in `~/.cita-cli/address_book`, encrypted by
+/// `profile encrypt`
+pub struct AddressBook {
+    path: PathBuf,
+    entries: BTreeMap<String, String>,
+}
+
+impl AddressBook {
+    /// Load the address book, empty if the file doesn't exist
+    pub fn load() -> Self {
+        let path = profile::address_book_path();
+        let entries = profile::read(&path)
+            .ok()
+            .flatten()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        AddressBook { path, entries }
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let content = serde_json::to_string_pretty(&self.entries).unwrap();
+        profile::write(&self.path, &content).map_err(io::Error::other)
+    }
+
+    /// Add or replace a name, return the address it had before
+    pub fn add(&mut self, name: &str, address: &str) -> Option<String> {
+        self.entries.insert(name.to_owned(), address.to_lowercase())
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<String> {
+        self.entries.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.entries.get(name).map(String::as_str)
+    }
+
+    pub fn entries(&self) -> &BTreeMap<String, String> {
+        &self.entries
+    }
+
+    /// Address -> name, used to annotate output, both lower case and checksum forms are included
+    pub fn names(&self) -> BTreeMap<String, String> {
+        let mut names = BTreeMap::new();
+        for (name, address) in &self.entries {
+            if let Ok(parsed) = Address::from_str(remove_0x(address)) {
+                names.insert(to_checksum_address(&parsed), name.clone());
+            }
+            names.insert(address.clone(), name.clone());
+        }
+        names
+    }
+
+    /// Substitute the names given to address args with their addresses
+    pub fn resolve_args(&self, args: Vec<String>) -> Vec<String> {
+        if self.entries.is_empty() {
+            return args;
+        }
+        let mut in_address_arg = false;
+        args.into_iter()
+            .map(|arg| {
+                if let Some(long) = arg.strip_prefix("--") {
+                    let (flag, value) = match long.find('=') {
+                        Some(index) => (&long[..index], Some(&long[index + 1..])),
+                        None => (long, None),
+                    };
+                    let is_address_arg = ADDRESS_ARGS.contains(&flag);
+                    in_address_arg = is_address_arg && value.is_none();
+                    match (is_address_arg, value.and_then(|value| self.get(value))) {
+                        (true, Some(address)) => format!("--{}={}", flag, address),
+                        _ => arg.clone(),
+                    }
+                } else if arg.starts_with('-') {
+                    in_address_arg = false;
+                    arg
+                } else if in_address_arg {
+                    self.get(&arg).map(ToOwned::to_owned).unwrap_or(arg)
+                } else {
+                    arg
+                }
+            })
+            .collect()
+    }
+}
+
+/// Whether the name can be used in the address book, it must not look like an address or a flag
+pub fn name_validator(name: &str) -> Result<(), String> {
+    if name.is_empty() || name.starts_with('-') || name.starts_with("0x") || name.contains('=') {
+        Err(format!("Invalid name: {}", name))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,337 @@
+use std::collections::BTreeSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+use cita_tool::client::Notice;
+use cita_tool::{Encryption, Hashable, LowerHex};
+
+use crate::cli::{build_cli, key_args};
+use crate::interactive::cita_cli_dir;
+
+/// The args of the passwords, written as `***` to the log with the args of the keys
+const PASSWORD_ARGS: [&str; 2] = ["--password", "--passphrase"];
+
+/// The keys kept by the devices, which aren't secrets
+const DEVICE_KEYS: [&str; 2] = [crate::hsm::HSM_KEY_PREFIX, crate::ledger::LEDGER_KEY_PREFIX];
+
+/// The command line being run, of the notices of the transactions it sends
+static COMMAND: Mutex<String> = Mutex::new(String::new());
+
+/// The audit log: `~/.cita-cli/audit.log`, a JSON object a line
+pub fn audit_log_path() -> PathBuf {
+    cita_cli_dir().join("audit.log")
+}
+
+/// Set the command of the transactions sent from now on, the args without the binary
+pub fn set_command(args: &[String]) {
+    *COMMAND.lock().unwrap() = redact(args).join(" ");
+}
+
+/// The args whose values are secrets: the ones of the command tree taking a private key
+/// and the passwords, a new arg of a key is found without being listed
+fn secret_args() -> &'static BTreeSet<String> {
+    static SECRET_ARGS: OnceLock<BTreeSet<String>> = OnceLock::new();
+    SECRET_ARGS.get_or_init(|| {
+        let mut keys = BTreeSet::new();
+        key_args(&build_cli(""), &mut keys);
+        keys.into_iter()
+            .map(|name| format!("--{}", name))
+            .chain(PASSWORD_ARGS.iter().map(|name| (*name).to_owned()))
+            .collect()
+    })
+}
+
+fn redact(args: &[String]) -> Vec<String> {
+    let secret_args = secret_args();
+    let is_secret = |value: &str| !DEVICE_KEYS.iter().any(|prefix| value.starts_with(prefix));
+    let mut redacted = Vec::with_capacity(args.len());
+    let mut secret_next = false;
+    for arg in args {
+        if secret_next {
+            secret_next = false;
+            if is_secret(arg) {
+                redacted.push("***".to_owned());
+                continue;
+            }
+        } else if let Some((name, value)) = arg.split_once('=') {
+            if secret_args.contains(name) && is_secret(value) {
+                redacted.push(format!("{}=***", name));
+                continue;
+            }
+        } else if secret_args.contains(arg) {
+            secret_next = true;
+        }
+        redacted.push(shell_words::quote(arg).into_owned());
+    }
+    redacted
+}
+
+/// Append the transaction of the notice to the log, the other notices are ignored
+pub fn record(notice: &Notice) {
+    if let Notice::Sent {
+        url,
+        chain_id,
+        transaction,
+        error,
+    } = notice
+    {
+        let entry = json!({
+            "timestamp": Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            "command": *COMMAND.lock().unwrap(),
+            "url": url,
+            "chainId": format!("{:#x}", chain_id),
+            "hash": transaction.hash,
+            "signer": transaction.sender,
+            "to": transaction.to,
+            "outcome": if error.is_some() { "rejected" } else { "accepted" },
+            "error": error,
+        });
+        if let Err(err) = append(&audit_log_path(), entry) {
+            eprintln!("Warning: the transaction isn't in the audit log: {}", err);
+        }
+    }
+}
+
+/// The digest chaining an entry to the one before, an edited or removed line breaks the chain
+fn digest(entry: &Value) -> String {
+    let mut entry = entry.clone();
+    if let Some(entry) = entry.as_object_mut() {
+        entry.remove("digest");
+    }
+    entry
+        .to_string()
+        .crypt_hash(Encryption::Secp256k1)
+        .completed_lower_hex_with_0x()
+}
+
+/// Append the entry chained to the last one, under an exclusive lock of the log, so the
+/// entries of the concurrent processes don't fork the chain
+fn append(path: &Path, mut entry: Value) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    let mut options = OpenOptions::new();
+    options.create(true).read(true).append(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options
+        .open(path)
+        .map_err(|err| format!("Can't open {}: {}", path.display(), err))?;
+    // A log created readable by the others before
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))
+            .map_err(|err| format!("Can't restrict {}: {}", path.display(), err))?;
+    }
+    file.lock()
+        .map_err(|err| format!("Can't lock {}: {}", path.display(), err))?;
+
+    let prev = match last_line(&mut file)
+        .map_err(|err| format!("Can't read {}: {}", path.display(), err))?
+    {
+        Some(line) => serde_json::from_str::<Value>(&line)
+            .map_err(|err| format!("The last line of {}: {}", path.display(), err))?["digest"]
+            .clone(),
+        None => Value::Null,
+    };
+    entry["prev"] = prev;
+    entry["digest"] = json!(digest(&entry));
+    writeln!(file, "{}", entry).map_err(|err| format!("Can't write {}: {}", path.display(), err))
+}
+
+/// The last line not blank, read back from the end of the file
+fn last_line(file: &mut File) -> io::Result<Option<String>> {
+    const CHUNK: u64 = 4096;
+    let trim_end = |content: &[u8]| {
+        let blank = content
+            .iter()
+            .rev()
+            .take_while(|byte| byte.is_ascii_whitespace())
+            .count();
+        content.len() - blank
+    };
+    let mut end = file.seek(SeekFrom::End(0))?;
+    let mut tail = Vec::new();
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK);
+        let mut chunk = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        end = start;
+        let content = &tail[..trim_end(&tail)];
+        if let Some(index) = content.iter().rposition(|byte| *byte == b'\n') {
+            return Ok(Some(
+                String::from_utf8_lossy(&content[index + 1..]).into_owned(),
+            ));
+        }
+    }
+    let content = &tail[..trim_end(&tail)];
+    Ok(if content.is_empty() {
+        None
+    } else {
+        Some(String::from_utf8_lossy(content).into_owned())
+    })
+}
+
+/// The entries of the log, the oldest first
+pub fn entries() -> Result<Vec<Value>, String> {
+    let path = audit_log_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => return Ok(Vec::new()),
+    };
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|err| format!("Line {} of {}: {}", index + 1, path.display(), err))
+        })
+        .collect()
+}
+
+/// The line of the first entry edited, removed before or inserted, None if the chain is intact
+pub fn verify(entries: &[Value]) -> Option<usize> {
+    let mut prev = Value::Null;
+    for (index, entry) in entries.iter().enumerate() {
+        if entry["prev"] != prev || entry["digest"] != json!(digest(entry)) {
+            return Some(index + 1);
+        }
+        prev = entry["digest"].clone();
+    }
+    None
+}
+
+/// The entries as CSV, without the digests
+pub fn to_csv(entries: &[Value]) -> String {
+    let columns = [
+        "timestamp",
+        "command",
+        "url",
+        "chainId",
+        "hash",
+        "signer",
+        "to",
+        "outcome",
+        "error",
+    ];
+    let field = |value: &Value| {
+        let content = value.as_str().unwrap_or_default();
+        format!("\"{}\"", content.replace('"', "\"\""))
+    };
+    let mut csv = vec![columns.join(",")];
+    csv.extend(entries.iter().map(|entry| {
+        columns
+            .iter()
+            .map(|column| field(&entry[column]))
+            .collect::<Vec<String>>()
+            .join(",")
+    }));
+    csv.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{append, digest, redact, verify};
+    use serde_json::json;
+
+    #[test]
+    fn test_audit_chain() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            redact(&args(&[
+                "transfer",
+                "--private-key",
+                "0x01",
+                "--admin-private-key=0x02",
+                "--memo",
+                "a b"
+            ])),
+            args(&[
+                "transfer",
+                "--private-key",
+                "***",
+                "--admin-private-key=***",
+                "--memo",
+                "'a b'"
+            ])
+        );
+        assert_eq!(
+            redact(&args(&[
+                "scm",
+                "NodeManager",
+                "approveNode",
+                "--admin-private",
+                "0x993ef0853d7bf1f4c2977457b50ea6b5f8bc2fd829e3ca3e19f6081ddabb07e9",
+                "--address",
+                "0x37d1c7449bfe76fe9c445e626da06265e9377601"
+            ])),
+            args(&[
+                "scm",
+                "NodeManager",
+                "approveNode",
+                "--admin-private",
+                "***",
+                "--address",
+                "0x37d1c7449bfe76fe9c445e626da06265e9377601"
+            ])
+        );
+        assert_eq!(
+            redact(&args(&["transfer", "--private-key", "hsm:ops"])),
+            args(&["transfer", "--private-key", "hsm:ops"])
+        );
+
+        let mut entries = Vec::new();
+        for hash in &["0x01", "0x02", "0x03"] {
+            let mut entry = json!({ "hash": hash, "outcome": "accepted" });
+            entry["prev"] = entries
+                .last()
+                .map_or(json!(null), |last: &serde_json::Value| {
+                    last["digest"].clone()
+                });
+            entry["digest"] = json!(digest(&entry));
+            entries.push(entry);
+        }
+        assert_eq!(verify(&entries), None);
+        let mut edited = entries.clone();
+        edited[1]["outcome"] = json!("rejected");
+        assert_eq!(verify(&edited), Some(2));
+        let mut removed = entries.clone();
+        removed.remove(0);
+        assert_eq!(verify(&removed), Some(1));
+    }
+
+    #[test]
+    fn test_audit_append() {
+        let path = std::env::temp_dir().join(format!("cita-cli-audit-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        // Longer than a chunk read back from the end
+        let command = "a".repeat(5000);
+        for hash in &["0x01", "0x02", "0x03"] {
+            append(&path, json!({ "hash": hash, "command": command })).unwrap();
+        }
+        let entries = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect::<Vec<serde_json::Value>>();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(verify(&entries), None);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
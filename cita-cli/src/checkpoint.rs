@@ -0,0 +1,145 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use clap::{Arg, ArgMatches};
+use serde_json::{self, json, Value};
+
+/// The cursor of a long-running scan, saved after every batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct Checkpoint {
+    /// The command and the filter of the scan, a checkpoint only resumes the same scan
+    pub scan: String,
+    /// The height of the first block that has not been processed
+    pub next_height: u64,
+    /// The aggregation of the scan so far, such as the changes of `validators history`
+    pub state: Value,
+}
+
+/// `--checkpoint` and `--resume`
+pub fn checkpoint_args() -> [Arg<'static, 'static>; 2] {
+    [
+        Arg::with_name("checkpoint")
+            .long("checkpoint")
+            .takes_value(true)
+            .help("Save the progress of the scan to the file after every batch"),
+        Arg::with_name("resume")
+            .long("resume")
+            .requires("checkpoint")
+            .help("Continue from the checkpoint instead of the start, if the file exists"),
+    ]
+}
+
+/// Load and save the checkpoint of a scan, nothing is saved without `--checkpoint`
+pub struct Checkpointer {
+    path: Option<String>,
+    scan: String,
+    resumed: Option<Checkpoint>,
+}
+
+impl Checkpointer {
+    /// The checkpointer of the scan by `--checkpoint` and `--resume`
+    pub fn open(m: &ArgMatches, scan: &str) -> Result<Self, String> {
+        let path = m.value_of("checkpoint").map(ToOwned::to_owned);
+        let resumed = match path {
+            Some(ref path) if m.is_present("resume") => load(path)?,
+            _ => None,
+        };
+        if let Some(ref checkpoint) = resumed {
+            if checkpoint.scan != scan {
+                return Err(format!(
+                    "The checkpoint is of `{}`, it can't resume `{}`",
+                    checkpoint.scan, scan
+                ));
+            }
+        }
+        Ok(Checkpointer {
+            path,
+            scan: scan.to_owned(),
+            resumed,
+        })
+    }
+
+    /// The checkpoint resumed from
+    pub fn resumed(&self) -> Option<&Checkpoint> {
+        self.resumed.as_ref()
+    }
+
+    /// The height to continue from, when resumed
+    pub fn next_height(&self) -> Option<u64> {
+        self.resumed
+            .as_ref()
+            .map(|checkpoint| checkpoint.next_height)
+    }
+
+    /// Replace the checkpoint file at once, an interrupted save never leaves a partial file
+    pub fn save(&self, next_height: u64, state: Value) -> Result<(), String> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let checkpoint = json!({
+            "scan": self.scan,
+            "nextHeight": next_height,
+            "state": state,
+        });
+        let temporary = format!("{}.tmp", path);
+        let content =
+            serde_json::to_string_pretty(&checkpoint).map_err(|err| format!("{}", err))?;
+        fs::write(&temporary, content).map_err(|err| format!("{}", err))?;
+        fs::rename(&temporary, path).map_err(|err| format!("{}", err))
+    }
+}
+
+/// The checkpoint in the file, `None` if there is no file yet
+fn load(path: &str) -> Result<Option<Checkpoint>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(ref err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(format!("{}", err)),
+    };
+    let invalid = || format!("Invalid checkpoint {}", path);
+    let mut checkpoint: Value = serde_json::from_str(&content).map_err(|_| invalid())?;
+    Ok(Some(Checkpoint {
+        scan: checkpoint["scan"].as_str().ok_or_else(invalid)?.to_owned(),
+        next_height: checkpoint["nextHeight"].as_u64().ok_or_else(invalid)?,
+        state: checkpoint["state"].take(),
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{checkpoint_args, Checkpointer};
+    use clap::App;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_resume_checkpoint() {
+        let path = std::env::temp_dir()
+            .join(format!("cita-cli-checkpoint-{}.json", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let app = App::new("scan").args(&checkpoint_args());
+        let fresh = app
+            .clone()
+            .get_matches_from(vec!["scan", "--checkpoint", &path]);
+        let resume = app.get_matches_from(vec!["scan", "--checkpoint", &path, "--resume"]);
+
+        // Resuming without the file starts from the beginning
+        let checkpointer = Checkpointer::open(&resume, "export blocks").unwrap();
+        assert_eq!(checkpointer.next_height(), None);
+        checkpointer.save(42, json!({ "records": 7 })).unwrap();
+
+        let checkpointer = Checkpointer::open(&resume, "export blocks").unwrap();
+        assert_eq!(checkpointer.next_height(), Some(42));
+        assert_eq!(checkpointer.resumed().unwrap().state["records"], 7);
+        assert!(Checkpointer::open(&resume, "export logs").is_err());
+        assert_eq!(
+            Checkpointer::open(&fresh, "export blocks")
+                .unwrap()
+                .next_height(),
+            None
+        );
+        fs::remove_file(path).unwrap();
+    }
+}
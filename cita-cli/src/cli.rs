@@ -1,35 +1,94 @@
 mod abi_command;
+mod addr_command;
+mod admin_command;
 mod amend_command;
+mod analyze_command;
+mod audit_command;
+mod chain_command;
+mod conformance_command;
 mod contract_command;
+mod devnet_command;
+mod diff_command;
+mod examples_command;
+mod export_command;
+mod faucet_command;
+mod grpc_command;
 mod key_command;
+mod migrate_command;
+mod monitor_command;
+mod mq_command;
+mod net_command;
+mod node_command;
 mod other_command;
+mod plugins_command;
+mod profile_command;
+mod replay_command;
 mod rpc_command;
+mod rules_command;
+mod schema_command;
 mod store_command;
+mod time_command;
 mod tx_command;
+mod txpool_command;
+mod update_command;
+mod user_contract_command;
 mod util;
+mod validators_command;
+mod watch_command;
 
 pub(crate) use self::util::{
-    encryption, get_url, h256_validator, is_hex, key_validator, parse_address, parse_height,
-    parse_privkey, parse_u256, parse_u32, parse_u64, search_app,
+    address_list_validator, address_validator, confirm, deep_value_of, deep_values_of, encryption,
+    flag_present, get_url, h256_validator, hex_output_args, is_hex, key_args, key_validator,
+    output_hex, parse_address, parse_extra_param, parse_height, parse_local_privkey, parse_privkey,
+    parse_u256, parse_u32, parse_u64, read_password, search_app,
 };
 
 pub use self::abi_command::{abi_command, abi_processor};
+pub use self::addr_command::{addr_command, addr_processor};
+pub use self::admin_command::{admin_command, admin_processor};
 pub use self::amend_command::{amend_command, amend_processor};
+pub use self::analyze_command::{analyze_command, analyze_processor};
+pub use self::audit_command::{audit_command, audit_processor};
+pub use self::chain_command::{chain_command, chain_processor};
+pub use self::conformance_command::{conformance_command, conformance_processor};
 pub use self::contract_command::{contract_command, contract_processor};
+pub use self::devnet_command::{devnet_command, devnet_processor};
+pub use self::diff_command::{diff_command, diff_processor};
+pub use self::examples_command::{examples_command, examples_processor, with_examples};
+pub use self::export_command::{export_command, export_processor};
+pub use self::faucet_command::{faucet_command, faucet_processor};
+pub use self::grpc_command::{grpc_command, grpc_processor};
 pub use self::key_command::{key_command, key_processor};
+pub use self::migrate_command::{migrate_command, migrate_processor};
+pub use self::monitor_command::{monitor_command, monitor_processor};
+pub use self::mq_command::{mq_command, mq_processor};
+pub use self::net_command::{net_command, net_processor};
+pub use self::node_command::{node_command, node_processor};
 pub use self::other_command::{
     benchmark_command, benchmark_processor, completion_command, completion_processor,
     search_command, search_processor, string_include, transfer_command, transfer_processor,
 };
+pub use self::plugins_command::{plugins_command, plugins_processor};
+pub use self::profile_command::{profile_command, profile_processor};
+pub use self::replay_command::{replay_command, replay_processor};
 pub use self::rpc_command::{rpc_command, rpc_processor};
+pub use self::rules_command::{rules_command, rules_processor};
+pub use self::schema_command::{schema_command, schema_processor};
 pub use self::store_command::{store_command, store_processor};
+pub use self::time_command::{time_command, time_processor};
 pub use self::tx_command::{tx_command, tx_processor};
+pub use self::txpool_command::{txpool_command, txpool_processor};
+pub use self::update_command::{update_command, update_processor};
+pub use self::user_contract_command::{user_contract_command, user_contract_processor};
+pub use self::validators_command::{validators_command, validators_processor};
+pub use self::watch_command::{watch_command, watch_processor};
 
+use cita_tool::client::RateLimiter;
 use cita_tool::parse_url;
 use clap::{crate_version, App, AppSettings, Arg, SubCommand};
 
 /// Generate cli
-pub fn build_cli(version: &str) -> App {
+pub fn build_cli(version: &str) -> App<'_, '_> {
     let arg_url = Arg::with_name("url")
         .long("url")
         .takes_value(true)
@@ -40,16 +99,45 @@ pub fn build_cli(version: &str) -> App {
         .version(version)
         .global_setting(AppSettings::ColoredHelp)
         .global_setting(AppSettings::DeriveDisplayOrder)
-        .subcommand(rpc_command().arg(arg_url.clone()))
-        .subcommand(contract_command().arg(arg_url.clone()))
-        .subcommand(key_command())
+        .setting(AppSettings::AllowExternalSubcommands)
+        .subcommand(with_examples(rpc_command().arg(arg_url.clone())))
+        .subcommand(with_examples(contract_command().arg(arg_url.clone())))
+        .subcommand(with_examples(user_contract_command().arg(arg_url.clone())))
+        .subcommand(with_examples(key_command().arg(arg_url.clone())))
+        .subcommand(addr_command())
         .subcommand(abi_command())
-        .subcommand(transfer_command().arg(arg_url.clone()))
+        .subcommand(with_examples(transfer_command().arg(arg_url.clone())))
         .subcommand(store_command().arg(arg_url.clone()))
         .subcommand(amend_command().arg(arg_url.clone()))
         .subcommand(search_command())
-        .subcommand(tx_command().arg(arg_url.clone()))
+        .subcommand(with_examples(tx_command().arg(arg_url.clone())))
+        .subcommand(txpool_command().arg(arg_url.clone()))
+        .subcommand(migrate_command().arg(arg_url.clone()))
+        .subcommand(rules_command().arg(arg_url.clone()))
+        .subcommand(schema_command().arg(arg_url.clone()))
         .subcommand(benchmark_command().arg(arg_url.clone()))
+        .subcommand(with_examples(watch_command().arg(arg_url.clone())))
+        .subcommand(chain_command().arg(arg_url.clone()))
+        .subcommand(analyze_command().arg(arg_url.clone()))
+        .subcommand(validators_command().arg(arg_url.clone()))
+        .subcommand(with_examples(admin_command().arg(arg_url.clone())))
+        .subcommand(faucet_command().arg(arg_url.clone()))
+        .subcommand(node_command().arg(arg_url.clone()))
+        .subcommand(monitor_command().arg(arg_url.clone()))
+        .subcommand(net_command().arg(arg_url.clone()))
+        .subcommand(conformance_command().arg(arg_url.clone()))
+        .subcommand(replay_command().arg(arg_url.clone()))
+        .subcommand(diff_command().arg(arg_url.clone()))
+        .subcommand(time_command().arg(arg_url.clone()))
+        .subcommand(devnet_command())
+        .subcommand(export_command().arg(arg_url.clone()))
+        .subcommand(grpc_command())
+        .subcommand(mq_command())
+        .subcommand(profile_command())
+        .subcommand(with_examples(audit_command()))
+        .subcommand(examples_command())
+        .subcommand(plugins_command())
+        .subcommand(update_command())
         .subcommand(completion_command())
         .arg(
             Arg::with_name("algorithm")
@@ -71,6 +159,186 @@ pub fn build_cli(version: &str) -> App {
                 .global(true)
                 .help("Display request parameters"),
         )
+        .arg(
+            Arg::with_name("no-limit-check")
+                .long("no-limit-check")
+                .global(true)
+                .help(
+                    "Send transactions over the max data size or the block quota limit of the \
+                     chain, the node decides",
+                ),
+        )
+        .arg(
+            Arg::with_name("no-chain-check")
+                .long("no-chain-check")
+                .global(true)
+                .help(
+                    "Send transactions without comparing their chain id with the chain of the \
+                     node first",
+                ),
+        )
+        .arg(
+            Arg::with_name("no-duplicate")
+                .long("no-duplicate")
+                .global(true)
+                .help(
+                    "Refuse to send a transaction whose content was already sent in this session",
+                ),
+        )
+        .arg(
+            Arg::with_name("show-chain")
+                .long("show-chain")
+                .global(true)
+                .help(
+                    "Annotate the addresses of the colored output with the chain name of the node",
+                ),
+        )
+        .arg(
+            Arg::with_name("utc")
+                .long("utc")
+                .global(true)
+                .conflicts_with_all(&["local", "unix"])
+                .help("Print the block timestamps in UTC"),
+        )
+        .arg(
+            Arg::with_name("local")
+                .long("local")
+                .global(true)
+                .conflicts_with("unix")
+                .help("Print the block timestamps in the local time zone"),
+        )
+        .arg(
+            Arg::with_name("unix")
+                .long("unix")
+                .global(true)
+                .help("Print the block timestamps in seconds, instead of milliseconds"),
+        )
+        .arg(Arg::with_name("signers").long("signers").global(true).help(
+            "List the validators signed the BFT proofs of the printed blocks, \
+                     with their names in the address book and the round",
+        ))
+        .arg(
+            Arg::with_name("check-fee")
+                .long("check-fee")
+                .global(true)
+                .help(
+                    "Show the max fee of the transactions in charge mode, and make sure the \
+                     senders can afford them before sending, implied by --max-fee",
+                ),
+        )
+        .arg(
+            Arg::with_name("max-fee")
+                .long("max-fee")
+                .global(true)
+                .takes_value(true)
+                .validator(|fee| parse_u256(fee.as_str()).map(|_| ()))
+                .help(
+                    "Refuse to send a transaction that may cost more than the fee in charge mode",
+                ),
+        )
+        .arg(Arg::with_name("strict").long("strict").global(true).help(
+            "Fail on the responses with unknown fields, missing fields or \
+                     non-canonical hex, to test the conformance of the nodes",
+        ))
+        .arg(
+            Arg::with_name("no-aliases")
+                .long("no-aliases")
+                .global(true)
+                .help(
+                    "Never retry the methods a node doesn't know by their names of the older \
+                     versions, the names learnt are kept in ~/.cita-cli/aliases.json",
+                ),
+        )
+        .arg(Arg::with_name("timing").long("timing").global(true).help(
+            "Record the round-trip time of every request, print the min, avg, p95, \
+                     max and a histogram to stderr at the end",
+        ))
+        .arg(
+            Arg::with_name("cache")
+                .long("cache")
+                .global(true)
+                .help("Cache blocks by hash, transactions and receipts in ~/.cita-cli/cache"),
+        )
+        .arg(
+            Arg::with_name("cache-dir")
+                .long("cache-dir")
+                .global(true)
+                .takes_value(true)
+                .help("Cache blocks by hash, transactions and receipts in the directory"),
+        )
+        .arg(
+            Arg::with_name("policy")
+                .long("policy")
+                .global(true)
+                .takes_value(true)
+                .help(
+                    "The program deciding on the transactions before they're sent, it reads the \
+                     transaction as JSON from stdin and vetoes it by a non-zero exit, \
+                     default is the `policy` of the profile",
+                ),
+        )
+        .arg(
+            Arg::with_name("rate-limit")
+                .long("rate-limit")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|limit| {
+                    RateLimiter::from_limits(vec![limit.as_str()])
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .help(
+                    "Limit the requests per second, RPS for every node or URL=RPS for one node, \
+                     the requests over the limit are delayed",
+                ),
+        )
+        .arg(
+            Arg::with_name("circuit-breaker")
+                .long("circuit-breaker")
+                .global(true)
+                .takes_value(true)
+                .validator(|failures| parse_u32(failures.as_str()).map(|_| ()))
+                .help(
+                    "Stop sending to a node for 30 seconds after the consecutive failures, \
+                     then probe it with one request",
+                ),
+        )
+        .arg(
+            Arg::with_name("extra")
+                .long("extra")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .validator(|param| parse_extra_param(param.as_str()).map(|_| ()))
+                .help(
+                    "Add the field key=value to the body of every JSON-RPC request, over the \
+                     extra_params of ~/.cita-cli/config, the value is json or a string",
+                ),
+        )
+        .arg(
+            Arg::with_name("nodes")
+                .long("nodes")
+                .global(true)
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                .help(
+                    "Send the reads to the fastest of the nodes, probed by blockNumber every \
+                     minute, the transactions still go to the url",
+                ),
+        )
+        .arg(
+            Arg::with_name("node")
+                .long("node")
+                .global(true)
+                .takes_value(true)
+                .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                .help("Pin the reads to the node instead of the fastest one"),
+        )
 }
 
 /// Interactive parser
@@ -78,6 +346,7 @@ pub fn build_interactive() -> App<'static, 'static> {
     App::new("interactive")
         .version(crate_version!())
         .setting(AppSettings::NoBinaryName)
+        .setting(AppSettings::AllowExternalSubcommands)
         .global_setting(AppSettings::ColoredHelp)
         .global_setting(AppSettings::DeriveDisplayOrder)
         .global_setting(AppSettings::DisableVersion)
@@ -127,19 +396,58 @@ pub fn build_interactive() -> App<'static, 'static> {
                     Arg::with_name("save_private")
                         .long("save_private")
                         .help("Switching whether save private key"),
+                )
+                .arg(
+                    Arg::with_name("signers")
+                        .long("signers")
+                        .help("Switching whether list the signers of the block proofs"),
+                )
+                .arg(
+                    Arg::with_name("time")
+                        .long("time")
+                        .takes_value(true)
+                        .possible_values(&["raw", "unix", "utc", "local"])
+                        .help("Switch how the block timestamps are printed"),
                 ),
         )
         .subcommand(search_command())
         .subcommand(SubCommand::with_name("info").about("Display global variables"))
-        .subcommand(rpc_command())
-        .subcommand(key_command())
+        .subcommand(with_examples(rpc_command()))
+        .subcommand(with_examples(key_command()))
+        .subcommand(addr_command())
         .subcommand(abi_command())
-        .subcommand(contract_command())
-        .subcommand(transfer_command())
+        .subcommand(with_examples(contract_command()))
+        .subcommand(with_examples(user_contract_command()))
+        .subcommand(with_examples(transfer_command()))
         .subcommand(store_command())
         .subcommand(amend_command())
-        .subcommand(tx_command())
+        .subcommand(with_examples(tx_command()))
+        .subcommand(txpool_command())
+        .subcommand(migrate_command())
+        .subcommand(rules_command())
+        .subcommand(schema_command())
         .subcommand(benchmark_command())
+        .subcommand(with_examples(watch_command()))
+        .subcommand(chain_command())
+        .subcommand(analyze_command())
+        .subcommand(validators_command())
+        .subcommand(with_examples(admin_command()))
+        .subcommand(faucet_command())
+        .subcommand(node_command())
+        .subcommand(monitor_command())
+        .subcommand(net_command())
+        .subcommand(conformance_command())
+        .subcommand(replay_command())
+        .subcommand(diff_command())
+        .subcommand(time_command())
+        .subcommand(devnet_command())
+        .subcommand(export_command())
+        .subcommand(grpc_command())
+        .subcommand(mq_command())
+        .subcommand(profile_command())
+        .subcommand(with_examples(audit_command()))
+        .subcommand(examples_command())
+        .subcommand(plugins_command())
         .subcommand(
             SubCommand::with_name("exit")
                 .visible_alias("quit")
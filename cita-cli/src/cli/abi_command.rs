@@ -1,9 +1,15 @@
+use std::fs;
+
 use clap::{App, Arg, ArgMatches, SubCommand};
 use serde_json::{self, Value};
 
+use crate::cli::{encryption, hex_output_args, output_hex};
 use crate::interactive::GlobalConfig;
 use crate::printer::Printer;
-use cita_tool::{decode_input, decode_logs, decode_params, encode_input, encode_params, remove_0x};
+use cita_tool::{
+    decode_input, decode_logs, decode_params, encode_input, encode_json_input, encode_params,
+    remove_0x,
+};
 
 /// Ethereum abi sub command
 pub fn abi_command() -> App<'static, 'static> {
@@ -27,6 +33,11 @@ pub fn abi_command() -> App<'static, 'static> {
         .long("file")
         .takes_value(true)
         .help("ABI json file path");
+    let args_file_arg = Arg::with_name("args-file")
+        .long("args-file")
+        .takes_value(true)
+        .conflicts_with("param")
+        .help("JSON file of the parameters, an object keyed by the names or an array in order");
 
     App::new("ethabi")
         .about("ABI operation, encode parameter, generate code based on abi and parameters")
@@ -44,12 +55,15 @@ pub fn abi_command() -> App<'static, 'static> {
                                 .help("Function name"),
                         )
                         .arg(param_arg.clone().number_of_values(1).value_name("value"))
-                        .arg(no_lenient_flag.clone()),
+                        .arg(args_file_arg.clone())
+                        .arg(no_lenient_flag.clone())
+                        .args(&hex_output_args()),
                 )
                 .subcommand(
                     SubCommand::with_name("params")
                         .arg(param_arg.clone().value_names(&["type", "value"]))
-                        .arg(no_lenient_flag.clone()),
+                        .arg(no_lenient_flag.clone())
+                        .args(&hex_output_args()),
                 )
                 .subcommand(
                     SubCommand::with_name("constructor")
@@ -63,7 +77,9 @@ pub fn abi_command() -> App<'static, 'static> {
                                 .help("Contract bin code"),
                         )
                         .arg(no_lenient_flag)
-                        .arg(param_arg.clone().number_of_values(1).value_name("value")),
+                        .arg(param_arg.clone().number_of_values(1).value_name("value"))
+                        .arg(args_file_arg)
+                        .args(&hex_output_args()),
                 ),
         )
         .subcommand(
@@ -144,9 +160,19 @@ pub fn abi_processor(
                     None => Vec::new(),
                     Some(param) => param.map(ToOwned::to_owned).collect::<Vec<String>>(),
                 };
-                let output = encode_input(file, abi, name, &values, lenient, false)
-                    .map_err(|err| format!("{}", err))?;
-                printer.println(&Value::String(output), is_color);
+                let output = match m.value_of("args-file") {
+                    Some(path) => encode_args_file(file, abi, name, path, false)?,
+                    None => encode_input(file, abi, name, &values, lenient, false)
+                        .map_err(|err| format!("{}", err))?,
+                };
+                output_hex(
+                    m,
+                    printer,
+                    &Value::String(output.clone()),
+                    &output,
+                    encryption(m, config),
+                    is_color,
+                )?;
             }
             ("params", Some(m)) => {
                 let lenient = !m.is_present("no-lenient");
@@ -162,7 +188,14 @@ pub fn abi_processor(
                 }
                 let output =
                     encode_params(&types, &values, lenient).map_err(|err| format!("{}", err))?;
-                printer.println(&Value::String(output), is_color);
+                output_hex(
+                    m,
+                    printer,
+                    &Value::String(output.clone()),
+                    &output,
+                    encryption(m, config),
+                    is_color,
+                )?;
             }
             ("constructor", Some(m)) => {
                 let file = m.value_of("file");
@@ -173,9 +206,19 @@ pub fn abi_processor(
                     None => Vec::new(),
                     Some(param) => param.map(ToOwned::to_owned).collect::<Vec<String>>(),
                 };
-                let output = encode_input(file, abi, code, &values, lenient, true)
-                    .map_err(|err| format!("{}", err))?;
-                printer.println(&Value::String(output), is_color);
+                let output = match m.value_of("args-file") {
+                    Some(path) => encode_args_file(file, abi, code, path, true)?,
+                    None => encode_input(file, abi, code, &values, lenient, true)
+                        .map_err(|err| format!("{}", err))?,
+                };
+                output_hex(
+                    m,
+                    printer,
+                    &Value::String(output.clone()),
+                    &output,
+                    encryption(m, config),
+                    is_color,
+                )?;
             }
             _ => {
                 return Err(em.usage().to_owned());
@@ -234,3 +277,24 @@ pub fn abi_processor(
     }
     Ok(())
 }
+
+/// Encode the parameters of the JSON file, checked against the types of the abi
+fn encode_args_file(
+    file: Option<&str>,
+    abi: Option<&str>,
+    function: &str,
+    path: &str,
+    constructor: bool,
+) -> Result<String, String> {
+    let abi = match (abi, file) {
+        (Some(abi), _) => abi.to_owned(),
+        (None, Some(file)) => {
+            fs::read_to_string(file).map_err(|err| format!("Can't read {}: {}", file, err))?
+        }
+        (None, None) => return Err("No input abi".to_string()),
+    };
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let args: Value = serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))?;
+    encode_json_input(&abi, function, &args, constructor).map_err(|err| format!("{}", err))
+}
@@ -0,0 +1,91 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{Map, Value};
+use std::str::FromStr;
+
+use cita_tool::{remove_0x, to_checksum_address, Address};
+
+use crate::address_book::{name_validator, AddressBook};
+use crate::cli::parse_address;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Address book command
+pub fn addr_command() -> App<'static, 'static> {
+    App::new("addr")
+        .about(
+            "Address book, the names can be used in place of addresses, \
+             such as `--address alice`",
+        )
+        .subcommand(
+            SubCommand::with_name("add")
+                .about("Add or replace a name")
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|name| name_validator(name.as_str()))
+                        .help("Human-readable name of the address"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The address"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("remove").about("Remove a name").arg(
+                Arg::with_name("name")
+                    .long("name")
+                    .required(true)
+                    .takes_value(true)
+                    .help("The name to remove"),
+            ),
+        )
+        .subcommand(SubCommand::with_name("list").about("List all the names"))
+}
+
+/// Address book processor
+pub fn addr_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &GlobalConfig,
+) -> Result<(), String> {
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let mut book = AddressBook::load();
+    match sub_matches.subcommand() {
+        ("add", Some(m)) => {
+            let name = m.value_of("name").unwrap();
+            let address = m.value_of("address").unwrap();
+            if let Some(old) = book.add(name, address) {
+                printer.println(&format!("Replace {}: {}", name, old), is_color);
+            }
+            book.save().map_err(|err| format!("{}", err))?;
+        }
+        ("remove", Some(m)) => {
+            let name = m.value_of("name").unwrap();
+            if book.remove(name).is_none() {
+                return Err(format!("No such name: {}", name));
+            }
+            book.save().map_err(|err| format!("{}", err))?;
+        }
+        ("list", _) => {
+            let entries: Map<String, Value> = book
+                .entries()
+                .iter()
+                .map(|(name, address)| {
+                    let address = Address::from_str(remove_0x(address))
+                        .map(|address| to_checksum_address(&address))
+                        .unwrap_or_else(|_| address.clone());
+                    (name.clone(), Value::String(address))
+                })
+                .collect();
+            printer.println(&Value::Object(entries), is_color);
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    Ok(())
+}
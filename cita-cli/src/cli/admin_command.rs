@@ -0,0 +1,187 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::system_contract::{
+    EmergencyBrakeClient, EmergencyBrakeExt, VersionManagerClient, VersionManagerExt,
+};
+use cita_tool::{JsonRpcResponse, ToolError, U256};
+
+use crate::cli::chain_command::decode_call;
+use crate::cli::{
+    encryption, get_url, key_validator, parse_height, parse_privkey, parse_u32, parse_u64,
+};
+use crate::interactive::{set_output, GlobalConfig};
+use crate::printer::Printer;
+use crate::risk::confirmed;
+
+/// Chain administration command
+pub fn admin_command() -> App<'static, 'static> {
+    let admin_private = Arg::with_name("admin-private")
+        .long("admin-private")
+        .takes_value(true)
+        .required(true)
+        .validator(|private_key| key_validator(private_key.as_ref()).map(|_| ()))
+        .help("Private key must be admin");
+    let quota_arg = Arg::with_name("quota")
+        .long("quota")
+        .takes_value(true)
+        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+        .help("Transaction quota costs, default is 10_000_000");
+    let yes_arg = Arg::with_name("yes")
+        .long("yes")
+        .short("y")
+        .help("Don't ask for confirmation");
+
+    App::new("admin")
+        .about("Chain administration for super admins")
+        .subcommand(
+            SubCommand::with_name("chain")
+                .about("Emergency brake and protocol version of the chain")
+                .subcommand(
+                    SubCommand::with_name("status")
+                        .about("Show the emergency brake state and the protocol version")
+                        .arg(
+                            Arg::with_name("height")
+                                .long("height")
+                                .default_value("latest")
+                                .takes_value(true)
+                                .validator(|s| parse_height(s.as_str()))
+                                .help("The height of the chain, hex string or tag 'latest'"),
+                        ),
+                )
+                .subcommand(
+                    SubCommand::with_name("brake")
+                        .about(
+                            "Set the emergency brake, only the transactions of the super admin \
+                             are accepted afterwards",
+                        )
+                        .arg(admin_private.clone())
+                        .arg(quota_arg.clone())
+                        .arg(yes_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("unbrake")
+                        .about("Unset the emergency brake, accept all the transactions again")
+                        .arg(admin_private.clone())
+                        .arg(quota_arg.clone())
+                        .arg(yes_arg.clone()),
+                )
+                .subcommand(
+                    SubCommand::with_name("set-version")
+                        .about("Set the protocol version, the nodes must all support it")
+                        .arg(
+                            Arg::with_name("version")
+                                .long("version")
+                                .takes_value(true)
+                                .required(true)
+                                .validator(|version| parse_u32(version.as_str()).map(|_| ()))
+                                .help("Protocol version"),
+                        )
+                        .arg(admin_private)
+                        .arg(quota_arg)
+                        .arg(yes_arg),
+                ),
+        )
+}
+
+/// Chain administration processor
+pub fn admin_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let mut client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let response = match sub_matches.subcommand() {
+        ("chain", Some(m)) => match m.subcommand() {
+            ("status", Some(m)) => {
+                let status =
+                    chain_status(client, m.value_of("height")).map_err(|err| format!("{}", err))?;
+                printer.println(&status, is_color);
+                config.set("result".to_string(), status);
+                return Ok(());
+            }
+            ("brake", Some(m)) => {
+                confirmed(
+                    m,
+                    "admin chain brake",
+                    "Set the emergency brake, only the super admin can send transactions then.",
+                )?;
+                client.set_private_key(&parse_privkey(
+                    m.value_of("admin-private").unwrap(),
+                    encryption(m, config),
+                )?);
+                let client: EmergencyBrakeClient<Client> = EmergencyBrakeExt::create(client);
+                let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
+                EmergencyBrakeExt::set_state(&client, true, quota)
+            }
+            ("unbrake", Some(m)) => {
+                confirmed(
+                    m,
+                    "admin chain unbrake",
+                    "Unset the emergency brake, everyone can send transactions again.",
+                )?;
+                client.set_private_key(&parse_privkey(
+                    m.value_of("admin-private").unwrap(),
+                    encryption(m, config),
+                )?);
+                let client: EmergencyBrakeClient<Client> = EmergencyBrakeExt::create(client);
+                let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
+                EmergencyBrakeExt::set_state(&client, false, quota)
+            }
+            ("set-version", Some(m)) => {
+                let version = parse_u32(m.value_of("version").unwrap())?;
+                let current = {
+                    let client: VersionManagerClient<Client> =
+                        VersionManagerExt::create(client.clone());
+                    decode_call(
+                        VersionManagerExt::get_version(&client, Some("latest"))
+                            .map_err(|err| format!("{}", err))?,
+                        "uint32",
+                    )
+                    .map_err(|err| format!("{}", err))?
+                    .as_str()
+                    .and_then(|current| parse_u32(current).ok())
+                    .map_or_else(|| "unknown".to_string(), |current| current.to_string())
+                };
+                confirmed(
+                    m,
+                    "admin chain set-version",
+                    &format!(
+                        "Set the protocol version from {} to {}, \
+                         the nodes not supporting it will stop.",
+                        current, version
+                    ),
+                )?;
+                client.set_private_key(&parse_privkey(
+                    m.value_of("admin-private").unwrap(),
+                    encryption(m, config),
+                )?);
+                let client: VersionManagerClient<Client> = VersionManagerExt::create(client);
+                let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
+                VersionManagerExt::set_version(&client, U256::from(version), quota)
+            }
+            _ => return Err(m.usage().to_owned()),
+        },
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    let response: JsonRpcResponse = response.map_err(|err| format!("{}", err))?;
+    printer.println(&response, is_color);
+    set_output(&response, config);
+    Ok(())
+}
+
+fn chain_status(client: Client, height: Option<&str>) -> Result<Value, ToolError> {
+    let brake: EmergencyBrakeClient<Client> = EmergencyBrakeExt::create(client.clone());
+    let version: VersionManagerClient<Client> = VersionManagerExt::create(client);
+    Ok(json!({
+        "emergencyBrake": decode_call(EmergencyBrakeExt::state(&brake, height)?, "bool")?,
+        "version": decode_call(VersionManagerExt::get_version(&version, height)?, "uint32")?,
+    }))
+}
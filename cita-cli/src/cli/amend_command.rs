@@ -9,6 +9,7 @@ use crate::cli::{
 };
 use crate::interactive::{set_output, GlobalConfig};
 use crate::printer::Printer;
+use crate::risk::confirmed;
 
 use std::fs;
 use std::io::Read;
@@ -35,6 +36,10 @@ pub fn amend_command() -> App<'static, 'static> {
             .takes_value(true)
             .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
             .help("Transaction quota costs, default is 1_000_000"),
+        Arg::with_name("yes")
+            .long("yes")
+            .short("y")
+            .help("Don't ask for confirmation"),
     ];
     App::new("amend")
         .about("Amend(update) ABI/contract code/H256KV")
@@ -143,6 +148,17 @@ pub fn amend_processor(
         .set_debug(debug)
         .set_uri(get_url(sub_matches, config));
 
+    if let (name, Some(m)) = sub_matches.subcommand() {
+        confirmed(
+            m,
+            &format!("amend {}", name),
+            &format!(
+                "Amend the {} of {} out of the transactions.",
+                name,
+                m.value_of("address").unwrap_or_default()
+            ),
+        )?;
+    }
     let result = match sub_matches.subcommand() {
         ("code", Some(m)) => {
             let encryption = encryption(m, config);
@@ -183,7 +199,7 @@ pub fn amend_processor(
             let h256_kv = m
                 .values_of("kv")
                 .unwrap()
-                .map(|s| remove_0x(s))
+                .map(remove_0x)
                 .collect::<Vec<&str>>()
                 .join("");
             let quota = m.value_of("quota").map(|s| parse_u64(s).unwrap());
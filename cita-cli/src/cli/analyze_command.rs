@@ -0,0 +1,477 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::client::system_contract::{QuotaManageClient, QuotaManagementExt};
+use cita_tool::{
+    encode, pubkey_to_address, remove_0x, Encryption, JsonRpcParams, LowerHex, ParamsValue,
+    Quantity, ToolError, UnverifiedTransaction,
+};
+
+use crate::cli::chain_command::{decode_call, result_value};
+use crate::cli::time_command::{block_range, time_range_args};
+use crate::cli::{encryption, get_url, parse_address, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Analytics command
+pub fn analyze_command() -> App<'static, 'static> {
+    App::new("analyze")
+        .about("Analyze the chain history")
+        .subcommand(
+            SubCommand::with_name("quota")
+                .about(
+                    "Aggregate quotaUsed per sender and per contract over a block range, \
+                     compared with the BQL/AQL of the quota manager",
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .required_unless("from-time")
+                        .takes_value(true)
+                        .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+                        .help("The first block height, hex string or number"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+                        .help("The last block height, default is the current height"),
+                )
+                .args(&time_range_args())
+                .arg(
+                    Arg::with_name("top")
+                        .long("top")
+                        .default_value("10")
+                        .takes_value(true)
+                        .validator(|top| parse_u64(top.as_str()).map(|_| ()))
+                        .help("Number of top consumers to report"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("nonce")
+                .about(
+                    "Scan the transactions of a sender over a block range for reused nonces, \
+                     resent contents and low entropy nonces",
+                )
+                .arg(
+                    Arg::with_name("sender")
+                        .long("sender")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The address of the sender"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .required_unless("from-time")
+                        .takes_value(true)
+                        .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+                        .help("The first block height, hex string or number"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+                        .help("The last block height, default is the current height"),
+                )
+                .args(&time_range_args())
+                .arg(
+                    Arg::with_name("min-entropy")
+                        .long("min-entropy")
+                        .default_value("64")
+                        .takes_value(true)
+                        .validator(|bits| parse_u64(bits.as_str()).map(|_| ()))
+                        .help(
+                            "Flag the nonces of fewer estimated bits, a random uuid has about 120",
+                        ),
+                ),
+        )
+}
+
+/// Analytics processor
+pub fn analyze_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let report = match sub_matches.subcommand() {
+        ("quota", Some(m)) => {
+            let (from, to) = block_range(m, &client)?;
+            let top = parse_u64(m.value_of("top").unwrap())? as usize;
+            let mut usage = QuotaUsage::default();
+            for height in from..=to {
+                usage
+                    .scan_block(&client, height, encryption(m, config))
+                    .map_err(|err| format!("{}", err))?;
+            }
+            usage
+                .report(client, from, to, top)
+                .map_err(|err| format!("{}", err))?
+        }
+        ("nonce", Some(m)) => {
+            let (from, to) = block_range(m, &client)?;
+            let sender = format!("0x{}", remove_0x(m.value_of("sender").unwrap())).to_lowercase();
+            let mut audit = NonceAudit::default();
+            for height in from..=to {
+                audit
+                    .scan_block(&client, height, &sender, encryption(m, config))
+                    .map_err(|err| format!("{}", err))?;
+            }
+            let mut report = audit.report(parse_u64(m.value_of("min-entropy").unwrap())? as f64);
+            report["sender"] = json!(sender);
+            report["from"] = json!(from);
+            report["to"] = json!(to);
+            report
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&report, is_color);
+    config.set("result".to_string(), report);
+    Ok(())
+}
+
+#[derive(Default)]
+struct Consumer {
+    transactions: u64,
+    quota_used: u64,
+    /// The most quota used in a single block, the AQL limits it
+    peak_block_quota: u64,
+}
+
+#[derive(Default)]
+struct QuotaUsage {
+    blocks: u64,
+    transactions: u64,
+    quota_used: u64,
+    /// (height, quota used) of the block using the most quota
+    peak_block: (u64, u64),
+    senders: HashMap<String, Consumer>,
+    contracts: HashMap<String, Consumer>,
+}
+
+impl QuotaUsage {
+    fn scan_block(
+        &mut self,
+        client: &Client,
+        height: u64,
+        encryption: Encryption,
+    ) -> Result<(), ToolError> {
+        let block = result_value(client.get_block_by_number(&format!("{:#x}", height), true)?)?;
+        let transactions = block["body"]["transactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let params = transactions.iter().map(|tx| {
+            JsonRpcParams::new()
+                .insert(
+                    "method",
+                    ParamsValue::String("getTransactionReceipt".to_string()),
+                )
+                .insert(
+                    "params",
+                    ParamsValue::List(vec![ParamsValue::String(
+                        tx["hash"].as_str().unwrap_or_default().to_owned(),
+                    )]),
+                )
+        });
+        let receipts = if transactions.is_empty() {
+            Vec::new()
+        } else {
+            client.send_request(params.collect::<Vec<_>>().into_iter())?
+        };
+
+        let mut block_quota = 0;
+        let mut block_senders: HashMap<String, u64> = HashMap::new();
+        for (tx, receipt) in transactions.iter().zip(receipts) {
+            let receipt = result_value(receipt)?;
+            let quota = Quantity::from_json(&receipt["quotaUsed"])
+                .or_else(|| Quantity::from_json(&receipt["gasUsed"]))
+                .and_then(|quota| quota.as_u64().ok())
+                .unwrap_or_default();
+            let unverified =
+                UnverifiedTransaction::from_str(tx["content"].as_str().unwrap_or_default())?;
+            let sender = unverified
+                .public_key(encryption)
+                .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+                .unwrap_or_else(|_| "unknown".to_string());
+            let transaction = unverified.get_transaction();
+            let contract = if !transaction.get_to().is_empty() {
+                format!("0x{}", remove_0x(transaction.get_to()))
+            } else if !transaction.get_to_v1().is_empty() {
+                format!("0x{}", encode(transaction.get_to_v1()))
+            } else {
+                receipt["contractAddress"]
+                    .as_str()
+                    .unwrap_or("create")
+                    .to_owned()
+            };
+
+            for consumer in [
+                self.senders.entry(sender.clone()).or_default(),
+                self.contracts.entry(contract.to_lowercase()).or_default(),
+            ] {
+                consumer.transactions += 1;
+                consumer.quota_used += quota;
+            }
+            *block_senders.entry(sender).or_default() += quota;
+            block_quota += quota;
+        }
+
+        for (sender, quota) in block_senders {
+            let consumer = self.senders.get_mut(&sender).unwrap();
+            consumer.peak_block_quota = consumer.peak_block_quota.max(quota);
+        }
+        if block_quota > self.peak_block.1 || self.blocks == 0 {
+            self.peak_block = (height, block_quota);
+        }
+        self.blocks += 1;
+        self.transactions += transactions.len() as u64;
+        self.quota_used += block_quota;
+        Ok(())
+    }
+
+    fn report(self, client: Client, from: u64, to: u64, top: usize) -> Result<Value, ToolError> {
+        let quota_manager: QuotaManageClient<Client> = QuotaManagementExt::create(client);
+        let height = Some("latest");
+
+        let mut senders = Vec::new();
+        for (address, consumer) in top_consumers(&self.senders, top) {
+            let aql = if address.starts_with("0x") {
+                decode_call(quota_manager.get_aql(address, height)?, "uint256")?
+            } else {
+                Value::Null
+            };
+            senders.push(json!({
+                "address": address,
+                "transactions": consumer.transactions,
+                "quotaUsed": consumer.quota_used,
+                "peakBlockQuota": consumer.peak_block_quota,
+                "aql": aql,
+            }));
+        }
+        let contracts: Vec<Value> = top_consumers(&self.contracts, top)
+            .into_iter()
+            .map(|(address, consumer)| {
+                json!({
+                    "address": address,
+                    "transactions": consumer.transactions,
+                    "quotaUsed": consumer.quota_used,
+                })
+            })
+            .collect();
+
+        Ok(json!({
+            "from": from,
+            "to": to,
+            "blocks": self.blocks,
+            "transactions": self.transactions,
+            "quotaUsed": self.quota_used,
+            "averageBlockQuota": self.quota_used / self.blocks.max(1),
+            "peakBlock": {
+                "height": self.peak_block.0,
+                "quotaUsed": self.peak_block.1,
+            },
+            "bql": decode_call(quota_manager.get_bql(height)?, "uint256")?,
+            "defaultAql": decode_call(quota_manager.get_default_aql(height)?, "uint256")?,
+            "topSenders": senders,
+            "topContracts": contracts,
+        }))
+    }
+}
+
+/// A transaction of the audited sender
+struct SentNonce {
+    hash: String,
+    height: u64,
+    nonce: String,
+    content_hash: String,
+}
+
+#[derive(Default)]
+struct NonceAudit {
+    blocks: u64,
+    transactions: Vec<SentNonce>,
+}
+
+impl NonceAudit {
+    fn scan_block(
+        &mut self,
+        client: &Client,
+        height: u64,
+        sender: &str,
+        encryption: Encryption,
+    ) -> Result<(), ToolError> {
+        let block = result_value(client.get_block_by_number(&format!("{:#x}", height), true)?)?;
+        for tx in block["body"]["transactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+        {
+            let unverified =
+                UnverifiedTransaction::from_str(tx["content"].as_str().unwrap_or_default())?;
+            let signer = unverified
+                .public_key(encryption)
+                .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x());
+            if signer.as_deref() != Ok(sender) {
+                continue;
+            }
+            let transaction = unverified.get_transaction();
+            self.add(SentNonce {
+                hash: tx["hash"].as_str().unwrap_or_default().to_owned(),
+                height,
+                nonce: transaction.get_nonce().to_owned(),
+                content_hash: transaction.content_hash().completed_lower_hex_with_0x(),
+            });
+        }
+        self.blocks += 1;
+        Ok(())
+    }
+
+    fn add(&mut self, sent: SentNonce) {
+        self.transactions.push(sent);
+    }
+
+    /// The nonces used more than once, the contents sent more than once under other
+    /// nonces, and the nonces estimated below `min_entropy` bits
+    fn report(&self, min_entropy: f64) -> Value {
+        let group = |key: fn(&SentNonce) -> &str| {
+            let mut groups: BTreeMap<&str, Vec<&SentNonce>> = BTreeMap::new();
+            for sent in &self.transactions {
+                groups.entry(key(sent)).or_default().push(sent);
+            }
+            groups
+                .into_iter()
+                .filter(|(_, sent)| sent.len() > 1)
+                .collect::<Vec<_>>()
+        };
+        let listed = |sent: &[&SentNonce]| -> Vec<Value> {
+            sent.iter()
+                .map(
+                    |sent| json!({ "hash": sent.hash, "height": sent.height, "nonce": sent.nonce }),
+                )
+                .collect()
+        };
+
+        let reused: Vec<Value> = group(|sent| sent.nonce.as_str())
+            .into_iter()
+            .map(|(nonce, sent)| json!({ "nonce": nonce, "transactions": listed(&sent) }))
+            .collect();
+        let resent: Vec<Value> = group(|sent| sent.content_hash.as_str())
+            .into_iter()
+            .map(|(content_hash, sent)| {
+                json!({ "contentHash": content_hash, "transactions": listed(&sent) })
+            })
+            .collect();
+        let low_entropy: Vec<Value> = self
+            .transactions
+            .iter()
+            .filter_map(|sent| {
+                let bits = nonce_entropy(&sent.nonce);
+                if bits < min_entropy {
+                    Some(json!({
+                        "hash": sent.hash,
+                        "height": sent.height,
+                        "nonce": sent.nonce,
+                        "entropyBits": (bits * 10.0).round() / 10.0,
+                    }))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        // Numbers increasing one by one come from a counter, unique but predictable
+        let numbers: Vec<u128> = self
+            .transactions
+            .iter()
+            .filter_map(|sent| sent.nonce.parse().ok())
+            .collect();
+        let counter = numbers.len() > 1
+            && numbers.len() == self.transactions.len()
+            && numbers.windows(2).all(|pair| pair[1] == pair[0] + 1);
+
+        json!({
+            "blocks": self.blocks,
+            "transactions": self.transactions.len(),
+            "findings": reused.len() + resent.len() + low_entropy.len() + counter as usize,
+            "reusedNonces": reused,
+            "resentContents": resent,
+            "lowEntropyNonces": low_entropy,
+            "counterNonces": counter,
+        })
+    }
+}
+
+/// Estimated bits of the nonce, its length by the Shannon entropy of its chars
+fn nonce_entropy(nonce: &str) -> f64 {
+    let mut counts: BTreeMap<char, usize> = BTreeMap::new();
+    for c in nonce.chars() {
+        *counts.entry(c).or_default() += 1;
+    }
+    let length = nonce.chars().count() as f64;
+    let per_char: f64 = counts
+        .values()
+        .map(|&count| {
+            let p = count as f64 / length;
+            -p * p.log2()
+        })
+        .sum();
+    per_char * length
+}
+
+fn top_consumers(consumers: &HashMap<String, Consumer>, top: usize) -> Vec<(&str, &Consumer)> {
+    let mut consumers: Vec<(&str, &Consumer)> = consumers
+        .iter()
+        .map(|(address, consumer)| (address.as_str(), consumer))
+        .collect();
+    consumers.sort_by(|a, b| b.1.quota_used.cmp(&a.1.quota_used).then(a.0.cmp(b.0)));
+    consumers.truncate(top);
+    consumers
+}
+
+#[cfg(test)]
+mod test {
+    use super::{nonce_entropy, NonceAudit, SentNonce};
+
+    #[test]
+    fn test_nonce_audit() {
+        assert!(nonce_entropy("") == 0.0);
+        assert!(nonce_entropy("1") == 0.0);
+        assert!(nonce_entropy("6f3c2a1e-97b4-4d0a-8c5e-1b2f3a4d5e6f") > 100.0);
+
+        let mut audit = NonceAudit::default();
+        for (index, (nonce, content)) in [("7", "a"), ("8", "b"), ("8", "c"), ("9", "a")]
+            .iter()
+            .enumerate()
+        {
+            audit.add(SentNonce {
+                hash: format!("0x{}", index),
+                height: index as u64,
+                nonce: nonce.to_string(),
+                content_hash: content.to_string(),
+            });
+        }
+        let report = audit.report(64.0);
+        assert_eq!(report["reusedNonces"][0]["nonce"], "8");
+        assert_eq!(
+            report["resentContents"][0]["transactions"][1]["hash"],
+            "0x3"
+        );
+        assert_eq!(report["lowEntropyNonces"].as_array().unwrap().len(), 4);
+        assert_eq!(report["counterNonces"], false);
+        assert_eq!(report["findings"], 6);
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use crate::audit;
+use crate::cli::parse_u64;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Audit command
+pub fn audit_command() -> App<'static, 'static> {
+    // Not `--signer`, which selects the device signing
+    let address = Arg::with_name("address")
+        .long("address")
+        .takes_value(true)
+        .help("Only the transactions signed by the address");
+    let failed = Arg::with_name("failed")
+        .long("failed")
+        .help("Only the transactions the node didn't accept");
+    App::new("audit")
+        .about(
+            "The append-only log of the transactions sent by cita-cli: \
+             ~/.cita-cli/audit.log, who sent what to which chain",
+        )
+        .subcommand(
+            SubCommand::with_name("show")
+                .about("Show the entries of the log, the latest last")
+                .arg(
+                    Arg::with_name("limit")
+                        .long("limit")
+                        .takes_value(true)
+                        .default_value("20")
+                        .validator(|limit| parse_u64(&limit).map(|_| ()))
+                        .help("The latest entries at most, 0 for all"),
+                )
+                .arg(address.clone())
+                .arg(failed.clone()),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Export the log for the accounting")
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["json", "csv"])
+                        .default_value("json")
+                        .help("JSON keeps the digests chaining the entries, CSV has no digests"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .help("The file to write, stdout if absent"),
+                )
+                .arg(address)
+                .arg(failed),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Check the digests chaining the entries, an edited or removed entry fails"),
+        )
+}
+
+/// Audit processor
+pub fn audit_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+) -> Result<(), String> {
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let entries = audit::entries()?;
+    let filter = |m: &ArgMatches| {
+        let address = m.value_of("address").map(str::to_lowercase);
+        let failed = m.is_present("failed");
+        entries
+            .iter()
+            .filter(|entry| {
+                address
+                    .as_ref()
+                    .is_none_or(|address| entry["signer"].as_str() == Some(address))
+                    && (!failed || entry["outcome"] == "rejected")
+            })
+            .cloned()
+            .collect::<Vec<Value>>()
+    };
+    match sub_matches.subcommand() {
+        ("show", Some(m)) => {
+            let mut entries = filter(m);
+            let limit = parse_u64(m.value_of("limit").unwrap())? as usize;
+            if limit > 0 && entries.len() > limit {
+                entries.drain(..entries.len() - limit);
+            }
+            printer.println(&json!(entries), is_color);
+        }
+        ("export", Some(m)) => {
+            let entries = filter(m);
+            let content = match m.value_of("format") {
+                Some("csv") => audit::to_csv(&entries),
+                _ => serde_json::to_string_pretty(&entries).unwrap(),
+            };
+            match m.value_of("output") {
+                Some(path) => {
+                    fs::write(path, content)
+                        .map_err(|err| format!("Can't write {}: {}", path, err))?;
+                    printer.println(
+                        &json!({ "output": path, "entries": entries.len() }),
+                        is_color,
+                    );
+                }
+                None => printer.println(&content, is_color),
+            }
+        }
+        ("verify", _) => match audit::verify(&entries) {
+            Some(line) => {
+                return Err(format!(
+                    "The audit log is broken from line {} of {}, \
+                     the entry is edited or one before it is removed",
+                    line,
+                    audit::audit_log_path().display()
+                ));
+            }
+            None => printer.println(
+                &json!({ "entries": entries.len(), "intact": true }),
+                is_color,
+            ),
+        },
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    Ok(())
+}
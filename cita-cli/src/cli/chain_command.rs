@@ -0,0 +1,609 @@
+use std::str::FromStr;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::client::light::LightClient;
+use cita_tool::client::system_contract::{
+    ContractCall, NodeManageClient, NodeManagementExt, PriceManagerClient, PriceManagerExt,
+    QuotaManageClient, QuotaManagementExt, SysConfigClient, SysConfigExt, VersionManagerClient,
+    VersionManagerExt,
+};
+use cita_tool::{
+    decode_params, remove_0x, Address, JsonRpcResponse, LowerHex, ToolError, H256, U256,
+};
+
+use crate::cli::time_command::{block_range, time_range_args};
+use crate::cli::{
+    encryption, get_url, h256_validator, parse_address, parse_height, parse_u32, parse_u64,
+};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Chain information command
+pub fn chain_command() -> App<'static, 'static> {
+    App::new("chain")
+        .about("Chain information for operators")
+        .subcommand(
+            SubCommand::with_name("config")
+                .about("Report the chain metadata and system config in one place")
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .default_value("latest")
+                        .takes_value(true)
+                        .validator(|s| parse_height(s.as_str()))
+                        .help("The height of the chain, hex string or tag 'latest'"),
+                ),
+        )
+        .subcommand(SubCommand::with_name("version").about(
+            "Detect the release and the protocol version of the node, and the \
+                     behaviours the client adapts to",
+        ))
+        .subcommand(
+            SubCommand::with_name("light-sync")
+                .about(
+                    "Download block headers from a trusted block and verify the hash links \
+                     and the validator signatures of each of them",
+                )
+                .args(&light_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("verified-balance")
+                .about(
+                    "Get the balance at a verified block, proved by the state root \
+                     of the block header",
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Account address"),
+                )
+                .args(&light_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("verified-storage")
+                .about(
+                    "Get the storage value at a verified block, proved by the state root \
+                     of the block header",
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Contract address"),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .long("key")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|key| h256_validator(key.as_str()))
+                        .help("The position of the variable"),
+                )
+                .args(&light_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about(
+                    "Recompute the block hashes, the transaction hashes and the transactions \
+                     roots of a block range, and flag the ones not matching the node",
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .required_unless("from-time")
+                        .takes_value(true)
+                        .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+                        .help("The first block height, hex string or number"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+                        .help("The last block height, default is the current height"),
+                )
+                .args(&time_range_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("init-config")
+                .about(
+                    "Derive the init data of the system contracts for a new chain, and the \
+                     admin transactions applying the same settings to a running chain",
+                )
+                .arg(
+                    Arg::with_name("admin")
+                        .long("admin")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The admin and super admin address"),
+                )
+                .arg(
+                    Arg::with_name("validators")
+                        .long("validators")
+                        .required(true)
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The addresses of the consensus nodes, separated by commas"),
+                )
+                .arg(
+                    Arg::with_name("stakes")
+                        .long("stakes")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .validator(|stake| parse_u64(stake.as_str()).map(|_| ()))
+                        .help("The stakes of the validators in the same order, default is 1 each"),
+                )
+                .arg(
+                    Arg::with_name("chain-name")
+                        .long("chain-name")
+                        .takes_value(true)
+                        .default_value("test-chain")
+                        .help("Name of the chain"),
+                )
+                .arg(
+                    Arg::with_name("chain-id")
+                        .long("chain-id")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(|id| parse_u64(id.as_str()).map(|_| ()))
+                        .help("Id of the chain"),
+                )
+                .arg(
+                    Arg::with_name("operator")
+                        .long("operator")
+                        .takes_value(true)
+                        .default_value("test-operator")
+                        .help("Operator of the chain"),
+                )
+                .arg(
+                    Arg::with_name("website")
+                        .long("website")
+                        .takes_value(true)
+                        .default_value("https://www.example.com")
+                        .help("Website of the operator"),
+                )
+                .arg(
+                    Arg::with_name("block-interval")
+                        .long("block-interval")
+                        .takes_value(true)
+                        .default_value("3000")
+                        .validator(|interval| parse_u64(interval.as_str()).map(|_| ()))
+                        .help("Block interval in milliseconds"),
+                )
+                .arg(
+                    Arg::with_name("economical-model")
+                        .long("economical-model")
+                        .takes_value(true)
+                        .possible_values(&["quota", "charge"])
+                        .default_value("quota")
+                        .help("Quota only, or charge the quota by the quota price"),
+                )
+                .arg(
+                    Arg::with_name("quota-price")
+                        .long("quota-price")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(|price| parse_u64(price.as_str()).map(|_| ()))
+                        .help("Price of a quota under the charge model"),
+                )
+                .arg(
+                    Arg::with_name("block-quota-limit")
+                        .long("block-quota-limit")
+                        .takes_value(true)
+                        .default_value("1073741824")
+                        .validator(|limit| parse_u64(limit.as_str()).map(|_| ()))
+                        .help("Quota limit of a block"),
+                )
+                .arg(
+                    Arg::with_name("account-quota-limit")
+                        .long("account-quota-limit")
+                        .takes_value(true)
+                        .default_value("268435455")
+                        .validator(|limit| parse_u64(limit.as_str()).map(|_| ()))
+                        .help("Default quota limit of an account in a block"),
+                )
+                .arg(
+                    Arg::with_name("version")
+                        .long("version")
+                        .takes_value(true)
+                        .default_value("2")
+                        .validator(|version| parse_u32(version.as_str()).map(|_| ()))
+                        .help("Protocol version"),
+                ),
+        )
+}
+
+/// The arguments to start a light client from a trusted block
+fn light_args() -> [Arg<'static, 'static>; 3] {
+    [
+        Arg::with_name("from")
+            .long("from")
+            .required(true)
+            .takes_value(true)
+            .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+            .help("Height of the trusted block"),
+        Arg::with_name("trusted-hash")
+            .long("trusted-hash")
+            .takes_value(true)
+            .validator(|hash| h256_validator(hash.as_str()))
+            .help("Hash of the trusted block, refuse to start from another block"),
+        Arg::with_name("to")
+            .long("to")
+            .takes_value(true)
+            .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+            .help("Verify up to the height, default is the block before the latest"),
+    ]
+}
+
+/// Chain information processor
+pub fn chain_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let report = match sub_matches.subcommand() {
+        ("config", Some(m)) => {
+            let height = m.value_of("height").unwrap();
+            chain_config(client, height).map_err(|err| format!("{}", err))?
+        }
+        ("version", _) => client
+            .detect_chain_version()
+            .map_err(|err| format!("{}", err))?
+            .to_value(),
+        ("light-sync", Some(m)) => {
+            let light = light_sync(m, config, client)?;
+            serde_json::to_value(light.head()).map_err(|err| format!("{}", err))?
+        }
+        ("verified-balance", Some(m)) => {
+            let address = Address::from_str(remove_0x(m.value_of("address").unwrap()))
+                .map_err(|err| err.to_string())?;
+            let light = light_sync(m, config, client)?;
+            let balance = light
+                .verified_get_balance(&address)
+                .map_err(|err| format!("{}", err))?;
+            verified_report(
+                &light,
+                json!({ "balance": balance.completed_lower_hex_with_0x() }),
+            )
+        }
+        ("init-config", Some(m)) => init_config(m).map_err(|err| format!("{}", err))?,
+        ("verify", Some(m)) => {
+            let (from, to) = block_range(m, &client)?;
+            let checks = client
+                .verify_blocks(from, to, encryption(m, config))
+                .map_err(|err| format!("{}", err))?;
+            let invalid = checks
+                .into_iter()
+                .filter(|check| !check.is_valid())
+                .collect::<Vec<_>>();
+            let report = json!({
+                "from": from,
+                "to": to,
+                "valid": invalid.is_empty(),
+                "invalid": invalid,
+            });
+            if !invalid.is_empty() {
+                printer.println(&report, is_color);
+                return Err(format!(
+                    "{} of {} blocks failed the verification",
+                    invalid.len(),
+                    to - from + 1
+                ));
+            }
+            report
+        }
+        ("verified-storage", Some(m)) => {
+            let address = Address::from_str(remove_0x(m.value_of("address").unwrap()))
+                .map_err(|err| err.to_string())?;
+            let key = H256::from_str(remove_0x(m.value_of("key").unwrap()))
+                .map_err(|err| err.to_string())?;
+            let light = light_sync(m, config, client)?;
+            let value = light
+                .verified_get_storage(&address, &key)
+                .map_err(|err| format!("{}", err))?;
+            verified_report(
+                &light,
+                json!({ "value": value.completed_lower_hex_with_0x() }),
+            )
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&report, is_color);
+    config.set("result".to_string(), report);
+    Ok(())
+}
+
+/// Start a light client with the arguments of `light_args`, and verify up to the height
+fn light_sync(
+    m: &ArgMatches,
+    config: &GlobalConfig,
+    client: Client,
+) -> Result<LightClient, String> {
+    let from = parse_u64(m.value_of("from").unwrap())?;
+    let trusted_hash = m
+        .value_of("trusted-hash")
+        .map(|hash| H256::from_str(remove_0x(hash)).unwrap());
+    // The latest block has no proof yet, it's carried by the next block
+    let to = match m.value_of("to") {
+        Some(to) => parse_u64(to)?,
+        None => client
+            .get_current_height()
+            .map_err(|err| format!("{}", err))?
+            .saturating_sub(1),
+    };
+    let mut light = LightClient::new(client, encryption(m, config), from, trusted_hash)
+        .map_err(|err| format!("{}", err))?;
+    light.sync_to(to).map_err(|err| format!("{}", err))?;
+    Ok(light)
+}
+
+/// Mark the value as verified at the head of the light client
+fn verified_report(light: &LightClient, mut report: Value) -> Value {
+    let head = light.head();
+    report["height"] = json!(head.height);
+    report["stateRoot"] = json!(head.state_root);
+    report["verified"] = json!(true);
+    report
+}
+
+/// Collect the metadata, system config flags and quota price at the height
+fn chain_config(client: Client, height: &str) -> Result<Value, ToolError> {
+    let metadata = result_value(client.get_metadata(height)?)?;
+    let height = Some(height);
+
+    let sys_config: SysConfigClient<Client> = SysConfigExt::create(client.clone());
+    let price_manager: PriceManagerClient<Client> = PriceManagerExt::create(client);
+
+    let economical_model = match decode_call(
+        SysConfigExt::get_economical_model(&sys_config, height)?,
+        "uint8",
+    )?
+    .as_str()
+    {
+        Some("0x0") => "Quota",
+        Some("0x1") => "Charge",
+        _ => "Unknown",
+    };
+
+    Ok(json!({
+        "chainId": metadata["chainId"],
+        "chainIdV1": metadata["chainIdV1"],
+        "chainName": metadata["chainName"],
+        "operator": metadata["operator"],
+        "website": metadata["website"],
+        "version": metadata["version"],
+        "genesisTimestamp": metadata["genesisTimestamp"],
+        "blockInterval": metadata["blockInterval"],
+        "validators": metadata["validators"],
+        "token": {
+            "name": metadata["tokenName"],
+            "symbol": metadata["tokenSymbol"],
+            "avatar": metadata["tokenAvatar"],
+        },
+        "economicalModel": economical_model,
+        "quotaPrice": decode_call(price_manager.price(height)?, "uint256")?,
+        "chainOwner": decode_call(SysConfigExt::get_chain_owner(&sys_config, height)?, "address")?,
+        "delayBlockNumber": decode_call(
+            SysConfigExt::get_delay_block_number(&sys_config, height)?,
+            "uint256",
+        )?,
+        "checks": {
+            "permission": decode_call(SysConfigExt::get_permission_check(&sys_config, height)?, "bool")?,
+            "sendTxPermission": decode_call(
+                SysConfigExt::get_send_permission_check(&sys_config, height)?,
+                "bool",
+            )?,
+            "createContractPermission": decode_call(
+                SysConfigExt::get_create_permission_check(&sys_config, height)?,
+                "bool",
+            )?,
+            "quota": decode_call(SysConfigExt::get_quota_check(&sys_config, height)?, "bool")?,
+            "feeBackPlatform": decode_call(
+                SysConfigExt::get_feeback_platform_check(&sys_config, height)?,
+                "bool",
+            )?,
+        },
+    }))
+}
+
+/// The init data of the system contracts, in the layout of `init_data.yml` of cita-config,
+/// and the admin transactions applying the same settings to a running chain
+fn init_config(m: &ArgMatches) -> Result<Value, ToolError> {
+    let address = |value: &str| format!("0x{}", remove_0x(value).to_lowercase());
+    let number = |name: &str| parse_u64(m.value_of(name).unwrap()).map_err(ToolError::Customize);
+    let admin = address(m.value_of("admin").unwrap());
+    let validators: Vec<String> = m.values_of("validators").unwrap().map(address).collect();
+    let stakes: Vec<u64> = match m.values_of("stakes") {
+        Some(stakes) => stakes
+            .map(|stake| parse_u64(stake).map_err(ToolError::Customize))
+            .collect::<Result<_, _>>()?,
+        None => vec![1; validators.len()],
+    };
+    if stakes.len() != validators.len() {
+        return Err(ToolError::Customize(format!(
+            "{} stakes for {} validators",
+            stakes.len(),
+            validators.len()
+        )));
+    }
+    let economical_model = match m.value_of("economical-model") {
+        Some("charge") => 1,
+        _ => 0,
+    };
+    let chain_name = m.value_of("chain-name").unwrap();
+    let operator = m.value_of("operator").unwrap();
+    let website = m.value_of("website").unwrap();
+    let block_interval = number("block-interval")?;
+    let quota_price = number("quota-price")?;
+    let block_quota_limit = number("block-quota-limit")?;
+    let account_quota_limit = number("account-quota-limit")?;
+    let version = parse_u32(m.value_of("version").unwrap()).map_err(ToolError::Customize)?;
+
+    let init_data = json!({
+        "Contracts": {
+            "SysConfig": {
+                "delayBlockNumber": 1,
+                "checkCallPermission": false,
+                "checkSendTxPermission": false,
+                "checkCreateContractPermission": false,
+                "checkQuota": false,
+                "checkFeeBackPlatform": false,
+                "chainOwner": "0x0000000000000000000000000000000000000000",
+                "chainName": chain_name,
+                "chainId": number("chain-id")?,
+                "operator": operator,
+                "website": website,
+                "blockInterval": block_interval,
+                "economicalModel": economical_model,
+                "autoExec": false,
+            },
+            "QuotaManager": { "admin": admin },
+            "NodeManager": { "nodes": validators, "stakes": stakes },
+            "ChainManager": { "parentChainId": 0, "parentChainAuthorities": [] },
+            "Authorization": { "superAdmin": admin },
+            "Group": {
+                "parent": "0x0000000000000000000000000000000000000000",
+                "name": "rootGroup",
+                "accounts": [admin],
+            },
+            "Admin": { "admin": admin },
+            "VersionManager": { "version": version },
+            "PriceManager": { "quotaPrice": quota_price },
+        }
+    });
+
+    // Only encoded, the client never sends
+    let client = Client::new();
+    let node_manager: NodeManageClient<Client> = NodeManagementExt::create(client.clone());
+    let quota_manager: QuotaManageClient<Client> = QuotaManagementExt::create(client.clone());
+    let sys_config: SysConfigClient<Client> = SysConfigExt::create(client.clone());
+    let price_manager: PriceManagerClient<Client> = PriceManagerExt::create(client.clone());
+    let version_manager: VersionManagerClient<Client> = VersionManagerExt::create(client);
+    let hex = |value: u64| U256::from(value).completed_lower_hex();
+
+    let mut transactions = Vec::new();
+    for (validator, stake) in validators.iter().zip(&stakes) {
+        let validator = remove_0x(validator);
+        transactions.push(admin_call(
+            &node_manager,
+            "NodeManager",
+            "approveNode",
+            &[validator],
+        )?);
+        transactions.push(admin_call(
+            &node_manager,
+            "NodeManager",
+            "setStake",
+            &[validator, &hex(*stake)],
+        )?);
+    }
+    transactions.push(admin_call(
+        &quota_manager,
+        "QuotaManager",
+        "setBQL",
+        &[&hex(block_quota_limit)],
+    )?);
+    transactions.push(admin_call(
+        &quota_manager,
+        "QuotaManager",
+        "setDefaultAQL",
+        &[&hex(account_quota_limit)],
+    )?);
+    transactions.push(admin_call(
+        &sys_config,
+        "SysConfig",
+        "setChainName",
+        &[chain_name],
+    )?);
+    transactions.push(admin_call(
+        &sys_config,
+        "SysConfig",
+        "setOperator",
+        &[operator],
+    )?);
+    transactions.push(admin_call(
+        &sys_config,
+        "SysConfig",
+        "setWebsite",
+        &[website],
+    )?);
+    transactions.push(admin_call(
+        &sys_config,
+        "SysConfig",
+        "setBlockInterval",
+        &[&hex(block_interval)],
+    )?);
+    if economical_model == 1 {
+        transactions.push(admin_call(
+            &price_manager,
+            "PriceManager",
+            "setQuotaPrice",
+            &[&hex(quota_price)],
+        )?);
+    }
+    transactions.push(admin_call(
+        &version_manager,
+        "VersionManager",
+        "setVersion",
+        &[&hex(u64::from(version))],
+    )?);
+
+    Ok(json!({ "initData": init_data, "transactions": transactions }))
+}
+
+/// Encode the call of the system contract function, to be sent by the admin
+fn admin_call(
+    client: &dyn ContractCall<JsonRpcResponse, ToolError>,
+    contract: &str,
+    function: &str,
+    values: &[&str],
+) -> Result<Value, ToolError> {
+    let (data, to) = client.prepare_call_args(function, values, None)?;
+    Ok(json!({
+        "contract": contract,
+        "function": function,
+        "args": values,
+        "to": to,
+        "data": data,
+    }))
+}
+
+pub(crate) fn result_value(response: JsonRpcResponse) -> Result<Value, ToolError> {
+    if let Some(err) = response.error() {
+        return Err(err.into_error());
+    }
+    serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)
+}
+
+/// Decode the single return value of a contract call, numbers and addresses are hex with 0x
+pub(crate) fn decode_call(response: JsonRpcResponse, kind: &str) -> Result<Value, ToolError> {
+    let data = result_value(response)?;
+    let data = remove_0x(data.as_str().unwrap_or_default());
+    let decoded = decode_params(&[kind.to_owned()], data)?;
+    let value: Value = serde_json::from_str(&decoded[0]).map_err(ToolError::SerdeJson)?;
+    match value[kind].as_str() {
+        Some(hex) if kind.starts_with("uint") || kind == "address" => {
+            Ok(Value::String(format!("0x{}", hex)))
+        }
+        _ => Ok(value[kind].clone()),
+    }
+}
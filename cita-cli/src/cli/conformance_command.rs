@@ -0,0 +1,446 @@
+use std::fmt;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::{JsonRpcParams, Params};
+
+use crate::cli::chain_command::result_value;
+use crate::cli::get_url;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// How many addresses are in the huge filters
+const HUGE_FILTER: u64 = 500;
+
+const ZERO_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+/// Node conformance command
+pub fn conformance_command() -> App<'static, 'static> {
+    App::new("conformance")
+        .about("Test a node against the jsonrpc spec of CITA")
+        .subcommand(
+            SubCommand::with_name("run")
+                .about(
+                    "Call the methods with valid and edge-case params, such as bad hex, \
+                     heights out of range and huge filters, and check every response \
+                     strictly, fail if any case doesn't behave per spec",
+                )
+                .arg(
+                    Arg::with_name("json")
+                        .long("json")
+                        .help("Print the report as json instead of a table"),
+                ),
+        )
+}
+
+/// Node conformance processor
+pub fn conformance_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    // The node is tested as it is, by the current method names and without caches
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config))
+        .set_strict(true)
+        .set_method_aliases(None)
+        .set_cache(None)
+        .set_node_selector(None);
+
+    match sub_matches.subcommand() {
+        ("run", Some(m)) => {
+            let height = client
+                .get_current_height()
+                .map_err(|err| format!("Can't get the height of the node: {}", err))?;
+            let checks = cases(height)
+                .into_iter()
+                .map(|case| run(&client, case))
+                .collect::<Vec<Check>>();
+            let failed = checks.iter().filter(|check| !check.passed).count();
+            let report = json!({
+                "url": client.uri().to_string(),
+                "height": height,
+                "passed": checks.len() - failed,
+                "failed": failed,
+                "cases": checks
+                    .iter()
+                    .map(|check| json!({
+                        "name": check.case.name,
+                        "method": check.case.method,
+                        "params": check.case.params,
+                        "expect": check.case.expect.to_string(),
+                        "passed": check.passed,
+                        "detail": check.detail,
+                    }))
+                    .collect::<Vec<Value>>(),
+            });
+
+            if m.is_present("json") {
+                printer.println(&report, !m.is_present("no-color") && config.color());
+            } else {
+                println!("{}", conformance_table(&checks));
+            }
+            config.set("result".to_string(), report);
+            if failed > 0 {
+                return Err(format!(
+                    "{} of {} cases don't behave per spec",
+                    failed,
+                    checks.len()
+                ));
+            }
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    Ok(())
+}
+
+/// What the spec expects of a call
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Expect {
+    /// A result which isn't null
+    Result,
+    /// A null result, such as the block of a height not reached
+    Null,
+    /// A jsonrpc error
+    Error,
+    /// A jsonrpc error of the code
+    Code(i64),
+    /// A valid response, a result or an error, but neither a crash nor a timeout
+    Any,
+}
+
+impl fmt::Display for Expect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expect::Result => write!(f, "result"),
+            Expect::Null => write!(f, "null"),
+            Expect::Error => write!(f, "error"),
+            Expect::Code(code) => write!(f, "error {}", code),
+            Expect::Any => write!(f, "any"),
+        }
+    }
+}
+
+/// A call of the battery
+struct Case {
+    name: &'static str,
+    method: &'static str,
+    params: Value,
+    expect: Expect,
+}
+
+struct Check {
+    case: Case,
+    passed: bool,
+    detail: String,
+}
+
+fn case(name: &'static str, method: &'static str, params: Value, expect: Expect) -> Case {
+    Case {
+        name,
+        method,
+        params,
+        expect,
+    }
+}
+
+/// The battery, by the current height of the node
+fn cases(height: u64) -> Vec<Case> {
+    let latest = format!("{:#x}", height);
+    let future = format!("{:#x}", height + 1_000_000);
+    let addresses = (1..=HUGE_FILTER)
+        .map(|index| format!("{:#042x}", index))
+        .collect::<Vec<String>>();
+    let huge_filter = json!({ "fromBlock": "0x0", "toBlock": "latest", "address": addresses });
+
+    vec![
+        case("height", "blockNumber", json!([]), Expect::Result),
+        case("peer count", "peerCount", json!([]), Expect::Result),
+        case("metadata", "getMetaData", json!(["latest"]), Expect::Result),
+        case(
+            "genesis block",
+            "getBlockByNumber",
+            json!(["0x0", false]),
+            Expect::Result,
+        ),
+        case(
+            "latest block",
+            "getBlockByNumber",
+            json!([latest, true]),
+            Expect::Result,
+        ),
+        case(
+            "latest tag",
+            "getBlockByNumber",
+            json!(["latest", false]),
+            Expect::Result,
+        ),
+        case(
+            "height not reached",
+            "getBlockByNumber",
+            json!([future, false]),
+            Expect::Null,
+        ),
+        case(
+            "bad hex height",
+            "getBlockByNumber",
+            json!(["0xzz", false]),
+            Expect::Error,
+        ),
+        case(
+            "number height",
+            "getBlockByNumber",
+            json!([1, false]),
+            Expect::Error,
+        ),
+        case(
+            "missing params",
+            "getBlockByNumber",
+            json!([]),
+            Expect::Error,
+        ),
+        case(
+            "unknown block hash",
+            "getBlockByHash",
+            json!([ZERO_HASH, false]),
+            Expect::Null,
+        ),
+        case(
+            "short block hash",
+            "getBlockByHash",
+            json!(["0x1234", false]),
+            Expect::Error,
+        ),
+        case(
+            "unknown transaction",
+            "getTransaction",
+            json!([ZERO_HASH]),
+            Expect::Null,
+        ),
+        case(
+            "unknown receipt",
+            "getTransactionReceipt",
+            json!([ZERO_HASH]),
+            Expect::Null,
+        ),
+        case(
+            "bad hex hash",
+            "getTransactionReceipt",
+            json!(["0xzz"]),
+            Expect::Error,
+        ),
+        case(
+            "balance",
+            "getBalance",
+            json!([ZERO_ADDRESS, "latest"]),
+            Expect::Result,
+        ),
+        case(
+            "short address",
+            "getBalance",
+            json!(["0xabc", "latest"]),
+            Expect::Error,
+        ),
+        case(
+            "balance not reached",
+            "getBalance",
+            json!([ZERO_ADDRESS, future]),
+            Expect::Error,
+        ),
+        case(
+            "code",
+            "getCode",
+            json!([ZERO_ADDRESS, "latest"]),
+            Expect::Result,
+        ),
+        case(
+            "nonce",
+            "getTransactionCount",
+            json!([ZERO_ADDRESS, "latest"]),
+            Expect::Result,
+        ),
+        case(
+            "empty call",
+            "call",
+            json!([{ "to": ZERO_ADDRESS, "data": "0x" }, "latest"]),
+            Expect::Result,
+        ),
+        case(
+            "reversed log range",
+            "getLogs",
+            json!([{ "fromBlock": latest, "toBlock": "0x0" }]),
+            Expect::Any,
+        ),
+        case(
+            "bad hex log range",
+            "getLogs",
+            json!([{ "fromBlock": "0xzz" }]),
+            Expect::Error,
+        ),
+        case(
+            "huge log filter",
+            "getLogs",
+            json!([huge_filter]),
+            Expect::Any,
+        ),
+        case(
+            "huge filter",
+            "newFilter",
+            json!([huge_filter]),
+            Expect::Any,
+        ),
+        case(
+            "unknown filter",
+            "uninstallFilter",
+            json!(["0xfffffff"]),
+            Expect::Result,
+        ),
+        case(
+            "invalid transaction",
+            "sendRawTransaction",
+            json!(["0x00"]),
+            Expect::Error,
+        ),
+        case(
+            "unknown method",
+            "noSuchMethod",
+            json!([]),
+            Expect::Code(-32601),
+        ),
+    ]
+}
+
+/// Call the case, the filters it installs are uninstalled
+fn run(client: &Client, case: Case) -> Check {
+    let outcome = Params::from_json(case.params.clone())
+        .map_err(|err| format!("{}", err))
+        .and_then(|params| {
+            let params = JsonRpcParams::request(case.method, params);
+            client
+                .send_request(Some(params).into_iter())
+                .map_err(|err| format!("{}", err))
+        })
+        .map(|mut responses| responses.remove(0));
+    let (passed, detail) = match outcome {
+        Ok(response) => {
+            let value = serde_json::to_value(&response).unwrap_or_default();
+            if case.method == "newFilter" {
+                if let Some(id) = result_value(response)
+                    .ok()
+                    .and_then(|id| id.as_str().map(ToOwned::to_owned))
+                {
+                    let _ = client.uninstall_filter(&id);
+                }
+            }
+            judge(case.expect, &value)
+        }
+        Err(err) => (false, err),
+    };
+    Check {
+        case,
+        passed,
+        detail,
+    }
+}
+
+/// Whether the response is the expected one, and what it is
+fn judge(expect: Expect, response: &Value) -> (bool, String) {
+    let error = &response["error"];
+    let result = &response["result"];
+    let detail = if error.is_null() {
+        let result = result.to_string();
+        if result.len() > 66 {
+            format!("{}...", &result[..63])
+        } else {
+            result
+        }
+    } else {
+        format!(
+            "error {}: {}",
+            error["code"],
+            error["message"].as_str().unwrap_or_default()
+        )
+    };
+    let passed = match expect {
+        Expect::Result => error.is_null() && !result.is_null(),
+        Expect::Null => error.is_null() && result.is_null(),
+        Expect::Error => !error.is_null(),
+        Expect::Code(code) => error["code"].as_i64() == Some(code),
+        Expect::Any => true,
+    };
+    (passed, detail)
+}
+
+fn conformance_table(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.case.name.len())
+        .max()
+        .unwrap_or_default()
+        .max(4);
+    let method_width = checks
+        .iter()
+        .map(|check| check.case.method.len())
+        .max()
+        .unwrap_or_default()
+        .max(6);
+    let mut table = format!(
+        "{:<width$}  {:<method_width$}  {:<12}  {:<4}  DETAIL",
+        "CASE",
+        "METHOD",
+        "EXPECT",
+        "",
+        width = width,
+        method_width = method_width
+    );
+    for check in checks {
+        table.push_str(&format!(
+            "\n{:<width$}  {:<method_width$}  {:<12}  {:<4}  {}",
+            check.case.name,
+            check.case.method,
+            check.case.expect.to_string(),
+            if check.passed { "ok" } else { "FAIL" },
+            check.detail,
+            width = width,
+            method_width = method_width
+        ));
+    }
+    table
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cases, judge, Expect};
+    use serde_json::json;
+
+    #[test]
+    fn test_judge() {
+        let result = json!({ "jsonrpc": "2.0", "id": 1, "result": "0x1" });
+        let null = json!({ "jsonrpc": "2.0", "id": 1 });
+        let error = json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "Method not found" } });
+
+        assert_eq!(
+            judge(Expect::Result, &result),
+            (true, "\"0x1\"".to_string())
+        );
+        assert!(!judge(Expect::Result, &null).0);
+        assert!(judge(Expect::Null, &null).0);
+        assert!(!judge(Expect::Null, &error).0);
+        assert_eq!(
+            judge(Expect::Code(-32601), &error),
+            (true, "error -32601: Method not found".to_string())
+        );
+        assert!(!judge(Expect::Code(-32602), &error).0);
+        assert!(judge(Expect::Any, &error).0);
+
+        let battery = cases(100);
+        assert_eq!(battery[4].params, json!(["0x64", true]));
+        assert_eq!(battery[6].params, json!(["0xf42a4", false]));
+    }
+}
@@ -14,11 +14,12 @@ use cita_tool::client::system_contract::{
 };
 
 use crate::cli::{
-    encryption, get_url, is_hex, key_validator, parse_address, parse_height, parse_privkey,
-    parse_u256, parse_u64,
+    address_list_validator, address_validator, encryption, get_url, is_hex, key_validator,
+    parse_address, parse_height, parse_privkey, parse_u256, parse_u64,
 };
 use crate::interactive::{set_output, GlobalConfig};
 use crate::printer::Printer;
+use crate::risk::confirmed;
 
 /// System contract
 pub fn contract_command() -> App<'static, 'static> {
@@ -49,18 +50,19 @@ pub fn contract_command() -> App<'static, 'static> {
         .long("origin")
         .takes_value(true)
         .required(true)
-        .validator(|address| is_hex(address.as_ref()))
+        .validator(|address| address_validator(address.as_str()))
         .help("Group origin address");
     let group_target_arg = Arg::with_name("target")
         .long("target")
         .takes_value(true)
         .required(true)
-        .validator(|address| is_hex(address.as_ref()))
+        .validator(|address| address_validator(address.as_str()))
         .help("Group target address");
     let group_accounts_arg = Arg::with_name("accounts")
         .long("accounts")
         .takes_value(true)
         .required(true)
+        .validator(|addresses| address_list_validator(addresses.as_str()))
         .help("Group account address list");
 
     let account_address_arg = Arg::with_name("account")
@@ -85,6 +87,7 @@ pub fn contract_command() -> App<'static, 'static> {
         .long("contracts")
         .takes_value(true)
         .required(true)
+        .validator(|addresses| address_list_validator(addresses.as_str()))
         .help("Contract address list");
     let function_hashes_arg = Arg::with_name("function-hashes")
         .long("function-hashes")
@@ -103,6 +106,10 @@ pub fn contract_command() -> App<'static, 'static> {
         .required(true)
         .validator(|private_key| key_validator(private_key.as_ref()).map(|_| ()))
         .help("Private key must be admin");
+    let yes_arg = Arg::with_name("yes")
+        .long("yes")
+        .short("y")
+        .help("Don't ask for confirmation");
 
     let role_address_arg = address_arg.clone().help("Role address");
     let role_name_arg = name_arg.clone().help("Role name");
@@ -135,6 +142,7 @@ pub fn contract_command() -> App<'static, 'static> {
                 .subcommand(
                     SubCommand::with_name("deleteNode")
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(
                             address_arg.clone().help("Degraded node address"),
                         )
@@ -143,6 +151,7 @@ pub fn contract_command() -> App<'static, 'static> {
                 .subcommand(
                     SubCommand::with_name("approveNode")
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(
                             address_arg.clone().help("Approve node address"),
                         )
@@ -151,6 +160,7 @@ pub fn contract_command() -> App<'static, 'static> {
                 .subcommand(
                     SubCommand::with_name("setStake")
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(
                             Arg::with_name("stake")
                                 .long("stake")
@@ -194,6 +204,7 @@ pub fn contract_command() -> App<'static, 'static> {
                                 ),
                         )
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(quota_arg.clone()),
                 )
                 .subcommand(
@@ -209,6 +220,7 @@ pub fn contract_command() -> App<'static, 'static> {
                                 ),
                         )
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(quota_arg.clone()),
                 )
                 .subcommand(
@@ -224,6 +236,7 @@ pub fn contract_command() -> App<'static, 'static> {
                                 ),
                         )
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(
                             address_arg.clone().help("Account address"),
                         )
@@ -601,6 +614,7 @@ pub fn contract_command() -> App<'static, 'static> {
                             address_arg.clone().help("Account address"),
                         )
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                         .arg(quota_arg.clone()),
                 ),
         )
@@ -680,6 +694,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
                 .subcommand(
                     SubCommand::with_name("setOperator")
@@ -692,6 +707,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
                 .subcommand(
                     SubCommand::with_name("setWebsite")
@@ -704,6 +720,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
                 .subcommand(
                     SubCommand::with_name("setBlockInterval")
@@ -716,6 +733,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
                 .subcommand(
                     SubCommand::with_name("getCreateContractPermissionCheck")
@@ -747,6 +765,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
         )
         .subcommand(
@@ -766,6 +785,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
         )
         .subcommand(
@@ -788,6 +808,7 @@ pub fn contract_command() -> App<'static, 'static> {
                         )
                         .arg(quota_arg.clone())
                         .arg(admin_private.clone())
+                        .arg(yes_arg.clone())
                 )
         )
 }
@@ -804,6 +825,15 @@ pub fn contract_processor(
         .set_debug(debug)
         .set_uri(get_url(sub_matches, config));
 
+    if let (contract, Some(m)) = sub_matches.subcommand() {
+        if let (function, Some(m)) = m.subcommand() {
+            confirmed(
+                m,
+                &format!("scm {} {}", contract, function),
+                &format!("Call {}.{} as the admin.", contract, function),
+            )?;
+        }
+    }
     let result = match sub_matches.subcommand() {
         ("NodeManager", Some(m)) => match m.subcommand() {
             ("listNode", Some(m)) => {
@@ -827,7 +857,7 @@ pub fn contract_processor(
                 )?);
                 let address = m.value_of("address").unwrap();
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
-                let mut client = NodeManageClient::create(client);
+                let client = NodeManageClient::create(client);
                 client.downgrade_consensus_node(address, quota)
             }
             ("approveNode", Some(m)) => {
@@ -838,7 +868,7 @@ pub fn contract_processor(
                 )?);
                 let address = m.value_of("address").unwrap();
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
-                let mut client = NodeManageClient::create(client);
+                let client = NodeManageClient::create(client);
                 client.approve_node(address, quota)
             }
             ("setStake", Some(m)) => {
@@ -853,7 +883,7 @@ pub fn contract_processor(
                     .value_of("stake")
                     .map(|stake| parse_u256(stake).unwrap())
                     .unwrap();
-                let mut client = NodeManageClient::create(client);
+                let client = NodeManageClient::create(client);
                 client.set_stake(address, stake, quota)
             }
             ("stakePermillage", Some(m)) => {
@@ -957,7 +987,7 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = GroupManageClient::create(client);
+                let client = GroupManageClient::create(client);
                 client.new_group(origin, name, accounts, quota)
             }
             ("deleteGroup", Some(m)) => {
@@ -969,7 +999,7 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = GroupManageClient::create(client);
+                let client = GroupManageClient::create(client);
                 client.delete_group(origin, target, quota)
             }
             ("updateGroupName", Some(m)) => {
@@ -982,7 +1012,7 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = GroupManageClient::create(client);
+                let client = GroupManageClient::create(client);
                 client.update_group_name(origin, target, name, quota)
             }
             ("addAccounts", Some(m)) => {
@@ -995,7 +1025,7 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = GroupManageClient::create(client);
+                let client = GroupManageClient::create(client);
                 client.add_accounts(origin, target, accounts, quota)
             }
             ("deleteAccounts", Some(m)) => {
@@ -1008,7 +1038,7 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = GroupManageClient::create(client);
+                let client = GroupManageClient::create(client);
                 client.delete_accounts(origin, target, accounts, quota)
             }
             ("checkScope", Some(m)) => {
@@ -1062,8 +1092,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::new_role(&mut client, name, permissions, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::new_role(&client, name, permissions, quota)
             }
             ("deleteRole", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1073,8 +1103,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::delete_role(&mut client, role, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::delete_role(&client, role, quota)
             }
             ("updateRoleName", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1085,8 +1115,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::update_role_name(&mut client, role, name, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::update_role_name(&client, role, name, quota)
             }
             ("addPermissions", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1097,8 +1127,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::add_permissions(&mut client, role, permissions, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::add_permissions(&client, role, permissions, quota)
             }
             ("deletePermissions", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1109,8 +1139,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::delete_permissions(&mut client, role, permissions, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::delete_permissions(&client, role, permissions, quota)
             }
             ("setRole", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1121,8 +1151,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::set_role(&mut client, account, role, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::set_role(&client, account, role, quota)
             }
             ("cancelRole", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1133,8 +1163,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::cancel_role(&mut client, account, role, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::cancel_role(&client, account, role, quota)
             }
             ("clearRole", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1144,8 +1174,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = RoleManageClient::create(client);
-                RoleManagementExt::clear_role(&mut client, account, quota)
+                let client = RoleManageClient::create(client);
+                RoleManagementExt::clear_role(&client, account, quota)
             }
             ("queryRoles", Some(m)) => {
                 let account = m.value_of("account").unwrap();
@@ -1242,9 +1272,9 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
+                let client = PermissionManageClient::create(client);
                 PermissionManagementExt::new_permission(
-                    &mut client,
+                    &client,
                     name,
                     contracts,
                     function_hashes,
@@ -1259,8 +1289,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::delete_permission(&mut client, permission, quota)
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::delete_permission(&client, permission, quota)
             }
             ("updatePermissionName", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1271,13 +1301,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::update_permission_name(
-                    &mut client,
-                    permission,
-                    name,
-                    quota,
-                )
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::update_permission_name(&client, permission, name, quota)
             }
             ("addResources", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1289,9 +1314,9 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
+                let client = PermissionManageClient::create(client);
                 PermissionManagementExt::add_resources(
-                    &mut client,
+                    &client,
                     permission,
                     contracts,
                     function_hashes,
@@ -1308,9 +1333,9 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
+                let client = PermissionManageClient::create(client);
                 PermissionManagementExt::delete_resources(
-                    &mut client,
+                    &client,
                     permission,
                     contracts,
                     function_hashes,
@@ -1326,8 +1351,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::set_authorization(&mut client, account, permission, quota)
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::set_authorization(&client, account, permission, quota)
             }
             ("setAuthorizations", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1338,13 +1363,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::set_authorizations(
-                    &mut client,
-                    account,
-                    permissions,
-                    quota,
-                )
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::set_authorizations(&client, account, permissions, quota)
             }
             ("cancelAuthorization", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1355,13 +1375,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::cancel_authorization(
-                    &mut client,
-                    account,
-                    permission,
-                    quota,
-                )
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::cancel_authorization(&client, account, permission, quota)
             }
             ("cancelAuthorizations", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1372,13 +1387,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::cancel_authorizations(
-                    &mut client,
-                    account,
-                    permissions,
-                    quota,
-                )
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::cancel_authorizations(&client, account, permissions, quota)
             }
             ("clearAuthorization", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1388,8 +1398,8 @@ pub fn contract_processor(
                     m.value_of("private-key").unwrap(),
                     encryption,
                 )?);
-                let mut client = PermissionManageClient::create(client);
-                PermissionManagementExt::clear_authorization(&mut client, account, quota)
+                let client = PermissionManageClient::create(client);
+                PermissionManagementExt::clear_authorization(&client, account, quota)
             }
             _ => return Err(m.usage().to_owned()),
         },
@@ -1463,10 +1473,10 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: SysConfigClient<Client> = SysConfigExt::create(client);
+                let client: SysConfigClient<Client> = SysConfigExt::create(client);
                 let name = m.value_of("chain-name").unwrap();
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
-                SysConfigExt::set_chain_name(&mut client, name, quota)
+                SysConfigExt::set_chain_name(&client, name, quota)
             }
             ("setOperator", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1474,10 +1484,10 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: SysConfigClient<Client> = SysConfigExt::create(client);
+                let client: SysConfigClient<Client> = SysConfigExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let operator = m.value_of("operator").unwrap();
-                SysConfigExt::set_operator(&mut client, operator, quota)
+                SysConfigExt::set_operator(&client, operator, quota)
             }
             ("setWebsite", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1485,10 +1495,10 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: SysConfigClient<Client> = SysConfigExt::create(client);
+                let client: SysConfigClient<Client> = SysConfigExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let website = m.value_of("website").unwrap();
-                SysConfigExt::set_website(&mut client, website, quota)
+                SysConfigExt::set_website(&client, website, quota)
             }
             ("setBlockInterval", Some(m)) => {
                 let encryption = encryption(m, config);
@@ -1496,14 +1506,14 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: SysConfigClient<Client> = SysConfigExt::create(client);
+                let client: SysConfigClient<Client> = SysConfigExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let block_interval = m
                     .value_of("blockInterval")
                     .map(|interval| parse_u256(interval).unwrap())
                     .unwrap();
 
-                SysConfigExt::set_block_interval(&mut client, block_interval, quota)
+                SysConfigExt::set_block_interval(&client, block_interval, quota)
             }
             ("getCreateContractPermissionCheck", Some(m)) => {
                 let client: SysConfigClient<Client> = SysConfigExt::create(client);
@@ -1526,13 +1536,13 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: EmergencyBrakeClient<Client> = EmergencyBrakeExt::create(client);
+                let client: EmergencyBrakeClient<Client> = EmergencyBrakeExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let state = m
                     .value_of("state")
                     .map(|state| state.parse::<bool>().unwrap())
                     .unwrap();
-                EmergencyBrakeExt::set_state(&mut client, state, quota)
+                EmergencyBrakeExt::set_state(&client, state, quota)
             }
             _ => return Err(sub_matches.usage().to_owned()),
         },
@@ -1547,13 +1557,13 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: PriceManagerClient<Client> = PriceManagerExt::create(client);
+                let client: PriceManagerClient<Client> = PriceManagerExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let price = m
                     .value_of("price")
                     .map(|price| parse_u256(price).unwrap())
                     .unwrap();
-                PriceManagerExt::set_price(&mut client, price, quota)
+                PriceManagerExt::set_price(&client, price, quota)
             }
             _ => return Err(sub_matches.usage().to_owned()),
         },
@@ -1568,13 +1578,13 @@ pub fn contract_processor(
                     m.value_of("admin-private").unwrap(),
                     encryption,
                 )?);
-                let mut client: VersionManagerClient<Client> = VersionManagerExt::create(client);
+                let client: VersionManagerClient<Client> = VersionManagerExt::create(client);
                 let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
                 let version = m
                     .value_of("version")
                     .map(|version| parse_u256(version).unwrap())
                     .unwrap();
-                VersionManagerExt::set_version(&mut client, version, quota)
+                VersionManagerExt::set_version(&client, version, quota)
             }
             _ => return Err(sub_matches.usage().to_owned()),
         },
@@ -0,0 +1,333 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::{Encryption, KeyPair, LowerHex};
+
+use crate::address_book::AddressBook;
+use crate::cli::{encryption, key_validator, parse_local_privkey, parse_u64};
+use crate::interactive::{cita_cli_dir, GlobalConfig};
+use crate::printer::Printer;
+
+/// The release of the CITA images run by docker
+const CITA_RELEASE: &str = "20.2.0";
+/// The name of the chain, its first node lives in `devnet/0`
+const CHAIN_NAME: &str = "devnet";
+/// The name the default key has in the address book
+const BOOK_NAME: &str = "devnet";
+
+/// Developer chain command
+pub fn devnet_command() -> App<'static, 'static> {
+    App::new("devnet")
+        .about("Run a single-node CITA chain for development, by docker or a local binary")
+        .subcommand(
+            SubCommand::with_name("up")
+                .about(
+                    "Start the chain, wait for the first blocks and write the profile with the \
+                     url and the default key, the super admin of the chain",
+                )
+                .arg(
+                    Arg::with_name("binary")
+                        .long("binary")
+                        .takes_value(true)
+                        .help("The `cita` script of a local release, docker is used by default"),
+                )
+                .arg(
+                    Arg::with_name("image")
+                        .long("image")
+                        .takes_value(true)
+                        .conflicts_with("binary")
+                        .help("The docker image, default is the CITA release of the algorithm"),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .default_value("cita-devnet")
+                        .help("The name of the docker container"),
+                )
+                .arg(
+                    Arg::with_name("port")
+                        .long("port")
+                        .takes_value(true)
+                        .default_value("1337")
+                        .validator(|port| {
+                            port.parse::<u16>()
+                                .map(|_| ())
+                                .map_err(|err| err.to_string())
+                        })
+                        .help("The local port of the JSON-RPC of the node"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .validator(|private_key| key_validator(private_key.as_ref()).map(|_| ()))
+                        .help("The default key, a new one is created when absent"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .default_value("120")
+                        .validator(|timeout| parse_u64(timeout.as_str()).map(|_| ()))
+                        .help("Seconds to wait for the node to produce blocks"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("down")
+                .about("Stop the chain of the profile and remove the profile")
+                .arg(
+                    Arg::with_name("keep-data")
+                        .long("keep-data")
+                        .help("Keep the data of the chain run by a local binary"),
+                ),
+        )
+}
+
+/// Developer chain processor
+pub fn devnet_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client.set_debug(debug);
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let result = match sub_matches.subcommand() {
+        ("up", Some(m)) => up(m, config, client)?,
+        ("down", Some(m)) => down(m)?,
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&result, is_color);
+    config.set("result".to_string(), result);
+    Ok(())
+}
+
+/// What runs the chain
+enum Runtime {
+    /// A docker container of the image
+    Docker { image: String, name: String },
+    /// The `cita` script of a release, the chain lives in the directory
+    Binary { path: String, dir: PathBuf },
+}
+
+impl Runtime {
+    /// Create, set up and start the chain of the super admin
+    fn start(&self, admin: &str, port: u16) -> Result<(), String> {
+        let create = format!(
+            "bebop create --super_admin {} --nodes 127.0.0.1:4000 --chain_name {}",
+            admin, CHAIN_NAME
+        );
+        let node = format!("{}/0", CHAIN_NAME);
+        match self {
+            Runtime::Docker { image, name } => {
+                let script = format!(
+                    "cita {} && cita bebop setup {} && cita bebop start {} && sleep infinity",
+                    create, node, node
+                );
+                let ports = format!("{}:1337", port);
+                run(Command::new("docker").args([
+                    "run",
+                    "-d",
+                    "--name",
+                    name,
+                    "-p",
+                    &ports,
+                    image,
+                    "/bin/bash",
+                    "-c",
+                    &script,
+                ]))
+            }
+            Runtime::Binary { path, dir } => {
+                fs::create_dir_all(dir).map_err(|err| format!("{}", err))?;
+                let create = format!("{} --jsonrpc_port {}", create, port);
+                run(binary(path, dir).args(create.split(' ')))?;
+                run(binary(path, dir).args(["bebop", "setup", &node]))?;
+                run(binary(path, dir).args(["bebop", "start", &node]))
+            }
+        }
+    }
+
+    /// Stop the chain, remove its data unless kept
+    fn stop(&self, keep_data: bool) -> Result<(), String> {
+        match self {
+            Runtime::Docker { name, .. } => run(Command::new("docker").args(["rm", "-f", name])),
+            Runtime::Binary { path, dir } => {
+                let node = format!("{}/0", CHAIN_NAME);
+                run(binary(path, dir).args(["bebop", "stop", &node]))?;
+                if !keep_data {
+                    fs::remove_dir_all(dir.join(CHAIN_NAME)).map_err(|err| format!("{}", err))?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn to_value(&self) -> Value {
+        match self {
+            Runtime::Docker { image, name } => {
+                json!({ "runtime": "docker", "image": image, "name": name })
+            }
+            Runtime::Binary { path, dir } => {
+                json!({ "runtime": "binary", "binary": path, "dir": dir })
+            }
+        }
+    }
+
+    fn from_value(profile: &Value) -> Option<Self> {
+        let text = |key: &str| profile[key].as_str().map(str::to_owned);
+        match profile["runtime"].as_str()? {
+            "docker" => Some(Runtime::Docker {
+                image: text("image")?,
+                name: text("name")?,
+            }),
+            "binary" => Some(Runtime::Binary {
+                path: text("binary")?,
+                dir: PathBuf::from(text("dir")?),
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn binary(path: &str, dir: &Path) -> Command {
+    let mut command = Command::new(path);
+    command.current_dir(dir);
+    command
+}
+
+/// Run the command to the end, its output is only shown when it fails
+fn run(command: &mut Command) -> Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|err| format!("Can't run {:?}: {}", command, err))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{:?} exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// The profile of the running chain: `~/.cita-cli/devnet.json`
+fn profile_path() -> PathBuf {
+    cita_cli_dir().join("devnet.json")
+}
+
+fn up(m: &ArgMatches, config: &GlobalConfig, client: Client) -> Result<Value, String> {
+    if profile_path().exists() {
+        return Err(format!(
+            "A devnet is already up, see {}, run `devnet down` first",
+            profile_path().display()
+        ));
+    }
+    let encryption = encryption(m, config);
+    let key_pair = match m.value_of("private-key") {
+        Some(key) => KeyPair::from_privkey(parse_local_privkey(key, encryption)?),
+        None => KeyPair::new(encryption),
+    };
+    let address = key_pair.address().completed_lower_hex_with_0x();
+    let port = m.value_of("port").unwrap().parse::<u16>().unwrap();
+    let runtime = match m.value_of("binary") {
+        Some(path) => Runtime::Binary {
+            path: path.to_owned(),
+            dir: cita_cli_dir().join("devnet"),
+        },
+        None => Runtime::Docker {
+            image: m
+                .value_of("image")
+                .map(str::to_owned)
+                .unwrap_or_else(|| default_image(encryption)),
+            name: m.value_of("name").unwrap().to_owned(),
+        },
+    };
+    runtime.start(&address, port)?;
+
+    let url = format!("http://127.0.0.1:{}", port);
+    let timeout = Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?);
+    let client = client.set_uri(&url);
+    if let Err(err) = wait_for_blocks(&client, timeout) {
+        let _ = runtime.stop(false);
+        return Err(err);
+    }
+
+    let mut profile = runtime.to_value();
+    profile["url"] = json!(url);
+    profile["encryption"] = json!(encryption.to_string());
+    profile["private_key"] = json!(format!("0x{}", key_pair.privkey()));
+    profile["address"] = json!(address);
+    fs::create_dir_all(cita_cli_dir()).map_err(|err| format!("{}", err))?;
+    fs::write(
+        profile_path(),
+        serde_json::to_string_pretty(&profile).unwrap(),
+    )
+    .map_err(|err| format!("{}", err))?;
+    let mut address_book = AddressBook::load();
+    address_book.add(BOOK_NAME, &address);
+    address_book.save().map_err(|err| format!("{}", err))?;
+
+    profile["balance"] = client
+        .get_current_balance(&address)
+        .map(|balance| json!(balance.completed_lower_hex_with_0x()))
+        .unwrap_or(Value::Null);
+    profile["profile"] = json!(profile_path());
+    profile["hint"] = json!(format!(
+        "export JSONRPC_URL={}, the default key is `{}` in the address book",
+        url, BOOK_NAME
+    ));
+    Ok(profile)
+}
+
+fn down(m: &ArgMatches) -> Result<Value, String> {
+    let path = profile_path();
+    let profile: Value = fs::read_to_string(&path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .ok_or_else(|| format!("No devnet is up, {} is missing", path.display()))?;
+    let runtime = Runtime::from_value(&profile)
+        .ok_or_else(|| format!("Invalid profile {}", path.display()))?;
+    runtime.stop(m.is_present("keep-data"))?;
+    fs::remove_file(&path).map_err(|err| format!("{}", err))?;
+    let mut address_book = AddressBook::load();
+    if address_book.get(BOOK_NAME) == profile["address"].as_str() {
+        address_book.remove(BOOK_NAME);
+        address_book.save().map_err(|err| format!("{}", err))?;
+    }
+    Ok(json!({ "stopped": runtime.to_value() }))
+}
+
+/// The image of the CITA release built for the algorithm
+fn default_image(encryption: Encryption) -> String {
+    format!("cita/cita-ce:{}-{}", CITA_RELEASE, encryption)
+}
+
+/// Poll the height until the node produces blocks
+fn wait_for_blocks(client: &Client, timeout: Duration) -> Result<u64, String> {
+    let started = Instant::now();
+    loop {
+        match client.get_current_height() {
+            Ok(height) if height > 0 => return Ok(height),
+            result if started.elapsed() >= timeout => {
+                return Err(match result {
+                    Ok(_) => format!("No block produced in {}s", timeout.as_secs()),
+                    Err(err) => format!("The node isn't ready in {}s: {}", timeout.as_secs(), err),
+                });
+            }
+            _ => thread::sleep(Duration::from_secs(1)),
+        }
+    }
+}
@@ -0,0 +1,495 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use ansi_term::Colour::{Green, Red, Yellow};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::{parse_url, remove_0x, JsonRpcResponse, ToolError};
+
+use crate::cli::chain_command::result_value;
+use crate::cli::{get_url, h256_validator, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// How many latest blocks are compared by default
+const DEFAULT_BLOCKS: u64 = 10;
+
+/// Diff command
+pub fn diff_command() -> App<'static, 'static> {
+    App::new("diff")
+        .about("Compare the chain data field by field")
+        .subcommand(
+            SubCommand::with_name("endpoints")
+                .about(
+                    "Run the same read queries against two endpoints and report the divergent \
+                     fields, to validate a node upgrade or a resynced replica",
+                )
+                .arg(
+                    Arg::with_name("left")
+                        .long("left")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                        .help("The first endpoint"),
+                )
+                .arg(
+                    Arg::with_name("right")
+                        .long("right")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                        .help("The second endpoint"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("The first block to compare, default is 10 blocks before the end"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("The last block to compare, default is the lower height of the two"),
+                )
+                .arg(
+                    Arg::with_name("receipts")
+                        .long("receipts")
+                        .help("Also compare the receipts of the transactions in the blocks"),
+                )
+                .arg(
+                    Arg::with_name("calls")
+                        .long("calls")
+                        .takes_value(true)
+                        .help(
+                            "JSON file of the call probes, a list of {\"to\", \"data\", \
+                             \"from\", \"height\"}, the height defaults to the last block",
+                        ),
+                ),
+        )
+        .subcommand(
+            object_command("receipt")
+                .about(
+                    "Align the fields of two transaction receipts and highlight the \
+                     differences",
+                )
+                .arg(
+                    Arg::with_name("left")
+                        .index(1)
+                        .required(true)
+                        .validator(|hash| h256_validator(hash.as_str()))
+                        .help("The hash of the first transaction"),
+                )
+                .arg(
+                    Arg::with_name("right")
+                        .index(2)
+                        .required(true)
+                        .validator(|hash| h256_validator(hash.as_str()))
+                        .help("The hash of the second transaction"),
+                ),
+        )
+        .subcommand(
+            object_command("block")
+                .about("Align the fields of two blocks and highlight the differences")
+                .arg(
+                    Arg::with_name("left")
+                        .index(1)
+                        .required(true)
+                        .validator(|block| block_validator(block.as_str()))
+                        .help("The height or the hash of the first block"),
+                )
+                .arg(
+                    Arg::with_name("right")
+                        .index(2)
+                        .required(true)
+                        .validator(|block| block_validator(block.as_str()))
+                        .help("The height or the hash of the second block"),
+                ),
+        )
+}
+
+/// The args shared by the diffs of two fetched objects
+fn object_command(name: &str) -> App<'static, 'static> {
+    SubCommand::with_name(name)
+        .arg(
+            Arg::with_name("right-url")
+                .long("right-url")
+                .takes_value(true)
+                .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                .help("Fetch the second one from this endpoint, default is the url"),
+        )
+        .arg(
+            Arg::with_name("ignore")
+                .long("ignore")
+                .takes_value(true)
+                .multiple(true)
+                .number_of_values(1)
+                .help("Skip the field and what's under it, such as `blockHash` or `logs`"),
+        )
+        .arg(
+            Arg::with_name("all")
+                .long("all")
+                .help("Also show the equal fields"),
+        )
+        .arg(
+            Arg::with_name("json")
+                .long("json")
+                .help("Print the differences as json instead of the aligned fields"),
+        )
+}
+
+/// A block height, decimal or hex, or a block hash
+fn block_validator(block: &str) -> Result<(), String> {
+    if remove_0x(block).len() == 64 {
+        h256_validator(block)
+    } else {
+        parse_u64(block).map(|_| ())
+    }
+}
+
+/// Diff processor
+pub fn diff_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let client = client.set_debug(debug);
+
+    let report = match sub_matches.subcommand() {
+        ("receipt", Some(m)) | ("block", Some(m)) => {
+            let left_client = client.set_uri(get_url(sub_matches, config));
+            let right_client = match m.value_of("right-url") {
+                Some(url) => left_client.clone().set_uri(url),
+                None => left_client.clone(),
+            };
+            let (left, right) = (m.value_of("left").unwrap(), m.value_of("right").unwrap());
+            let fetch = |client: &Client, id: &str| -> Result<Value, String> {
+                let response = match sub_matches.subcommand_name() {
+                    Some("receipt") => client.get_transaction_receipt(id),
+                    _ if remove_0x(id).len() == 64 => client.get_block_by_hash(id, false),
+                    _ => client.get_block_by_number(&format!("{:#x}", parse_u64(id)?), false),
+                };
+                match response.and_then(result_value) {
+                    Ok(Value::Null) => Err(format!("{} is not found", id)),
+                    Ok(value) => Ok(value),
+                    Err(err) => Err(format!("{}", err)),
+                }
+            };
+            let (left_value, right_value) =
+                (fetch(&left_client, left)?, fetch(&right_client, right)?);
+            let ignored: Vec<&str> = m.values_of("ignore").into_iter().flatten().collect();
+            let is_ignored = |path: &str| {
+                ignored.iter().any(|ignore| {
+                    path == *ignore
+                        || path.starts_with(&format!("{}.", ignore))
+                        || path.starts_with(&format!("{}[", ignore))
+                })
+            };
+            let differences: Vec<Value> = diff_values(&left_value, &right_value)
+                .into_iter()
+                .filter(|difference| !is_ignored(difference["path"].as_str().unwrap_or_default()))
+                .collect();
+            let report = json!({
+                "left": left,
+                "right": right,
+                "differences": differences,
+            });
+            if m.is_present("json") {
+                printer.println(&report, is_color);
+            } else {
+                let fields = aligned_fields(&left_value, &right_value, m.is_present("all"));
+                let fields: Vec<_> = fields
+                    .into_iter()
+                    .filter(|(path, _, _)| !is_ignored(path))
+                    .collect();
+                printer.println(&render_fields(&fields, is_color), false);
+            }
+            config.set("result".to_string(), report);
+            return Ok(());
+        }
+        ("endpoints", Some(m)) => {
+            let left = client.clone().set_uri(m.value_of("left").unwrap());
+            let right = client.set_uri(m.value_of("right").unwrap());
+            diff_endpoints(m, &left, &right)?
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&report, is_color);
+    let divergent = report["divergent"].as_u64().unwrap_or_default();
+    config.set("result".to_string(), report);
+    if divergent > 0 {
+        return Err(format!("{} queries diverged", divergent));
+    }
+    Ok(())
+}
+
+fn diff_endpoints(m: &ArgMatches, left: &Client, right: &Client) -> Result<Value, String> {
+    let to = match m.value_of("to") {
+        Some(to) => parse_u64(to)?,
+        None => left
+            .get_current_height()
+            .map_err(|err| format!("{}", err))?
+            .min(
+                right
+                    .get_current_height()
+                    .map_err(|err| format!("{}", err))?,
+            ),
+    };
+    let from = match m.value_of("from") {
+        Some(from) => parse_u64(from)?,
+        None => to.saturating_sub(DEFAULT_BLOCKS - 1),
+    };
+    let calls: Vec<Value> = match m.value_of("calls") {
+        Some(path) => {
+            let content =
+                fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+            serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))?
+        }
+        None => Vec::new(),
+    };
+
+    let mut compared = 0;
+    let mut divergences = Vec::new();
+    let mut compare = |query: String, run: &dyn Fn(&Client) -> Value| -> Value {
+        let (left, right) = (run(left), run(right));
+        compared += 1;
+        let differences = diff_values(&left, &right);
+        if !differences.is_empty() {
+            divergences.push(json!({ "query": query, "differences": differences }));
+        }
+        left
+    };
+
+    for height in from..=to {
+        let height = format!("{:#x}", height);
+        let block = compare(format!("getBlockByNumber {}", height), &|client| {
+            query(client.get_block_by_number(&height, true))
+        });
+        if !m.is_present("receipts") {
+            continue;
+        }
+        let hashes = block["body"]["transactions"]
+            .as_array()
+            .map(|transactions| {
+                transactions
+                    .iter()
+                    .filter_map(|tx| tx["hash"].as_str().map(ToOwned::to_owned))
+                    .collect::<Vec<String>>()
+            })
+            .unwrap_or_default();
+        for hash in hashes {
+            compare(format!("getTransactionReceipt {}", hash), &|client| {
+                query(client.get_transaction_receipt(&hash))
+            });
+        }
+    }
+    for call in &calls {
+        let to_address = call["to"].as_str().unwrap_or_default();
+        let data = call["data"].as_str();
+        let sender = call["from"].as_str();
+        let height = call["height"]
+            .as_str()
+            .map(ToOwned::to_owned)
+            .unwrap_or_else(|| format!("{:#x}", to));
+        compare(
+            format!("call {} {} at {}", to_address, data.unwrap_or("0x"), height),
+            &|client| query(client.call(sender, to_address, data, &height)),
+        );
+    }
+
+    Ok(json!({
+        "from": from,
+        "to": to,
+        "compared": compared,
+        "divergent": divergences.len(),
+        "divergences": divergences,
+    }))
+}
+
+/// The result of the response, or its error, so an error differs from a result
+fn query(response: Result<JsonRpcResponse, ToolError>) -> Value {
+    match response.and_then(result_value) {
+        Ok(result) => result,
+        Err(err) => json!({ "error": format!("{}", err) }),
+    }
+}
+
+/// The differing fields of the values, as `{"path", "left", "right"}`,
+/// a field absent on one side is null there
+pub(crate) fn diff_values(left: &Value, right: &Value) -> Vec<Value> {
+    let mut differences = Vec::new();
+    diff_at("", left, right, &mut differences);
+    differences
+}
+
+/// The leaf fields of the two values side by side, in the order of the paths,
+/// only the differing ones unless `all`
+fn aligned_fields(left: &Value, right: &Value, all: bool) -> Vec<(String, Value, Value)> {
+    let (mut left_leaves, mut right_leaves) = (BTreeMap::new(), BTreeMap::new());
+    leaves("", left, &mut left_leaves);
+    leaves("", right, &mut right_leaves);
+    let paths: BTreeSet<&String> = left_leaves.keys().chain(right_leaves.keys()).collect();
+    paths
+        .into_iter()
+        .map(|path| {
+            (
+                path.to_owned(),
+                left_leaves.get(path).cloned().unwrap_or(Value::Null),
+                right_leaves.get(path).cloned().unwrap_or(Value::Null),
+            )
+        })
+        .filter(|(_, left, right)| all || left != right)
+        .collect()
+}
+
+fn leaves(path: &str, value: &Value, leaves_found: &mut BTreeMap<String, Value>) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                let path = if path.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                leaves(&path, value, leaves_found);
+            }
+        }
+        Value::Array(list) if !list.is_empty() => {
+            for (index, value) in list.iter().enumerate() {
+                leaves(&format!("{}[{}]", path, index), value, leaves_found);
+            }
+        }
+        _ => {
+            leaves_found.insert(path.to_owned(), value.clone());
+        }
+    }
+}
+
+/// One line per field, `path  left  right`, the differing values in red and green
+fn render_fields(fields: &[(String, Value, Value)], color: bool) -> String {
+    if fields.is_empty() {
+        return "No differences".to_owned();
+    }
+    let show = |value: &Value| match value {
+        Value::String(text) => text.to_owned(),
+        other => other.to_string(),
+    };
+    let path_width = fields
+        .iter()
+        .map(|(path, _, _)| path.len())
+        .max()
+        .unwrap_or(0);
+    let left_width = fields
+        .iter()
+        .map(|(_, left, _)| show(left).len())
+        .max()
+        .unwrap_or(0);
+    fields
+        .iter()
+        .map(|(path, left, right)| {
+            let (left_text, right_text) = (show(left), show(right));
+            let line = format!(
+                "{:path_width$}  {:left_width$}  {}",
+                path,
+                left_text,
+                right_text,
+                path_width = path_width,
+                left_width = left_width
+            );
+            match (left == right, color) {
+                (true, _) | (false, false) => line,
+                (false, true) => format!(
+                    "{}  {}  {}",
+                    Yellow.paint(format!("{:1$}", path, path_width)),
+                    Red.paint(format!("{:1$}", left_text, left_width)),
+                    Green.paint(right_text)
+                ),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn diff_at(path: &str, left: &Value, right: &Value, differences: &mut Vec<Value>) {
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let keys: BTreeSet<&String> = left.keys().chain(right.keys()).collect();
+            for key in keys {
+                let path = if path.is_empty() {
+                    key.to_owned()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                diff_at(
+                    &path,
+                    left.get(key).unwrap_or(&Value::Null),
+                    right.get(key).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                diff_at(
+                    &format!("{}[{}]", path, index),
+                    left.get(index).unwrap_or(&Value::Null),
+                    right.get(index).unwrap_or(&Value::Null),
+                    differences,
+                );
+            }
+        }
+        _ if left != right => {
+            differences.push(json!({ "path": path, "left": left, "right": right }));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::{aligned_fields, diff_values};
+
+    #[test]
+    fn test_diff_values() {
+        let left = json!({
+            "header": { "number": "0x1", "stateRoot": "0xaa" },
+            "transactions": ["0x01", "0x02"],
+        });
+        let right = json!({
+            "header": { "number": "0x1", "stateRoot": "0xbb", "proof": {} },
+            "transactions": ["0x01"],
+        });
+        assert_eq!(
+            diff_values(&left, &right),
+            vec![
+                json!({ "path": "header.proof", "left": null, "right": {} }),
+                json!({ "path": "header.stateRoot", "left": "0xaa", "right": "0xbb" }),
+                json!({ "path": "transactions[1]", "left": "0x02", "right": null }),
+            ]
+        );
+        assert!(diff_values(&left, &left).is_empty());
+
+        let fields = aligned_fields(&left, &right, true);
+        let paths: Vec<&str> = fields.iter().map(|(path, _, _)| path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "header.number",
+                "header.proof",
+                "header.stateRoot",
+                "transactions[0]",
+                "transactions[1]",
+            ]
+        );
+        assert_eq!(aligned_fields(&left, &right, false).len(), 3);
+    }
+}
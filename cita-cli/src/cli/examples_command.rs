@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use clap::{App, Arg, ArgMatches};
+
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// The key of the super admin of a chain created by the CITA tools for the development
+const ADMIN_KEY: &str = "0x5f0258a4778057a8a7d97809bd209055b2fbafa654ce7d31ec7191066b9225e6";
+/// The address of the key
+const ADMIN_ADDRESS: &str = "0x4b5ae4567ad5d9fb92bc9afd6a657e6fa13a2523";
+
+/// A command of a topic, the tests parse every one by the parser of the commands
+pub struct Example {
+    pub topic: &'static str,
+    pub about: &'static str,
+    /// The args after `cita-cli`, `{key}` and `{address}` are the super admin of a development
+    /// chain
+    pub command: &'static str,
+}
+
+pub const EXAMPLES: &[Example] = &[
+    Example {
+        topic: "key",
+        about: "Create a key pair and keep it in an encrypted key file",
+        command: "key create --output alice.json --password secret",
+    },
+    Example {
+        topic: "key",
+        about: "The public key and the address of a private key",
+        command: "key from-private --private-key {key}",
+    },
+    Example {
+        topic: "query",
+        about: "The latest height",
+        command: "rpc blockNumber --url http://127.0.0.1:1337",
+    },
+    Example {
+        topic: "query",
+        about: "The latest block with its transactions",
+        command: "rpc getBlockByNumber --height latest --with-txs",
+    },
+    Example {
+        topic: "query",
+        about: "The balance of an account",
+        command: "rpc getBalance --address {address}",
+    },
+    Example {
+        topic: "transfer",
+        about: "Transfer 100 to an address",
+        command: "transfer --address 0xffffffffffffffffffffffffffffffffffffffff --value 100 \
+                  --private-key {key}",
+    },
+    Example {
+        topic: "transfer",
+        about: "Wait for the receipt of the transaction sent, 3 blocks deep",
+        command: "tx wait-receipt --hash 0x{hash} --confirmations 3",
+    },
+    Example {
+        topic: "deploy",
+        about: "Deploy a contract by its creation code, recorded under a name",
+        command: "contract deploy --code 0x6080604052 --name token --private-key {key}",
+    },
+    Example {
+        topic: "deploy",
+        about: "Call a function of the contract",
+        command:
+            "contract call --abi token.abi --address 0xffffffffffffffffffffffffffffffffff020004 \
+                  --function balanceOf --args {address}",
+    },
+    Example {
+        topic: "permission",
+        about: "Create a permission of a function of a contract",
+        command: "scm PermissionManagement newPermission --name transfer-only \
+                  --contracts 0xffffffffffffffffffffffffffffffffff020004 \
+                  --function-hashes 0xa9059cbb --private-key {key}",
+    },
+    Example {
+        topic: "permission",
+        about: "Grant the permission to an account",
+        command: "scm PermissionManagement setAuthorization --account {address} \
+                  --permission 0xffffffffffffffffffffffffffffffffff021000 --private-key {key}",
+    },
+    Example {
+        topic: "permission",
+        about: "The permissions of an account",
+        command: "scm Authorization queryPermissions --account {address}",
+    },
+    Example {
+        topic: "events",
+        about: "Watch the logs of a contract, decoded by its ABI",
+        command:
+            "watch events --address 0xffffffffffffffffffffffffffffffffff020004 --abi token.abi",
+    },
+    Example {
+        topic: "admin",
+        about: "The emergency brake state and the protocol version",
+        command: "admin chain status",
+    },
+    Example {
+        topic: "admin",
+        about: "Set the emergency brake, without the confirmation",
+        command: "admin chain brake --admin-private {key} --yes",
+    },
+    Example {
+        topic: "audit",
+        about: "The transactions the nodes rejected",
+        command: "audit show --failed",
+    },
+    Example {
+        topic: "audit",
+        about: "Export the audit log for the accounting",
+        command: "audit export --format csv --output audit.csv",
+    },
+];
+
+impl Example {
+    /// The args after `cita-cli`
+    pub fn args(&self) -> String {
+        self.command
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .replace("{key}", ADMIN_KEY)
+            .replace("{address}", ADMIN_ADDRESS)
+            .replace("{hash}", &"0".repeat(64))
+    }
+
+    /// The first subcommand, the help of which shows the example
+    fn command_name(&self) -> &'static str {
+        self.command.split(' ').next().unwrap_or_default()
+    }
+
+    /// The example as the help shows it, the long ones broken at the flags by `\` so that
+    /// the wrapping of the help doesn't break them
+    fn text(&self) -> String {
+        let mut lines = vec![String::from("cita-cli")];
+        for word in self.args().split(' ') {
+            let line = lines.last_mut().unwrap();
+            if word.starts_with("--") && line.len() + word.len() > 40 {
+                line.push_str(" \\");
+                lines.push(format!("    {}", word));
+            } else {
+                line.push(' ');
+                line.push_str(word);
+            }
+        }
+        format!("    # {}\n    {}\n", self.about, lines.join("\n    "))
+    }
+}
+
+fn topics() -> Vec<&'static str> {
+    let mut topics = EXAMPLES
+        .iter()
+        .map(|example| example.topic)
+        .collect::<Vec<&str>>();
+    topics.dedup();
+    topics
+}
+
+/// The examples at the end of the help of a subcommand, None if it has none
+pub fn examples_help(command: &str) -> Option<&'static str> {
+    static HELPS: OnceLock<BTreeMap<&'static str, String>> = OnceLock::new();
+    HELPS
+        .get_or_init(|| {
+            let mut helps = BTreeMap::new();
+            for example in EXAMPLES {
+                helps
+                    .entry(example.command_name())
+                    .or_insert_with(|| "EXAMPLES:\n".to_owned())
+                    .push_str(&example.text());
+            }
+            helps
+        })
+        .get(command)
+        .map(String::as_str)
+}
+
+/// Show the examples of the subcommand at the end of its help
+pub fn with_examples<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    match examples_help(subcommand.get_name()) {
+        Some(help) => subcommand.after_help(help),
+        None => subcommand,
+    }
+}
+
+/// Examples command
+pub fn examples_command() -> App<'static, 'static> {
+    App::new("examples")
+        .about("Runnable examples of the commands by topic, the help of a command has its examples")
+        .arg(
+            Arg::with_name("topic")
+                .possible_values(&topics())
+                .help("The topic, all the topics if absent"),
+        )
+}
+
+/// Examples processor
+pub fn examples_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    _config: &mut GlobalConfig,
+) -> Result<(), String> {
+    let topic = sub_matches.value_of("topic");
+    let mut text = Vec::new();
+    for current in topics()
+        .into_iter()
+        .filter(|current| topic.is_none_or(|topic| topic == *current))
+    {
+        text.push(format!("{}:", current));
+        text.extend(
+            EXAMPLES
+                .iter()
+                .filter(|example| example.topic == current)
+                .map(Example::text),
+        );
+    }
+    printer.println(&text.join("\n"), false);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::EXAMPLES;
+    use crate::cli::build_cli;
+    use crate::plugin;
+
+    #[test]
+    fn test_examples_parse() {
+        let parser = build_cli("test");
+        for example in EXAMPLES {
+            let args = shell_words::split(&format!("cita-cli {}", example.args())).unwrap();
+            if let Err(err) = parser.clone().get_matches_from_safe(&args) {
+                panic!("The example `{}` doesn't parse: {}", example.command, err);
+            }
+            // In the help of its subcommand
+            let help = parser
+                .clone()
+                .get_matches_from_safe([&args[0], &args[1], "--help"])
+                .unwrap_err();
+            assert!(
+                help.message.contains(example.about),
+                "The help of `{}` hasn't the example `{}`",
+                args[1],
+                example.command
+            );
+            // Not taken for a plugin
+            assert_eq!(plugin::external(&parser, &args), None);
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::fs;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+
+use crate::checkpoint::{checkpoint_args, Checkpointer};
+use crate::cli::chain_command::result_value;
+use crate::cli::time_command::{block_range, time_range_args};
+use crate::cli::watch_command::decode_log;
+use crate::cli::{get_url, h256_validator, parse_address, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+use crate::sink::{open_sink, sink_args, RecordKind};
+
+/// How many blocks are exported in a batch, the checkpoint is saved after each one
+const EXPORT_BATCH: u64 = 100;
+
+/// Export the chain history subcommand
+pub fn export_command() -> App<'static, 'static> {
+    let from = Arg::with_name("from")
+        .long("from")
+        .required_unless("from-time")
+        .takes_value(true)
+        .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+        .help("The first block height, hex string or number");
+    let to = Arg::with_name("to")
+        .long("to")
+        .takes_value(true)
+        .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+        .help("The last block height, default is the current height");
+
+    App::new("export")
+        .about("Export the blocks or the logs of a block range, bounded by heights or dates")
+        .subcommand(
+            SubCommand::with_name("blocks")
+                .about("Export the height, hash, timestamp and transactions of every block")
+                .arg(from.clone())
+                .arg(to.clone())
+                .args(&time_range_args())
+                .args(&sink_args())
+                .args(&checkpoint_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("logs")
+                .about("Export the logs of the blocks, decoded by the ABI when given")
+                .arg(from)
+                .arg(to)
+                .args(&time_range_args())
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Only export the logs emitted by these contract addresses"),
+                )
+                .arg(
+                    Arg::with_name("topic")
+                        .long("topic")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(|topic| h256_validator(topic.as_str()))
+                        .help("Only export the logs matching these topics"),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .help("The path of the ABI json file used to decode the logs"),
+                )
+                .args(&sink_args())
+                .args(&checkpoint_args()),
+        )
+}
+
+/// Export the chain history processor
+pub fn export_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let (m, kind) = match sub_matches.subcommand() {
+        ("blocks", Some(m)) => (m, RecordKind::Block),
+        ("logs", Some(m)) => (m, RecordKind::Log),
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    let (from, to) = block_range(m, &client)?;
+    let abi = match m.value_of("abi") {
+        Some(path) => Some(fs::read_to_string(path).map_err(|err| format!("{}", err))?),
+        None => None,
+    };
+    let topics: Option<Vec<&str>> = m.values_of("topic").map(Iterator::collect);
+    let addresses: Option<Vec<&str>> = m.values_of("address").map(Iterator::collect);
+    let scan = match kind {
+        RecordKind::Block => "export blocks".to_string(),
+        _ => format!(
+            "export logs {}",
+            json!({ "address": addresses, "topic": topics })
+        ),
+    };
+
+    let checkpointer = Checkpointer::open(m, &scan)?;
+    let mut records = checkpointer
+        .resumed()
+        .and_then(|checkpoint| checkpoint.state["records"].as_u64())
+        .unwrap_or_default();
+    let mut sink = open_sink(m, printer, is_color, &client)?;
+    let mut start = checkpointer.next_height().unwrap_or(from);
+    while start <= to {
+        let end = to.min(start + EXPORT_BATCH - 1);
+        let batch = match kind {
+            RecordKind::Block => (start..=end)
+                .map(|height| export_block(&client, height))
+                .collect::<Result<Vec<Value>, String>>()?,
+            _ => export_logs(&client, &topics, &addresses, start, end)?
+                .into_iter()
+                .map(|log| decode_log(log, abi.as_deref()))
+                .collect(),
+        };
+        sink.write_all(kind, &batch)?;
+        records += batch.len() as u64;
+        start = end + 1;
+        checkpointer.save(start, json!({ "records": records }))?;
+    }
+
+    if let Some(output) = m.value_of("output") {
+        let summary = json!({ "output": output, "records": records });
+        printer.println(&summary, is_color);
+        config.set("result".to_string(), summary);
+    }
+    Ok(())
+}
+
+fn export_logs(
+    client: &Client,
+    topics: &Option<Vec<&str>>,
+    addresses: &Option<Vec<&str>>,
+    from: u64,
+    to: u64,
+) -> Result<Vec<Value>, String> {
+    let response = client
+        .get_logs(
+            topics.clone(),
+            addresses.clone(),
+            Some(&format!("{:#x}", from)),
+            Some(&format!("{:#x}", to)),
+        )
+        .map_err(|err| format!("{}", err))?;
+    match result_value(response).map_err(|err| format!("{}", err))? {
+        Value::Array(logs) => Ok(logs),
+        _ => Ok(Vec::new()),
+    }
+}
+
+fn export_block(client: &Client, height: u64) -> Result<Value, String> {
+    let block = client
+        .get_block_by_number(&format!("{:#x}", height), true)
+        .map_err(|err| format!("{}", err))
+        .and_then(|response| result_value(response).map_err(|err| format!("{}", err)))?;
+    Ok(json!({
+        "height": height,
+        "hash": block["hash"],
+        "timestamp": block["header"]["timestamp"],
+        "proposer": block["header"]["proposer"],
+        "quotaUsed": block["header"]["quotaUsed"],
+        "transactions": block["body"]["transactions"],
+    }))
+}
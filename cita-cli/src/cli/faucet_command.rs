@@ -0,0 +1,288 @@
+use std::fs;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg, ArgMatches};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::system_contract::{QuotaManageClient, QuotaManagementExt};
+use cita_tool::{
+    remove_0x, Address, JsonRpcParams, JsonRpcResponse, LowerHex, ParamsValue, ToolError,
+    TransactionOptions, U256,
+};
+
+use crate::cli::chain_command::result_value;
+use crate::cli::{encryption, get_url, key_validator, parse_privkey, parse_u256, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Faucet command
+pub fn faucet_command() -> App<'static, 'static> {
+    App::new("faucet")
+        .about(
+            "Fund a list of addresses from a funder key, wait for the receipts \
+             and report which grants succeeded",
+        )
+        .arg(
+            Arg::with_name("addresses")
+                .long("addresses")
+                .takes_value(true)
+                .required(true)
+                .help(
+                    "File of the addresses, one per line with `#` comments, \
+                     or a JSON list such as a key file",
+                ),
+        )
+        .arg(
+            Arg::with_name("private-key")
+                .long("private-key")
+                .takes_value(true)
+                .required(true)
+                .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                .help("The private key of the funder"),
+        )
+        .arg(
+            Arg::with_name("value")
+                .long("value")
+                .takes_value(true)
+                .validator(|value| parse_u256(value.as_ref()).map(|_| ()))
+                .help("The balance to transfer to every address"),
+        )
+        .arg(
+            Arg::with_name("aql")
+                .long("aql")
+                .takes_value(true)
+                .validator(|aql| parse_u256(aql.as_ref()).map(|_| ()))
+                .help("Set the account quota limit of every address, the funder must be admin"),
+        )
+        .arg(
+            Arg::with_name("quota")
+                .long("quota")
+                .takes_value(true)
+                .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                .help("Transaction quota costs, default 10_000_000"),
+        )
+        .arg(
+            Arg::with_name("batch")
+                .long("batch")
+                .takes_value(true)
+                .default_value("100")
+                .validator(|batch| parse_u64(batch.as_ref()).map(|_| ()))
+                .help("Number of transactions sent in one request"),
+        )
+        .arg(
+            Arg::with_name("timeout")
+                .long("timeout")
+                .takes_value(true)
+                .default_value("120")
+                .validator(|timeout| parse_u64(timeout.as_ref()).map(|_| ()))
+                .help("Seconds to wait for the receipts"),
+        )
+}
+
+/// Faucet processor
+pub fn faucet_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let mut client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let value = sub_matches
+        .value_of("value")
+        .map(|value| parse_u256(value).unwrap());
+    let aql = sub_matches
+        .value_of("aql")
+        .map(|aql| parse_u256(aql).unwrap());
+    if value.is_none() && aql.is_none() {
+        return Err("Nothing to grant, set --value or --aql".to_string());
+    }
+    client.set_private_key(&parse_privkey(
+        sub_matches.value_of("private-key").unwrap(),
+        encryption(sub_matches, config),
+    )?);
+    let addresses = read_addresses(sub_matches.value_of("addresses").unwrap())?;
+    let quota = sub_matches
+        .value_of("quota")
+        .map(|quota| parse_u64(quota).unwrap());
+    let batch = parse_u64(sub_matches.value_of("batch").unwrap())?.max(1) as usize;
+    let timeout = Duration::from_secs(parse_u64(sub_matches.value_of("timeout").unwrap())?);
+
+    let mut grants = Vec::new();
+    if let Some(value) = value {
+        grants.extend(
+            send_transfers(&mut client, &addresses, value, quota, batch)
+                .map_err(|err| format!("{}", err))?,
+        );
+    }
+    if let Some(aql) = aql {
+        let quota_manager: QuotaManageClient<Client> = QuotaManagementExt::create(client.clone());
+        for address in &addresses {
+            let response = quota_manager
+                .set_aql(address, aql, quota)
+                .map_err(|err| format!("{}", err))?;
+            grants.push(grant(address, "aql", response));
+        }
+    }
+    wait_receipts(&client, &mut grants, timeout).map_err(|err| format!("{}", err))?;
+
+    let count = |status: &str| grants.iter().filter(|g| g["status"] == status).count();
+    let report = json!({
+        "addresses": addresses.len(),
+        "success": count("success"),
+        "failed": count("failed"),
+        "rejected": count("rejected"),
+        "timeout": count("timeout"),
+        "grants": grants,
+    });
+    printer.println(&report, is_color);
+    config.set("result".to_string(), report);
+    Ok(())
+}
+
+/// The addresses in the file, one per line or a JSON list of addresses or of objects
+/// with an `address` field
+fn read_addresses(path: &str) -> Result<Vec<String>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let addresses: Vec<String> = if content.trim_start().starts_with('[') {
+        let list: Vec<Value> = serde_json::from_str(&content).map_err(|err| err.to_string())?;
+        list.iter()
+            .map(|item| {
+                item.as_str()
+                    .or_else(|| item["address"].as_str())
+                    .unwrap_or_default()
+                    .to_owned()
+            })
+            .collect()
+    } else {
+        content
+            .lines()
+            .map(|line| line.split('#').next().unwrap().trim())
+            .filter(|line| !line.is_empty())
+            .map(str::to_owned)
+            .collect()
+    };
+    addresses
+        .into_iter()
+        .map(|address| {
+            Address::from_str(remove_0x(&address))
+                .map(|parsed| parsed.completed_lower_hex_with_0x())
+                .map_err(|_| format!("Invalid address in {}: {}", path, address))
+        })
+        .collect()
+}
+
+/// Sign a transfer to every address and send them in checked batches
+fn send_transfers(
+    client: &mut Client,
+    addresses: &[String],
+    value: U256,
+    quota: Option<u64>,
+    batch: usize,
+) -> Result<Vec<Value>, ToolError> {
+    let current_height = client.get_current_height()?;
+    let mut grants = Vec::with_capacity(addresses.len());
+    for chunk in addresses.chunks(batch) {
+        let mut txs = Vec::with_capacity(chunk.len());
+        for address in chunk {
+            let tx_options = TransactionOptions::new()
+                .set_address(address)
+                .set_current_height(Some(current_height))
+                .set_quota(quota)
+                .set_value(Some(value));
+            let tx = client.generate_transaction(tx_options)?;
+            txs.push(client.generate_sign_transaction(&tx)?);
+        }
+        // The checks of every sending command, the duplicates, the chain, the limits,
+        // the fee and the policy, before any transfer of the batch goes out
+        let responses =
+            client.send_signed_transactions(&txs.iter().map(String::as_str).collect::<Vec<_>>())?;
+        grants.extend(
+            chunk
+                .iter()
+                .zip(responses)
+                .map(|(address, response)| grant(address, "value", response)),
+        );
+    }
+    Ok(grants)
+}
+
+pub(crate) fn grant(address: &str, kind: &str, response: JsonRpcResponse) -> Value {
+    match result_value(response) {
+        Ok(result) => json!({
+            "address": address,
+            "grant": kind,
+            "hash": result["hash"],
+        }),
+        Err(err) => json!({
+            "address": address,
+            "grant": kind,
+            "status": "rejected",
+            "error": err.to_string(),
+        }),
+    }
+}
+
+/// Poll the receipts of the sent grants until all are committed or the timeout
+pub(crate) fn wait_receipts(
+    client: &Client,
+    grants: &mut [Value],
+    timeout: Duration,
+) -> Result<(), ToolError> {
+    let start = Instant::now();
+    loop {
+        let pending: Vec<usize> = (0..grants.len())
+            .filter(|index| grants[*index]["status"].is_null())
+            .collect();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        if start.elapsed() >= timeout {
+            for index in pending {
+                grants[index]["status"] = json!("timeout");
+            }
+            return Ok(());
+        }
+
+        let params = pending.iter().map(|index| {
+            JsonRpcParams::new()
+                .insert(
+                    "method",
+                    ParamsValue::String("getTransactionReceipt".to_string()),
+                )
+                .insert(
+                    "params",
+                    ParamsValue::List(vec![ParamsValue::String(
+                        grants[*index]["hash"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_owned(),
+                    )]),
+                )
+        });
+        let receipts = client.send_request(params.collect::<Vec<_>>().into_iter())?;
+        for (index, receipt) in pending.into_iter().zip(receipts) {
+            let receipt = match result_value(receipt) {
+                Ok(receipt) if !receipt.is_null() => receipt,
+                _ => continue,
+            };
+            let grant = &mut grants[index];
+            grant["blockNumber"] = receipt["blockNumber"].clone();
+            if receipt["errorMessage"].is_null() {
+                grant["status"] = json!("success");
+            } else {
+                grant["status"] = json!("failed");
+                grant["error"] = receipt["errorMessage"].clone();
+            }
+        }
+        thread::sleep(Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,107 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+
+use crate::cli::{parse_address, parse_height};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// gRPC command, talk to the CITA components directly
+pub fn grpc_command() -> App<'static, 'static> {
+    let height_arg = Arg::with_name("height")
+        .long("height")
+        .takes_value(true)
+        .validator(|s| parse_height(s.as_str()))
+        .default_value("latest")
+        .help("The block number");
+    App::new("grpc")
+        .about(
+            "Query the controller and the executor components by gRPC, without the JSON-RPC \
+             gateway (needs the grpc feature)",
+        )
+        .arg(
+            Arg::with_name("controller")
+                .long("controller")
+                .takes_value(true)
+                .default_value("http://127.0.0.1:50004")
+                .help("gRPC url of the controller"),
+        )
+        .arg(
+            Arg::with_name("executor")
+                .long("executor")
+                .takes_value(true)
+                .default_value("http://127.0.0.1:50002")
+                .help("gRPC url of the executor"),
+        )
+        .subcommand(SubCommand::with_name("blockNumber").about("Get current height"))
+        .subcommand(SubCommand::with_name("peerCount").about("Get network peer count"))
+        .subcommand(SubCommand::with_name("getVersion").about("Get the software version"))
+        .subcommand(
+            SubCommand::with_name("call")
+                .about("Call a contract function by the executor")
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .validator(|address| parse_address(address.as_str()))
+                        .takes_value(true)
+                        .help("From address"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .validator(|address| parse_address(address.as_str()))
+                        .takes_value(true)
+                        .required(true)
+                        .help("To address"),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .long("data")
+                        .takes_value(true)
+                        .help("The data"),
+                )
+                .arg(height_arg),
+        )
+}
+
+/// gRPC processor
+#[cfg(feature = "grpc")]
+pub fn grpc_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+) -> Result<(), String> {
+    use cita_tool::client::basic::ClientExt;
+    use cita_tool::client::grpc::GrpcClient;
+
+    use crate::interactive::set_output;
+
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let client = GrpcClient::new(sub_matches.value_of("controller").unwrap())
+        .and_then(|client| client.set_executor(sub_matches.value_of("executor").unwrap()))
+        .map_err(|err| format!("{}", err))?;
+    let response = match sub_matches.subcommand() {
+        ("blockNumber", _) => client.get_block_number(),
+        ("peerCount", _) => client.get_peer_count(),
+        ("getVersion", _) => client.get_version(),
+        ("call", Some(m)) => client.call(
+            m.value_of("from"),
+            m.value_of("to").unwrap(),
+            m.value_of("data"),
+            m.value_of("height").unwrap(),
+        ),
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    .map_err(|err| format!("{}", err))?;
+    printer.println(&response, is_color);
+    set_output(&response, config);
+    Ok(())
+}
+
+/// gRPC processor
+#[cfg(not(feature = "grpc"))]
+pub fn grpc_processor(
+    _sub_matches: &ArgMatches,
+    _printer: &Printer,
+    _config: &mut GlobalConfig,
+) -> Result<(), String> {
+    Err("cita-cli is built without the grpc feature, rebuild it by `--features grpc`".to_string())
+}
@@ -1,20 +1,73 @@
 use ansi_term::Colour::Yellow;
-use clap::{App, Arg, ArgMatches, SubCommand};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
 
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, Transfer};
+use cita_tool::client::system_contract::{
+    AuthorizationClient, AuthorizationExt, PermissionManageClient, PermissionManagementExt,
+    RoleClient, RoleExt, RoleManageClient, RoleManagementExt,
+};
 use cita_tool::{
-    decode, pubkey_to_address, remove_0x, Hashable, KeyPair, LowerHex, Message, PubKey, Signature,
+    decode, pubkey_to_address, recover_message, remove_0x, sign_message, to_checksum_address,
+    EncryptedData, Encryption, Hashable, JsonRpcResponse, KeyPair, LowerHex, Message, PrivateKey,
+    PubKey, Signature, ToolError,
 };
 
-use crate::cli::{encryption, h256_validator, is_hex, key_validator};
-use crate::interactive::GlobalConfig;
+use crate::cli::chain_command::decode_call;
+use crate::cli::faucet_command::{grant, wait_receipts};
+use crate::cli::{
+    encryption, get_url, h256_validator, is_hex, key_validator, parse_address, parse_local_privkey,
+    parse_u64, read_password,
+};
+use crate::hsm;
+use crate::interactive::{cita_cli_dir, GlobalConfig};
+use crate::key_agent;
+use crate::ledger;
 use crate::printer::Printer;
+use crate::risk::confirmed;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The signing key of `sign-message` when `--private-key` isn't given
+const ENV_SIGNING_KEY: &str = "CITA_CLI_PRIVATE_KEY";
 
 /// Key related commands
 pub fn key_command() -> App<'static, 'static> {
     App::new("key")
         .about("Some key operations, such as generating address, public key")
-        .subcommand(SubCommand::with_name("create"))
+        .subcommand(
+            SubCommand::with_name("create")
+                .about("Create key pairs, print them or write them to a key file")
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .takes_value(true)
+                        .default_value("1")
+                        .validator(|count| parse_u64(&count).map(|_| ()))
+                        .help("Number of key pairs, generated in parallel"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Write the key pairs to the file as a JSON list"),
+                )
+                .arg(
+                    Arg::with_name("password")
+                        .long("password")
+                        .takes_value(true)
+                        .requires("output")
+                        .help("Encrypt the key file with the password"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("from-private").arg(
                 Arg::with_name("private-key")
@@ -74,20 +127,282 @@ pub fn key_command() -> App<'static, 'static> {
                         .help("signature"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("sign-message")
+                .about("Sign arbitrary data, prefixed so that it can't be a transaction")
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .required(true)
+                        .env(ENV_SIGNING_KEY)
+                        .hide_env_values(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help(
+                            "The private key to sign with, read from the environment it stays \
+                             out of the process list",
+                        ),
+                )
+                .arg(message_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("verify-message")
+                .about("Recover the signer address of a message signature")
+                .arg(message_arg())
+                .arg(
+                    Arg::with_name("signature")
+                        .long("signature")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|signature| is_hex(signature.as_str()))
+                        .help("The message signature"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Expected signer address, fail if the signer is another one"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("vanity")
+                .about("Generate key pairs until the address matches the prefix/suffix")
+                .arg(
+                    Arg::with_name("prefix")
+                        .long("prefix")
+                        .takes_value(true)
+                        .required_unless("suffix")
+                        .validator(|pattern| vanity_validator(&pattern))
+                        .help("Hex prefix of the address, case insensitive"),
+                )
+                .arg(
+                    Arg::with_name("suffix")
+                        .long("suffix")
+                        .takes_value(true)
+                        .validator(|pattern| vanity_validator(&pattern))
+                        .help("Hex suffix of the address, case insensitive"),
+                )
+                .arg(
+                    Arg::with_name("threads")
+                        .long("threads")
+                        .takes_value(true)
+                        .validator(|threads| parse_u64(&threads).map(|_| ()))
+                        .help("Number of worker threads, default is the number of CPUs"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("unlock")
+                .about(
+                    "Cache the keys of a key file in the key agent for a while, then an address \
+                     or an address book name given to --private-key signs by its cached key",
+                )
+                .arg(
+                    Arg::with_name("keys")
+                        .long("keys")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Key file written by `key create --output`"),
+                )
+                .arg(
+                    Arg::with_name("password")
+                        .long("password")
+                        .takes_value(true)
+                        .help("The password of the encrypted key file, prompted if absent"),
+                )
+                .arg(
+                    Arg::with_name("ttl")
+                        .long("ttl")
+                        .takes_value(true)
+                        .default_value("900")
+                        .validator(|ttl| parse_u64(&ttl).map(|_| ()))
+                        .help("Seconds to keep the keys"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("lock").about("Drop the cached keys and stop the key agent"),
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about(
+                    "List the keys of a device: of the HSM of the profile, the `hsm` of \
+                     ~/.cita-cli/config, the tokens of its slots and their public keys, a key \
+                     signs by `--private-key hsm:<label>`",
+                )
+                .arg(
+                    Arg::with_name("hsm")
+                        .long("hsm")
+                        .required_unless("ledger")
+                        .conflicts_with("ledger")
+                        .help("List the keys of the HSM"),
+                )
+                .arg(
+                    Arg::with_name("slot")
+                        .long("slot")
+                        .takes_value(true)
+                        .requires("hsm")
+                        .validator(|slot| parse_u64(&slot).map(|_| ()))
+                        .help("Only the keys of the slot"),
+                )
+                .arg(
+                    Arg::with_name("login")
+                        .long("login")
+                        .requires("hsm")
+                        .help("Log in by the pin first, for tokens hiding the keys until then"),
+                )
+                // The Ledger is experimental, out of the help until a CITA app is published
+                .arg(Arg::with_name("ledger").long("ledger").hidden(true).help(
+                    "List the addresses of the first Ledger plugged in, a key signs by \
+                     `--signer ledger --derivation-path <path>`",
+                ))
+                .arg(
+                    Arg::with_name("derivation-path")
+                        .long("derivation-path")
+                        .takes_value(true)
+                        .requires("ledger")
+                        .hidden(true)
+                        .validator(|path| ledger::path_validator(&path))
+                        .help(
+                            "The derivation path of the first address, the default is \
+                             m/44'/60'/0'/0/0",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("count")
+                        .long("count")
+                        .takes_value(true)
+                        .requires("ledger")
+                        .hidden(true)
+                        .validator(|count| parse_u64(&count).map(|_| ()))
+                        .help(
+                            "How many addresses, the last component of the path counts up, \
+                             the default is 1",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("confirm")
+                        .long("confirm")
+                        .requires("ledger")
+                        .hidden(true)
+                        .help("Show every address on the Ledger to check it there"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("rotate")
+                .about(
+                    "Replace a key by a new one: grant the roles and permissions of the old \
+                     address to the new address, optionally move the balance, and record the \
+                     mapping",
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key being replaced"),
+                )
+                .arg(
+                    Arg::with_name("admin-private-key")
+                        .long("admin-private-key")
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help(
+                            "The private key granting the roles and permissions, \
+                             default is the old key",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Write the new key pair to the file, before anything is sent"),
+                )
+                .arg(
+                    Arg::with_name("password")
+                        .long("password")
+                        .takes_value(true)
+                        .help("Encrypt the new key file with the password"),
+                )
+                .arg(
+                    Arg::with_name("transfer")
+                        .long("transfer")
+                        .help("Also transfer the balance, less the max fee, to the new address"),
+                )
+                .arg(
+                    Arg::with_name("record")
+                        .long("record")
+                        .takes_value(true)
+                        .help(
+                            "JSONL file the rotation is appended to, \
+                             default is ~/.cita-cli/key-rotations.jsonl",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Quota of the grant transactions, default is 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .takes_value(true)
+                        .default_value("120")
+                        .validator(|timeout| parse_u64(timeout.as_ref()).map(|_| ()))
+                        .help("Seconds to wait for the receipts"),
+                )
+                .arg(
+                    Arg::with_name("yes")
+                        .long("yes")
+                        .short("y")
+                        .help("Don't ask for confirmation"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("agent")
+                .setting(AppSettings::Hidden)
+                .about("Run the key agent, started by `key unlock`")
+                .arg(
+                    Arg::with_name("ttl")
+                        .long("ttl")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|ttl| parse_u64(&ttl).map(|_| ())),
+                ),
+        )
 }
 
 /// Key processor
 pub fn key_processor(
     sub_matches: &ArgMatches,
     printer: &Printer,
-    config: &GlobalConfig,
+    config: &mut GlobalConfig,
+    client: Client,
 ) -> Result<(), String> {
     match sub_matches.subcommand() {
         ("create", Some(m)) => {
             let encryption = encryption(m, config);
-            let key_pair = KeyPair::new(encryption);
+            let count = parse_u64(m.value_of("count").unwrap())? as usize;
             let is_color = !sub_matches.is_present("no-color") && config.color();
-            printer.println(&key_pair, is_color);
+            match m.value_of("output") {
+                None if count == 1 => printer.println(&KeyPair::new(encryption), is_color),
+                None => printer.println(&key_list(&create_key_pairs(encryption, count)), is_color),
+                Some(path) => {
+                    let key_pairs = create_key_pairs(encryption, count);
+                    write_key_file(path, &key_pairs, m.value_of("password"))?;
+                    printer.println(
+                        &json!({
+                            "output": path,
+                            "count": count,
+                            "encrypted": m.is_present("password"),
+                        }),
+                        is_color,
+                    );
+                }
+            }
         }
         ("from-private", Some(m)) => {
             let encryption = encryption(m, config);
@@ -102,11 +417,18 @@ pub fn key_processor(
             let address = pubkey_to_address(&PubKey::from_str(remove_0x(pubkey), encryption)?);
             if printer.color() {
                 printer.println(
-                    &format!("{} 0x{:#x}", Yellow.paint("[address]:"), address),
+                    &format!(
+                        "{} {}",
+                        Yellow.paint("[address]:"),
+                        to_checksum_address(&address)
+                    ),
                     true,
                 );
             } else {
-                printer.println(&format!("{} 0x{:#x}", "[address]:", address), false);
+                printer.println(
+                    &format!("{} {}", "[address]:", to_checksum_address(&address)),
+                    false,
+                );
             }
         }
         ("hash", Some(m)) => {
@@ -125,9 +447,442 @@ pub fn key_processor(
             );
             println!("{}", sig.verify_public(pubkey, &message)?);
         }
+        ("sign-message", Some(m)) => {
+            let encryption = encryption(m, config);
+            let private_key = parse_local_privkey(m.value_of("private-key").unwrap(), encryption)?;
+            let signature = sign_message(&private_key, &message_data(m)?);
+            printer.println(&format!("0x{}", signature), printer.color());
+        }
+        ("verify-message", Some(m)) => {
+            let signature = Signature::from(
+                &decode(remove_0x(m.value_of("signature").unwrap())).map_err(|e| e.to_string())?,
+            );
+            let signer = recover_message(&signature, &message_data(m)?)?;
+            printer.println(&to_checksum_address(&signer), printer.color());
+            if let Some(address) = m.value_of("address") {
+                if remove_0x(address).to_lowercase() != signer.completed_lower_hex() {
+                    return Err(format!(
+                        "Signed by {}, not {}",
+                        to_checksum_address(&signer),
+                        address
+                    ));
+                }
+            }
+        }
+        ("list", Some(m)) => {
+            let keys = if m.is_present("ledger") {
+                let count = m.value_of("count").map(parse_u64).transpose()?;
+                ledger::list(
+                    m.value_of("derivation-path"),
+                    count.unwrap_or(1),
+                    m.is_present("confirm"),
+                )?
+            } else {
+                let slot = m.value_of("slot").map(parse_u64).transpose()?;
+                hsm::list(slot, m.is_present("login"))?
+            };
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            printer.println(&keys, is_color);
+        }
+        ("unlock", Some(m)) => {
+            let encryption = encryption(m, config);
+            let path = m.value_of("keys").unwrap();
+            let password = match m.value_of("password") {
+                Some(password) => Some(password.to_owned()),
+                None if is_encrypted_key_file(path)? => {
+                    Some(read_password(&format!("Password of {}", path))?)
+                }
+                None => None,
+            };
+            let keys = read_key_file(path, password.as_deref(), encryption)?
+                .into_iter()
+                .map(|key| {
+                    (
+                        KeyPair::from_privkey(key)
+                            .address()
+                            .completed_lower_hex_with_0x(),
+                        format!("0x{}", key),
+                    )
+                })
+                .collect();
+            let report = key_agent::unlock(keys, parse_u64(m.value_of("ttl").unwrap())?)?;
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            printer.println(&report, is_color);
+        }
+        ("lock", _) => {
+            let report = key_agent::request(&json!({ "command": "lock" }))
+                .unwrap_or_else(|_| json!({ "locked": 0 }));
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            printer.println(&report, is_color);
+        }
+        ("rotate", Some(m)) => {
+            let debug = sub_matches.is_present("debug") || config.debug();
+            let client = client
+                .set_debug(debug)
+                .set_uri(get_url(sub_matches, config));
+            let report = rotate(m, client, encryption(m, config))?;
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            printer.println(&report, is_color);
+            let failed = report["failed"].as_u64().unwrap_or_default();
+            config.set("result".to_string(), report);
+            if failed > 0 {
+                return Err(format!("{} transactions of the rotation failed", failed));
+            }
+        }
+        ("agent", Some(m)) => key_agent::serve(parse_u64(m.value_of("ttl").unwrap())?)?,
+        ("vanity", Some(m)) => {
+            let encryption = encryption(m, config);
+            let pattern = |name| {
+                m.value_of(name)
+                    .map(|pattern| remove_0x(pattern).to_lowercase())
+                    .unwrap_or_default()
+            };
+            let threads = match m.value_of("threads") {
+                Some(threads) => parse_u64(threads)? as usize,
+                None => thread::available_parallelism()
+                    .map(|threads| threads.get())
+                    .unwrap_or(1),
+            };
+            let key_pair = vanity(encryption, &pattern("prefix"), &pattern("suffix"), threads)?;
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            printer.println(&key_pair, is_color);
+        }
         _ => {
             return Err(sub_matches.usage().to_owned());
         }
     }
     Ok(())
 }
+
+/// The quota of the balance transfer to the new address
+const TRANSFER_QUOTA: u64 = 30_000;
+
+/// Generate a new key, grant it the roles and the permissions of the old one,
+/// optionally move the balance, and append the mapping to the record
+fn rotate(m: &ArgMatches, client: Client, encryption: Encryption) -> Result<Value, String> {
+    let old_key = parse_local_privkey(m.value_of("private-key").unwrap(), encryption)?;
+    let old = KeyPair::from_privkey(old_key)
+        .address()
+        .completed_lower_hex_with_0x();
+    let admin_key = match m.value_of("admin-private-key") {
+        Some(key) => parse_local_privkey(key, encryption)?,
+        None => old_key,
+    };
+    let quota = m.value_of("quota").map(|quota| parse_u64(quota).unwrap());
+    let timeout = Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?);
+
+    let (roles, permissions) = granted(&client, &old).map_err(|err| format!("{}", err))?;
+    let key_pair = KeyPair::new(encryption);
+    let new = key_pair.address().completed_lower_hex_with_0x();
+    let action = format!(
+        "Rotate {} to {}: grant {} roles and {} permissions{}.",
+        old,
+        new,
+        roles.len(),
+        permissions.len(),
+        if m.is_present("transfer") {
+            ", transfer the balance"
+        } else {
+            ""
+        }
+    );
+    confirmed(m, "key rotate", &action)?;
+    // The new key is kept before anything depends on it
+    let output = m.value_of("output").unwrap();
+    write_key_file(output, &[key_pair], m.value_of("password"))?;
+
+    let mut admin = client.clone();
+    admin.set_private_key(&admin_key);
+    let role_manager: RoleManageClient<Client> = RoleManagementExt::create(admin.clone());
+    let permission_manager: PermissionManageClient<Client> = PermissionManagementExt::create(admin);
+    let mut transactions = Vec::new();
+    for role in &roles {
+        let response = RoleManagementExt::set_role(&role_manager, &new, role, quota)
+            .map_err(|err| format!("{}", err))?;
+        transactions.push(grant(&new, &format!("role {}", role), response));
+    }
+    if !permissions.is_empty() {
+        let list = format!(
+            "[{}]",
+            permissions
+                .iter()
+                .map(|permission| remove_0x(permission))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let response = permission_manager
+            .set_authorizations(&new, &list, quota)
+            .map_err(|err| format!("{}", err))?;
+        transactions.push(grant(&new, "permissions", response));
+    }
+    wait_receipts(&client, &mut transactions, timeout).map_err(|err| format!("{}", err))?;
+
+    let mut transferred = None;
+    if m.is_present("transfer") {
+        let mut sender = client.clone();
+        sender.set_private_key(&old_key);
+        let balance = sender
+            .get_current_balance(&old)
+            .map_err(|err| format!("{}", err))?;
+        let fee = sender
+            .estimate_fee(TRANSFER_QUOTA)
+            .map_err(|err| format!("{}", err))?
+            .map(|estimate| estimate.max_fee)
+            .unwrap_or_default();
+        if balance > fee {
+            let value = balance - fee;
+            let response = sender
+                .transfer(value, &new, Some(TRANSFER_QUOTA))
+                .map_err(|err| format!("{}", err))?;
+            let mut transfer = [grant(&new, "value", response)];
+            wait_receipts(&sender, &mut transfer, timeout).map_err(|err| format!("{}", err))?;
+            transferred = Some(value);
+            transactions.extend(transfer);
+        }
+    }
+
+    let failed = transactions
+        .iter()
+        .filter(|transaction| transaction["status"] != "success")
+        .count();
+    let record = json!({
+        "timestamp": SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default(),
+        "old": old,
+        "new": new,
+        "roles": roles,
+        "permissions": permissions,
+        "transferred": transferred.map(|value| value.to_string()),
+        "transactions": transactions,
+    });
+    let record_path = match m.value_of("record") {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let dir = cita_cli_dir();
+            fs::create_dir_all(&dir).map_err(|err| err.to_string())?;
+            dir.join("key-rotations.jsonl")
+        }
+    };
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&record_path)
+        .and_then(|mut file| writeln!(file, "{}", record))
+        .map_err(|err| format!("Can't write {}: {}", record_path.display(), err))?;
+
+    let mut report = record;
+    report["output"] = json!(output);
+    report["record"] = json!(record_path);
+    report["failed"] = json!(failed);
+    Ok(report)
+}
+
+/// The roles of the account, and its permissions not coming with the roles
+fn granted(client: &Client, account: &str) -> Result<(Vec<String>, Vec<String>), ToolError> {
+    let role_manager: RoleManageClient<Client> = RoleManagementExt::create(client.clone());
+    let role_client: RoleClient<Client> = RoleExt::create(client.clone());
+    let authorization: AuthorizationClient<Client> = AuthorizationExt::create(client.clone());
+    let roles = addresses(RoleManagementExt::query_roles(
+        &role_manager,
+        account,
+        None,
+    )?)?;
+    let mut with_roles = BTreeSet::new();
+    for role in &roles {
+        with_roles.extend(addresses(RoleExt::query_permissions(
+            &role_client,
+            role,
+            None,
+        )?)?);
+    }
+    let permissions = addresses(AuthorizationExt::query_permissions(
+        &authorization,
+        account,
+        None,
+    )?)?
+    .into_iter()
+    .filter(|permission| !with_roles.contains(permission))
+    .collect();
+    Ok((roles, permissions))
+}
+
+/// The addresses returned as `address[]`, with 0x
+fn addresses(response: JsonRpcResponse) -> Result<Vec<String>, ToolError> {
+    let list = decode_call(response, "address[]")?;
+    Ok(list
+        .as_str()
+        .unwrap_or_default()
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(',')
+        .filter(|address| !address.is_empty())
+        .map(|address| format!("0x{}", address.to_lowercase()))
+        .collect())
+}
+
+/// Generate the key pairs in as many threads as CPUs
+fn create_key_pairs(encryption: Encryption, count: usize) -> Vec<KeyPair> {
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1)
+        .min(count.max(1));
+    let workers: Vec<_> = (0..threads)
+        .map(|index| {
+            // Spread the remainder over the first workers
+            let share = count / threads + usize::from(index < count % threads);
+            thread::spawn(move || {
+                (0..share)
+                    .map(|_| KeyPair::new(encryption))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+    workers
+        .into_iter()
+        .flat_map(|worker| worker.join().unwrap())
+        .collect()
+}
+
+fn key_list(key_pairs: &[KeyPair]) -> Value {
+    key_pairs
+        .iter()
+        .map(|key_pair| {
+            json!({
+                "private": format!("0x{}", key_pair.privkey()),
+                "public": format!("0x{}", key_pair.pubkey()),
+                "address": to_checksum_address(&key_pair.address()),
+            })
+        })
+        .collect()
+}
+
+/// Write the key pairs as a JSON list, or as the list encrypted with the password
+pub(crate) fn write_key_file(
+    path: &str,
+    key_pairs: &[KeyPair],
+    password: Option<&str>,
+) -> Result<(), String> {
+    let keys = key_list(key_pairs);
+    let content = match password {
+        Some(password) => json!(EncryptedData::encrypt(
+            keys.to_string().as_bytes(),
+            password
+        )),
+        None => keys,
+    };
+    let content = serde_json::to_string_pretty(&content).map_err(|err| err.to_string())?;
+    fs::write(path, content).map_err(|err| format!("Can't write {}: {}", path, err))
+}
+
+/// Read the private keys of a key file written by `key create --output`
+pub(crate) fn read_key_file(
+    path: &str,
+    password: Option<&str>,
+    encryption: Encryption,
+) -> Result<Vec<PrivateKey>, String> {
+    let content = fs::read(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let mut keys: Value = serde_json::from_slice(&content).map_err(|err| err.to_string())?;
+    if keys.is_object() {
+        let password =
+            password.ok_or_else(|| format!("{} is encrypted, need the password", path))?;
+        let encrypted: EncryptedData =
+            serde_json::from_value(keys).map_err(|err| err.to_string())?;
+        keys =
+            serde_json::from_slice(&encrypted.decrypt(password)?).map_err(|err| err.to_string())?;
+    }
+    keys.as_array()
+        .ok_or_else(|| format!("{} is not a key file", path))?
+        .iter()
+        .map(|key| parse_local_privkey(key["private"].as_str().unwrap_or_default(), encryption))
+        .collect()
+}
+
+/// Whether the key file is encrypted by a password
+fn is_encrypted_key_file(path: &str) -> Result<bool, String> {
+    let content = fs::read(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let keys: Value = serde_json::from_slice(&content).map_err(|err| err.to_string())?;
+    Ok(keys.is_object())
+}
+
+fn message_arg() -> Arg<'static, 'static> {
+    Arg::with_name("message")
+        .long("message")
+        .takes_value(true)
+        .required(true)
+        .help("The message, UTF-8 text, or hex data when it starts with 0x")
+}
+
+fn message_data(m: &ArgMatches) -> Result<Vec<u8>, String> {
+    let message = m.value_of("message").unwrap();
+    if is_hex(message).is_ok() {
+        decode(remove_0x(message)).map_err(|err| err.to_string())
+    } else {
+        Ok(message.as_bytes().to_vec())
+    }
+}
+
+fn vanity_validator(pattern: &str) -> Result<(), String> {
+    let pattern = remove_0x(pattern);
+    if pattern.is_empty() || pattern.len() > 40 || !pattern.chars().all(|c| c.is_ascii_hexdigit()) {
+        Err(format!("Invalid address pattern: {}", pattern))
+    } else {
+        Ok(())
+    }
+}
+
+/// Generate key pairs in worker threads until one address matches, the progress goes to stderr
+fn vanity(
+    encryption: Encryption,
+    prefix: &str,
+    suffix: &str,
+    threads: usize,
+) -> Result<KeyPair, String> {
+    let found = Arc::new(AtomicBool::new(false));
+    let attempts = Arc::new(AtomicU64::new(0));
+    let (sender, receiver) = mpsc::channel();
+
+    for _ in 0..threads.max(1) {
+        let (found, attempts, sender) = (Arc::clone(&found), Arc::clone(&attempts), sender.clone());
+        let (prefix, suffix) = (prefix.to_owned(), suffix.to_owned());
+        thread::spawn(move || {
+            while !found.load(Ordering::Relaxed) {
+                let key_pair = KeyPair::new(encryption);
+                attempts.fetch_add(1, Ordering::Relaxed);
+                let address = key_pair.address().completed_lower_hex();
+                if address.starts_with(&prefix)
+                    && address.ends_with(&suffix)
+                    && !found.swap(true, Ordering::SeqCst)
+                {
+                    let _ = sender.send(key_pair);
+                }
+            }
+        });
+    }
+    drop(sender);
+
+    // Every hex char matches with probability 1/16
+    let expected = 16f64.powi((prefix.len() + suffix.len()) as i32);
+    let start = Instant::now();
+    loop {
+        match receiver.recv_timeout(Duration::from_secs(1)) {
+            Ok(key_pair) => {
+                eprintln!();
+                return Ok(key_pair);
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                let count = attempts.load(Ordering::Relaxed);
+                let rate = count as f64 / start.elapsed().as_secs_f64();
+                eprint!(
+                    "\rSearched {} addresses, {:.0}/s, estimated time {:.0}s    ",
+                    count,
+                    rate,
+                    expected / rate
+                );
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("Vanity workers exited unexpectedly".to_string());
+            }
+        }
+    }
+}
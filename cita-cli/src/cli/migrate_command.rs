@@ -0,0 +1,212 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+
+use crate::cli::user_contract_command::{chain_key, Upgrade};
+use crate::cli::{encryption, get_url, key_validator, parse_address, parse_privkey, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::migrations::{Migration, MigrationState, Step};
+use crate::printer::Printer;
+
+/// Migration command
+pub fn migrate_command() -> App<'static, 'static> {
+    let dir_arg = Arg::with_name("dir")
+        .long("dir")
+        .default_value("migrations")
+        .takes_value(true)
+        .help("The directory of the numbered migration files");
+    let state_arg = Arg::with_name("state")
+        .long("state")
+        .takes_value(true)
+        .help("The file of the applied migrations, default is ~/.cita-cli/migrations.json");
+
+    App::new("migrate")
+        .about(
+            "Run the numbered migration files of deploy and call steps, each exactly once \
+             per chain",
+        )
+        .subcommand(
+            SubCommand::with_name("run")
+                .about(
+                    "Apply the migrations not applied to the chain yet, in order, stop at the \
+                     first failed step",
+                )
+                .arg(dir_arg.clone())
+                .arg(state_arg.clone())
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .required_unless("dry-run")
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transactions"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .default_value("60")
+                        .takes_value(true)
+                        .validator(|timeout| parse_u64(timeout.as_ref()).map(|_| ()))
+                        .help("Seconds to wait for the receipt of every step"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Only show the migrations to apply and their steps"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("status")
+                .about("Show which migrations are applied to the chain")
+                .arg(dir_arg)
+                .arg(state_arg),
+        )
+}
+
+/// Migration processor
+pub fn migrate_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let mut client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let result = match sub_matches.subcommand() {
+        ("run", Some(m)) => {
+            let dir = Path::new(m.value_of("dir").unwrap());
+            let migrations = Migration::load_dir(dir)?;
+            let mut state = MigrationState::load(m.value_of("state"))?;
+            let chain = chain_key(&client)?;
+            let pending = migrations
+                .into_iter()
+                .filter(|migration| !state.is_applied(&chain, &migration.id))
+                .collect::<Vec<Migration>>();
+
+            if m.is_present("dry-run") {
+                json!({
+                    "chain": chain,
+                    "pending": pending
+                        .iter()
+                        .map(|migration| json!({
+                            "migration": migration.id,
+                            "steps": migration.steps.iter().map(step_value).collect::<Vec<Value>>(),
+                        }))
+                        .collect::<Vec<Value>>(),
+                })
+            } else {
+                let encryption = encryption(m, config);
+                client.set_private_key(&parse_privkey(
+                    m.value_of("private-key").unwrap(),
+                    encryption,
+                )?);
+                let runner = Upgrade {
+                    address: "0x",
+                    quota: m.value_of("quota").map(|quota| parse_u64(quota).unwrap()),
+                    timeout: Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?),
+                    encryption,
+                };
+                let mut applied = Vec::new();
+                for migration in pending {
+                    let mut contracts = state.contracts(&chain);
+                    let steps = apply(&mut client, &runner, &migration, &mut contracts)?;
+                    // Recorded once every step succeeded, a failed migration is run again
+                    state.apply(&chain, &migration.id, &contracts);
+                    state.save()?;
+                    applied.push(json!({ "migration": migration.id, "steps": steps }));
+                }
+                json!({
+                    "chain": chain,
+                    "applied": applied,
+                    "contracts": state.contracts(&chain),
+                })
+            }
+        }
+        ("status", Some(m)) => {
+            let migrations = Migration::load_dir(Path::new(m.value_of("dir").unwrap()))?;
+            let state = MigrationState::load(m.value_of("state"))?;
+            let chain = chain_key(&client)?;
+            json!({
+                "chain": chain,
+                "migrations": migrations
+                    .iter()
+                    .map(|migration| json!({
+                        "migration": migration.id,
+                        "steps": migration.steps.len(),
+                        "applied": state.is_applied(&chain, &migration.id),
+                    }))
+                    .collect::<Vec<Value>>(),
+                "contracts": state.contracts(&chain),
+            })
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&result, is_color);
+    config.set("result".to_string(), result);
+    Ok(())
+}
+
+/// Run the steps of the migration, the contracts it deploys are added to the known ones
+fn apply(
+    client: &mut Client,
+    runner: &Upgrade,
+    migration: &Migration,
+    contracts: &mut BTreeMap<String, String>,
+) -> Result<Vec<Value>, String> {
+    let mut steps = Vec::new();
+    for (index, step) in migration.steps.iter().enumerate() {
+        let failed = |err: String| format!("{} step {}: {}", migration.id, index + 1, err);
+        let mut report = step_value(step);
+        match step {
+            Step::Deploy { name, code } => {
+                let address = runner.deploy(client, code).map_err(failed)?;
+                report["address"] = json!(address);
+                contracts.insert(name.clone(), address);
+            }
+            Step::Call { to, data } => {
+                let address = match contracts.get(to) {
+                    Some(address) => address.clone(),
+                    None => {
+                        parse_address(to).map_err(|_| {
+                            failed(format!(
+                                "{} is neither a deployed contract nor an address",
+                                to
+                            ))
+                        })?;
+                        to.clone()
+                    }
+                };
+                let receipt = runner
+                    .send(client, &address, data, "call")
+                    .map_err(failed)?;
+                report["address"] = json!(address);
+                report["transactionHash"] = receipt["transactionHash"].clone();
+            }
+        }
+        steps.push(report);
+    }
+    Ok(steps)
+}
+
+fn step_value(step: &Step) -> Value {
+    match step {
+        Step::Deploy { name, .. } => json!({ "deploy": name }),
+        Step::Call { to, data } => json!({ "call": to, "data": data }),
+    }
+}
@@ -0,0 +1,350 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::process::Command;
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg, ArgMatches};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::parse_url;
+
+use crate::cli::node_command::{health, lag, NodeHealth};
+use crate::cli::{get_url, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Monitor command
+pub fn monitor_command() -> App<'static, 'static> {
+    App::new("monitor")
+        .about(
+            "Check the block progression and the lag of the nodes on an interval, \
+             alert when the chain stalls or a node falls behind, and when it recovers",
+        )
+        .arg(
+            Arg::with_name("urls")
+                .long("urls")
+                .takes_value(true)
+                .multiple(true)
+                .use_delimiter(true)
+                .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                .help("The nodes to monitor, separated by commas, default is the url"),
+        )
+        .arg(
+            Arg::with_name("interval")
+                .long("interval")
+                .takes_value(true)
+                .default_value("10")
+                .validator(|interval| parse_u64(interval.as_str()).map(|_| ()))
+                .help("Checking interval in seconds"),
+        )
+        .arg(
+            Arg::with_name("stall")
+                .long("stall")
+                .takes_value(true)
+                .default_value("60")
+                .validator(|stall| parse_u64(stall.as_str()).map(|_| ()))
+                .help("The chain stalls when no node gets a new block in these seconds"),
+        )
+        .arg(
+            Arg::with_name("max-lag")
+                .long("max-lag")
+                .takes_value(true)
+                .default_value("10")
+                .validator(|lag| parse_u64(lag.as_str()).map(|_| ()))
+                .help("Blocks a node may be behind the highest node"),
+        )
+        .arg(
+            Arg::with_name("webhook")
+                .long("webhook")
+                .takes_value(true)
+                .help("POST each alert as json to this url"),
+        )
+        .arg(
+            Arg::with_name("exec")
+                .long("exec")
+                .takes_value(true)
+                .help("Run the shell command on each alert, with the alert json in $CITA_ALERT"),
+        )
+        .arg(
+            Arg::with_name("textfile")
+                .long("textfile")
+                .takes_value(true)
+                .help(
+                    "Write the height, lag and peer count of the nodes to this Prometheus \
+                     textfile after each check, for the textfile collector of node_exporter",
+                ),
+        )
+}
+
+/// Monitor processor
+pub fn monitor_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client.set_debug(debug);
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let urls: Vec<String> = match sub_matches.values_of("urls") {
+        Some(urls) => urls.map(str::to_owned).collect(),
+        None => vec![get_url(sub_matches, config).to_owned()],
+    };
+    let interval = parse_u64(sub_matches.value_of("interval").unwrap())?;
+    let webhook = sub_matches.value_of("webhook");
+    let exec = sub_matches.value_of("exec");
+    let textfile = sub_matches.value_of("textfile");
+    let mut monitor = Monitor::new(
+        Duration::from_secs(parse_u64(sub_matches.value_of("stall").unwrap())?),
+        parse_u64(sub_matches.value_of("max-lag").unwrap())?,
+    );
+
+    loop {
+        let alerts = match health(&client, &urls) {
+            Ok(nodes) => {
+                let alerts = monitor.check(&nodes, Instant::now());
+                if let Some(path) = textfile {
+                    if let Err(err) = write_textfile(path, &metrics(&nodes, monitor.stalled)) {
+                        printer.eprintln(&Rc::new(format!("Can't write {}: {}", path, err)), true);
+                    }
+                }
+                alerts
+            }
+            Err(err) => {
+                printer.eprintln(&Rc::new(format!("Check failed: {}", err)), true);
+                Vec::new()
+            }
+        };
+        for alert in alerts {
+            printer.println(&alert, is_color);
+            if let Some(url) = webhook {
+                match client.post_json(url, &alert) {
+                    Ok(status) if status < 300 => {}
+                    Ok(status) => printer.eprintln(
+                        &Rc::new(format!("Webhook responded with status {}", status)),
+                        true,
+                    ),
+                    Err(err) => printer
+                        .eprintln(&Rc::new(format!("Webhook delivery failed: {}", err)), true),
+                }
+            }
+            if let Some(command) = exec {
+                match Command::new("sh")
+                    .args(["-c", command])
+                    .env("CITA_ALERT", alert.to_string())
+                    .status()
+                {
+                    Ok(status) if status.success() => {}
+                    Ok(status) => printer.eprintln(
+                        &Rc::new(format!("Alert command exited with {}", status)),
+                        true,
+                    ),
+                    Err(err) => printer.eprintln(
+                        &Rc::new(format!("Can't run the alert command: {}", err)),
+                        true,
+                    ),
+                }
+            }
+        }
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+/// Alert on the changes only, a condition is reported once when it starts and once
+/// when it's resolved
+struct Monitor {
+    stall: Duration,
+    max_lag: u64,
+    max_height: Option<u64>,
+    progressed: Option<Instant>,
+    stalled: bool,
+    behind: BTreeSet<String>,
+}
+
+impl Monitor {
+    fn new(stall: Duration, max_lag: u64) -> Self {
+        Monitor {
+            stall,
+            max_lag,
+            max_height: None,
+            progressed: None,
+            stalled: false,
+            behind: BTreeSet::new(),
+        }
+    }
+
+    /// The alerts of the conditions started or resolved since the last check
+    fn check(&mut self, nodes: &[NodeHealth], now: Instant) -> Vec<Value> {
+        let mut alerts = Vec::new();
+
+        let max_height = nodes.iter().filter_map(|node| node.height).max();
+        if max_height.is_some() && max_height > self.max_height {
+            self.max_height = max_height;
+            self.progressed = Some(now);
+        }
+        let progressed = *self.progressed.get_or_insert(now);
+        let stalled = now.saturating_duration_since(progressed) >= self.stall;
+        if stalled != self.stalled {
+            self.stalled = stalled;
+            alerts.push(json!({
+                "alert": if stalled { "stalled" } else { "progressing" },
+                "height": self.max_height,
+                "seconds": now.saturating_duration_since(progressed).as_secs(),
+            }));
+        }
+
+        for node in nodes {
+            let lag = lag(nodes, node);
+            let behind = node.error.is_some() || lag.is_none_or(|lag| lag > self.max_lag);
+            let was_behind = self.behind.contains(&node.url);
+            if behind && !was_behind {
+                self.behind.insert(node.url.clone());
+            } else if !behind && was_behind {
+                self.behind.remove(&node.url);
+            } else {
+                continue;
+            }
+            alerts.push(json!({
+                "alert": match (behind, node.error.is_some()) {
+                    (true, true) => "down",
+                    (true, false) => "behind",
+                    (false, _) => "recovered",
+                },
+                "url": node.url,
+                "height": node.height,
+                "lag": lag,
+                "error": node.error,
+            }));
+        }
+        alerts
+    }
+}
+
+/// The metrics of the check in the Prometheus text format
+fn metrics(nodes: &[NodeHealth], stalled: bool) -> String {
+    let mut text = String::new();
+    let mut gauge = |name: &str, help: &str, values: Vec<(Option<&str>, Option<u64>)>| {
+        text.push_str(&format!(
+            "# HELP {} {}\n# TYPE {} gauge\n",
+            name, help, name
+        ));
+        for (url, value) in values {
+            let value = match value {
+                Some(value) => value,
+                None => continue,
+            };
+            match url {
+                Some(url) => text.push_str(&format!(
+                    "{}{{url=\"{}\"}} {}\n",
+                    name,
+                    url.replace('\\', "\\\\").replace('"', "\\\""),
+                    value
+                )),
+                None => text.push_str(&format!("{} {}\n", name, value)),
+            }
+        }
+    };
+    let per_node = |value: &dyn Fn(&NodeHealth) -> Option<u64>| {
+        nodes
+            .iter()
+            .map(|node| (Some(node.url.as_str()), value(node)))
+            .collect::<Vec<_>>()
+    };
+
+    gauge(
+        "cita_node_up",
+        "Whether the node answered the last check",
+        per_node(&|node| Some(node.error.is_none() as u64)),
+    );
+    gauge(
+        "cita_node_height",
+        "Block height of the node",
+        per_node(&|node| node.height),
+    );
+    gauge(
+        "cita_node_lag_blocks",
+        "Blocks the node is behind the highest node",
+        per_node(&|node| lag(nodes, node)),
+    );
+    gauge(
+        "cita_node_peers",
+        "Peer count of the node",
+        per_node(&|node| node.peers),
+    );
+    gauge(
+        "cita_chain_height",
+        "Highest block height of the nodes",
+        vec![(None, nodes.iter().filter_map(|node| node.height).max())],
+    );
+    gauge(
+        "cita_chain_stalled",
+        "Whether no node got a new block in the stall seconds",
+        vec![(None, Some(stalled as u64))],
+    );
+    text
+}
+
+/// Replace the textfile at once, so the collector never reads a partial file
+fn write_textfile(path: &str, text: &str) -> std::io::Result<()> {
+    let temporary = format!("{}.tmp", path);
+    fs::write(&temporary, text)?;
+    fs::rename(&temporary, path)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{metrics, Monitor};
+    use crate::cli::node_command::NodeHealth;
+
+    fn node(url: &str, height: Option<u64>) -> NodeHealth {
+        NodeHealth {
+            url: url.to_owned(),
+            height,
+            peers: Some(3),
+            error: height.map_or(Some("Connection refused".to_owned()), |_| None),
+        }
+    }
+
+    #[test]
+    fn test_monitor_alerts() {
+        let mut monitor = Monitor::new(Duration::from_secs(30), 5);
+        let now = Instant::now();
+        let alerts = |monitor: &mut Monitor, nodes: &[NodeHealth], seconds| {
+            monitor
+                .check(nodes, now + Duration::from_secs(seconds))
+                .iter()
+                .map(|alert| alert["alert"].as_str().unwrap().to_owned())
+                .collect::<Vec<String>>()
+        };
+
+        let nodes = [node("a", Some(100)), node("b", Some(98))];
+        assert!(alerts(&mut monitor, &nodes, 0).is_empty());
+        // Reported once, not on every check
+        let nodes = [node("a", Some(110)), node("b", None)];
+        assert_eq!(alerts(&mut monitor, &nodes, 10), vec!["down"]);
+        assert!(alerts(&mut monitor, &nodes, 20).is_empty());
+        assert_eq!(alerts(&mut monitor, &nodes, 40), vec!["stalled"]);
+
+        let nodes = [node("a", Some(111)), node("b", Some(111))];
+        assert_eq!(
+            alerts(&mut monitor, &nodes, 50),
+            vec!["progressing", "recovered"]
+        );
+    }
+
+    #[test]
+    fn test_metrics() {
+        let text = metrics(&[node("a", Some(100)), node("b", None)], false);
+        assert!(text.contains("# TYPE cita_node_height gauge\n"));
+        assert!(text.contains("cita_node_up{url=\"b\"} 0\n"));
+        assert!(text.contains("cita_node_lag_blocks{url=\"a\"} 0\n"));
+        assert!(!text.contains("cita_node_height{url=\"b\"}"));
+        assert!(text.contains("cita_chain_height 100\n"));
+    }
+}
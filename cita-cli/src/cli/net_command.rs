@@ -0,0 +1,331 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::parse_url;
+
+use crate::cli::chain_command::result_value;
+use crate::cli::node_command::{ask_nodes, node_urls};
+use crate::cli::parse_u64;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Network command
+pub fn net_command() -> App<'static, 'static> {
+    App::new("net")
+        .about("Inspect the p2p network of the nodes")
+        .subcommand(
+            SubCommand::with_name("topology")
+                .about(
+                    "Assemble the peer graph from `peersInfo` of every node, \
+                 fail if the graph is partitioned or a node is under-connected",
+                )
+                .arg(
+                    Arg::with_name("urls")
+                        .long("urls")
+                        .takes_value(true)
+                        .multiple(true)
+                        .use_delimiter(true)
+                        .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                        .help("The nodes to ask, separated by commas, default is the url"),
+                )
+                .arg(
+                    Arg::with_name("min-peers")
+                        .long("min-peers")
+                        .takes_value(true)
+                        .default_value("2")
+                        .validator(|peers| parse_u64(peers.as_str()).map(|_| ()))
+                        .help("A node with fewer peers is under-connected"),
+                )
+                .arg(
+                    Arg::with_name("dot")
+                        .long("dot")
+                        .help("Print the graph in the DOT language of Graphviz"),
+                ),
+        )
+}
+
+/// Network processor
+pub fn net_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client.set_debug(debug);
+
+    match sub_matches.subcommand() {
+        ("topology", Some(m)) => {
+            let urls = node_urls(m, config);
+            let min_peers = parse_u64(m.value_of("min-peers").unwrap())?;
+            let peers = ask_nodes(&client, &urls, "peersInfo")
+                .map_err(|err| format!("{}", err))?
+                .into_iter()
+                .map(|response| {
+                    let result = response
+                        .and_then(result_value)
+                        .map_err(|err| format!("{}", err))?;
+                    Ok(result["peers"]
+                        .as_object()
+                        .map(|peers| {
+                            peers
+                                .iter()
+                                .map(|(address, ip)| {
+                                    let ip = ip.as_str().unwrap_or_default().to_owned();
+                                    (address.to_lowercase(), ip)
+                                })
+                                .collect()
+                        })
+                        .unwrap_or_default())
+                })
+                .collect::<Vec<_>>();
+            let report = topology(&urls, &peers, min_peers);
+
+            if m.is_present("dot") {
+                println!("{}", topology_dot(&report));
+            } else {
+                printer.println(&report, !m.is_present("no-color") && config.color());
+            }
+            let components = report["components"].as_array().map_or(0, Vec::len);
+            let under_connected = report["underConnected"].as_u64().unwrap_or_default();
+            config.set("result".to_string(), report);
+            if components > 1 || under_connected > 0 {
+                return Err(format!(
+                    "The network has {} partitions and {} nodes with fewer than {} peers",
+                    components, under_connected, min_peers
+                ));
+            }
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    Ok(())
+}
+
+/// The peers of a node by their addresses, with the ips they connect from
+type Peers = BTreeMap<String, String>;
+
+/// The host of the url, to match the ips of the peers
+fn host(url: &str) -> &str {
+    let url = url.split("://").nth(1).unwrap_or(url);
+    url.split([':', '/']).next().unwrap_or(url)
+}
+
+/// Find the address of every node, `peersInfo` only tells the others.
+///
+/// A node is the one address it doesn't list, or the one of them connecting
+/// from the host of its url, the rest are only known by their urls.
+fn identify(urls: &[String], peers: &[Result<Peers, String>]) -> Vec<Option<String>> {
+    let mut ips: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for (address, ip) in peers.iter().flatten().flatten() {
+        ips.entry(address).or_default().insert(ip);
+    }
+    let mut ids: Vec<Option<String>> = vec![None; urls.len()];
+    loop {
+        let mut progress = false;
+        for (index, own) in peers.iter().enumerate() {
+            let own = match own {
+                Ok(own) if ids[index].is_none() => own,
+                _ => continue,
+            };
+            let candidates = ips
+                .keys()
+                .filter(|address| !own.contains_key(**address))
+                .filter(|address| !ids.contains(&Some(address.to_string())))
+                .collect::<Vec<_>>();
+            let by_host = candidates
+                .iter()
+                .filter(|address| ips[**address].contains(host(&urls[index])))
+                .collect::<Vec<_>>();
+            let id = match (by_host.len(), candidates.len()) {
+                (1, _) => Some(by_host[0].to_string()),
+                (_, 1) => Some(candidates[0].to_string()),
+                _ => None,
+            };
+            if id.is_some() {
+                ids[index] = id;
+                progress = true;
+            }
+        }
+        if !progress {
+            return ids;
+        }
+    }
+}
+
+/// The vertex representing the component of the vertex
+fn root(parent: &BTreeMap<String, String>, vertex: &str) -> String {
+    let mut vertex = vertex;
+    while parent[vertex] != vertex {
+        vertex = &parent[vertex];
+    }
+    vertex.to_owned()
+}
+
+/// The peer graph of the nodes, the vertices are the addresses of the nodes and their peers
+fn topology(urls: &[String], peers: &[Result<Peers, String>], min_peers: u64) -> Value {
+    let ids = identify(urls, peers);
+    let id = |index: usize| ids[index].clone().unwrap_or_else(|| urls[index].clone());
+
+    let mut edges: BTreeSet<(String, String)> = BTreeSet::new();
+    let mut vertices: BTreeSet<String> = BTreeSet::new();
+    for (index, own) in peers.iter().enumerate() {
+        if let Ok(own) = own {
+            let node = id(index);
+            vertices.insert(node.clone());
+            for address in own.keys() {
+                vertices.insert(address.clone());
+                let edge = if &node < address {
+                    (node.clone(), address.clone())
+                } else {
+                    (address.clone(), node.clone())
+                };
+                edges.insert(edge);
+            }
+        }
+    }
+
+    // The connected components, every vertex points to a vertex of its component
+    let mut parent: BTreeMap<String, String> = vertices
+        .iter()
+        .map(|vertex| (vertex.clone(), vertex.clone()))
+        .collect();
+    for (left, right) in &edges {
+        let (left, right) = (root(&parent, left), root(&parent, right));
+        parent.insert(left, right);
+    }
+    let mut components: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for vertex in &vertices {
+        components
+            .entry(root(&parent, vertex))
+            .or_default()
+            .push(vertex.clone());
+    }
+
+    let mut nodes = Vec::new();
+    for (index, url) in urls.iter().enumerate() {
+        let (peers, error) = match peers[index] {
+            Ok(ref peers) => (Some(peers.keys().collect::<Vec<_>>()), None),
+            Err(ref err) => (None, Some(err)),
+        };
+        nodes.push(json!({
+            "id": id(index),
+            "address": ids[index],
+            "url": url,
+            "peerCount": peers.as_ref().map(Vec::len),
+            "peers": peers,
+            "underConnected": peers
+                .as_ref()
+                .is_some_and(|peers| (peers.len() as u64) < min_peers),
+            "error": error,
+        }));
+    }
+    // The peers not asked are in the graph too, with the ip they connect from
+    let asked: BTreeSet<String> = (0..urls.len()).map(id).collect();
+    for vertex in vertices.iter().filter(|vertex| !asked.contains(*vertex)) {
+        let ip = peers.iter().flatten().find_map(|peers| peers.get(vertex));
+        nodes.push(json!({
+            "id": vertex,
+            "address": vertex,
+            "ip": ip,
+        }));
+    }
+
+    json!({
+        "nodes": nodes,
+        "edges": edges
+            .iter()
+            .map(|(left, right)| json!([left, right]))
+            .collect::<Vec<Value>>(),
+        "components": components.into_values().collect::<Vec<_>>(),
+        "underConnected": nodes
+            .iter()
+            .filter(|node| node["underConnected"] == true)
+            .count(),
+        "unreachable": nodes.iter().filter(|node| !node["error"].is_null()).count(),
+    })
+}
+
+/// The graph in DOT, the under-connected nodes are red, the unreachable ones are dashed
+/// and the peers not asked are gray
+fn topology_dot(report: &Value) -> String {
+    let mut dot = "graph topology {\n    node [shape=box];\n".to_string();
+    for node in report["nodes"].as_array().into_iter().flatten() {
+        let id = node["id"].as_str().unwrap_or_default();
+        let mut label = id.to_owned();
+        if let Some(url) = node["url"].as_str().filter(|url| *url != id) {
+            label.push_str(&format!("\\n{}", url));
+        }
+        let style = if !node["error"].is_null() {
+            ", style=dashed"
+        } else if node["underConnected"] == true {
+            ", color=red"
+        } else if node["url"].is_null() {
+            ", color=gray"
+        } else {
+            ""
+        };
+        dot.push_str(&format!("    \"{}\" [label=\"{}\"{}];\n", id, label, style));
+    }
+    for edge in report["edges"].as_array().into_iter().flatten() {
+        dot.push_str(&format!(
+            "    \"{}\" -- \"{}\";\n",
+            edge[0].as_str().unwrap_or_default(),
+            edge[1].as_str().unwrap_or_default()
+        ));
+    }
+    dot.push('}');
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use super::{topology, topology_dot, Peers};
+
+    #[test]
+    fn test_topology() {
+        let peers = |list: &[(&str, &str)]| -> Result<Peers, String> {
+            Ok(list
+                .iter()
+                .map(|(address, ip)| (address.to_string(), ip.to_string()))
+                .collect())
+        };
+        let urls = [
+            "http://10.0.0.1:1337",
+            "http://10.0.0.2:1337",
+            "http://10.0.0.3:1337",
+            "http://10.0.0.4:1337",
+        ]
+        .iter()
+        .map(|url| url.to_string())
+        .collect::<Vec<_>>();
+        // `a` and `b` are connected, `c` only knows `d` which isn't asked, the last is down
+        let report = topology(
+            &urls,
+            &[
+                peers(&[("0xb", "10.0.0.2")]),
+                peers(&[("0xa", "10.0.0.1")]),
+                peers(&[("0xd", "10.0.0.9")]),
+                Err("Connection refused".to_string()),
+            ],
+            2,
+        );
+
+        assert_eq!(report["nodes"][0]["id"], "0xa");
+        assert_eq!(report["nodes"][1]["id"], "0xb");
+        // The only address `c` doesn't list is `d`'s peer, so it's only known by its url
+        assert_eq!(report["nodes"][2]["id"], "http://10.0.0.3:1337");
+        assert_eq!(report["nodes"][4]["ip"], "10.0.0.9");
+        assert_eq!(report["components"].as_array().unwrap().len(), 2);
+        assert_eq!(report["underConnected"], 3);
+        assert_eq!(report["unreachable"], 1);
+
+        let dot = topology_dot(&report);
+        assert!(dot.contains("    \"0xa\" -- \"0xb\";\n"));
+        assert!(dot.contains("\"0xd\" [label=\"0xd\", color=gray];"));
+        assert!(dot
+            .contains("\"http://10.0.0.4:1337\" [label=\"http://10.0.0.4:1337\", style=dashed];"));
+    }
+}
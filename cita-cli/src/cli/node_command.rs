@@ -0,0 +1,210 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::{parse_url, JsonRpcParams, JsonRpcResponse, ParamsValue, Quantity, ToolError};
+
+use crate::cli::chain_command::result_value;
+use crate::cli::{get_url, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Node fleet command
+pub fn node_command() -> App<'static, 'static> {
+    App::new("node").about("Check a fleet of nodes").subcommand(
+        SubCommand::with_name("health")
+            .about(
+                "Check the reachability, height and peer count of every node concurrently, \
+                 fail if any node is unreachable or behind the highest one",
+            )
+            .arg(
+                Arg::with_name("urls")
+                    .long("urls")
+                    .takes_value(true)
+                    .multiple(true)
+                    .use_delimiter(true)
+                    .validator(|url| parse_url(url.as_ref()).map(|_| ()))
+                    .help("The nodes to check, separated by commas, default is the url"),
+            )
+            .arg(
+                Arg::with_name("max-lag")
+                    .long("max-lag")
+                    .takes_value(true)
+                    .default_value("10")
+                    .validator(|lag| parse_u64(lag.as_str()).map(|_| ()))
+                    .help("Blocks a node may be behind the highest node"),
+            )
+            .arg(
+                Arg::with_name("json")
+                    .long("json")
+                    .help("Print the report as json instead of a table"),
+            ),
+    )
+}
+
+/// Node fleet processor
+pub fn node_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client.set_debug(debug);
+
+    match sub_matches.subcommand() {
+        ("health", Some(m)) => {
+            let urls = node_urls(m, config);
+            let max_lag = parse_u64(m.value_of("max-lag").unwrap())?;
+            let nodes = health(&client, &urls).map_err(|err| format!("{}", err))?;
+            let report = health_report(&nodes, max_lag);
+
+            if m.is_present("json") {
+                printer.println(&report, !m.is_present("no-color") && config.color());
+            } else {
+                println!("{}", health_table(&nodes, max_lag));
+            }
+            let unhealthy = report["unhealthy"].as_u64().unwrap_or_default();
+            config.set("result".to_string(), report);
+            if unhealthy > 0 {
+                return Err(format!(
+                    "{} of {} nodes are unreachable or behind by more than {} blocks",
+                    unhealthy,
+                    nodes.len(),
+                    max_lag
+                ));
+            }
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    }
+    Ok(())
+}
+
+/// The state of a node, `error` is set when it's unreachable
+pub(crate) struct NodeHealth {
+    pub(crate) url: String,
+    pub(crate) height: Option<u64>,
+    pub(crate) peers: Option<u64>,
+    pub(crate) error: Option<String>,
+}
+
+/// Send the request without params to every node at once
+pub(crate) fn ask_nodes(
+    client: &Client,
+    urls: &[String],
+    method: &str,
+) -> Result<Vec<Result<JsonRpcResponse, ToolError>>, ToolError> {
+    let params = JsonRpcParams::new()
+        .insert("jsonrpc", ParamsValue::String("2.0".to_string()))
+        .insert("method", ParamsValue::String(method.to_string()))
+        .insert("params", ParamsValue::List(Vec::new()))
+        .insert("id", ParamsValue::Int(1));
+    client.send_request_to_nodes(urls.iter().map(|url| url.parse().unwrap()), params)
+}
+
+/// The urls of `--urls`, the url when absent
+pub(crate) fn node_urls(m: &ArgMatches, config: &GlobalConfig) -> Vec<String> {
+    match m.values_of("urls") {
+        Some(urls) => urls.map(str::to_owned).collect(),
+        None => vec![get_url(m, config).to_owned()],
+    }
+}
+
+/// Ask every node for its height and peer count at once
+pub(crate) fn health(client: &Client, urls: &[String]) -> Result<Vec<NodeHealth>, ToolError> {
+    let heights = ask_nodes(client, urls, "blockNumber")?;
+    let peers = ask_nodes(client, urls, "peerCount")?;
+
+    Ok(urls
+        .iter()
+        .zip(heights.into_iter().zip(peers))
+        .map(|(url, (height, peers))| {
+            let quantity = |response| {
+                Quantity::from_json(&result_value(response?)?)
+                    .ok_or_else(|| ToolError::Customize("Not a quantity".to_string()))?
+                    .as_u64()
+            };
+            let height = quantity(height);
+            let peers = quantity(peers);
+            NodeHealth {
+                url: url.clone(),
+                error: height
+                    .as_ref()
+                    .err()
+                    .or_else(|| peers.as_ref().err())
+                    .map(|err| format!("{}", err)),
+                height: height.ok(),
+                peers: peers.ok(),
+            }
+        })
+        .collect())
+}
+
+/// Blocks the node is behind the highest node, None if unreachable
+pub(crate) fn lag(nodes: &[NodeHealth], node: &NodeHealth) -> Option<u64> {
+    let max = nodes.iter().filter_map(|node| node.height).max()?;
+    node.height.map(|height| max - height)
+}
+
+fn is_healthy(nodes: &[NodeHealth], node: &NodeHealth, max_lag: u64) -> bool {
+    node.error.is_none() && lag(nodes, node).is_some_and(|lag| lag <= max_lag)
+}
+
+fn health_report(nodes: &[NodeHealth], max_lag: u64) -> Value {
+    json!({
+        "maxHeight": nodes.iter().filter_map(|node| node.height).max(),
+        "unhealthy": nodes.iter().filter(|node| !is_healthy(nodes, node, max_lag)).count(),
+        "nodes": nodes
+            .iter()
+            .map(|node| json!({
+                "url": node.url,
+                "healthy": is_healthy(nodes, node, max_lag),
+                "height": node.height,
+                "peerCount": node.peers,
+                "lag": lag(nodes, node),
+                "error": node.error,
+            }))
+            .collect::<Vec<Value>>(),
+    })
+}
+
+fn health_table(nodes: &[NodeHealth], max_lag: u64) -> String {
+    let width = nodes
+        .iter()
+        .map(|node| node.url.len())
+        .max()
+        .unwrap_or_default()
+        .max(3);
+    let show = |value: Option<u64>| value.map_or_else(|| "-".to_string(), |v| v.to_string());
+    let mut table = format!(
+        "{:<width$}  {:<9}  {:>10}  {:>5}  {:>6}",
+        "URL",
+        "STATUS",
+        "HEIGHT",
+        "PEERS",
+        "LAG",
+        width = width
+    );
+    for node in nodes {
+        let status = if node.error.is_some() {
+            "down"
+        } else if is_healthy(nodes, node, max_lag) {
+            "ok"
+        } else {
+            "behind"
+        };
+        table.push_str(&format!(
+            "\n{:<width$}  {:<9}  {:>10}  {:>5}  {:>6}",
+            node.url,
+            status,
+            show(node.height),
+            show(node.peers),
+            show(lag(nodes, node)),
+            width = width
+        ));
+        if let Some(ref error) = node.error {
+            table.push_str(&format!("  {}", error));
+        }
+    }
+    table
+}
@@ -1,19 +1,31 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
-use serde_json::json;
+use serde_json::{json, Value};
 
 use cita_tool::client::basic::{Client, Transfer};
-use cita_tool::{JsonRpcParams, ParamsValue, TransactionOptions};
+use cita_tool::client::system_contract::{QuotaManageClient, QuotaManagementExt};
+use cita_tool::client::watch::{BlockEvent, BlockWatcher};
+use cita_tool::client::{bench_series_csv, BenchRecorder, BenchSecond};
+use cita_tool::{
+    Encryption, JsonRpcParams, KeyPair, LowerHex, ParamsValue, PrivateKey, TransactionOptions,
+    UnverifiedTransaction, U256,
+};
+
+use crate::cli::key_command::read_key_file;
 
 use crate::cli::{
-    encryption, get_url, is_hex, key_validator, parse_address, parse_privkey, parse_u256,
-    parse_u32, parse_u64, search_app,
+    encryption, get_url, is_hex, key_validator, parse_address, parse_local_privkey, parse_privkey,
+    parse_u256, parse_u32, parse_u64, search_app,
 };
 use crate::interactive::{set_output, GlobalConfig};
 use crate::printer::Printer;
 
 use std::collections::BTreeSet;
+use std::fs;
 use std::io;
-use std::time::SystemTime;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 /// Search command tree
 pub fn search_command() -> App<'static, 'static> {
@@ -55,12 +67,7 @@ pub fn fuzzy_match(x: &str, y: &str) -> bool {
         }
     }
 
-    for j in 0..len_a {
-        if matrix[len_pat - 1][j] != 0 {
-            return true;
-        }
-    }
-    false
+    matrix[len_pat - 1].iter().any(|&matched| matched != 0)
 }
 
 /// judge if y in x
@@ -100,7 +107,7 @@ pub fn search_processor<'a, 'b>(app: &App<'a, 'b>, sub_matches: &ArgMatches) {
                 if cmd_lower.contains(keyword) {
                     cmd_lower.contains(keyword)
                 } else {
-                    fuzzy_match(&keyword, &cmd_lower)
+                    fuzzy_match(keyword, &cmd_lower)
                 }
             })
         })
@@ -187,7 +194,10 @@ pub fn benchmark_command() -> App<'static, 'static> {
         .subcommand(SubCommand::with_name("get-height").about("Send 1,000 query height requests"))
         .subcommand(
             SubCommand::with_name("sendTransaction")
-                .about("Send the same transaction at the same time and send n times")
+                .about(
+                    "Send the transaction n times and follow them until they're in blocks, \
+                     report the throughput and the confirmation latencies",
+                )
                 .arg(
                     Arg::with_name("code")
                         .long("code")
@@ -225,7 +235,7 @@ pub fn benchmark_command() -> App<'static, 'static> {
                     Arg::with_name("private-key")
                         .long("private-key")
                         .takes_value(true)
-                        .required(true)
+                        .required_unless("keys")
                         .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
                         .help("The private key of transaction"),
                 )
@@ -257,6 +267,99 @@ pub fn benchmark_command() -> App<'static, 'static> {
                         .default_value("1000")
                         .validator(|version| parse_u32(version.as_str()).map(|_| ()))
                         .help("The number of transmissions, default is 1000"),
+                )
+                .args(&bench_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("mix")
+                .about(
+                    "Send a mix of transfers, contract calls and deploys by the weights of \
+                     a profile, report the statistics of every kind",
+                )
+                .arg(
+                    Arg::with_name("profile")
+                        .long("profile")
+                        .takes_value(true)
+                        .required(true)
+                        .help(
+                            "Toml file of the workloads, `[[workload]]` tables of `kind` \
+                             (transfer, call or deploy), `weight`, and `to` and `value`, \
+                             `address` and `data`, or `code`, an optional `name` tells \
+                             the workloads of the same kind apart",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .required_unless("keys")
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transactions"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("number")
+                        .long("number")
+                        .takes_value(true)
+                        .default_value("1000")
+                        .validator(|number| parse_u32(number.as_str()).map(|_| ()))
+                        .help("The number of transactions of all the workloads"),
+                )
+                .args(&bench_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("fund")
+                .about(
+                    "Transfer the initial balance and set the quota limit of every account \
+                     in the key file, to prepare the identities of a load test",
+                )
+                .arg(
+                    Arg::with_name("keys")
+                        .long("keys")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The key file written by `key create --output`"),
+                )
+                .arg(
+                    Arg::with_name("password")
+                        .long("password")
+                        .takes_value(true)
+                        .help("The password of the encrypted key file"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the funder"),
+                )
+                .arg(
+                    Arg::with_name("value")
+                        .long("value")
+                        .takes_value(true)
+                        .validator(|value| parse_u256(value.as_ref()).map(|_| ()))
+                        .help("The balance to transfer to every account"),
+                )
+                .arg(
+                    Arg::with_name("aql")
+                        .long("aql")
+                        .takes_value(true)
+                        .validator(|aql| parse_u256(aql.as_ref()).map(|_| ()))
+                        .help("Set the account quota limit of every account, the funder must be admin"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
                 ),
         )
 }
@@ -303,9 +406,7 @@ pub fn benchmark_processor(
             if let Some(chain_id) = m.value_of("chain-id").map(|s| parse_u256(s).unwrap()) {
                 client.set_chain_id(chain_id);
             }
-            if let Some(private_key) = m.value_of("private-key") {
-                client.set_private_key(&parse_privkey(private_key, encryption)?);
-            }
+            let mut signer = BenchSigner::new(m, encryption)?;
             let code = m.value_of("code").unwrap();
             let address = m.value_of("address").unwrap();
             let current_height = m.value_of("height").map(|s| parse_u64(s).unwrap());
@@ -328,27 +429,96 @@ pub fn benchmark_processor(
 
             let mut txs = Vec::with_capacity(number as usize);
             for _ in 0..number {
-                let tx = client
-                    .generate_transaction(tx_options)
-                    .map_err(|err| format!("{}", err))?;
-                let byte_code = client
-                    .generate_sign_transaction(&tx)
+                txs.push(signer.sign(&mut client, "transaction", tx_options)?);
+            }
+            bench(m, &client, txs, printer)?;
+        }
+        ("mix", Some(m)) => {
+            let mut signer = BenchSigner::new(m, encryption(m, config))?;
+            let workloads = load_mix(m.value_of("profile").unwrap())?;
+            let number = parse_u32(m.value_of("number").unwrap())?;
+            let quota = m.value_of("quota").map(|s| parse_u64(s).unwrap());
+            let current_height = client
+                .get_current_height()
+                .map_err(|err| format!("{}", err))?;
+
+            let weights = workloads
+                .iter()
+                .map(|workload| workload.weight)
+                .collect::<Vec<u32>>();
+            let mut txs = Vec::with_capacity(number as usize);
+            for index in mix_order(&weights, number as usize) {
+                let workload = &workloads[index];
+                let tx_options = TransactionOptions::new()
+                    .set_code(&workload.code)
+                    .set_address(&workload.address)
+                    .set_current_height(Some(current_height))
+                    .set_quota(quota)
+                    .set_value(workload.value);
+                txs.push(signer.sign(&mut client, &workload.name, tx_options)?);
+            }
+            bench(m, &client, txs, printer)?;
+        }
+        ("fund", Some(m)) => {
+            let encryption = encryption(m, config);
+            client.set_private_key(&parse_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption,
+            )?);
+            let keys = read_key_file(
+                m.value_of("keys").unwrap(),
+                m.value_of("password"),
+                encryption,
+            )?;
+            let addresses: Vec<String> = keys
+                .into_iter()
+                .map(|key| {
+                    KeyPair::from_privkey(key)
+                        .address()
+                        .completed_lower_hex_with_0x()
+                })
+                .collect();
+            let quota = m.value_of("quota").map(|s| parse_u64(s).unwrap());
+            let mut report = json!({ "accounts": addresses });
+
+            if let Some(value) = m.value_of("value").map(|value| parse_u256(value).unwrap()) {
+                let current_height = client
+                    .get_current_height()
                     .map_err(|err| format!("{}", err))?;
-                let params = JsonRpcParams::new()
-                    .insert(
-                        "method",
-                        ParamsValue::String(String::from("sendRawTransaction")),
-                    )
-                    .insert(
-                        "params",
-                        ParamsValue::List(vec![ParamsValue::String(byte_code)]),
+                let mut txs = Vec::with_capacity(addresses.len());
+                for address in &addresses {
+                    let tx_options = TransactionOptions::new()
+                        .set_address(address)
+                        .set_current_height(Some(current_height))
+                        .set_quota(quota)
+                        .set_value(Some(value));
+                    let tx = client
+                        .generate_transaction(tx_options)
+                        .map_err(|err| format!("{}", err))?;
+                    txs.push(
+                        client
+                            .generate_sign_transaction(&tx)
+                            .map_err(|err| format!("{}", err))?,
                     );
-                txs.push(params);
+                }
+                // Unlike the load, the funding moves value, so it gets the checks of
+                // every sending command
+                let result = client
+                    .send_signed_transactions(&txs.iter().map(String::as_str).collect::<Vec<_>>())
+                    .map_err(|err| format!("{}", err))?;
+                report["transfers"] = json!(result);
             }
-            let result = client
-                .send_request(txs.into_iter())
-                .map_err(|err| format!("{}", err))?;
-            printer.println(&json!(result), true);
+
+            if let Some(aql) = m.value_of("aql").map(|aql| parse_u256(aql).unwrap()) {
+                let quota_manager: QuotaManageClient<Client> = QuotaManagementExt::create(client);
+                let result = addresses
+                    .iter()
+                    .map(|address| quota_manager.set_aql(address, aql, quota))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("{}", err))?;
+                report["aql"] = json!(result);
+            }
+            printer.println(&report, true);
         }
         _ => return Err(sub_matches.usage().to_owned()),
     }
@@ -356,6 +526,321 @@ pub fn benchmark_processor(
     Ok(())
 }
 
+/// `--keys`, `--password`, `--rate`, `--timeout` and `--series` of the benchmarks sending
+/// transactions
+fn bench_args() -> [Arg<'static, 'static>; 5] {
+    [
+        Arg::with_name("keys").long("keys").takes_value(true).help(
+            "Sign the transactions round-robin by the pre-funded keys of the key file \
+                 written by `key create --output`, instead of the private key",
+        ),
+        Arg::with_name("password")
+            .long("password")
+            .takes_value(true)
+            .requires("keys")
+            .help("The password of the encrypted key file"),
+        Arg::with_name("rate")
+            .long("rate")
+            .takes_value(true)
+            .validator(|rate| parse_u32(rate.as_str()).map(|_| ()))
+            .help("Send this many transactions a second, default is all at once"),
+        Arg::with_name("timeout")
+            .long("timeout")
+            .takes_value(true)
+            .default_value("60")
+            .validator(|timeout| parse_u64(timeout.as_str()).map(|_| ()))
+            .help("Seconds to wait for the confirmations after the last submission"),
+        Arg::with_name("series")
+            .long("series")
+            .takes_value(true)
+            .help(
+                "Write the counts and latencies of every second to the file, \
+                 CSV when it ends with .csv, JSON otherwise",
+            ),
+    ]
+}
+
+/// A signed transaction of a benchmark
+struct BenchTx {
+    kind: String,
+    sender: String,
+    transaction: UnverifiedTransaction,
+    params: JsonRpcParams,
+}
+
+/// Sign the transactions of a benchmark round-robin by its keys, the keys of `--keys` or
+/// the private key
+struct BenchSigner {
+    /// The keys and their addresses
+    keys: Vec<(PrivateKey, String)>,
+    next: usize,
+}
+
+impl BenchSigner {
+    fn new(m: &ArgMatches, encryption: Encryption) -> Result<Self, String> {
+        let keys = match m.value_of("keys") {
+            Some(path) => read_key_file(path, m.value_of("password"), encryption)?,
+            None => vec![parse_local_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption,
+            )?],
+        };
+        if keys.is_empty() {
+            return Err("No key in the key file".to_string());
+        }
+        let keys = keys
+            .into_iter()
+            .map(|key| {
+                let address = KeyPair::from_privkey(key)
+                    .address()
+                    .completed_lower_hex_with_0x();
+                (key, address)
+            })
+            .collect();
+        Ok(BenchSigner { keys, next: 0 })
+    }
+
+    /// The signed `sendRawTransaction` of the options by the next key
+    fn sign(
+        &mut self,
+        client: &mut Client,
+        kind: &str,
+        tx_options: TransactionOptions,
+    ) -> Result<BenchTx, String> {
+        let (key, sender) = &self.keys[self.next % self.keys.len()];
+        self.next += 1;
+        client.set_private_key(key);
+        let tx = client
+            .generate_transaction(tx_options)
+            .map_err(|err| format!("{}", err))?;
+        let byte_code = client
+            .generate_sign_transaction(&tx)
+            .map_err(|err| format!("{}", err))?;
+        let transaction =
+            UnverifiedTransaction::from_str(&byte_code).map_err(|err| format!("{}", err))?;
+        // The policy vetoes before the run, not by failing its submissions
+        client
+            .check_policy(&transaction)
+            .map_err(|err| format!("{}", err))?;
+        let params = JsonRpcParams::new()
+            .insert(
+                "method",
+                ParamsValue::String(String::from("sendRawTransaction")),
+            )
+            .insert(
+                "params",
+                ParamsValue::List(vec![ParamsValue::String(byte_code)]),
+            );
+        Ok(BenchTx {
+            kind: kind.to_owned(),
+            sender: sender.clone(),
+            transaction,
+            params,
+        })
+    }
+}
+
+/// Run the benchmark of the transactions, write the series and print the summary by their
+/// kinds and senders
+fn bench(
+    m: &ArgMatches,
+    client: &Client,
+    txs: Vec<BenchTx>,
+    printer: &Printer,
+) -> Result<(), String> {
+    let rate = m.value_of("rate").map(|rate| parse_u32(rate).unwrap());
+    let timeout = Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?);
+    let recorder = run_bench(client, txs, rate, timeout, printer)?;
+
+    let series = recorder.series();
+    if let Some(path) = m.value_of("series") {
+        let content = if path.ends_with(".csv") {
+            bench_series_csv(&series)
+        } else {
+            serde_json::to_string_pretty(&series).unwrap()
+        };
+        fs::write(path, content).map_err(|err| format!("Can't write {}: {}", path, err))?;
+    }
+    let total = |count: fn(&BenchSecond) -> u64| series.iter().map(count).sum::<u64>();
+    let seconds = series.len().max(1) as f64;
+    printer.println(
+        &json!({
+            "submitted": total(|second| second.submitted),
+            "accepted": total(|second| second.accepted),
+            "rejected": total(|second| second.rejected),
+            "confirmed": total(|second| second.confirmed),
+            "unconfirmed": recorder.pending(),
+            "seconds": series.len(),
+            "confirmedTps": total(|second| second.confirmed) as f64 / seconds,
+            "latency": recorder.latency(),
+            "kinds": recorder.kinds(),
+            "senders": recorder.senders(),
+        }),
+        true,
+    );
+    Ok(())
+}
+
+/// A workload of `benchmark mix`
+struct Workload {
+    name: String,
+    weight: u32,
+    code: String,
+    address: String,
+    value: Option<U256>,
+}
+
+/// The workloads of the toml profile
+fn load_mix(path: &str) -> Result<Vec<Workload>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let profile: Value =
+        toml::from_str(&content).map_err(|err| format!("Invalid profile {}: {}", path, err))?;
+    let workloads = profile["workload"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default()
+        .iter()
+        .map(|workload| {
+            let text = |key: &str| workload[key].as_str().map(str::to_owned);
+            let kind = text("kind").unwrap_or_default();
+            let required =
+                |key: &str| text(key).ok_or_else(|| format!("A {} workload needs `{}`", kind, key));
+            let (code, address) = match kind.as_str() {
+                "transfer" => ("0x".to_string(), required("to")?),
+                "call" => (required("data")?, required("address")?),
+                "deploy" => (required("code")?, "0x".to_string()),
+                _ => return Err(format!("Unknown workload kind `{}`", kind)),
+            };
+            let value = match workload["value"] {
+                Value::Null => None,
+                Value::String(ref value) => Some(parse_u256(value)?),
+                ref value => Some(parse_u256(&value.to_string())?),
+            };
+            Ok(Workload {
+                name: text("name").unwrap_or_else(|| kind.clone()),
+                weight: workload["weight"].as_u64().unwrap_or(1) as u32,
+                code,
+                address,
+                value,
+            })
+        })
+        .collect::<Result<Vec<Workload>, String>>()?;
+    if workloads.iter().all(|workload| workload.weight == 0) {
+        return Err(format!("No workload of a weight in {}", path));
+    }
+    Ok(workloads)
+}
+
+/// The workload of every transaction, interleaved by the smooth weighted round-robin,
+/// so every prefix of the order has about the proportions of the weights
+fn mix_order(weights: &[u32], number: usize) -> Vec<usize> {
+    let total = weights.iter().map(|weight| i64::from(*weight)).sum::<i64>();
+    let mut current = vec![0i64; weights.len()];
+    (0..number)
+        .map(|_| {
+            for (current, weight) in current.iter_mut().zip(weights) {
+                *current += i64::from(*weight);
+            }
+            let chosen = (0..weights.len())
+                .max_by_key(|index| (current[*index], std::cmp::Reverse(*index)))
+                .unwrap();
+            current[chosen] -= total;
+            chosen
+        })
+        .collect()
+}
+
+/// Send the transactions, a batch of `rate` every second, poll the new blocks until all
+/// the accepted ones are confirmed or the timeout after the last submission
+fn run_bench(
+    client: &Client,
+    mut txs: Vec<BenchTx>,
+    rate: Option<u32>,
+    timeout: Duration,
+    printer: &Printer,
+) -> Result<BenchRecorder, String> {
+    let mut watcher = BlockWatcher::new(client.clone()).set_window_size(1);
+    // From the current height, the blocks before the first submission have none of them
+    watcher.poll().map_err(|err| format!("{}", err))?;
+    let started = Instant::now();
+    let mut recorder = BenchRecorder::new(started);
+    let mut last_submission = started;
+
+    loop {
+        let tick = Instant::now();
+        if !txs.is_empty() {
+            let size = rate.map_or(txs.len(), |rate| (rate as usize).min(txs.len()));
+            let mut senders = Vec::with_capacity(size);
+            let mut signed = Vec::with_capacity(size);
+            let mut batch = Vec::with_capacity(size);
+            for tx in txs.drain(..size) {
+                senders.push((tx.kind, tx.sender));
+                signed.push(tx.transaction);
+                batch.push(tx.params);
+            }
+            for (kind, sender) in &senders {
+                recorder.submit(tick, kind, sender);
+            }
+            let responses = client.send_request(batch.into_iter());
+            for (index, transaction) in signed.iter().enumerate() {
+                client.report_sent(
+                    transaction,
+                    responses.as_ref().map(|responses| &responses[index]),
+                );
+            }
+            match responses {
+                Ok(responses) => {
+                    let now = Instant::now();
+                    for ((kind, sender), response) in senders.iter().zip(responses) {
+                        let hash = match response.error() {
+                            Some(err) => Err(err.message()),
+                            None => serde_json::to_value(response.result())
+                                .ok()
+                                .and_then(|result| result["hash"].as_str().map(str::to_owned))
+                                .ok_or_else(|| "No transaction hash".to_string()),
+                        };
+                        match hash {
+                            Ok(hash) => recorder.accept(now, kind, sender, &hash, tick),
+                            Err(err) => recorder.reject(now, kind, sender, &err),
+                        }
+                    }
+                }
+                Err(err) => {
+                    let now = Instant::now();
+                    for (kind, sender) in &senders {
+                        recorder.reject(now, kind, sender, "Submission failed");
+                    }
+                    printer.eprintln(&Rc::new(format!("Submission failed: {}", err)), true);
+                }
+            }
+            last_submission = Instant::now();
+        }
+
+        for event in watcher.poll().map_err(|err| format!("{}", err))? {
+            if let BlockEvent::NewBlock { block, .. } = event {
+                let now = Instant::now();
+                for hash in block["body"]["transactions"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                {
+                    recorder.confirm(now, hash);
+                }
+            }
+        }
+        if txs.is_empty() && (recorder.pending() == 0 || last_submission.elapsed() >= timeout) {
+            return Ok(recorder);
+        }
+        let next = tick + Duration::from_secs(1);
+        let now = Instant::now();
+        if next > now {
+            thread::sleep(next - now);
+        }
+    }
+}
+
 // Generate completion scripts
 pub fn completion_command() -> App<'static, 'static> {
     App::new("completions")
@@ -373,20 +858,28 @@ pub fn completion_processor(app: &mut App, sub_matches: &ArgMatches) {
 }
 #[cfg(test)]
 mod test {
-    use super::string_include;
+    use super::{mix_order, string_include};
 
     #[test]
     fn test_string_include() {
-        assert_eq!(string_include("abcdef", "ace"), true);
-        assert_eq!(string_include("abcdef", "acc"), false);
-        assert_eq!(string_include("abcdef", "ack"), false);
-        assert_eq!(string_include("ads fety", "af"), true);
-        assert_eq!(string_include("ads fety", "ta"), false);
-        assert_eq!(string_include("ads fety", "sa"), false);
-        assert_eq!(string_include("ads fety", "yf"), false);
-        assert_eq!(string_include("ads fety", "fy"), true);
-        assert_eq!(string_include("ads fety", "a-"), false);
-        assert_eq!(string_include("ads fety", "  "), false);
-        assert_eq!(string_include("ads fety", " f"), true);
+        assert!(string_include("abcdef", "ace"));
+        assert!(!string_include("abcdef", "acc"));
+        assert!(!string_include("abcdef", "ack"));
+        assert!(string_include("ads fety", "af"));
+        assert!(!string_include("ads fety", "ta"));
+        assert!(!string_include("ads fety", "sa"));
+        assert!(!string_include("ads fety", "yf"));
+        assert!(string_include("ads fety", "fy"));
+        assert!(!string_include("ads fety", "a-"));
+        assert!(!string_include("ads fety", "  "));
+        assert!(string_include("ads fety", " f"));
+    }
+
+    #[test]
+    fn test_mix_order() {
+        let order = mix_order(&[5, 3, 2], 10);
+        assert_eq!(order, vec![0, 1, 2, 0, 0, 1, 0, 2, 1, 0]);
+        assert_eq!(order.iter().filter(|index| **index == 1).count(), 3);
+        assert_eq!(mix_order(&[1, 0], 3), vec![0, 0, 0]);
     }
 }
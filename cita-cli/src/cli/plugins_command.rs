@@ -0,0 +1,28 @@
+use clap::{App, ArgMatches};
+use serde_json::{json, Map};
+
+use crate::interactive::GlobalConfig;
+use crate::plugin;
+use crate::printer::Printer;
+
+/// Plugins command
+pub fn plugins_command() -> App<'static, 'static> {
+    App::new("plugins").about(
+        "The plugins, the `cita-cli-<name>` executables on PATH run by `cita-cli <name>`, \
+         with the url and the other settings in CITA_CLI_* variables",
+    )
+}
+
+/// Plugins processor
+pub fn plugins_processor(
+    _sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+) -> Result<(), String> {
+    let plugins = plugin::list()
+        .into_iter()
+        .map(|(name, path)| (name, json!(path.display().to_string())))
+        .collect::<Map<_, _>>();
+    printer.println(&json!(plugins), config.color());
+    Ok(())
+}
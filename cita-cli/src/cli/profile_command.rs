@@ -0,0 +1,100 @@
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::json;
+
+use crate::cli::{parse_u64, read_password};
+use crate::interactive::GlobalConfig;
+use crate::key_agent;
+use crate::printer::Printer;
+use crate::profile;
+
+/// Profile command
+pub fn profile_command() -> App<'static, 'static> {
+    App::new("profile")
+        .about(
+            "Encrypt the profile and the address book at rest by a master passphrase, \
+             it's read from CITA_CLI_PASSPHRASE, the key agent or a prompt at the startup",
+        )
+        .subcommand(
+            SubCommand::with_name("encrypt")
+                .about("Encrypt ~/.cita-cli/config and ~/.cita-cli/address_book")
+                .arg(
+                    Arg::with_name("passphrase")
+                        .long("passphrase")
+                        .takes_value(true)
+                        .help("The new master passphrase, prompted twice if absent"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("decrypt")
+                .about("Keep the profile and the address book in plain"),
+        )
+        .subcommand(
+            SubCommand::with_name("unlock")
+                .about(
+                    "Cache the master passphrase in the key agent, the commands read the \
+                     profile without asking for it until the agent expires or `key lock`",
+                )
+                .arg(
+                    Arg::with_name("ttl")
+                        .long("ttl")
+                        .takes_value(true)
+                        .default_value("900")
+                        .validator(|ttl| parse_u64(&ttl).map(|_| ()))
+                        .help("Seconds to keep the passphrase, extended by later unlocks"),
+                ),
+        )
+}
+
+/// Profile processor
+pub fn profile_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+) -> Result<(), String> {
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let result = match sub_matches.subcommand() {
+        ("encrypt", Some(m)) => {
+            let passphrase = match m.value_of("passphrase") {
+                Some(passphrase) => passphrase.to_owned(),
+                None => {
+                    let passphrase = read_password("New master passphrase")?;
+                    if read_password("Repeat the master passphrase")? != passphrase {
+                        return Err("The passphrases don't match".to_string());
+                    }
+                    passphrase
+                }
+            };
+            if passphrase.is_empty() {
+                return Err("Empty master passphrase".to_string());
+            }
+            let mut encrypted = Vec::new();
+            for path in profile::files() {
+                if profile::seal(&path, Some(&passphrase))? {
+                    encrypted.push(path.display().to_string());
+                }
+            }
+            profile::set_passphrase(Some(passphrase));
+            json!({ "encrypted": encrypted })
+        }
+        ("decrypt", _) => {
+            let mut decrypted = Vec::new();
+            for path in profile::files() {
+                if profile::is_encrypted(&path) && profile::seal(&path, None)? {
+                    decrypted.push(path.display().to_string());
+                }
+            }
+            json!({ "decrypted": decrypted })
+        }
+        ("unlock", Some(m)) => {
+            let passphrase = profile::passphrase()?;
+            // Checked before it's cached
+            for path in profile::files() {
+                profile::read(&path)?;
+            }
+            key_agent::unlock_passphrase(&passphrase, parse_u64(m.value_of("ttl").unwrap())?)?
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&result, is_color);
+    Ok(())
+}
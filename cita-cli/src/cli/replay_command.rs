@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::{App, Arg, ArgMatches};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::{
+    encode, pubkey_to_address, Encryption, LowerHex, PrivateKey, ProtoMessage, ToolError,
+    TransactionOptions, UnverifiedTransaction, U256,
+};
+
+use crate::cli::{encryption, get_url, key_validator, parse_local_privkey};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// The target height is queried again after this long, for the valid until block
+const HEIGHT_REFRESH: Duration = Duration::from_secs(30);
+
+/// Replay command
+pub fn replay_command() -> App<'static, 'static> {
+    App::new("replay")
+        .about(
+            "Re-sign exported transactions by mapped test keys and send them to the target \
+             chain, at the original relative timing or at max speed",
+        )
+        .arg(
+            Arg::with_name("file")
+                .long("file")
+                .required(true)
+                .takes_value(true)
+                .help(
+                    "JSONL of the transactions, one getTransaction result per line, the \
+                     optional `timestamp` in milliseconds sets the timing, `-` is stdin",
+                ),
+        )
+        .arg(
+            Arg::with_name("keys")
+                .long("keys")
+                .takes_value(true)
+                .help("JSON file mapping the original senders to their test private keys"),
+        )
+        .arg(
+            Arg::with_name("default-key")
+                .long("default-key")
+                .takes_value(true)
+                .validator(|key| key_validator(key.as_str()).map(|_| ()))
+                .help("The private key signing for the senders not in the key map"),
+        )
+        .arg(
+            Arg::with_name("speed")
+                .long("speed")
+                .takes_value(true)
+                .default_value("1")
+                .validator(|speed| match speed.parse::<f64>() {
+                    Ok(speed) if speed > 0.0 && speed.is_finite() => Ok(()),
+                    _ => Err(format!("Invalid speed: {}", speed)),
+                })
+                .help("Replay this many times faster than the original timing"),
+        )
+        .arg(
+            Arg::with_name("max-speed")
+                .long("max-speed")
+                .help("Ignore the original timing, send the transactions one after another"),
+        )
+}
+
+/// Replay processor
+pub fn replay_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+    let encryption = encryption(sub_matches, config);
+
+    let keys = load_keys(sub_matches.value_of("keys"), encryption)?;
+    let default_key = sub_matches
+        .value_of("default-key")
+        .map(|key| parse_local_privkey(key, encryption))
+        .transpose()?;
+    if keys.is_empty() && default_key.is_none() {
+        return Err("No key to sign the transactions, set --keys or --default-key".to_string());
+    }
+    let speed = if sub_matches.is_present("max-speed") {
+        None
+    } else {
+        Some(
+            sub_matches
+                .value_of("speed")
+                .unwrap()
+                .parse::<f64>()
+                .unwrap(),
+        )
+    };
+
+    let reader: Box<dyn BufRead> = match sub_matches.value_of("file").unwrap() {
+        "-" => Box::new(BufReader::new(io::stdin())),
+        path => Box::new(BufReader::new(
+            fs::File::open(path).map_err(|err| format!("Can't open {}: {}", path, err))?,
+        )),
+    };
+
+    let version = client
+        .chain_version()
+        .map_err(|err| format!("{}", err))?
+        .protocol;
+    let mut height = client
+        .get_current_height()
+        .map_err(|err| format!("{}", err))?;
+    let mut height_at = Instant::now();
+    let started = Instant::now();
+    let mut first_timestamp = None;
+    let (mut sent, mut skipped, mut failed) = (0u64, 0u64, 0u64);
+
+    for (number, line) in reader.lines().enumerate() {
+        let line = line.map_err(|err| format!("{}", err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let warn = |message: String| {
+            printer.eprintln(&Rc::new(format!("Line {}: {}", number + 1, message)), true)
+        };
+        let record: Value = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(err) => {
+                warn(format!("{}", err));
+                skipped += 1;
+                continue;
+            }
+        };
+
+        // Keep the original gaps between the transactions, scaled by the speed
+        if let (Some(speed), Some(timestamp)) = (speed, record["timestamp"].as_u64()) {
+            let first = *first_timestamp.get_or_insert(timestamp);
+            let due = started
+                + Duration::from_secs_f64(timestamp.saturating_sub(first) as f64 / 1000.0 / speed);
+            let now = Instant::now();
+            if due > now {
+                thread::sleep(due - now);
+            }
+        }
+        if height_at.elapsed() >= HEIGHT_REFRESH {
+            if let Ok(current) = client.get_current_height() {
+                height = current;
+                height_at = Instant::now();
+            }
+        }
+
+        let result = resign(
+            &record,
+            &keys,
+            default_key,
+            encryption,
+            version,
+            height,
+            &client,
+        )
+        .and_then(|signed| match signed {
+            Some(signed) => client.send_signed_transaction(&signed).map(Some),
+            None => Ok(None),
+        });
+        match result {
+            Ok(Some(response)) => match response.error() {
+                Some(err) => {
+                    warn(err.message());
+                    failed += 1;
+                }
+                None => sent += 1,
+            },
+            Ok(None) => {
+                warn("No key for the sender".to_string());
+                skipped += 1;
+            }
+            Err(err) => {
+                warn(format!("{}", err));
+                failed += 1;
+            }
+        }
+    }
+
+    printer.println(
+        &json!({
+            "sent": sent,
+            "skipped": skipped,
+            "failed": failed,
+            "seconds": started.elapsed().as_secs(),
+        }),
+        is_color,
+    );
+    Ok(())
+}
+
+/// Load the key map of `{"sender address": "private key"}`
+fn load_keys(
+    path: Option<&str>,
+    encryption: Encryption,
+) -> Result<HashMap<String, PrivateKey>, String> {
+    let path = match path {
+        Some(path) => path,
+        None => return Ok(HashMap::new()),
+    };
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let keys: HashMap<String, String> =
+        serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))?;
+    keys.into_iter()
+        .map(|(address, key)| {
+            Ok((
+                address.to_lowercase(),
+                parse_local_privkey(&key, encryption)?,
+            ))
+        })
+        .collect()
+}
+
+/// Rebuild the transaction of the record for the target chain and sign it by the mapped key,
+/// None if no key signs for the sender
+fn resign(
+    record: &Value,
+    keys: &HashMap<String, PrivateKey>,
+    default_key: Option<PrivateKey>,
+    encryption: Encryption,
+    version: u32,
+    height: u64,
+    client: &Client,
+) -> Result<Option<String>, ToolError> {
+    let content = record["content"]
+        .as_str()
+        .ok_or_else(|| ToolError::Customize("No transaction content".to_string()))?;
+    let unverified = UnverifiedTransaction::from_str(content)?;
+    let sender = match record["from"].as_str() {
+        Some(from) => from.to_lowercase(),
+        None => unverified
+            .public_key(encryption)
+            .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+            .map_err(ToolError::Customize)?,
+    };
+    let key = match keys.get(&sender).cloned().or(default_key) {
+        Some(key) => key,
+        None => return Ok(None),
+    };
+
+    let tx = unverified.get_transaction();
+    let to = if tx.get_version() == 0 {
+        tx.get_to().to_owned()
+    } else {
+        encode(tx.get_to_v1())
+    };
+    let code = format!("0x{}", encode(tx.get_data()));
+    let value = tx.get_value();
+    let options = TransactionOptions::new()
+        .set_code(&code)
+        .set_address(&to)
+        .set_quota(Some(tx.get_quota()))
+        .set_value(if value.is_empty() {
+            None
+        } else {
+            Some(U256::from(value))
+        })
+        .set_version(Some(version))
+        .set_current_height(Some(height));
+    let signed = client
+        .generate_transaction(options)?
+        .build_unverified(key)
+        .write_to_bytes()
+        .map_err(ToolError::Proto)?;
+    Ok(Some(format!("0x{}", encode(signed))))
+}
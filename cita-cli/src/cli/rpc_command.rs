@@ -13,6 +13,7 @@ use crate::cli::{
 };
 use crate::interactive::{set_output, GlobalConfig};
 use crate::printer::Printer;
+use std::rc::Rc;
 use std::str::FromStr;
 
 /// Generate rpc sub command
@@ -86,6 +87,11 @@ pub fn rpc_command() -> App<'static, 'static> {
                         .takes_value(true)
                         .validator(|version| parse_u32(version.as_str()).map(|_| ()))
                         .help("The version of transaction, default is 0"),
+                )
+                .arg(
+                    Arg::with_name("simulate-first")
+                        .long("simulate-first")
+                        .help("Call the transaction first, don't send it if the call fails"),
                 ),
         )
         .subcommand(
@@ -271,6 +277,16 @@ pub fn rpc_command() -> App<'static, 'static> {
                         .takes_value(true)
                         .validator(|to| is_hex(to.as_ref()))
                         .help("Block height hex string, default is latest"),
+                )
+                .arg(
+                    Arg::with_name("chunk-size")
+                        .long("chunk-size")
+                        .takes_value(true)
+                        .validator(|size| parse_u64(size.as_ref()).map(|_| ()))
+                        .help(
+                            "Split the range into requests of this many blocks, \
+                             default is 1000, 0 means never split",
+                        ),
                 ),
         )
         .subcommand(
@@ -534,6 +550,14 @@ pub fn rpc_processor(
                 .set_quota(quota)
                 .set_value(value)
                 .set_version(version);
+            if m.is_present("simulate-first") {
+                let simulation = client
+                    .simulate(&tx_options, None)
+                    .map_err(|err| format!("{}", err))?;
+                if let Some(err) = simulation.error {
+                    return Err(format!("The transaction would fail, not sent: {}", err));
+                }
+            }
             client.send_raw_transaction(tx_options)
         }
         ("getBlockByHash", Some(m)) => {
@@ -562,12 +586,23 @@ pub fn rpc_processor(
             let hash = m.value_of("hash").unwrap();
             client.get_transaction_receipt(hash)
         }
-        ("call", Some(m)) => client.call(
-            m.value_of("from"),
-            m.value_of("to").unwrap(),
-            m.value_of("data"),
-            m.value_of("height").unwrap(),
-        ),
+        ("call", Some(m)) => {
+            let result = client.call(
+                m.value_of("from"),
+                m.value_of("to").unwrap(),
+                m.value_of("data"),
+                m.value_of("height").unwrap(),
+            );
+            let reason = result
+                .as_ref()
+                .ok()
+                .and_then(JsonRpcResponse::error)
+                .and_then(|err| err.revert_reason());
+            if let Some(reason) = reason {
+                printer.eprintln(&Rc::new(format!("Execution reverted: {}", reason)), true);
+            }
+            result
+        }
         ("getTransactionProof", Some(m)) => {
             client.get_transaction_proof(m.value_of("hash").unwrap())
         }
@@ -575,12 +610,17 @@ pub fn rpc_processor(
             let height = m.value_of("height").unwrap();
             client.get_metadata(height)
         }
-        ("getLogs", Some(m)) => client.get_logs(
-            m.values_of("topic").map(Iterator::collect),
-            m.values_of("address").map(Iterator::collect),
-            m.value_of("from"),
-            m.value_of("to"),
-        ),
+        ("getLogs", Some(m)) => {
+            if let Some(size) = m.value_of("chunk-size") {
+                client = client.set_log_chunk_size(parse_u64(size)?);
+            }
+            client.get_logs(
+                m.values_of("topic").map(Iterator::collect),
+                m.values_of("address").map(Iterator::collect),
+                m.value_of("from"),
+                m.value_of("to"),
+            )
+        }
         ("getTransaction", Some(m)) => {
             let encryption = encryption(m, config);
             let hash = m.value_of("hash").unwrap();
@@ -589,7 +629,7 @@ pub fn rpc_processor(
                 if let Ok(ref resp) = result {
                     if let Some(ResponseValue::Map(map)) = resp.result() {
                         if let Some(ParamsValue::String(content)) = map.get("content") {
-                            let tx = UnverifiedTransaction::from_str(&content).unwrap();
+                            let tx = UnverifiedTransaction::from_str(content).unwrap();
                             printer
                                 .println(&"---- [UnverifiedTransaction] ----".to_owned(), is_color);
                             printer.println(&tx.to_json(encryption)?, is_color);
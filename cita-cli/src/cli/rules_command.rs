@@ -0,0 +1,542 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::client::watch::LogWatcher;
+use cita_tool::client::TransactionOptions;
+use cita_tool::{encode_json_input, remove_0x};
+
+use crate::checkpoint::{checkpoint_args, Checkpointer};
+use crate::cli::chain_command::result_value;
+use crate::cli::watch_command::decode_log;
+use crate::cli::{encryption, get_url, key_validator, parse_privkey, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Automation rules command
+pub fn rules_command() -> App<'static, 'static> {
+    App::new("rules")
+        .about("Run automations on the events of the chain, configured by rules")
+        .subcommand(
+            SubCommand::with_name("run")
+                .about(
+                    "Watch the logs of new blocks and run the actions of the rules they match: \
+                     call a webhook, run a command or send a transaction",
+                )
+                .arg(
+                    Arg::with_name("config")
+                        .long("config")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The toml file of the rules"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transactions of the send actions"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("dry-run")
+                        .long("dry-run")
+                        .help("Only print the actions the matched logs would run"),
+                )
+                .args(&checkpoint_args())
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("Start watching from this height, default is the current height"),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .default_value("3")
+                        .validator(|interval| parse_u64(interval.as_str()).map(|_| ()))
+                        .help("Polling interval in seconds"),
+                ),
+        )
+}
+
+/// Automation rules processor
+pub fn rules_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let mut client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    match sub_matches.subcommand() {
+        ("run", Some(m)) => {
+            let path = m.value_of("config").unwrap();
+            let rules = Rules::load(path)?;
+            let dry_run = m.is_present("dry-run");
+            if rules.sends() && !dry_run {
+                let private_key = m
+                    .value_of("private-key")
+                    .ok_or("The rules send transactions, --private-key is needed")?;
+                client.set_private_key(&parse_privkey(private_key, encryption(m, config))?);
+            }
+            let quota = match m.value_of("quota") {
+                Some(quota) => Some(parse_u64(quota)?),
+                None => None,
+            };
+            let interval = parse_u64(m.value_of("interval").unwrap())?;
+            let checkpointer = Checkpointer::open(m, &format!("rules run {}", path))?;
+            let mut watcher = LogWatcher::new(client.clone())
+                .set_addresses(rules.addresses())
+                .set_from(
+                    checkpointer
+                        .next_height()
+                        .or_else(|| m.value_of("from").map(|s| parse_u64(s).unwrap())),
+                );
+
+            loop {
+                let logs = watcher.poll().map_err(|err| format!("{}", err))?;
+                for log in logs {
+                    for rule in &rules.rules {
+                        let log = match rule.matches(&log) {
+                            Some(log) => log,
+                            None => continue,
+                        };
+                        for action in &rule.actions {
+                            let mut report = json!({
+                                "rule": rule.name,
+                                "transactionHash": log["transactionHash"],
+                                "logIndex": log["logIndex"],
+                                "action": action_value(action, &log),
+                            });
+                            if !dry_run {
+                                match run_action(&client, rule, action, &log, quota) {
+                                    Ok(outcome) => report["outcome"] = outcome,
+                                    Err(err) => {
+                                        report["error"] = json!(err);
+                                        printer.eprintln(
+                                            &Rc::new(format!("{}: {}", rule.name, err)),
+                                            true,
+                                        );
+                                    }
+                                }
+                            }
+                            printer.println(&report, is_color);
+                        }
+                    }
+                }
+                if let Some(next_height) = watcher.next_height() {
+                    checkpointer.save(next_height, Value::Null)?;
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        _ => Err(sub_matches.usage().to_owned()),
+    }
+}
+
+/// The action with the values of the log
+fn action_value(action: &Action, log: &Value) -> Value {
+    match action {
+        Action::Webhook(url) => json!({ "webhook": render(url, log) }),
+        Action::Command(command) => json!({ "command": render(command, log) }),
+        Action::Send {
+            to,
+            data,
+            function,
+            args,
+        } => json!({
+            "send": render(to, log),
+            "data": data.as_ref().map(|data| render(data, log)),
+            "function": function,
+            "args": args.iter().map(|arg| render(arg, log)).collect::<Vec<String>>(),
+        }),
+    }
+}
+
+fn run_action(
+    client: &Client,
+    rule: &Rule,
+    action: &Action,
+    log: &Value,
+    quota: Option<u64>,
+) -> Result<Value, String> {
+    match action {
+        Action::Webhook(url) => match client.post_json(&render(url, log), log) {
+            Ok(status) if status < 300 => Ok(json!({ "status": status })),
+            Ok(status) => Err(format!("The webhook responded with status {}", status)),
+            Err(err) => Err(format!("The webhook failed: {}", err)),
+        },
+        Action::Command(command) => {
+            let status = Command::new("sh")
+                .args(["-c", &render(command, log)])
+                .env("CITA_LOG", log.to_string())
+                .status()
+                .map_err(|err| format!("Can't run the command: {}", err))?;
+            if status.success() {
+                Ok(json!({ "status": status.code() }))
+            } else {
+                Err(format!("The command exited with {}", status))
+            }
+        }
+        Action::Send {
+            to,
+            data,
+            function,
+            args,
+        } => {
+            let data = match (data, function) {
+                (Some(data), _) => render(data, log),
+                (None, Some(function)) => {
+                    let args = Value::Array(
+                        args.iter()
+                            .map(|arg| Value::String(render(arg, log)))
+                            .collect(),
+                    );
+                    format!(
+                        "0x{}",
+                        encode_json_input(rule.abi.as_deref().unwrap(), function, &args, false)
+                            .map_err(|err| format!("{}", err))?
+                    )
+                }
+                (None, None) => unreachable!(),
+            };
+            let to = render(to, log);
+            let tx_options = TransactionOptions::new()
+                .set_code(&data)
+                .set_address(&to)
+                .set_quota(quota);
+            let response = client
+                .send_raw_transaction(tx_options)
+                .map_err(|err| format!("{}", err))?;
+            result_value(response).map_err(|err| format!("The transaction failed: {}", err))
+        }
+    }
+}
+
+/// What a rule does when a log matches it
+#[derive(Debug, Clone, PartialEq)]
+enum Action {
+    /// POST the log as json to the url
+    Webhook(String),
+    /// Run the command by `sh -c`, the log is in the environment variable `CITA_LOG`
+    Command(String),
+    /// Send a transaction to the address, of the data or of the function and its args
+    Send {
+        to: String,
+        data: Option<String>,
+        function: Option<String>,
+        args: Vec<String>,
+    },
+}
+
+/// A log filter and the actions of the logs it matches
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    name: String,
+    /// The contract emitting the logs
+    address: Option<String>,
+    /// The name of the decoded event
+    event: Option<String>,
+    /// The values the decoded params must have, by their names
+    conditions: BTreeMap<String, String>,
+    /// The content of the ABI decoding the logs
+    abi: Option<String>,
+    actions: Vec<Action>,
+}
+
+/// The rules of a toml config
+///
+/// ```toml
+/// abi = "Token.abi"
+///
+/// [[rule]]
+/// name = "large transfer"
+/// address = "0x..."
+/// event = "Transfer"
+/// where = { to = "0x..." }
+///
+/// [[rule.action]]
+/// webhook = "https://example.com/hook"
+///
+/// [[rule.action]]
+/// command = "notify-send 'transfer of {value} to {to}'"
+///
+/// [[rule.action]]
+/// send = "0x..."
+/// function = "credit"
+/// args = ["{to}", "{value}"]
+/// ```
+///
+/// The ABI paths are relative to the config. `{<param>}`, `{event}`, `{address}`,
+/// `{transactionHash}` and `{blockNumber}` in the strings of the actions are replaced by
+/// the values of the matched log.
+struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    fn load(path: &str) -> Result<Self, String> {
+        let content =
+            fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+        let dir = Path::new(path).parent().unwrap_or_else(|| Path::new("."));
+        Rules::parse(dir, &content).map_err(|err| format!("{}: {}", path, err))
+    }
+
+    fn parse(dir: &Path, content: &str) -> Result<Self, String> {
+        let config: Value = toml::from_str(content).map_err(|err| format!("{}", err))?;
+        let abi = |value: &Value| -> Result<Option<String>, String> {
+            match value.as_str() {
+                Some(path) => fs::read_to_string(dir.join(path))
+                    .map(Some)
+                    .map_err(|err| format!("Can't read {}: {}", path, err)),
+                None => Ok(None),
+            }
+        };
+        let default_abi = abi(&config["abi"])?;
+        let rules = config["rule"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(index, rule)| {
+                let name = rule["name"]
+                    .as_str()
+                    .map(str::to_owned)
+                    .unwrap_or_else(|| format!("rule {}", index + 1));
+                let rule_abi = abi(&rule["abi"])?.or_else(|| default_abi.clone());
+                let conditions = rule["where"]
+                    .as_object()
+                    .map(|conditions| {
+                        conditions
+                            .iter()
+                            .map(|(param, value)| (param.clone(), text(value)))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let actions = rule["action"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|action| parse_action(action, rule_abi.is_some()))
+                    .collect::<Result<Vec<Action>, String>>()
+                    .map_err(|err| format!("{}: {}", name, err))?;
+                if actions.is_empty() {
+                    return Err(format!("{}: no [[rule.action]]", name));
+                }
+                if (rule["event"].is_string() || !rule["where"].is_null()) && rule_abi.is_none() {
+                    return Err(format!("{}: an ABI is needed to match the events", name));
+                }
+                Ok(Rule {
+                    name,
+                    address: rule["address"].as_str().map(str::to_owned),
+                    event: rule["event"].as_str().map(str::to_owned),
+                    conditions,
+                    abi: rule_abi,
+                    actions,
+                })
+            })
+            .collect::<Result<Vec<Rule>, String>>()?;
+        if rules.is_empty() {
+            return Err("no [[rule]]".to_string());
+        }
+        Ok(Rules { rules })
+    }
+
+    /// The addresses to watch, None when a rule matches any address
+    fn addresses(&self) -> Option<Vec<String>> {
+        self.rules
+            .iter()
+            .map(|rule| rule.address.clone())
+            .collect::<Option<Vec<String>>>()
+    }
+
+    /// Whether a rule sends transactions, which needs a private key
+    fn sends(&self) -> bool {
+        self.rules.iter().any(|rule| {
+            rule.actions
+                .iter()
+                .any(|action| matches!(action, Action::Send { .. }))
+        })
+    }
+}
+
+fn parse_action(action: &Value, has_abi: bool) -> Result<Action, String> {
+    if let Some(url) = action["webhook"].as_str() {
+        Ok(Action::Webhook(url.to_owned()))
+    } else if let Some(command) = action["command"].as_str() {
+        Ok(Action::Command(command.to_owned()))
+    } else if let Some(to) = action["send"].as_str() {
+        let data = action["data"].as_str().map(str::to_owned);
+        let function = action["function"].as_str().map(str::to_owned);
+        match (&data, &function) {
+            (None, None) => return Err("send needs data or function".to_string()),
+            (None, Some(_)) if !has_abi => {
+                return Err("an ABI is needed to encode the function".to_string())
+            }
+            _ => {}
+        }
+        Ok(Action::Send {
+            to: to.to_owned(),
+            data,
+            function,
+            args: action["args"]
+                .as_array()
+                .map(|args| args.iter().map(text).collect())
+                .unwrap_or_default(),
+        })
+    } else {
+        Err("an action is webhook, command or send".to_string())
+    }
+}
+
+/// A toml value as the text the decoded params are compared to
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
+impl Rule {
+    /// The log decoded by the ABI of the rule, if the rule matches it
+    fn matches(&self, log: &Value) -> Option<Value> {
+        if let Some(ref address) = self.address {
+            let emitter = log["address"].as_str().unwrap_or_default();
+            if !same(address, emitter) {
+                return None;
+            }
+        }
+        let log = decode_log(log.clone(), self.abi.as_deref());
+        if let Some(ref event) = self.event {
+            if log["decoded"]["event"].as_str() != Some(event.as_str()) {
+                return None;
+            }
+        }
+        let params = params(&log);
+        let met = self
+            .conditions
+            .iter()
+            .all(|(param, expected)| params.get(param).is_some_and(|value| same(expected, value)));
+        if met {
+            Some(log)
+        } else {
+            None
+        }
+    }
+}
+
+/// The decoded params of the log by their names
+fn params(log: &Value) -> BTreeMap<String, String> {
+    log["decoded"]["params"]
+        .as_array()
+        .map(|params| {
+            params
+                .iter()
+                .filter_map(Value::as_object)
+                .flatten()
+                .map(|(name, value)| (name.clone(), text(value)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Compare values ignoring the case and the `0x` prefix of hex
+fn same(expected: &str, value: &str) -> bool {
+    remove_0x(expected).eq_ignore_ascii_case(remove_0x(value))
+}
+
+/// Replace the placeholders of the template by the values of the log
+fn render(template: &str, log: &Value) -> String {
+    let mut values = params(log);
+    values.insert("event".to_string(), text(&log["decoded"]["event"]));
+    for field in &["address", "transactionHash", "blockNumber"] {
+        values.insert((*field).to_string(), text(&log[*field]));
+    }
+    values
+        .iter()
+        .fold(template.to_owned(), |rendered, (name, value)| {
+            rendered.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{render, Action, Rules};
+    use serde_json::json;
+    use std::path::Path;
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules::parse(
+            Path::new("."),
+            r#"
+            [[rule]]
+            name = "from the contract"
+            address = "0x00000000000000000000000000000000000000aa"
+
+            [[rule.action]]
+            command = "echo {transactionHash} at {blockNumber}"
+
+            [[rule.action]]
+            send = "0x00000000000000000000000000000000000000bb"
+            data = "0x01"
+            "#,
+        )
+        .unwrap();
+        let rule = &rules.rules[0];
+        assert_eq!(rule.name, "from the contract");
+        assert!(rules.sends());
+        assert_eq!(
+            rules.addresses(),
+            Some(vec![
+                "0x00000000000000000000000000000000000000aa".to_string()
+            ])
+        );
+
+        let log = json!({
+            "address": "0x00000000000000000000000000000000000000AA",
+            "transactionHash": "0x12",
+            "blockNumber": "0x3",
+        });
+        let matched = rule.matches(&log).unwrap();
+        match rule.actions[0] {
+            Action::Command(ref command) => {
+                assert_eq!(render(command, &matched), "echo 0x12 at 0x3")
+            }
+            _ => panic!("Not the command"),
+        }
+        assert!(rule
+            .matches(&json!({ "address": "0x00000000000000000000000000000000000000cc" }))
+            .is_none());
+
+        // Events and params need an ABI to be decoded
+        assert!(Rules::parse(
+            Path::new("."),
+            "[[rule]]\nevent = \"Transfer\"\n[[rule.action]]\ncommand = \"true\"\n",
+        )
+        .is_err());
+    }
+}
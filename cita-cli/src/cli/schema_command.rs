@@ -0,0 +1,265 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::{diff_shapes, SchemaChange, Shape};
+use cita_tool::{JsonRpcParams, Params};
+
+use crate::cli::get_url;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+const ZERO_HASH: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+/// How many blocks back to look for a transaction to capture
+const TRANSACTION_SEARCH: u64 = 20;
+
+/// RPC schema command
+pub fn schema_command() -> App<'static, 'static> {
+    App::new("schema")
+        .about("Record the JSON shape of the RPC responses of a node and compare the records")
+        .subcommand(
+            SubCommand::with_name("capture")
+                .about(
+                    "Call the read methods and record the field names and types of every \
+                     response, the transactions are sampled from the latest blocks",
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Write the capture to the file instead of printing it"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("diff")
+                .about(
+                    "Compare two captures, such as of two releases of the node, \
+                     fail if a field is removed or its type changes",
+                )
+                .arg(
+                    Arg::with_name("old")
+                        .required(true)
+                        .index(1)
+                        .help("The capture of the known node"),
+                )
+                .arg(
+                    Arg::with_name("new")
+                        .required(true)
+                        .index(2)
+                        .help("The capture of the new node"),
+                ),
+        )
+}
+
+/// RPC schema processor
+pub fn schema_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let result = match sub_matches.subcommand() {
+        ("capture", Some(m)) => {
+            // The node is recorded as it is, by the current method names and without caches
+            let client = client
+                .set_debug(debug)
+                .set_uri(get_url(m, config))
+                .set_method_aliases(None)
+                .set_cache(None)
+                .set_node_selector(None);
+            let capture = capture(&client)?;
+            match m.value_of("output") {
+                Some(path) => {
+                    fs::write(path, serde_json::to_string_pretty(&capture).unwrap())
+                        .map_err(|err| format!("Can't write {}: {}", path, err))?;
+                    json!({
+                        "output": path,
+                        "methods": capture["methods"].as_object().map(|methods| methods.len()),
+                    })
+                }
+                None => capture,
+            }
+        }
+        ("diff", Some(m)) => {
+            let old = load_methods(m.value_of("old").unwrap())?;
+            let new = load_methods(m.value_of("new").unwrap())?;
+            let (changes, uncaptured) = diff_methods(&old, &new);
+            let breaking = changes.iter().filter(|change| change.is_breaking()).count();
+            let report = json!({
+                "breaking": breaking,
+                "changes": changes,
+                "uncaptured": uncaptured,
+            });
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            if breaking > 0 {
+                return Err(format!(
+                    "{} breaking changes of the responses, the typed structs may fail to parse them",
+                    breaking
+                ));
+            }
+            return Ok(());
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&result, is_color);
+    config.set("result".to_string(), result);
+    Ok(())
+}
+
+/// The calls of the capture, by name, of the current height of the node and a transaction
+/// of the latest blocks if there is one
+fn calls(client: &Client, height: u64) -> Vec<(String, &'static str, Value)> {
+    let latest = format!("{:#x}", height);
+    let mut calls = vec![
+        ("blockNumber", "blockNumber", json!([])),
+        ("peerCount", "peerCount", json!([])),
+        ("peersInfo", "peersInfo", json!([])),
+        ("getVersion", "getVersion", json!([])),
+        ("getMetaData", "getMetaData", json!(["latest"])),
+        (
+            "getBlockByNumber",
+            "getBlockByNumber",
+            json!([latest, false]),
+        ),
+        (
+            "getBlockByNumber(full)",
+            "getBlockByNumber",
+            json!([latest, true]),
+        ),
+        ("getBalance", "getBalance", json!([ZERO_ADDRESS, "latest"])),
+        ("getCode", "getCode", json!([ZERO_ADDRESS, "latest"])),
+        ("getAbi", "getAbi", json!([ZERO_ADDRESS, "latest"])),
+        (
+            "getTransactionCount",
+            "getTransactionCount",
+            json!([ZERO_ADDRESS, "latest"]),
+        ),
+        (
+            "call",
+            "call",
+            json!([{ "to": ZERO_ADDRESS, "data": "0x" }, "latest"]),
+        ),
+        (
+            "getLogs",
+            "getLogs",
+            json!([{ "fromBlock": latest, "toBlock": latest }]),
+        ),
+        (
+            "getTransaction(unknown)",
+            "getTransaction",
+            json!([ZERO_HASH]),
+        ),
+        ("error", "noSuchMethod", json!([])),
+    ]
+    .into_iter()
+    .map(|(name, method, params)| (name.to_owned(), method, params))
+    .collect::<Vec<_>>();
+
+    if let Some((block_hash, hash)) = sample_transaction(client, height) {
+        calls.push((
+            "getBlockByHash".to_owned(),
+            "getBlockByHash",
+            json!([block_hash, false]),
+        ));
+        calls.push(("getTransaction".to_owned(), "getTransaction", json!([hash])));
+        calls.push((
+            "getTransactionReceipt".to_owned(),
+            "getTransactionReceipt",
+            json!([hash]),
+        ));
+        calls.push((
+            "getTransactionProof".to_owned(),
+            "getTransactionProof",
+            json!([hash]),
+        ));
+    }
+    calls
+}
+
+/// The hash of the block and of the first transaction of the latest block having one
+fn sample_transaction(client: &Client, height: u64) -> Option<(String, String)> {
+    (height.saturating_sub(TRANSACTION_SEARCH)..=height)
+        .rev()
+        .filter_map(|number| {
+            request(
+                client,
+                "getBlockByNumber",
+                json!([format!("{:#x}", number), false]),
+            )
+            .ok()
+        })
+        .find_map(|response| {
+            let block = &response["result"];
+            let hash = block["body"]["transactions"][0].as_str()?;
+            Some((block["hash"].as_str()?.to_owned(), hash.to_owned()))
+        })
+}
+
+/// The response of the call as JSON
+fn request(client: &Client, method: &str, params: Value) -> Result<Value, String> {
+    let params = Params::from_json(params).map_err(|err| format!("{}", err))?;
+    let mut responses = client
+        .send_request(Some(JsonRpcParams::request(method, params)).into_iter())
+        .map_err(|err| format!("{}", err))?;
+    Ok(serde_json::to_value(responses.remove(0)).unwrap_or_default())
+}
+
+/// The capture of the node: the shape of the response of every call
+fn capture(client: &Client) -> Result<Value, String> {
+    let height = client
+        .get_current_height()
+        .map_err(|err| format!("Can't get the height of the node: {}", err))?;
+    let mut methods = BTreeMap::new();
+    let mut failed = BTreeMap::new();
+    for (name, method, params) in calls(client, height) {
+        match request(client, method, params) {
+            Ok(response) => {
+                methods.insert(name, Shape::of(&response));
+            }
+            Err(err) => {
+                failed.insert(name, err);
+            }
+        }
+    }
+    Ok(json!({
+        "url": client.uri().to_string(),
+        "version": client.chain_version().ok().map(|version| version.to_value()),
+        "height": height,
+        "methods": methods,
+        "failed": failed,
+    }))
+}
+
+fn load_methods(path: &str) -> Result<BTreeMap<String, Shape>, String> {
+    let content =
+        fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    let capture: Value = serde_json::from_str(&content)
+        .map_err(|err| format!("Invalid capture {}: {}", path, err))?;
+    serde_json::from_value(capture["methods"].clone())
+        .map_err(|err| format!("Invalid capture {}: {}", path, err))
+}
+
+/// The changes of the methods captured by both, and the methods only one of them captured
+fn diff_methods(
+    old: &BTreeMap<String, Shape>,
+    new: &BTreeMap<String, Shape>,
+) -> (Vec<SchemaChange>, Vec<String>) {
+    let names = old.keys().chain(new.keys()).collect::<BTreeSet<&String>>();
+    let mut changes = Vec::new();
+    let mut uncaptured = Vec::new();
+    for name in names {
+        match (old.get(name), new.get(name)) {
+            (Some(old), Some(new)) => changes.extend(diff_shapes(name, old, new)),
+            _ => uncaptured.push(name.clone()),
+        }
+    }
+    (changes, uncaptured)
+}
@@ -0,0 +1,190 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::BlockTime;
+use cita_tool::ToolError;
+
+use crate::cli::{get_url, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::{format_timestamp, Printer, TimeFormat};
+
+/// Block time command
+pub fn time_command() -> App<'static, 'static> {
+    App::new("time")
+        .about("Convert between block heights and wall-clock times")
+        .subcommand(
+            SubCommand::with_name("at-height")
+                .about("The time of a block in milliseconds, seconds, UTC and local time")
+                .arg(
+                    Arg::with_name("height")
+                        .required(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("The block height, hex or decimal"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("height-at")
+                .about("Binary search the last block produced at or before the time")
+                .arg(
+                    Arg::with_name("time")
+                        .required(true)
+                        .validator(|time| parse_time(time.as_str()).map(|_| ()))
+                        .help(
+                            "RFC 3339 like 2020-09-13T12:26:40+08:00, `YYYY-MM-DD[ HH:MM:SS]` \
+                             in UTC, or a unix time in seconds or milliseconds",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("closest")
+                        .long("closest")
+                        .help("The block closest to the time, before or after it"),
+                ),
+        )
+}
+
+/// Block time processor
+pub fn time_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let result = match sub_matches.subcommand() {
+        ("at-height", Some(m)) => {
+            let height = parse_u64(m.value_of("height").unwrap())?;
+            client
+                .block_time(height)
+                .map_err(|err| format!("{}", err))?
+        }
+        ("height-at", Some(m)) => {
+            let time = parse_time(m.value_of("time").unwrap())?;
+            if m.is_present("closest") {
+                client.block_at_time(time)
+            } else {
+                client.last_block_before(time).and_then(|block| {
+                    block.ok_or_else(|| {
+                        ToolError::Customize("The time is before the genesis block".to_string())
+                    })
+                })
+            }
+            .map_err(|err| format!("{}", err))?
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    let result = block_json(result);
+    printer.println(&result, is_color);
+    config.set("result".to_string(), result);
+    Ok(())
+}
+
+fn block_json(block: BlockTime) -> Value {
+    json!({
+        "height": block.height,
+        "hash": block.hash,
+        "timestamp": block.timestamp,
+        "unix": format_timestamp(block.timestamp, TimeFormat::Unix),
+        "utc": format_timestamp(block.timestamp, TimeFormat::Utc),
+        "local": format_timestamp(block.timestamp, TimeFormat::Local),
+    })
+}
+
+/// `--from-time` and `--to-time`, bounding a block range by dates instead of heights
+pub(crate) fn time_range_args() -> [Arg<'static, 'static>; 2] {
+    [
+        Arg::with_name("from-time")
+            .long("from-time")
+            .takes_value(true)
+            .conflicts_with("from")
+            .validator(|time| parse_time(time.as_str()).map(|_| ()))
+            .help("Start from the first block produced at or after the time, instead of --from"),
+        Arg::with_name("to-time")
+            .long("to-time")
+            .takes_value(true)
+            .requires("from-time")
+            .conflicts_with("to")
+            .validator(|time| parse_time(time.as_str()).map(|_| ()))
+            .help("End with the last block produced at or before the time, default is now"),
+    ]
+}
+
+/// The block range of `--from` and `--to`, or of `--from-time` and `--to-time`,
+/// the end defaults to the current height
+pub(crate) fn block_range(m: &ArgMatches, client: &Client) -> Result<(u64, u64), String> {
+    if let Some(from_time) = m.value_of("from-time") {
+        let from_time = parse_time(from_time)?;
+        let to_time = match m.value_of("to-time") {
+            Some(to_time) => Some(parse_time(to_time)?),
+            None => None,
+        };
+        return client
+            .blocks_between(from_time, to_time)
+            .map_err(|err| format!("{}", err))?
+            .ok_or_else(|| "No block is produced in the time range".to_string());
+    }
+    let from = parse_u64(m.value_of("from").unwrap())?;
+    let to = match m.value_of("to") {
+        Some(to) => parse_u64(to)?,
+        None => client
+            .get_current_height()
+            .map_err(|err| format!("{}", err))?,
+    };
+    if from > to {
+        return Err(format!("Empty block range {}..={}", from, to));
+    }
+    Ok((from, to))
+}
+
+/// Milliseconds since the epoch of RFC 3339, a UTC date and time, or a unix time,
+/// which is in seconds below 10^11 and in milliseconds above
+pub(crate) fn parse_time(time: &str) -> Result<u64, String> {
+    if let Ok(number) = time.parse::<u64>() {
+        return Ok(if number < 100_000_000_000 {
+            number * 1000
+        } else {
+            number
+        });
+    }
+    let millis = if let Ok(time) = DateTime::parse_from_rfc3339(time) {
+        time.timestamp_millis()
+    } else if let Ok(time) = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S") {
+        time.and_utc().timestamp_millis()
+    } else if let Ok(date) = NaiveDate::parse_from_str(time, "%Y-%m-%d") {
+        date.and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc()
+            .timestamp_millis()
+    } else {
+        return Err(format!("Invalid time {}", time));
+    };
+    if millis < 0 {
+        Err(format!("{} is before 1970", time))
+    } else {
+        Ok(millis as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_time;
+
+    #[test]
+    fn test_parse_time() {
+        let millis = 1_600_000_000_000;
+        assert_eq!(parse_time("1600000000"), Ok(millis));
+        assert_eq!(parse_time("1600000000000"), Ok(millis));
+        assert_eq!(parse_time("2020-09-13T12:26:40Z"), Ok(millis));
+        assert_eq!(parse_time("2020-09-13T20:26:40+08:00"), Ok(millis));
+        assert_eq!(parse_time("2020-09-13 12:26:40"), Ok(millis));
+        assert_eq!(parse_time("2020-09-13"), Ok(1_599_955_200_000));
+        assert!(parse_time("yesterday").is_err());
+        assert!(parse_time("1960-01-01").is_err());
+    }
+}
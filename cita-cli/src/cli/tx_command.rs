@@ -1,17 +1,25 @@
 use clap::{App, Arg, ArgMatches, SubCommand};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
 
-use cita_tool::client::basic::Client;
-use cita_tool::{encode, ProtoMessage, TransactionOptions, UnverifiedTransaction};
+use cita_tool::client::basic::{Client, ClientExt, ContractExt, ReceiptStatus};
+use cita_tool::{
+    decode, encode, remove_0x, LowerHex, MultisigProposal, ProtoMessage, SelectorTable,
+    SignatureShare, TestVector, Transaction, TransactionOptions, UnverifiedTransaction,
+    MULTISIG_EXECUTE, U256,
+};
 
 use crate::cli::{
-    encryption, get_url, is_hex, key_validator, parse_address, parse_privkey, parse_u256,
-    parse_u32, parse_u64,
+    encryption, get_url, h256_validator, hex_output_args, is_hex, key_validator, output_hex,
+    parse_address, parse_local_privkey, parse_privkey, parse_u256, parse_u32, parse_u64,
 };
 use crate::interactive::{set_output, GlobalConfig};
 use crate::printer::Printer;
+use crate::qr::{from_qr_parts, qr_parts, render_qr, QrKind};
 use std::fs::File;
 use std::io::Read;
 use std::str::FromStr;
+use std::time::Duration;
 
 /// Transaction command
 pub fn tx_command() -> App<'static, 'static> {
@@ -73,6 +81,53 @@ pub fn tx_command() -> App<'static, 'static> {
                         .takes_value(true)
                         .validator(|version| parse_u32(version.as_str()).map(|_| ()))
                         .help("The version of transaction, default is 0"),
+                )
+                .arg(qr_arg())
+                .args(&hex_output_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("sign")
+                .about("Sign a transaction made by `tx make` offline, without sending it")
+                .arg(
+                    Arg::with_name("byte-code")
+                        .long("byte-code")
+                        .takes_value(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .required(true)
+                        .help("Unsigned transaction binary data"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .validator(|private| key_validator(private.as_str()).map(|_| ()))
+                        .takes_value(true)
+                        .required(true)
+                        .help("The private key signing the transaction"),
+                )
+                .arg(qr_arg())
+                .args(&hex_output_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("from-qr")
+                .about(
+                    "Join the scanned texts of the transaction QR codes, in any order, \
+                     and decode the transaction",
+                )
+                .arg(
+                    Arg::with_name("part")
+                        .long("part")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .required_unless("file")
+                        .help("The text of a QR code"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .takes_value(true)
+                        .conflicts_with("part")
+                        .help("File of the texts of the QR codes, one per line, `-` is stdin"),
                 ),
         )
         .subcommand(
@@ -107,6 +162,62 @@ pub fn tx_command() -> App<'static, 'static> {
                         .help("Transfer Account Private Key"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("create-contract")
+                .about("Sign and send a transaction creating a contract of the code")
+                .arg(
+                    Arg::with_name("code")
+                        .long("code")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .help("The code of the contract, with the encoded constructor arguments"),
+                )
+                .args(&send_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("call-contract")
+                .about("Sign and send a transaction of the data to a contract")
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .required(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The address of the contract, it can't be empty"),
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .long("data")
+                        .takes_value(true)
+                        .default_value("0x")
+                        .validator(|data| is_hex(data.as_str()))
+                        .help("The encoded function call"),
+                )
+                .args(&send_args()),
+        )
+        .subcommand(
+            SubCommand::with_name("inspect-signature")
+                .about(
+                    "Recover the sender of an unverifiedTransaction and check its signature \
+                     for malleability and invalid recovery ids",
+                )
+                .arg(
+                    Arg::with_name("content")
+                        .long("content")
+                        .takes_value(true)
+                        .validator(|content| is_hex(content.as_str()))
+                        .conflicts_with("file")
+                        .required(true)
+                        .help("UnverifiedTransaction content"),
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .takes_value(true)
+                        .help("content data file path"),
+                ),
+        )
         .subcommand(
             SubCommand::with_name("decode-unverifiedTransaction")
                 .about("Decode unverifiedTransaction")
@@ -126,6 +237,209 @@ pub fn tx_command() -> App<'static, 'static> {
                         .help("content data file path"),
                 ),
         )
+        .subcommand(
+            SubCommand::with_name("decode-data")
+                .about(
+                    "Split calldata into the selector and the arguments, and decode the \
+                     arguments by the known function signatures",
+                )
+                .arg(
+                    Arg::with_name("data")
+                        .index(1)
+                        .required(true)
+                        .validator(|data| is_hex(data.as_str()))
+                        .help("The calldata of the transaction"),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .help("ABI files adding function signatures to the bundled ones"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("test-vector")
+                .about(
+                    "Regenerate the outputs of the transaction encoding test vectors, \
+                     the bundled ones by default",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .takes_value(true)
+                        .help("JSON file of the test vectors"),
+                )
+                .arg(
+                    Arg::with_name("check")
+                        .long("check")
+                        .help("Compare with the outputs in the file instead, fail on differences"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multisig-export")
+                .about(
+                    "Export the unsigned call of a multisig wallet, for the owners to sign \
+                     with `tx cosign`",
+                )
+                .arg(
+                    Arg::with_name("wallet")
+                        .long("wallet")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The multisig wallet contract"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The destination of the call made by the wallet"),
+                )
+                .arg(
+                    Arg::with_name("value")
+                        .long("value")
+                        .default_value("0")
+                        .takes_value(true)
+                        .validator(|value| parse_u256(value.as_ref()).map(|_| ()))
+                        .help("The value sent by the wallet"),
+                )
+                .arg(
+                    Arg::with_name("code")
+                        .long("code")
+                        .default_value("0x")
+                        .takes_value(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .help("The calldata sent by the wallet"),
+                )
+                .arg(
+                    Arg::with_name("nonce")
+                        .long("nonce")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|nonce| parse_u256(nonce.as_ref()).map(|_| ()))
+                        .help("The nonce of the wallet"),
+                )
+                .arg(
+                    Arg::with_name("chain-id")
+                        .long("chain-id")
+                        .takes_value(true)
+                        .validator(|chain_id| parse_u256(chain_id.as_ref()).map(|_| ()))
+                        .help("The chain id, default query to the chain"),
+                )
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .validator(|threshold| parse_u64(threshold.as_ref()).map(|_| ()))
+                        .help("Signatures the wallet requires, checked when combining"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Write the proposal to the file instead of stdout"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("cosign")
+                .about("Sign a multisig proposal, producing a signature share")
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The proposal written by `tx multisig-export`"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|private| key_validator(private.as_str()).map(|_| ()))
+                        .help("The private key of the owner"),
+                )
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .takes_value(true)
+                        .help("Write the share to the file instead of stdout"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multisig-combine")
+                .about(
+                    "Check the signature shares of a multisig proposal and combine them into \
+                     the call of the wallet, optionally sending it",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help("The proposal written by `tx multisig-export`"),
+                )
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .required(true)
+                        .takes_value(true)
+                        .multiple(true)
+                        .help("The share files written by `tx cosign`"),
+                )
+                .arg(
+                    Arg::with_name("function")
+                        .long("function")
+                        .default_value(MULTISIG_EXECUTE)
+                        .takes_value(true)
+                        .help("The wallet function taking (to, value, data, signatures)"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .validator(|private| key_validator(private.as_str()).map(|_| ()))
+                        .help("Send the combined call to the wallet, signed by this key"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default is 10_000_000"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("wait-receipt")
+                .about("Wait for the receipt of transaction")
+                .arg(
+                    Arg::with_name("hash")
+                        .long("hash")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|hash| h256_validator(hash.as_str()))
+                        .help("The hash of transaction"),
+                )
+                .arg(
+                    Arg::with_name("confirmations")
+                        .long("confirmations")
+                        .default_value("0")
+                        .takes_value(true)
+                        .validator(|number| parse_u64(number.as_str()).map(|_| ()))
+                        .help("Also wait until the receipt's block is this many blocks deep"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .default_value("120")
+                        .takes_value(true)
+                        .validator(|timeout| parse_u64(timeout.as_str()).map(|_| ()))
+                        .help("Give up after this many seconds"),
+                ),
+        )
 }
 
 pub fn tx_processor(
@@ -163,13 +477,68 @@ pub fn tx_processor(
             let tx = client
                 .generate_transaction(tx_options)
                 .map_err(|err| format!("{}", err))?;
-            printer.println(
-                &format!(
-                    "0x{}",
-                    encode(tx.write_to_bytes().map_err(|err| format!("{}", err))?)
-                ),
-                is_color,
+            let hex = format!(
+                "0x{}",
+                encode(tx.write_to_bytes().map_err(|err| format!("{}", err))?)
+            );
+            if m.is_present("qr") {
+                print_qr(printer, QrKind::Unsigned, &hex)?;
+            } else {
+                output_hex(m, printer, &hex, &hex, encryption(m, config), is_color)?;
+            }
+            return Ok(());
+        }
+        ("sign", Some(m)) => {
+            let private_key =
+                parse_local_privkey(m.value_of("private-key").unwrap(), encryption(m, config))?;
+            let tx = parse_transaction(m.value_of("byte-code").unwrap())?;
+            let signed = format!(
+                "0x{}",
+                encode(
+                    tx.build_unverified(private_key)
+                        .write_to_bytes()
+                        .map_err(|err| format!("{}", err))?
+                )
             );
+            if m.is_present("qr") {
+                print_qr(printer, QrKind::Signed, &signed)?;
+            } else {
+                output_hex(
+                    m,
+                    printer,
+                    &signed,
+                    &signed,
+                    encryption(m, config),
+                    is_color,
+                )?;
+            }
+            return Ok(());
+        }
+        ("from-qr", Some(m)) => {
+            let texts: Vec<String> = match m.values_of("part") {
+                Some(parts) => parts.map(ToOwned::to_owned).collect(),
+                None => {
+                    let mut content = String::new();
+                    get_input(m.value_of("file").unwrap())?
+                        .read_to_string(&mut content)
+                        .map_err(|err| format!("{}", err))?;
+                    content
+                        .lines()
+                        .filter(|line| !line.trim().is_empty())
+                        .map(ToOwned::to_owned)
+                        .collect()
+                }
+            };
+            let (kind, hex) = from_qr_parts(&texts)?;
+            let transaction = match kind {
+                QrKind::Unsigned => transaction_json(&parse_transaction(&hex)?),
+                QrKind::Signed => UnverifiedTransaction::from_str(&hex)
+                    .map_err(|err| format!("{}", err))?
+                    .to_json(encryption(m, config))?,
+            };
+            let decoded = json!({ "kind": kind.name(), "hex": hex, "transaction": transaction });
+            printer.println(&decoded, is_color);
+            config.set("result".to_string(), decoded);
             return Ok(());
         }
         ("sendSignedTransaction", Some(m)) => {
@@ -184,6 +553,42 @@ pub fn tx_processor(
             let byte_code = m.value_of("byte-code").unwrap();
             client.send_transaction(byte_code)
         }
+        ("create-contract", Some(m)) => {
+            client.set_private_key(&parse_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption(m, config),
+            )?);
+            if let Some(chain_id) = m.value_of("chain-id").map(|s| parse_u256(s).unwrap()) {
+                client.set_chain_id(chain_id);
+            }
+            client.create_contract(m.value_of("code").unwrap(), send_options(m))
+        }
+        ("call-contract", Some(m)) => {
+            client.set_private_key(&parse_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption(m, config),
+            )?);
+            if let Some(chain_id) = m.value_of("chain-id").map(|s| parse_u256(s).unwrap()) {
+                client.set_chain_id(chain_id);
+            }
+            client.call_contract(
+                m.value_of("address").unwrap(),
+                m.value_of("data").unwrap(),
+                send_options(m),
+            )
+        }
+        ("inspect-signature", Some(m)) => {
+            let encryption = encryption(sub_matches, config);
+            let mut content_reader = get_content(m.value_of("file"), m.value_of("content"))?;
+            let mut content_data = String::new();
+            content_reader
+                .read_to_string(&mut content_data)
+                .map_err(|err| format!("{}", err))?;
+            let tx = UnverifiedTransaction::from_str(content_data.trim())
+                .map_err(|err| format!("{}", err))?;
+            printer.println(&tx.inspect_signature(encryption)?, is_color);
+            return Ok(());
+        }
         ("decode-unverifiedTransaction", Some(m)) => {
             let encryption = encryption(sub_matches, config);
             let content = m.value_of("content");
@@ -195,10 +600,137 @@ pub fn tx_processor(
                 .map_err(|err| format!("{}", err))?;
             let content_data = content_data.trim();
             let tx =
-                UnverifiedTransaction::from_str(&content_data).map_err(|err| format!("{}", err))?;
+                UnverifiedTransaction::from_str(content_data).map_err(|err| format!("{}", err))?;
             printer.println(&tx.to_json(encryption)?, is_color);
             return Ok(());
         }
+        ("decode-data", Some(m)) => {
+            let mut table = SelectorTable::bundled();
+            for path in m.values_of("abi").into_iter().flatten() {
+                let abi = std::fs::read_to_string(path)
+                    .map_err(|err| format!("Can't read {}: {}", path, err))?;
+                table.add_abi(&abi).map_err(|err| format!("{}", err))?;
+            }
+            let decoded = table
+                .decode(m.value_of("data").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            printer.println(&decoded, is_color);
+            config.set("result".to_string(), decoded);
+            return Ok(());
+        }
+        ("test-vector", Some(m)) => {
+            let vectors: Vec<TestVector> = match m.value_of("file") {
+                Some(path) => serde_json::from_str(
+                    &std::fs::read_to_string(path).map_err(|err| format!("{}", err))?,
+                )
+                .map_err(|err| format!("{}", err))?,
+                None => TestVector::bundled(),
+            };
+            if m.is_present("check") {
+                let mut failed = Vec::new();
+                for vector in &vectors {
+                    let differences = vector.check().map_err(|err| format!("{}", err))?;
+                    if !differences.is_empty() {
+                        failed.push(json!({ "name": vector.name, "differences": differences }));
+                    }
+                }
+                if !failed.is_empty() {
+                    printer.println(&json!(failed), is_color);
+                    return Err(format!("{} test vectors changed", failed.len()));
+                }
+                printer.println(&json!({ "checked": vectors.len() }), is_color);
+            } else {
+                let regenerated = vectors
+                    .iter()
+                    .map(TestVector::regenerate)
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|err| format!("{}", err))?;
+                printer.println(&json!(regenerated), is_color);
+            }
+            return Ok(());
+        }
+        ("multisig-export", Some(m)) => {
+            let chain_id = match m.value_of("chain-id") {
+                Some(chain_id) => parse_u256(chain_id)?,
+                None => client.get_chain_id_v1().map_err(|err| format!("{}", err))?,
+            };
+            let proposal = MultisigProposal::new(
+                m.value_of("wallet").unwrap(),
+                m.value_of("to").unwrap(),
+                parse_u256(m.value_of("value").unwrap())?,
+                m.value_of("code").unwrap(),
+                parse_u256(m.value_of("nonce").unwrap())?,
+                chain_id,
+                m.value_of("threshold")
+                    .map(|threshold| parse_u64(threshold).map(|threshold| threshold as usize))
+                    .transpose()?,
+            )
+            .map_err(|err| format!("{}", err))?;
+            write_json(printer, m.value_of("output"), &json!(proposal), is_color)?;
+            return Ok(());
+        }
+        ("cosign", Some(m)) => {
+            let proposal: MultisigProposal = read_json(m.value_of("file").unwrap())?;
+            let private_key =
+                parse_local_privkey(m.value_of("private-key").unwrap(), encryption(m, config))?;
+            let share = proposal
+                .cosign(&private_key)
+                .map_err(|err| format!("{}", err))?;
+            write_json(printer, m.value_of("output"), &json!(share), is_color)?;
+            return Ok(());
+        }
+        ("multisig-combine", Some(m)) => {
+            let proposal: MultisigProposal = read_json(m.value_of("file").unwrap())?;
+            let shares = m
+                .values_of("shares")
+                .unwrap()
+                .map(read_json)
+                .collect::<Result<Vec<SignatureShare>, String>>()?;
+            let combined = proposal
+                .combine(&shares, m.value_of("function").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            let private_key = match m.value_of("private-key") {
+                Some(private_key) => parse_local_privkey(private_key, encryption(m, config))?,
+                None => {
+                    printer.println(&json!(combined), is_color);
+                    return Ok(());
+                }
+            };
+            client.set_private_key(&private_key);
+            let tx_options = TransactionOptions::new()
+                .set_code(&combined.data)
+                .set_address(&proposal.wallet)
+                .set_quota(m.value_of("quota").map(|quota| parse_u64(quota).unwrap()));
+            client.send_raw_transaction(tx_options)
+        }
+        ("wait-receipt", Some(m)) => {
+            let hash = m.value_of("hash").unwrap();
+            let confirmations = parse_u64(m.value_of("confirmations").unwrap())?;
+            let timeout = parse_u64(m.value_of("timeout").unwrap())?;
+            match client
+                .wait_for_receipt(
+                    hash,
+                    confirmations,
+                    Duration::from_secs(1),
+                    Duration::from_secs(timeout),
+                )
+                .map_err(|err| format!("{}", err))?
+            {
+                ReceiptStatus::Confirmed(receipt) => Ok(receipt),
+                ReceiptStatus::Dropped => {
+                    return Err(format!(
+                        "Transaction {} was dropped, its receipt disappeared from the chain",
+                        hash
+                    ));
+                }
+                ReceiptStatus::Timeout => {
+                    return Err(format!(
+                        "Timeout waiting for the receipt of transaction {}",
+                        hash
+                    ));
+                }
+            }
+        }
         _ => {
             return Err(sub_matches.usage().to_owned());
         }
@@ -209,6 +741,135 @@ pub fn tx_processor(
     Ok(())
 }
 
+/// `--private-key`, `--height`, `--chain-id`, `--quota`, `--value` and `--version` of the
+/// transactions sent by `create-contract` and `call-contract`
+fn send_args() -> [Arg<'static, 'static>; 6] {
+    [
+        Arg::with_name("private-key")
+            .long("private-key")
+            .takes_value(true)
+            .required(true)
+            .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+            .help("The private key of transaction"),
+        Arg::with_name("height")
+            .long("height")
+            .takes_value(true)
+            .validator(|height| parse_u64(height.as_ref()).map(|_| ()))
+            .help("Current chain height, default query to the chain"),
+        Arg::with_name("chain-id")
+            .long("chain-id")
+            .takes_value(true)
+            .validator(|chain_id| parse_u256(chain_id.as_ref()).map(|_| ()))
+            .help("The chain_id of transaction, default query to the chain"),
+        Arg::with_name("quota")
+            .long("quota")
+            .takes_value(true)
+            .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+            .help("Transaction quota costs, default 10_000_000"),
+        Arg::with_name("value")
+            .long("value")
+            .takes_value(true)
+            .validator(|value| parse_u256(value.as_ref()).map(|_| ()))
+            .help("The value to send, default is 0"),
+        Arg::with_name("version")
+            .long("version")
+            .takes_value(true)
+            .validator(|version| parse_u32(version.as_str()).map(|_| ()))
+            .help("The version of transaction, default query to the chain"),
+    ]
+}
+
+/// The options of `send_args`, without code and address
+fn send_options<'a>(m: &'a ArgMatches) -> TransactionOptions<'a> {
+    TransactionOptions::new()
+        .set_current_height(m.value_of("height").map(|s| parse_u64(s).unwrap()))
+        .set_quota(m.value_of("quota").map(|s| parse_u64(s).unwrap()))
+        .set_value(m.value_of("value").map(|value| parse_u256(value).unwrap()))
+        .set_version(
+            m.value_of("version")
+                .map(|version| parse_u32(version).unwrap()),
+        )
+}
+
+fn qr_arg() -> Arg<'static, 'static> {
+    Arg::with_name("qr")
+        .long("qr")
+        .conflicts_with_all(&["out", "copy"])
+        .help("Render the transaction as terminal QR codes, read them back by `tx from-qr`")
+}
+
+/// Print the QR codes of the transaction, numbered when it takes several
+fn print_qr(printer: &Printer, kind: QrKind, hex: &str) -> Result<(), String> {
+    let parts = qr_parts(kind, hex);
+    for (index, part) in parts.iter().enumerate() {
+        if parts.len() > 1 {
+            printer.println(&format!("Part {} of {}", index + 1, parts.len()), false);
+        }
+        printer.println(&render_qr(part)?, false);
+    }
+    Ok(())
+}
+
+fn parse_transaction(hex: &str) -> Result<Transaction, String> {
+    let bytes = decode(remove_0x(hex)).map_err(|err| format!("{}", err))?;
+    let mut tx = Transaction::new();
+    tx.merge_from_bytes(&bytes)
+        .map_err(|err| format!("{}", err))?;
+    Ok(tx)
+}
+
+/// The fields of an unsigned transaction
+fn transaction_json(tx: &Transaction) -> Value {
+    json!({
+        "to": tx.get_to(),
+        "to_v1": format!("0x{}", encode(tx.get_to_v1())),
+        "nonce": tx.get_nonce(),
+        "quota": tx.get_quota(),
+        "valid_until_block": tx.get_valid_until_block(),
+        "data": format!("0x{}", encode(tx.get_data())),
+        "value": U256::from(tx.get_value()).completed_lower_hex_with_0x(),
+        "chain_id": tx.get_chain_id(),
+        "chain_id_v1": U256::from(tx.get_chain_id_v1()).completed_lower_hex_with_0x(),
+        "version": tx.get_version(),
+    })
+}
+
+/// The file, or stdin for `-`
+fn get_input(path: &str) -> Result<Box<dyn Read>, String> {
+    match path {
+        "-" => Ok(Box::new(std::io::stdin())),
+        path => Ok(Box::new(
+            File::open(path).map_err(|err| format!("Can't open {}: {}", path, err))?,
+        )),
+    }
+}
+
+/// Read a multisig file
+fn read_json<T: DeserializeOwned>(path: &str) -> Result<T, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|err| format!("Can't read {}: {}", path, err))?;
+    serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))
+}
+
+/// Write a multisig file, or print it without a path
+fn write_json(
+    printer: &Printer,
+    path: Option<&str>,
+    content: &Value,
+    is_color: bool,
+) -> Result<(), String> {
+    match path {
+        Some(path) => {
+            let content = serde_json::to_string_pretty(content).map_err(|err| err.to_string())?;
+            std::fs::write(path, content)
+                .map_err(|err| format!("Can't write {}: {}", path, err))?;
+            printer.println(&json!({ "output": path }), is_color);
+        }
+        None => printer.println(content, is_color),
+    }
+    Ok(())
+}
+
 fn get_content(path: Option<&str>, content: Option<&str>) -> Result<Box<dyn Read>, String> {
     match content {
         Some(data) => Ok(Box::new(::std::io::Cursor::new(data.to_owned()))),
@@ -0,0 +1,45 @@
+use clap::{App, Arg, ArgMatches};
+
+use cita_tool::client::basic::Client;
+
+use crate::cli::{get_url, parse_address};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Transaction pool command
+pub fn txpool_command() -> App<'static, 'static> {
+    App::new("txpool")
+        .about(
+            "Show the pending transactions by the pool RPCs of the node, or the transactions \
+             sent in this session without a receipt when the node has none",
+        )
+        .arg(
+            Arg::with_name("address")
+                .long("address")
+                .takes_value(true)
+                .validator(|address| parse_address(address.as_str()))
+                .help("Only show the transactions sent by the address"),
+        )
+}
+
+/// Transaction pool processor
+pub fn txpool_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let pool = client
+        .txpool(sub_matches.value_of("address"))
+        .map_err(|err| format!("{}", err))?;
+    let pool = serde_json::to_value(pool).map_err(|err| format!("{}", err))?;
+    printer.println(&pool, is_color);
+    config.set("result".to_string(), pool);
+    Ok(())
+}
@@ -0,0 +1,265 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+
+use clap::{crate_version, App, Arg, ArgMatches};
+use serde_json::json;
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::{check_sha256, parse_version, GithubRelease};
+use cita_tool::{decode, recover_message, remove_0x, to_checksum_address, LowerHex, Signature};
+
+use crate::cli::parse_address;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+use crate::profile;
+
+/// Update command
+pub fn update_command() -> App<'static, 'static> {
+    App::new("update")
+        .about(
+            "Replace this executable by the binary of a GitHub release, checked by the sha256 \
+             of the release signed by the pinned release signer",
+        )
+        .arg(
+            Arg::with_name("check")
+                .long("check")
+                .help("Only show whether a newer release is out"),
+        )
+        .arg(
+            Arg::with_name("tag")
+                .long("tag")
+                .takes_value(true)
+                .help("The release to install, even an older one, the latest if absent"),
+        )
+        .arg(
+            Arg::with_name("force")
+                .long("force")
+                .help("Install the latest release even if it's not newer"),
+        )
+        .arg(
+            Arg::with_name("release-signer")
+                .long("release-signer")
+                .takes_value(true)
+                .validator(|address| parse_address(&address))
+                .help(
+                    "The address signing the checksums of the releases, `<asset>.sha256.sig`, \
+                     the `release_signer` of the profile if absent",
+                ),
+        )
+        .arg(
+            Arg::with_name("insecure")
+                .long("insecure")
+                .conflicts_with("release-signer")
+                .help(
+                    "Install without a release signer pinned, trusting whoever can publish \
+                     the release: the sha256 comes from the release too",
+                ),
+        )
+        .arg(
+            Arg::with_name("repo")
+                .long("repo")
+                .takes_value(true)
+                .default_value("citahub/cita-cli")
+                .help("The GitHub repository of the releases, `owner/name`"),
+        )
+        .arg(
+            Arg::with_name("api-url")
+                .long("api-url")
+                .takes_value(true)
+                .default_value("https://api.github.com")
+                .help("The GitHub API, the one of a GitHub Enterprise hosting the releases"),
+        )
+}
+
+/// Update processor
+pub fn update_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    _config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let current = crate_version!();
+    let release = client
+        .github_release(
+            sub_matches.value_of("api-url").unwrap(),
+            sub_matches.value_of("repo").unwrap(),
+            sub_matches.value_of("tag"),
+        )
+        .map_err(|err| err.to_string())?;
+    let newer = parse_version(&release.tag) > parse_version(current);
+    if sub_matches.is_present("check") {
+        printer.println(
+            &json!({ "current": current, "latest": release.tag, "newer": newer }),
+            printer.color(),
+        );
+        return Ok(());
+    }
+    if !newer && sub_matches.value_of("tag").is_none() && !sub_matches.is_present("force") {
+        printer.println(
+            &format!("Already the latest release {}", current),
+            printer.color(),
+        );
+        return Ok(());
+    }
+
+    let release_signer = sub_matches
+        .value_of("release-signer")
+        .map(str::to_owned)
+        .or_else(|| {
+            profile::load_config()["release_signer"]
+                .as_str()
+                .map(str::to_owned)
+        });
+    if release_signer.is_none() && !sub_matches.is_present("insecure") {
+        return Err(
+            "No release signer pinned: the sha256 of a release proves nothing about who \
+             published it. Pin the signer by --release-signer or the `release_signer` of the \
+             profile, or give --insecure"
+                .to_string(),
+        );
+    }
+
+    let name = asset_name(&release.tag)?;
+    let archive = download(&client, &release, &name)?;
+    let checksums_name = format!("{}.sha256", name);
+    let checksums = String::from_utf8(download(&client, &release, &checksums_name)?)
+        .map_err(|err| format!("{}: {}", checksums_name, err))?;
+    check_sha256(&archive, &checksums, &name).map_err(|err| err.to_string())?;
+    match release_signer {
+        Some(address) => {
+            let signature_name = format!("{}.sig", checksums_name);
+            let signature = String::from_utf8_lossy(&download(&client, &release, &signature_name)?)
+                .trim()
+                .to_owned();
+            let signature = Signature::from(
+                &decode(remove_0x(&signature))
+                    .map_err(|err| format!("{}: {}", signature_name, err))?,
+            );
+            // Signed by `key sign-message --message "$(cat <asset>.sha256)"`, without the newline
+            let signer = recover_message(&signature, checksums.trim_end().as_bytes())?;
+            if remove_0x(&address).to_lowercase() != signer.completed_lower_hex() {
+                return Err(format!(
+                    "The checksums of {} are signed by {}, not the release signer {}",
+                    release.tag,
+                    to_checksum_address(&signer),
+                    address
+                ));
+            }
+        }
+        None => printer.eprintln(
+            &"--insecure: the signature isn't checked, only the sha256 the release has".to_string(),
+            false,
+        ),
+    }
+
+    let exe = env::current_exe()
+        .and_then(fs::canonicalize)
+        .map_err(|err| format!("Can't locate this executable: {}", err))?;
+    // In the directory of the executable, so the rename replacing it is atomic
+    let work = exe
+        .parent()
+        .ok_or_else(|| format!("No directory of {}", exe.display()))?
+        .join(format!(".cita-cli-update-{}", process::id()));
+    fs::create_dir(&work).map_err(|err| format!("Can't create {}: {}", work.display(), err))?;
+    let installed = install(&work, &name, &archive, &exe);
+    let _ = fs::remove_dir_all(&work);
+    installed?;
+    printer.println(
+        &json!({ "from": current, "to": release.tag, "path": exe.display().to_string() }),
+        printer.color(),
+    );
+    Ok(())
+}
+
+/// The asset of the release built for this platform, named by the scripts in `.ci-script`
+fn asset_name(tag: &str) -> Result<String, String> {
+    let platform = match (env::consts::OS, env::consts::ARCH) {
+        ("linux", "x86_64") => "x86_64-musl",
+        ("macos", "x86_64") => "x86_64-mac-osx",
+        (os, arch) => {
+            return Err(format!(
+                "No release binary for {} {}, build it by `cargo install` instead",
+                os, arch
+            ))
+        }
+    };
+    Ok(format!("cita-cli-{}-tls-{}.tar.gz", platform, tag))
+}
+
+fn download(client: &Client, release: &GithubRelease, name: &str) -> Result<Vec<u8>, String> {
+    let url = release
+        .asset(name)
+        .ok_or_else(|| format!("The release {} has no {}", release.tag, name))?;
+    client.download(url).map_err(|err| err.to_string())
+}
+
+/// Extract the binary from the archive and move it over the executable, it isn't run before
+/// it replaces this one
+fn install(work: &Path, name: &str, archive: &[u8], exe: &Path) -> Result<(), String> {
+    let archive_path = work.join(name);
+    fs::write(&archive_path, archive).map_err(|err| err.to_string())?;
+    let status = Command::new("tar")
+        .arg("-xzf")
+        .arg(&archive_path)
+        .arg("-C")
+        .arg(work)
+        .status()
+        .map_err(|err| format!("Can't run tar: {}", err))?;
+    if !status.success() {
+        return Err(format!("tar can't extract {}: {}", name, status));
+    }
+    let binary = find_binary(work).ok_or_else(|| format!("No cita-cli in {}", name))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&binary, fs::Permissions::from_mode(0o755))
+            .map_err(|err| err.to_string())?;
+    }
+    fs::rename(&binary, exe).map_err(|err| format!("Can't replace {}: {}", exe.display(), err))
+}
+
+/// The `cita-cli` in the directory or below, the archive of macOS keeps the path it was built at
+fn find_binary(dir: &Path) -> Option<PathBuf> {
+    let mut entries = fs::read_dir(dir)
+        .ok()?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .collect::<Vec<PathBuf>>();
+    entries.sort();
+    entries.iter().find_map(|path| {
+        if path.is_dir() {
+            find_binary(path)
+        } else if path.file_name().is_some_and(|name| name == "cita-cli") {
+            Some(path.to_owned())
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{asset_name, find_binary};
+    use std::fs;
+
+    #[test]
+    fn test_find_binary() {
+        let dir = std::env::temp_dir().join(format!("cita-cli-update-{}", std::process::id()));
+        let bin = dir.join("Users/travis/.cargo/bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(dir.join("cita-cli-x86_64-mac-osx-tls-v20.2.3.tar.gz"), b"").unwrap();
+        assert_eq!(find_binary(&dir), None);
+        fs::write(bin.join("cita-cli"), b"").unwrap();
+        assert_eq!(find_binary(&dir), Some(bin.join("cita-cli")));
+        fs::remove_dir_all(&dir).unwrap();
+
+        if cfg!(all(target_os = "linux", target_arch = "x86_64")) {
+            assert_eq!(
+                asset_name("v20.2.3").unwrap(),
+                "cita-cli-x86_64-musl-tls-v20.2.3.tar.gz"
+            );
+        }
+    }
+}
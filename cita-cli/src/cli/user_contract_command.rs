@@ -0,0 +1,1069 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::process::Command;
+use std::time::Duration;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, json, Value};
+
+use cita_tool::client::basic::{AmendExt, Client, ClientExt, ReceiptStatus};
+use cita_tool::client::{
+    compare_quota, quota_baseline, QuotaCall, QuotaStatus, ReadCall, ReadCallResult, StorageLayout,
+    TransactionOptions,
+};
+use cita_tool::{
+    decode, decode_named_output, encode_json_input, remove_0x, Encryption, Hashable,
+    JsonRpcResponse, LowerHex,
+};
+
+use crate::cli::chain_command::result_value;
+use crate::cli::{
+    encryption, get_url, is_hex, key_validator, parse_address, parse_height, parse_privkey,
+    parse_u64,
+};
+use crate::deployments::Deployments;
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// User contract command
+pub fn user_contract_command() -> App<'static, 'static> {
+    App::new("contract")
+        .about("Interact with user contracts")
+        .subcommand(
+            SubCommand::with_name("call")
+                .about("Call a function of the contract by its ABI and decode the named outputs")
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .required(true)
+                        .takes_value(true)
+                        .help("ABI file path of the contract"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Address of the contract"),
+                )
+                .arg(
+                    Arg::with_name("function")
+                        .long("function")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Function name"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .multiple(true)
+                        .allow_hyphen_values(true)
+                        .help(
+                            "Function arguments in order, an array is a JSON literal such as \
+                             '[\"0x..\", \"0x..\"]', nested for the multi-dimensional arrays",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("args-file")
+                        .long("args-file")
+                        .takes_value(true)
+                        .conflicts_with("args")
+                        .help(
+                            "JSON file of the arguments, an object keyed by the parameter names \
+                             or an array in order",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("sender")
+                        .long("sender")
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The sender of the call"),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .default_value("latest")
+                        .takes_value(true)
+                        .validator(|s| parse_height(s.as_str()))
+                        .help("The height of the chain, hex string or tag 'latest'"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("simulate")
+                .about(
+                    "Call a prospective transaction at the latest state with its data and \
+                     sender, and report whether it would succeed before sending it",
+                )
+                .arg(
+                    Arg::with_name("code")
+                        .long("code")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .help("Binary content of the transaction"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The address of the invoking contract"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transaction, its address is the sender"),
+                )
+                .arg(
+                    Arg::with_name("sender")
+                        .long("sender")
+                        .takes_value(true)
+                        .conflicts_with("private-key")
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The sender of the transaction"),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .requires("function")
+                        .help("ABI file path to decode the output"),
+                )
+                .arg(
+                    Arg::with_name("function")
+                        .long("function")
+                        .takes_value(true)
+                        .requires("abi")
+                        .help("The function of the output"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("multicall")
+                .about("Execute many read calls in a batch and report the decoded results")
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help(
+                            "Json file of the calls: \
+                             [{\"address\": \"0x..\", \"function\": \"name\", \"args\": [], \"abi\": \"path\"}]",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .help("ABI file path used by the calls without their own abi"),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .default_value("latest")
+                        .takes_value(true)
+                        .validator(|s| parse_height(s.as_str()))
+                        .help("The height of the chain, hex string or tag 'latest'"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .default_value("json")
+                        .possible_values(&["json", "csv"])
+                        .takes_value(true)
+                        .help("Report format"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("quota-report")
+                .about(
+                    "Estimate the quota of the calls without sending them and compare it to \
+                     the baseline, fail if a call costs more than the tolerance allows",
+                )
+                .arg(
+                    Arg::with_name("file")
+                        .long("file")
+                        .required(true)
+                        .takes_value(true)
+                        .help(
+                            "Json file of the calls: [{\"name\": \"transfer\", \"address\": \"0x..\", \
+                             \"function\": \"name\", \"args\": [], \"abi\": \"path\", \"sender\": \"0x..\"}], \
+                             the name defaults to the function",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .help("ABI file path used by the calls without their own abi"),
+                )
+                .arg(
+                    Arg::with_name("baseline")
+                        .long("baseline")
+                        .default_value("quota-baseline.json")
+                        .takes_value(true)
+                        .help("Json file of the quota of every call by its name"),
+                )
+                .arg(
+                    Arg::with_name("tolerance")
+                        .long("tolerance")
+                        .default_value("0")
+                        .takes_value(true)
+                        .validator(|tolerance| match tolerance.parse::<f64>() {
+                            Ok(tolerance) if tolerance >= 0.0 && tolerance.is_finite() => Ok(()),
+                            _ => Err(format!("Invalid tolerance: {}", tolerance)),
+                        })
+                        .help("The change to the baseline allowed, in percent"),
+                )
+                .arg(
+                    Arg::with_name("update")
+                        .long("update")
+                        .help("Write the measured quota into the baseline instead of failing"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about(
+                    "Compile the source with solc and compare the runtime code, without the \
+                     metadata, against the code deployed at the address",
+                )
+                .arg(
+                    Arg::with_name("source")
+                        .long("source")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Solidity source file"),
+                )
+                .arg(
+                    Arg::with_name("contract")
+                        .long("contract")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Name of the contract in the source"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Address of the deployed contract"),
+                )
+                .arg(
+                    Arg::with_name("solc")
+                        .long("solc")
+                        .default_value("solc")
+                        .takes_value(true)
+                        .help("Path of the solc compiler, its version must match the deployment"),
+                )
+                .arg(
+                    Arg::with_name("optimize-runs")
+                        .long("optimize-runs")
+                        .takes_value(true)
+                        .validator(|runs| parse_u64(runs.as_ref()).map(|_| ()))
+                        .help("Enable the optimizer with the runs"),
+                )
+                .arg(
+                    Arg::with_name("evm-version")
+                        .long("evm-version")
+                        .takes_value(true)
+                        .help("Target EVM version of solc"),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .default_value("latest")
+                        .takes_value(true)
+                        .validator(|s| parse_height(s.as_str()))
+                        .help("The height of the chain, hex string or tag 'latest'"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("state")
+                .about("Read the storage of the contract and decode it into the state variables")
+                .arg(
+                    Arg::with_name("layout")
+                        .long("layout")
+                        .required(true)
+                        .takes_value(true)
+                        .help("Storage layout file output by `solc --storage-layout`"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Address of the contract"),
+                )
+                .arg(
+                    Arg::with_name("key")
+                        .long("key")
+                        .takes_value(true)
+                        .multiple(true)
+                        .number_of_values(1)
+                        .validator(|key| {
+                            if key.contains('=') {
+                                Ok(())
+                            } else {
+                                Err(format!("Invalid key {}, expect `name=key`", key))
+                            }
+                        })
+                        .help(
+                            "Key of a mapping as `name=key`, keys of nested mappings are \
+                             separated by commas",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("height")
+                        .long("height")
+                        .default_value("latest")
+                        .takes_value(true)
+                        .validator(|s| parse_height(s.as_str()))
+                        .help("The height of the chain, hex string or tag 'latest'"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("deploy")
+                .about(
+                    "Deploy the contract and record it in the registry by its name, \
+                     skip the deployment of the code already recorded with --idempotent",
+                )
+                .arg(
+                    Arg::with_name("code")
+                        .long("code")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .help("Creation code of the contract, with the constructor args"),
+                )
+                .arg(
+                    Arg::with_name("name")
+                        .long("name")
+                        .takes_value(true)
+                        .help("The name of the contract in the registry"),
+                )
+                .arg(
+                    Arg::with_name("idempotent")
+                        .long("idempotent")
+                        .requires("name")
+                        .help(
+                            "Skip the deployment if the registry has the same code under the \
+                             name on this chain and the code is still at its address",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("registry")
+                        .long("registry")
+                        .takes_value(true)
+                        .help("The registry file, default is ~/.cita-cli/deployments.json"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transaction"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .default_value("60")
+                        .takes_value(true)
+                        .validator(|timeout| parse_u64(timeout.as_ref()).map(|_| ()))
+                        .help("Seconds to wait for the receipt"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("upgrade")
+                .about(
+                    "Deploy a new implementation, point the contract to it and verify the \
+                     code hash afterwards",
+                )
+                .arg(
+                    Arg::with_name("code")
+                        .long("code")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|code| is_hex(code.as_str()))
+                        .help("Creation code of the new implementation"),
+                )
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("The contract to upgrade, the proxy in proxy mode"),
+                )
+                .arg(
+                    Arg::with_name("mode")
+                        .long("mode")
+                        .required(true)
+                        .possible_values(&["amend", "proxy"])
+                        .takes_value(true)
+                        .help(
+                            "amend: replace the code of the contract with the amend transaction \
+                             of the super admin; proxy: call the upgrade function of the proxy",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("upgrade-function")
+                        .long("upgrade-function")
+                        .default_value("upgradeTo(address)")
+                        .takes_value(true)
+                        .help("Upgrade function of the proxy, called with the new implementation"),
+                )
+                .arg(
+                    Arg::with_name("implementation-function")
+                        .long("implementation-function")
+                        .default_value("implementation()")
+                        .takes_value(true)
+                        .help("Function of the proxy returning the implementation, to verify"),
+                )
+                .arg(
+                    Arg::with_name("private-key")
+                        .long("private-key")
+                        .required(true)
+                        .takes_value(true)
+                        .validator(|privkey| key_validator(privkey.as_ref()).map(|_| ()))
+                        .help("The private key of the transactions, super admin in amend mode"),
+                )
+                .arg(
+                    Arg::with_name("quota")
+                        .long("quota")
+                        .takes_value(true)
+                        .validator(|quota| parse_u64(quota.as_ref()).map(|_| ()))
+                        .help("Transaction quota costs, default 10_000_000"),
+                )
+                .arg(
+                    Arg::with_name("timeout")
+                        .long("timeout")
+                        .default_value("60")
+                        .takes_value(true)
+                        .validator(|timeout| parse_u64(timeout.as_ref()).map(|_| ()))
+                        .help("Seconds to wait for the receipt of every step"),
+                ),
+        )
+}
+
+/// User contract processor
+pub fn user_contract_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    match sub_matches.subcommand() {
+        ("call", Some(m)) => {
+            let abi =
+                fs::read_to_string(m.value_of("abi").unwrap()).map_err(|err| format!("{}", err))?;
+            let function = m.value_of("function").unwrap();
+            let args = match m.value_of("args-file") {
+                Some(path) => {
+                    let content = fs::read_to_string(path)
+                        .map_err(|err| format!("Can't read {}: {}", path, err))?;
+                    serde_json::from_str(&content).map_err(|err| format!("{}: {}", path, err))?
+                }
+                None => Value::Array(
+                    m.values_of("args")
+                        .into_iter()
+                        .flatten()
+                        .map(|arg| {
+                            if arg.trim_start().starts_with('[') {
+                                serde_json::from_str(arg).map_err(|err| format!("{}: {}", arg, err))
+                            } else {
+                                Ok(Value::String(arg.to_owned()))
+                            }
+                        })
+                        .collect::<Result<Vec<Value>, String>>()?,
+                ),
+            };
+            let data = encode_json_input(&abi, function, &args, false)
+                .map_err(|err| format!("{}", err))?;
+            let address = m.value_of("address").unwrap();
+            let output = result_value(
+                client
+                    .call(
+                        m.value_of("sender"),
+                        address,
+                        Some(&format!("0x{}", data)),
+                        m.value_of("height").unwrap(),
+                    )
+                    .map_err(|err| format!("{}", err))?,
+            )
+            .map_err(|err| format!("{}", err))?;
+            let output = decode_named_output(&abi, function, output.as_str().unwrap_or_default())
+                .map_err(|err| format!("{}", err))?;
+            let report = json!({
+                "address": address,
+                "function": function,
+                "args": args,
+                "output": output,
+            });
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        ("simulate", Some(m)) => {
+            let mut client = client;
+            if let Some(private_key) = m.value_of("private-key") {
+                client.set_private_key(&parse_privkey(private_key, encryption(m, config))?);
+            }
+            let options = TransactionOptions::new()
+                .set_code(m.value_of("code").unwrap())
+                .set_address(m.value_of("address").unwrap());
+            let simulation = client
+                .simulate(&options, m.value_of("sender"))
+                .map_err(|err| format!("{}", err))?;
+            let decoded = match (m.value_of("abi"), &simulation.output) {
+                (Some(path), Some(output)) => {
+                    let abi = fs::read_to_string(path).map_err(|err| format!("{}", err))?;
+                    Some(
+                        decode_named_output(&abi, m.value_of("function").unwrap(), output)
+                            .map_err(|err| format!("{}", err))?,
+                    )
+                }
+                _ => None,
+            };
+            let mut report = serde_json::to_value(&simulation).map_err(|err| format!("{}", err))?;
+            if let Some(decoded) = decoded {
+                report["decoded"] = decoded;
+            }
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            match simulation.error {
+                Some(err) => Err(format!("The transaction would fail: {}", err)),
+                None => Ok(()),
+            }
+        }
+        ("multicall", Some(m)) => {
+            let calls = fs::read_to_string(m.value_of("file").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            let calls: Vec<ReadCall> =
+                serde_json::from_str(&calls).map_err(|err| format!("{}", err))?;
+            let abi = match m.value_of("abi") {
+                Some(path) => Some(fs::read_to_string(path).map_err(|err| format!("{}", err))?),
+                None => None,
+            };
+            let results = client
+                .multicall(&calls, abi.as_deref(), m.value_of("height").unwrap())
+                .map_err(|err| format!("{}", err))?;
+
+            let report = serde_json::to_value(&results).map_err(|err| format!("{}", err))?;
+            if m.value_of("format") == Some("csv") {
+                printer.println(&to_csv(&results), false);
+            } else {
+                printer.println(&report, is_color);
+            }
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        ("quota-report", Some(m)) => {
+            let calls = fs::read_to_string(m.value_of("file").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            let calls: Vec<QuotaCall> =
+                serde_json::from_str(&calls).map_err(|err| format!("{}", err))?;
+            let abi = match m.value_of("abi") {
+                Some(path) => Some(fs::read_to_string(path).map_err(|err| format!("{}", err))?),
+                None => None,
+            };
+            let baseline_path = m.value_of("baseline").unwrap();
+            let baseline: BTreeMap<String, u64> = match fs::read_to_string(baseline_path) {
+                Ok(content) => serde_json::from_str(&content)
+                    .map_err(|err| format!("Invalid baseline {}: {}", baseline_path, err))?,
+                Err(_) => BTreeMap::new(),
+            };
+            let tolerance = m.value_of("tolerance").unwrap().parse::<f64>().unwrap();
+
+            let used = client.quota_used(&calls, abi.as_deref());
+            let usages = compare_quota(&used, &baseline, tolerance);
+            let failed = usages
+                .iter()
+                .filter(|usage| {
+                    usage.status == QuotaStatus::Regressed || usage.status == QuotaStatus::Failed
+                })
+                .count();
+            let report = json!({
+                "baseline": baseline_path,
+                "tolerance": tolerance,
+                "calls": usages,
+                "failed": failed,
+            });
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+
+            if m.is_present("update") {
+                let content = serde_json::to_string_pretty(&quota_baseline(&used)).unwrap();
+                fs::write(baseline_path, content).map_err(|err| format!("{}", err))?;
+                Ok(())
+            } else if failed > 0 {
+                Err(format!(
+                    "{} of {} calls regressed or failed against {}",
+                    failed,
+                    usages.len(),
+                    baseline_path
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        ("verify", Some(m)) => {
+            let compiled = compile_runtime(
+                m.value_of("solc").unwrap(),
+                m.value_of("source").unwrap(),
+                m.value_of("contract").unwrap(),
+                m.value_of("optimize-runs"),
+                m.value_of("evm-version"),
+            )?;
+            let address = m.value_of("address").unwrap();
+            let deployed = result_value(
+                client
+                    .get_code(address, m.value_of("height").unwrap())
+                    .map_err(|err| format!("{}", err))?,
+            )
+            .map_err(|err| format!("{}", err))?;
+            let deployed = decode(remove_0x(deployed.as_str().unwrap_or_default()))
+                .map_err(|err| err.to_string())?;
+            if deployed.is_empty() {
+                return Err(format!("No code at {}", address));
+            }
+
+            let (compiled, deployed) = (strip_metadata(&compiled), strip_metadata(&deployed));
+            let mismatch = compiled
+                .iter()
+                .zip(deployed)
+                .position(|(a, b)| a != b)
+                .or_else(|| {
+                    if compiled.len() == deployed.len() {
+                        None
+                    } else {
+                        Some(compiled.len().min(deployed.len()))
+                    }
+                });
+            let report = json!({
+                "address": address,
+                "contract": m.value_of("contract").unwrap(),
+                "match": mismatch.is_none(),
+                "compiledSize": compiled.len(),
+                "deployedSize": deployed.len(),
+                "firstMismatchOffset": mismatch,
+            });
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        ("state", Some(m)) => {
+            let layout = fs::read_to_string(m.value_of("layout").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            let layout = StorageLayout::from_json(&layout).map_err(|err| format!("{}", err))?;
+            let mut keys: HashMap<String, Vec<String>> = HashMap::new();
+            for key in m.values_of("key").into_iter().flatten() {
+                let mut parts = key.splitn(2, '=');
+                keys.entry(parts.next().unwrap().trim().to_owned())
+                    .or_default()
+                    .push(parts.next().unwrap().trim().to_owned());
+            }
+            let address = m.value_of("address").unwrap();
+            let state = client
+                .storage_state(&layout, address, &keys, m.value_of("height").unwrap())
+                .map_err(|err| format!("{}", err))?;
+            let report = json!({
+                "address": address,
+                "state": state,
+            });
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        ("deploy", Some(m)) => {
+            let encryption = encryption(m, config);
+            let mut client = client;
+            client.set_private_key(&parse_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption,
+            )?);
+            let deployer = Upgrade {
+                address: "0x",
+                quota: m.value_of("quota").map(|quota| parse_u64(quota).unwrap()),
+                timeout: Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?),
+                encryption,
+            };
+            let code = m.value_of("code").unwrap();
+            let code_hash = deployer.code_hash(code)?;
+            let name = m.value_of("name");
+            let registry = match name {
+                Some(_) => Some(Deployments::load(m.value_of("registry"))?),
+                None => None,
+            };
+            let chain = chain_key(&client)?;
+
+            if let (true, Some(name), Some(registry)) =
+                (m.is_present("idempotent"), name, &registry)
+            {
+                if let Some(deployment) = registry.get(&chain, name, &code_hash) {
+                    let address = deployment["address"].as_str().unwrap_or_default();
+                    // The chain may be reset since, the code must still be there
+                    let runtime_hash = deployer
+                        .code(&client, address)
+                        .and_then(|runtime| deployer.code_hash(&runtime))
+                        .ok();
+                    if runtime_hash.is_some()
+                        && runtime_hash.as_deref() == deployment["runtimeCodeHash"].as_str()
+                    {
+                        let mut report = deployment.clone();
+                        report["deployed"] = json!(false);
+                        printer.println(&report, is_color);
+                        config.set("result".to_string(), report);
+                        return Ok(());
+                    }
+                }
+            }
+
+            let address = deployer.deploy(&mut client, code)?;
+            let runtime_hash = deployer.code_hash(&deployer.code(&client, &address)?)?;
+            let mut report = json!({
+                "name": name,
+                "chain": chain,
+                "address": address,
+                "codeHash": code_hash,
+                "runtimeCodeHash": runtime_hash,
+            });
+            if let (Some(name), Some(mut registry)) = (name, registry) {
+                registry.record(&chain, name, &code_hash, report.clone());
+                registry.save()?;
+            }
+            report["deployed"] = json!(true);
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        ("upgrade", Some(m)) => {
+            let encryption = encryption(m, config);
+            let mut client = client;
+            client.set_private_key(&parse_privkey(
+                m.value_of("private-key").unwrap(),
+                encryption,
+            )?);
+            let upgrade = Upgrade {
+                address: m.value_of("address").unwrap(),
+                quota: m.value_of("quota").map(|quota| parse_u64(quota).unwrap()),
+                timeout: Duration::from_secs(parse_u64(m.value_of("timeout").unwrap())?),
+                encryption,
+            };
+            let report = match m.value_of("mode") {
+                Some("amend") => upgrade.amend(&mut client, m.value_of("code").unwrap()),
+                _ => upgrade.proxy(
+                    &mut client,
+                    m.value_of("code").unwrap(),
+                    m.value_of("upgrade-function").unwrap(),
+                    m.value_of("implementation-function").unwrap(),
+                ),
+            }?;
+            printer.println(&report, is_color);
+            config.set("result".to_string(), report);
+            Ok(())
+        }
+        _ => Err(sub_matches.usage().to_owned()),
+    }
+}
+
+/// Compile the contract with solc and return its runtime code
+fn compile_runtime(
+    solc: &str,
+    source: &str,
+    contract: &str,
+    optimize_runs: Option<&str>,
+    evm_version: Option<&str>,
+) -> Result<Vec<u8>, String> {
+    let mut command = Command::new(solc);
+    command.args(["--combined-json", "bin-runtime"]);
+    if let Some(runs) = optimize_runs {
+        command.args(["--optimize", "--optimize-runs", runs]);
+    }
+    if let Some(version) = evm_version {
+        command.args(["--evm-version", version]);
+    }
+    let output = command
+        .arg(source)
+        .output()
+        .map_err(|err| format!("Can't run {}: {}", solc, err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).into_owned());
+    }
+
+    let compiled: Value =
+        serde_json::from_slice(&output.stdout).map_err(|err| format!("{}", err))?;
+    // The contracts are keyed by `<path>:<name>`
+    let code = compiled["contracts"]
+        .as_object()
+        .and_then(|contracts| {
+            contracts
+                .iter()
+                .find(|(name, _)| name.rsplit(':').next() == Some(contract))
+        })
+        .and_then(|(_, compiled)| compiled["bin-runtime"].as_str())
+        .ok_or_else(|| format!("No contract {} in {}", contract, source))?;
+    decode(remove_0x(code)).map_err(|err| err.to_string())
+}
+
+/// Strip the CBOR metadata appended by solc, the last 2 bytes are its length
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code;
+    }
+    let len = usize::from(code[code.len() - 2]) << 8 | usize::from(code[code.len() - 1]);
+    match code.len().checked_sub(len + 2) {
+        // The metadata is a CBOR map
+        Some(start) if len > 0 && (0xa1..=0xa5).contains(&code[start]) => &code[..start],
+        _ => code,
+    }
+}
+
+/// The chain the registries of deployments and migrations key by, the v1 chain id or
+/// the v0 one when the chain has none
+pub(crate) fn chain_key(client: &Client) -> Result<String, String> {
+    let chain = client.get_chain_info().map_err(|err| format!("{}", err))?;
+    if chain.chain_id_v1.is_zero() {
+        Ok(format!("{:#x}", chain.chain_id))
+    } else {
+        Ok(format!("{:#x}", chain.chain_id_v1))
+    }
+}
+
+/// The steps of `contract upgrade`, `contract deploy` and `migrate`
+pub(crate) struct Upgrade<'a> {
+    pub(crate) address: &'a str,
+    pub(crate) quota: Option<u64>,
+    pub(crate) timeout: Duration,
+    pub(crate) encryption: Encryption,
+}
+
+impl<'a> Upgrade<'a> {
+    /// Deploy the code to get its runtime code, then amend the code of the contract with it
+    fn amend(&self, client: &mut Client, code: &str) -> Result<Value, String> {
+        let implementation = self.deploy(client, code)?;
+        let runtime = self.code(client, &implementation)?;
+        let response = client
+            .amend_code(self.address, &runtime, self.quota)
+            .map_err(|err| format!("{}", err))?;
+        let amend = self.wait(client, response, "amend code")?;
+
+        let code_hash = self.code_hash(&self.code(client, self.address)?)?;
+        let expected = self.code_hash(&runtime)?;
+        if code_hash != expected {
+            return Err(format!(
+                "Code hash of {} is {} after the amend, expect {}",
+                self.address, code_hash, expected
+            ));
+        }
+        Ok(json!({
+            "mode": "amend",
+            "address": self.address,
+            "implementation": implementation,
+            "amendTransaction": amend["transactionHash"],
+            "codeHash": code_hash,
+            "verified": true,
+        }))
+    }
+
+    /// Deploy the code, call the upgrade function of the proxy with the new implementation,
+    /// then check the proxy reports the implementation
+    fn proxy(
+        &self,
+        client: &mut Client,
+        code: &str,
+        upgrade_function: &str,
+        implementation_function: &str,
+    ) -> Result<Value, String> {
+        let implementation = self.deploy(client, code)?;
+        let data = format!(
+            "0x{}{:0>64}",
+            selector(upgrade_function),
+            remove_0x(&implementation)
+        );
+        let upgrade = self.send(client, self.address, &data, "upgrade")?;
+
+        let current = result_value(
+            client
+                .call(
+                    None,
+                    self.address,
+                    Some(&format!("0x{}", selector(implementation_function))),
+                    "latest",
+                )
+                .map_err(|err| format!("{}", err))?,
+        )
+        .map_err(|err| format!("{}", err))?;
+        let current = current
+            .as_str()
+            .map(remove_0x)
+            .filter(|word| word.len() == 64)
+            .map(|word| format!("0x{}", &word[24..]));
+        if current.as_deref() != Some(implementation.to_lowercase().as_str()) {
+            return Err(format!(
+                "The proxy points to {}, not the new implementation {}",
+                current.unwrap_or_else(|| "unknown".to_string()),
+                implementation
+            ));
+        }
+        Ok(json!({
+            "mode": "proxy",
+            "address": self.address,
+            "implementation": implementation,
+            "upgradeTransaction": upgrade["transactionHash"],
+            "codeHash": self.code_hash(&self.code(client, &implementation)?)?,
+            "verified": true,
+        }))
+    }
+
+    /// Deploy the code and return the address of the contract
+    pub(crate) fn deploy(&self, client: &mut Client, code: &str) -> Result<String, String> {
+        let tx_options = TransactionOptions::new()
+            .set_code(code)
+            .set_address("0x")
+            .set_quota(self.quota);
+        let response = client
+            .send_raw_transaction(tx_options)
+            .map_err(|err| format!("{}", err))?;
+        let receipt = self.wait(client, response, "deploy")?;
+        receipt["contractAddress"]
+            .as_str()
+            .map(str::to_owned)
+            .ok_or_else(|| "The deployment created no contract".to_string())
+    }
+
+    /// Send the data to the contract and return the receipt
+    pub(crate) fn send(
+        &self,
+        client: &mut Client,
+        address: &str,
+        data: &str,
+        step: &str,
+    ) -> Result<Value, String> {
+        let tx_options = TransactionOptions::new()
+            .set_code(data)
+            .set_address(address)
+            .set_quota(self.quota);
+        let response = client
+            .send_raw_transaction(tx_options)
+            .map_err(|err| format!("{}", err))?;
+        self.wait(client, response, step)
+    }
+
+    /// Wait for the receipt of the sent transaction, fail if it failed
+    pub(crate) fn wait(
+        &self,
+        client: &Client,
+        response: JsonRpcResponse,
+        step: &str,
+    ) -> Result<Value, String> {
+        let sent = result_value(response).map_err(|err| format!("{} failed: {}", step, err))?;
+        let hash = sent["hash"].as_str().unwrap_or_default();
+        let receipt = match client
+            .wait_for_receipt(hash, 0, Duration::from_secs(1), self.timeout)
+            .map_err(|err| format!("{}", err))?
+        {
+            ReceiptStatus::Confirmed(receipt) => {
+                result_value(receipt).map_err(|err| format!("{}", err))?
+            }
+            ReceiptStatus::Dropped => {
+                return Err(format!("{} transaction {} was dropped", step, hash))
+            }
+            ReceiptStatus::Timeout => {
+                return Err(format!(
+                    "Timeout waiting for the {} transaction {}",
+                    step, hash
+                ))
+            }
+        };
+        match receipt["errorMessage"].as_str() {
+            Some(err) => Err(format!("{} transaction {} failed: {}", step, hash, err)),
+            None => Ok(receipt),
+        }
+    }
+
+    pub(crate) fn code(&self, client: &Client, address: &str) -> Result<String, String> {
+        let code = result_value(
+            client
+                .get_code(address, "latest")
+                .map_err(|err| format!("{}", err))?,
+        )
+        .map_err(|err| format!("{}", err))?;
+        match code.as_str() {
+            Some(code) if !remove_0x(code).is_empty() => Ok(code.to_owned()),
+            _ => Err(format!("No code at {}", address)),
+        }
+    }
+
+    pub(crate) fn code_hash(&self, code: &str) -> Result<String, String> {
+        let code = decode(remove_0x(code)).map_err(|err| err.to_string())?;
+        Ok(code
+            .crypt_hash(self.encryption)
+            .completed_lower_hex_with_0x())
+    }
+}
+
+/// The 4 bytes function selector of the signature, in hex
+fn selector(signature: &str) -> String {
+    signature
+        .as_bytes()
+        .crypt_hash(Encryption::Secp256k1)
+        .lower_hex()[..8]
+        .to_owned()
+}
+
+fn to_csv(results: &[ReadCallResult]) -> String {
+    let field = |content: &str| format!("\"{}\"", content.replace('"', "\"\""));
+    let mut csv = vec!["address,function,args,output,error".to_string()];
+    csv.extend(results.iter().map(|result| {
+        [
+            field(&result.address),
+            field(&result.function),
+            field(&result.args.join(" ")),
+            field(&Value::Array(result.output.clone()).to_string()),
+            field(result.error.as_deref().unwrap_or_default()),
+        ]
+        .join(",")
+    }));
+    csv.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::strip_metadata;
+
+    #[test]
+    fn test_strip_metadata() {
+        // a1 65 "bzzr0" 58 20 <32 bytes> 00 29
+        let mut code = vec![0x60, 0x80, 0x60, 0x40];
+        code.extend_from_slice(&[0xa1, 0x65, b'b', b'z', b'z', b'r', b'0', 0x58, 0x20]);
+        code.extend_from_slice(&[0x11; 32]);
+        code.extend_from_slice(&[0x00, 0x29]);
+        assert_eq!(strip_metadata(&code), &[0x60, 0x80, 0x60, 0x40]);
+        assert_eq!(
+            strip_metadata(&[0x60, 0x80, 0x00, 0x01]),
+            &[0x60, 0x80, 0x00, 0x01]
+        );
+        assert_eq!(strip_metadata(&[0x00]), &[0x00]);
+    }
+}
@@ -1,10 +1,21 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
 use std::str::FromStr;
 
-use clap::{App, ArgMatches};
+use clap::{App, Arg, ArgMatches};
+use serde_json::{json, Value};
 
-use cita_tool::{remove_0x, Address, Encryption, PrivateKey, H256, H512, U256};
+use cita_tool::{
+    decode, hex_to_u256, is_mixed_case, parse_checksum_address, parse_hex_address, remove_0x,
+    to_checksum_address, Encryption, Hashable, LowerHex, ParamsValue, PrivateKey, H256, H512, U256,
+};
 
+use crate::hsm::HSM_KEY_PREFIX;
 use crate::interactive::GlobalConfig;
+use crate::ledger::{self, LEDGER_KEY_PREFIX};
+use crate::printer::{Printable, Printer};
 
 /// Get url from arg match
 pub fn get_url<'a>(m: &'a ArgMatches, config: &'a GlobalConfig) -> &'a str {
@@ -20,6 +31,27 @@ pub fn get_url<'a>(m: &'a ArgMatches, config: &'a GlobalConfig) -> &'a str {
     }
 }
 
+/// Whether the flag is present in the arg match or in any of its subcommands
+pub fn flag_present(m: &ArgMatches, name: &str) -> bool {
+    m.is_present(name) || m.subcommand().1.is_some_and(|m| flag_present(m, name))
+}
+
+/// Get the value of the arg from the arg match or from the deepest subcommand that has it
+pub fn deep_value_of<'a>(m: &'a ArgMatches, name: &str) -> Option<&'a str> {
+    m.subcommand()
+        .1
+        .and_then(|m| deep_value_of(m, name))
+        .or_else(|| m.value_of(name))
+}
+
+/// Get the values of the arg from the deepest subcommand that has it
+pub fn deep_values_of<'a>(m: &'a ArgMatches, name: &str) -> Option<Vec<&'a str>> {
+    m.subcommand()
+        .1
+        .and_then(|m| deep_values_of(m, name))
+        .or_else(|| m.values_of(name).map(Iterator::collect))
+}
+
 /// the hexadecimal or numeric type string resolves to u64
 pub fn parse_u64(height: &str) -> Result<u64, String> {
     match is_hex(height) {
@@ -42,13 +74,53 @@ pub fn parse_u32(value: &str) -> Result<u32, String> {
     }
 }
 
-/// Attempt to resolve the private key
+/// Parse `key=value` of `--extra`, the value is json if it parses, or else a string
+pub fn parse_extra_param(param: &str) -> Result<(String, ParamsValue), String> {
+    let (key, value) = param
+        .split_once('=')
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("Expected key=value, got {}", param))?;
+    let value = serde_json::from_str::<Value>(value)
+        .map(ParamsValue::from)
+        .unwrap_or_else(|_| ParamsValue::from(value));
+    Ok((key.to_owned(), value))
+}
+
+/// Whether the key is kept by a device, `hsm:<label>` or `ledger:<derivation path>`
+fn is_device_key(hash: &str) -> bool {
+    hash.starts_with(HSM_KEY_PREFIX) || hash.starts_with(LEDGER_KEY_PREFIX)
+}
+
+/// Attempt to resolve the private key, a key of the HSM, `hsm:<label>`, or of the Ledger,
+/// `ledger:<derivation path>`, is `PrivateKey::Null`: it never leaves the device, the signer
+/// set to the client by `hsm::signer` or `ledger::signer` signs instead
 pub fn parse_privkey(hash: &str, encryption: Encryption) -> Result<PrivateKey, String> {
+    if is_device_key(hash) {
+        return Ok(PrivateKey::Null);
+    }
+    parse_local_privkey(hash, encryption)
+}
+
+/// Attempt to resolve the private key itself, for the commands using more than the transaction
+/// signatures of the key
+pub fn parse_local_privkey(hash: &str, encryption: Encryption) -> Result<PrivateKey, String> {
+    if is_device_key(hash) {
+        return Err(format!(
+            "{} is kept by the device, it only signs the transactions sent",
+            hash
+        ));
+    }
     is_hex(hash)?;
-    Ok(PrivateKey::from_str(remove_0x(hash), encryption)?)
+    PrivateKey::from_str(remove_0x(hash), encryption)
 }
 
 pub fn key_validator(hash: &str) -> Result<(), String> {
+    if hash.len() > HSM_KEY_PREFIX.len() && hash.starts_with(HSM_KEY_PREFIX) {
+        return Ok(());
+    }
+    if let Some(path) = hash.strip_prefix(LEDGER_KEY_PREFIX) {
+        return ledger::path_validator(path);
+    }
     is_hex(hash)?;
     if hash.len() > 66 {
         h512_validator(hash)
@@ -80,8 +152,7 @@ pub fn parse_height(height: &str) -> Result<(), String> {
 
 pub fn parse_u256(value: &str) -> Result<U256, String> {
     match is_hex(value) {
-        Ok(_) => Ok(U256::from_str(remove_0x(value))
-            .map_err(|_| String::from("Value can't parse into u256"))?),
+        Ok(_) => Ok(hex_to_u256(value).map_err(|_| String::from("Value can't parse into u256"))?),
         Err(_) => {
             Ok(U256::from_dec_str(value)
                 .map_err(|_| String::from("Value can't parse into u256"))?)
@@ -104,13 +175,31 @@ pub fn h512_validator(value: &str) -> Result<(), String> {
 }
 
 pub fn parse_address(value: &str) -> Result<(), String> {
-    is_hex(value)?;
     if remove_0x(value).is_empty() {
         return Ok(());
     }
-    Address::from_str(remove_0x(value))
-        .map(|_| ())
-        .map_err(|err| err.to_string())
+    address_validator(value)
+}
+
+/// A non-empty address of 20 bytes, warn about a mixed-case one failing the checksum
+pub fn address_validator(value: &str) -> Result<(), String> {
+    let address = parse_hex_address(value).map_err(|err| err.to_string())?;
+    if is_mixed_case(value) && parse_checksum_address(value).is_err() {
+        eprintln!(
+            "Warning: address {} fails the checksum, expect {}",
+            value,
+            to_checksum_address(&address)
+        );
+    }
+    Ok(())
+}
+
+/// A comma separated list of addresses
+pub fn address_list_validator(value: &str) -> Result<(), String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .try_for_each(address_validator)
 }
 
 pub fn encryption(m: &ArgMatches, config: &GlobalConfig) -> Encryption {
@@ -120,6 +209,133 @@ pub fn encryption(m: &ArgMatches, config: &GlobalConfig) -> Encryption {
     }
 }
 
+/// Ask the user to confirm on the terminal, only `y` or `yes` is taken as consent
+pub fn confirm(prompt: &str) -> Result<bool, String> {
+    eprint!("{} [y/N] ", prompt);
+    io::stderr().flush().map_err(|err| err.to_string())?;
+    let mut answer = String::new();
+    io::stdin()
+        .read_line(&mut answer)
+        .map_err(|err| err.to_string())?;
+    let answer = answer.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Ask the user for a password on the terminal, without echo where `stty` is available,
+/// or read it from the piped stdin
+pub fn read_password(prompt: &str) -> Result<String, String> {
+    let is_tty = atty::is(atty::Stream::Stdin);
+    if is_tty {
+        eprint!("{}: ", prompt);
+        io::stderr().flush().map_err(|err| err.to_string())?;
+    }
+    let stty = |mode: &str| {
+        Command::new("stty")
+            .arg(mode)
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    };
+    let no_echo = is_tty && stty("-echo");
+    let mut password = String::new();
+    let read = io::stdin().read_line(&mut password);
+    if no_echo {
+        stty("echo");
+        eprintln!();
+    }
+    read.map_err(|err| err.to_string())?;
+    Ok(password.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+/// The clipboard tools tried in order, with their args reading the text from stdin
+const CLIPBOARD_TOOLS: [(&str, &[&str]); 5] = [
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip.exe", &[]),
+];
+
+/// The `--out`, `--binary` and `--copy` args of the commands producing long hex
+pub fn hex_output_args() -> Vec<Arg<'static, 'static>> {
+    vec![
+        Arg::with_name("out")
+            .long("out")
+            .takes_value(true)
+            .help("Write the hex to the file, print only its hash and size"),
+        Arg::with_name("binary")
+            .long("binary")
+            .requires("out")
+            .help("Write the raw bytes to the file instead of the hex"),
+        Arg::with_name("copy")
+            .long("copy")
+            .help("Copy the hex to the clipboard, print only its hash and size"),
+    ]
+}
+
+/// Print the content, or send its hex to the file of `--out` and or the clipboard
+/// by `--copy` and print the hash and the size of the bytes
+pub fn output_hex<P: Printable>(
+    m: &ArgMatches,
+    printer: &Printer,
+    content: &P,
+    hex: &str,
+    encryption: Encryption,
+    is_color: bool,
+) -> Result<(), String> {
+    let out = m.value_of("out");
+    if out.is_none() && !m.is_present("copy") {
+        printer.println(content, is_color);
+        return Ok(());
+    }
+    let bytes = decode(remove_0x(hex)).map_err(|err| err.to_string())?;
+    let hex = format!("0x{}", remove_0x(hex));
+    if let Some(path) = out {
+        let written = if m.is_present("binary") {
+            fs::write(path, &bytes)
+        } else {
+            fs::write(path, &hex)
+        };
+        written.map_err(|err| format!("Can't write {}: {}", path, err))?;
+    }
+    if m.is_present("copy") {
+        copy_to_clipboard(&hex)?;
+    }
+    printer.println(
+        &json!({
+            "output": out,
+            "copied": m.is_present("copy"),
+            "bytes": bytes.len(),
+            "hash": bytes.crypt_hash(encryption).lower_hex_with_0x(),
+        }),
+        is_color,
+    );
+    Ok(())
+}
+
+/// Pipe the text to the first clipboard tool available
+fn copy_to_clipboard(text: &str) -> Result<(), String> {
+    for (tool, args) in CLIPBOARD_TOOLS.iter() {
+        let mut child = match Command::new(tool)
+            .args(args.iter())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+        let written = child.stdin.take().unwrap().write_all(text.as_bytes());
+        if child.wait().map(|status| status.success()).unwrap_or(false) && written.is_ok() {
+            return Ok(());
+        }
+    }
+    Err("No clipboard tool works, install xclip, xsel or wl-clipboard".to_string())
+}
+
 /// Search command tree
 pub fn search_app<'a, 'b>(
     app: &App<'a, 'b>,
@@ -149,3 +365,22 @@ pub fn search_app<'a, 'b>(
         };
     }
 }
+
+/// The long names of the options taking a private key in the command tree, the ones
+/// validated by `key_validator`, told apart by accepting the key of an HSM
+pub fn key_args<'a, 'b>(app: &App<'a, 'b>, names: &mut BTreeSet<String>) {
+    let hsm_key = format!("{}key", HSM_KEY_PREFIX);
+    for opt in app.p.opts() {
+        let takes_key = opt
+            .v
+            .validator
+            .as_ref()
+            .is_some_and(|validator| validator(hsm_key.clone()).is_ok());
+        if let (true, Some(long)) = (takes_key, opt.s.long) {
+            names.insert(long.to_owned());
+        }
+    }
+    for inner_app in &app.p.subcommands {
+        key_args(inner_app, names);
+    }
+}
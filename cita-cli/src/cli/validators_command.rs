@@ -0,0 +1,225 @@
+use std::collections::BTreeSet;
+use std::str::FromStr;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{json, Value};
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::{
+    pubkey_to_address, remove_0x, Encryption, Hashable, JsonRpcParams, LowerHex, ParamsValue,
+    ToolError, UnverifiedTransaction,
+};
+
+use crate::checkpoint::{checkpoint_args, Checkpointer};
+use crate::cli::chain_command::result_value;
+use crate::cli::time_command::{block_range, time_range_args};
+use crate::cli::{encryption, get_url, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+
+/// Address of the NodeManager system contract
+const NODE_MANAGER: &str = "0xffffffffffffffffffffffffffffffffff020001";
+/// Events of the NodeManager which change the consensus nodes
+const NODE_EVENTS: [&str; 3] = [
+    "ApproveNode(address)",
+    "DeleteNode(address)",
+    "SetStake(address,uint256)",
+];
+/// How many getMetaData requests are sent in one batch
+const METADATA_BATCH: u64 = 100;
+
+/// Validators command
+pub fn validators_command() -> App<'static, 'static> {
+    App::new("validators")
+        .about("Consensus nodes of the chain")
+        .subcommand(
+            SubCommand::with_name("history")
+                .about(
+                    "Report every change of the validator set over a block range, \
+                     with the NodeManager transactions that caused it",
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .required_unless("from-time")
+                        .takes_value(true)
+                        .validator(|from| parse_u64(from.as_str()).map(|_| ()))
+                        .help("The first block height, hex string or number"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .validator(|to| parse_u64(to.as_str()).map(|_| ()))
+                        .help("The last block height, default is the current height"),
+                )
+                .args(&time_range_args())
+                .args(&checkpoint_args()),
+        )
+}
+
+/// Validators processor
+pub fn validators_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &mut GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+    let is_color = !sub_matches.is_present("no-color") && config.color();
+
+    let report = match sub_matches.subcommand() {
+        ("history", Some(m)) => {
+            let (from, to) = block_range(m, &client)?;
+            let checkpointer = Checkpointer::open(m, "validators history")?;
+            validators_history(&client, from, to, encryption(m, config), &checkpointer)
+                .map_err(|err| format!("{}", err))?
+        }
+        _ => return Err(sub_matches.usage().to_owned()),
+    };
+    printer.println(&report, is_color);
+    config.set("result".to_string(), report);
+    Ok(())
+}
+
+/// Compare the validators of every block with the previous one,
+/// the report so far is saved in the checkpoint after every batch
+fn validators_history(
+    client: &Client,
+    mut from: u64,
+    to: u64,
+    encryption: Encryption,
+    checkpointer: &Checkpointer,
+) -> Result<Value, ToolError> {
+    let mut previous: Option<BTreeSet<String>> = None;
+    let mut initial = Value::Null;
+    let mut changes = Vec::new();
+
+    let mut start = from;
+    if let Some(checkpoint) = checkpointer.resumed() {
+        let state = &checkpoint.state;
+        from = state["from"].as_u64().unwrap_or(from);
+        initial = state["initial"].clone();
+        changes = state["changes"].as_array().cloned().unwrap_or_default();
+        previous = state["current"].as_array().map(|validators| {
+            validators
+                .iter()
+                .filter_map(|address| address.as_str().map(ToOwned::to_owned))
+                .collect()
+        });
+        start = checkpoint.next_height;
+    }
+    while start <= to {
+        let end = to.min(start + METADATA_BATCH - 1);
+        let params = (start..=end)
+            .map(|height| {
+                JsonRpcParams::new()
+                    .insert("method", ParamsValue::String("getMetaData".to_string()))
+                    .insert(
+                        "params",
+                        ParamsValue::List(vec![ParamsValue::String(format!("{:#x}", height))]),
+                    )
+            })
+            .collect::<Vec<_>>();
+        let responses = client.send_request(params.into_iter())?;
+
+        for (height, response) in (start..=end).zip(responses) {
+            // Nodes are repeated by their stakes, only the set matters here
+            let validators: BTreeSet<String> = result_value(response)?["validators"]
+                .as_array()
+                .map(|validators| {
+                    validators
+                        .iter()
+                        .filter_map(|address| address.as_str())
+                        .map(|address| address.to_lowercase())
+                        .collect()
+                })
+                .unwrap_or_default();
+            match previous {
+                None => initial = json!(validators),
+                Some(ref previous) if *previous != validators => {
+                    changes.push(json!({
+                        "height": height,
+                        "added": validators.difference(previous).collect::<Vec<_>>(),
+                        "removed": previous.difference(&validators).collect::<Vec<_>>(),
+                        "validators": validators,
+                        "transactions": node_transactions(client, height, encryption)?,
+                    }));
+                }
+                Some(_) => {}
+            }
+            previous = Some(validators);
+        }
+        start = end + 1;
+        checkpointer
+            .save(
+                start,
+                json!({
+                    "from": from,
+                    "initial": initial,
+                    "changes": changes,
+                    "current": previous,
+                }),
+            )
+            .map_err(ToolError::Customize)?;
+    }
+
+    Ok(json!({
+        "from": from,
+        "to": to,
+        "initial": initial,
+        "changes": changes,
+        "current": previous,
+    }))
+}
+
+/// The NodeManager events emitted in the block, with the senders of their transactions
+fn node_transactions(
+    client: &Client,
+    height: u64,
+    encryption: Encryption,
+) -> Result<Vec<Value>, ToolError> {
+    let height = format!("{:#x}", height);
+    let logs = result_value(client.get_logs(
+        None,
+        Some(vec![NODE_MANAGER]),
+        Some(&height),
+        Some(&height),
+    )?)?;
+
+    let mut transactions = Vec::new();
+    for log in logs.as_array().cloned().unwrap_or_default() {
+        let topic = log["topics"][0].as_str().unwrap_or_default().to_lowercase();
+        let event = match NODE_EVENTS.iter().find(|event| {
+            event
+                .as_bytes()
+                .crypt_hash(Encryption::Secp256k1)
+                .completed_lower_hex_with_0x()
+                == topic
+        }) {
+            Some(event) => event.split('(').next().unwrap(),
+            None => continue,
+        };
+        let node = log["topics"][1]
+            .as_str()
+            .and_then(|topic| remove_0x(topic).get(24..))
+            .map(|node| format!("0x{}", node));
+        let hash = log["transactionHash"].as_str().unwrap_or_default();
+        let transaction = result_value(client.get_transaction(hash)?)?;
+        let sender =
+            UnverifiedTransaction::from_str(transaction["content"].as_str().unwrap_or_default())?
+                .public_key(encryption)
+                .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+                .ok();
+        transactions.push(json!({
+            "hash": hash,
+            "sender": sender,
+            "event": event,
+            "node": node,
+        }));
+    }
+    Ok(transactions)
+}
@@ -0,0 +1,256 @@
+use std::fs;
+use std::rc::Rc;
+use std::thread;
+use std::time::Duration;
+
+use clap::{App, Arg, ArgMatches, SubCommand};
+use serde_json::{self, json, Value};
+
+use cita_tool::client::basic::Client;
+use cita_tool::client::watch::{BlockEvent, BlockWatcher, LogWatcher};
+use cita_tool::decode_log_by_topic;
+
+use crate::checkpoint::{checkpoint_args, Checkpointer};
+use crate::cli::{get_url, h256_validator, parse_address, parse_u64};
+use crate::interactive::GlobalConfig;
+use crate::printer::Printer;
+use crate::sink::{open_sink, sink_args, RecordKind};
+
+/// Watch the chain subcommand
+pub fn watch_command() -> App<'static, 'static> {
+    App::new("watch")
+        .about("Watch the chain, handle new events as they arrive")
+        .subcommand(
+            SubCommand::with_name("events")
+                .about("Watch the logs of new blocks, decode and deliver them to a webhook")
+                .arg(
+                    Arg::with_name("address")
+                        .long("address")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(|address| parse_address(address.as_str()))
+                        .help("Only watch the logs emitted by these contract addresses"),
+                )
+                .arg(
+                    Arg::with_name("topic")
+                        .long("topic")
+                        .takes_value(true)
+                        .multiple(true)
+                        .validator(|topic| h256_validator(topic.as_str()))
+                        .help("Only watch the logs matching these topics"),
+                )
+                .arg(
+                    Arg::with_name("abi")
+                        .long("abi")
+                        .takes_value(true)
+                        .help("The path of the ABI json file used to decode the logs"),
+                )
+                .arg(
+                    Arg::with_name("webhook")
+                        .long("webhook")
+                        .takes_value(true)
+                        .conflicts_with("output")
+                        .help("POST each log as json to this url, the same as `--output <url>`"),
+                )
+                .args(&sink_args())
+                .args(&checkpoint_args())
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("Start watching from this height, default is the current height"),
+                )
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .default_value("3")
+                        .validator(|interval| parse_u64(interval.as_str()).map(|_| ()))
+                        .help("Polling interval in seconds"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("blocks")
+                .about("Watch new blocks, report chain reorganization")
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .validator(|height| parse_u64(height.as_str()).map(|_| ()))
+                        .help("Start watching from this height, default is the current height"),
+                )
+                .arg(
+                    Arg::with_name("window")
+                        .long("window")
+                        .takes_value(true)
+                        .default_value("64")
+                        .validator(|size| parse_u64(size.as_str()).map(|_| ()))
+                        .help("How many recent block hashes are kept to detect reorg"),
+                )
+                .args(&sink_args())
+                .args(&checkpoint_args())
+                .arg(
+                    Arg::with_name("interval")
+                        .long("interval")
+                        .takes_value(true)
+                        .default_value("3")
+                        .validator(|interval| parse_u64(interval.as_str()).map(|_| ()))
+                        .help("Polling interval in seconds"),
+                ),
+        )
+}
+
+/// Watch the chain processor
+pub fn watch_processor(
+    sub_matches: &ArgMatches,
+    printer: &Printer,
+    config: &GlobalConfig,
+    client: Client,
+) -> Result<(), String> {
+    let debug = sub_matches.is_present("debug") || config.debug();
+    let client = client
+        .set_debug(debug)
+        .set_uri(get_url(sub_matches, config));
+
+    match sub_matches.subcommand() {
+        ("events", Some(m)) => {
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            let abi = match m.value_of("abi") {
+                Some(path) => Some(fs::read_to_string(path).map_err(|err| format!("{}", err))?),
+                None => None,
+            };
+            let interval = parse_u64(m.value_of("interval").unwrap())?;
+            let mut sink = open_sink(m, printer, is_color, &client)?;
+            let addresses: Option<Vec<String>> = m
+                .values_of("address")
+                .map(|values| values.map(ToOwned::to_owned).collect());
+            let topics: Option<Vec<String>> = m
+                .values_of("topic")
+                .map(|values| values.map(ToOwned::to_owned).collect());
+            let checkpointer = Checkpointer::open(
+                m,
+                &format!(
+                    "watch events {}",
+                    json!({ "address": addresses, "topic": topics })
+                ),
+            )?;
+            let mut watcher = LogWatcher::new(client.clone())
+                .set_addresses(addresses)
+                .set_topics(topics)
+                .set_from(
+                    checkpointer
+                        .next_height()
+                        .or_else(|| m.value_of("from").map(|s| parse_u64(s).unwrap())),
+                );
+
+            loop {
+                let logs = watcher.poll().map_err(|err| format!("{}", err))?;
+                let logs = logs
+                    .into_iter()
+                    .map(|log| decode_log(log, abi.as_deref()))
+                    .collect::<Vec<_>>();
+                // Stopped before the checkpoint passes the logs not delivered
+                sink.write_all(RecordKind::Log, &logs)?;
+                if let Some(next_height) = watcher.next_height() {
+                    checkpointer.save(next_height, Value::Null)?;
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        ("blocks", Some(m)) => {
+            let is_color = !sub_matches.is_present("no-color") && config.color();
+            let interval = parse_u64(m.value_of("interval").unwrap())?;
+            let mut sink = open_sink(m, printer, is_color, &client)?;
+            let checkpointer = Checkpointer::open(m, "watch blocks")?;
+            let mut watcher = BlockWatcher::new(client)
+                .set_window_size(parse_u64(m.value_of("window").unwrap())? as usize)
+                .set_from(
+                    checkpointer
+                        .next_height()
+                        .or_else(|| m.value_of("from").map(|s| parse_u64(s).unwrap())),
+                );
+
+            loop {
+                let events = watcher.poll().map_err(|err| format!("{}", err))?;
+                for event in events {
+                    let delivered = match event {
+                        BlockEvent::NewBlock {
+                            height,
+                            hash,
+                            block,
+                        } => sink.write(
+                            RecordKind::Block,
+                            &json!({
+                                "height": height,
+                                "hash": hash,
+                                "prevHash": block["header"]["prevHash"],
+                                "timestamp": block["header"]["timestamp"],
+                            }),
+                        ),
+                        BlockEvent::Reorg {
+                            height,
+                            depth,
+                            below_window,
+                        } => {
+                            printer.eprintln(
+                                &Rc::new(format!(
+                                    "Chain reorg detected: {} block(s) replaced since height {}{}",
+                                    depth,
+                                    height,
+                                    if below_window {
+                                        ", it may go deeper than the --window"
+                                    } else {
+                                        ""
+                                    }
+                                )),
+                                true,
+                            );
+                            sink.write(
+                                RecordKind::Other,
+                                &json!({ "reorg": {
+                                    "height": height,
+                                    "depth": depth,
+                                    "belowWindow": below_window,
+                                } }),
+                            )
+                        }
+                    };
+                    delivered.and_then(|_| sink.flush())?;
+                }
+                if let Some(next_height) = watcher.next_height() {
+                    checkpointer.save(next_height, Value::Null)?;
+                }
+                thread::sleep(Duration::from_secs(interval));
+            }
+        }
+        _ => Err(sub_matches.usage().to_owned()),
+    }
+}
+
+/// Attach the decoded event to the log when the ABI knows it
+pub(crate) fn decode_log(mut log: Value, abi: Option<&str>) -> Value {
+    let abi = match abi {
+        Some(abi) => abi,
+        None => return log,
+    };
+    let topics = log["topics"]
+        .as_array()
+        .map(|topics| {
+            topics
+                .iter()
+                .filter_map(|topic| topic.as_str().map(ToOwned::to_owned))
+                .collect::<Vec<String>>()
+        })
+        .unwrap_or_default();
+    let data = log["data"].as_str().unwrap_or("0x").to_owned();
+
+    if let Ok((event, params)) = decode_log_by_topic(None, Some(abi), &topics, &data) {
+        let params = params
+            .iter()
+            .filter_map(|param| serde_json::from_str::<Value>(param).ok())
+            .collect::<Vec<Value>>();
+        log["decoded"] = json!({ "event": event, "params": params });
+    }
+    log
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde_json::Value;
+
+use crate::interactive::cita_cli_dir;
+
+/// The contracts deployed by `contract deploy --name`, by chain, name and the hash of the
+/// creation code, stored in `~/.cita-cli/deployments.json` unless another file is given
+///
+/// A CI pipeline keeps the file with the sources, so a deployment is only repeated
+/// when the code changes.
+pub struct Deployments {
+    path: PathBuf,
+    /// chain id -> `<name>@<code hash>` -> deployment
+    entries: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+impl Deployments {
+    /// Load the registry, empty if the file doesn't exist
+    pub fn load(path: Option<&str>) -> Result<Self, String> {
+        let path = path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| cita_cli_dir().join("deployments.json"));
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| format!("Invalid registry {}: {}", path.display(), err))?,
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(Deployments { path, entries })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}", err))?;
+        }
+        let content = serde_json::to_string_pretty(&self.entries).unwrap();
+        fs::write(&self.path, content).map_err(|err| format!("{}", err))
+    }
+
+    /// The deployment of the code under the name on the chain
+    pub fn get(&self, chain: &str, name: &str, code_hash: &str) -> Option<&Value> {
+        self.entries
+            .get(chain)
+            .and_then(|deployments| deployments.get(&key(name, code_hash)))
+    }
+
+    /// Record a deployment, replacing the one of the same code
+    pub fn record(&mut self, chain: &str, name: &str, code_hash: &str, deployment: Value) {
+        self.entries
+            .entry(chain.to_owned())
+            .or_default()
+            .insert(key(name, code_hash), deployment);
+    }
+}
+
+fn key(name: &str, code_hash: &str) -> String {
+    format!("{}@{}", name, code_hash)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Deployments;
+    use serde_json::json;
+    use std::fs;
+
+    #[test]
+    fn test_deployments() {
+        let path = std::env::temp_dir()
+            .join(format!("cita-cli-deployments-{}.json", std::process::id()))
+            .to_string_lossy()
+            .into_owned();
+        let mut deployments = Deployments::load(Some(&path)).unwrap();
+        assert!(deployments.get("0x1", "Token", "0xaa").is_none());
+        deployments.record("0x1", "Token", "0xaa", json!({ "address": "0x01" }));
+        deployments.save().unwrap();
+
+        let deployments = Deployments::load(Some(&path)).unwrap();
+        assert_eq!(
+            deployments.get("0x1", "Token", "0xaa").unwrap()["address"],
+            "0x01"
+        );
+        // Another chain or another code is another deployment
+        assert!(deployments.get("0x2", "Token", "0xaa").is_none());
+        assert!(deployments.get("0x1", "Token", "0xbb").is_none());
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use cita_tool::Signer;
+
+/// The prefix of the keys kept by the HSM, `--private-key hsm:<label>`
+pub const HSM_KEY_PREFIX: &str = "hsm:";
+
+/// The pin of the token, instead of the profile or the prompt
+#[cfg(feature = "hsm")]
+const ENV_HSM_PIN: &str = "CITA_HSM_PIN";
+
+/// The `hsm` object of `~/.cita-cli/config`:
+/// `{ "module": "/usr/lib/softhsm/libsofthsm2.so", "slot": 0, "pin": "1234" }`,
+/// the pin is asked for when neither the profile nor `CITA_HSM_PIN` has it
+#[cfg(feature = "hsm")]
+pub struct HsmProfile {
+    pub module: String,
+    pub slot: u64,
+    pin: Option<String>,
+}
+
+#[cfg(feature = "hsm")]
+impl HsmProfile {
+    pub fn load() -> Result<Self, String> {
+        let hsm = crate::profile::load_config()["hsm"].clone();
+        let module = hsm["module"].as_str().ok_or_else(|| {
+            format!(
+                "No PKCS#11 module, set `hsm.module` in {}",
+                crate::profile::config_path().display()
+            )
+        })?;
+        Ok(HsmProfile {
+            module: module.to_owned(),
+            slot: hsm["slot"].as_u64().unwrap_or_default(),
+            pin: hsm["pin"].as_str().map(ToOwned::to_owned),
+        })
+    }
+
+    pub fn pin(&self) -> Result<String, String> {
+        match std::env::var(ENV_HSM_PIN).ok().or_else(|| self.pin.clone()) {
+            Some(pin) => Ok(pin),
+            None => crate::cli::read_password("HSM pin"),
+        }
+    }
+}
+
+/// The key of the device given to the args by its prefix, one at most
+pub fn device_key<'a>(args: &'a [String], prefix: &str) -> Result<Option<&'a str>, String> {
+    let mut labels = args
+        .iter()
+        .filter_map(|arg| {
+            let value = arg.split_once('=').map_or(arg.as_str(), |(_, value)| value);
+            value.strip_prefix(prefix)
+        })
+        .collect::<Vec<&str>>();
+    labels.dedup();
+    match labels.as_slice() {
+        [] => Ok(None),
+        [label] => Ok(Some(label)),
+        _ => Err(format!(
+            "One key of {}* at most for a command, got {}",
+            prefix,
+            labels.join(", ")
+        )),
+    }
+}
+
+/// The signer of the HSM key given to the args, None when no key is of the HSM
+pub fn signer(args: &[String]) -> Result<Option<Arc<dyn Signer>>, String> {
+    match device_key(args, HSM_KEY_PREFIX)? {
+        Some(label) => open_signer(label).map(Some),
+        None => Ok(None),
+    }
+}
+
+#[cfg(feature = "hsm")]
+fn open_signer(label: &str) -> Result<Arc<dyn Signer>, String> {
+    use cita_tool::{Pkcs11Module, Pkcs11Signer};
+
+    let profile = HsmProfile::load()?;
+    let module = Pkcs11Module::open(&profile.module).map_err(|err| err.to_string())?;
+    let signer = Pkcs11Signer::open(Arc::new(module), profile.slot, &profile.pin()?, label)
+        .map_err(|err| err.to_string())?;
+    Ok(Arc::new(signer))
+}
+
+#[cfg(not(feature = "hsm"))]
+fn open_signer(_label: &str) -> Result<Arc<dyn Signer>, String> {
+    Err(not_built())
+}
+
+/// The slots of the module of the profile and the public keys of their tokens,
+/// of the slot only if any
+#[cfg(feature = "hsm")]
+pub fn list(slot: Option<u64>, login: bool) -> Result<Value, String> {
+    use cita_tool::Pkcs11Module;
+    use serde_json::json;
+
+    let profile = HsmProfile::load()?;
+    let module = Pkcs11Module::open(&profile.module).map_err(|err| err.to_string())?;
+    let pin = if login { Some(profile.pin()?) } else { None };
+    let slots = module
+        .slots()
+        .map_err(|err| err.to_string())?
+        .into_iter()
+        .filter(|token| slot.is_none() || slot == Some(token.id))
+        .map(|token| {
+            let keys = module
+                .keys(token.id, pin.as_deref())
+                .map_err(|err| err.to_string())?;
+            let mut value = json!(token);
+            value["keys"] = json!(keys
+                .into_iter()
+                .map(|key| {
+                    let mut value = json!(key);
+                    value["use"] = json!(format!("--private-key {}{}", HSM_KEY_PREFIX, key.label));
+                    value
+                })
+                .collect::<Vec<Value>>());
+            Ok(value)
+        })
+        .collect::<Result<Vec<Value>, String>>()?;
+    Ok(json!({ "module": profile.module, "slots": slots }))
+}
+
+#[cfg(not(feature = "hsm"))]
+pub fn list(_slot: Option<u64>, _login: bool) -> Result<Value, String> {
+    Err(not_built())
+}
+
+#[cfg(not(feature = "hsm"))]
+fn not_built() -> String {
+    "cita-cli is built without the hsm feature, rebuild it by `--features hsm`".to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{device_key, HSM_KEY_PREFIX};
+
+    #[test]
+    fn test_hsm_key_label() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let key_label =
+            |args: &[String]| device_key(args, HSM_KEY_PREFIX).map(|key| key.map(str::to_owned));
+        assert_eq!(key_label(&args(&["tx", "--private-key", "0x01"])), Ok(None));
+        assert_eq!(
+            key_label(&args(&["tx", "--private-key", "hsm:ops"])),
+            Ok(Some("ops".to_owned()))
+        );
+        assert_eq!(
+            key_label(&args(&[
+                "tx",
+                "--admin-private=hsm:ops",
+                "--private-key",
+                "hsm:ops"
+            ])),
+            Ok(Some("ops".to_owned()))
+        );
+        assert!(key_label(&args(&[
+            "--private-key",
+            "hsm:a",
+            "--admin-private",
+            "hsm:b"
+        ]))
+        .is_err());
+    }
+}
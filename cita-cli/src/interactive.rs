@@ -3,16 +3,13 @@ use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::io;
-use std::io::{Read, Write};
 use std::iter;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 use std::str::FromStr;
-use std::sync::Arc;
 
 use ansi_term::Colour::{Green, Red, Yellow, RGB};
-use clap;
-use dirs;
 
 use rustyline::completion::{extract_word, Completer, Pair};
 use rustyline::config::Configurer;
@@ -23,14 +20,27 @@ use rustyline::{Cmd, CompletionType, Config, Context, EditMode, Editor, Helper,
 
 use regex::{Captures, Regex};
 use serde_json::{self, json};
-use shell_words;
 
+use crate::address_book::AddressBook;
+use crate::audit;
 use crate::cli::{
-    abi_processor, amend_processor, benchmark_processor, build_interactive, contract_processor,
-    encryption, key_processor, key_validator, rpc_processor, search_processor, store_processor,
-    string_include, transfer_processor, tx_processor,
+    abi_processor, addr_processor, admin_processor, amend_processor, analyze_processor,
+    audit_processor, benchmark_processor, build_interactive, chain_processor,
+    conformance_processor, contract_processor, devnet_processor, diff_processor, encryption,
+    examples_processor, export_processor, faucet_processor, grpc_processor, key_processor,
+    key_validator, migrate_processor, monitor_processor, mq_processor, net_processor,
+    node_processor, plugins_processor, profile_processor, replay_processor, rpc_processor,
+    rules_processor, schema_processor, search_processor, store_processor, string_include,
+    time_processor, transfer_processor, tx_processor, txpool_processor, user_contract_processor,
+    validators_processor, watch_processor,
 };
-use crate::printer::{OutputFormat, Printable, Printer};
+use crate::hsm;
+use crate::key_agent;
+use crate::ledger;
+use crate::plugin;
+use crate::printer::{OutputFormat, Printable, Printer, TimeFormat};
+use crate::profile;
+use crate::recording::Recording;
 use cita_tool::client::basic::Client;
 use cita_tool::{Encryption, JsonRpcResponse};
 
@@ -51,40 +61,34 @@ static DEFAULT_BREAK_CHARS: [u8; 17] = [
 #[cfg(windows)]
 static ESCAPE_CHAR: Option<char> = None;
 
+/// The directory of config, history and other local data: `~/.cita-cli`
+pub fn cita_cli_dir() -> PathBuf {
+    let mut cita_cli_dir = dirs::home_dir().unwrap();
+    cita_cli_dir.push(".cita-cli");
+    cita_cli_dir
+}
+
+/// The `extra_params` object of `~/.cita-cli/config`, the fields added to every
+/// JSON-RPC request
+pub fn profile_extra_params() -> serde_json::Map<String, serde_json::Value> {
+    profile::load_config()["extra_params"]
+        .as_object()
+        .cloned()
+        .unwrap_or_default()
+}
+
 /// Interactive command line
 pub fn start(url: &str, client: &Client) -> io::Result<()> {
     let mut config = GlobalConfig::new(url.to_string());
 
-    let mut cita_cli_dir = dirs::home_dir().unwrap();
-    cita_cli_dir.push(".cita-cli");
+    let cita_cli_dir = cita_cli_dir();
     if !cita_cli_dir.as_path().exists() {
         fs::create_dir(&cita_cli_dir)?;
     }
     let mut history_file = cita_cli_dir.clone();
     history_file.push("history");
     let history_file = history_file.to_str().unwrap();
-    let mut config_file = cita_cli_dir.clone();
-    config_file.push("config");
-    if config_file.as_path().exists() {
-        let mut file = fs::File::open(&config_file)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-        let configs: serde_json::Value = serde_json::from_str(content.as_str()).unwrap();
-        if let Some(value) = configs["url"].as_str() {
-            config.set_url(value.to_string());
-        }
-        if let Some(value) = configs["encryption"].as_str() {
-            let encryption = Encryption::from_str(value).unwrap_or(Encryption::Secp256k1);
-            config.set_encryption(encryption)
-        }
-
-        config.set_debug(configs["debug"].as_bool().unwrap_or(false));
-        config.set_color(configs["color"].as_bool().unwrap_or(true));
-        config.set_json_format(configs["json_format"].as_bool().unwrap_or(true));
-        config.set_completion_style(configs["completion_style"].as_bool().unwrap_or(true));
-        config.set_edit_style(configs["edit_style"].as_bool().unwrap_or(true));
-        config.set_save_private(configs["save_private"].as_bool().unwrap_or(false));
-    }
+    config.load_profile();
 
     let mut env_file = cita_cli_dir;
     env_file.push("env_vars");
@@ -98,25 +102,19 @@ pub fn start(url: &str, client: &Client) -> io::Result<()> {
     }
 
     let mut printer = Printer::default();
+    printer.set_names(AddressBook::load().names());
     if !config.json_format() {
         printer.switch_format();
     }
 
     config.print();
 
-    start_rustyline(
-        &mut config,
-        &mut printer,
-        &config_file,
-        history_file,
-        &client,
-    )
+    start_rustyline(&mut config, &mut printer, history_file, client)
 }
 
 fn start_rustyline(
     config: &mut GlobalConfig,
     printer: &mut Printer,
-    config_file: &PathBuf,
     history_file: &str,
     client: &Client,
 ) -> io::Result<()> {
@@ -153,31 +151,37 @@ fn start_rustyline(
     }
 
     loop {
-        rl_mode(&mut rl, &config);
+        rl_mode(&mut rl, config);
         match rl.readline(&colored_prompt) {
             Ok(line) => {
-                match handle_commands(
-                    line.as_str(),
-                    config,
-                    printer,
-                    &parser,
-                    &env_regex,
-                    config_file,
-                    &client,
-                ) {
+                let history_line = if config.save_private() {
+                    line.clone()
+                } else {
+                    remove_private(line.as_ref())
+                };
+                // The commands of the shell itself aren't recorded
+                let recorded = !line.trim_start().starts_with('.');
+                if recorded {
+                    if let Some(recording) = printer.recording().as_mut() {
+                        let _ = recording.command(&history_line);
+                    }
+                }
+                match handle_commands(line.as_str(), config, printer, &parser, &env_regex, client) {
                     Ok(true) => {
                         break;
                     }
-                    Ok(false) => {}
+                    Ok(false) => {
+                        if recorded {
+                            if let Some(recording) = printer.recording().as_mut() {
+                                let _ = recording.succeeded(&history_line);
+                            }
+                        }
+                    }
                     Err(err) => {
                         printer.eprintln(&err.to_string(), true);
                     }
                 }
-                if config.save_private() {
-                    rl.add_history_entry(&line);
-                } else {
-                    rl.add_history_entry(remove_private(line.as_ref()));
-                }
+                rl.add_history_entry(history_line);
             }
             Err(ReadlineError::Interrupted) => {
                 println!("CTRL-C");
@@ -195,6 +199,11 @@ fn start_rustyline(
     if let Err(err) = rl.save_history(history_file) {
         eprintln!("Save command history failed: {}", err);
     }
+    if let Some(recording) = printer.set_recording(None) {
+        if let Err(err) = recording.stop() {
+            eprintln!("Save the recording failed: {}", err);
+        }
+    }
     Ok(())
 }
 
@@ -204,21 +213,45 @@ fn handle_commands(
     printer: &mut Printer,
     parser: &clap::App<'static, 'static>,
     env_regex: &Regex,
-    config_file: &PathBuf,
     client: &Client,
 ) -> Result<bool, String> {
-    let args = match shell_words::split(replace_cmd(&env_regex, line, &config).as_str()) {
-        Ok(args) => args,
+    let trimmed = line.trim();
+    if trimmed.starts_with('#') {
+        return Ok(false);
+    } else if trimmed.starts_with('.') {
+        return shell_command(trimmed, config, printer).map(|_| false);
+    }
+    let args = match shell_words::split(replace_cmd(env_regex, line, config).as_str()) {
+        Ok(args) => {
+            if let Some((name, args)) = plugin::external(parser, &args) {
+                return plugin::run(&name, &args, config).map(|_| false);
+            }
+            audit::set_command(&args);
+            let book = AddressBook::load();
+            let args = book.resolve_args(args);
+            ledger::resolve_args(key_agent::resolve_args(&book, args))?
+        }
         Err(e) => return Err(e.to_string()),
     };
+    let device_client;
+    let signer = hsm::signer(&args)
+        .transpose()
+        .or_else(|| ledger::signer(&args).transpose())
+        .transpose()?;
+    let client = match signer {
+        Some(signer) => {
+            device_client = client.clone().set_signer(Some(signer));
+            &device_client
+        }
+        None => client,
+    };
 
     match parser.clone().get_matches_from_safe(args) {
         Ok(matches) => match matches.subcommand() {
             ("switch", Some(m)) => {
-                m.value_of("url").and_then(|url| {
+                if let Some(url) = m.value_of("url") {
                     config.set_url(url.to_string());
-                    Some(())
-                });
+                }
                 if m.is_present("color") {
                     config.switch_color();
                 }
@@ -244,13 +277,23 @@ fn handle_commands(
                     config.switch_save_private();
                 }
 
-                let encryption = encryption(m, &config);
+                if let Some(time_format) = m.value_of("time").and_then(TimeFormat::from_name) {
+                    printer.set_time_format(time_format);
+                }
+
+                if m.is_present("signers") {
+                    let signers = match printer.signers() {
+                        Some(_) => None,
+                        None => Some(config.encryption()),
+                    };
+                    printer.set_signers(signers);
+                }
+
+                let encryption = encryption(m, config);
                 config.set_encryption(encryption);
 
                 config.print();
-                let mut file = fs::File::create(config_file.as_path())
-                    .map_err(|err| format!("open config error: {:?}", err))?;
-                let content = serde_json::to_string_pretty(&json!({
+                profile::save_config(json!({
                     "url": config.get_url().clone(),
                     "encryption": config.encryption().to_string(),
                     "color": config.color(),
@@ -260,9 +303,7 @@ fn handle_commands(
                     "edit_style": config.edit_style(),
                     "save_private": config.save_private(),
                 }))
-                .unwrap();
-                file.write_all(content.as_bytes())
-                    .map_err(|err| format!("save config error: {:?}", err))?;
+                .map_err(|err| format!("save config error: {:?}", err))?;
                 Ok(())
             }
             ("set", Some(m)) => {
@@ -276,23 +317,54 @@ fn handle_commands(
                 printer.println(&config.get(key).clone(), config.color());
                 Ok(())
             }
-            ("rpc", Some(m)) => rpc_processor(m, &printer, config, client.clone()),
-            ("ethabi", Some(m)) => abi_processor(m, &printer, &config),
-            ("key", Some(m)) => key_processor(m, &printer, &config),
-            ("scm", Some(m)) => contract_processor(m, &printer, config, client.clone()),
-            ("transfer", Some(m)) => transfer_processor(m, &printer, config, client.clone()),
-            ("store", Some(m)) => store_processor(m, &printer, config, client.clone()),
-            ("amend", Some(m)) => amend_processor(m, &printer, config, client.clone()),
+            ("rpc", Some(m)) => rpc_processor(m, printer, config, client.clone()),
+            ("ethabi", Some(m)) => abi_processor(m, printer, config),
+            ("key", Some(m)) => key_processor(m, printer, config, client.clone()),
+            ("addr", Some(m)) => {
+                let result = addr_processor(m, printer, config);
+                printer.set_names(AddressBook::load().names());
+                result
+            }
+            ("scm", Some(m)) => contract_processor(m, printer, config, client.clone()),
+            ("contract", Some(m)) => user_contract_processor(m, printer, config, client.clone()),
+            ("transfer", Some(m)) => transfer_processor(m, printer, config, client.clone()),
+            ("store", Some(m)) => store_processor(m, printer, config, client.clone()),
+            ("amend", Some(m)) => amend_processor(m, printer, config, client.clone()),
             ("info", _) => {
                 config.print();
                 Ok(())
             }
             ("search", Some(m)) => {
-                search_processor(&parser, m);
+                search_processor(parser, m);
                 Ok(())
             }
-            ("tx", Some(m)) => tx_processor(m, &printer, config, client.clone()),
-            ("benchmark", Some(m)) => benchmark_processor(m, &printer, &config, client.clone()),
+            ("tx", Some(m)) => tx_processor(m, printer, config, client.clone()),
+            ("benchmark", Some(m)) => benchmark_processor(m, printer, config, client.clone()),
+            ("watch", Some(m)) => watch_processor(m, printer, config, client.clone()),
+            ("chain", Some(m)) => chain_processor(m, printer, config, client.clone()),
+            ("analyze", Some(m)) => analyze_processor(m, printer, config, client.clone()),
+            ("validators", Some(m)) => validators_processor(m, printer, config, client.clone()),
+            ("admin", Some(m)) => admin_processor(m, printer, config, client.clone()),
+            ("faucet", Some(m)) => faucet_processor(m, printer, config, client.clone()),
+            ("node", Some(m)) => node_processor(m, printer, config, client.clone()),
+            ("monitor", Some(m)) => monitor_processor(m, printer, config, client.clone()),
+            ("net", Some(m)) => net_processor(m, printer, config, client.clone()),
+            ("txpool", Some(m)) => txpool_processor(m, printer, config, client.clone()),
+            ("migrate", Some(m)) => migrate_processor(m, printer, config, client.clone()),
+            ("rules", Some(m)) => rules_processor(m, printer, config, client.clone()),
+            ("schema", Some(m)) => schema_processor(m, printer, config, client.clone()),
+            ("conformance", Some(m)) => conformance_processor(m, printer, config, client.clone()),
+            ("replay", Some(m)) => replay_processor(m, printer, config, client.clone()),
+            ("diff", Some(m)) => diff_processor(m, printer, config, client.clone()),
+            ("time", Some(m)) => time_processor(m, printer, config, client.clone()),
+            ("devnet", Some(m)) => devnet_processor(m, printer, config, client.clone()),
+            ("export", Some(m)) => export_processor(m, printer, config, client.clone()),
+            ("grpc", Some(m)) => grpc_processor(m, printer, config),
+            ("mq", Some(m)) => mq_processor(m, printer, config),
+            ("profile", Some(m)) => profile_processor(m, printer, config),
+            ("audit", Some(m)) => audit_processor(m, printer, config),
+            ("examples", Some(m)) => examples_processor(m, printer, config),
+            ("plugins", Some(m)) => plugins_processor(m, printer, config),
             ("exit", _) => {
                 return Ok(true);
             }
@@ -303,21 +375,49 @@ fn handle_commands(
     .map(|_| false)
 }
 
+/// `.record <file>` and `.stop`, the commands of the shell itself
+fn shell_command(line: &str, config: &GlobalConfig, printer: &mut Printer) -> Result<(), String> {
+    let args = shell_words::split(line).map_err(|err| err.to_string())?;
+    let args = args.iter().map(String::as_str).collect::<Vec<&str>>();
+    let output = match args.as_slice() {
+        [".record", path] => {
+            if printer.recording().is_some() {
+                return Err("Already recording, `.stop` it first".to_string());
+            }
+            let recording = Recording::start(Path::new(path))?;
+            printer.set_recording(Some(recording));
+            json!({ "recording": path })
+        }
+        [".stop"] => match printer.set_recording(None) {
+            Some(recording) => recording.stop()?,
+            None => return Err("Not recording".to_string()),
+        },
+        _ => {
+            return Err(format!(
+                "Unknown command {}, the commands of the shell are `.record <file>` and `.stop`",
+                line
+            ))
+        }
+    };
+    printer.println(&output, config.color());
+    Ok(())
+}
+
 struct CitaCompleter<'a, 'b>
 where
     'a: 'b,
 {
-    clap_app: Arc<clap::App<'a, 'b>>,
+    clap_app: Rc<clap::App<'a, 'b>>,
 }
 
 impl<'a, 'b> CitaCompleter<'a, 'b> {
     fn new(clap_app: clap::App<'a, 'b>) -> Self {
         CitaCompleter {
-            clap_app: Arc::new(clap_app),
+            clap_app: Rc::new(clap_app),
         }
     }
 
-    fn get_completions(app: &Arc<clap::App<'a, 'b>>, args: &[String]) -> Vec<(String, String)> {
+    fn get_completions(app: &Rc<clap::App<'a, 'b>>, args: &[String]) -> Vec<(String, String)> {
         let args_set = args.iter().collect::<HashSet<&String>>();
         let switched_completions =
             |short: Option<char>, long: Option<&str>, multiple: bool, required: bool| {
@@ -326,7 +426,7 @@ impl<'a, 'b> CitaCompleter<'a, 'b> {
                     long.map(|s| format!("--{}", s)),
                 ]
                 .into_iter()
-                .filter_map(|s| s)
+                .flatten()
                 .map(|s| {
                     let display = if required {
                         format!("{}(*)", s)
@@ -358,7 +458,7 @@ impl<'a, 'b> CitaCompleter<'a, 'b> {
                                 .map(|(alias, _)| ((*alias).to_string(), (*alias).to_string()))
                                 .collect::<Vec<(String, String)>>()
                         })
-                        .unwrap_or_else(|| vec![]),
+                        .unwrap_or_else(std::vec::Vec::new),
                 ]
                 .concat()
             })
@@ -383,9 +483,9 @@ impl<'a, 'b> CitaCompleter<'a, 'b> {
     }
 
     fn find_subcommand<'s, Iter: iter::Iterator<Item = &'s str>>(
-        app: Arc<clap::App<'a, 'b>>,
+        app: Rc<clap::App<'a, 'b>>,
         mut prefix_names: iter::Peekable<Iter>,
-    ) -> Option<Arc<clap::App<'a, 'b>>> {
+    ) -> Option<Rc<clap::App<'a, 'b>>> {
         if let Some(name) = prefix_names.next() {
             for inner_app in &(app.p.subcommands) {
                 if inner_app.p.meta.name == name
@@ -398,9 +498,9 @@ impl<'a, 'b> CitaCompleter<'a, 'b> {
                         .unwrap_or(false)
                 {
                     return if prefix_names.peek().is_none() {
-                        Some(Arc::new(inner_app.to_owned()))
+                        Some(Rc::new(inner_app.to_owned()))
                     } else {
-                        Self::find_subcommand(Arc::new(inner_app.to_owned()), prefix_names)
+                        Self::find_subcommand(Rc::new(inner_app.to_owned()), prefix_names)
                     };
                 }
             }
@@ -564,12 +664,33 @@ impl GlobalConfig {
         }
     }
 
-    fn set(&mut self, key: String, value: serde_json::Value) -> &mut Self {
+    /// Apply the settings of the profile, `~/.cita-cli/config`
+    pub fn load_profile(&mut self) {
+        let configs = profile::load_config();
+        if configs.is_object() {
+            if let Some(value) = configs["url"].as_str() {
+                self.set_url(value.to_string());
+            }
+            if let Some(value) = configs["encryption"].as_str() {
+                let encryption = Encryption::from_str(value).unwrap_or(Encryption::Secp256k1);
+                self.set_encryption(encryption)
+            }
+
+            self.set_debug(configs["debug"].as_bool().unwrap_or(false));
+            self.set_color(configs["color"].as_bool().unwrap_or(true));
+            self.set_json_format(configs["json_format"].as_bool().unwrap_or(true));
+            self.set_completion_style(configs["completion_style"].as_bool().unwrap_or(true));
+            self.set_edit_style(configs["edit_style"].as_bool().unwrap_or(true));
+            self.set_save_private(configs["save_private"].as_bool().unwrap_or(false));
+        }
+    }
+
+    pub fn set(&mut self, key: String, value: serde_json::Value) -> &mut Self {
         self.env_variable.insert(key, value);
         self
     }
 
-    fn get(&self, key: Option<&str>) -> KV {
+    fn get(&self, key: Option<&str>) -> KV<'_> {
         match key {
             Some(key) => {
                 let mut parts_iter = key.split('.');
@@ -27,7 +27,7 @@ macro_rules! colorize {
 }
 
 /// The set of available colors for the various JSON components.
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub enum Color {
     #[allow(dead_code)]
     Black,
@@ -43,15 +43,10 @@ pub enum Color {
     Yellow,
 
     /// Default color
+    #[default]
     Plain,
 }
 
-impl Default for Color {
-    fn default() -> Self {
-        Color::Plain
-    }
-}
-
 #[derive(Default)]
 pub struct ColorizerBuilder {
     null: Color,
@@ -169,9 +164,9 @@ impl Colorizer {
         Ok(string)
     }
 
-    fn to_vec<T: ?Sized>(&self, value: &T) -> Result<Vec<u8>>
+    fn to_vec<T>(&self, value: &T) -> Result<Vec<u8>>
     where
-        T: Serialize,
+        T: ?Sized + Serialize,
     {
         let mut writer = Vec::with_capacity(128);
 
@@ -179,10 +174,10 @@ impl Colorizer {
         Ok(writer)
     }
 
-    fn to_writer<W: ?Sized, T: ?Sized>(&self, writer: &mut W, value: &T) -> Result<()>
+    fn to_writer<W, T>(&self, writer: &mut W, value: &T) -> Result<()>
     where
-        W: Write,
-        T: Serialize,
+        W: ?Sized + Write,
+        T: ?Sized + Serialize,
     {
         let mut ser = Serializer::with_formatter(writer, self.clone());
         value.serialize(&mut ser)?;
@@ -205,129 +200,125 @@ impl Colorizer {
 }
 
 impl Formatter for Colorizer {
-    fn write_null<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn write_null<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         write!(writer, "{}", colorize!("null", &self.null))
     }
 
-    fn write_bool<W: ?Sized>(&mut self, writer: &mut W, value: bool) -> Result<()>
+    fn write_bool<W>(&mut self, writer: &mut W, value: bool) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.boolean))
     }
 
-    fn write_i8<W: ?Sized>(&mut self, writer: &mut W, value: i8) -> Result<()>
+    fn write_i8<W>(&mut self, writer: &mut W, value: i8) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_i16<W: ?Sized>(&mut self, writer: &mut W, value: i16) -> Result<()>
+    fn write_i16<W>(&mut self, writer: &mut W, value: i16) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_i32<W: ?Sized>(&mut self, writer: &mut W, value: i32) -> Result<()>
+    fn write_i32<W>(&mut self, writer: &mut W, value: i32) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_i64<W: ?Sized>(&mut self, writer: &mut W, value: i64) -> Result<()>
+    fn write_i64<W>(&mut self, writer: &mut W, value: i64) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_u8<W: ?Sized>(&mut self, writer: &mut W, value: u8) -> Result<()>
+    fn write_u8<W>(&mut self, writer: &mut W, value: u8) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_u16<W: ?Sized>(&mut self, writer: &mut W, value: u16) -> Result<()>
+    fn write_u16<W>(&mut self, writer: &mut W, value: u16) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_u32<W: ?Sized>(&mut self, writer: &mut W, value: u32) -> Result<()>
+    fn write_u32<W>(&mut self, writer: &mut W, value: u32) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_u64<W: ?Sized>(&mut self, writer: &mut W, value: u64) -> Result<()>
+    fn write_u64<W>(&mut self, writer: &mut W, value: u64) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_f32<W: ?Sized>(&mut self, writer: &mut W, value: f32) -> Result<()>
+    fn write_f32<W>(&mut self, writer: &mut W, value: f32) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn write_f64<W: ?Sized>(&mut self, writer: &mut W, value: f64) -> Result<()>
+    fn write_f64<W>(&mut self, writer: &mut W, value: f64) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let value_as_string = format!("{}", value);
         write!(writer, "{}", colorize!(&value_as_string, &self.number))
     }
 
-    fn begin_string<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn begin_string<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         write!(writer, "{}", colorize!("\"", self.get_string_color()))
     }
 
-    fn end_string<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn end_string<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         write!(writer, "{}", colorize!("\"", self.get_string_color()))
     }
 
-    fn write_string_fragment<W: ?Sized>(&mut self, writer: &mut W, fragment: &str) -> Result<()>
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         write!(writer, "{}", colorize!(fragment, self.get_string_color()))
     }
 
-    fn write_char_escape<W: ?Sized>(
-        &mut self,
-        writer: &mut W,
-        char_escape: CharEscape,
-    ) -> Result<()>
+    fn write_char_escape<W>(&mut self, writer: &mut W, char_escape: CharEscape) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         let s = match char_escape {
             CharEscape::Quote => "\\\"",
@@ -354,25 +345,25 @@ impl Formatter for Colorizer {
         write!(writer, "{}", colorize!(s, &self.escape_sequence))
     }
 
-    fn begin_array<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn begin_array<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         self.indent_level += 1;
         write!(writer, "[")
     }
 
-    fn end_array<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn end_array<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         self.indent_level -= 1;
         write!(writer, "\n{}]", self.get_indentation())
     }
 
-    fn begin_array_value<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> Result<()>
+    fn begin_array_value<W>(&mut self, writer: &mut W, first: bool) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         if !first {
             write!(writer, ",")?;
@@ -381,9 +372,9 @@ impl Formatter for Colorizer {
         write!(writer, "\n{}", self.get_indentation())
     }
 
-    fn begin_object_key<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> Result<()>
+    fn begin_object_key<W>(&mut self, writer: &mut W, first: bool) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         if !first {
             write!(writer, ",")?;
@@ -394,32 +385,32 @@ impl Formatter for Colorizer {
         write!(writer, "\n{}", self.get_indentation())
     }
 
-    fn end_object_key<W: ?Sized>(&mut self, _writer: &mut W) -> Result<()>
+    fn end_object_key<W>(&mut self, _writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         self.current_is_key = false;
         Ok(())
     }
 
-    fn begin_object_value<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn begin_object_value<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         write!(writer, ": ")
     }
 
-    fn begin_object<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn begin_object<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         self.indent_level += 1;
         write!(writer, "{{")
     }
 
-    fn end_object<W: ?Sized>(&mut self, writer: &mut W) -> Result<()>
+    fn end_object<W>(&mut self, writer: &mut W) -> Result<()>
     where
-        W: Write,
+        W: ?Sized + Write,
     {
         self.indent_level -= 1;
         write!(writer, "\n{}}}", self.get_indentation())
@@ -0,0 +1,321 @@
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+#[cfg(unix)]
+use std::io::{BufRead, BufReader, Read};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+
+use cita_tool::remove_0x;
+
+use crate::address_book::AddressBook;
+use crate::interactive::cita_cli_dir;
+use crate::profile;
+
+/// The long names of the args that take a private key, an address or a name in the address
+/// book is replaced there by the key unlocked in the agent
+const KEY_ARGS: [&str; 4] = [
+    "private-key",
+    "admin-private",
+    "admin-private-key",
+    "default-key",
+];
+
+/// How often the agent checks for connections and the expiration
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long `key unlock` waits for a new agent to listen
+const START_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The directory of the socket: `~/.cita-cli/agent`, only its owner can get in
+fn socket_dir() -> PathBuf {
+    let mut path = cita_cli_dir();
+    path.push("agent");
+    path
+}
+
+/// The socket of the agent: `~/.cita-cli/agent/agent.sock`
+fn socket_path() -> PathBuf {
+    let mut path = socket_dir();
+    path.push("agent.sock");
+    path
+}
+
+/// Create the directory of the socket 0700, or make sure an existing one is a directory of
+/// this user closed to the others, before the socket is bound in it: the socket itself
+/// takes the umask and is only restricted after the bind
+#[cfg(unix)]
+fn private_socket_dir() -> Result<(), String> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+    let dir = socket_dir();
+    std::fs::create_dir_all(cita_cli_dir()).map_err(|err| err.to_string())?;
+    match std::fs::DirBuilder::new().mode(0o700).create(&dir) {
+        Ok(()) => {}
+        Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+        Err(err) => return Err(format!("Can't create {}: {}", dir.display(), err)),
+    }
+    // Only the owner can change the mode, so it fails on a directory of another user
+    std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))
+        .map_err(|err| format!("Can't restrict {}: {}", dir.display(), err))?;
+    let metadata = std::fs::symlink_metadata(&dir).map_err(|err| err.to_string())?;
+    if !metadata.is_dir() || metadata.permissions().mode() & 0o777 != 0o700 {
+        return Err(format!(
+            "{} must be a directory only its owner can get in",
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Send a request to the running agent and read its response
+#[cfg(unix)]
+pub fn request(request: &Value) -> io::Result<Value> {
+    let mut stream = UnixStream::connect(socket_path())?;
+    stream.set_read_timeout(Some(START_TIMEOUT))?;
+    writeln!(stream, "{}", request)?;
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    serde_json::from_str(&response).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+#[cfg(not(unix))]
+pub fn request(_request: &Value) -> io::Result<Value> {
+    Err(io::Error::new(
+        io::ErrorKind::Other,
+        "The key agent needs unix sockets",
+    ))
+}
+
+/// Cache the keys, `address -> private key`, in the running agent for the seconds,
+/// or start an agent for them, return the addresses now unlocked
+pub fn unlock(keys: BTreeMap<String, String>, ttl: u64) -> Result<Value, String> {
+    let add = json!({ "command": "add", "keys": keys, "ttl": ttl });
+    if let Ok(response) = request(&add) {
+        return Ok(response);
+    }
+
+    let exe = std::env::current_exe().map_err(|err| err.to_string())?;
+    let mut command = Command::new(exe);
+    command
+        .args(["key", "agent", "--ttl", &ttl.to_string()])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // It reads the keys from stdin, not the passphrase of an encrypted profile
+    if let Some(passphrase) = profile::cached_passphrase() {
+        command.env(profile::ENV_PASSPHRASE, passphrase);
+    }
+    // Out of the process group of the terminal, so it outlives the interrupted scripts
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut agent = command
+        .spawn()
+        .map_err(|err| format!("Can't start the key agent: {}", err))?;
+    agent
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(json!(keys).to_string().as_bytes())
+        .map_err(|err| format!("Can't pass the keys to the agent: {}", err))?;
+
+    let started = Instant::now();
+    loop {
+        match request(&json!({ "command": "status" })) {
+            Ok(status) => return Ok(status),
+            Err(_) if started.elapsed() < START_TIMEOUT => thread::sleep(POLL_INTERVAL),
+            Err(err) => return Err(format!("The key agent doesn't answer: {}", err)),
+        }
+    }
+}
+
+/// Cache the master passphrase of the profile in the running agent for the seconds,
+/// or start an agent for it
+pub fn unlock_passphrase(passphrase: &str, ttl: u64) -> Result<Value, String> {
+    unlock(BTreeMap::new(), ttl)?;
+    request(&json!({ "command": "add", "passphrase": passphrase, "ttl": ttl }))
+        .map_err(|err| format!("The key agent doesn't answer: {}", err))
+}
+
+/// The master passphrase of the profile cached in the agent, None without an agent
+pub fn passphrase() -> Option<String> {
+    request(&json!({ "command": "passphrase" }))
+        .ok()
+        .and_then(|response| response["passphrase"].as_str().map(ToOwned::to_owned))
+}
+
+/// Run the agent with the keys read from stdin, until the seconds pass or it's locked
+#[cfg(unix)]
+pub fn serve(ttl: u64) -> Result<(), String> {
+    let mut input = String::new();
+    io::stdin()
+        .read_to_string(&mut input)
+        .map_err(|err| err.to_string())?;
+    let mut keys: BTreeMap<String, String> =
+        serde_json::from_str(&input).map_err(|err| err.to_string())?;
+    let mut passphrase = None;
+    let mut deadline = Instant::now() + Duration::from_secs(ttl);
+
+    private_socket_dir()?;
+    let path = socket_path();
+    // A socket left by a crashed agent
+    if UnixStream::connect(&path).is_err() {
+        let _ = std::fs::remove_file(&path);
+    }
+    let listener = UnixListener::bind(&path).map_err(|err| err.to_string())?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|err| err.to_string())?;
+    }
+    listener
+        .set_nonblocking(true)
+        .map_err(|err| err.to_string())?;
+
+    while Instant::now() < deadline {
+        let stream = match listener.accept() {
+            Ok((stream, _)) => stream,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+            Err(err) => return Err(err.to_string()),
+        };
+        if let Ok(false) = answer(stream, &mut keys, &mut passphrase, &mut deadline) {
+            break;
+        }
+    }
+    keys.clear();
+    passphrase.take();
+    let _ = std::fs::remove_file(&path);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn serve(_ttl: u64) -> Result<(), String> {
+    Err("The key agent needs unix sockets".to_string())
+}
+
+/// Answer one request, false when the agent is locked
+#[cfg(unix)]
+fn answer(
+    stream: UnixStream,
+    keys: &mut BTreeMap<String, String>,
+    passphrase: &mut Option<String>,
+    deadline: &mut Instant,
+) -> io::Result<bool> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(START_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let request: Value = serde_json::from_str(&line).unwrap_or_default();
+
+    let mut running = true;
+    let response = match request["command"].as_str() {
+        Some("get") => {
+            let address = request["address"].as_str().unwrap_or_default();
+            match keys.get(&address.to_lowercase()) {
+                Some(key) => json!({ "key": key }),
+                None => json!({ "error": format!("{} is not unlocked", address) }),
+            }
+        }
+        Some("add") => {
+            if let Some(added) = request["keys"].as_object() {
+                for (address, key) in added {
+                    if let Some(key) = key.as_str() {
+                        keys.insert(address.to_lowercase(), key.to_owned());
+                    }
+                }
+            }
+            if let Some(added) = request["passphrase"].as_str() {
+                *passphrase = Some(added.to_owned());
+            }
+            let ttl = Duration::from_secs(request["ttl"].as_u64().unwrap_or_default());
+            *deadline = (*deadline).max(Instant::now() + ttl);
+            status(keys, passphrase, *deadline)
+        }
+        Some("passphrase") => match passphrase {
+            Some(passphrase) => json!({ "passphrase": passphrase }),
+            None => json!({ "error": "The profile is not unlocked" }),
+        },
+        Some("status") => status(keys, passphrase, *deadline),
+        Some("lock") => {
+            running = false;
+            json!({ "locked": keys.len() })
+        }
+        _ => json!({ "error": "Unknown request" }),
+    };
+    writeln!(&stream, "{}", response)?;
+    Ok(running)
+}
+
+#[cfg(unix)]
+fn status(
+    keys: &BTreeMap<String, String>,
+    passphrase: &Option<String>,
+    deadline: Instant,
+) -> Value {
+    json!({
+        "addresses": keys.keys().collect::<Vec<_>>(),
+        "profile": passphrase.is_some(),
+        "expiresIn": deadline.saturating_duration_since(Instant::now()).as_secs(),
+    })
+}
+
+/// Substitute the addresses and the address book names given to the key args with
+/// the keys unlocked in the agent
+pub fn resolve_args(book: &AddressBook, args: Vec<String>) -> Vec<String> {
+    let resolve = |value: &str| -> Option<String> {
+        let address = book.get(value).unwrap_or(value);
+        let is_address = address.len() == 42
+            && address.starts_with("0x")
+            && remove_0x(address).chars().all(|c| c.is_ascii_hexdigit());
+        if !is_address {
+            return None;
+        }
+        let response = request(&json!({ "command": "get", "address": address }));
+        match response {
+            Ok(ref response) if response["key"].is_string() => {
+                response["key"].as_str().map(ToOwned::to_owned)
+            }
+            Ok(response) => {
+                eprintln!("Key agent: {}", response["error"]);
+                None
+            }
+            Err(_) => {
+                eprintln!("No key agent, unlock the key of {} by `key unlock`", value);
+                None
+            }
+        }
+    };
+
+    let mut in_key_arg = false;
+    args.into_iter()
+        .map(|arg| {
+            if let Some(long) = arg.strip_prefix("--") {
+                let (flag, value) = match long.find('=') {
+                    Some(index) => (&long[..index], Some(&long[index + 1..])),
+                    None => (long, None),
+                };
+                let is_key_arg = KEY_ARGS.contains(&flag);
+                in_key_arg = is_key_arg && value.is_none();
+                match value.filter(|_| is_key_arg).and_then(resolve) {
+                    Some(key) => format!("--{}={}", flag, key),
+                    None => arg.clone(),
+                }
+            } else if in_key_arg {
+                in_key_arg = false;
+                resolve(&arg).unwrap_or(arg)
+            } else {
+                arg
+            }
+        })
+        .collect()
+}
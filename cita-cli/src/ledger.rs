@@ -0,0 +1,184 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use cita_tool::Signer;
+
+use crate::hsm::device_key;
+
+/// The prefix of the keys of the Ledger, `--private-key ledger:<derivation path>`
+pub const LEDGER_KEY_PREFIX: &str = "ledger:";
+
+const SIGNER_ARG: &str = "--signer";
+const PATH_ARG: &str = "--derivation-path";
+
+/// Substitute `--signer ledger` and its `--derivation-path` with the key of the Ledger,
+/// `--private-key ledger:<derivation path>`, which the commands sending transactions take
+pub fn resolve_args(args: Vec<String>) -> Result<Vec<String>, String> {
+    let value_of = |name: &str| -> Option<(usize, usize, String)> {
+        args.iter().enumerate().find_map(|(index, arg)| {
+            if arg == name {
+                args.get(index + 1)
+                    .map(|value| (index, index + 1, value.clone()))
+            } else {
+                arg.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix('='))
+                    .map(|value| (index, index, value.to_owned()))
+            }
+        })
+    };
+    let (signer_start, signer_end, signer) = match value_of(SIGNER_ARG) {
+        Some(signer) => signer,
+        None => return Ok(args),
+    };
+    if signer != "ledger" {
+        return Err(format!("Unknown signer {}, the signer is ledger", signer));
+    }
+    if args
+        .iter()
+        .any(|arg| arg == "--private-key" || arg.starts_with("--private-key="))
+    {
+        return Err("`--signer ledger` signs instead of `--private-key`, give one of them".into());
+    }
+    let path = value_of(PATH_ARG);
+    let derivation_path = derivation_path(path.as_ref().map(|(_, _, path)| path.as_str()))?;
+
+    let mut removed = vec![(signer_start, signer_end)];
+    removed.extend(path.map(|(start, end, _)| (start, end)));
+    let mut args = args
+        .into_iter()
+        .enumerate()
+        .filter(|(index, _)| {
+            !removed
+                .iter()
+                .any(|(start, end)| start <= index && index <= end)
+        })
+        .map(|(_, arg)| arg)
+        .collect::<Vec<String>>();
+    // The key of the last subcommand, the one sending the transaction
+    args.push(format!(
+        "--private-key={}{}",
+        LEDGER_KEY_PREFIX, derivation_path
+    ));
+    Ok(args)
+}
+
+/// The signer of the Ledger key given to the args, None when no key is of the Ledger
+pub fn signer(args: &[String]) -> Result<Option<Arc<dyn Signer>>, String> {
+    match device_key(args, LEDGER_KEY_PREFIX)? {
+        Some(path) => open_signer(path).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Check the derivation path of `ledger:<derivation path>`
+pub fn path_validator(path: &str) -> Result<(), String> {
+    derivation_path(Some(path)).map(|_| ())
+}
+
+/// The derivation path written canonically, the default one if None
+#[cfg(feature = "ledger")]
+fn derivation_path(path: Option<&str>) -> Result<String, String> {
+    use cita_tool::DerivationPath;
+
+    match path {
+        Some(path) => path.parse::<DerivationPath>().map(|path| path.to_string()),
+        None => Ok(DerivationPath::default().to_string()),
+    }
+}
+
+#[cfg(not(feature = "ledger"))]
+fn derivation_path(_path: Option<&str>) -> Result<String, String> {
+    Err(not_built())
+}
+
+#[cfg(feature = "ledger")]
+fn open_signer(path: &str) -> Result<Arc<dyn Signer>, String> {
+    use cita_tool::LedgerSigner;
+
+    let signer = LedgerSigner::open(path.parse()?).map_err(|err| err.to_string())?;
+    Ok(Arc::new(signer))
+}
+
+#[cfg(not(feature = "ledger"))]
+fn open_signer(_path: &str) -> Result<Arc<dyn Signer>, String> {
+    Err(not_built())
+}
+
+/// The addresses of the accounts from the path on, the default one if None, shown on the
+/// device one by one for the confirmation if `confirm`
+#[cfg(feature = "ledger")]
+pub fn list(path: Option<&str>, count: u64, confirm: bool) -> Result<Value, String> {
+    use cita_tool::{pubkey_to_address, DerivationPath, LedgerDevice, LowerHex, PubKey};
+    use serde_json::json;
+
+    let device = LedgerDevice::open(None).map_err(|err| err.to_string())?;
+    let mut path = derivation_path(path)?.parse::<DerivationPath>()?;
+    let mut keys = Vec::new();
+    for _ in 0..count {
+        let pubkey = device
+            .pubkey(&path, confirm)
+            .map_err(|err| err.to_string())?;
+        keys.push(json!({
+            "path": path.to_string(),
+            "address": pubkey_to_address(&PubKey::Secp256k1(pubkey)).completed_lower_hex_with_0x(),
+            "pubkey": pubkey.completed_lower_hex_with_0x(),
+            "use": format!("--signer ledger --derivation-path \"{}\"", path),
+        }));
+        path = path.next();
+    }
+    Ok(json!({ "device": device.path().display().to_string(), "keys": keys }))
+}
+
+#[cfg(not(feature = "ledger"))]
+pub fn list(_path: Option<&str>, _count: u64, _confirm: bool) -> Result<Value, String> {
+    Err(not_built())
+}
+
+#[cfg(not(feature = "ledger"))]
+fn not_built() -> String {
+    "cita-cli is built without the ledger feature, rebuild it by `--features ledger`".to_string()
+}
+
+#[cfg(all(test, feature = "ledger"))]
+mod test {
+    use super::resolve_args;
+
+    #[test]
+    fn test_ledger_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let unchanged = args(&["cita-cli", "transfer", "--private-key", "0x01"]);
+        assert_eq!(resolve_args(unchanged.clone()), Ok(unchanged));
+        assert_eq!(
+            resolve_args(args(&[
+                "cita-cli", "transfer", "--signer", "ledger", "--value", "1"
+            ])),
+            Ok(args(&[
+                "cita-cli",
+                "transfer",
+                "--value",
+                "1",
+                "--private-key=ledger:m/44'/60'/0'/0/0"
+            ]))
+        );
+        assert_eq!(
+            resolve_args(args(&[
+                "transfer",
+                "--derivation-path",
+                "m/44'/60'/1'/0/2",
+                "--signer=ledger"
+            ])),
+            Ok(args(&["transfer", "--private-key=ledger:m/44'/60'/1'/0/2"]))
+        );
+        assert!(resolve_args(args(&["transfer", "--signer", "trezor"])).is_err());
+        assert!(resolve_args(args(&[
+            "transfer",
+            "--signer",
+            "ledger",
+            "--private-key",
+            "0x01"
+        ]))
+        .is_err());
+        assert!(resolve_args(args(&["--signer", "ledger", "--derivation-path", "m/x"])).is_err());
+    }
+}
@@ -1,32 +1,69 @@
 #![deny(warnings)]
 
+mod address_book;
+mod audit;
+mod checkpoint;
 mod cli;
+mod deployments;
+mod hsm;
 mod interactive;
 mod json_color;
+mod key_agent;
+mod ledger;
+mod migrations;
+mod plugin;
+mod policy;
 mod printer;
+mod profile;
+mod qr;
+mod recording;
+mod risk;
+mod sink;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
 use std::collections::HashMap;
 use std::env;
 use std::iter::FromIterator;
 use std::process;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
+use ansi_term::Colour::Red;
 use cita_tool::client::basic::Client;
+use cita_tool::client::{
+    CircuitBreaker, DuplicatePolicy, MethodAliases, NodeSelector, Notice, RateLimiter,
+    RequestTimings, ResponseCache,
+};
+use cita_tool::ParamsValue;
 use clap::crate_version;
 use dotenv::dotenv;
 
 include!(concat!(env!("OUT_DIR"), "/build_info.rs"));
 
+use crate::address_book::AddressBook;
 use crate::cli::{
-    abi_processor, amend_processor, benchmark_processor, build_cli, completion_processor,
-    contract_processor, key_processor, rpc_processor, search_processor, store_processor,
-    transfer_processor, tx_processor,
+    abi_processor, addr_processor, admin_processor, amend_processor, analyze_processor,
+    audit_processor, benchmark_processor, build_cli, chain_processor, completion_processor,
+    conformance_processor, contract_processor, deep_value_of, deep_values_of, devnet_processor,
+    diff_processor, examples_processor, export_processor, faucet_processor, flag_present,
+    grpc_processor, key_processor, migrate_processor, monitor_processor, mq_processor,
+    net_processor, node_processor, parse_extra_param, parse_u256, parse_u32, plugins_processor,
+    profile_processor, replay_processor, rpc_processor, rules_processor, schema_processor,
+    search_processor, store_processor, time_processor, transfer_processor, tx_processor,
+    txpool_processor, update_processor, user_contract_processor, validators_processor,
+    watch_processor,
 };
 use crate::interactive::GlobalConfig;
-use crate::printer::Printer;
+use crate::printer::{Printer, TimeFormat};
 
 const ENV_JSONRPC_URL: &str = "JSONRPC_URL";
 const DEFAULT_JSONRPC_URL: &str = "http://127.0.0.1:1337";
+/// Seconds before probing a node whose circuit is open
+const CIRCUIT_COOLDOWN: u64 = 30;
+/// Seconds between the latency probes of the nodes
+const NODE_PROBE_INTERVAL: u64 = 60;
 
 fn main() {
     dotenv().ok();
@@ -42,17 +79,130 @@ fn main() {
         .remove(ENV_JSONRPC_URL)
         .unwrap_or_else(|| DEFAULT_JSONRPC_URL.to_owned());
 
-    let printer = Printer::default();
+    let mut printer = Printer::default();
+    if let Err(err) = profile::unlock() {
+        printer.eprintln(&Rc::new(err), true);
+        process::exit(1);
+    }
+    let address_book = AddressBook::load();
+    printer.set_names(address_book.names());
     let mut config = GlobalConfig::new(default_jsonrpc_url.to_string());
     let mut parser = build_cli(version.as_str());
-    let matches = parser.clone().get_matches();
-    let client = Client::new();
+    if let Some((name, args)) = plugin::external(&parser, &env::args().collect::<Vec<String>>()) {
+        config.load_profile();
+        if let Ok(url) = env::var(ENV_JSONRPC_URL) {
+            config.set_url(url);
+        }
+        printer.eprintln(&Rc::new(plugin::exec(&name, &args, &config)), true);
+        process::exit(1);
+    }
+    audit::set_command(&env::args().skip(1).collect::<Vec<String>>());
+    let args = ledger::resolve_args(key_agent::resolve_args(
+        &address_book,
+        address_book.resolve_args(env::args().collect()),
+    ))
+    .unwrap_or_else(|err| {
+        printer.eprintln(&Rc::new(err), true);
+        process::exit(1);
+    });
+    let matches = parser.clone().get_matches_from(&args);
+    let signer = hsm::signer(&args)
+        .transpose()
+        .or_else(|| ledger::signer(&args).transpose())
+        .transpose()
+        .unwrap_or_else(|err| {
+            printer.eprintln(&Rc::new(err), true);
+            process::exit(1);
+        });
+    let send_policy = policy::from_args(deep_value_of(&matches, "policy")).unwrap_or_else(|err| {
+        printer.eprintln(&Rc::new(err), true);
+        process::exit(1);
+    });
+    let client = Client::new()
+        .set_signer(signer)
+        .set_send_policy(send_policy)
+        .set_duplicate_policy(if flag_present(&matches, "no-duplicate") {
+            DuplicatePolicy::Reject
+        } else {
+            DuplicatePolicy::Warn
+        })
+        .set_fee_check(flag_present(&matches, "check-fee"))
+        .set_chain_check(!flag_present(&matches, "no-chain-check"))
+        .set_limit_check(!flag_present(&matches, "no-limit-check"))
+        .set_notifier(Some(Arc::new(|notice: &Notice| match notice {
+            Notice::Request(_) => println!("{}", notice),
+            Notice::ChainMismatch { .. } => eprintln!("{}", Red.bold().paint(notice.to_string())),
+            Notice::Sent { .. } => audit::record(notice),
+            _ => eprintln!("{}", notice),
+        })))
+        .set_max_fee(deep_value_of(&matches, "max-fee").map(|fee| parse_u256(fee).unwrap()))
+        .set_cache(match deep_value_of(&matches, "cache-dir") {
+            Some(dir) => Some(ResponseCache::new(dir)),
+            None if flag_present(&matches, "cache") => Some(ResponseCache::new(
+                interactive::cita_cli_dir().join("cache"),
+            )),
+            None => None,
+        })
+        .set_rate_limiter(
+            deep_values_of(&matches, "rate-limit")
+                .map(|limits| RateLimiter::from_limits(limits).unwrap()),
+        )
+        .set_circuit_breaker(deep_value_of(&matches, "circuit-breaker").map(|failures| {
+            CircuitBreaker::new(
+                parse_u32(failures).unwrap(),
+                Duration::from_secs(CIRCUIT_COOLDOWN),
+            )
+        }))
+        .set_node_selector(node_selector(&matches))
+        .set_strict(flag_present(&matches, "strict"))
+        .set_method_aliases(if flag_present(&matches, "no-aliases") {
+            None
+        } else {
+            Some(MethodAliases::from_file(
+                interactive::cita_cli_dir().join("aliases.json"),
+            ))
+        })
+        .set_extra_params(extra_params(&matches))
+        .set_timings(if flag_present(&matches, "timing") {
+            Some(RequestTimings::new())
+        } else {
+            None
+        });
+
+    if flag_present(&matches, "utc") {
+        printer.set_time_format(TimeFormat::Utc);
+    } else if flag_present(&matches, "local") {
+        printer.set_time_format(TimeFormat::Local);
+    } else if flag_present(&matches, "unix") {
+        printer.set_time_format(TimeFormat::Unix);
+    }
+    if flag_present(&matches, "signers") {
+        printer.set_signers(Some(config.encryption()));
+    }
 
-    if let Err(err) = match matches.subcommand() {
+    if flag_present(&matches, "show-chain") {
+        let url = deep_value_of(&matches, "url").unwrap_or(&default_jsonrpc_url);
+        match client.clone().set_uri(url).get_chain_info() {
+            Ok(chain) if !chain.chain_name.is_empty() => {
+                printer.set_chain(Some(chain.chain_name));
+            }
+            Ok(chain) => {
+                printer.set_chain(Some(format!("chain {:#x}", chain.chain_id_v1)));
+            }
+            Err(err) => {
+                printer.eprintln(&Rc::new(format!("Can't get the chain name: {}", err)), true)
+            }
+        }
+    }
+
+    let timings = client.timings().cloned();
+    let result = match matches.subcommand() {
         ("rpc", Some(m)) => rpc_processor(m, &printer, &mut config, client),
         ("ethabi", Some(m)) => abi_processor(m, &printer, &config),
-        ("key", Some(m)) => key_processor(m, &printer, &config),
+        ("key", Some(m)) => key_processor(m, &printer, &mut config, client),
+        ("addr", Some(m)) => addr_processor(m, &printer, &config),
         ("scm", Some(m)) => contract_processor(m, &printer, &mut config, client),
+        ("contract", Some(m)) => user_contract_processor(m, &printer, &mut config, client),
         ("transfer", Some(m)) => transfer_processor(m, &printer, &mut config, client),
         ("store", Some(m)) => store_processor(m, &printer, &mut config, client),
         ("amend", Some(m)) => amend_processor(m, &printer, &mut config, client),
@@ -62,6 +212,32 @@ fn main() {
         }
         ("tx", Some(m)) => tx_processor(m, &printer, &mut config, client),
         ("benchmark", Some(m)) => benchmark_processor(m, &printer, &config, client),
+        ("watch", Some(m)) => watch_processor(m, &printer, &config, client),
+        ("chain", Some(m)) => chain_processor(m, &printer, &mut config, client),
+        ("analyze", Some(m)) => analyze_processor(m, &printer, &mut config, client),
+        ("validators", Some(m)) => validators_processor(m, &printer, &mut config, client),
+        ("admin", Some(m)) => admin_processor(m, &printer, &mut config, client),
+        ("faucet", Some(m)) => faucet_processor(m, &printer, &mut config, client),
+        ("node", Some(m)) => node_processor(m, &printer, &mut config, client),
+        ("monitor", Some(m)) => monitor_processor(m, &printer, &config, client),
+        ("net", Some(m)) => net_processor(m, &printer, &mut config, client),
+        ("txpool", Some(m)) => txpool_processor(m, &printer, &mut config, client),
+        ("migrate", Some(m)) => migrate_processor(m, &printer, &mut config, client),
+        ("rules", Some(m)) => rules_processor(m, &printer, &config, client),
+        ("schema", Some(m)) => schema_processor(m, &printer, &mut config, client),
+        ("conformance", Some(m)) => conformance_processor(m, &printer, &mut config, client),
+        ("replay", Some(m)) => replay_processor(m, &printer, &config, client),
+        ("diff", Some(m)) => diff_processor(m, &printer, &mut config, client),
+        ("time", Some(m)) => time_processor(m, &printer, &mut config, client),
+        ("devnet", Some(m)) => devnet_processor(m, &printer, &mut config, client),
+        ("export", Some(m)) => export_processor(m, &printer, &mut config, client),
+        ("grpc", Some(m)) => grpc_processor(m, &printer, &mut config),
+        ("mq", Some(m)) => mq_processor(m, &printer, &mut config),
+        ("profile", Some(m)) => profile_processor(m, &printer, &mut config),
+        ("audit", Some(m)) => audit_processor(m, &printer, &mut config),
+        ("examples", Some(m)) => examples_processor(m, &printer, &mut config),
+        ("plugins", Some(m)) => plugins_processor(m, &printer, &mut config),
+        ("update", Some(m)) => update_processor(m, &printer, &mut config, client),
         ("completions", Some(m)) => {
             completion_processor(&mut parser, m);
             Ok(())
@@ -72,7 +248,11 @@ fn main() {
             }
             Ok(())
         }
-    } {
+    };
+    if let Some(timings) = timings {
+        eprintln!("{}", timings);
+    }
+    if let Err(err) = result {
         printer.eprintln(&Rc::new(err), true);
         process::exit(1);
     }
@@ -85,3 +265,32 @@ fn feature_version() -> String {
         "use rustls".to_owned()
     }
 }
+
+/// The extra params of the config, overridden by `--extra`
+fn extra_params(matches: &clap::ArgMatches) -> HashMap<String, ParamsValue> {
+    let mut extra_params: HashMap<String, ParamsValue> = interactive::profile_extra_params()
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, serde_json::from_value(value).ok()?)))
+        .collect();
+    for param in deep_values_of(matches, "extra").unwrap_or_default() {
+        let (key, value) = parse_extra_param(param).unwrap();
+        extra_params.insert(key, value);
+    }
+    extra_params
+}
+
+/// Select the nodes of the reads by `--nodes` and `--node`
+fn node_selector(matches: &clap::ArgMatches) -> Option<NodeSelector> {
+    let nodes = deep_values_of(matches, "nodes").unwrap_or_default();
+    let pinned = deep_value_of(matches, "node");
+    if nodes.is_empty() && pinned.is_none() {
+        return None;
+    }
+    Some(
+        NodeSelector::new(
+            nodes.into_iter().map(|url| url.parse().unwrap()).collect(),
+            Duration::from_secs(NODE_PROBE_INTERVAL),
+        )
+        .pin(pinned.map(|url| url.parse().unwrap())),
+    )
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+
+use cita_tool::encode_json_input;
+
+use crate::interactive::cita_cli_dir;
+
+/// A step of a migration
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    /// Deploy the code, the later steps and migrations know the contract by the name
+    Deploy { name: String, code: String },
+    /// Send the data to the contract, by its name or its address
+    Call { to: String, data: String },
+}
+
+/// A numbered migration file, `<number>_<name>.toml` of `[[step]]` tables
+///
+/// A deploy step has `deploy = "<name>"` and either `code` or `code_file`, with
+/// `abi_file` and `args` when the constructor takes arguments. A call step has
+/// `call = "<name or address>"` and either `data`, or `abi_file`, `function` and `args`.
+/// The files are relative to the directory of the migrations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    pub number: u64,
+    /// The file name without the extension, what the state records
+    pub id: String,
+    pub steps: Vec<Step>,
+}
+
+impl Migration {
+    /// Parse the migration file of the directory
+    pub fn parse(dir: &Path, file_name: &str, content: &str) -> Result<Self, String> {
+        let id = file_name.trim_end_matches(".toml").to_owned();
+        let number = id
+            .split('_')
+            .next()
+            .and_then(|number| number.parse::<u64>().ok())
+            .ok_or_else(|| format!("{}: the file name must start with its number", file_name))?;
+        let migration: Value = toml::from_str(content)
+            .map_err(|err| format!("Invalid migration {}: {}", file_name, err))?;
+        let steps = migration["step"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .iter()
+            .enumerate()
+            .map(|(index, step)| {
+                parse_step(dir, step).map_err(|err| format!("{} step {}: {}", id, index + 1, err))
+            })
+            .collect::<Result<Vec<Step>, String>>()?;
+        if steps.is_empty() {
+            return Err(format!("{}: no [[step]]", file_name));
+        }
+        Ok(Migration { number, id, steps })
+    }
+
+    /// The migrations of the directory, in the order of their numbers
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>, String> {
+        let entries = fs::read_dir(dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+        let mut migrations = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|err| format!("{}", err))?.path();
+            let file_name = path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            if !file_name.ends_with(".toml") {
+                continue;
+            }
+            let content =
+                fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.display(), err))?;
+            migrations.push(Migration::parse(dir, &file_name, &content)?);
+        }
+        migrations.sort_by_key(|migration| migration.number);
+        if let Some(pair) = migrations
+            .windows(2)
+            .find(|pair| pair[0].number == pair[1].number)
+        {
+            return Err(format!(
+                "{} and {} have the same number",
+                pair[0].id, pair[1].id
+            ));
+        }
+        Ok(migrations)
+    }
+}
+
+fn parse_step(dir: &Path, step: &Value) -> Result<Step, String> {
+    let text = |key: &str| step[key].as_str().map(str::to_owned);
+    let file = |key: &str| -> Result<Option<String>, String> {
+        match step[key].as_str() {
+            Some(file) => fs::read_to_string(dir.join(file))
+                .map(|content| Some(content.trim().to_owned()))
+                .map_err(|err| format!("{}: {}", file, err)),
+            None => Ok(None),
+        }
+    };
+    let args = if step["args"].is_null() {
+        json!([])
+    } else {
+        step["args"].clone()
+    };
+
+    if let Some(name) = text("deploy") {
+        let code = match (text("code"), file("code_file")?) {
+            (Some(code), _) | (None, Some(code)) => code,
+            (None, None) => return Err("deploy needs code or code_file".to_string()),
+        };
+        let code = match file("abi_file")? {
+            Some(abi) => {
+                encode_json_input(&abi, &code, &args, true).map_err(|err| format!("{}", err))?
+            }
+            None => code,
+        };
+        Ok(Step::Deploy { name, code })
+    } else if let Some(to) = text("call") {
+        let data = match (text("data"), file("abi_file")?, text("function")) {
+            (Some(data), _, _) => data,
+            (None, Some(abi), Some(function)) => format!(
+                "0x{}",
+                encode_json_input(&abi, &function, &args, false)
+                    .map_err(|err| format!("{}", err))?
+            ),
+            _ => return Err("call needs data, or abi_file and function".to_string()),
+        };
+        Ok(Step::Call { to, data })
+    } else {
+        Err("a step is either deploy or call".to_string())
+    }
+}
+
+/// The migrations applied to every chain and the contracts they deployed, stored in
+/// `~/.cita-cli/migrations.json` unless another file is given
+pub struct MigrationState {
+    path: PathBuf,
+    /// chain id -> `{ "applied": [<id>], "contracts": { <name>: <address> } }`
+    chains: BTreeMap<String, Value>,
+}
+
+impl MigrationState {
+    /// Load the state, empty if the file doesn't exist
+    pub fn load(path: Option<&str>) -> Result<Self, String> {
+        let path = path
+            .map(PathBuf::from)
+            .unwrap_or_else(|| cita_cli_dir().join("migrations.json"));
+        let chains = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| format!("Invalid migration state {}: {}", path.display(), err))?,
+            Err(_) => BTreeMap::new(),
+        };
+        Ok(MigrationState { path, chains })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|err| format!("{}", err))?;
+        }
+        let content = serde_json::to_string_pretty(&self.chains).unwrap();
+        fs::write(&self.path, content).map_err(|err| format!("{}", err))
+    }
+
+    /// Whether the migration is applied to the chain
+    pub fn is_applied(&self, chain: &str, id: &str) -> bool {
+        self.chains
+            .get(chain)
+            .and_then(|state| state["applied"].as_array())
+            .is_some_and(|applied| applied.iter().any(|applied| applied == id))
+    }
+
+    /// The contracts deployed on the chain by the applied migrations
+    pub fn contracts(&self, chain: &str) -> BTreeMap<String, String> {
+        self.chains
+            .get(chain)
+            .and_then(|state| state["contracts"].as_object())
+            .map(|contracts| {
+                contracts
+                    .iter()
+                    .filter_map(|(name, address)| {
+                        address
+                            .as_str()
+                            .map(|address| (name.clone(), address.to_owned()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Record the migration as applied with the contracts it deployed
+    pub fn apply(&mut self, chain: &str, id: &str, contracts: &BTreeMap<String, String>) {
+        let state = self
+            .chains
+            .entry(chain.to_owned())
+            .or_insert_with(|| json!({ "applied": [], "contracts": {} }));
+        if let Some(applied) = state["applied"].as_array_mut() {
+            applied.push(json!(id));
+        }
+        for (name, address) in contracts {
+            state["contracts"][name] = json!(address);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Migration, MigrationState, Step};
+    use std::collections::BTreeMap;
+    use std::fs;
+
+    #[test]
+    fn test_migrations() {
+        let dir = std::env::temp_dir().join(format!("cita-cli-migrations-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("2_setup.toml"),
+            "[[step]]\ncall = \"Token\"\ndata = \"0x01\"\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("1_token.toml"),
+            "[[step]]\ndeploy = \"Token\"\ncode = \"0x6060\"\n",
+        )
+        .unwrap();
+        fs::write(dir.join("README"), "").unwrap();
+
+        let migrations = Migration::load_dir(&dir).unwrap();
+        assert_eq!(
+            migrations
+                .iter()
+                .map(|migration| migration.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1_token", "2_setup"]
+        );
+        assert_eq!(
+            migrations[0].steps,
+            vec![Step::Deploy {
+                name: "Token".to_string(),
+                code: "0x6060".to_string()
+            }]
+        );
+        assert!(Migration::parse(&dir, "setup.toml", "[[step]]\ncall = \"A\"").is_err());
+        assert!(Migration::parse(&dir, "3_bad.toml", "[[step]]\ncall = \"A\"").is_err());
+
+        let path = dir.join("state.json").to_string_lossy().into_owned();
+        let mut state = MigrationState::load(Some(&path)).unwrap();
+        let mut contracts = BTreeMap::new();
+        contracts.insert("Token".to_string(), "0x01".to_string());
+        state.apply("0x1", "1_token", &contracts);
+        state.save().unwrap();
+        let state = MigrationState::load(Some(&path)).unwrap();
+        assert!(state.is_applied("0x1", "1_token"));
+        assert!(!state.is_applied("0x2", "1_token"));
+        assert_eq!(state.contracts("0x1"), contracts);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::App;
+
+use crate::interactive::{cita_cli_dir, GlobalConfig};
+
+/// The executables of the plugins are `cita-cli-<name>` on PATH, run by `cita-cli <name>`
+const PREFIX: &str = "cita-cli-";
+
+/// The name of a subcommand cita-cli doesn't have and its args, None for the subcommands it has
+///
+/// The args are the ones given, before the names and the keys are substituted in them, so that
+/// a plugin never gets the keys of the agent.
+pub fn external(parser: &App, args: &[String]) -> Option<(String, Vec<String>)> {
+    let matches = parser.clone().get_matches_from_safe(args).ok()?;
+    let (name, m) = matches.subcommand();
+    // The args after an external subcommand are the values of "" of its matches
+    if let Some(values) = m?.values_of("") {
+        return Some((name.to_owned(), values.map(str::to_owned).collect()));
+    }
+    // No args follow the name: an external subcommand takes `--help` as its arg, one of the
+    // parser shows its help instead
+    let mut probe = args.to_vec();
+    probe.push("--help".to_owned());
+    let probe = parser.clone().get_matches_from_safe(probe).ok()?;
+    probe.subcommand_matches(name)?.values_of("")?;
+    Some((name.to_owned(), Vec::new()))
+}
+
+fn is_executable(path: &Path) -> bool {
+    match fs::metadata(path) {
+        #[cfg(unix)]
+        Ok(metadata) => {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        }
+        #[cfg(not(unix))]
+        Ok(metadata) => metadata.is_file(),
+        Err(_) => false,
+    }
+}
+
+fn find_in(paths: &OsStr, name: &str) -> Option<PathBuf> {
+    env::split_paths(paths)
+        .map(|dir| dir.join(format!("{}{}{}", PREFIX, name, env::consts::EXE_SUFFIX)))
+        .find(|path| is_executable(path))
+}
+
+fn list_in(paths: &OsStr) -> BTreeMap<String, PathBuf> {
+    let mut plugins = BTreeMap::new();
+    for dir in env::split_paths(paths) {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for path in entries.filter_map(Result::ok).map(|entry| entry.path()) {
+            let name = path
+                .file_name()
+                .and_then(OsStr::to_str)
+                .and_then(|name| name.strip_prefix(PREFIX))
+                .map(|name| name.trim_end_matches(env::consts::EXE_SUFFIX).to_owned());
+            if let Some(name) = name.filter(|name| !name.is_empty()) {
+                if is_executable(&path) {
+                    // The first on PATH is the one run
+                    plugins.entry(name).or_insert(path);
+                }
+            }
+        }
+    }
+    plugins
+}
+
+/// The plugins on PATH by their names
+pub fn list() -> BTreeMap<String, PathBuf> {
+    env::var_os("PATH")
+        .map(|paths| list_in(&paths))
+        .unwrap_or_default()
+}
+
+/// The command running the plugin, the settings of the shell or the profile are in its
+/// environment: `CITA_CLI_URL` (and `JSONRPC_URL`, so `$CITA_CLI` talks to the same node),
+/// `CITA_CLI_ENCRYPTION`, `CITA_CLI_COLOR`, `CITA_CLI_DEBUG`, `CITA_CLI_DIR` and `CITA_CLI`,
+/// the path of cita-cli
+fn command(name: &str, args: &[String], config: &GlobalConfig) -> Result<Command, String> {
+    let path = env::var_os("PATH")
+        .and_then(|paths| find_in(&paths, name))
+        .ok_or_else(|| {
+            format!(
+                "No such command `{}`, and no {}{} plugin on PATH, see `cita-cli --help` \
+                 and `cita-cli plugins`",
+                name, PREFIX, name
+            )
+        })?;
+    let mut command = Command::new(path);
+    command
+        .args(args)
+        .env("CITA_CLI", env::current_exe().unwrap_or_default())
+        .env("CITA_CLI_DIR", cita_cli_dir())
+        .env("CITA_CLI_URL", config.get_url())
+        .env("JSONRPC_URL", config.get_url())
+        .env("CITA_CLI_ENCRYPTION", config.encryption().to_string())
+        .env("CITA_CLI_COLOR", config.color().to_string())
+        .env("CITA_CLI_DEBUG", config.debug().to_string());
+    Ok(command)
+}
+
+/// Run the plugin in place of cita-cli, it returns only if the plugin can't be run
+pub fn exec(name: &str, args: &[String], config: &GlobalConfig) -> String {
+    let mut command = match command(name, args, config) {
+        Ok(command) => command,
+        Err(err) => return err,
+    };
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        format!("Can't run {}{}: {}", PREFIX, name, command.exec())
+    }
+    #[cfg(not(unix))]
+    match command.status() {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(err) => format!("Can't run {}{}: {}", PREFIX, name, err),
+    }
+}
+
+/// Run the plugin from the interactive shell, waiting for it
+pub fn run(name: &str, args: &[String], config: &GlobalConfig) -> Result<(), String> {
+    let status = command(name, args, config)?
+        .status()
+        .map_err(|err| format!("Can't run {}{}: {}", PREFIX, name, err))?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(format!("{}{} exited with {}", PREFIX, name, status))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{external, find_in, list_in};
+    use clap::{App, AppSettings, Arg, SubCommand};
+    use std::fs;
+
+    #[test]
+    fn test_plugins() {
+        let dir = std::env::temp_dir().join(format!("cita-cli-plugins-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let plugin = dir.join("cita-cli-hello");
+        fs::write(&plugin, "#!/bin/sh\necho hello\n").unwrap();
+        fs::write(dir.join("cita-cli-not-executable"), "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&plugin, fs::Permissions::from_mode(0o755)).unwrap();
+            assert_eq!(find_in(dir.as_os_str(), "not-executable"), None);
+        }
+        assert_eq!(find_in(dir.as_os_str(), "hello"), Some(plugin.clone()));
+        assert_eq!(find_in(dir.as_os_str(), "rpc"), None);
+        assert!(list_in(dir.as_os_str()).contains_key("hello"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        let parser = App::new("cita-cli")
+            .setting(AppSettings::AllowExternalSubcommands)
+            .subcommand(SubCommand::with_name("rpc").arg(Arg::with_name("url").long("url")));
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(external(&parser, &args(&["cita-cli"])), None);
+        assert_eq!(external(&parser, &args(&["cita-cli", "rpc"])), None);
+        assert_eq!(
+            external(&parser, &args(&["cita-cli", "rpc", "--url"])),
+            None
+        );
+        assert_eq!(
+            external(&parser, &args(&["cita-cli", "hello"])),
+            Some(("hello".to_owned(), Vec::new()))
+        );
+        assert_eq!(
+            external(
+                &parser,
+                &args(&["cita-cli", "hello", "--private-key", "alice"])
+            ),
+            Some(("hello".to_owned(), args(&["--private-key", "alice"])))
+        );
+    }
+}
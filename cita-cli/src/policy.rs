@@ -0,0 +1,86 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use cita_tool::client::{OutgoingTransaction, SendPolicy};
+
+use crate::profile;
+
+/// An external program deciding on the transactions: it reads the decoded transaction as JSON
+/// from stdin, exits 0 to let it be sent, or else vetoes it with what it printed as the reason
+pub struct ProgramPolicy {
+    command: Vec<String>,
+}
+
+impl ProgramPolicy {
+    /// The command line of the program, split as a shell does
+    pub fn new(command: &str) -> Result<Self, String> {
+        let command = shell_words::split(command).map_err(|err| err.to_string())?;
+        if command.is_empty() {
+            return Err("Empty policy program".to_string());
+        }
+        Ok(ProgramPolicy { command })
+    }
+}
+
+impl SendPolicy for ProgramPolicy {
+    fn check(&self, transaction: &OutgoingTransaction) -> Result<(), String> {
+        let mut child = Command::new(&self.command[0])
+            .args(&self.command[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Can't run {}: {}", self.command[0], err))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            // A program deciding without reading it all closes the pipe early
+            let _ = writeln!(stdin, "{}", transaction.to_value());
+        }
+        let output = child.wait_with_output().map_err(|err| err.to_string())?;
+        if output.status.success() {
+            return Ok(());
+        }
+        let reason = [&output.stdout, &output.stderr]
+            .iter()
+            .map(|content| String::from_utf8_lossy(content).trim().to_owned())
+            .find(|content| !content.is_empty())
+            .unwrap_or_else(|| format!("{} exited with {}", self.command[0], output.status));
+        Err(reason)
+    }
+}
+
+/// The policy program of `--policy`, else the `policy` of `~/.cita-cli/config`
+pub fn from_args(policy: Option<&str>) -> Result<Option<Arc<dyn SendPolicy>>, String> {
+    let config = profile::load_config();
+    match policy.or_else(|| config["policy"].as_str()) {
+        Some(command) => Ok(Some(Arc::new(ProgramPolicy::new(command)?))),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ProgramPolicy;
+    use cita_tool::client::{OutgoingTransaction, SendPolicy};
+    use cita_tool::{TransactionBuilder, TransactionVersion};
+
+    #[test]
+    fn test_program_policy() {
+        let tx = TransactionBuilder::new(TransactionVersion::V2)
+            .build()
+            .unwrap();
+        let outgoing = OutgoingTransaction {
+            url: "http://127.0.0.1:1337/",
+            sender: None,
+            transaction: &tx,
+        };
+        let policy = |command: &str| ProgramPolicy::new(command).unwrap().check(&outgoing);
+        assert_eq!(policy("grep -q 127.0.0.1"), Ok(()));
+        assert_eq!(
+            policy("sh -c 'echo \"Not in the business hours\"; exit 1'"),
+            Err("Not in the business hours".to_owned())
+        );
+        assert!(policy("false").unwrap_err().contains("exited with"));
+        assert!(ProgramPolicy::new("").is_err());
+    }
+}
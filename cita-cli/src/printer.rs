@@ -1,14 +1,18 @@
+use std::cell::{RefCell, RefMut};
+use std::collections::BTreeMap;
 use std::default;
 use std::env;
 use std::io;
 use std::rc::Rc;
 
 use ansi_term::Colour::{Red, Yellow};
-use atty;
-use serde_json::{self, json};
+use chrono::{Local, SecondsFormat, TimeZone, Utc};
+use serde_json::{self, json, Value};
 
 use crate::json_color::Colorizer;
-use cita_tool::{JsonRpcResponse, KeyPair};
+use crate::recording::Recording;
+use cita_tool::client::light::BftProof;
+use cita_tool::{to_checksum_address, Encryption, JsonRpcResponse, KeyPair, LowerHex, Quantity};
 
 pub fn is_a_tty(stderr: bool) -> bool {
     let stream = if stderr {
@@ -50,9 +54,39 @@ impl default::Default for ColorWhen {
     }
 }
 
+/// How the block timestamps in milliseconds are printed
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum TimeFormat {
+    /// As the node returns
+    Raw,
+    /// Seconds since the epoch
+    Unix,
+    /// RFC 3339 in UTC
+    Utc,
+    /// RFC 3339 in the local time zone
+    Local,
+}
+
+impl TimeFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "raw" => Some(TimeFormat::Raw),
+            "unix" => Some(TimeFormat::Unix),
+            "utc" => Some(TimeFormat::Utc),
+            "local" => Some(TimeFormat::Local),
+            _ => None,
+        }
+    }
+}
+
 pub struct Printer {
     format: OutputFormat,
     color: ColorWhen,
+    names: BTreeMap<String, String>,
+    chain: Option<String>,
+    time_format: TimeFormat,
+    signers: Option<Encryption>,
+    recording: RefCell<Option<Recording>>,
 }
 
 impl default::Default for Printer {
@@ -60,6 +94,11 @@ impl default::Default for Printer {
         Printer {
             format: OutputFormat::Json,
             color: ColorWhen::default(),
+            names: BTreeMap::new(),
+            chain: None,
+            time_format: TimeFormat::Raw,
+            signers: None,
+            recording: RefCell::new(None),
         }
     }
 }
@@ -86,6 +125,55 @@ impl Printer {
         self
     }
 
+    /// Address -> name, colored output annotates the addresses with their names
+    pub fn set_names(&mut self, names: BTreeMap<String, String>) -> &mut Self {
+        self.names = names;
+        self
+    }
+
+    /// The chain name of the node, colored output annotates all the addresses with it
+    pub fn set_chain(&mut self, chain: Option<String>) -> &mut Self {
+        self.chain = chain;
+        self
+    }
+
+    /// Print the `timestamp` fields of the json output in the format
+    pub fn set_time_format(&mut self, time_format: TimeFormat) -> &mut Self {
+        self.time_format = time_format;
+        self
+    }
+
+    /// List the validators signed the BFT proofs of the blocks, recovered by the encryption
+    pub fn set_signers(&mut self, encryption: Option<Encryption>) -> &mut Self {
+        self.signers = encryption;
+        self
+    }
+
+    pub fn signers(&self) -> Option<Encryption> {
+        self.signers
+    }
+
+    /// Copy the outputs to the transcript of the recording, the recording before is returned
+    pub fn set_recording(&mut self, recording: Option<Recording>) -> Option<Recording> {
+        self.recording.replace(recording)
+    }
+
+    pub fn recording(&self) -> RefMut<'_, Option<Recording>> {
+        self.recording.borrow_mut()
+    }
+
+    fn record<P: Printable>(&self, content: &P) {
+        if let Some(recording) = self.recording.borrow_mut().as_mut() {
+            // The shell goes on whether the transcript is written or not
+            let _ = self.print(
+                recording.transcript(),
+                content,
+                true,
+                Some(ColorWhen::Never),
+            );
+        }
+    }
+
     pub fn print<W: io::Write, P: Printable>(
         &self,
         target: &mut W,
@@ -97,9 +185,26 @@ impl Printer {
             ColorWhen::Always | ColorWhen::Auto => true,
             ColorWhen::Never => false,
         };
-        target.write_all(content.rc_string(self.format, color).as_bytes())?;
+        let content = match content.json_value() {
+            Some(mut value) if self.time_format != TimeFormat::Raw || self.signers.is_some() => {
+                if self.time_format != TimeFormat::Raw {
+                    format_timestamps(&mut value, self.time_format);
+                }
+                if let Some(encryption) = self.signers {
+                    annotate_signers(&mut value, encryption, &self.names);
+                }
+                value.rc_string(self.format, color)
+            }
+            _ => content.rc_string(self.format, color),
+        };
+        if color && (!self.names.is_empty() || self.chain.is_some()) {
+            let annotated = annotate_addresses(&content, &self.names, self.chain.as_deref());
+            target.write_all(annotated.as_bytes())?;
+        } else {
+            target.write_all(content.as_bytes())?;
+        }
         if newline {
-            target.write_all(&[b'\n'])?;
+            target.write_all(b"\n")?;
         }
         Ok(())
     }
@@ -109,6 +214,7 @@ impl Printer {
         let color = if color { None } else { Some(ColorWhen::Never) };
         self.print(&mut stdout.lock(), content, true, color)
             .unwrap();
+        self.record(content);
     }
 
     pub fn eprintln<P: Printable>(&self, content: &P, color: bool) {
@@ -121,11 +227,136 @@ impl Printer {
         let color = if color { None } else { Some(ColorWhen::Never) };
         self.print(&mut stderr.lock(), content, true, color)
             .unwrap();
+        self.record(content);
+    }
+}
+
+/// Follow every address in the text by its name and the chain, like `0x... (alice@test-chain)`
+fn annotate_addresses(text: &str, names: &BTreeMap<String, String>, chain: Option<&str>) -> String {
+    let bytes = text.as_bytes();
+    // Not a part of a longer hex, the escapes of the colors end with letters but not digits
+    let is_hex = |index: usize| bytes.get(index).is_some_and(u8::is_ascii_hexdigit);
+    let mut annotated = String::with_capacity(text.len());
+    let mut copied = 0;
+    let mut index = 0;
+    while let Some(found) = text[index..].find("0x") {
+        let start = index + found;
+        let end = start + 42;
+        index = start + 2;
+        if (start > 0 && is_hex(start - 1))
+            || end > bytes.len()
+            || !bytes[start + 2..end].iter().all(u8::is_ascii_hexdigit)
+            || is_hex(end)
+        {
+            continue;
+        }
+        let address = &text[start..end];
+        let name = names
+            .get(address)
+            .or_else(|| names.get(&address.to_lowercase()));
+        let label = match (name, chain) {
+            (Some(name), Some(chain)) => format!("{}@{}", name, chain),
+            (Some(name), None) => name.to_owned(),
+            (None, Some(chain)) => chain.to_owned(),
+            (None, None) => continue,
+        };
+        annotated.push_str(&text[copied..end]);
+        annotated.push_str(&format!(" ({})", label));
+        copied = end;
+        index = end;
+    }
+    annotated.push_str(&text[copied..]);
+    annotated
+}
+
+/// The timestamp in milliseconds in the format, `null` if it is out of range
+pub fn format_timestamp(millis: u64, time_format: TimeFormat) -> Value {
+    let millis = millis as i64;
+    match time_format {
+        TimeFormat::Raw => json!(millis),
+        TimeFormat::Unix => json!(millis / 1000),
+        TimeFormat::Utc => json!(Utc
+            .timestamp_millis_opt(millis)
+            .single()
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, true))),
+        TimeFormat::Local => json!(Local
+            .timestamp_millis_opt(millis)
+            .single()
+            .map(|time| time.to_rfc3339_opts(SecondsFormat::Millis, false))),
+    }
+}
+
+/// Rewrite the `timestamp` fields at any depth, the ones that aren't numbers are kept
+fn format_timestamps(value: &mut Value, time_format: TimeFormat) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match Quantity::from_json(field).and_then(|millis| millis.as_u64().ok()) {
+                    Some(millis) if key == "timestamp" => {
+                        *field = format_timestamp(millis, time_format)
+                    }
+                    _ => format_timestamps(field, time_format),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| format_timestamps(value, time_format)),
+        _ => {}
+    }
+}
+
+/// Add the `signers` of the BFT proofs at any depth, the validators with their names in the
+/// address book and whether their signatures are valid
+fn annotate_signers(value: &mut Value, encryption: Encryption, names: &BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                match BftProof::from_json(field) {
+                    Some(proof) if key == "proof" => {
+                        let validators = proof
+                            .commits
+                            .iter()
+                            .map(|(sender, signature)| {
+                                let address = sender.completed_lower_hex_with_0x();
+                                let name = names.get(&address).cloned().or_else(|| {
+                                    names
+                                        .iter()
+                                        .find(|(other, _)| other.eq_ignore_ascii_case(&address))
+                                        .map(|(_, name)| name.clone())
+                                });
+                                json!({
+                                    "address": address,
+                                    "name": name,
+                                    "valid": proof.is_signed_by(sender, signature, encryption),
+                                })
+                            })
+                            .collect::<Vec<_>>();
+                        field["signers"] = json!({
+                            "height": proof.height,
+                            "round": proof.round,
+                            "proposal": proof.proposal.completed_lower_hex_with_0x(),
+                            "validators": validators,
+                        });
+                    }
+                    _ => annotate_signers(field, encryption, names),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| annotate_signers(value, encryption, names)),
+        _ => {}
     }
 }
 
 pub trait Printable {
     fn rc_string(&self, format: OutputFormat, color: bool) -> Rc<String>;
+
+    /// The content as json, which can be rewritten before printing
+    fn json_value(&self) -> Option<Value> {
+        None
+    }
 }
 
 impl Printable for String {
@@ -152,6 +383,10 @@ impl Printable for JsonRpcResponse {
         };
         Rc::new(content)
     }
+
+    fn json_value(&self) -> Option<Value> {
+        serde_json::to_value(self).ok()
+    }
 }
 
 impl Printable for serde_json::Value {
@@ -166,6 +401,10 @@ impl Printable for serde_json::Value {
         };
         Rc::new(content)
     }
+
+    fn json_value(&self) -> Option<Value> {
+        Some(self.clone())
+    }
 }
 
 impl Printable for KeyPair {
@@ -174,29 +413,29 @@ impl Printable for KeyPair {
             OutputFormat::Json => json!({
                 "private": format!("0x{}", self.privkey()),
                 "public": format!("0x{}", self.pubkey()),
-                "address": format!("0x{:x}", self.address())
+                "address": to_checksum_address(&self.address())
             })
             .rc_string(format, color),
             OutputFormat::Raw => {
                 let content = if color {
                     format!(
-                        concat!("{} 0x{}\n", "{} 0x{}\n", "{} 0x{:x}"),
+                        concat!("{} 0x{}\n", "{} 0x{}\n", "{} {}"),
                         Yellow.paint("[ private ]:"),
                         self.privkey(),
                         Yellow.paint("[ public  ]:"),
                         self.pubkey(),
                         Yellow.paint("[ address ]:"),
-                        self.address()
+                        to_checksum_address(&self.address())
                     )
                 } else {
                     format!(
-                        concat!("{} 0x{}\n", "{} 0x{}\n", "{} 0x{:x}"),
+                        concat!("{} 0x{}\n", "{} 0x{}\n", "{} {}"),
                         "[ private ]:",
                         self.privkey(),
                         "[ public  ]:",
                         self.pubkey(),
                         "[ address ]:",
-                        self.address()
+                        to_checksum_address(&self.address())
                     )
                 };
                 Rc::new(content)
@@ -204,3 +443,80 @@ impl Printable for KeyPair {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{annotate_addresses, annotate_signers, format_timestamps, TimeFormat};
+    use cita_tool::Encryption;
+    use serde_json::json;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_annotate_addresses() {
+        let alice = "0xffffffffffffffffffffffffffffffffff020000";
+        let bob = "0x0000000000000000000000000000000000000001";
+        let hash = format!("0x{}", "ab".repeat(32));
+        let text = format!(
+            "{{\"from\": \"{}\", \"to\": \"{}\", \"hash\": \"{}\"}}",
+            alice, bob, hash
+        );
+        let mut names = BTreeMap::new();
+        names.insert(alice.to_owned(), "alice".to_owned());
+
+        let annotated = annotate_addresses(&text, &names, Some("test-chain"));
+        assert!(annotated.contains(&format!("{} (alice@test-chain)", alice)));
+        assert!(annotated.contains(&format!("{} (test-chain)", bob)));
+        assert!(annotated.contains(&format!("\"{}\"", hash)));
+
+        let annotated = annotate_addresses(&text, &names, None);
+        assert!(annotated.contains(&format!("{} (alice)", alice)));
+        assert!(annotated.contains(&format!("\"{}\"", bob)));
+    }
+
+    #[test]
+    fn test_format_timestamps() {
+        let mut block = json!({
+            "header": {"timestamp": 1_600_000_000_123u64, "number": "0x1"},
+            "logs": [{"timestamp": "0x174876e800"}],
+            "timestamp": "yesterday",
+        });
+        format_timestamps(&mut block, TimeFormat::Utc);
+        assert_eq!(block["header"]["timestamp"], "2020-09-13T12:26:40.123Z");
+        assert_eq!(block["header"]["number"], "0x1");
+        assert_eq!(block["logs"][0]["timestamp"], "1973-03-03T09:46:40.000Z");
+        assert_eq!(block["timestamp"], "yesterday");
+
+        let mut block = json!({"timestamp": 1_600_000_000_123u64});
+        format_timestamps(&mut block, TimeFormat::Unix);
+        assert_eq!(block["timestamp"], 1_600_000_000);
+    }
+
+    #[test]
+    fn test_annotate_signers() {
+        let alice = "0xffffffffffffffffffffffffffffffffff020000";
+        let mut names = BTreeMap::new();
+        names.insert(alice.to_owned(), "alice".to_owned());
+        let mut block = json!({"header": {"proof": {"Bft": {
+            "proposal": format!("0x{}", "ab".repeat(32)),
+            "height": 9,
+            "round": 2,
+            "commits": {
+                alice: format!("0x{}", "00".repeat(65)),
+                "0x0000000000000000000000000000000000000001": format!("0x{}", "00".repeat(65)),
+            },
+        }}}});
+        annotate_signers(&mut block, Encryption::Secp256k1, &names);
+
+        let signers = &block["header"]["proof"]["signers"];
+        assert_eq!(signers["height"], 9);
+        assert_eq!(signers["round"], 2);
+        let validators = signers["validators"].as_array().unwrap();
+        assert_eq!(validators.len(), 2);
+        let alice = validators
+            .iter()
+            .find(|validator| validator["address"] == alice)
+            .unwrap();
+        assert_eq!(alice["name"], "alice");
+        assert_eq!(alice["valid"], false);
+    }
+}
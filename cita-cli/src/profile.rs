@@ -0,0 +1,188 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use cita_tool::EncryptedData;
+
+use crate::cli::read_password;
+use crate::interactive::cita_cli_dir;
+use crate::key_agent;
+
+/// The master passphrase of the profile, instead of the agent or the prompt
+pub const ENV_PASSPHRASE: &str = "CITA_CLI_PASSPHRASE";
+
+/// The passphrase got at the startup, for the files read and written later
+static PASSPHRASE: Mutex<Option<String>> = Mutex::new(None);
+
+/// The profile: `~/.cita-cli/config`
+pub fn config_path() -> PathBuf {
+    cita_cli_dir().join("config")
+}
+
+/// The address book: `~/.cita-cli/address_book`
+pub fn address_book_path() -> PathBuf {
+    cita_cli_dir().join("address_book")
+}
+
+/// The files `profile encrypt` encrypts, the ones which may have the key references and the
+/// internal endpoints
+pub fn files() -> Vec<PathBuf> {
+    vec![config_path(), address_book_path()]
+}
+
+/// The encrypted content, the file is the `EncryptedData` as the encrypted key files are
+fn encrypted(content: &str) -> Option<EncryptedData> {
+    serde_json::from_str(content).ok()
+}
+
+pub fn is_encrypted(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| encrypted(&content))
+        .is_some()
+}
+
+/// The master passphrase, of `CITA_CLI_PASSPHRASE`, of the key agent unlocked by
+/// `profile unlock`, or prompted for, asked once for a process
+pub fn passphrase() -> Result<String, String> {
+    let mut cached = PASSPHRASE.lock().unwrap();
+    if let Some(ref passphrase) = *cached {
+        return Ok(passphrase.clone());
+    }
+    let passphrase = match std::env::var(ENV_PASSPHRASE) {
+        Ok(passphrase) => passphrase,
+        Err(_) => match key_agent::passphrase() {
+            Some(passphrase) => passphrase,
+            None => read_password("Master passphrase of the profile")?,
+        },
+    };
+    *cached = Some(passphrase.clone());
+    Ok(passphrase)
+}
+
+/// The passphrase got so far, if any
+pub fn cached_passphrase() -> Option<String> {
+    PASSPHRASE.lock().unwrap().clone()
+}
+
+/// Set the passphrase of the files written later
+pub fn set_passphrase(passphrase: Option<String>) {
+    *PASSPHRASE.lock().unwrap() = passphrase;
+}
+
+/// Decrypt the encrypted files of the profile once at the startup, so a wrong passphrase
+/// fails before anything reads them
+pub fn unlock() -> Result<(), String> {
+    for path in files().iter().filter(|path| is_encrypted(path)) {
+        if let Err(err) = read(path) {
+            set_passphrase(None);
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// The content of the file, decrypted if it's encrypted, None if there is no such file
+pub fn read(path: &Path) -> Result<Option<String>, String> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(_) => return Ok(None),
+    };
+    match encrypted(&content) {
+        Some(data) => {
+            let plain = data
+                .decrypt(&passphrase()?)
+                .map_err(|err| format!("Can't decrypt {}: {}", path.display(), err))?;
+            String::from_utf8(plain)
+                .map(Some)
+                .map_err(|err| format!("Can't decrypt {}: {}", path.display(), err))
+        }
+        None => Ok(Some(content)),
+    }
+}
+
+/// Write the content, encrypted if the file is
+pub fn write(path: &Path, content: &str) -> Result<(), String> {
+    let content = if is_encrypted(path) {
+        let data = EncryptedData::encrypt(content.as_bytes(), &passphrase()?);
+        serde_json::to_string_pretty(&data).unwrap()
+    } else {
+        content.to_owned()
+    };
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    }
+    fs::write(path, content).map_err(|err| format!("Can't write {}: {}", path.display(), err))
+}
+
+/// Encrypt the file with the passphrase, or decrypt it if None
+pub fn seal(path: &Path, passphrase: Option<&str>) -> Result<bool, String> {
+    let content = match read(path)? {
+        Some(content) => content,
+        None => return Ok(false),
+    };
+    let content = match passphrase {
+        Some(passphrase) => {
+            serde_json::to_string_pretty(&EncryptedData::encrypt(content.as_bytes(), passphrase))
+                .unwrap()
+        }
+        None => content,
+    };
+    fs::write(path, content).map_err(|err| format!("Can't write {}: {}", path.display(), err))?;
+    Ok(true)
+}
+
+/// The profile, null if there is none
+pub fn load_config() -> Value {
+    read(&config_path())
+        .ok()
+        .flatten()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Save the fields to the profile, the other fields are kept
+pub fn save_config(fields: Value) -> Result<(), String> {
+    let mut config = match load_config() {
+        Value::Object(config) => config,
+        _ => serde_json::Map::new(),
+    };
+    if let Value::Object(fields) = fields {
+        config.extend(fields);
+    }
+    write(
+        &config_path(),
+        &serde_json::to_string_pretty(&config).unwrap(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{read, seal, set_passphrase, write};
+
+    #[test]
+    fn test_encrypted_profile() {
+        let path = std::env::temp_dir().join(format!("cita-cli-profile-{}", std::process::id()));
+        let content = r#"{"url": "http://10.0.0.1:1337"}"#;
+        write(&path, content).unwrap();
+        assert_eq!(read(&path).unwrap().as_deref(), Some(content));
+
+        assert!(seal(&path, Some("secret")).unwrap());
+        let sealed = std::fs::read_to_string(&path).unwrap();
+        assert!(!sealed.contains("10.0.0.1"));
+        set_passphrase(Some("secret".to_owned()));
+        assert_eq!(read(&path).unwrap().as_deref(), Some(content));
+        // Written back encrypted
+        write(&path, r#"{"url": "http://10.0.0.2:1337"}"#).unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("10.0.0.2"));
+        set_passphrase(Some("wrong".to_owned()));
+        assert!(read(&path).is_err());
+
+        set_passphrase(Some("secret".to_owned()));
+        assert!(seal(&path, None).unwrap());
+        assert!(std::fs::read_to_string(&path).unwrap().contains("10.0.0.2"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
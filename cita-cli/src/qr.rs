@@ -0,0 +1,149 @@
+use qrcode::render::unicode::Dense1x2;
+use qrcode::{EcLevel, QrCode};
+
+use cita_tool::remove_0x;
+
+/// Hex chars carried by one QR code, more parts are rendered for longer payloads
+/// so every code stays small enough to scan from a terminal
+const PART_SIZE: usize = 600;
+
+/// What a QR payload carries
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum QrKind {
+    /// Protobuf `Transaction`, to be signed
+    Unsigned,
+    /// Protobuf `UnverifiedTransaction`, to be sent
+    Signed,
+}
+
+impl QrKind {
+    fn tag(self) -> &'static str {
+        match self {
+            QrKind::Unsigned => "TX",
+            QrKind::Signed => "SIGNEDTX",
+        }
+    }
+
+    /// The name in the json output
+    pub fn name(self) -> &'static str {
+        match self {
+            QrKind::Unsigned => "unsigned",
+            QrKind::Signed => "signed",
+        }
+    }
+}
+
+/// Split the hex into the texts of the QR codes, `CITA:<kind>:<part>/<parts>:<HEX>`
+///
+/// Only uppercase letters, digits and `:` `/` are used, so the codes are encoded in the
+/// alphanumeric mode, which holds much more than the byte mode.
+pub fn qr_parts(kind: QrKind, hex: &str) -> Vec<String> {
+    let hex = remove_0x(hex).to_uppercase();
+    let chunks: Vec<&str> = if hex.is_empty() {
+        vec![""]
+    } else {
+        hex.as_bytes()
+            .chunks(PART_SIZE)
+            .map(|chunk| std::str::from_utf8(chunk).unwrap())
+            .collect()
+    };
+    chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            format!(
+                "CITA:{}:{}/{}:{}",
+                kind.tag(),
+                index + 1,
+                chunks.len(),
+                chunk
+            )
+        })
+        .collect()
+}
+
+/// Render the text as a QR code of unicode half blocks, light on dark terminals work too
+pub fn render_qr(text: &str) -> Result<String, String> {
+    let code = QrCode::with_error_correction_level(text.as_bytes(), EcLevel::L)
+        .map_err(|err| format!("Can't encode the QR code: {}", err))?;
+    Ok(code
+        .render::<Dense1x2>()
+        .dark_color(Dense1x2::Light)
+        .light_color(Dense1x2::Dark)
+        .quiet_zone(true)
+        .build())
+}
+
+/// Join the scanned texts of the QR codes, in any order, back into the hex with 0x
+pub fn from_qr_parts<T: AsRef<str>>(texts: &[T]) -> Result<(QrKind, String), String> {
+    let mut kind = None;
+    let mut chunks: Vec<Option<String>> = Vec::new();
+    for text in texts {
+        let text = text.as_ref().trim();
+        let fields: Vec<&str> = text.splitn(4, ':').collect();
+        let parsed_kind = match fields.as_slice() {
+            ["CITA", "TX", _, _] => QrKind::Unsigned,
+            ["CITA", "SIGNEDTX", _, _] => QrKind::Signed,
+            _ => return Err(format!("Not a CITA transaction QR code: {}", text)),
+        };
+        if kind.get_or_insert(parsed_kind) != &parsed_kind {
+            return Err("The QR codes carry different transactions".to_string());
+        }
+        let (index, count) = match fields[2].split_once('/') {
+            Some((index, count)) => (
+                index.parse::<usize>().map_err(|err| err.to_string())?,
+                count.parse::<usize>().map_err(|err| err.to_string())?,
+            ),
+            None => return Err(format!("No part number in {}", text)),
+        };
+        if index == 0 || index > count || (!chunks.is_empty() && chunks.len() != count) {
+            return Err(format!("Invalid part {}/{}", index, count));
+        }
+        chunks.resize(count, None);
+        chunks[index - 1] = Some(fields[3].to_lowercase());
+    }
+
+    let kind = kind.ok_or_else(|| "No QR code text".to_string())?;
+    let missing: Vec<String> = chunks
+        .iter()
+        .enumerate()
+        .filter(|(_, chunk)| chunk.is_none())
+        .map(|(index, _)| (index + 1).to_string())
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!(
+            "Missing the parts {} of {}",
+            missing.join(", "),
+            chunks.len()
+        ));
+    }
+    let hex: String = chunks.into_iter().flatten().collect();
+    Ok((kind, format!("0x{}", hex)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_qr_parts, qr_parts, render_qr, QrKind, PART_SIZE};
+
+    #[test]
+    fn test_qr_parts() {
+        let hex = format!("0x{}", "0a1b".repeat(PART_SIZE / 2 + 10));
+        let mut parts = qr_parts(QrKind::Signed, &hex);
+        assert_eq!(parts.len(), 3);
+        assert!(parts[0].starts_with("CITA:SIGNEDTX:1/3:0A1B"));
+        parts.reverse();
+        assert_eq!(from_qr_parts(&parts), Ok((QrKind::Signed, hex)));
+        assert!(from_qr_parts(&parts[..2])
+            .unwrap_err()
+            .contains("Missing the parts 1"));
+        assert!(render_qr(&parts[0]).is_ok());
+
+        let unsigned = qr_parts(QrKind::Unsigned, "0x");
+        assert_eq!(unsigned, vec!["CITA:TX:1/1:".to_string()]);
+        assert_eq!(
+            from_qr_parts(&unsigned),
+            Ok((QrKind::Unsigned, "0x".to_string()))
+        );
+        assert!(from_qr_parts(&["CITA:TX:1/2:00", "CITA:SIGNEDTX:2/2:00"]).is_err());
+    }
+}
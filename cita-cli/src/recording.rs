@@ -0,0 +1,102 @@
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+
+/// A recording of the interactive shell, started by `.record <file>` and ended by `.stop`
+///
+/// The script has the commands that succeeded, it's replayed by `cita-cli < <file>`.
+/// The transcript `<file>.transcript` has all the commands and their outputs.
+pub struct Recording {
+    script_path: PathBuf,
+    transcript_path: PathBuf,
+    script: File,
+    transcript: File,
+    commands: usize,
+}
+
+impl Recording {
+    pub fn start(path: &Path) -> Result<Self, String> {
+        let transcript_path = PathBuf::from(format!("{}.transcript", path.display()));
+        let create = |path: &Path| {
+            File::create(path).map_err(|err| format!("Can't create {}: {}", path.display(), err))
+        };
+        let mut recording = Recording {
+            script_path: path.to_owned(),
+            script: create(path)?,
+            transcript: create(&transcript_path)?,
+            transcript_path,
+            commands: 0,
+        };
+        let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+        writeln!(
+            recording.script,
+            "# Recorded by cita-cli at {}, replay it by `cita-cli < {}`",
+            now,
+            path.display()
+        )
+        .map_err(|err| err.to_string())?;
+        writeln!(recording.transcript, "# Recorded by cita-cli at {}", now)
+            .map_err(|err| err.to_string())?;
+        Ok(recording)
+    }
+
+    /// The transcript, where the outputs are copied
+    pub fn transcript(&mut self) -> &mut File {
+        &mut self.transcript
+    }
+
+    /// A command about to run, to the transcript
+    pub fn command(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.transcript, "cita> {}", line.trim_end())
+    }
+
+    /// A command that succeeded, to the script
+    pub fn succeeded(&mut self, line: &str) -> io::Result<()> {
+        if !line.starts_with('#') {
+            self.commands += 1;
+        }
+        writeln!(self.script, "{}", line.trim_end())
+    }
+
+    /// End the recording
+    pub fn stop(mut self) -> Result<Value, String> {
+        self.script.flush().map_err(|err| err.to_string())?;
+        self.transcript.flush().map_err(|err| err.to_string())?;
+        Ok(json!({
+            "script": self.script_path.display().to_string(),
+            "transcript": self.transcript_path.display().to_string(),
+            "commands": self.commands,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Recording;
+    use std::io::Write;
+
+    #[test]
+    fn test_recording() {
+        let path = std::env::temp_dir().join(format!("cita-cli-session-{}", std::process::id()));
+        let mut recording = Recording::start(&path).unwrap();
+        recording.command("rpc blockNumber").unwrap();
+        writeln!(recording.transcript(), "0x64").unwrap();
+        recording.succeeded("rpc blockNumber").unwrap();
+        recording.command("rpc getBlock").unwrap();
+        let stopped = recording.stop().unwrap();
+        assert_eq!(stopped["commands"], 1);
+
+        let script = std::fs::read_to_string(&path).unwrap();
+        let lines = script.lines().collect::<Vec<_>>();
+        assert!(lines[0].starts_with('#'));
+        assert_eq!(&lines[1..], ["rpc blockNumber"]);
+        let transcript_path = format!("{}.transcript", path.display());
+        let transcript = std::fs::read_to_string(&transcript_path).unwrap();
+        assert!(transcript.ends_with("cita> rpc blockNumber\n0x64\ncita> rpc getBlock\n"));
+        std::fs::remove_file(&path).unwrap();
+        std::fs::remove_file(&transcript_path).unwrap();
+    }
+}
@@ -0,0 +1,161 @@
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use clap::ArgMatches;
+
+use crate::cli::confirm;
+use crate::profile;
+
+/// What a command can change, from a level on it's confirmed before running
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Risk {
+    /// Only queries
+    Read,
+    /// Sends the transactions of its own key
+    Write,
+    /// Sends the transactions of an admin key
+    Admin,
+    /// Changes the chain for everyone or can't be taken back by another command
+    Destructive,
+}
+
+/// The commands beyond what their keys tell
+const DESTRUCTIVE: [&str; 7] = [
+    "admin chain brake",
+    "admin chain set-version",
+    "amend code",
+    "amend set-h256",
+    "key rotate",
+    "scm NodeManager deleteNode",
+    "scm GroupManagement deleteGroup",
+];
+
+/// The args of the admin keys
+const ADMIN_KEYS: [&str; 2] = ["admin-private", "admin-private-key"];
+
+impl Risk {
+    /// The risk of the command, `m` is the matches of its last subcommand
+    pub fn of(command: &str, m: &ArgMatches) -> Self {
+        if DESTRUCTIVE.contains(&command) {
+            Risk::Destructive
+        } else if ADMIN_KEYS.iter().any(|key| m.is_present(key)) {
+            Risk::Admin
+        } else if m.is_present("private-key") {
+            Risk::Write
+        } else {
+            Risk::Read
+        }
+    }
+}
+
+impl FromStr for Risk {
+    type Err = String;
+
+    fn from_str(risk: &str) -> Result<Self, Self::Err> {
+        match risk {
+            "read" => Ok(Risk::Read),
+            "write" => Ok(Risk::Write),
+            "admin" => Ok(Risk::Admin),
+            "destructive" => Ok(Risk::Destructive),
+            _ => Err(format!(
+                "Unknown risk {}, it's read, write, admin or destructive",
+                risk
+            )),
+        }
+    }
+}
+
+/// The lowest risk confirmed, the `confirm` of `~/.cita-cli/config`: `admin` by default,
+/// `destructive`, or `none` for the automation, which never confirms
+fn confirmed_from() -> Result<Option<Risk>, String> {
+    match profile::load_config()["confirm"].as_str() {
+        None => Ok(Some(Risk::Admin)),
+        Some("none") => Ok(None),
+        Some(risk) => risk.parse().map(Some).map_err(|err| {
+            format!(
+                "`confirm` in {}: {} or none",
+                profile::config_path().display(),
+                err
+            )
+        }),
+    }
+}
+
+/// Abort unless `--yes` is given or the user agrees, an admin command is agreed by `y`,
+/// a destructive one by typing its name
+pub fn confirmed(m: &ArgMatches, command: &str, action: &str) -> Result<(), String> {
+    let risk = Risk::of(command, m);
+    if m.is_present("yes") || confirmed_from()?.is_none_or(|from| risk < from) {
+        return Ok(());
+    }
+    let agreed = match risk {
+        Risk::Destructive => {
+            let name = command.rsplit(' ').next().unwrap_or(command);
+            eprint!("{} It's destructive, type `{}` to continue: ", action, name);
+            io::stderr().flush().map_err(|err| err.to_string())?;
+            let mut answer = String::new();
+            io::stdin()
+                .read_line(&mut answer)
+                .map_err(|err| err.to_string())?;
+            answer.trim() == name
+        }
+        _ => confirm(&format!("{} Continue?", action))?,
+    };
+    if agreed {
+        Ok(())
+    } else {
+        Err("Aborted".to_string())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Risk;
+    use clap::{App, Arg, SubCommand};
+
+    #[test]
+    fn test_risk_of_command() {
+        let app = App::new("cita-cli").subcommand(
+            SubCommand::with_name("amend")
+                .subcommand(
+                    SubCommand::with_name("code").arg(
+                        Arg::with_name("admin-private-key")
+                            .long("admin-private-key")
+                            .takes_value(true),
+                    ),
+                )
+                .subcommand(
+                    SubCommand::with_name("abi")
+                        .arg(
+                            Arg::with_name("admin-private-key")
+                                .long("admin-private-key")
+                                .takes_value(true),
+                        )
+                        .arg(
+                            Arg::with_name("private-key")
+                                .long("private-key")
+                                .takes_value(true),
+                        ),
+                ),
+        );
+        let risk = |args: &[&str]| {
+            let matches = app.clone().get_matches_from(args);
+            let (_, amend) = matches.subcommand();
+            let (name, m) = amend.unwrap().subcommand();
+            Risk::of(&format!("amend {}", name), m.unwrap())
+        };
+        assert_eq!(risk(&["cita-cli", "amend", "code"]), Risk::Destructive);
+        assert_eq!(
+            risk(&["cita-cli", "amend", "abi", "--admin-private-key", "0x01"]),
+            Risk::Admin
+        );
+        assert_eq!(
+            risk(&["cita-cli", "amend", "abi", "--private-key", "0x01"]),
+            Risk::Write
+        );
+        assert_eq!(risk(&["cita-cli", "amend", "abi"]), Risk::Read);
+        assert!(Risk::Write < Risk::Admin);
+        assert_eq!("destructive".parse(), Ok(Risk::Destructive));
+        assert!("none".parse::<Risk>().is_err());
+    }
+}
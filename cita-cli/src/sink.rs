@@ -0,0 +1,392 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::thread;
+use std::time::Duration;
+
+use clap::{Arg, ArgMatches};
+use serde_json::Value;
+
+use cita_tool::client::basic::Client;
+
+use crate::cli::parse_u64;
+use crate::printer::Printer;
+
+/// How many times a record is POSTed before its delivery fails
+const HTTP_ATTEMPTS: u32 = 5;
+
+/// What the records are, the sinks of tables store them by the kind
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordKind {
+    /// A block of `export blocks` or `watch blocks`
+    Block,
+    /// A log of `export logs` or `watch events`
+    Log,
+    /// Anything else, such as a reorg of `watch blocks`
+    Other,
+}
+
+/// The destination of the exported or watched records
+pub trait Sink {
+    /// Deliver a record
+    fn write(&mut self, kind: RecordKind, record: &Value) -> Result<(), String>;
+
+    /// Deliver the records of a batch, such as a block range or a poll
+    fn write_all(&mut self, kind: RecordKind, records: &[Value]) -> Result<(), String> {
+        for record in records {
+            self.write(kind, record)?;
+        }
+        self.flush()
+    }
+
+    /// Make sure the written records are delivered
+    fn flush(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// `--output` and the rotation of its files
+pub fn sink_args() -> [Arg<'static, 'static>; 3] {
+    [
+        Arg::with_name("output")
+            .long("output")
+            .takes_value(true)
+            .help(
+                "Deliver the records to a file of one json per line, `sqlite:<path>`, \
+                 an `http(s)://` endpoint by POST, retried 5 times with a doubling wait, \
+                 or `kafka://<host>:<port>/<topic>`, print them when absent; it stops on a \
+                 record not delivered. sqlite and kafka need the features of the same names",
+            ),
+        Arg::with_name("rotate-size")
+            .long("rotate-size")
+            .takes_value(true)
+            .validator(|size| parse_u64(size.as_str()).map(|_| ()))
+            .help(
+                "Rotate the output file when it grows over the bytes, `<output>.1` is the last one",
+            ),
+        Arg::with_name("rotate-keep")
+            .long("rotate-keep")
+            .takes_value(true)
+            .default_value("5")
+            .validator(|keep| parse_u64(keep.as_str()).map(|_| ()))
+            .help("How many rotated files are kept"),
+    ]
+}
+
+/// The sink of `--output`
+pub fn open_sink<'a>(
+    m: &ArgMatches,
+    printer: &'a Printer,
+    is_color: bool,
+    client: &Client,
+) -> Result<Box<dyn Sink + 'a>, String> {
+    // `watch events --webhook` is kept for the scripts written before `--output`
+    let output = match m.value_of("output").or_else(|| m.value_of("webhook")) {
+        Some(output) if output != "-" => output,
+        _ => return Ok(Box::new(StdoutSink { printer, is_color })),
+    };
+    if output.starts_with("http://") || output.starts_with("https://") {
+        Ok(Box::new(HttpSink {
+            client: client.clone(),
+            url: output.to_owned(),
+            retry_delay: Duration::from_secs(1),
+        }))
+    } else if let Some(path) = output.strip_prefix("sqlite:") {
+        open_sqlite(path)
+    } else if let Some(address) = output.strip_prefix("kafka://") {
+        open_kafka(address)
+    } else {
+        let rotate_size = match m.value_of("rotate-size") {
+            Some(size) => Some(parse_u64(size)?),
+            None => None,
+        };
+        let keep = match m.value_of("rotate-keep") {
+            Some(keep) => parse_u64(keep)? as usize,
+            None => 0,
+        };
+        // A resumed scan continues the file of the interrupted one
+        let append = m.is_present("resume");
+        Ok(Box::new(FileSink::open(output, rotate_size, keep, append)?))
+    }
+}
+
+/// Print the records as the other results
+pub struct StdoutSink<'a> {
+    printer: &'a Printer,
+    is_color: bool,
+}
+
+impl<'a> Sink for StdoutSink<'a> {
+    fn write(&mut self, _kind: RecordKind, record: &Value) -> Result<(), String> {
+        self.printer.println(record, self.is_color);
+        Ok(())
+    }
+}
+
+/// Append one json per line to a file, rotated by size
+pub struct FileSink {
+    path: String,
+    writer: BufWriter<File>,
+    written: u64,
+    rotate_size: Option<u64>,
+    keep: usize,
+}
+
+impl FileSink {
+    /// Create or append to the file, rotate it to `<path>.1`, `<path>.2` ... when it grows
+    /// over the size, the rotated files over `keep` are removed
+    pub fn open(
+        path: &str,
+        rotate_size: Option<u64>,
+        keep: usize,
+        append: bool,
+    ) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .map_err(|err| format!("{}", err))?;
+        let written = file.metadata().map(|meta| meta.len()).unwrap_or_default();
+        Ok(FileSink {
+            path: path.to_owned(),
+            writer: BufWriter::new(file),
+            written,
+            rotate_size,
+            keep,
+        })
+    }
+
+    fn rotate(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|err| format!("{}", err))?;
+        if self.keep == 0 {
+            let _ = fs::remove_file(&self.path);
+        } else {
+            let _ = fs::remove_file(format!("{}.{}", self.path, self.keep));
+            for index in (1..self.keep).rev() {
+                let _ = fs::rename(
+                    format!("{}.{}", self.path, index),
+                    format!("{}.{}", self.path, index + 1),
+                );
+            }
+            fs::rename(&self.path, format!("{}.1", self.path)).map_err(|err| format!("{}", err))?;
+        }
+        let file = File::create(&self.path).map_err(|err| format!("{}", err))?;
+        self.writer = BufWriter::new(file);
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Sink for FileSink {
+    fn write(&mut self, _kind: RecordKind, record: &Value) -> Result<(), String> {
+        let line = format!("{}\n", record);
+        if let Some(size) = self.rotate_size {
+            if self.written > 0 && self.written + line.len() as u64 > size {
+                self.rotate()?;
+            }
+        }
+        self.writer
+            .write_all(line.as_bytes())
+            .map_err(|err| format!("{}", err))?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), String> {
+        self.writer.flush().map_err(|err| format!("{}", err))
+    }
+}
+
+/// POST each record as json to an endpoint, retried while the endpoint is unreachable,
+/// overloaded or failing
+pub struct HttpSink {
+    client: Client,
+    url: String,
+    /// The wait after the first failed POST, doubled after every other one
+    retry_delay: Duration,
+}
+
+impl Sink for HttpSink {
+    fn write(&mut self, _kind: RecordKind, record: &Value) -> Result<(), String> {
+        let mut delay = self.retry_delay;
+        let mut attempt = 1;
+        loop {
+            let failure = match self.client.post_json(&self.url, record) {
+                Ok(status) if status < 300 => return Ok(()),
+                // The record is refused, it would be refused again
+                Ok(status) if status < 500 && status != 429 => {
+                    return Err(format!("{} responded with status {}", self.url, status))
+                }
+                Ok(status) => format!("{} responded with status {}", self.url, status),
+                Err(err) => format!("Delivery to {} failed: {}", self.url, err),
+            };
+            if attempt == HTTP_ATTEMPTS {
+                return Err(format!("{}, gave up after {} attempts", failure, attempt));
+            }
+            thread::sleep(delay);
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+}
+
+#[cfg(feature = "sqlite")]
+fn open_sqlite<'a>(path: &str) -> Result<Box<dyn Sink + 'a>, String> {
+    Ok(Box::new(crate::sqlite::SqliteSink::open(path)?))
+}
+
+#[cfg(not(feature = "sqlite"))]
+fn open_sqlite<'a>(_path: &str) -> Result<Box<dyn Sink + 'a>, String> {
+    Err(
+        "cita-cli is built without the sqlite feature, rebuild it by `--features sqlite`"
+            .to_string(),
+    )
+}
+
+/// Produce each record as json to a topic of Kafka
+#[cfg(feature = "kafka")]
+pub struct KafkaSink {
+    producer: kafka::producer::Producer,
+    topic: String,
+}
+
+#[cfg(feature = "kafka")]
+impl Sink for KafkaSink {
+    fn write(&mut self, kind: RecordKind, record: &Value) -> Result<(), String> {
+        self.write_all(kind, std::slice::from_ref(record))
+    }
+
+    fn write_all(&mut self, _kind: RecordKind, records: &[Value]) -> Result<(), String> {
+        use kafka::producer::Record;
+
+        let topic = self.topic.as_str();
+        let records = records
+            .iter()
+            .map(|record| Record::from_value(topic, record.to_string()))
+            .collect::<Vec<_>>();
+        let confirms = self
+            .producer
+            .send_all(&records)
+            .map_err(|err| format!("Kafka error: {}", err))?;
+        for confirm in confirms {
+            for partition in confirm.partition_confirms {
+                if let Err(code) = partition.offset {
+                    return Err(format!(
+                        "Kafka rejected the records of partition {}: {:?}",
+                        partition.partition, code
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "kafka")]
+fn open_kafka<'a>(address: &str) -> Result<Box<dyn Sink + 'a>, String> {
+    use kafka::producer::{Producer, RequiredAcks};
+    use std::time::Duration;
+
+    let (host, topic) = match address.find('/') {
+        Some(index) if index + 1 < address.len() => (&address[..index], &address[index + 1..]),
+        _ => return Err("The Kafka output is kafka://<host>:<port>/<topic>".to_string()),
+    };
+    let producer = Producer::from_hosts(vec![host.to_owned()])
+        .with_ack_timeout(Duration::from_secs(3))
+        .with_required_acks(RequiredAcks::One)
+        .create()
+        .map_err(|err| format!("Kafka error: {}", err))?;
+    Ok(Box::new(KafkaSink {
+        producer,
+        topic: topic.to_owned(),
+    }))
+}
+
+#[cfg(not(feature = "kafka"))]
+fn open_kafka<'a>(_address: &str) -> Result<Box<dyn Sink + 'a>, String> {
+    Err("cita-cli is built without the kafka feature, rebuild it by `--features kafka`".to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FileSink, HttpSink, RecordKind, Sink};
+    use cita_tool::client::basic::Client;
+    use serde_json::json;
+    use std::fs;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    /// An endpoint answering the POSTs by the statuses, one connection each
+    fn endpoint(statuses: Vec<u16>) -> (String, thread::JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let handle = thread::spawn(move || {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request).unwrap();
+                write!(
+                    stream,
+                    "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                )
+                .unwrap();
+            }
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_http_sink_retry() {
+        let http_sink = |url: String| HttpSink {
+            client: Client::new(),
+            url,
+            retry_delay: Duration::from_millis(10),
+        };
+        let record = json!({ "height": 10 });
+
+        let (url, server) = endpoint(vec![503, 429, 200]);
+        assert!(http_sink(url).write(RecordKind::Log, &record).is_ok());
+        server.join().unwrap();
+
+        let (url, server) = endpoint(vec![400]);
+        assert!(http_sink(url).write(RecordKind::Log, &record).is_err());
+        server.join().unwrap();
+
+        let (url, server) = endpoint(vec![500; 5]);
+        let err = http_sink(url).write(RecordKind::Log, &record).unwrap_err();
+        assert!(err.contains("gave up after 5 attempts"), "{}", err);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_file_sink_rotation() {
+        let dir = std::env::temp_dir().join(format!("cita-cli-sink-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.jsonl").to_string_lossy().into_owned();
+
+        // Every record is 14 bytes with the newline, two records fit in a file
+        let mut sink = FileSink::open(&path, Some(28), 2, false).unwrap();
+        let records = (1..8)
+            .map(|index| json!({ "height": index * 10 }))
+            .collect::<Vec<_>>();
+        sink.write_all(RecordKind::Block, &records[..1]).unwrap();
+        sink.write_all(RecordKind::Block, &records[1..]).unwrap();
+
+        let read = |path: String| fs::read_to_string(path).unwrap_or_default();
+        assert_eq!(read(path.clone()), "{\"height\":70}\n");
+        assert_eq!(
+            read(format!("{}.1", path)),
+            "{\"height\":50}\n{\"height\":60}\n"
+        );
+        assert_eq!(
+            read(format!("{}.2", path)),
+            "{\"height\":30}\n{\"height\":40}\n"
+        );
+        assert!(!dir.join("events.jsonl.3").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,207 @@
+use rusqlite::{params, Connection};
+use serde_json::Value;
+
+use cita_tool::Quantity;
+
+use crate::sink::{RecordKind, Sink};
+
+/// The tables of `export --output sqlite:<path>`, created when they don't exist
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS blocks (
+    height INTEGER PRIMARY KEY,
+    hash TEXT NOT NULL,
+    timestamp INTEGER,
+    proposer TEXT,
+    quota_used TEXT,
+    transactions INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS txs (
+    hash TEXT PRIMARY KEY,
+    block_height INTEGER NOT NULL,
+    tx_index INTEGER NOT NULL,
+    sender TEXT,
+    content TEXT
+);
+CREATE TABLE IF NOT EXISTS logs (
+    block_height INTEGER NOT NULL,
+    log_index INTEGER NOT NULL,
+    tx_hash TEXT,
+    tx_index INTEGER,
+    address TEXT NOT NULL,
+    topics TEXT NOT NULL,
+    data TEXT NOT NULL,
+    event TEXT,
+    params TEXT,
+    PRIMARY KEY (block_height, log_index)
+);
+CREATE INDEX IF NOT EXISTS logs_address ON logs (address);
+CREATE INDEX IF NOT EXISTS logs_event ON logs (event);
+";
+
+/// Write the exported blocks, transactions and logs into a SQLite database,
+/// exporting a range again replaces its rows
+pub struct SqliteSink {
+    connection: Connection,
+}
+
+impl SqliteSink {
+    /// Open or create the database and its tables
+    pub fn open(path: &str) -> Result<Self, String> {
+        let connection = Connection::open(path).map_err(sqlite_error)?;
+        connection.execute_batch(SCHEMA).map_err(sqlite_error)?;
+        Ok(SqliteSink { connection })
+    }
+
+    /// Insert the blocks of `export blocks` into `blocks` and their transactions into `txs`
+    pub fn write_blocks(&mut self, blocks: &[Value]) -> Result<(), String> {
+        let tx = self.connection.transaction().map_err(sqlite_error)?;
+        for block in blocks {
+            let height = number(&block["height"]);
+            let transactions = block["transactions"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default();
+            tx.execute(
+                "INSERT OR REPLACE INTO blocks VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    height,
+                    block["hash"].as_str().unwrap_or_default(),
+                    number(&block["timestamp"]),
+                    text(&block["proposer"]),
+                    text(&block["quotaUsed"]),
+                    transactions.len() as i64,
+                ],
+            )
+            .map_err(sqlite_error)?;
+            for (index, transaction) in transactions.iter().enumerate() {
+                tx.execute(
+                    "INSERT OR REPLACE INTO txs VALUES (?1, ?2, ?3, ?4, ?5)",
+                    params![
+                        text(&transaction["hash"]),
+                        height,
+                        index as i64,
+                        text(&transaction["from"]),
+                        text(&transaction["content"]),
+                    ],
+                )
+                .map_err(sqlite_error)?;
+            }
+        }
+        tx.commit().map_err(sqlite_error)
+    }
+
+    /// Insert the logs of `getLogs` into `logs`, with the event name and the params
+    /// in json when the log is decoded
+    pub fn write_logs(&mut self, logs: &[Value]) -> Result<(), String> {
+        let tx = self.connection.transaction().map_err(sqlite_error)?;
+        for log in logs {
+            let decoded = &log["decoded"];
+            tx.execute(
+                "INSERT OR REPLACE INTO logs VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    number(&log["blockNumber"]),
+                    number(&log["logIndex"]),
+                    text(&log["transactionHash"]),
+                    number(&log["transactionIndex"]),
+                    log["address"].as_str().unwrap_or_default().to_lowercase(),
+                    log["topics"].to_string(),
+                    log["data"].as_str().unwrap_or("0x"),
+                    text(&decoded["event"]),
+                    if decoded["params"].is_null() {
+                        None
+                    } else {
+                        Some(decoded["params"].to_string())
+                    },
+                ],
+            )
+            .map_err(sqlite_error)?;
+        }
+        tx.commit().map_err(sqlite_error)
+    }
+}
+
+impl Sink for SqliteSink {
+    fn write(&mut self, kind: RecordKind, record: &Value) -> Result<(), String> {
+        self.write_all(kind, std::slice::from_ref(record))
+    }
+
+    /// The blocks and the logs of a batch are inserted in a transaction,
+    /// the other records aren't stored
+    fn write_all(&mut self, kind: RecordKind, records: &[Value]) -> Result<(), String> {
+        match kind {
+            RecordKind::Block => self.write_blocks(records),
+            RecordKind::Log => self.write_logs(records),
+            RecordKind::Other => Ok(()),
+        }
+    }
+}
+
+fn number(value: &Value) -> Option<i64> {
+    Quantity::from_json(value)
+        .and_then(|quantity| quantity.as_u64().ok())
+        .map(|number| number as i64)
+}
+
+fn text(value: &Value) -> Option<String> {
+    value.as_str().map(ToOwned::to_owned)
+}
+
+fn sqlite_error(err: rusqlite::Error) -> String {
+    format!("SQLite error: {}", err)
+}
+
+#[cfg(test)]
+mod test {
+    use super::SqliteSink;
+    use serde_json::json;
+
+    #[test]
+    fn test_sqlite_sink() {
+        let mut sink = SqliteSink::open(":memory:").unwrap();
+        let block = json!({
+            "height": 10,
+            "hash": "0xab",
+            "timestamp": 1_600_000_000_000u64,
+            "quotaUsed": "0x5208",
+            "transactions": [{"hash": "0x01", "from": "0xcd", "content": "0x0a"}],
+        });
+        // Exporting the block again replaces it
+        sink.write_blocks(&[block.clone(), block]).unwrap();
+        sink.write_logs(&[json!({
+            "address": "0xEF",
+            "topics": ["0x02"],
+            "data": "0x",
+            "blockNumber": "0xa",
+            "logIndex": "0x0",
+            "transactionHash": "0x01",
+            "transactionIndex": "0x0",
+            "decoded": {"event": "Transfer", "params": [1]},
+        })])
+        .unwrap();
+
+        let connection = &sink.connection;
+        let (height, timestamp, count): (i64, i64, i64) = connection
+            .query_row(
+                "SELECT height, timestamp, (SELECT COUNT(*) FROM blocks) FROM blocks",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((height, timestamp, count), (10, 1_600_000_000_000, 1));
+        let sender: String = connection
+            .query_row(
+                "SELECT sender FROM txs WHERE block_height = 10",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(sender, "0xcd");
+        let (address, event, params): (String, String, String) = connection
+            .query_row("SELECT address, event, params FROM logs", [], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+            })
+            .unwrap();
+        assert_eq!((address.as_str(), event.as_str()), ("0xef", "Transfer"));
+        assert_eq!(params, "[1]");
+    }
+}
@@ -0,0 +1,181 @@
+//! The commands against a real node: a devnet run by docker.
+//!
+//! `cargo test --features e2e --test e2e`, `CITA_E2E_PORT` and `CITA_E2E_IMAGE` choose the port
+//! and the image of the node.
+#![cfg(feature = "e2e")]
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// `SimpleStorage` of solc 0.4.24, `set(uint256)` and `get()`
+const STORAGE_CODE: &str = "0x608060405234801561001057600080fd5b5060df8061001f6000396000f3006080\
+    604052600436106049576000357c0100000000000000000000000000000000000000000000000000000000900463\
+    ffffffff16806360fe47b114604e5780636d4ce63c146078575b600080fd5b348015605957600080fd5b50607660\
+    04803603810190808035906020019092919050505060a0565b005b348015608357600080fd5b50608a60aa565b60\
+    40518082815260200191505060405180910390f35b8060008190555050565b600080549050905600a165627a7a72\
+    30582099c66a25d59f0aa78f7ebc40748fa1d1fbc335d8d780f284841b30e0365acd960029";
+const SET: &str = "0x60fe47b1";
+const GET: &str = "0x6d4ce63c";
+
+/// The devnet of the test, brought down when dropped
+struct Devnet {
+    home: PathBuf,
+    url: String,
+    private_key: String,
+    address: String,
+}
+
+impl Devnet {
+    fn up() -> Self {
+        let home = env::temp_dir().join(format!("cita-e2e-{}", std::process::id()));
+        let port = env::var("CITA_E2E_PORT").unwrap_or_else(|_| "11337".to_string());
+        let mut args = vec!["devnet", "up", "--port", &port, "--name", "cita-e2e"];
+        let image = env::var("CITA_E2E_IMAGE");
+        if let Ok(ref image) = image {
+            args.extend_from_slice(&["--image", image]);
+        }
+        let profile = cli(&home, &args).unwrap_or_else(|err| panic!("devnet up: {}", err));
+        Devnet {
+            home,
+            url: profile["url"].as_str().unwrap().to_owned(),
+            private_key: profile["private_key"].as_str().unwrap().to_owned(),
+            address: profile["address"].as_str().unwrap().to_owned(),
+        }
+    }
+
+    /// Run the command against the node, the printed JSON
+    fn run(&self, args: &[&str]) -> Value {
+        let mut args = args.to_vec();
+        args.extend_from_slice(&["--url", &self.url]);
+        cli(&self.home, &args).unwrap_or_else(|err| panic!("{}: {}", args.join(" "), err))
+    }
+
+    /// The result of the JSON-RPC response of the command
+    fn result(&self, args: &[&str]) -> Value {
+        let response = self.run(args);
+        assert!(
+            response["error"].is_null(),
+            "{}: {}",
+            args.join(" "),
+            response
+        );
+        response["result"].clone()
+    }
+
+    /// Send the transaction, the receipt once it's in a block
+    fn send(&self, args: &[&str]) -> Value {
+        let hash = self.result(args)["hash"].as_str().unwrap().to_owned();
+        let receipt = self.result(&["tx", "wait-receipt", "--hash", &hash, "--timeout", "60"]);
+        assert!(receipt["errorMessage"].is_null(), "{}", receipt);
+        receipt
+    }
+}
+
+impl Drop for Devnet {
+    fn drop(&mut self) {
+        let _ = cli(&self.home, &["devnet", "down"]);
+        let _ = std::fs::remove_dir_all(&self.home);
+    }
+}
+
+/// Run cita-cli with the home, the JSON it prints or its error output
+fn cli(home: &Path, args: &[&str]) -> Result<Value, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_cita-cli"))
+        .args(args)
+        .arg("--no-color")
+        .env("HOME", home)
+        .env_remove("JSONRPC_URL")
+        .output()
+        .map_err(|err| err.to_string())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!(
+            "{}{}",
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    serde_json::from_str(&stdout).map_err(|err| format!("{}: {}", err, stdout))
+}
+
+/// The unsigned integer of the hex, a quantity or 32 bytes
+fn word(value: &Value) -> u64 {
+    let hex = value.as_str().unwrap().trim_start_matches("0x");
+    u64::from_str_radix(&hex[hex.len().saturating_sub(16)..], 16).unwrap()
+}
+
+#[test]
+fn test_command_matrix() {
+    let devnet = Devnet::up();
+    let key = devnet.private_key.as_str();
+
+    // Read the chain
+    assert!(word(&devnet.result(&["rpc", "blockNumber"])) > 0);
+    assert_eq!(
+        devnet.result(&["rpc", "getMetaData"])["chainName"],
+        "devnet"
+    );
+
+    // Deploy and call a contract
+    let receipt = devnet.send(&[
+        "tx",
+        "create-contract",
+        "--code",
+        STORAGE_CODE,
+        "--private-key",
+        key,
+        "--quota",
+        "1000000",
+    ]);
+    let contract = receipt["contractAddress"].as_str().unwrap().to_owned();
+    let get = ["rpc", "call", "--to", &contract, "--data", GET];
+    assert_eq!(word(&devnet.result(&get)), 0);
+    let set = format!("{}{:064x}", SET, 42);
+    devnet.send(&[
+        "tx",
+        "call-contract",
+        "--address",
+        &contract,
+        "--data",
+        &set,
+        "--private-key",
+        key,
+    ]);
+    assert_eq!(word(&devnet.result(&get)), 42);
+
+    // Admin operations by the super admin
+    let is_admin = ["scm", "AdminManagement", "isAdmin", "--address"];
+    assert_eq!(
+        word(&devnet.result(&[&is_admin[..], &[devnet.address.as_str()]].concat())),
+        1
+    );
+    devnet.send(&[
+        "scm",
+        "QuotaManager",
+        "setBQL",
+        "--quota-limit",
+        "1073741825",
+        "--admin-private",
+        key,
+    ]);
+    assert_eq!(
+        word(&devnet.result(&["scm", "QuotaManager", "getBQL"])),
+        1_073_741_825
+    );
+
+    // Filters
+    let id = devnet.result(&["rpc", "newBlockFilter"]);
+    let id = id.as_str().unwrap();
+    thread::sleep(Duration::from_secs(6));
+    let changes = devnet.result(&["rpc", "getFilterChanges", "--id", id]);
+    assert!(!changes.as_array().unwrap().is_empty());
+    assert_eq!(
+        devnet.result(&["rpc", "uninstallFilter", "--id", id]),
+        Value::Bool(true)
+    );
+}
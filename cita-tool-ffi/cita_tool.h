@@ -0,0 +1,38 @@
+/*
+ * C bindings of cita-tool.
+ *
+ * All the strings are NUL terminated UTF-8. The returned strings are owned by the
+ * caller and must be released by cita_string_free. On failure NULL is returned and
+ * cita_last_error tells why.
+ */
+
+#ifndef CITA_TOOL_H
+#define CITA_TOOL_H
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Generate a key pair of "secp256k1", "ed25519" or "sm2", returns
+ * {"private": "0x..", "public": "0x..", "address": "0x.."} */
+char *cita_key_generate(const char *encryption);
+
+/* Build and sign a transaction described by a JSON object with to, data, value, quota,
+ * currentHeight, chainId and version, returns the hex of the UnverifiedTransaction */
+char *cita_transaction_sign(const char *transaction, const char *private_key,
+                            const char *encryption);
+
+/* Encode the call of the function with a JSON list of arguments by the ABI */
+char *cita_abi_encode(const char *abi, const char *function, const char *arguments);
+
+/* The error of the last failed call in this thread, NULL if none, owned by the library */
+const char *cita_last_error(void);
+
+/* Release a string returned by the library */
+void cita_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* CITA_TOOL_H */
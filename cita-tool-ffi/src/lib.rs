@@ -0,0 +1,267 @@
+//! C bindings of cita-tool
+//!
+//! Keys, transactions and ABI encoding for the backends which aren't written in Rust.
+//! See `cita_tool.h` for the C declarations.
+//!
+//! All the strings are NUL terminated UTF-8. The returned strings are owned by the caller
+//! and must be released by `cita_string_free`. On failure `NULL` is returned and
+//! `cita_last_error` tells why.
+
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+#[macro_use]
+extern crate serde_derive;
+
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::str::FromStr;
+
+use cita_tool::{
+    encode, encode_input, hex_to_u256, remove_0x, Encryption, KeyPair, LowerHex, PrivateKey,
+    ProtoMessage, TransactionOptions, U256,
+};
+use serde_json::json;
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The transaction to sign, as the JSON argument of `cita_transaction_sign`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionJson {
+    #[serde(default)]
+    to: String,
+    #[serde(default)]
+    data: String,
+    #[serde(default)]
+    value: Option<String>,
+    #[serde(default)]
+    quota: Option<u64>,
+    current_height: u64,
+    chain_id: String,
+    #[serde(default)]
+    version: u32,
+}
+
+fn set_last_error(err: String) {
+    LAST_ERROR.with(|last| *last.borrow_mut() = CString::new(err).ok());
+}
+
+/// Read a string argument, `NULL` is an error
+unsafe fn argument<'a>(name: &str, value: *const c_char) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| format!("{} is not UTF-8", name))
+}
+
+/// Hand the result to the caller, or keep the error and return `NULL`
+fn output(result: Result<String, String>) -> *mut c_char {
+    match result.and_then(|value| CString::new(value).map_err(|err| err.to_string())) {
+        Ok(value) => value.into_raw(),
+        Err(err) => {
+            set_last_error(err);
+            ptr::null_mut()
+        }
+    }
+}
+
+fn parse_u256(value: &str) -> Result<U256, String> {
+    if value.starts_with("0x") || value.starts_with("0X") {
+        hex_to_u256(value).map_err(|err| err.to_string())
+    } else {
+        U256::from_dec_str(value).map_err(|err| format!("{:?}", err))
+    }
+}
+
+fn generate_key(encryption: &str) -> Result<String, String> {
+    let key_pair = KeyPair::new(Encryption::from_str(encryption)?);
+    Ok(json!({
+        "private": format!("0x{}", key_pair.privkey()),
+        "public": format!("0x{}", key_pair.pubkey()),
+        "address": key_pair.address().completed_lower_hex_with_0x(),
+    })
+    .to_string())
+}
+
+fn sign_transaction(
+    transaction: &str,
+    private_key: &str,
+    encryption: &str,
+) -> Result<String, String> {
+    let options: TransactionJson =
+        serde_json::from_str(transaction).map_err(|err| err.to_string())?;
+    let private_key =
+        PrivateKey::from_str(remove_0x(private_key), Encryption::from_str(encryption)?)?;
+
+    let value = match options.value {
+        Some(ref value) => Some(parse_u256(value)?),
+        None => None,
+    };
+    let tx = TransactionOptions::new()
+        .set_code(&options.data)
+        .set_address(&options.to)
+        .set_current_height(Some(options.current_height))
+        .set_quota(options.quota)
+        .set_value(value)
+        .set_version(Some(options.version))
+        .build(parse_u256(&options.chain_id)?)
+        .map_err(|err| err.to_string())?;
+
+    let signed = tx
+        .build_unverified(private_key)
+        .write_to_bytes()
+        .map_err(|err| err.to_string())?;
+    Ok(format!("0x{}", encode(signed)))
+}
+
+fn abi_encode(abi: &str, function: &str, arguments: &str) -> Result<String, String> {
+    let arguments: Vec<String> = serde_json::from_str(arguments).map_err(|err| err.to_string())?;
+    encode_input(None, Some(abi), function, &arguments, true, false)
+        .map(|data| format!("0x{}", data))
+        .map_err(|err| err.to_string())
+}
+
+/// Generate a key pair of the algorithm: `secp256k1`, `ed25519` or `sm2`
+///
+/// Returns `{"private": "0x..", "public": "0x..", "address": "0x.."}`.
+///
+/// # Safety
+///
+/// `encryption` must be a valid C string.
+#[no_mangle]
+pub unsafe extern "C" fn cita_key_generate(encryption: *const c_char) -> *mut c_char {
+    output(argument("encryption", encryption).and_then(generate_key))
+}
+
+/// Build and sign a transaction, returns the hex of the `UnverifiedTransaction`
+///
+/// `transaction` is a JSON object: `to`, `data`, `value` (hex or decimal), `quota`,
+/// `currentHeight`, `chainId` (hex or decimal) and `version`. The transaction is valid
+/// until 88 blocks after the current height.
+///
+/// # Safety
+///
+/// The arguments must be valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn cita_transaction_sign(
+    transaction: *const c_char,
+    private_key: *const c_char,
+    encryption: *const c_char,
+) -> *mut c_char {
+    output(
+        argument("transaction", transaction).and_then(|transaction| {
+            sign_transaction(
+                transaction,
+                argument("private_key", private_key)?,
+                argument("encryption", encryption)?,
+            )
+        }),
+    )
+}
+
+/// Encode the call of the function with the JSON list of arguments, by the ABI
+///
+/// The unsigned and signed integers of the arguments are decimal.
+///
+/// # Safety
+///
+/// The arguments must be valid C strings.
+#[no_mangle]
+pub unsafe extern "C" fn cita_abi_encode(
+    abi: *const c_char,
+    function: *const c_char,
+    arguments: *const c_char,
+) -> *mut c_char {
+    output(argument("abi", abi).and_then(|abi| {
+        abi_encode(
+            abi,
+            argument("function", function)?,
+            argument("arguments", arguments)?,
+        )
+    }))
+}
+
+/// The error of the last failed call in this thread, `NULL` if none
+///
+/// The string is owned by the library and valid until the next failed call in this thread.
+#[no_mangle]
+pub extern "C" fn cita_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |err| err.as_ptr())
+    })
+}
+
+/// Release a string returned by the library
+///
+/// # Safety
+///
+/// `value` must be returned by this library and not released yet, or `NULL`.
+#[no_mangle]
+pub unsafe extern "C" fn cita_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::{CStr, CString};
+    use std::str::FromStr;
+
+    use cita_tool::{pubkey_to_address, Encryption, LowerHex, UnverifiedTransaction};
+    use serde_json::Value;
+
+    use super::{cita_key_generate, cita_last_error, cita_string_free, cita_transaction_sign};
+
+    unsafe fn take(value: *mut std::os::raw::c_char) -> String {
+        assert!(!value.is_null());
+        let result = CStr::from_ptr(value).to_str().unwrap().to_owned();
+        cita_string_free(value);
+        result
+    }
+
+    #[test]
+    fn test_sign_transaction() {
+        unsafe {
+            let encryption = CString::new("secp256k1").unwrap();
+            let key: Value =
+                serde_json::from_str(&take(cita_key_generate(encryption.as_ptr()))).unwrap();
+
+            let transaction = CString::new(
+                r#"{"to": "0xffffffffffffffffffffffffffffffffff020001", "value": "100",
+                    "currentHeight": 0, "chainId": "1", "version": 2}"#,
+            )
+            .unwrap();
+            let private_key = CString::new(key["private"].as_str().unwrap()).unwrap();
+            let signed = take(cita_transaction_sign(
+                transaction.as_ptr(),
+                private_key.as_ptr(),
+                encryption.as_ptr(),
+            ));
+            let sender = UnverifiedTransaction::from_str(&signed)
+                .unwrap()
+                .public_key(Encryption::Secp256k1)
+                .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+                .unwrap();
+            assert_eq!(sender, key["address"]);
+
+            let invalid = CString::new("{}").unwrap();
+            assert!(cita_transaction_sign(
+                invalid.as_ptr(),
+                private_key.as_ptr(),
+                encryption.as_ptr()
+            )
+            .is_null());
+            assert!(!cita_last_error().is_null());
+        }
+    }
+}
@@ -0,0 +1,336 @@
+//! Python bindings of cita-tool
+//!
+//! Build and install with `maturin develop` in this directory, then:
+//!
+//! ```python
+//! import cita_tool
+//!
+//! client = cita_tool.Client("http://127.0.0.1:1337")
+//! client.block_number()
+//! client.set_private_key("0x...")
+//! client.send_transaction(to="0x...", data=cita_tool.encode_input(abi, "set", ["1"]))
+//! ```
+//!
+//! The jsonrpc results are returned as Python objects, the jsonrpc errors raise
+//! `RuntimeError` and the invalid arguments raise `ValueError`.
+
+#![deny(warnings)]
+#![deny(missing_docs)]
+// The conversions are generated by the pyo3 macros
+#![allow(clippy::useless_conversion)]
+
+use std::str::FromStr;
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+
+use cita_tool::client::basic::{Client, ClientExt};
+use cita_tool::{
+    decode_input, decode_logs, encode, encode_input, hex_to_u256, parse_url, remove_0x, Encryption,
+    JsonRpcResponse, KeyPair, LowerHex, PrivateKey, ProtoMessage, SelectorTable, ToolError,
+    TransactionOptions, U256,
+};
+
+fn value_error<E: ToString>(err: E) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+fn runtime_error<E: ToString>(err: E) -> PyErr {
+    PyRuntimeError::new_err(err.to_string())
+}
+
+/// Load JSON into Python objects
+fn json_loads(py: Python<'_>, json: &str) -> PyResult<PyObject> {
+    Ok(py
+        .import_bound("json")?
+        .call_method1("loads", (json,))?
+        .unbind())
+}
+
+/// The result of the response, or raise its error
+fn response(py: Python<'_>, response: Result<JsonRpcResponse, ToolError>) -> PyResult<PyObject> {
+    let response = response.map_err(runtime_error)?;
+    if let Some(err) = response.error() {
+        return Err(PyRuntimeError::new_err(err.message()));
+    }
+    json_loads(
+        py,
+        &serde_json::to_string(&response.result()).map_err(runtime_error)?,
+    )
+}
+
+fn parse_u256(value: &str) -> PyResult<U256> {
+    if value.starts_with("0x") || value.starts_with("0X") {
+        hex_to_u256(value).map_err(value_error)
+    } else {
+        U256::from_dec_str(value).map_err(|err| value_error(format!("{:?}", err)))
+    }
+}
+
+fn parse_privkey(private_key: &str, encryption: &str) -> PyResult<PrivateKey> {
+    PrivateKey::from_str(
+        remove_0x(private_key),
+        Encryption::from_str(encryption).map_err(value_error)?,
+    )
+    .map_err(value_error)
+}
+
+/// Jsonrpc client of a CITA node
+#[pyclass(name = "Client")]
+struct PyClient {
+    client: Client,
+}
+
+#[pymethods]
+impl PyClient {
+    #[new]
+    #[pyo3(signature = (url = "http://127.0.0.1:1337"))]
+    fn new(url: &str) -> PyResult<Self> {
+        parse_url(url).map_err(value_error)?;
+        Ok(PyClient {
+            client: Client::new().set_uri(url),
+        })
+    }
+
+    /// Set the private key signing the transactions
+    #[pyo3(signature = (private_key, encryption = "secp256k1"))]
+    fn set_private_key(&mut self, private_key: &str, encryption: &str) -> PyResult<()> {
+        self.client
+            .set_private_key(&parse_privkey(private_key, encryption)?);
+        Ok(())
+    }
+
+    /// The current height
+    fn block_number(&self) -> PyResult<u64> {
+        self.client.get_current_height().map_err(runtime_error)
+    }
+
+    /// The metadata of the chain
+    #[pyo3(signature = (height = "latest"))]
+    fn get_metadata(&self, py: Python<'_>, height: &str) -> PyResult<PyObject> {
+        response(py, self.client.get_metadata(height))
+    }
+
+    /// The block at the height, a hex string or `latest`
+    #[pyo3(signature = (height = "latest", transaction_info = false))]
+    fn get_block_by_number(
+        &self,
+        py: Python<'_>,
+        height: &str,
+        transaction_info: bool,
+    ) -> PyResult<PyObject> {
+        response(
+            py,
+            self.client.get_block_by_number(height, transaction_info),
+        )
+    }
+
+    /// The block of the hash
+    #[pyo3(signature = (hash, transaction_info = false))]
+    fn get_block_by_hash(
+        &self,
+        py: Python<'_>,
+        hash: &str,
+        transaction_info: bool,
+    ) -> PyResult<PyObject> {
+        response(py, self.client.get_block_by_hash(hash, transaction_info))
+    }
+
+    /// The transaction of the hash
+    fn get_transaction(&self, py: Python<'_>, hash: &str) -> PyResult<PyObject> {
+        response(py, self.client.get_transaction(hash))
+    }
+
+    /// The receipt of the transaction, `None` before it's committed
+    fn get_transaction_receipt(&self, py: Python<'_>, hash: &str) -> PyResult<PyObject> {
+        response(py, self.client.get_transaction_receipt(hash))
+    }
+
+    /// The balance of the address, a hex string
+    #[pyo3(signature = (address, height = "latest"))]
+    fn get_balance(&self, py: Python<'_>, address: &str, height: &str) -> PyResult<PyObject> {
+        response(py, self.client.get_balance(address, height))
+    }
+
+    /// The code of the contract
+    #[pyo3(signature = (address, height = "latest"))]
+    fn get_code(&self, py: Python<'_>, address: &str, height: &str) -> PyResult<PyObject> {
+        response(py, self.client.get_code(address, height))
+    }
+
+    /// The storage of the contract at the key
+    #[pyo3(signature = (address, key, height = "latest"))]
+    fn get_storage_at(
+        &self,
+        py: Python<'_>,
+        address: &str,
+        key: &str,
+        height: &str,
+    ) -> PyResult<PyObject> {
+        response(py, self.client.get_storage_at(address, key, height))
+    }
+
+    /// Call the contract without a transaction, return the output data
+    #[pyo3(signature = (to, data, height = "latest", sender = None))]
+    fn call(
+        &self,
+        py: Python<'_>,
+        to: &str,
+        data: &str,
+        height: &str,
+        sender: Option<&str>,
+    ) -> PyResult<PyObject> {
+        response(py, self.client.call(sender, to, Some(data), height))
+    }
+
+    /// The logs matching the topics and the addresses in the block range
+    #[pyo3(signature = (topics = None, addresses = None, from_block = None, to_block = None))]
+    fn get_logs(
+        &self,
+        py: Python<'_>,
+        topics: Option<Vec<String>>,
+        addresses: Option<Vec<String>>,
+        from_block: Option<&str>,
+        to_block: Option<&str>,
+    ) -> PyResult<PyObject> {
+        let topics = topics
+            .as_ref()
+            .map(|topics| topics.iter().map(String::as_str).collect());
+        let addresses = addresses
+            .as_ref()
+            .map(|addresses| addresses.iter().map(String::as_str).collect());
+        response(
+            py,
+            self.client
+                .get_logs(topics, addresses, from_block, to_block),
+        )
+    }
+
+    /// Sign and send a transaction by the private key, an empty `to` creates a contract
+    #[pyo3(signature = (to = "", data = "0x", value = None, quota = None))]
+    fn send_transaction(
+        &mut self,
+        py: Python<'_>,
+        to: &str,
+        data: &str,
+        value: Option<&str>,
+        quota: Option<u64>,
+    ) -> PyResult<PyObject> {
+        let value = value.map(parse_u256).transpose()?;
+        let options = TransactionOptions::new()
+            .set_code(data)
+            .set_address(to)
+            .set_value(value)
+            .set_quota(quota);
+        response(py, self.client.send_raw_transaction(options))
+    }
+
+    /// Send a signed transaction
+    fn send_signed_transaction(&mut self, py: Python<'_>, content: &str) -> PyResult<PyObject> {
+        response(py, self.client.send_signed_transaction(content))
+    }
+}
+
+/// Generate a key pair of `secp256k1`, `ed25519` or `sm2`
+#[pyfunction]
+#[pyo3(signature = (encryption = "secp256k1"))]
+fn generate_key(py: Python<'_>, encryption: &str) -> PyResult<PyObject> {
+    let key_pair = KeyPair::new(Encryption::from_str(encryption).map_err(value_error)?);
+    let key = serde_json::json!({
+        "private": format!("0x{}", key_pair.privkey()),
+        "public": format!("0x{}", key_pair.pubkey()),
+        "address": key_pair.address().completed_lower_hex_with_0x(),
+    });
+    json_loads(py, &key.to_string())
+}
+
+/// Build and sign a transaction offline, return the hex of the `UnverifiedTransaction`
+#[allow(clippy::too_many_arguments)]
+#[pyfunction]
+#[pyo3(signature = (
+    private_key, current_height, chain_id, to = "", data = "0x", value = None, quota = None,
+    version = 0, encryption = "secp256k1"
+))]
+fn sign_transaction(
+    private_key: &str,
+    current_height: u64,
+    chain_id: &str,
+    to: &str,
+    data: &str,
+    value: Option<&str>,
+    quota: Option<u64>,
+    version: u32,
+    encryption: &str,
+) -> PyResult<String> {
+    let private_key = parse_privkey(private_key, encryption)?;
+    let tx = TransactionOptions::new()
+        .set_code(data)
+        .set_address(to)
+        .set_current_height(Some(current_height))
+        .set_quota(quota)
+        .set_value(value.map(parse_u256).transpose()?)
+        .set_version(Some(version))
+        .build(parse_u256(chain_id)?)
+        .map_err(value_error)?;
+    let signed = tx
+        .build_unverified(private_key)
+        .write_to_bytes()
+        .map_err(runtime_error)?;
+    Ok(format!("0x{}", encode(signed)))
+}
+
+/// Encode the call of the function by the ABI, integers are decimal strings
+#[pyfunction]
+#[pyo3(name = "encode_input")]
+fn encode_input_py(abi: &str, function: &str, args: Vec<String>) -> PyResult<String> {
+    encode_input(None, Some(abi), function, &args, true, false)
+        .map(|data| format!("0x{}", data))
+        .map_err(value_error)
+}
+
+/// Decode the output of the function call by the ABI
+#[pyfunction]
+fn decode_output(py: Python<'_>, abi: &str, function: &str, data: &str) -> PyResult<PyObject> {
+    let output = decode_input(None, Some(abi), function, data).map_err(value_error)?;
+    json_loads(py, &format!("[{}]", output.join(",")))
+}
+
+/// Decode the log of the event by the ABI
+#[pyfunction]
+fn decode_log(
+    py: Python<'_>,
+    abi: &str,
+    event: &str,
+    topics: Vec<String>,
+    data: &str,
+) -> PyResult<PyObject> {
+    let log = decode_logs(None, Some(abi), event, &topics, remove_0x(data)).map_err(value_error)?;
+    json_loads(py, &format!("[{}]", log.join(",")))
+}
+
+/// Split calldata into the selector and the arguments, decoded by the known signatures
+/// and the ABIs
+#[pyfunction]
+#[pyo3(signature = (data, abis = None))]
+fn decode_calldata(py: Python<'_>, data: &str, abis: Option<Vec<String>>) -> PyResult<PyObject> {
+    let mut table = SelectorTable::bundled();
+    for abi in abis.unwrap_or_default() {
+        table.add_abi(&abi).map_err(value_error)?;
+    }
+    let decoded = table.decode(data).map_err(value_error)?;
+    json_loads(py, &decoded.to_string())
+}
+
+/// Python module `cita_tool`
+#[pymodule]
+#[pyo3(name = "cita_tool")]
+fn cita_tool_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyClient>()?;
+    m.add_function(wrap_pyfunction!(generate_key, m)?)?;
+    m.add_function(wrap_pyfunction!(sign_transaction, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_input_py, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_output, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_log, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_calldata, m)?)?;
+    Ok(())
+}
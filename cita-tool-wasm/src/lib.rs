@@ -0,0 +1,92 @@
+//! WebAssembly bindings of cita-tool
+//!
+//! Build with `wasm-pack build cita-tool-wasm --target web`, then web wallets can create
+//! keys, encode calls and sign transactions the same way as cita-cli, without a node.
+//! The networking of cita-tool is left out, send the signed transactions by yourself.
+//!
+//! Building for `wasm32-unknown-unknown` needs clang with the wasm32 target for
+//! libsecp256k1. The sm2 dependency `cryptape-sm` still pulls `rustc-serialize`, which
+//! doesn't build for wasm32, so the wasm build waits for it to be replaced.
+
+#![deny(warnings)]
+#![deny(missing_docs)]
+
+use std::str::FromStr;
+
+use cita_tool::{
+    encode, encode_input, hex_to_u256, remove_0x, Encryption, KeyPair, LowerHex, PrivateKey,
+    ProtoMessage, TransactionOptions, U256,
+};
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+fn parse_u256(value: &str) -> Result<U256, JsValue> {
+    if value.starts_with("0x") || value.starts_with("0X") {
+        hex_to_u256(value).map_err(|err| JsValue::from_str(&err.to_string()))
+    } else {
+        U256::from_dec_str(value).map_err(|err| JsValue::from_str(&format!("{:?}", err)))
+    }
+}
+
+fn encryption(encryption: &str) -> Result<Encryption, JsValue> {
+    Encryption::from_str(encryption).map_err(|err| JsValue::from_str(&err))
+}
+
+/// Generate a key pair of `secp256k1`, `ed25519` or `sm2`
+///
+/// Returns the JSON `{"private": "0x..", "public": "0x..", "address": "0x.."}`.
+#[wasm_bindgen(js_name = generateKey)]
+pub fn generate_key(algorithm: &str) -> Result<String, JsValue> {
+    let key_pair = KeyPair::new(encryption(algorithm)?);
+    Ok(json!({
+        "private": format!("0x{}", key_pair.privkey()),
+        "public": format!("0x{}", key_pair.pubkey()),
+        "address": key_pair.address().completed_lower_hex_with_0x(),
+    })
+    .to_string())
+}
+
+/// Encode the call of the function with the JSON list of arguments, by the ABI
+#[wasm_bindgen(js_name = encodeInput)]
+pub fn encode_call(abi: &str, function: &str, arguments: &str) -> Result<String, JsValue> {
+    let arguments: Vec<String> =
+        serde_json::from_str(arguments).map_err(|err| JsValue::from_str(&err.to_string()))?;
+    encode_input(None, Some(abi), function, &arguments, true, false)
+        .map(|data| format!("0x{}", data))
+        .map_err(|err| JsValue::from_str(&err.to_string()))
+}
+
+/// Build and sign a transaction, returns the hex of the `UnverifiedTransaction`
+///
+/// `value` and `chain_id` are hex or decimal, an empty `to` creates a contract.
+/// The transaction is valid until 88 blocks after the current height.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = signTransaction)]
+pub fn sign_transaction(
+    to: &str,
+    data: &str,
+    value: &str,
+    quota: u64,
+    current_height: u64,
+    chain_id: &str,
+    version: u32,
+    private_key: &str,
+    algorithm: &str,
+) -> Result<String, JsValue> {
+    let private_key = PrivateKey::from_str(remove_0x(private_key), encryption(algorithm)?)
+        .map_err(|err| JsValue::from_str(&err))?;
+    let tx = TransactionOptions::new()
+        .set_code(data)
+        .set_address(to)
+        .set_current_height(Some(current_height))
+        .set_quota(Some(quota))
+        .set_value(Some(parse_u256(value)?))
+        .set_version(Some(version))
+        .build(parse_u256(chain_id)?)
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    let signed = tx
+        .build_unverified(private_key)
+        .write_to_bytes()
+        .map_err(|err| JsValue::from_str(&err.to_string()))?;
+    Ok(format!("0x{}", encode(signed)))
+}
@@ -0,0 +1,32 @@
+fn main() {
+    println!("cargo:rerun-if-changed=proto/blockchain.proto");
+    #[cfg(feature = "regenerate-protos")]
+    regenerate_protos();
+}
+
+/// Generate the protobuf code from `proto/` into `OUT_DIR`, instead of the checked-in
+/// `src/protos/blockchain.rs`, without depending on `protoc`
+#[cfg(feature = "regenerate-protos")]
+fn regenerate_protos() {
+    use std::fs;
+    use std::path::Path;
+
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    protobuf_codegen_pure::run(protobuf_codegen_pure::Args {
+        out_dir: &out_dir,
+        input: &["proto/blockchain.proto"],
+        includes: &[""],
+        customize: Default::default(),
+    })
+    .expect("Failed to generate the protobuf code");
+
+    // The code is included in a module, which can't take the inner attributes and docs
+    let path = Path::new(&out_dir).join("blockchain.rs");
+    let code = fs::read_to_string(&path).unwrap();
+    let code = code
+        .lines()
+        .filter(|line| !line.starts_with("#![") && !line.starts_with("//!"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    fs::write(&path, code).unwrap();
+}
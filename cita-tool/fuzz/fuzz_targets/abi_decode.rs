@@ -0,0 +1,59 @@
+#![no_main]
+
+use std::sync::OnceLock;
+
+use cita_tool::{decode_input, decode_log_by_topic, decode_params, encode, SelectorTable};
+use libfuzzer_sys::fuzz_target;
+
+/// The types decoded by `decode_params`, chosen by the first byte of the input
+const TYPES: &[&[&str]] = &[
+    &["uint256"],
+    &["int8", "bool"],
+    &["address", "bytes32"],
+    &["string"],
+    &["bytes"],
+    &["uint256[]"],
+    &["bool[]", "string[]"],
+    &["address[2]", "bytes"],
+];
+
+const ABI: &str = r#"[
+    {"type": "function", "name": "get", "inputs": [], "outputs": [
+        {"name": "a", "type": "uint256"}, {"name": "b", "type": "string"},
+        {"name": "c", "type": "address[]"}
+    ]},
+    {"type": "event", "name": "Transfer", "anonymous": false, "inputs": [
+        {"name": "from", "type": "address", "indexed": true},
+        {"name": "to", "type": "address", "indexed": true},
+        {"name": "value", "type": "uint256", "indexed": false}
+    ]}
+]"#;
+
+static SELECTORS: OnceLock<SelectorTable> = OnceLock::new();
+
+// Outputs, logs and calldata are decoded from what the node returns
+fuzz_target!(|data: &[u8]| {
+    let (index, data) = match data.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let types = TYPES[usize::from(*index) % TYPES.len()]
+        .iter()
+        .map(|ty| ty.to_string())
+        .collect::<Vec<_>>();
+    let _ = decode_params(&types, &encode(data));
+    let _ = decode_input(None, Some(ABI), "get", &encode(data));
+
+    // Up to 3 topics, then the data of the log
+    let topics = data
+        .chunks(32)
+        .take(usize::from(*index % 4))
+        .filter(|topic| topic.len() == 32)
+        .map(encode)
+        .collect::<Vec<_>>();
+    let _ = decode_log_by_topic(None, Some(ABI), &topics, &encode(data));
+
+    let _ = SELECTORS
+        .get_or_init(SelectorTable::bundled)
+        .decode(&encode(data));
+});
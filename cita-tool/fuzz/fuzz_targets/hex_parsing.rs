@@ -0,0 +1,22 @@
+#![no_main]
+
+use std::str::FromStr;
+
+use cita_tool::{decode, hex_to_u256, remove_0x, Address, Encryption, UnverifiedTransaction, H256};
+use libfuzzer_sys::fuzz_target;
+
+// The hex fields of the responses, and the signed transactions returned by getTransaction
+fuzz_target!(|data: &str| {
+    let hex = remove_0x(data);
+    let _ = decode(hex);
+    let _ = hex_to_u256(hex);
+    let _ = H256::from_str(hex);
+    let _ = Address::from_str(hex);
+
+    if let Ok(tx) = UnverifiedTransaction::from_str(data) {
+        for encryption in &[Encryption::Secp256k1, Encryption::Ed25519, Encryption::Sm2] {
+            let _ = tx.public_key(*encryption);
+            let _ = tx.inspect_signature(*encryption);
+        }
+    }
+});
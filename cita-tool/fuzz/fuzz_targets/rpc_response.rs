@@ -0,0 +1,18 @@
+#![no_main]
+
+use cita_tool::JsonRpcResponse;
+use libfuzzer_sys::fuzz_target;
+
+// Whatever a node answers, reading the response must not panic
+fuzz_target!(|data: &[u8]| {
+    if let Ok(response) = serde_json::from_slice::<JsonRpcResponse>(data) {
+        let _ = response.is_ok();
+        let _ = response.to_string();
+        if let Some(result) = response.result() {
+            let _ = result.to_string();
+        }
+        if let Some(err) = response.error() {
+            let _ = (err.code(), err.message(), err.to_string());
+        }
+    }
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use cita_tool::client::light::{verify_trie_proof, StateProof};
+use cita_tool::{Encryption, Hashable};
+use libfuzzer_sys::fuzz_target;
+
+// The proof returned by getStateProof, walked down from a root matching its first node
+fuzz_target!(|data: &[u8]| {
+    let encryption = Encryption::Secp256k1;
+    if let Some(proof) = StateProof::decode(data) {
+        let account_key = proof.address.crypt_hash(encryption);
+        let value_key = proof.key.crypt_hash(encryption);
+        for (key, nodes) in vec![
+            (account_key, &proof.account_proof),
+            (value_key, &proof.value_proof),
+        ] {
+            if let Some(root) = nodes.first().map(|node| node.crypt_hash(encryption)) {
+                let _ = verify_trie_proof(&root, &key, nodes, encryption);
+            }
+        }
+    }
+});
@@ -1,48 +1,170 @@
 use std::fs::File;
 use std::io::Read;
 
-use crate::LowerHex;
 use ethabi::param_type::{ParamType, Reader};
 use ethabi::token::{LenientTokenizer, StrictTokenizer, Token, Tokenizer};
-use ethabi::{decode, encode, Contract, Hash};
+use ethabi::{decode, encode, Contract, Event, Hash, Param, Uint};
 use hex::{decode as hex_decode, encode as hex_encode};
+use serde_json::{Map, Value};
 use types::U256;
 
+use crate::client::{hex_to_u256, remove_0x};
 use crate::error::ToolError;
 
+/// Parse the values into the tokens of the types, checked against the sizes of the types
+///
+/// The lenient integers are decimal with optional `_` separators, hex with `0x`, or
+/// scientific notation such as `1e18` and `1.5e9`, also as the elements of the arrays.
 pub fn parse_tokens(params: &[(ParamType, &str)], lenient: bool) -> Result<Vec<Token>, ToolError> {
     params
         .iter()
         .map(|&(ref param, value)| {
-            if lenient {
-                let type_name = format!("{}", param);
-                if type_name.starts_with("uint") && type_name.find(']').is_none() {
-                    let y = U256::from_dec_str(value)
-                        .map_err(|_| "Can't parse into u256")?
-                        .completed_lower_hex();
-                    StrictTokenizer::tokenize(param, &y)
-                } else if type_name.starts_with("int") && type_name.find(']').is_none() {
-                    let x = if value.starts_with('-') {
-                        let x = (!U256::from_dec_str(&value[1..])
-                            .map_err(|_| "Can't parse into u256")?
-                            + U256::from(1))
-                        .lower_hex();
-                        format!("{:f>64}", x)
-                    } else {
-                        U256::from_dec_str(value)
-                            .map_err(|_| "Can't parse into u256")?
-                            .completed_lower_hex()
-                    };
-                    StrictTokenizer::tokenize(param, &x)
-                } else {
-                    LenientTokenizer::tokenize(param, value)
-                }
+            let token = if lenient {
+                IntegerTokenizer::tokenize(param, value)
             } else {
                 StrictTokenizer::tokenize(param, value)
             }
+            .map_err(|e| {
+                e.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<String>>()
+                    .join(": ")
+            })?;
+            if in_range(param, &token) {
+                Ok(token)
+            } else {
+                Err(format!("{} overflows {}", value, param))
+            }
         })
         .collect::<Result<_, _>>()
-        .map_err(|e| ToolError::Abi(e.to_string()))
+        .map_err(ToolError::Abi)
+}
+
+/// The lenient tokenizer, with the integers in more notations
+struct IntegerTokenizer;
+
+impl Tokenizer for IntegerTokenizer {
+    fn tokenize_address(value: &str) -> Result<[u8; 20], ethabi::Error> {
+        LenientTokenizer::tokenize_address(value)
+    }
+
+    fn tokenize_string(value: &str) -> Result<String, ethabi::Error> {
+        LenientTokenizer::tokenize_string(value)
+    }
+
+    fn tokenize_bool(value: &str) -> Result<bool, ethabi::Error> {
+        LenientTokenizer::tokenize_bool(value)
+    }
+
+    fn tokenize_bytes(value: &str) -> Result<Vec<u8>, ethabi::Error> {
+        LenientTokenizer::tokenize_bytes(value)
+    }
+
+    fn tokenize_fixed_bytes(value: &str, len: usize) -> Result<Vec<u8>, ethabi::Error> {
+        LenientTokenizer::tokenize_fixed_bytes(value, len)
+    }
+
+    fn tokenize_uint(value: &str) -> Result<[u8; 32], ethabi::Error> {
+        let (magnitude, negative) = parse_integer(value)?;
+        if negative && !magnitude.is_zero() {
+            return Err(format!("{} is negative", value).into());
+        }
+        let mut word = [0u8; 32];
+        magnitude.to_big_endian(&mut word);
+        Ok(word)
+    }
+
+    fn tokenize_int(value: &str) -> Result<[u8; 32], ethabi::Error> {
+        let (magnitude, negative) = parse_integer(value)?;
+        let min = U256::one() << 255;
+        let integer = if negative {
+            if magnitude > min {
+                return Err(format!("{} overflows int256", value).into());
+            }
+            (!magnitude).overflowing_add(U256::one()).0
+        } else if magnitude >= min {
+            return Err(format!("{} overflows int256", value).into());
+        } else {
+            magnitude
+        };
+        let mut word = [0u8; 32];
+        integer.to_big_endian(&mut word);
+        Ok(word)
+    }
+}
+
+/// The magnitude of the integer and whether it's negative
+fn parse_integer(value: &str) -> Result<(U256, bool), String> {
+    let digits = value.trim().replace('_', "");
+    let (negative, digits) = match digits.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, digits.as_str()),
+    };
+    let invalid = || format!("Invalid integer {}", value);
+    let overflow = || format!("{} overflows 256 bits", value);
+
+    // The 64 digits word of the strict tokenizer is still hex
+    let is_word = digits.len() == 64
+        && digits.chars().all(|c| c.is_ascii_hexdigit())
+        && !digits.chars().all(|c| c.is_ascii_digit());
+    if digits.starts_with("0x") || is_word {
+        if remove_0x(digits).is_empty() {
+            return Err(invalid());
+        }
+        return hex_to_u256(digits)
+            .map(|magnitude| (magnitude, negative))
+            .map_err(|_| invalid());
+    }
+
+    let (mantissa, exponent) = match digits.find(['e', 'E']) {
+        Some(index) => (
+            &digits[..index],
+            digits[index + 1..]
+                .trim_start_matches('+')
+                .parse::<u32>()
+                .map_err(|_| invalid())?,
+        ),
+        None => (digits, 0),
+    };
+    let (whole, fraction) = match mantissa.find('.') {
+        Some(index) => (&mantissa[..index], &mantissa[index + 1..]),
+        None => (mantissa, ""),
+    };
+    let fraction = fraction.trim_end_matches('0');
+    if whole.is_empty() && fraction.is_empty() {
+        return Err(invalid());
+    }
+    if fraction.len() as u32 > exponent {
+        return Err(format!("{} is not an integer", value));
+    }
+    let digits = format!("{}{}", whole, fraction);
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let mut magnitude = U256::from_dec_str(&digits).map_err(|_| overflow())?;
+    if !magnitude.is_zero() {
+        for _ in 0..exponent - fraction.len() as u32 {
+            magnitude = magnitude.checked_mul(U256::from(10)).ok_or_else(overflow)?;
+        }
+    }
+    Ok((magnitude, negative))
+}
+
+/// Whether the integers of the token fit into the sizes of the type
+fn in_range(param: &ParamType, token: &Token) -> bool {
+    match (param, token) {
+        (ParamType::Uint(bits), Token::Uint(value)) if *bits < 256 => (*value >> *bits).is_zero(),
+        // The higher bits only extend the sign
+        (ParamType::Int(bits), Token::Int(value)) if *bits < 256 => {
+            let high = *value >> (*bits - 1);
+            high.is_zero() || high == !Uint::zero() >> (*bits - 1)
+        }
+        (ParamType::Array(inner), Token::Array(tokens))
+        | (ParamType::FixedArray(inner, _), Token::FixedArray(tokens)) => {
+            tokens.iter().all(|token| in_range(inner, token))
+        }
+        _ => true,
+    }
 }
 
 /// According to the contract, encode the function and parameter values
@@ -187,10 +309,11 @@ pub fn decode_input(
     let function = contract
         .function(function)
         .map_err(|e| ToolError::Abi(format!("{}", e)))?;
+    let data = hex_decode(remove_0x(data)).map_err(ToolError::Decode)?;
     let tokens = function
-        .decode_output(data.as_bytes())
+        .decode_output(&data)
         .map_err(|e| ToolError::Abi(format!("{}", e)))?;
-    let types = function.outputs.iter().map(|ref param| &param.kind);
+    let types = function.outputs.iter().map(|param| &param.kind);
 
     assert_eq!(types.len(), tokens.len());
 
@@ -208,6 +331,232 @@ pub fn decode_input(
     Ok(result)
 }
 
+/// According to the given abi, encode the function, or the constructor after the code,
+/// and the JSON arguments
+///
+/// `args` is an array of the arguments in order, or an object keyed by the parameter names.
+/// An array argument is a JSON array, nested for the multi-dimensional arrays, a scalar
+/// is a string or a number, the integers also take the hex strings with `0x`.
+pub fn encode_json_input(
+    abi: &str,
+    function: &str,
+    args: &Value,
+    constructor: bool,
+) -> Result<String, ToolError> {
+    let contract = Contract::load(abi.as_bytes()).map_err(|e| ToolError::Abi(format!("{}", e)))?;
+    if constructor {
+        let constructor = contract
+            .constructor
+            .as_ref()
+            .ok_or_else(|| ToolError::Abi("No constructor on abi".to_string()))?;
+        let tokens = json_tokens("constructor", &constructor.inputs, args)?;
+        let result = constructor
+            .encode_input(Vec::new(), &tokens)
+            .map_err(|e| ToolError::Abi(e.to_string()))?;
+        Ok(format!("{}{}", function, hex_encode(result)))
+    } else {
+        let function = contract
+            .function(function)
+            .map_err(|e| ToolError::Abi(format!("{}", e)))?;
+        let tokens = json_tokens(&function.name, &function.inputs, args)?;
+        let result = function
+            .encode_input(&tokens)
+            .map_err(|e| ToolError::Abi(e.to_string()))?;
+        Ok(hex_encode(result))
+    }
+}
+
+/// Match the JSON arguments to the parameters and check them against the types
+fn json_tokens(name: &str, inputs: &[Param], args: &Value) -> Result<Vec<Token>, ToolError> {
+    let signature = || {
+        inputs
+            .iter()
+            .map(|param| format!("{} {}", param.kind, param.name))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let args = match args {
+        Value::Array(args) if args.len() == inputs.len() => args.iter().collect(),
+        Value::Array(args) => {
+            return Err(ToolError::Abi(format!(
+                "{}({}) takes {} arguments, got {}",
+                name,
+                signature(),
+                inputs.len(),
+                args.len()
+            )));
+        }
+        Value::Object(args) => {
+            if let Some(unknown) = args
+                .keys()
+                .find(|key| inputs.iter().all(|param| &param.name != *key))
+            {
+                return Err(ToolError::Abi(format!(
+                    "{}({}) has no parameter {}",
+                    name,
+                    signature(),
+                    unknown
+                )));
+            }
+            inputs
+                .iter()
+                .map(|param| {
+                    args.get(&param.name).ok_or_else(|| {
+                        ToolError::Abi(format!("Missing argument {} of {}", param.name, param.kind))
+                    })
+                })
+                .collect::<Result<Vec<&Value>, ToolError>>()?
+        }
+        _ => {
+            return Err(ToolError::Abi(
+                "Expect the arguments in a JSON array or object".to_string(),
+            ))
+        }
+    };
+    inputs
+        .iter()
+        .zip(args)
+        .map(|(param, arg)| {
+            json_to_token(&param.kind, arg).map_err(|e| {
+                ToolError::Abi(format!("Argument {} of {}: {}", param.name, param.kind, e))
+            })
+        })
+        .collect()
+}
+
+/// Decode the revert payload of a failed call, `Error(string)` into its message and
+/// `Panic(uint256)` into the code with its meaning, None if it's neither
+pub fn decode_revert_reason(data: &str) -> Option<String> {
+    let data = hex_decode(remove_0x(data.trim())).ok()?;
+    if data.len() < 4 {
+        return None;
+    }
+    let (selector, payload) = data.split_at(4);
+    match selector {
+        // keccak256("Error(string)")
+        [0x08, 0xc3, 0x79, 0xa0] => match decode(&[ParamType::String], payload).ok()?.pop()? {
+            Token::String(message) => Some(message),
+            _ => None,
+        },
+        // keccak256("Panic(uint256)")
+        [0x4e, 0x48, 0x7b, 0x71] => match decode(&[ParamType::Uint(256)], payload).ok()?.pop()? {
+            Token::Uint(code) => {
+                let meaning = match code.low_u64() {
+                    _ if code > Uint::from(0xff) => "unknown panic",
+                    0x01 => "assertion failed",
+                    0x11 => "arithmetic overflow or underflow",
+                    0x12 => "division or modulo by zero",
+                    0x21 => "invalid enum value",
+                    0x22 => "invalid storage byte array",
+                    0x31 => "pop on an empty array",
+                    0x32 => "array index out of bounds",
+                    0x41 => "out of memory",
+                    0x51 => "call to a zero internal function",
+                    _ => "generic panic",
+                };
+                Some(format!("Panic({:#x}): {}", code, meaning))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// According to the given abi, decode the output of the function into a JSON object
+/// keyed by the output names, an unnamed output is keyed by its position
+pub fn decode_named_output(abi: &str, function: &str, data: &str) -> Result<Value, ToolError> {
+    let contract = Contract::load(abi.as_bytes()).map_err(|e| ToolError::Abi(format!("{}", e)))?;
+    let function = contract
+        .function(function)
+        .map_err(|e| ToolError::Abi(format!("{}", e)))?;
+    let data = hex_decode(remove_0x(data)).map_err(ToolError::Decode)?;
+    let tokens = function
+        .decode_output(&data)
+        .map_err(|e| ToolError::Abi(format!("{}", e)))?;
+
+    let mut outputs = Map::new();
+    for (index, (param, token)) in function.outputs.iter().zip(tokens).enumerate() {
+        let name = if param.name.is_empty() {
+            index.to_string()
+        } else {
+            param.name.clone()
+        };
+        outputs.insert(name, token_to_json(&param.kind, token));
+    }
+    Ok(Value::Object(outputs))
+}
+
+fn json_to_token(param: &ParamType, value: &Value) -> Result<Token, String> {
+    match (param, value) {
+        (ParamType::Array(inner), Value::Array(values)) => values
+            .iter()
+            .map(|value| json_to_token(inner, value))
+            .collect::<Result<_, _>>()
+            .map(Token::Array),
+        (ParamType::FixedArray(inner, len), Value::Array(values)) => {
+            if values.len() != *len {
+                return Err(format!("Expect {} elements, got {}", len, values.len()));
+            }
+            values
+                .iter()
+                .map(|value| json_to_token(inner, value))
+                .collect::<Result<_, _>>()
+                .map(Token::FixedArray)
+        }
+        (ParamType::Array(_), _) | (ParamType::FixedArray(_, _), _) => {
+            Err(format!("Expect a JSON array for {}, got {}", param, value))
+        }
+        (_, Value::Array(_)) | (_, Value::Object(_)) | (_, Value::Null) => {
+            Err(format!("Expect a scalar for {}, got {}", param, value))
+        }
+        (_, Value::Number(number)) if number.is_f64() => Err(format!(
+            "{} is not an integer, quote the big numbers",
+            number
+        )),
+        _ => {
+            let scalar = match value {
+                Value::String(scalar) => scalar.clone(),
+                other => other.to_string(),
+            };
+            match param {
+                ParamType::Address | ParamType::Bytes | ParamType::FixedBytes(_) => {
+                    parse_tokens(&[(param.clone(), remove_0x(&scalar))], false)
+                }
+                _ => parse_tokens(&[(param.clone(), &scalar)], true),
+            }
+            .map(|mut tokens| tokens.remove(0))
+            .map_err(|err| match err {
+                ToolError::Abi(err) => err,
+                err => err.to_string(),
+            })
+        }
+    }
+}
+
+fn token_to_json(param: &ParamType, token: Token) -> Value {
+    match (param, token) {
+        (ParamType::Array(inner), Token::Array(tokens))
+        | (ParamType::FixedArray(inner, _), Token::FixedArray(tokens)) => Value::Array(
+            tokens
+                .into_iter()
+                .map(|token| token_to_json(inner, token))
+                .collect(),
+        ),
+        (_, Token::Address(address)) => Value::String(format!("0x{:x}", address)),
+        (_, Token::Bytes(bytes)) | (_, Token::FixedBytes(bytes)) => {
+            Value::String(format!("0x{}", hex_encode(bytes)))
+        }
+        (_, Token::Bool(value)) => Value::Bool(value),
+        (_, Token::String(value)) => Value::String(value),
+        // The integers are decimal strings, they may not fit into JSON numbers
+        (ParamType::Int(_), Token::Int(value)) if value.bit(255) => {
+            Value::String(format!("-{}", !value + Uint::from(1)))
+        }
+        (_, Token::Int(value)) | (_, Token::Uint(value)) => Value::String(value.to_string()),
+        (_, token) => Value::String(format!("{}", token)),
+    }
+}
+
 /// According to the given abi file, decode the topic
 pub fn decode_logs(
     path: Option<&str>,
@@ -222,12 +571,44 @@ pub fn decode_logs(
         .event(event)
         .map_err(|e| ToolError::Abi(format!("{}", e)))?;
 
-    let topics: Vec<Hash> = topics
+    let topics = parse_topics(topics)?;
+    parse_log(event, topics, data)
+}
+
+/// According to the given abi file, find the event by the first topic and decode the log,
+/// return the event name and the decoded params
+pub fn decode_log_by_topic(
+    path: Option<&str>,
+    abi: Option<&str>,
+    topics: &[String],
+    data: &str,
+) -> Result<(String, Vec<String>), ToolError> {
+    let contract =
+        Contract::load(get_abi(path, abi)?).map_err(|e| ToolError::Abi(format!("{}", e)))?;
+
+    let topics = parse_topics(topics)?;
+    let signature = topics
+        .first()
+        .cloned()
+        .ok_or_else(|| ToolError::Abi("Anonymous log can't be matched".to_string()))?;
+    let event = contract
+        .events()
+        .find(|event| event.signature() == signature)
+        .ok_or_else(|| ToolError::Abi(format!("No event matches topic {:x}", signature)))?;
+
+    Ok((event.name.clone(), parse_log(event, topics, data)?))
+}
+
+fn parse_topics(topics: &[String]) -> Result<Vec<Hash>, ToolError> {
+    topics
         .iter()
-        .map(|t| t.parse())
+        .map(|t| remove_0x(t).parse())
         .collect::<Result<_, _>>()
-        .map_err(|e| ToolError::Abi(format!("{}", e)))?;
-    let data = hex_decode(data).map_err(ToolError::Decode)?;
+        .map_err(|e| ToolError::Abi(format!("{}", e)))
+}
+
+fn parse_log(event: &Event, topics: Vec<Hash>, data: &str) -> Result<Vec<String>, ToolError> {
+    let data = hex_decode(remove_0x(data)).map_err(ToolError::Decode)?;
     let decoded = event
         .parse_log((topics, data).into())
         .map_err(|e| ToolError::Abi(format!("{}", e)))?;
@@ -256,7 +637,216 @@ fn get_abi(path: Option<&str>, abi: Option<&str>) -> Result<Box<dyn Read>, ToolE
 
 #[cfg(test)]
 mod test {
-    use super::{decode_params, encode_params};
+    use super::{
+        decode_input, decode_named_output, decode_params, decode_revert_reason, encode_json_input,
+        encode_params,
+    };
+    use ethabi::param_type::ParamType;
+    use ethabi::token::Token;
+    use ethabi::{decode, encode, Uint};
+    use hex::{decode as hex_decode, encode as hex_encode};
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    /// The tuples are not supported by ethabi 8, everything else is generated
+    fn param_type() -> impl Strategy<Value = ParamType> {
+        let leaf = prop_oneof![
+            Just(ParamType::Address),
+            Just(ParamType::Bool),
+            Just(ParamType::String),
+            Just(ParamType::Bytes),
+            (1..=32usize).prop_map(ParamType::FixedBytes),
+            (1..=32usize).prop_map(|size| ParamType::Uint(size * 8)),
+            (1..=32usize).prop_map(|size| ParamType::Int(size * 8)),
+        ];
+        leaf.prop_recursive(2, 8, 3, |inner| {
+            prop_oneof![
+                inner
+                    .clone()
+                    .prop_map(|param| ParamType::Array(Box::new(param))),
+                (inner, 1..=3usize)
+                    .prop_map(|(param, len)| ParamType::FixedArray(Box::new(param), len)),
+            ]
+        })
+    }
+
+    /// A token of the type, the strings have no quotes and commas so they can be
+    /// elements of the arrays, where an empty string or bytes would read as an empty array
+    fn token(param: &ParamType, element: bool) -> BoxedStrategy<Token> {
+        let min_len = if element { 1 } else { 0 };
+        match param {
+            ParamType::Address => any::<[u8; 20]>()
+                .prop_map(|address| Token::Address(address.into()))
+                .boxed(),
+            ParamType::Bool => any::<bool>().prop_map(Token::Bool).boxed(),
+            ParamType::String => "[a-zA-Z0-9 ]{1,40}".prop_map(Token::String).boxed(),
+            ParamType::Bytes => vec(any::<u8>(), min_len..70).prop_map(Token::Bytes).boxed(),
+            ParamType::FixedBytes(size) => {
+                vec(any::<u8>(), *size).prop_map(Token::FixedBytes).boxed()
+            }
+            ParamType::Uint(bits) => {
+                let shift = 256 - bits;
+                any::<[u8; 32]>()
+                    .prop_map(move |value| Token::Uint(Uint::from(value) >> shift))
+                    .boxed()
+            }
+            ParamType::Int(bits) => {
+                // Sign extend the value of the bits
+                let shift = 256 - bits;
+                any::<[u8; 32]>()
+                    .prop_map(move |value| {
+                        let value = Uint::from(value) << shift;
+                        let negative = value.bit(255);
+                        let mut value = value >> shift;
+                        if negative && shift > 0 {
+                            value = value | (!Uint::zero() << (256 - shift));
+                        }
+                        Token::Int(value)
+                    })
+                    .boxed()
+            }
+            ParamType::Array(param) => vec(token(param, true), 0..4).prop_map(Token::Array).boxed(),
+            ParamType::FixedArray(param, len) => vec(token(param, true), *len)
+                .prop_map(Token::FixedArray)
+                .boxed(),
+        }
+    }
+
+    fn params() -> impl Strategy<Value = Vec<(ParamType, Token)>> {
+        vec(
+            param_type().prop_flat_map(|param| (Just(param.clone()), token(&param, false))),
+            1..5,
+        )
+    }
+
+    /// The value accepted by the strict tokenizer
+    fn strict_value(token: &Token) -> String {
+        match token {
+            Token::Uint(value) | Token::Int(value) => {
+                let mut word = [0u8; 32];
+                value.to_big_endian(&mut word);
+                hex_encode(word)
+            }
+            Token::Array(tokens) | Token::FixedArray(tokens) => format!(
+                "[{}]",
+                tokens
+                    .iter()
+                    .map(strict_value)
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ),
+            _ => format!("{}", token),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn test_encode_decode_round_trip(params in params()) {
+            let types = params.iter().map(|(param, _)| format!("{}", param)).collect::<Vec<_>>();
+            let kinds = params.iter().map(|(param, _)| param.clone()).collect::<Vec<_>>();
+            let tokens = params.iter().map(|(_, token)| token.clone()).collect::<Vec<_>>();
+            let values = tokens.iter().map(strict_value).collect::<Vec<_>>();
+
+            // Same bytes as the reference encoder, and they decode to the tokens
+            let encoded = encode_params(&types, &values, false).unwrap();
+            prop_assert_eq!(&encoded, &hex_encode(encode(&tokens)));
+            prop_assert_eq!(decode(&kinds, &hex_decode(&encoded).unwrap()).unwrap(), tokens);
+
+            let decoded = decode_params(&types, &encoded).unwrap();
+            prop_assert_eq!(decoded.len(), types.len());
+            for (decoded, ty) in decoded.iter().zip(types.iter()) {
+                let prefix = format!("{{\"{}\": ", ty);
+                prop_assert!(decoded.starts_with(&prefix));
+            }
+        }
+
+        #[test]
+        fn test_lenient_integers(value in any::<i128>(), unsigned in any::<u128>()) {
+            let encoded = encode_params(
+                &["int256".to_string(), "uint128".to_string()],
+                &[value.to_string(), unsigned.to_string()],
+                true,
+            )
+            .unwrap();
+            let mut expected = if value < 0 { vec![0xff; 16] } else { vec![0; 16] };
+            expected.extend_from_slice(&value.to_be_bytes());
+            expected.extend_from_slice(&[0; 16]);
+            expected.extend_from_slice(&unsigned.to_be_bytes());
+            prop_assert_eq!(encoded, hex_encode(expected));
+        }
+    }
+
+    #[test]
+    fn test_reference_vectors() {
+        // The examples of the Solidity ABI specification
+        let baz = encode_params(
+            &["uint32".to_string(), "bool".to_string()],
+            &["69".to_string(), "true".to_string()],
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            baz,
+            "0000000000000000000000000000000000000000000000000000000000000045\
+             0000000000000000000000000000000000000000000000000000000000000001"
+        );
+
+        let sam = encode_params(
+            &[
+                "bytes".to_string(),
+                "bool".to_string(),
+                "uint256[]".to_string(),
+            ],
+            &[
+                hex_encode("dave"),
+                "true".to_string(),
+                "[1,2,3]".to_string(),
+            ],
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            sam,
+            "0000000000000000000000000000000000000000000000000000000000000060\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             00000000000000000000000000000000000000000000000000000000000000a0\
+             0000000000000000000000000000000000000000000000000000000000000004\
+             6461766500000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000000000000000003\
+             0000000000000000000000000000000000000000000000000000000000000001\
+             0000000000000000000000000000000000000000000000000000000000000002\
+             0000000000000000000000000000000000000000000000000000000000000003"
+        );
+    }
+
+    #[test]
+    fn test_integer_notations() {
+        let encode =
+            |ty: &str, value: &str| encode_params(&[ty.to_string()], &[value.to_string()], true);
+        let word = |value: u64| format!("{:064x}", value);
+        assert_eq!(encode("uint256", "1_000_000").unwrap(), word(1_000_000));
+        assert_eq!(
+            encode("uint256", "1e18").unwrap(),
+            word(1_000_000_000_000_000_000)
+        );
+        assert_eq!(encode("uint64", "1.5e3").unwrap(), word(1500));
+        assert_eq!(encode("uint16", "0x1_0").unwrap(), word(16));
+        assert_eq!(
+            encode("uint256[]", "[1e3,2_000]").unwrap(),
+            format!("{}{}{}{}", word(32), word(2), word(1000), word(2000))
+        );
+        assert_eq!(encode("int8", "-128").unwrap(), format!("{:f>64}", "80"));
+        assert!(encode("uint256", "1.5").is_err());
+        assert!(encode("uint256", "-1").is_err());
+        assert!(encode("uint256", "1e78").is_err());
+        assert_eq!(
+            encode("uint8", "256").unwrap_err().to_string(),
+            "ABI error: 256 overflows uint8"
+        );
+        assert!(encode("int8", "-129").is_err());
+        assert!(encode("int8", "128").is_err());
+        assert!(encode("uint8[]", "[1,300]").is_err());
+    }
 
     #[test]
     fn test_encode() {
@@ -302,4 +892,73 @@ mod test {
         let f = decode_params(&["string".to_string()], &e).unwrap();
         assert_eq!(f, ["{\"string\": \"\\\"\"}".to_string()]);
     }
+
+    #[test]
+    fn test_decode_input() {
+        let abi = r#"[{"constant":true,"inputs":[],"name":"get","outputs":[{"name":"","type":"uint256"}],"payable":false,"stateMutability":"view","type":"function"}]"#;
+        let output = decode_input(
+            None,
+            Some(abi),
+            "get",
+            "0x000000000000000000000000000000000000000000000000000000000000002a",
+        )
+        .unwrap();
+        assert_eq!(output, ["{\"uint256\": \"2a\"}".to_string()]);
+    }
+
+    #[test]
+    fn test_json_input_named_output() {
+        let abi = r#"[{"constant":true,"inputs":[{"name":"owners","type":"address[]"},{"name":"limits","type":"int256[2][]"}],"name":"check","outputs":[{"name":"total","type":"int256"},{"name":"","type":"bool"}],"payable":false,"stateMutability":"view","type":"function"}]"#;
+        let args = serde_json::json!([
+            ["0x0000000000000000000000000000000000000001"],
+            [["0x10", "-1"], [2, 3]],
+        ]);
+        let encoded = encode_json_input(abi, "check", &args, false).unwrap();
+        let expected = encode_params(
+            &["address[]".to_string(), "int256[2][]".to_string()],
+            &[
+                "[0000000000000000000000000000000000000001]".to_string(),
+                "[[16,-1],[2,3]]".to_string(),
+            ],
+            true,
+        )
+        .unwrap();
+        assert_eq!(&encoded[8..], expected);
+        let named = serde_json::json!({
+            "limits": [["0x10", "-1"], [2, 3]],
+            "owners": ["0x0000000000000000000000000000000000000001"],
+        });
+        assert_eq!(
+            encode_json_input(abi, "check", &named, false).unwrap(),
+            encoded
+        );
+        assert!(encode_json_input(abi, "check", &serde_json::json!([[]]), false).is_err());
+        let mismatch = serde_json::json!({ "owners": [], "limits": [[1, 2, 3]] });
+        assert_eq!(
+            encode_json_input(abi, "check", &mismatch, false)
+                .unwrap_err()
+                .to_string(),
+            "ABI error: Argument limits of int256[2][]: Expect 2 elements, got 3"
+        );
+
+        let output =
+            decode_named_output(abi, "check", &format!("{}{:064x}", "f".repeat(64), 1)).unwrap();
+        assert_eq!(output, serde_json::json!({ "total": "-1", "1": true }));
+    }
+
+    #[test]
+    fn test_decode_revert_reason() {
+        let error = format!(
+            "0x08c379a0{}",
+            encode_params(&["string".to_string()], &["Not owner".to_string()], true).unwrap()
+        );
+        assert_eq!(decode_revert_reason(&error), Some("Not owner".to_string()));
+        let panic = format!("0x4e487b71{:064x}", 0x11);
+        assert_eq!(
+            decode_revert_reason(&panic),
+            Some("Panic(0x11): arithmetic overflow or underflow".to_string())
+        );
+        assert_eq!(decode_revert_reason("0x"), None);
+        assert_eq!(decode_revert_reason("0x12345678"), None);
+    }
 }
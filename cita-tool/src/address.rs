@@ -0,0 +1,150 @@
+use std::str::FromStr;
+
+use types::Address;
+
+use crate::client::remove_0x;
+use crate::crypto::{Encryption, Hashable};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+/// Encode the address with EIP-55 mixed-case checksum, with 0x
+pub fn to_checksum_address(address: &Address) -> String {
+    let lower = address.completed_lower_hex();
+    let hash = lower.as_bytes().crypt_hash(Encryption::Secp256k1);
+    let checksummed: String = lower
+        .chars()
+        .enumerate()
+        .map(|(index, c)| {
+            let nibble = (hash[index / 2] >> (if index % 2 == 0 { 4 } else { 0 })) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", checksummed)
+}
+
+/// Whether the address hex uses both lower and upper case letters, which means it has a checksum
+pub fn is_mixed_case(address: &str) -> bool {
+    let hex = remove_0x(address);
+    hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase())
+}
+
+/// Why the hex isn't an address of 20 bytes, None if it is one
+fn address_error(address: &str) -> Option<String> {
+    let hex = remove_0x(address);
+    if hex.is_empty() {
+        Some("it is empty".to_string())
+    } else if let Some(c) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        Some(format!("{:?} is not a hex digit", c))
+    } else if hex.len() % 2 == 1 {
+        Some(format!("odd number of hex digits, {}", hex.len()))
+    } else if hex.len() != 40 {
+        Some(format!("expect 20 bytes, got {}", hex.len() / 2))
+    } else {
+        None
+    }
+}
+
+/// Parse the address of 20 bytes in hex, with or without 0x
+///
+/// Example:
+/// ```rust
+/// extern crate cita_tool;
+///
+/// use cita_tool::parse_hex_address;
+///
+/// assert!(parse_hex_address("0xffffffffffffffffffffffffffffffffff020004").is_ok());
+/// assert!(parse_hex_address("ffffffffffffffffffffffffffffffffff020004").is_ok());
+/// assert!(parse_hex_address("0xffffffffffffffffffffffffffffffffff02000").is_err());
+/// assert!(parse_hex_address("0xffff").is_err());
+/// ```
+pub fn parse_hex_address(address: &str) -> Result<Address, ToolError> {
+    match address_error(address) {
+        Some(reason) => Err(ToolError::Customize(format!(
+            "Invalid address {}: {}",
+            address, reason
+        ))),
+        None => Ok(Address::from_str(remove_0x(address)).unwrap()),
+    }
+}
+
+/// Check the address given as the argument, the error names the argument
+pub(crate) fn check_address(argument: &str, address: &str) -> Result<(), ToolError> {
+    match address_error(address) {
+        Some(reason) => Err(ToolError::Customize(format!(
+            "Invalid address of `{}`, {}: {}",
+            argument, address, reason
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Parse the address, mixed-case addresses must have a correct checksum
+pub fn parse_checksum_address(address: &str) -> Result<Address, ToolError> {
+    let parsed = parse_hex_address(address)?;
+    if is_mixed_case(address) && remove_0x(&to_checksum_address(&parsed)) != remove_0x(address) {
+        return Err(ToolError::Customize(format!(
+            "Address checksum mismatch, expect {}",
+            to_checksum_address(&parsed)
+        )));
+    }
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        check_address, is_mixed_case, parse_checksum_address, parse_hex_address,
+        to_checksum_address,
+    };
+    use std::str::FromStr;
+    use types::Address;
+
+    #[test]
+    fn test_checksum() {
+        let addresses = [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ];
+        for address in addresses.iter() {
+            let parsed = Address::from_str(&address[2..].to_lowercase()).unwrap();
+            assert_eq!(&to_checksum_address(&parsed), address);
+            assert!(is_mixed_case(address));
+            assert_eq!(parse_checksum_address(address).unwrap(), parsed);
+        }
+
+        assert!(parse_checksum_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").is_ok());
+        assert!(parse_checksum_address("0x5AAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+    }
+
+    #[test]
+    fn test_parse_hex_address() {
+        let address = "ffffffffffffffffffffffffffffffffff020004";
+        assert_eq!(
+            parse_hex_address(address).unwrap(),
+            parse_hex_address(&format!("0x{}", address)).unwrap()
+        );
+        let too_long = format!("0x{}00", address);
+        let invalid_digit = format!("0x{}g", &address[1..]);
+        for (invalid, reason) in &[
+            ("0x", "it is empty"),
+            (invalid_digit.as_str(), "'g' is not a hex digit"),
+            (&too_long[..41], "odd number of hex digits, 39"),
+            (too_long.as_str(), "expect 20 bytes, got 21"),
+        ] {
+            assert!(parse_hex_address(invalid)
+                .unwrap_err()
+                .to_string()
+                .ends_with(reason));
+        }
+        assert!(check_address("to", "0x1234")
+            .unwrap_err()
+            .to_string()
+            .contains("`to`"));
+    }
+}
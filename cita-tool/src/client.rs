@@ -1,17 +1,121 @@
 #[cfg(all(feature = "rustls", feature = "openssl"))]
 compile_error!("features `rustls` and `openssl` are mutually exclusive");
+#[cfg(all(feature = "http", not(any(feature = "rustls", feature = "openssl"))))]
+compile_error!("feature `http` needs `rustls` or `openssl`");
 
 /// Basic client api, for Low-level interface
+#[cfg(feature = "http")]
 pub mod basic;
+/// gRPC transport, talk to the controller and the executor components directly
+#[cfg(feature = "grpc")]
+pub mod grpc;
+/// Light client, verify block headers against the validator set
+#[cfg(feature = "http")]
+pub mod light;
+/// Check the responses strictly against the shapes of the results of CITA
+#[cfg(feature = "http")]
+pub mod strict;
 /// System contract client api, call system contract more easy
+#[cfg(feature = "http")]
 pub mod system_contract;
+/// Verify blocks against the hashes and the roots computed locally
+#[cfg(feature = "http")]
+pub mod verify;
+/// Watch the chain, polling new blocks and logs
+#[cfg(feature = "http")]
+pub mod watch;
 
+#[cfg(feature = "http")]
+mod alias;
+mod bench;
+#[cfg(feature = "http")]
+mod block_time;
+#[cfg(feature = "http")]
+mod cache;
+#[cfg(feature = "http")]
+mod chain;
+#[cfg(feature = "http")]
+mod chain_version;
+#[cfg(feature = "http")]
+mod circuit_breaker;
+#[cfg(feature = "http")]
+mod fee;
+#[cfg(feature = "http")]
+mod filter;
+#[cfg(feature = "http")]
+mod limits;
+#[cfg(feature = "http")]
+mod multicall;
+#[cfg(feature = "http")]
+mod node_select;
+#[cfg(feature = "http")]
+mod notice;
+mod pending;
+mod policy;
+#[cfg(feature = "http")]
+mod quota_report;
+#[cfg(feature = "http")]
+mod rate_limit;
+#[cfg(feature = "http")]
+mod release;
+mod schema;
+#[cfg(feature = "http")]
+mod simulate;
+mod storage_layout;
+#[cfg(feature = "http")]
+mod timing;
 mod transaction_option;
+#[cfg(feature = "http")]
+mod txpool;
 
+#[cfg(feature = "http")]
+pub use self::alias::MethodAliases;
+pub use self::bench::{bench_series_csv, BenchRecorder, BenchSecond, BenchTotals, LatencySummary};
+#[cfg(feature = "http")]
+pub use self::block_time::BlockTime;
+#[cfg(feature = "http")]
+pub use self::cache::ResponseCache;
+#[cfg(feature = "http")]
+pub use self::chain::ChainInfo;
+#[cfg(feature = "http")]
+pub use self::chain_version::{ChainVersion, Feature, Release};
+#[cfg(feature = "http")]
+pub use self::circuit_breaker::{CircuitBreaker, CircuitState, Transition};
+#[cfg(feature = "http")]
+pub use self::fee::FeeEstimate;
+#[cfg(feature = "http")]
+pub use self::limits::{intrinsic_quota, ChainLimits};
+#[cfg(feature = "http")]
+pub use self::multicall::{ReadCall, ReadCallResult};
+#[cfg(feature = "http")]
+pub use self::node_select::NodeSelector;
+#[cfg(feature = "http")]
+pub use self::notice::{Notice, Notifier};
+pub use self::pending::{DuplicatePolicy, PendingStore, PendingTransaction};
+pub use self::policy::{OutgoingTransaction, SendPolicy};
+#[cfg(feature = "http")]
+pub use self::quota_report::{compare_quota, quota_baseline, QuotaCall, QuotaStatus, QuotaUsage};
+#[cfg(feature = "http")]
+pub use self::rate_limit::RateLimiter;
+#[cfg(feature = "http")]
+pub use self::release::{check_sha256, parse_version, sha256_hex, GithubRelease};
+pub use self::schema::{diff_shapes, ChangeKind, SchemaChange, Shape};
+#[cfg(feature = "http")]
+pub use self::simulate::Simulation;
+pub use self::storage_layout::{StorageEntry, StorageLayout, StorageType};
+#[cfg(feature = "http")]
+pub use self::timing::RequestTimings;
 pub use self::transaction_option::TransactionOptions;
+#[cfg(feature = "http")]
+pub use self::txpool::{PoolSource, TxPool};
 
+#[cfg(feature = "http")]
 use hyper::Uri;
 use std::str;
+use std::str::FromStr;
+use types::U256;
+
+use crate::error::ToolError;
 
 /// Remove hexadecimal prefix "0x" or "0X".
 /// Example:
@@ -39,7 +143,33 @@ pub fn remove_0x(hex: &str) -> &str {
     hex
 }
 
+/// Parse hexadecimal into U256, with or without "0x"
+///
+/// Unlike `U256::from_str`, which panics, the values longer than 256 bits are an error.
+/// Example:
+/// ```rust
+/// extern crate cita_tool;
+///
+/// use cita_tool::{hex_to_u256, U256};
+///
+/// assert_eq!(hex_to_u256("0x0100").unwrap(), U256::from(256));
+/// assert!(hex_to_u256(&format!("0x1{}", "0".repeat(64))).is_err());
+/// ```
+pub fn hex_to_u256(hex: &str) -> Result<U256, ToolError> {
+    let digits = remove_0x(hex).trim_start_matches('0');
+    if digits.len() > 64 {
+        return Err(ToolError::Customize(format!(
+            "{} is longer than 256 bits",
+            hex
+        )));
+    } else if digits.is_empty() && !remove_0x(hex).is_empty() {
+        return Ok(U256::zero());
+    }
+    U256::from_str(digits).map_err(|e| ToolError::Customize(e.to_string()))
+}
+
 /// Verify the validity of the url address
+#[cfg(feature = "http")]
 #[inline]
 pub fn parse_url(url: &str) -> Result<Uri, String> {
     url.parse().map_err(|_| "Invalid address".to_string())
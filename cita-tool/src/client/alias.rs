@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+
+use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue};
+
+/// The jsonrpc error code of an unknown method
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The names of the methods across the versions of CITA, the current one first
+///
+/// The versions before 0.18 prefixed the methods by `cita_`, `eth_` and `net_`.
+const ALIASES: &[&[&str]] = &[
+    &["blockNumber", "cita_blockNumber", "getBlockNumber"],
+    &[
+        "sendRawTransaction",
+        "sendTransaction",
+        "cita_sendTransaction",
+    ],
+    &["getBlockByNumber", "cita_getBlockByNumber"],
+    &["getBlockByHash", "cita_getBlockByHash"],
+    &["getTransaction", "cita_getTransaction"],
+    &["getTransactionProof", "cita_getTransactionProof"],
+    &["getMetaData", "cita_getMetaData"],
+    &["peerCount", "net_peerCount"],
+    &["getTransactionReceipt", "eth_getTransactionReceipt"],
+    &["getTransactionCount", "eth_getTransactionCount"],
+    &["getLogs", "eth_getLogs"],
+    &["call", "eth_call"],
+    &["getCode", "eth_getCode"],
+    &["getAbi", "eth_getAbi"],
+    &["getBalance", "eth_getBalance"],
+    &["newFilter", "eth_newFilter"],
+    &["newBlockFilter", "eth_newBlockFilter"],
+    &["uninstallFilter", "eth_uninstallFilter"],
+    &["getFilterChanges", "eth_getFilterChanges"],
+    &["getFilterLogs", "eth_getFilterLogs"],
+];
+
+/// The method names each node accepts, learnt when a node doesn't know a name
+///
+/// A request is sent by the name the node is known to accept, the current name at first.
+/// When the node answers it doesn't know the method, the other names are tried in order
+/// and the one it accepts is kept for the node. The clones share the names, and they are
+/// saved to the file when there is one.
+#[derive(Debug, Clone, Default)]
+pub struct MethodAliases {
+    path: Option<PathBuf>,
+    /// The url -> the current name -> the name the node accepts
+    resolved: Arc<RwLock<HashMap<String, HashMap<String, String>>>>,
+}
+
+impl MethodAliases {
+    /// The names learnt in this session only
+    pub fn new() -> Self {
+        MethodAliases::default()
+    }
+
+    /// The names saved in the file, the new names are saved to it
+    pub fn from_file<P: Into<PathBuf>>(path: P) -> Self {
+        let path = path.into();
+        let resolved = fs::read(&path)
+            .ok()
+            .and_then(|content| serde_json::from_slice(&content).ok())
+            .unwrap_or_default();
+        MethodAliases {
+            path: Some(path),
+            resolved: Arc::new(RwLock::new(resolved)),
+        }
+    }
+
+    /// The names of the method, the current one first, empty if it has no alias
+    pub fn names(method: &str) -> &'static [&'static str] {
+        ALIASES
+            .iter()
+            .find(|names| names.contains(&method))
+            .copied()
+            .unwrap_or(&[])
+    }
+
+    /// The name of the method the node accepts, the method itself if not learnt
+    pub fn resolve(&self, url: &str, method: &str) -> String {
+        let current = Self::names(method).first().copied().unwrap_or(method);
+        self.resolved
+            .read()
+            .unwrap()
+            .get(url)
+            .and_then(|names| names.get(current))
+            .cloned()
+            .unwrap_or_else(|| method.to_owned())
+    }
+
+    /// The names learnt for the node, by the current names
+    pub fn resolved(&self, url: &str) -> HashMap<String, String> {
+        self.resolved
+            .read()
+            .unwrap()
+            .get(url)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Keep the name the node accepts for the method
+    pub fn learn(&self, url: &str, method: &str, name: &str) {
+        let current = match Self::names(method).first() {
+            Some(current) => *current,
+            None => return,
+        };
+        let mut resolved = self.resolved.write().unwrap();
+        resolved
+            .entry(url.to_owned())
+            .or_default()
+            .insert(current.to_owned(), name.to_owned());
+        if let Some(ref path) = self.path {
+            if let Some(dir) = path.parent() {
+                let _ = fs::create_dir_all(dir);
+            }
+            // A failed save only means probing again next time
+            if let Ok(content) = serde_json::to_vec_pretty(&*resolved) {
+                let _ = fs::write(path, content);
+            }
+        }
+    }
+
+    /// The params with the method renamed to the name the node accepts
+    pub(crate) fn rename(&self, url: &str, params: JsonRpcParams) -> JsonRpcParams {
+        match method(&params) {
+            Some(method) => {
+                let name = self.resolve(url, &method);
+                params.insert("method", ParamsValue::String(name))
+            }
+            None => params,
+        }
+    }
+}
+
+/// The method of the params
+pub(crate) fn method(params: &JsonRpcParams) -> Option<String> {
+    params.method().map(ToOwned::to_owned)
+}
+
+/// Whether the node doesn't know the method of the request
+pub(crate) fn is_method_not_found(response: &JsonRpcResponse) -> bool {
+    response
+        .error()
+        .is_some_and(|err| err.code() == METHOD_NOT_FOUND)
+}
+
+#[cfg(test)]
+mod test {
+    use super::MethodAliases;
+    use crate::rpctypes::{JsonRpcParams, ParamsValue};
+
+    #[test]
+    fn test_method_aliases() {
+        let url = "http://127.0.0.1:1337/";
+        let aliases = MethodAliases::new();
+        assert_eq!(aliases.resolve(url, "blockNumber"), "blockNumber");
+        assert!(MethodAliases::names("peersInfo").is_empty());
+
+        // Learnt by any of the names, for the node only
+        aliases.learn(url, "cita_blockNumber", "cita_blockNumber");
+        assert_eq!(aliases.resolve(url, "blockNumber"), "cita_blockNumber");
+        assert_eq!(aliases.resolve(url, "getBlockNumber"), "cita_blockNumber");
+        assert_eq!(
+            aliases.resolve("http://127.0.0.1:1338/", "blockNumber"),
+            "blockNumber"
+        );
+        let params = aliases.rename(
+            url,
+            JsonRpcParams::new().insert("method", ParamsValue::String("blockNumber".to_string())),
+        );
+        match params.get("method") {
+            Some(ParamsValue::String(method)) => assert_eq!(method, "cita_blockNumber"),
+            _ => panic!("The method is lost"),
+        }
+
+        let path = std::env::temp_dir().join(format!("cita-aliases-{}.json", std::process::id()));
+        MethodAliases::from_file(&path).learn(url, "sendRawTransaction", "sendTransaction");
+        assert_eq!(
+            MethodAliases::from_file(&path).resolve(url, "sendRawTransaction"),
+            "sendTransaction"
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,25 +1,36 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::str;
 use std::str::FromStr;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::{str, u64};
+use std::sync::{Arc, RwLock};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::LowerHex;
 use failure::Fail;
-use futures::{future::join_all, future::JoinAll, sync, Future, Stream};
+use futures::{future, future::join_all, future::JoinAll, sync, Future, Stream};
 use hex::{decode, encode};
 use hyper::{client::HttpConnector, Body, Client as HyperClient, Request, Uri};
 use protobuf::{parse_from_bytes, Message};
 use serde;
 use serde_json;
 use tokio;
+use tokio::timer::Delay;
 use types::U256;
-use uuid::Uuid;
 
-use crate::client::{remove_0x, TransactionOptions};
-use crate::crypto::PrivateKey;
+use crate::address::{check_address, parse_checksum_address};
+use crate::client::alias::{is_method_not_found, method};
+use crate::client::filter::{FilterKind, FilterRegistry};
+use crate::client::strict::strict_response;
+use crate::client::{
+    remove_0x, ChainVersion, CircuitBreaker, DuplicatePolicy, MethodAliases, NodeSelector, Notice,
+    Notifier, OutgoingTransaction, PendingStore, PendingTransaction, RateLimiter, RequestTimings,
+    ResponseCache, SendPolicy, TransactionOptions, Transition,
+};
+use crate::crypto::{PrivateKey, Signer};
 use crate::error::ToolError;
-use crate::protos::{Transaction, UnverifiedTransaction};
-use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue, ResponseValue};
+use crate::protos::{Transaction, TransactionVersion, UnverifiedTransaction};
+use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue, Quantity, ResponseValue};
 
 const BLOCK_NUMBER: &str = "blockNumber";
 const GET_META_DATA: &str = "getMetaData";
@@ -53,6 +64,16 @@ const GET_VERSION: &str = "getVersion";
 
 const ESTIMATE_QUOTA: &str = "estimateQuota";
 
+/// The methods which must go to the configured url, the filters live in the node
+const NODE_BOUND_METHODS: &[&str] = &[
+    SEND_RAW_TRANSACTION,
+    NEW_FILTER,
+    NEW_BLOCK_FILTER,
+    UNINSTALL_FILTER,
+    GET_FILTER_CHANGES,
+    GET_FILTER_LOGS,
+];
+
 /// Store action target address
 pub const STORE_ADDRESS: &str = "0xffffffffffffffffffffffffffffffffff010000";
 /// StoreAbi action target address
@@ -69,14 +90,55 @@ pub const AMEND_KV_H256: &str = "0x03";
 /// amend account balance
 pub const AMEND_BALANCE: &str = "0x05";
 
+/// Default number of blocks queried by one getLogs request
+pub const DEFAULT_LOG_CHUNK_SIZE: u64 = 1000;
+/// How many getLogs chunks are requested at the same time
+const LOG_CHUNK_PARALLELISM: usize = 4;
+
+/// The result of waiting for a transaction receipt
+#[derive(Debug)]
+pub enum ReceiptStatus {
+    /// The receipt is on chain with enough confirmations
+    Confirmed(JsonRpcResponse),
+    /// The receipt was seen once, but the transaction is no longer on chain
+    Dropped,
+    /// No confirmed receipt before timeout
+    Timeout,
+}
+
 /// Jsonrpc client, Only to one chain
+///
+/// The requests are run by a runtime in a background thread and all the methods
+/// sending them take `&self`, so one client behind an `Arc` can be used by many threads.
 pub struct Client {
     id: AtomicUsize,
     url: Uri,
     sender: sync::mpsc::UnboundedSender<Box<dyn Future<Item = (), Error = ()> + Send + 'static>>,
-    chain_id: Option<U256>,
+    /// Read from the metadata on first use
+    chain_id: RwLock<Option<U256>>,
+    /// The url -> the version detected for the node
+    chain_versions: Arc<RwLock<HashMap<String, ChainVersion>>>,
     private_key: Option<PrivateKey>,
+    signer: Option<Arc<dyn Signer>>,
+    send_policy: Option<Arc<dyn SendPolicy>>,
     debug: bool,
+    pending: PendingStore,
+    duplicate_policy: DuplicatePolicy,
+    fee_check: bool,
+    chain_check: bool,
+    limit_check: bool,
+    max_fee: Option<U256>,
+    filters: FilterRegistry,
+    log_chunk_size: u64,
+    cache: Option<ResponseCache>,
+    notifier: Option<Notifier>,
+    rate_limiter: Option<RateLimiter>,
+    circuit_breaker: Option<CircuitBreaker>,
+    node_selector: Option<NodeSelector>,
+    method_aliases: Option<MethodAliases>,
+    strict: bool,
+    extra_params: HashMap<String, ParamsValue>,
+    timings: Option<RequestTimings>,
 }
 
 impl Client {
@@ -86,7 +148,7 @@ impl Client {
 
         ::std::thread::spawn(move || {
             let task = receiver
-                .map_err(|_| ::std::io::Error::new(::std::io::ErrorKind::Other, ""))
+                .map_err(|_| ::std::io::Error::other(""))
                 .for_each(|item| {
                     tokio::spawn(item);
                     Ok(())
@@ -100,9 +162,29 @@ impl Client {
             id: AtomicUsize::new(0),
             url: "http://127.0.0.1:1337".parse().unwrap(),
             sender,
-            chain_id: None,
+            chain_id: RwLock::new(None),
+            chain_versions: Arc::new(RwLock::new(HashMap::new())),
             private_key: None,
+            signer: None,
+            send_policy: None,
             debug: false,
+            pending: PendingStore::new(),
+            duplicate_policy: DuplicatePolicy::default(),
+            fee_check: false,
+            chain_check: false,
+            limit_check: false,
+            max_fee: None,
+            filters: FilterRegistry::default(),
+            log_chunk_size: DEFAULT_LOG_CHUNK_SIZE,
+            cache: None,
+            notifier: None,
+            rate_limiter: None,
+            circuit_breaker: None,
+            node_selector: None,
+            method_aliases: None,
+            strict: false,
+            extra_params: HashMap::new(),
+            timings: None,
         }
     }
 
@@ -119,9 +201,14 @@ impl Client {
         &self.url
     }
 
+    /// The versions detected for the nodes, shared by the clones
+    pub(crate) fn chain_versions(&self) -> &RwLock<HashMap<String, ChainVersion>> {
+        &self.chain_versions
+    }
+
     /// Set chain id
     pub fn set_chain_id(&mut self, chain_id: U256) -> &mut Self {
-        self.chain_id = Some(chain_id);
+        *self.chain_id.get_mut().unwrap() = Some(chain_id);
         self
     }
 
@@ -139,6 +226,30 @@ impl Client {
         self.private_key.as_ref()
     }
 
+    /// Set the signer of the transactions, it signs instead of the private key,
+    /// e.g. a key kept by an HSM
+    pub fn set_signer(mut self, signer: Option<Arc<dyn Signer>>) -> Self {
+        self.signer = signer;
+        self
+    }
+
+    /// Get the signer
+    pub fn signer(&self) -> Option<&dyn Signer> {
+        self.signer.as_deref()
+    }
+
+    /// Set the policy the signed transactions have to pass before they're sent
+    pub fn set_send_policy(mut self, policy: Option<Arc<dyn SendPolicy>>) -> Self {
+        self.send_policy = policy;
+        self
+    }
+
+    /// The signer, else the private key
+    pub(crate) fn transaction_signer(&self) -> Option<&dyn Signer> {
+        self.signer()
+            .or_else(|| self.private_key().map(|key| key as &dyn Signer))
+    }
+
     /// Get debug
     pub fn debug(&self) -> bool {
         self.debug
@@ -150,16 +261,454 @@ impl Client {
         self
     }
 
+    /// Set the receiver of the notices, they are dropped without one
+    pub fn set_notifier(mut self, notifier: Option<Notifier>) -> Self {
+        self.notifier = notifier;
+        self
+    }
+
+    /// Report a notice to the notifier
+    pub(crate) fn notify(&self, notice: Notice) {
+        if let Some(ref notifier) = self.notifier {
+            notifier(&notice);
+        }
+    }
+
+    /// Set what to do when sending a transaction whose content was already sent in this session
+    pub fn set_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// Transactions sent in this session
+    pub fn pending(&self) -> &PendingStore {
+        &self.pending
+    }
+
+    /// Check the fee and the sender balance before sending transactions in charge mode,
+    /// off by default
+    pub fn set_fee_check(mut self, check: bool) -> Self {
+        self.fee_check = check;
+        self
+    }
+
+    /// Get fee check mode
+    pub fn fee_check(&self) -> bool {
+        self.fee_check
+    }
+
+    /// Compare the chain id of the transactions with the chain of the node before sending
+    pub fn set_chain_check(mut self, check: bool) -> Self {
+        self.chain_check = check;
+        self
+    }
+
+    /// Get chain check mode
+    pub fn chain_check(&self) -> bool {
+        self.chain_check
+    }
+
+    /// Check the data size and the quota of the transactions against the limits of the chain
+    /// before sending
+    pub fn set_limit_check(mut self, check: bool) -> Self {
+        self.limit_check = check;
+        self
+    }
+
+    /// Get limit check mode
+    pub fn limit_check(&self) -> bool {
+        self.limit_check
+    }
+
+    /// Refuse to send transactions whose max fee is above the limit, imply fee check
+    pub fn set_max_fee(mut self, max_fee: Option<U256>) -> Self {
+        if max_fee.is_some() {
+            self.fee_check = true;
+        }
+        self.max_fee = max_fee;
+        self
+    }
+
+    /// Get the max fee limit
+    pub fn max_fee(&self) -> Option<U256> {
+        self.max_fee
+    }
+
+    /// Set the number of blocks queried by one getLogs request,
+    /// larger ranges are split into chunks, 0 never splits
+    pub fn set_log_chunk_size(mut self, size: u64) -> Self {
+        self.log_chunk_size = size;
+        self
+    }
+
+    /// Get the number of blocks queried by one getLogs request
+    pub fn log_chunk_size(&self) -> u64 {
+        self.log_chunk_size
+    }
+
+    /// Split the getLogs range into chunks, empty when the range needn't be split
+    fn log_chunks(
+        &self,
+        from: Option<&str>,
+        to: Option<&str>,
+    ) -> Result<Vec<(u64, u64)>, ToolError> {
+        let from = match from.and_then(tag_height) {
+            Some(from) if self.log_chunk_size > 0 => from,
+            _ => return Ok(Vec::new()),
+        };
+        let to = match to.and_then(tag_height) {
+            Some(to) => to,
+            None => self.get_current_height()?,
+        };
+        if to < from || to - from < self.log_chunk_size {
+            return Ok(Vec::new());
+        }
+        Ok((from..=to)
+            .step_by(self.log_chunk_size as usize)
+            .map(|start| (start, to.min(start.saturating_add(self.log_chunk_size - 1))))
+            .collect())
+    }
+
+    /// Cache the responses of immutable queries, such as blocks and receipts, on disk
+    pub fn set_cache(mut self, cache: Option<ResponseCache>) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Get the response cache
+    pub fn cache(&self) -> Option<&ResponseCache> {
+        self.cache.as_ref()
+    }
+
+    /// Delay the requests over the limits of the urls
+    pub fn set_rate_limiter(mut self, rate_limiter: Option<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Get the rate limiter
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Stop sending to the urls which keep failing
+    pub fn set_circuit_breaker(mut self, circuit_breaker: Option<CircuitBreaker>) -> Self {
+        self.circuit_breaker = circuit_breaker;
+        self
+    }
+
+    /// Get the circuit breaker
+    pub fn circuit_breaker(&self) -> Option<&CircuitBreaker> {
+        self.circuit_breaker.as_ref()
+    }
+
+    /// Route the reads to the node chosen by the selector, the transactions and the
+    /// filters still go to the url
+    pub fn set_node_selector(mut self, node_selector: Option<NodeSelector>) -> Self {
+        self.node_selector = node_selector;
+        self
+    }
+
+    /// Get the node selector
+    pub fn node_selector(&self) -> Option<&NodeSelector> {
+        self.node_selector.as_ref()
+    }
+
+    /// Use the older names of the methods for the nodes which don't know the current ones
+    pub fn set_method_aliases(mut self, method_aliases: Option<MethodAliases>) -> Self {
+        self.method_aliases = method_aliases;
+        self
+    }
+
+    /// Get the method aliases
+    pub fn method_aliases(&self) -> Option<&MethodAliases> {
+        self.method_aliases.as_ref()
+    }
+
+    /// Fail on the responses with unknown fields, missing fields or non-canonical hex,
+    /// instead of reading what can be read
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Get strict mode
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Set the fields added to the body of every request, such as the tenant id a gateway
+    /// requires, the fields of the request itself are never replaced
+    pub fn set_extra_params(mut self, extra_params: HashMap<String, ParamsValue>) -> Self {
+        self.extra_params = extra_params;
+        self
+    }
+
+    /// Get the fields added to every request
+    pub fn extra_params(&self) -> &HashMap<String, ParamsValue> {
+        &self.extra_params
+    }
+
+    /// Record the round-trip time of every request
+    pub fn set_timings(mut self, timings: Option<RequestTimings>) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Get the round-trip times recorded
+    pub fn timings(&self) -> Option<&RequestTimings> {
+        self.timings.as_ref()
+    }
+
+    /// Probe the latencies of the nodes of the selector by `blockNumber`
+    pub fn probe_nodes(&self) -> Result<Vec<(Uri, Option<Duration>)>, ToolError> {
+        let selector = match self.node_selector {
+            Some(ref selector) => selector,
+            None => return Ok(Vec::new()),
+        };
+        let params = JsonRpcParams::new()
+            .insert("jsonrpc", ParamsValue::String("2.0".to_string()))
+            .insert("method", ParamsValue::String(String::from(BLOCK_NUMBER)))
+            .insert("id", ParamsValue::Int(1));
+        let client = create_client();
+        let probes: Vec<_> = selector
+            .nodes()
+            .iter()
+            .map(|node| {
+                let start = Instant::now();
+                self.post(&client, node.clone(), &params)
+                    .then(move |result| {
+                        Ok::<_, ToolError>(match result {
+                            Ok(ref response) if response.is_ok() => Some(start.elapsed()),
+                            _ => None,
+                        })
+                    })
+            })
+            .collect();
+
+        let (tx, rx) = sync::oneshot::channel();
+        let req = join_all(probes)
+            .then(move |res| tx.send(res))
+            .map(|_| ())
+            .map_err(|_| ());
+        self.sender
+            .unbounded_send(Box::new(req))
+            .map_err(|e| ToolError::Customize(e.to_string()))?;
+        let latencies = rx
+            .wait()
+            .map_err(|e| ToolError::Customize(e.to_string()))??;
+        selector.update(latencies);
+        Ok(selector.latencies())
+    }
+
+    /// The node to send the params to
+    fn target_url(&self, params: &[JsonRpcParams]) -> Uri {
+        let selector = match self.node_selector {
+            Some(ref selector) => selector,
+            None => return self.url.clone(),
+        };
+        let node_bound = params.iter().any(|param| match param.get("method") {
+            Some(ParamsValue::String(method)) => NODE_BOUND_METHODS.contains(&method.as_str()),
+            _ => false,
+        });
+        if node_bound {
+            return self.url.clone();
+        }
+        if selector.needs_probe() {
+            let _ = self.probe_nodes();
+        }
+        selector.select().unwrap_or_else(|| self.url.clone())
+    }
+
+    /// Report the transition of the circuit of the url
+    fn notify_circuit(notifier: &Option<Notifier>, url: &str, transition: Option<Transition>) {
+        if let (Some(notifier), Some((from, to))) = (notifier, transition) {
+            notifier(&Notice::Circuit { url, from, to });
+        }
+    }
+
+    /// The params with the extra params they don't have
+    fn request_body(&self, params: &JsonRpcParams) -> String {
+        if self.extra_params.is_empty() {
+            return serde_json::to_string(params).unwrap();
+        }
+        let mut body = serde_json::to_value(params).unwrap();
+        if let Some(fields) = body.as_object_mut() {
+            for (key, value) in &self.extra_params {
+                fields
+                    .entry(key.to_owned())
+                    .or_insert_with(|| serde_json::to_value(value).unwrap());
+            }
+        }
+        body.to_string()
+    }
+
+    /// Post the request to the url, after the delay of the rate limiter,
+    /// unless the circuit of the url is open
+    fn post(
+        &self,
+        client: &HttpsClient,
+        url: Uri,
+        params: &JsonRpcParams,
+    ) -> Box<dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send> {
+        let node = url.to_string();
+        if let Some(ref breaker) = self.circuit_breaker {
+            let (allowed, transition) = breaker.allow(&node);
+            Client::notify_circuit(&self.notifier, &node, transition);
+            if !allowed {
+                return Box::new(future::err(ToolError::Customize(format!(
+                    "The circuit of {} is open, the node keeps failing",
+                    node
+                ))));
+            }
+        }
+        let at = self
+            .rate_limiter
+            .as_ref()
+            .map(|limiter| limiter.reserve(&url));
+        let req: Request<Body> = Request::builder()
+            .uri(url)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(self.request_body(params)))
+            .unwrap();
+        let strict = if self.strict { method(params) } else { None };
+        let client = client.clone();
+        let timings = self.timings.clone();
+        // Lazy, so the round trip starts after the delay of the rate limiter
+        let request = future::lazy(move || {
+            let sent = Instant::now();
+            client
+                .request(req)
+                .and_then(|res| res.into_body().concat2())
+                .then(move |response| {
+                    if let Some(timings) = timings {
+                        timings.record(sent.elapsed());
+                    }
+                    response
+                })
+        })
+        .map_err(ToolError::Hyper)
+        .and_then(move |response| {
+            if let Some(method) = strict {
+                strict_response(&method, &response)?;
+            }
+            serde_json::from_slice::<JsonRpcResponse>(&response).map_err(ToolError::SerdeJson)
+        });
+        let request: Box<dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send> =
+            match at {
+                Some(at) if at > Instant::now() => Box::new(
+                    Delay::new(at)
+                        .map_err(|e| ToolError::Customize(e.to_string()))
+                        .and_then(|_| request),
+                ),
+                _ => Box::new(request),
+            };
+        match self.circuit_breaker.clone() {
+            Some(breaker) => {
+                let notifier = self.notifier.clone();
+                Box::new(request.then(move |result| {
+                    let transition = breaker.record(&node, result.is_ok());
+                    Client::notify_circuit(&notifier, &node, transition);
+                    result
+                }))
+            }
+            None => request,
+        }
+    }
+
+    /// Filters installed by the client
+    pub(crate) fn filters(&self) -> &FilterRegistry {
+        &self.filters
+    }
+
     /// Send requests
     pub fn send_request<T: Iterator<Item = JsonRpcParams>>(
         &self,
         params: T,
     ) -> Result<Vec<JsonRpcResponse>, ToolError> {
         let params = params.collect::<Vec<JsonRpcParams>>();
+        let url = self.target_url(&params);
+        let cache = match self.cache {
+            Some(ref cache) => cache,
+            None => return self.fetch(url, params),
+        };
+
+        let namespace = url.to_string();
+        let mut responses: Vec<Option<JsonRpcResponse>> = params
+            .iter()
+            .map(|param| cache.get(&namespace, param))
+            .collect();
+        let misses: Vec<JsonRpcParams> = params
+            .iter()
+            .zip(responses.iter())
+            .filter(|(_, response)| response.is_none())
+            .map(|(param, _)| param.clone())
+            .collect();
+        if !misses.is_empty() {
+            let mut fetched = misses.iter().zip(self.fetch(url, misses.clone())?);
+            for response in responses.iter_mut().filter(|response| response.is_none()) {
+                let (param, fetched) = fetched.next().unwrap();
+                cache.put(&namespace, param, &fetched);
+                *response = Some(fetched);
+            }
+        }
+        Ok(responses.into_iter().map(Option::unwrap).collect())
+    }
 
-        let reqs = self.make_requests_with_params_list(params.into_iter());
-
-        self.run(reqs)
+    /// Send the params to the node by the method names it accepts, a request the node
+    /// doesn't know the method of is sent again by the other names of the method
+    fn fetch(
+        &self,
+        url: Uri,
+        params: Vec<JsonRpcParams>,
+    ) -> Result<Vec<JsonRpcResponse>, ToolError> {
+        let params = match self.known_chain_version(&url.to_string()) {
+            Some(version) => params
+                .into_iter()
+                .map(|param| version.rename(param))
+                .collect(),
+            None => params,
+        };
+        let aliases = match self.method_aliases {
+            Some(ref aliases) => aliases,
+            None => return self.run(self.make_requests_with_params_list(url, params.into_iter())),
+        };
+        let node = url.to_string();
+        let params: Vec<JsonRpcParams> = params
+            .into_iter()
+            .map(|param| aliases.rename(&node, param))
+            .collect();
+        let reqs = self.make_requests_with_params_list(url.clone(), params.clone().into_iter());
+        let mut responses = self.run(reqs)?;
+        for (param, response) in params.iter().zip(responses.iter_mut()) {
+            let sent = match method(param) {
+                Some(ref sent) if is_method_not_found(response) => sent.to_owned(),
+                _ => continue,
+            };
+            for name in MethodAliases::names(&sent)
+                .iter()
+                .filter(|name| **name != sent)
+            {
+                let retry = param
+                    .clone()
+                    .insert("method", ParamsValue::String(name.to_string()));
+                let reqs =
+                    self.make_requests_with_params_list(url.clone(), Some(retry).into_iter());
+                let retried = self.run(reqs)?.remove(0);
+                if !is_method_not_found(&retried) {
+                    aliases.learn(&node, &sent, name);
+                    self.notify(Notice::Alias {
+                        url: &node,
+                        method: MethodAliases::names(&sent)[0],
+                        alias: name,
+                    });
+                    *response = retried;
+                    break;
+                }
+            }
+        }
+        Ok(responses)
     }
 
     /// Send multiple params to one node
@@ -173,6 +722,59 @@ impl Client {
         self.run(reqs)
     }
 
+    /// Send the params to every node, each node gets its own result,
+    /// so the failing nodes don't fail the others
+    pub fn send_request_to_nodes<T: Iterator<Item = Uri>>(
+        &self,
+        urls: T,
+        params: JsonRpcParams,
+    ) -> Result<Vec<Result<JsonRpcResponse, ToolError>>, ToolError> {
+        if self.debug {
+            self.notify(Notice::Request(&params));
+        }
+        let client = create_client();
+        let reqs: Vec<_> = urls
+            .map(|url| self.post(&client, url, &params).then(Ok::<_, ToolError>))
+            .collect();
+
+        let (tx, rx) = sync::oneshot::channel();
+        let req = join_all(reqs)
+            .then(move |res| tx.send(res))
+            .map(|_| ())
+            .map_err(|_| ());
+        self.sender
+            .unbounded_send(Box::new(req))
+            .map_err(|e| ToolError::Customize(e.to_string()))?;
+        rx.wait().map_err(|e| ToolError::Customize(e.to_string()))?
+    }
+
+    /// Post a json body to an arbitrary http endpoint, such as a webhook,
+    /// return the response status code
+    pub fn post_json(&self, url: &str, body: &serde_json::Value) -> Result<u16, ToolError> {
+        let url: Uri = url
+            .parse()
+            .map_err(|_| ToolError::Customize(format!("Invalid url: {}", url)))?;
+        let req: Request<Body> = Request::builder()
+            .uri(url)
+            .method("POST")
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+
+        let (tx, rx) = sync::oneshot::channel::<Result<u16, ToolError>>();
+        let req = create_client()
+            .request(req)
+            .map(|res| res.status().as_u16())
+            .map_err(ToolError::Hyper)
+            .then(move |res| tx.send(res))
+            .map(|_| ())
+            .map_err(|_| ());
+        self.sender
+            .unbounded_send(Box::new(req))
+            .map_err(|e| ToolError::Customize(e.to_string()))?;
+        rx.wait().map_err(|e| ToolError::Customize(e.to_string()))?
+    }
+
     #[inline]
     fn make_requests_with_all_url<T: Iterator<Item = Uri>>(
         &self,
@@ -187,44 +789,23 @@ impl Client {
         );
 
         if self.debug {
-            Self::debug_request(vec![&params].into_iter())
+            self.notify(Notice::Request(&params));
         }
 
         let client = create_client();
-        let mut reqs = Vec::with_capacity(100);
-        urls.for_each(|url| {
-            let req: Request<Body> = Request::builder()
-                .uri(url)
-                .method("POST")
-                .header("Content-Type", "application/json")
-                .body(Body::from(serde_json::to_string(&params).unwrap()))
-                .unwrap();
-            let future: Box<
-                dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send,
-            > = Box::new(
-                client
-                    .request(req)
-                    .and_then(|res| res.into_body().concat2())
-                    .map_err(ToolError::Hyper)
-                    .and_then(|response| {
-                        serde_json::from_slice::<JsonRpcResponse>(&response)
-                            .map_err(ToolError::SerdeJson)
-                    }),
-            );
-            reqs.push(future);
-        });
+        let reqs = urls.map(|url| self.post(&client, url, &params)).collect();
         join_all(reqs)
     }
 
     #[inline]
     fn make_requests_with_params_list<T: Iterator<Item = JsonRpcParams>>(
         &self,
+        url: Uri,
         params: T,
     ) -> JoinAll<Vec<Box<dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send>>>
     {
         let client = create_client();
-        let mut reqs = Vec::with_capacity(100);
-        params
+        let reqs = params
             .map(|param| {
                 self.id.fetch_add(1, Ordering::Relaxed);
                 let param = param.insert(
@@ -232,127 +813,225 @@ impl Client {
                     ParamsValue::Int(self.id.load(Ordering::Relaxed) as u64),
                 );
                 if self.debug {
-                    Self::debug_request(vec![&param].into_iter())
+                    self.notify(Notice::Request(&param));
                 }
-                param
+                self.post(&client, url.clone(), &param)
             })
-            .for_each(|param| {
-                let req: Request<Body> = Request::builder()
-                    .uri(self.url.clone())
-                    .method("POST")
-                    .header("Content-Type", "application/json")
-                    .body(Body::from(serde_json::to_string(&param).unwrap()))
-                    .unwrap();
-                let future: Box<
-                    dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send,
-                > = Box::new(
-                    client
-                        .request(req)
-                        .and_then(|res| res.into_body().concat2())
-                        .map_err(ToolError::Hyper)
-                        .and_then(|response| {
-                            serde_json::from_slice::<JsonRpcResponse>(&response)
-                                .map_err(ToolError::SerdeJson)
-                        }),
-                );
-                reqs.push(future);
-            });
+            .collect();
 
         join_all(reqs)
     }
 
     /// Constructing a Transaction
     pub fn generate_transaction(
-        &mut self,
+        &self,
         transaction_options: TransactionOptions,
     ) -> Result<Transaction, ToolError> {
-        let data = decode(remove_0x(transaction_options.code())).map_err(ToolError::Decode)?;
         let current_height = transaction_options
             .current_height()
             .ok_or_else(|| ToolError::Customize("No height input".to_string()))
             .or_else(|_| self.get_current_height())?;
-
-        let mut tx = Transaction::new();
-        tx.set_data(data);
-
-        tx.set_nonce(encode(Uuid::new_v4().as_bytes()));
-        tx.set_valid_until_block(current_height + 88);
-        tx.set_quota(transaction_options.quota().unwrap_or_else(|| 10_000_000));
-        let value = transaction_options
-            .value()
-            .map(|value| value.completed_lower_hex())
-            .unwrap_or_else(|| U256::zero().completed_lower_hex());
-        tx.set_value(decode(value).map_err(ToolError::Decode)?);
-
-        let version = transaction_options
-            .version()
-            .unwrap_or_else(|| self.get_version().unwrap_or_else(|_| 0));
-
-        if version == 0 {
-            // Create a contract if the target address is empty
-            tx.set_to(remove_0x(transaction_options.address()).to_string());
-            tx.set_chain_id(self.get_chain_id()?);
-        } else if version < 3 {
-            // Create a contract if the target address is empty
-            tx.set_to_v1(
-                decode(remove_0x(transaction_options.address())).map_err(ToolError::Decode)?,
-            );
-            tx.set_chain_id_v1(
-                decode(self.get_chain_id_v1()?.completed_lower_hex()).map_err(ToolError::Decode)?,
-            );
-        } else {
-            return Err(ToolError::Customize("Invalid version".to_string()));
-        }
-
-        tx.set_version(version);
-
-        Ok(tx)
+        let version = transaction_options.version().unwrap_or_else(|| {
+            self.chain_version()
+                .map(|version| version.protocol)
+                .unwrap_or(0)
+        });
+        let chain_id = match TransactionVersion::new(version)? {
+            TransactionVersion::V0 => U256::from(self.get_chain_id()?),
+            _ => self.get_chain_id_v1()?,
+        };
+
+        transaction_options
+            .set_current_height(Some(current_height))
+            .set_version(Some(version))
+            .build(chain_id)
     }
 
     /// Constructing a UnverifiedTransaction hex string
     #[inline]
     pub fn generate_sign_transaction(&self, tx: &Transaction) -> Result<String, ToolError> {
+        let signer = self.transaction_signer().ok_or_else(|| {
+            ToolError::Customize("The provided private key do not match the algorithm".to_string())
+        })?;
         Ok(format!(
             "0x{}",
             encode(
-                tx.build_unverified(*self.private_key().ok_or_else(|| ToolError::Customize(
-                    "The provided private key do not match the algorithm".to_string(),
-                ))?)
-                .write_to_bytes()
-                .map_err(ToolError::Proto)?
+                tx.build_unverified_by(signer)?
+                    .write_to_bytes()
+                    .map_err(ToolError::Proto)?
             )
         ))
     }
 
     /// Send a signed transaction
-    pub fn send_signed_transaction(&mut self, param: &str) -> Result<JsonRpcResponse, ToolError> {
-        let byte_code = format!(
-            "0x{}",
-            encode(
-                parse_from_bytes::<UnverifiedTransaction>(
-                    decode(remove_0x(param))
-                        .map_err(ToolError::Decode)?
-                        .as_slice()
-                )
-                .map_err(ToolError::Proto)?
-                .write_to_bytes()
-                .map_err(ToolError::Proto)?
-            )
-        );
-        let params = JsonRpcParams::new()
-            .insert(
-                "method",
-                ParamsValue::String(String::from(SEND_RAW_TRANSACTION)),
+    pub fn send_signed_transaction(&self, param: &str) -> Result<JsonRpcResponse, ToolError> {
+        self.send_signed_transactions(&[param])
+            .map(|mut responses| responses.pop().unwrap())
+    }
+
+    /// Send the signed transactions in one batch, every transaction gets the checks of
+    /// `send_signed_transaction` before any of them is sent
+    pub fn send_signed_transactions(
+        &self,
+        params: &[&str],
+    ) -> Result<Vec<JsonRpcResponse>, ToolError> {
+        let mut unverified_txs = Vec::with_capacity(params.len());
+        let mut content_hashes = HashSet::with_capacity(params.len());
+        for param in params {
+            let unverified_tx = parse_from_bytes::<UnverifiedTransaction>(
+                decode(remove_0x(param))
+                    .map_err(ToolError::Decode)?
+                    .as_slice(),
             )
-            .insert(
-                "params",
-                ParamsValue::List(vec![ParamsValue::String(byte_code)]),
+            .map_err(ToolError::Proto)?;
+            self.check_duplicate(&unverified_tx, &mut content_hashes)?;
+            unverified_txs.push(unverified_tx);
+        }
+        self.check_chain(&unverified_txs)?;
+        for unverified_tx in &unverified_txs {
+            self.check_limits(unverified_tx)?;
+        }
+        self.check_fee(&unverified_txs)?;
+        for unverified_tx in &unverified_txs {
+            self.check_policy(unverified_tx)?;
+        }
+
+        let mut batch = Vec::with_capacity(unverified_txs.len());
+        for unverified_tx in &unverified_txs {
+            let byte_code = format!(
+                "0x{}",
+                encode(unverified_tx.write_to_bytes().map_err(ToolError::Proto)?)
             );
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+            batch.push(
+                JsonRpcParams::new()
+                    .insert(
+                        "method",
+                        ParamsValue::String(String::from(SEND_RAW_TRANSACTION)),
+                    )
+                    .insert(
+                        "params",
+                        ParamsValue::List(vec![ParamsValue::String(byte_code)]),
+                    ),
+            );
+        }
+        let responses = self.send_request(batch.into_iter());
+
+        for (index, unverified_tx) in unverified_txs.iter().enumerate() {
+            self.report_sent(
+                unverified_tx,
+                responses.as_ref().map(|responses| &responses[index]),
+            );
+        }
+        responses
+    }
+
+    /// Warn about or refuse a transaction with the content of one already sent, or of one
+    /// before it in the batch, by the duplicate policy
+    fn check_duplicate(
+        &self,
+        unverified_tx: &UnverifiedTransaction,
+        batch: &mut HashSet<String>,
+    ) -> Result<(), ToolError> {
+        let content_hash = unverified_tx
+            .get_transaction()
+            .content_hash()
+            .completed_lower_hex_with_0x();
+        let sent = self.pending.find_by_content(&content_hash);
+        if batch.insert(content_hash.clone()) && sent.is_none() {
+            return Ok(());
+        }
+        match (self.duplicate_policy, sent) {
+            (DuplicatePolicy::Allow, _) => {}
+            (DuplicatePolicy::Warn, Some(sent)) => self.notify(Notice::Duplicate(&sent)),
+            (DuplicatePolicy::Warn, None) => self.notify(Notice::DuplicateInBatch(&content_hash)),
+            (DuplicatePolicy::Reject, Some(sent)) => {
+                return Err(ToolError::Customize(format!(
+                    "A transaction with the same content was already sent: {}",
+                    sent.hash
+                )));
+            }
+            (DuplicatePolicy::Reject, None) => {
+                return Err(ToolError::Customize(format!(
+                    "The batch carries the same content twice: {}",
+                    content_hash
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Report the signed transaction sent with the response of the node by `Notice::Sent`,
+    /// and record it when the node accepted it; the callers sending their own batches of
+    /// signed transactions, instead of by `send_signed_transactions`, report each of them
+    pub fn report_sent(
+        &self,
+        unverified_tx: &UnverifiedTransaction,
+        response: Result<&JsonRpcResponse, &ToolError>,
+    ) {
+        let tx = unverified_tx.get_transaction();
+        let hash = match response.map(JsonRpcResponse::result) {
+            Ok(Some(ResponseValue::Map(result))) => match result.get("hash") {
+                Some(ParamsValue::String(hash)) => Some(hash.clone()),
+                _ => None,
+            },
+            _ => None,
+        };
+        let error = match response {
+            Ok(response) => response.error().map(|error| error.to_string()),
+            Err(err) => Some(err.to_string()),
+        }
+        .or_else(|| {
+            hash.is_none()
+                .then(|| "No hash of the transaction in the response".to_owned())
+        });
+        let to = if tx.get_to().is_empty() {
+            encode(tx.get_to_v1())
+        } else {
+            tx.get_to().to_owned()
+        };
+        let transaction = PendingTransaction {
+            hash: hash.unwrap_or_default(),
+            content_hash: tx.content_hash().completed_lower_hex_with_0x(),
+            sender: unverified_tx
+                .sender()
+                .map(|sender| sender.completed_lower_hex_with_0x())
+                .unwrap_or_default(),
+            to,
+            valid_until_block: tx.get_valid_until_block(),
+        };
+        self.notify(Notice::Sent {
+            url: &self.url.to_string(),
+            chain_id: TransactionVersion::of(tx)
+                .map(|version| version.chain_id(tx))
+                .unwrap_or_default(),
+            transaction: &transaction,
+            error: error.as_deref(),
+        });
+        if error.is_none() {
+            self.pending.insert(transaction);
+        }
+    }
+
+    /// Check the signed transaction against the send policy, the callers sending their own
+    /// batches of signed transactions, instead of by `send_signed_transactions`, check each
+    /// of them before the batch
+    pub fn check_policy(&self, unverified_tx: &UnverifiedTransaction) -> Result<(), ToolError> {
+        if let Some(ref policy) = self.send_policy {
+            let url = self.url.to_string();
+            let outgoing = OutgoingTransaction {
+                url: &url,
+                sender: unverified_tx.sender(),
+                transaction: unverified_tx.get_transaction(),
+            };
+            policy.check(&outgoing).map_err(|reason| {
+                ToolError::Customize(format!("The policy vetoed the transaction: {}", reason))
+            })?;
+        }
+        Ok(())
     }
 
     /// Send unsigned transactions
-    pub fn send_transaction(&mut self, param: &str) -> Result<JsonRpcResponse, ToolError> {
+    pub fn send_transaction(&self, param: &str) -> Result<JsonRpcResponse, ToolError> {
         let tx: Transaction = parse_from_bytes(
             decode(remove_0x(param))
                 .map_err(ToolError::Decode)?
@@ -360,27 +1039,19 @@ impl Client {
         )
         .map_err(ToolError::Proto)?;
         let byte_code = self.generate_sign_transaction(&tx)?;
-        let params = JsonRpcParams::new()
-            .insert(
-                "method",
-                ParamsValue::String(String::from(SEND_RAW_TRANSACTION)),
-            )
-            .insert(
-                "params",
-                ParamsValue::List(vec![ParamsValue::String(byte_code)]),
-            );
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        self.send_signed_transaction(&byte_code)
     }
 
     /// Get chain id
-    pub fn get_chain_id(&mut self) -> Result<u32, ToolError> {
-        if self.chain_id.is_some() && self.check_chain_id() {
-            Ok(self.chain_id.unwrap().low_u32())
+    pub fn get_chain_id(&self) -> Result<u32, ToolError> {
+        let cached = *self.chain_id.read().unwrap();
+        if let Some(chain_id) = cached.filter(|_| self.check_chain_id()) {
+            Ok(chain_id.low_u32())
         } else if let Some(ResponseValue::Map(mut value)) = self.get_metadata("latest")?.result() {
-            match value.remove("chainId").unwrap() {
-                ParamsValue::Int(chain_id) => {
-                    self.chain_id = Some(U256::from(chain_id));
-                    Ok(chain_id as u32)
+            match value.remove("chainId").map(|id| Quantity::from_params(&id)) {
+                Some(Ok(chain_id)) => {
+                    *self.chain_id.write().unwrap() = Some(chain_id.0);
+                    Ok(chain_id.0.low_u32())
                 }
                 _ => Ok(0),
             }
@@ -392,23 +1063,25 @@ impl Client {
     #[inline]
     fn check_chain_id(&self) -> bool {
         self.chain_id
-            .map(|id| id > U256::from(u32::max_value()))
+            .read()
+            .unwrap()
+            .map(|id| id > U256::from(u32::MAX))
             .unwrap_or(false)
     }
 
     /// Get chain id v1
-    pub fn get_chain_id_v1(&mut self) -> Result<U256, ToolError> {
-        if self.chain_id.is_some() {
-            Ok(self.chain_id.unwrap())
+    pub fn get_chain_id_v1(&self) -> Result<U256, ToolError> {
+        let cached = *self.chain_id.read().unwrap();
+        if let Some(chain_id) = cached {
+            Ok(chain_id)
         } else if let Some(ResponseValue::Map(mut value)) = self.get_metadata("latest")?.result() {
             match value.remove("chainIdV1") {
-                Some(ParamsValue::String(chain_id)) => {
-                    let chain_id = U256::from_str(remove_0x(&chain_id))
-                        .map_err(|e| ToolError::Customize(e.to_string()))?;
-                    self.chain_id = Some(chain_id);
+                Some(ParamsValue::Null) | None => Ok(U256::zero()),
+                Some(chain_id) => {
+                    let chain_id = Quantity::from_params(&chain_id)?.0;
+                    *self.chain_id.write().unwrap() = Some(chain_id);
                     Ok(chain_id)
                 }
-                _ => Ok(U256::zero()),
             }
         } else {
             Ok(U256::zero())
@@ -421,8 +1094,8 @@ impl Client {
             JsonRpcParams::new().insert("method", ParamsValue::String(String::from(BLOCK_NUMBER)));
         let response = self.send_request(vec![params].into_iter())?.pop().unwrap();
 
-        if let Some(ResponseValue::Singe(ParamsValue::String(height))) = response.result() {
-            Ok(u64::from_str_radix(remove_0x(&height), 16).map_err(ToolError::Parse)?)
+        if let Some(ResponseValue::Singe(height)) = response.result() {
+            Quantity::from_params(&height)?.as_u64()
         } else {
             Err(ToolError::Customize(
                 "Corresponding address does not respond".to_string(),
@@ -430,9 +1103,39 @@ impl Client {
         }
     }
 
-    /// Get version
+    /// Wait until the receipt of the transaction is `confirmations` blocks deep,
+    /// polling every `interval` and giving up after `timeout`
+    ///
+    /// The block of the receipt is checked again before returning, so a receipt
+    /// that was moved or removed by a reorg is not reported as confirmed.
+    pub fn wait_for_receipt(
+        &self,
+        hash: &str,
+        confirmations: u64,
+        interval: Duration,
+        timeout: Duration,
+    ) -> Result<ReceiptStatus, ToolError> {
+        wait_receipt(
+            interval,
+            timeout,
+            || self.get_transaction_receipt(hash),
+            |height, block_hash| {
+                Ok(self.get_current_height()? >= height + confirmations
+                    && self.is_block_canonical(height, block_hash)?)
+            },
+        )
+    }
+
+    fn is_block_canonical(&self, height: u64, hash: &str) -> Result<bool, ToolError> {
+        let block = self.get_block_by_number(&format!("{:#x}", height), false)?;
+        let block = serde_json::to_value(block.result()).map_err(ToolError::SerdeJson)?;
+        Ok(block["hash"].as_str() == Some(hash))
+    }
+
+    /// Get the protocol version from the version manager,
+    /// [`chain_version`](#method.chain_version) has the version of the node
     pub fn get_version(&self) -> Result<u32, ToolError> {
-        if let Some(ResponseValue::Singe(ParamsValue::String(version))) = self
+        if let Some(ResponseValue::Singe(version)) = self
             .call(
                 None,
                 "0xffffffffffffffffffffffffffffffffff020011",
@@ -441,7 +1144,7 @@ impl Client {
             )?
             .result()
         {
-            Ok(u32::from_str_radix(remove_0x(&version), 16).map_err(ToolError::Parse)?)
+            Quantity::from_params(&version)?.as_u32()
         } else {
             Ok(0)
         }
@@ -454,8 +1157,17 @@ impl Client {
             Vec<Box<dyn Future<Item = JsonRpcResponse, Error = ToolError> + 'static + Send>>,
         >,
     ) -> Result<Vec<JsonRpcResponse>, ToolError> {
-        let (tx, rx) = sync::oneshot::channel::<Result<Vec<JsonRpcResponse>, ToolError>>();
-        let req = reqs
+        self.block_on(reqs)
+    }
+
+    /// Run the future on the runtime of the client, waiting for its result
+    pub(crate) fn block_on<T, F>(&self, future: F) -> Result<T, ToolError>
+    where
+        T: Send + 'static,
+        F: Future<Item = T, Error = ToolError> + Send + 'static,
+    {
+        let (tx, rx) = sync::oneshot::channel::<Result<T, ToolError>>();
+        let req = future
             .then(move |res| tx.send(res))
             .map(|_| ())
             .map_err(|_| ());
@@ -464,12 +1176,6 @@ impl Client {
             .map_err(|e| ToolError::Customize(e.to_string()))?;
         rx.wait().map_err(|e| ToolError::Customize(e.to_string()))?
     }
-
-    fn debug_request<'a, T: Iterator<Item = &'a JsonRpcParams>>(params: T) {
-        params.for_each(|param| {
-            println!("<--{}", param);
-        });
-    }
 }
 
 impl Clone for Client {
@@ -478,9 +1184,29 @@ impl Clone for Client {
             id: AtomicUsize::new(self.id.load(Ordering::Relaxed)),
             url: self.url.clone(),
             sender: self.sender.clone(),
-            chain_id: None,
+            chain_id: RwLock::new(None),
+            chain_versions: self.chain_versions.clone(),
             private_key: self.private_key,
+            signer: self.signer.clone(),
+            send_policy: self.send_policy.clone(),
             debug: self.debug,
+            pending: self.pending.clone(),
+            duplicate_policy: self.duplicate_policy,
+            fee_check: self.fee_check,
+            chain_check: self.chain_check,
+            limit_check: self.limit_check,
+            max_fee: self.max_fee,
+            filters: self.filters.clone(),
+            log_chunk_size: self.log_chunk_size,
+            cache: self.cache.clone(),
+            notifier: self.notifier.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            circuit_breaker: self.circuit_breaker.clone(),
+            node_selector: self.node_selector.clone(),
+            method_aliases: self.method_aliases.clone(),
+            strict: self.strict,
+            extra_params: self.extra_params.clone(),
+            timings: self.timings.clone(),
         }
     }
 }
@@ -491,6 +1217,90 @@ impl Default for Client {
     }
 }
 
+/// Check the addresses of the logs
+fn check_addresses(addresses: &Option<Vec<&str>>) -> Result<(), ToolError> {
+    for address in addresses.iter().flatten() {
+        check_address("address", address)?;
+    }
+    Ok(())
+}
+
+/// Build the params of jsonrpc `getLogs`
+fn logs_params(
+    topic: &Option<Vec<&str>>,
+    address: &Option<Vec<&str>>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> JsonRpcParams {
+    let mut object = HashMap::new();
+    object.insert(
+        String::from("fromBlock"),
+        ParamsValue::String(String::from(from.unwrap_or("latest"))),
+    );
+    object.insert(
+        String::from("toBlock"),
+        ParamsValue::String(String::from(to.unwrap_or("latest"))),
+    );
+
+    if topic.is_some() {
+        object.insert(
+            String::from("topics"),
+            ParamsValue::from(serde_json::to_value(topic).unwrap()),
+        );
+    } else {
+        object.insert(String::from("topics"), ParamsValue::List(Vec::new()));
+    }
+
+    object.insert(
+        String::from("address"),
+        ParamsValue::from(serde_json::to_value(address).unwrap()),
+    );
+
+    JsonRpcParams::new()
+        .insert("method", ParamsValue::String(String::from(GET_LOGS)))
+        .insert("params", ParamsValue::List(vec![ParamsValue::Map(object)]))
+}
+
+/// Height of the block tag, None for `latest` and `pending`
+fn tag_height(tag: &str) -> Option<u64> {
+    match tag {
+        "earliest" => Some(0),
+        _ => u64::from_str_radix(remove_0x(tag), 16).ok(),
+    }
+}
+
+/// Build the params of jsonrpc `call`
+pub(crate) fn call_params(
+    from: Option<&str>,
+    to: &str,
+    data: Option<&str>,
+    height: &str,
+) -> JsonRpcParams {
+    let mut object = HashMap::new();
+
+    object.insert(String::from("to"), ParamsValue::String(String::from(to)));
+    if let Some(from) = from {
+        object.insert(
+            String::from("from"),
+            ParamsValue::String(String::from(from)),
+        );
+    }
+    if let Some(data) = data {
+        object.insert(
+            String::from("data"),
+            ParamsValue::String(String::from(data)),
+        );
+    }
+
+    let param = ParamsValue::List(vec![
+        ParamsValue::Map(object),
+        ParamsValue::String(String::from(height)),
+    ]);
+    JsonRpcParams::new()
+        .insert("method", ParamsValue::String(String::from(CALL)))
+        .insert("params", param)
+}
+
 /// High level jsonrpc call
 ///
 /// [Documentation](https://cryptape.github.io/cita/zh/usage-guide/rpc/index.html)
@@ -534,7 +1344,7 @@ where
     /// blockNumber: Get current height
     fn get_block_number(&self) -> Result<T, E>;
     /// sendTransaction: Send a transaction and return transaction hash
-    fn send_raw_transaction(&mut self, transaction_option: TransactionOptions) -> Result<T, E>;
+    fn send_raw_transaction(&self, transaction_option: TransactionOptions) -> Result<T, E>;
     /// getBlockByHash: Get block by hash
     fn get_block_by_hash(&self, hash: &str, transaction_info: bool) -> Result<T, E>;
     /// getBlockByNumber: Get block by number
@@ -619,12 +1429,12 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
     }
 
     fn send_raw_transaction(
-        &mut self,
+        &self,
         transaction_option: TransactionOptions,
     ) -> Result<JsonRpcResponse, ToolError> {
         let tx = self.generate_transaction(transaction_option)?;
         let byte_code = self.generate_sign_transaction(&tx)?;
-        Ok(self.send_signed_transaction(&byte_code)?)
+        self.send_signed_transaction(&byte_code)
     }
 
     fn get_block_by_hash(
@@ -687,35 +1497,36 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         from: Option<&str>,
         to: Option<&str>,
     ) -> Result<JsonRpcResponse, ToolError> {
-        let mut object = HashMap::new();
-        object.insert(
-            String::from("fromBlock"),
-            ParamsValue::String(String::from(from.unwrap_or("latest"))),
-        );
-        object.insert(
-            String::from("toBlock"),
-            ParamsValue::String(String::from(to.unwrap_or("latest"))),
-        );
-
-        if topic.is_some() {
-            object.insert(
-                String::from("topics"),
-                serde_json::from_str::<ParamsValue>(&serde_json::to_string(&topic).unwrap())
-                    .unwrap(),
-            );
-        } else {
-            object.insert(String::from("topics"), ParamsValue::List(Vec::new()));
+        check_addresses(&address)?;
+        let chunks = self.log_chunks(from, to)?;
+        if chunks.is_empty() {
+            let params = logs_params(&topic, &address, from, to);
+            return Ok(self.send_request(vec![params].into_iter())?.pop().unwrap());
         }
 
-        object.insert(
-            String::from("address"),
-            serde_json::from_str::<ParamsValue>(&serde_json::to_string(&address).unwrap()).unwrap(),
-        );
-
-        let params = JsonRpcParams::new()
-            .insert("method", ParamsValue::String(String::from(GET_LOGS)))
-            .insert("params", ParamsValue::List(vec![ParamsValue::Map(object)]));
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        let mut logs = Vec::new();
+        for batch in chunks.chunks(LOG_CHUNK_PARALLELISM) {
+            let params = batch.iter().map(|(start, end)| {
+                logs_params(
+                    &topic,
+                    &address,
+                    Some(&format!("{:#x}", start)),
+                    Some(&format!("{:#x}", end)),
+                )
+            });
+            for response in self.send_request(params.collect::<Vec<_>>().into_iter())? {
+                if response.error().is_some() {
+                    return Ok(response);
+                }
+                if let Some(serde_json::Value::Array(mut chunk)) = response
+                    .result()
+                    .and_then(|result| serde_json::to_value(result).ok())
+                {
+                    logs.append(&mut chunk);
+                }
+            }
+        }
+        JsonRpcResponse::from_result(serde_json::Value::Array(logs))
     }
 
     fn call(
@@ -725,30 +1536,11 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         data: Option<&str>,
         height: &str,
     ) -> Result<JsonRpcResponse, ToolError> {
-        let mut object = HashMap::new();
-
-        object.insert(String::from("to"), ParamsValue::String(String::from(to)));
+        check_address("to", to)?;
         if let Some(from) = from {
-            object.insert(
-                String::from("from"),
-                ParamsValue::String(String::from(from)),
-            );
-        }
-        if let Some(data) = data {
-            object.insert(
-                String::from("data"),
-                ParamsValue::String(String::from(data)),
-            );
+            check_address("from", from)?;
         }
-
-        let param = ParamsValue::List(vec![
-            ParamsValue::Map(object),
-            ParamsValue::String(String::from(height)),
-        ]);
-        let params = JsonRpcParams::new()
-            .insert("method", ParamsValue::String(String::from(CALL)))
-            .insert("params", param);
-
+        let params = call_params(from, to, data, height);
         Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
     }
 
@@ -768,6 +1560,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         address: &str,
         height: &str,
     ) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert(
                 "method",
@@ -785,6 +1578,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
     }
 
     fn get_code(&self, address: &str, height: &str) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert("method", ParamsValue::String(String::from(GET_CODE)))
             .insert(
@@ -799,6 +1593,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
     }
 
     fn get_abi(&self, address: &str, height: &str) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert("method", ParamsValue::String(String::from(GET_ABI)))
             .insert(
@@ -813,6 +1608,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
     }
 
     fn get_balance(&self, address: &str, height: &str) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert("method", ParamsValue::String(String::from(GET_BALANCE)))
             .insert(
@@ -833,6 +1629,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         from: Option<&str>,
         to: Option<&str>,
     ) -> Result<JsonRpcResponse, ToolError> {
+        check_addresses(&address)?;
         let mut object = HashMap::new();
         object.insert(
             String::from("fromBlock"),
@@ -844,25 +1641,37 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         );
         object.insert(
             String::from("topics"),
-            serde_json::from_str::<ParamsValue>(&serde_json::to_string(&topic).unwrap()).unwrap(),
+            ParamsValue::from(serde_json::to_value(&topic).unwrap()),
         );
         object.insert(
             String::from("address"),
-            serde_json::from_str::<ParamsValue>(&serde_json::to_string(&address).unwrap()).unwrap(),
+            ParamsValue::from(serde_json::to_value(&address).unwrap()),
         );
 
+        let kind = FilterKind::Logs {
+            topics: topic.map(|topic| topic.into_iter().map(ToOwned::to_owned).collect()),
+            addresses: address.map(|address| address.into_iter().map(ToOwned::to_owned).collect()),
+            to: to.map(ToOwned::to_owned),
+        };
+        let start = self.filter_start(from)?;
+
         let params = JsonRpcParams::new()
             .insert("method", ParamsValue::String(String::from(NEW_FILTER)))
             .insert("params", ParamsValue::List(vec![ParamsValue::Map(object)]));
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        let response = self.send_request(vec![params].into_iter())?.pop().unwrap();
+        self.filter_installed(&response, kind, start);
+        Ok(response)
     }
 
     fn new_block_filter(&self) -> Result<JsonRpcResponse, ToolError> {
+        let start = self.filter_start(None)?;
         let params = JsonRpcParams::new().insert(
             "method",
             ParamsValue::String(String::from(NEW_BLOCK_FILTER)),
         );
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        let response = self.send_request(vec![params].into_iter())?.pop().unwrap();
+        self.filter_installed(&response, FilterKind::Blocks, start);
+        Ok(response)
     }
 
     fn uninstall_filter(&self, filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
@@ -873,10 +1682,14 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
             )
             .insert(
                 "params",
-                ParamsValue::List(vec![ParamsValue::String(String::from(filter_id))]),
+                ParamsValue::List(vec![ParamsValue::String(
+                    self.installed_filter_id(filter_id),
+                )]),
             );
 
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        let response = self.send_request(vec![params].into_iter())?.pop().unwrap();
+        self.filter_uninstalled(filter_id);
+        Ok(response)
     }
 
     fn get_filter_changes(&self, filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
@@ -887,10 +1700,13 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
             )
             .insert(
                 "params",
-                ParamsValue::List(vec![ParamsValue::String(String::from(filter_id))]),
+                ParamsValue::List(vec![ParamsValue::String(
+                    self.installed_filter_id(filter_id),
+                )]),
             );
 
-        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+        let response = self.send_request(vec![params].into_iter())?.pop().unwrap();
+        self.track_filter_changes(filter_id, response)
     }
 
     fn get_filter_logs(&self, filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
@@ -898,7 +1714,9 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
             .insert("method", ParamsValue::String(String::from(GET_FILTER_LOGS)))
             .insert(
                 "params",
-                ParamsValue::List(vec![ParamsValue::String(String::from(filter_id))]),
+                ParamsValue::List(vec![ParamsValue::String(
+                    self.installed_filter_id(filter_id),
+                )]),
             );
         Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
     }
@@ -945,6 +1763,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         key: &str,
         height: &str,
     ) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert(
                 "params",
@@ -964,6 +1783,7 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         key: &str,
         height: &str,
     ) -> Result<JsonRpcResponse, ToolError> {
+        check_address("address", address)?;
         let params = JsonRpcParams::new()
             .insert(
                 "params",
@@ -990,6 +1810,13 @@ impl ClientExt<JsonRpcResponse, ToolError> for Client {
         data: Option<&str>,
         height: &str,
     ) -> Result<JsonRpcResponse, ToolError> {
+        // An empty address estimates a contract creation
+        if !remove_0x(to).is_empty() {
+            check_address("to", to)?;
+        }
+        if let Some(from) = from {
+            check_address("from", from)?;
+        }
         let mut object = HashMap::new();
 
         object.insert(String::from("to"), ParamsValue::String(String::from(to)));
@@ -1025,7 +1852,7 @@ where
     E: Fail + From<ToolError>,
 {
     /// Store data to chain, data can be get back by `getTransaction` rpc call
-    fn store_data(&mut self, content: &str, quota: Option<u64>) -> Result<T, E> {
+    fn store_data(&self, content: &str, quota: Option<u64>) -> Result<T, E> {
         let tx_options = TransactionOptions::new()
             .set_code(content)
             .set_address(STORE_ADDRESS)
@@ -1034,7 +1861,8 @@ where
     }
 
     /// Store contract ABI to chain, ABI can be get back by `getAbi` rpc call
-    fn store_abi(&mut self, address: &str, content: String, quota: Option<u64>) -> Result<T, E> {
+    fn store_abi(&self, address: &str, content: String, quota: Option<u64>) -> Result<T, E> {
+        check_address("address", address)?;
         let address = remove_0x(address);
         let content_abi = encode(content);
         let data = format!("0x{}{}", address, content_abi);
@@ -1055,7 +1883,8 @@ where
     E: Fail + From<ToolError>,
 {
     /// Amend contract code
-    fn amend_code(&mut self, address: &str, content: &str, quota: Option<u64>) -> Result<T, E> {
+    fn amend_code(&self, address: &str, content: &str, quota: Option<u64>) -> Result<T, E> {
+        check_address("address", address)?;
         let address = remove_0x(address);
         let content = remove_0x(content);
         let data = format!("0x{}{}", address, content);
@@ -1068,7 +1897,8 @@ where
     }
 
     /// Amend contract ABI
-    fn amend_abi(&mut self, address: &str, content: String, quota: Option<u64>) -> Result<T, E> {
+    fn amend_abi(&self, address: &str, content: String, quota: Option<u64>) -> Result<T, E> {
+        check_address("address", address)?;
         let address = remove_0x(address);
         let content_abi = encode(content);
         let data = format!("0x{}{}", address, content_abi);
@@ -1081,7 +1911,8 @@ where
     }
 
     /// Amend H256KV
-    fn amend_h256kv(&mut self, address: &str, h256_kv: &str, quota: Option<u64>) -> Result<T, E> {
+    fn amend_h256kv(&self, address: &str, h256_kv: &str, quota: Option<u64>) -> Result<T, E> {
+        check_address("address", address)?;
         let address = remove_0x(address);
         let data = format!("0x{}{}", address, h256_kv);
         let tx_options = TransactionOptions::new()
@@ -1093,7 +1924,8 @@ where
     }
 
     /// Amend account balance
-    fn amend_balance(&mut self, address: &str, balance: U256, quota: Option<u64>) -> Result<T, E> {
+    fn amend_balance(&self, address: &str, balance: U256, quota: Option<u64>) -> Result<T, E> {
+        check_address("address", address)?;
         let address = remove_0x(address);
         let data = format!("0x{}{}", address, balance.completed_lower_hex());
         let tx_options = TransactionOptions::new()
@@ -1114,7 +1946,7 @@ where
     E: Fail,
 {
     /// Account transfer, only applies to charge mode
-    fn transfer(&mut self, value: U256, address: &str, quota: Option<u64>) -> Result<T, E> {
+    fn transfer(&self, value: U256, address: &str, quota: Option<u64>) -> Result<T, E> {
         let tx_options = TransactionOptions::new()
             .set_address(address)
             .set_quota(quota)
@@ -1125,14 +1957,272 @@ where
 
 impl Transfer<JsonRpcResponse, ToolError> for Client {}
 
+/// Create contracts and send transactions to them, instead of telling them apart by
+/// the empty address of `send_raw_transaction`
+pub trait ContractExt<T, E>: ClientExt<T, E>
+where
+    T: serde::Serialize + serde::Deserialize<'static> + ::std::fmt::Display,
+    E: Fail + From<ToolError>,
+{
+    /// Create a contract of the code, the options must have no address
+    fn create_contract<'a>(
+        &self,
+        code: &'a str,
+        tx_options: TransactionOptions<'a>,
+    ) -> Result<T, E> {
+        if !remove_0x(tx_options.address()).is_empty() {
+            return Err(ToolError::Customize(format!(
+                "A contract creation has no target address, got {}",
+                tx_options.address()
+            ))
+            .into());
+        }
+        if remove_0x(code).is_empty() {
+            return Err(ToolError::Customize(
+                "The code of a contract creation is empty".to_string(),
+            )
+            .into());
+        }
+        self.send_raw_transaction(tx_options.set_code(code).set_address("0x"))
+    }
+
+    /// Send the data to the contract, the address must be a valid one,
+    /// the address of the options is replaced
+    fn call_contract<'a>(
+        &self,
+        address: &'a str,
+        data: &'a str,
+        tx_options: TransactionOptions<'a>,
+    ) -> Result<T, E> {
+        if remove_0x(address).is_empty() {
+            return Err(ToolError::Customize(
+                "The address of a contract call is empty, create contracts by create_contract"
+                    .to_string(),
+            )
+            .into());
+        }
+        parse_checksum_address(address)?;
+        self.send_raw_transaction(tx_options.set_address(address).set_code(data))
+    }
+}
+
+impl ContractExt<JsonRpcResponse, ToolError> for Client {}
+
+/// The http client of the enabled tls implementation
+#[cfg(feature = "openssl")]
+pub(crate) type HttpsClient = HyperClient<hyper_tls::HttpsConnector<HttpConnector>>;
+/// The http client of the enabled tls implementation
+#[cfg(feature = "rustls")]
+pub(crate) type HttpsClient = HyperClient<hyper_rustls::HttpsConnector<HttpConnector>>;
+
 #[cfg(feature = "openssl")]
-pub(crate) fn create_client() -> HyperClient<hyper_tls::HttpsConnector<HttpConnector>> {
+pub(crate) fn create_client() -> HttpsClient {
     let https = hyper_tls::HttpsConnector::new(4).unwrap();
     HyperClient::builder().build::<_, Body>(https)
 }
 
 #[cfg(feature = "rustls")]
-pub(crate) fn create_client() -> HyperClient<hyper_rustls::HttpsConnector<HttpConnector>> {
+pub(crate) fn create_client() -> HttpsClient {
     let https = hyper_rustls::HttpsConnector::new(4);
     HyperClient::builder().build::<_, Body>(https)
 }
+
+/// Poll the receipt by `fetch` every `interval` until `confirmed` says its block, of the height
+/// and the hash, is deep enough on the chain, the receipt is gone after it was seen, or `timeout`
+fn wait_receipt<F, C>(
+    interval: Duration,
+    timeout: Duration,
+    mut fetch: F,
+    mut confirmed: C,
+) -> Result<ReceiptStatus, ToolError>
+where
+    F: FnMut() -> Result<JsonRpcResponse, ToolError>,
+    C: FnMut(u64, &str) -> Result<bool, ToolError>,
+{
+    let start = Instant::now();
+    let mut seen = false;
+    loop {
+        let receipt = fetch()?;
+        if let Some(err) = receipt.error() {
+            return Err(ToolError::Customize(err.message()));
+        }
+        let location = serde_json::to_value(receipt.result())
+            .ok()
+            .and_then(|receipt| {
+                let height = Quantity::from_json(&receipt["blockNumber"])?
+                    .as_u64()
+                    .ok()?;
+                let block_hash = receipt["blockHash"].as_str()?.to_owned();
+                Some((height, block_hash))
+            });
+
+        match location {
+            Some((height, block_hash)) => {
+                seen = true;
+                if confirmed(height, &block_hash)? {
+                    return Ok(ReceiptStatus::Confirmed(receipt));
+                }
+            }
+            None if seen => return Ok(ReceiptStatus::Dropped),
+            None => {}
+        }
+
+        if start.elapsed() >= timeout {
+            return Ok(ReceiptStatus::Timeout);
+        }
+        thread::sleep(interval);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use std::thread;
+
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Mutex;
+
+    use super::{wait_receipt, Client, ReceiptStatus};
+    use crate::client::{DuplicatePolicy, Notice};
+    use crate::crypto::{Encryption, KeyPair};
+    use crate::error::ToolError;
+    use crate::protos::{TransactionBuilder, TransactionVersion};
+    use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue};
+    use hex::encode;
+    use protobuf::Message;
+    use serde_json::json;
+    use std::time::Duration;
+    use types::U256;
+
+    #[test]
+    fn test_log_chunks() {
+        let client = Client::new().set_log_chunk_size(10);
+        assert!(client
+            .log_chunks(Some("0x0"), Some("0x9"))
+            .unwrap()
+            .is_empty());
+        assert!(client.log_chunks(None, Some("0x100")).unwrap().is_empty());
+        assert_eq!(
+            client.log_chunks(Some("earliest"), Some("0x18")).unwrap(),
+            vec![(0, 9), (10, 19), (20, 24)]
+        );
+        let client = client.set_log_chunk_size(0);
+        assert!(client
+            .log_chunks(Some("0x0"), Some("0x100"))
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_client_shared_by_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Client>();
+
+        let mut client = Client::new();
+        client.set_chain_id(U256::from(7));
+        let client = Arc::new(client);
+        let threads = (0..4)
+            .map(|_| {
+                let client = Arc::clone(&client);
+                thread::spawn(move || client.get_chain_id_v1().unwrap())
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            assert_eq!(thread.join().unwrap(), U256::from(7));
+        }
+    }
+
+    #[test]
+    fn test_duplicate_in_batch() {
+        let privkey = KeyPair::new(Encryption::Secp256k1).privkey();
+        let transfer = |value: u64| {
+            TransactionBuilder::new(TransactionVersion::V2)
+                .set_value(U256::from(value))
+                .build()
+                .unwrap()
+                .build_unverified(privkey)
+        };
+        let signed = format!("0x{}", encode(transfer(1).write_to_bytes().unwrap()));
+
+        // Refused before anything of the batch is sent
+        let client = Client::new().set_duplicate_policy(DuplicatePolicy::Reject);
+        let err = client
+            .send_signed_transactions(&[&signed, &signed])
+            .unwrap_err();
+        assert!(err.to_string().contains("the same content twice"));
+        assert!(client.pending().list().is_empty());
+
+        let warned = Arc::new(Mutex::new(Vec::new()));
+        let notices = Arc::clone(&warned);
+        let client = Client::new().set_notifier(Some(Arc::new(move |notice: &Notice| {
+            if let Notice::DuplicateInBatch(content_hash) = notice {
+                notices.lock().unwrap().push(content_hash.to_string());
+            }
+        })));
+        let mut batch = HashSet::new();
+        for tx in &[transfer(1), transfer(2), transfer(1)] {
+            client.check_duplicate(tx, &mut batch).unwrap();
+        }
+        assert_eq!(warned.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_wait_receipt() {
+        // The blocks of the receipt of the polls in turn, None while it isn't on chain,
+        // only the block 0xaa is confirmed
+        let status = |blocks: Vec<Option<&str>>, timeout: u64| {
+            let mut blocks = blocks.into_iter();
+            wait_receipt(
+                Duration::from_millis(0),
+                Duration::from_millis(timeout),
+                || match blocks.next() {
+                    Some(Some(block_hash)) => JsonRpcResponse::from_result(json!({
+                        "blockNumber": "0x10",
+                        "blockHash": block_hash,
+                    })),
+                    Some(None) => Ok(JsonRpcResponse::default()),
+                    None => Err(ToolError::Customize("No more polls".to_owned())),
+                },
+                |height, block_hash| Ok(height == 16 && block_hash == "0xaa"),
+            )
+        };
+
+        // Not on chain, then not deep enough or not canonical, then confirmed
+        match status(vec![None, Some("0xbb"), Some("0xaa")], 1000) {
+            Ok(ReceiptStatus::Confirmed(receipt)) => assert!(receipt.is_ok()),
+            other => panic!("Not confirmed: {:?}", other),
+        }
+        // Seen in a block replaced by a reorg, then gone
+        assert!(matches!(
+            status(vec![Some("0xbb"), None], 1000),
+            Ok(ReceiptStatus::Dropped)
+        ));
+        assert!(matches!(
+            status(vec![None, None], 0),
+            Ok(ReceiptStatus::Timeout)
+        ));
+        assert!(matches!(
+            status(vec![Some("0xbb"), Some("0xbb")], 0),
+            Ok(ReceiptStatus::Timeout)
+        ));
+        assert!(status(vec![], 1000).is_err());
+    }
+
+    #[test]
+    fn test_extra_params() {
+        let mut extra = HashMap::new();
+        extra.insert("tenant".to_owned(), ParamsValue::String("t1".to_owned()));
+        extra.insert(
+            "method".to_owned(),
+            ParamsValue::String("forged".to_owned()),
+        );
+        let client = Client::new().set_extra_params(extra);
+        let params = JsonRpcParams::new()
+            .insert("method", ParamsValue::String("blockNumber".to_owned()))
+            .insert("id", ParamsValue::Int(1));
+        let body: serde_json::Value = serde_json::from_str(&client.request_body(&params)).unwrap();
+        assert_eq!(body["tenant"], "t1");
+        assert_eq!(body["method"], "blockNumber");
+        assert_eq!(body["jsonrpc"], "2.0");
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+/// The minimum, average, percentiles and maximum of latencies, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct LatencySummary {
+    /// The fastest
+    pub min: u64,
+    /// The mean
+    pub avg: u64,
+    /// The median
+    pub p50: u64,
+    /// 95% are faster or the same
+    pub p95: u64,
+    /// 99% are faster or the same
+    pub p99: u64,
+    /// The slowest
+    pub max: u64,
+}
+
+impl LatencySummary {
+    /// Summarize the latencies, None when there are none
+    pub fn from_latencies(latencies: &[u64]) -> Option<Self> {
+        if latencies.is_empty() {
+            return None;
+        }
+        let mut sorted = latencies.to_vec();
+        sorted.sort_unstable();
+        let percentile = |p: usize| sorted[((sorted.len() * p).div_ceil(100)).max(1) - 1];
+        Some(LatencySummary {
+            min: sorted[0],
+            avg: sorted.iter().sum::<u64>() / sorted.len() as u64,
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+            max: sorted[sorted.len() - 1],
+        })
+    }
+}
+
+/// What happened in a second of a benchmark, counted from its start
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BenchSecond {
+    /// Seconds since the start
+    pub second: u64,
+    /// Transactions sent
+    pub submitted: u64,
+    /// Transactions the node accepted into the pool
+    pub accepted: u64,
+    /// Transactions the node refused, or which failed to be sent
+    pub rejected: u64,
+    /// Transactions found in a block
+    pub confirmed: u64,
+    /// The confirmation latencies of the transactions confirmed in the second
+    #[serde(skip)]
+    pub latencies: Vec<u64>,
+    /// The median of the latencies, in milliseconds
+    pub p50: Option<u64>,
+    /// The 95th percentile of the latencies, in milliseconds
+    pub p95: Option<u64>,
+    /// The 99th percentile of the latencies, in milliseconds
+    pub p99: Option<u64>,
+}
+
+/// The totals of the transactions of a kind, such as the transfers of a mix, or of a sender
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct BenchTotals {
+    /// Transactions sent
+    pub submitted: u64,
+    /// Transactions the node accepted into the pool
+    pub accepted: u64,
+    /// Transactions the node refused, or which failed to be sent
+    pub rejected: u64,
+    /// Transactions found in a block
+    pub confirmed: u64,
+    /// How many times each error refused a transaction
+    pub errors: BTreeMap<String, u64>,
+    #[serde(skip)]
+    latencies: Vec<u64>,
+    /// The confirmation latencies
+    pub latency: Option<LatencySummary>,
+}
+
+/// Record the transactions of a benchmark by the second they happen, from the submission
+/// until they're found in a block
+#[derive(Debug)]
+pub struct BenchRecorder {
+    started: Instant,
+    seconds: Vec<BenchSecond>,
+    kinds: BTreeMap<String, BenchTotals>,
+    senders: BTreeMap<String, BenchTotals>,
+    /// hash -> when its transaction was submitted, its kind and sender
+    pending: HashMap<String, (Instant, String, String)>,
+}
+
+impl BenchRecorder {
+    /// Start recording at the instant
+    pub fn new(started: Instant) -> Self {
+        BenchRecorder {
+            started,
+            seconds: Vec::new(),
+            kinds: BTreeMap::new(),
+            senders: BTreeMap::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    fn second(&mut self, at: Instant) -> &mut BenchSecond {
+        let second = at.saturating_duration_since(self.started).as_secs() as usize;
+        while self.seconds.len() <= second {
+            let next = self.seconds.len() as u64;
+            self.seconds.push(BenchSecond {
+                second: next,
+                ..Default::default()
+            });
+        }
+        &mut self.seconds[second]
+    }
+
+    /// The totals of the kind and of the sender
+    fn totals(&mut self, kind: &str, sender: &str) -> [&mut BenchTotals; 2] {
+        [
+            self.kinds.entry(kind.to_owned()).or_default(),
+            self.senders.entry(sender.to_owned()).or_default(),
+        ]
+    }
+
+    /// A transaction of the kind and the sender was sent at the instant
+    pub fn submit(&mut self, at: Instant, kind: &str, sender: &str) {
+        self.second(at).submitted += 1;
+        for totals in self.totals(kind, sender).iter_mut() {
+            totals.submitted += 1;
+        }
+    }
+
+    /// The transaction of the hash, submitted at `submitted`, was accepted at the instant
+    pub fn accept(
+        &mut self,
+        at: Instant,
+        kind: &str,
+        sender: &str,
+        hash: &str,
+        submitted: Instant,
+    ) {
+        self.second(at).accepted += 1;
+        for totals in self.totals(kind, sender).iter_mut() {
+            totals.accepted += 1;
+        }
+        self.pending.insert(
+            hash.to_lowercase(),
+            (submitted, kind.to_owned(), sender.to_owned()),
+        );
+    }
+
+    /// A transaction of the kind and the sender was refused by the error at the instant
+    pub fn reject(&mut self, at: Instant, kind: &str, sender: &str, error: &str) {
+        self.second(at).rejected += 1;
+        for totals in self.totals(kind, sender).iter_mut() {
+            totals.rejected += 1;
+            *totals.errors.entry(error.to_owned()).or_default() += 1;
+        }
+    }
+
+    /// The transaction of the hash was found in a block at the instant,
+    /// false if it isn't a pending one of the benchmark
+    pub fn confirm(&mut self, at: Instant, hash: &str) -> bool {
+        match self.pending.remove(&hash.to_lowercase()) {
+            Some((submitted, kind, sender)) => {
+                let latency = at.saturating_duration_since(submitted).as_millis() as u64;
+                let second = self.second(at);
+                second.confirmed += 1;
+                second.latencies.push(latency);
+                for totals in self.totals(&kind, &sender).iter_mut() {
+                    totals.confirmed += 1;
+                    totals.latencies.push(latency);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// How many accepted transactions aren't confirmed yet
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Every second from the start until the last event, with the percentiles of its latencies
+    pub fn series(&self) -> Vec<BenchSecond> {
+        self.seconds
+            .iter()
+            .map(|second| {
+                let summary = LatencySummary::from_latencies(&second.latencies);
+                BenchSecond {
+                    p50: summary.map(|summary| summary.p50),
+                    p95: summary.map(|summary| summary.p95),
+                    p99: summary.map(|summary| summary.p99),
+                    ..second.clone()
+                }
+            })
+            .collect()
+    }
+
+    /// The totals of every kind of transactions
+    pub fn kinds(&self) -> BTreeMap<String, BenchTotals> {
+        summarize(&self.kinds)
+    }
+
+    /// The totals of the transactions of every sender
+    pub fn senders(&self) -> BTreeMap<String, BenchTotals> {
+        summarize(&self.senders)
+    }
+
+    /// The confirmation latencies of the whole benchmark
+    pub fn latency(&self) -> Option<LatencySummary> {
+        let latencies = self
+            .seconds
+            .iter()
+            .flat_map(|second| second.latencies.iter().copied())
+            .collect::<Vec<u64>>();
+        LatencySummary::from_latencies(&latencies)
+    }
+}
+
+fn summarize(totals: &BTreeMap<String, BenchTotals>) -> BTreeMap<String, BenchTotals> {
+    totals
+        .iter()
+        .map(|(name, totals)| {
+            let totals = BenchTotals {
+                latency: LatencySummary::from_latencies(&totals.latencies),
+                ..totals.clone()
+            };
+            (name.clone(), totals)
+        })
+        .collect()
+}
+
+/// The series as CSV with a header line, an empty cell for the percentiles of a second
+/// without confirmation
+pub fn bench_series_csv(series: &[BenchSecond]) -> String {
+    let cell = |value: Option<u64>| value.map(|value| value.to_string()).unwrap_or_default();
+    let mut csv = "second,submitted,accepted,rejected,confirmed,p50,p95,p99\n".to_string();
+    for second in series {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{},{}\n",
+            second.second,
+            second.submitted,
+            second.accepted,
+            second.rejected,
+            second.confirmed,
+            cell(second.p50),
+            cell(second.p95),
+            cell(second.p99)
+        ));
+    }
+    csv
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bench_series_csv, BenchRecorder, LatencySummary};
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_bench_recorder() {
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let mut recorder = BenchRecorder::new(start);
+        recorder.submit(at(0), "transfer", "0x01");
+        recorder.submit(at(0), "transfer", "0x02");
+        recorder.submit(at(0), "deploy", "0x01");
+        recorder.accept(at(100), "transfer", "0x01", "0xAA", at(0));
+        recorder.accept(at(100), "deploy", "0x01", "0xbb", at(0));
+        recorder.reject(at(100), "transfer", "0x02", "InvalidNonce");
+        assert!(recorder.confirm(at(2500), "0xaa"));
+        assert!(!recorder.confirm(at(2500), "0xcc"));
+        assert_eq!(recorder.pending(), 1);
+        let kinds = recorder.kinds();
+        assert_eq!(
+            (kinds["transfer"].submitted, kinds["transfer"].confirmed),
+            (2, 1)
+        );
+        assert_eq!(kinds["transfer"].latency.unwrap().max, 2500);
+        assert_eq!(
+            (kinds["deploy"].accepted, kinds["deploy"].confirmed),
+            (1, 0)
+        );
+        let senders = recorder.senders();
+        assert_eq!(
+            (senders["0x01"].submitted, senders["0x01"].confirmed),
+            (2, 1)
+        );
+        assert_eq!(senders["0x02"].errors["InvalidNonce"], 1);
+
+        let series = recorder.series();
+        assert_eq!(series.len(), 3);
+        assert_eq!(
+            (series[0].submitted, series[0].accepted, series[0].rejected),
+            (3, 2, 1)
+        );
+        assert_eq!(series[1].confirmed, 0);
+        assert_eq!((series[2].confirmed, series[2].p99), (1, Some(2500)));
+        assert_eq!(
+            bench_series_csv(&series).lines().nth(2),
+            Some("1,0,0,0,0,,,")
+        );
+
+        let summary = LatencySummary::from_latencies(&(1..=100).collect::<Vec<u64>>()).unwrap();
+        assert_eq!(
+            (
+                summary.min,
+                summary.p50,
+                summary.p95,
+                summary.p99,
+                summary.max
+            ),
+            (1, 50, 95, 99, 100)
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use serde_json;
+
+use crate::client::basic::{Client, ClientExt};
+use crate::error::ToolError;
+use crate::rpctypes::Quantity;
+
+/// A block and its timestamp
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockTime {
+    /// Block height
+    pub height: u64,
+    /// Block hash
+    pub hash: String,
+    /// Milliseconds since the epoch, in the block header
+    pub timestamp: u64,
+}
+
+impl Client {
+    /// The timestamp of the block at the height
+    pub fn block_time(&self, height: u64) -> Result<BlockTime, ToolError> {
+        let response = self.get_block_by_number(&format!("{:#x}", height), false)?;
+        if let Some(err) = response.error() {
+            return Err(ToolError::Customize(err.message()));
+        }
+        let block = serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)?;
+        let timestamp = Quantity::from_json(&block["header"]["timestamp"])
+            .ok_or_else(|| ToolError::Customize(format!("Block {} has no timestamp", height)))?
+            .as_u64()?;
+        Ok(BlockTime {
+            height,
+            hash: block["hash"].as_str().unwrap_or_default().to_owned(),
+            timestamp,
+        })
+    }
+
+    /// The block closest to the timestamp in milliseconds, the earlier one on a tie
+    ///
+    /// The timestamps of the blocks only increase, so it takes about `log2(height)` requests.
+    pub fn block_at_time(&self, timestamp: u64) -> Result<BlockTime, ToolError> {
+        let before = match self.last_block_before(timestamp)? {
+            Some(before) => before,
+            None => return self.block_time(0),
+        };
+        if before.timestamp == timestamp || before.height >= self.get_current_height()? {
+            return Ok(before);
+        }
+        let after = self.block_time(before.height + 1)?;
+        if after.timestamp - timestamp < timestamp - before.timestamp {
+            Ok(after)
+        } else {
+            Ok(before)
+        }
+    }
+
+    /// The last block produced at or before the timestamp, `None` if it is before the genesis
+    pub fn last_block_before(&self, timestamp: u64) -> Result<Option<BlockTime>, ToolError> {
+        search_before(timestamp, self.get_current_height()?, |height| {
+            self.block_time(height)
+        })
+    }
+
+    /// The heights of the first and the last blocks produced in the time range,
+    /// `None` if there is no block in it
+    pub fn blocks_between(
+        &self,
+        from: u64,
+        to: Option<u64>,
+    ) -> Result<Option<(u64, u64)>, ToolError> {
+        let last = match to {
+            Some(to) => match self.last_block_before(to)? {
+                Some(last) => last.height,
+                None => return Ok(None),
+            },
+            None => self.get_current_height()?,
+        };
+        let first = match self.last_block_before(from)? {
+            Some(block) if block.timestamp == from => block.height,
+            Some(block) => block.height + 1,
+            None => 0,
+        };
+        Ok(if first <= last {
+            Some((first, last))
+        } else {
+            None
+        })
+    }
+}
+
+/// Binary search the blocks up to the current height, their timestamps only increase
+fn search_before<F>(
+    timestamp: u64,
+    current_height: u64,
+    mut block_time: F,
+) -> Result<Option<BlockTime>, ToolError>
+where
+    F: FnMut(u64) -> Result<BlockTime, ToolError>,
+{
+    let mut low = block_time(0)?;
+    if low.timestamp > timestamp {
+        return Ok(None);
+    }
+    let mut high = current_height;
+    while low.height < high {
+        let middle = block_time(low.height + (high - low.height).div_ceil(2))?;
+        if middle.timestamp <= timestamp {
+            low = middle;
+        } else {
+            high = middle.height - 1;
+        }
+    }
+    Ok(Some(low))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{search_before, BlockTime};
+
+    #[test]
+    fn test_search_before() {
+        // A block every 3 seconds, with a gap of a minute after block 5
+        let timestamp =
+            |height: u64| 1_000_000 + height * 3000 + if height > 5 { 60_000 } else { 0 };
+        let mut requests = 0;
+        let mut search = |time: u64| {
+            search_before(time, 100, |height| {
+                requests += 1;
+                Ok(BlockTime {
+                    height,
+                    hash: String::new(),
+                    timestamp: timestamp(height),
+                })
+            })
+            .unwrap()
+            .map(|block| block.height)
+        };
+        assert_eq!(search(999_999), None);
+        assert_eq!(search(1_000_000), Some(0));
+        assert_eq!(search(1_015_000), Some(5));
+        assert_eq!(search(1_050_000), Some(5));
+        assert_eq!(search(timestamp(42)), Some(42));
+        assert_eq!(search(timestamp(42) + 1), Some(42));
+        assert_eq!(search(u64::MAX), Some(100));
+        assert!(requests < 7 * 9);
+    }
+}
@@ -0,0 +1,129 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::crypto::{Encryption, Hashable};
+use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue, ResponseValue};
+use crate::LowerHex;
+
+/// The methods whose results never change once available
+const IMMUTABLE_METHODS: [&str; 3] = ["getBlockByHash", "getTransaction", "getTransactionReceipt"];
+
+/// On-disk cache of the responses of immutable queries, keyed by node, method and params
+///
+/// The entries of a node live under a namespace of its own, so the chains sharing the
+/// directory never answer each other's queries.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+}
+
+impl ResponseCache {
+    /// Create a cache stored in the directory
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        ResponseCache { dir: dir.into() }
+    }
+
+    /// The directory of the cache
+    pub fn dir(&self) -> &PathBuf {
+        &self.dir
+    }
+
+    /// Get the cached response of the request to the node of the namespace, such as its URL
+    pub fn get(&self, namespace: &str, params: &JsonRpcParams) -> Option<JsonRpcResponse> {
+        let path = self.path(namespace, params)?;
+        let content = fs::read(path).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    /// Cache the response, only successful results of immutable queries are kept
+    pub fn put(&self, namespace: &str, params: &JsonRpcParams, response: &JsonRpcResponse) {
+        let path = match self.path(namespace, params) {
+            Some(path) => path,
+            None => return,
+        };
+        match response.result() {
+            None | Some(ResponseValue::Singe(ParamsValue::Null)) => return,
+            _ => {}
+        }
+        if let Some(dir) = path.parent() {
+            if fs::create_dir_all(dir).is_err() {
+                return;
+            }
+        }
+        if let Ok(content) = serde_json::to_vec(response) {
+            // A failed write only means a miss next time
+            let _ = fs::write(path, content);
+        }
+    }
+
+    /// `<dir>/<hash of namespace>/<method>/<hash of params>`, None when the request is not
+    /// cacheable
+    fn path(&self, namespace: &str, params: &JsonRpcParams) -> Option<PathBuf> {
+        let method = match params.get("method") {
+            Some(ParamsValue::String(method)) if IMMUTABLE_METHODS.contains(&method.as_str()) => {
+                method
+            }
+            _ => return None,
+        };
+        let key = serde_json::to_string(params.get("params")?).ok()?;
+        let mut path = self.dir.clone();
+        path.push(hash(namespace.as_bytes()));
+        path.push(method);
+        path.push(hash(key.as_bytes()));
+        Some(path)
+    }
+}
+
+fn hash(data: &[u8]) -> String {
+    data.crypt_hash(Encryption::Secp256k1).completed_lower_hex()
+}
+
+#[cfg(test)]
+mod test {
+    use super::ResponseCache;
+    use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue};
+    use serde_json::json;
+    use std::env;
+
+    #[test]
+    fn test_cache() {
+        let mut dir = env::temp_dir();
+        dir.push(format!("cita-cache-{}", uuid::Uuid::new_v4()));
+        let cache = ResponseCache::new(&dir);
+        let params = |method: &str, hash: &str| {
+            JsonRpcParams::new()
+                .insert("method", ParamsValue::String(method.to_string()))
+                .insert(
+                    "params",
+                    ParamsValue::List(vec![
+                        ParamsValue::String(hash.to_string()),
+                        ParamsValue::Bool(false),
+                    ]),
+                )
+        };
+        let response = JsonRpcResponse::from_result(json!({"hash": "0x01"})).unwrap();
+        let node = "http://127.0.0.1:1337";
+
+        cache.put(node, &params("getBlockByHash", "0x01"), &response);
+        cache.put(node, &params("getBlockByNumber", "0x1"), &response);
+        cache.put(node, &params("blockNumber", "0x1"), &response);
+        assert_eq!(
+            cache
+                .get(node, &params("getBlockByHash", "0x01"))
+                .map(|response| response.to_string()),
+            Some(response.to_string())
+        );
+        assert!(cache.get(node, &params("getBlockByHash", "0x02")).is_none());
+        assert!(cache
+            .get(node, &params("getBlockByNumber", "0x1"))
+            .is_none());
+        assert!(cache.get(node, &params("blockNumber", "0x1")).is_none());
+
+        // The node of another chain doesn't share the entry
+        assert!(cache
+            .get("http://127.0.0.1:1338", &params("getBlockByHash", "0x01"))
+            .is_none());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
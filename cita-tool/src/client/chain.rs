@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use types::U256;
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::Notice;
+use crate::error::ToolError;
+use crate::protos::{TransactionVersion, UnverifiedTransaction};
+use crate::rpctypes::{ParamsValue, Quantity, ResponseValue};
+
+/// The identity of the chain of a node, read from its metadata
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainInfo {
+    /// `chainName` of the metadata
+    pub chain_name: String,
+    /// `chainId`, checked by the transactions of version 0
+    pub chain_id: u64,
+    /// `chainIdV1`, checked by the transactions of version 1 and later
+    pub chain_id_v1: U256,
+}
+
+impl ChainInfo {
+    /// The chain id a transaction of the version has to carry
+    pub fn chain_id_of(&self, version: u32) -> U256 {
+        if version == 0 {
+            U256::from(self.chain_id)
+        } else {
+            self.chain_id_v1
+        }
+    }
+}
+
+impl Client {
+    /// Get the name and the ids of the chain at the latest height
+    pub fn get_chain_info(&self) -> Result<ChainInfo, ToolError> {
+        let mut metadata = self.get_latest_metadata()?;
+        Ok(ChainInfo {
+            chain_name: match metadata.remove("chainName") {
+                Some(ParamsValue::String(name)) => name,
+                _ => String::new(),
+            },
+            chain_id: match metadata.remove("chainId") {
+                Some(ParamsValue::Null) | None => 0,
+                Some(chain_id) => Quantity::from_params(&chain_id)?.as_u64()?,
+            },
+            chain_id_v1: match metadata.remove("chainIdV1") {
+                Some(ParamsValue::Null) | None => U256::zero(),
+                Some(chain_id) => Quantity::from_params(&chain_id)?.0,
+            },
+        })
+    }
+
+    /// The metadata at the latest height, an error response is an error
+    pub(crate) fn get_latest_metadata(&self) -> Result<HashMap<String, ParamsValue>, ToolError> {
+        let response = self.get_metadata("latest")?;
+        if let Some(err) = response.error() {
+            return Err(ToolError::Customize(err.message()));
+        }
+        match response.result() {
+            Some(ResponseValue::Map(metadata)) => Ok(metadata),
+            _ => Err(ToolError::Customize(
+                "The node returns no metadata".to_string(),
+            )),
+        }
+    }
+
+    /// Before sending, compare the chain ids signed in the transactions of a batch with the
+    /// chain of the node, read once for the batch, a mismatch is notified and the
+    /// transaction is still sent
+    ///
+    /// A transaction signed for another chain is rejected by the node anyway, but a chain
+    /// sharing the id would accept it, so the replay is warned about before it happens.
+    pub(crate) fn check_chain(
+        &self,
+        unverified_txs: &[UnverifiedTransaction],
+    ) -> Result<(), ToolError> {
+        if !self.chain_check() || unverified_txs.is_empty() {
+            return Ok(());
+        }
+        let chain = self.get_chain_info()?;
+        for unverified_tx in unverified_txs {
+            let tx = unverified_tx.get_transaction();
+            let signed = TransactionVersion::of(tx)?.chain_id(tx);
+            let expected = chain.chain_id_of(tx.get_version());
+            if signed != expected {
+                self.notify(Notice::ChainMismatch {
+                    signed,
+                    expected,
+                    chain: &chain,
+                });
+            }
+        }
+        Ok(())
+    }
+}
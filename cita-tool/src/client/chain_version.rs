@@ -0,0 +1,261 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+use serde_json::{json, Value};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::MethodAliases;
+use crate::error::ToolError;
+use crate::protos::TransactionVersion;
+use crate::rpctypes::{JsonRpcParams, ParamsValue, Quantity, ResponseValue};
+
+/// A release of CITA, `major.minor.patch`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Release {
+    /// Major version
+    pub major: u32,
+    /// Minor version
+    pub minor: u32,
+    /// Patch version
+    pub patch: u32,
+}
+
+impl Release {
+    /// The release of the versions
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Release {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl FromStr for Release {
+    type Err = ToolError;
+
+    /// Parse `v20.2.0`, `0.25.0` or `20.2.0-rc.1`, a missing part is 0
+    fn from_str(version: &str) -> Result<Self, Self::Err> {
+        let invalid = || ToolError::Customize(format!("Invalid release {}", version));
+        let core = version
+            .trim()
+            .trim_start_matches('v')
+            .split(['-', '+'])
+            .next()
+            .unwrap_or_default();
+        let mut parts = core.split('.').map(|part| part.parse::<u32>());
+        let mut next = || parts.next().unwrap_or(Ok(0)).map_err(|_| invalid());
+        let release = Release::new(next()?, next()?, next()?);
+        if parts.next().is_some() {
+            return Err(invalid());
+        }
+        Ok(release)
+    }
+}
+
+impl fmt::Display for Release {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl Serialize for Release {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// A behaviour the releases of CITA differ in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Feature {
+    /// The blocks are sealed by BFT, the header proof is under `Bft` instead of `Tendermint`
+    BftProof,
+    /// The methods aren't prefixed by `cita_`, `eth_` and `net_`
+    UnprefixedMethods,
+}
+
+/// The release each feature comes with
+const FEATURES: &[(Feature, Release)] = &[
+    (Feature::BftProof, Release::new(0, 17, 0)),
+    (Feature::UnprefixedMethods, Release::new(0, 18, 0)),
+];
+
+/// What a node runs, the behaviours the client adapts to are decided by it
+///
+/// The release is read from `getVersion`, a node without it is taken as the latest release,
+/// the protocol version is the version of the metadata, the version of its transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainVersion {
+    /// The release of CITA, None when the node doesn't tell
+    pub release: Option<Release>,
+    /// The protocol version
+    pub protocol: u32,
+}
+
+impl ChainVersion {
+    /// The node of the release and the protocol version
+    pub fn new(release: Option<Release>, protocol: u32) -> Self {
+        ChainVersion { release, protocol }
+    }
+
+    /// Whether the node has the behaviour
+    pub fn supports(&self, feature: Feature) -> bool {
+        let since = FEATURES
+            .iter()
+            .find(|(known, _)| *known == feature)
+            .map(|(_, since)| *since);
+        match (self.release, since) {
+            (Some(release), Some(since)) => release >= since,
+            _ => true,
+        }
+    }
+
+    /// The behaviours of the node
+    pub fn features(&self) -> Vec<Feature> {
+        FEATURES
+            .iter()
+            .map(|(feature, _)| *feature)
+            .filter(|feature| self.supports(*feature))
+            .collect()
+    }
+
+    /// The version of the transactions the node accepts
+    pub fn transaction_version(&self) -> Result<TransactionVersion, ToolError> {
+        TransactionVersion::new(self.protocol)
+    }
+
+    /// The name the node knows the method by
+    pub fn method_name<'a>(&self, method: &'a str) -> &'a str {
+        if self.supports(Feature::UnprefixedMethods) {
+            return MethodAliases::names(method)
+                .first()
+                .copied()
+                .unwrap_or(method);
+        }
+        MethodAliases::names(method)
+            .iter()
+            .find(|name| name.contains('_'))
+            .copied()
+            .unwrap_or(method)
+    }
+
+    /// The consensus proof in the `proof` of a block header
+    pub fn block_proof<'a>(&self, proof: &'a Value) -> &'a Value {
+        if self.supports(Feature::BftProof) {
+            &proof["Bft"]
+        } else {
+            &proof["Tendermint"]
+        }
+    }
+
+    /// The params with the method renamed to the name the node knows
+    pub(crate) fn rename(&self, params: JsonRpcParams) -> JsonRpcParams {
+        let name = match params.method() {
+            Some(method) => self.method_name(method).to_owned(),
+            None => return params,
+        };
+        params.insert("method", ParamsValue::String(name))
+    }
+
+    /// The release, the protocol version and the behaviours as JSON
+    pub fn to_value(&self) -> Value {
+        json!({
+            "release": self.release,
+            "protocol": self.protocol,
+            "features": self.features(),
+        })
+    }
+}
+
+impl fmt::Display for ChainVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+impl Client {
+    /// The version of the node of the url, detected on first use and kept for the node
+    pub fn chain_version(&self) -> Result<ChainVersion, ToolError> {
+        match self.known_chain_version(&self.uri().to_string()) {
+            Some(version) => Ok(version),
+            None => self.detect_chain_version(),
+        }
+    }
+
+    /// Detect the version of the node of the url again, from `getVersion` and the metadata
+    ///
+    /// The protocol version falls back to the version manager when the metadata has none.
+    pub fn detect_chain_version(&self) -> Result<ChainVersion, ToolError> {
+        let release = match ClientExt::get_version(self).map(|response| response.result()) {
+            Ok(Some(ResponseValue::Map(mut version))) => match version.remove("softwareVersion") {
+                Some(ParamsValue::String(version)) => version.parse().ok(),
+                _ => None,
+            },
+            _ => None,
+        };
+        // The metadata is asked by the name the release knows
+        self.keep_chain_version(ChainVersion::new(release, 0));
+        let protocol = match self.get_latest_metadata()?.remove("version") {
+            Some(ParamsValue::Null) | None => self.get_version().unwrap_or(0),
+            Some(version) => Quantity::from_params(&version)?.as_u32()?,
+        };
+        let version = ChainVersion::new(release, protocol);
+        self.keep_chain_version(version);
+        Ok(version)
+    }
+
+    fn keep_chain_version(&self, version: ChainVersion) {
+        self.chain_versions()
+            .write()
+            .unwrap()
+            .insert(self.uri().to_string(), version);
+    }
+
+    /// The version detected for the node, None when it isn't
+    pub(crate) fn known_chain_version(&self, url: &str) -> Option<ChainVersion> {
+        self.chain_versions().read().unwrap().get(url).copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ChainVersion, Feature, Release};
+    use crate::rpctypes::{JsonRpcParams, Params};
+    use serde_json::json;
+
+    #[test]
+    fn test_chain_version() {
+        assert_eq!(
+            "v20.2.0-rc.1".parse::<Release>().unwrap(),
+            Release::new(20, 2, 0)
+        );
+        assert_eq!("0.17".parse::<Release>().unwrap(), Release::new(0, 17, 0));
+        assert!("0.17.0.1".parse::<Release>().is_err());
+        assert!("latest".parse::<Release>().is_err());
+
+        let latest = ChainVersion::new(None, 2);
+        assert!(latest.supports(Feature::UnprefixedMethods));
+        assert_eq!(latest.method_name("eth_getLogs"), "getLogs");
+        assert_eq!(latest.transaction_version().unwrap().as_u32(), 2);
+
+        let old = ChainVersion::new(Some(Release::new(0, 16, 3)), 0);
+        assert!(old.features().is_empty());
+        assert_eq!(old.method_name("getLogs"), "eth_getLogs");
+        assert_eq!(old.method_name("peersInfo"), "peersInfo");
+        let proof = json!({"Tendermint": {"height": 1}});
+        assert_eq!(old.block_proof(&proof)["height"], 1);
+        assert!(latest.block_proof(&proof).is_null());
+
+        let params = old.rename(JsonRpcParams::request("blockNumber", Params::None));
+        assert_eq!(params.method(), Some("cita_blockNumber"));
+        let middle = ChainVersion::new(Some(Release::new(0, 17, 1)), 0);
+        assert_eq!(middle.features(), vec![Feature::BftProof]);
+        assert_eq!(
+            middle.to_string(),
+            r#"{"features":["bftProof"],"protocol":0,"release":"0.17.1"}"#
+        );
+    }
+}
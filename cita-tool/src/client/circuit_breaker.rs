@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::client::rate_limit::normalize;
+
+/// State of the circuit of a url
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// The requests are sent
+    Closed,
+    /// The node keeps failing, the requests fail without being sent
+    Open,
+    /// The cooldown is over, one probe request is sent to see whether the node is back
+    HalfOpen,
+}
+
+impl fmt::Display for CircuitState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CircuitState::Closed => write!(f, "closed"),
+            CircuitState::Open => write!(f, "open"),
+            CircuitState::HalfOpen => write!(f, "half-open"),
+        }
+    }
+}
+
+/// Stop sending to the urls which keep failing, by a circuit per url
+///
+/// A circuit opens after the consecutive failures reach the threshold, the requests
+/// to it fail at once until the cooldown is over. Then one probe request is sent,
+/// its success closes the circuit, its failure opens it again. Only the transport
+/// failures count, a jsonrpc error means the node is up. The clones share the circuits.
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    threshold: u32,
+    cooldown: Duration,
+    circuits: Arc<Mutex<HashMap<String, Circuit>>>,
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    failures: u32,
+    opened: Instant,
+}
+
+/// A state change of a circuit, `(from, to)`
+pub type Transition = (CircuitState, CircuitState);
+
+impl CircuitBreaker {
+    /// Open a circuit after `threshold` consecutive failures, probe it after `cooldown`
+    pub fn new(threshold: u32, cooldown: Duration) -> Self {
+        CircuitBreaker {
+            threshold: threshold.max(1),
+            cooldown,
+            circuits: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// The state of the circuit of the url
+    pub fn state(&self, url: &str) -> CircuitState {
+        self.circuits
+            .lock()
+            .unwrap()
+            .get(normalize(url))
+            .map_or(CircuitState::Closed, |circuit| circuit.state)
+    }
+
+    /// Whether a request may be sent to the url, and the transition it caused
+    ///
+    /// An open circuit whose cooldown is over turns half-open and lets this request
+    /// through as the probe, the other requests wait for its result.
+    pub fn allow(&self, url: &str) -> (bool, Option<Transition>) {
+        self.allow_at(url, Instant::now())
+    }
+
+    /// Record the result of a request to the url, return the transition it caused
+    pub fn record(&self, url: &str, success: bool) -> Option<Transition> {
+        self.record_at(url, success, Instant::now())
+    }
+
+    fn allow_at(&self, url: &str, now: Instant) -> (bool, Option<Transition>) {
+        let mut circuits = self.circuits.lock().unwrap();
+        match circuits.get_mut(normalize(url)) {
+            Some(circuit) if circuit.state == CircuitState::Open => {
+                if now.saturating_duration_since(circuit.opened) >= self.cooldown {
+                    circuit.state = CircuitState::HalfOpen;
+                    (true, Some((CircuitState::Open, CircuitState::HalfOpen)))
+                } else {
+                    (false, None)
+                }
+            }
+            Some(circuit) if circuit.state == CircuitState::HalfOpen => (false, None),
+            _ => (true, None),
+        }
+    }
+
+    fn record_at(&self, url: &str, success: bool, now: Instant) -> Option<Transition> {
+        let mut circuits = self.circuits.lock().unwrap();
+        let circuit = circuits
+            .entry(normalize(url).to_owned())
+            .or_insert(Circuit {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened: now,
+            });
+        let from = circuit.state;
+        if success {
+            circuit.failures = 0;
+            circuit.state = CircuitState::Closed;
+        } else {
+            circuit.failures = circuit.failures.saturating_add(1);
+            if from == CircuitState::HalfOpen || circuit.failures >= self.threshold {
+                circuit.state = CircuitState::Open;
+                circuit.opened = now;
+            }
+        }
+        if from == circuit.state {
+            None
+        } else {
+            Some((from, circuit.state))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::{CircuitBreaker, CircuitState};
+
+    #[test]
+    fn test_transitions() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(10));
+        let url = "http://127.0.0.1:1337";
+        let now = Instant::now();
+
+        assert_eq!(breaker.record_at(url, false, now), None);
+        assert_eq!(
+            breaker.record_at(url, false, now),
+            Some((CircuitState::Closed, CircuitState::Open))
+        );
+        assert_eq!(breaker.allow_at(url, now), (false, None));
+        // Other urls are not affected
+        assert_eq!(breaker.allow_at("http://127.0.0.1:1338", now), (true, None));
+
+        // One probe after the cooldown, a failed probe opens the circuit again
+        let later = now + Duration::from_secs(10);
+        assert_eq!(
+            breaker.allow_at(url, later),
+            (true, Some((CircuitState::Open, CircuitState::HalfOpen)))
+        );
+        assert_eq!(breaker.allow_at(url, later), (false, None));
+        assert_eq!(
+            breaker.record_at(url, false, later),
+            Some((CircuitState::HalfOpen, CircuitState::Open))
+        );
+
+        let later = later + Duration::from_secs(10);
+        assert!(breaker.allow_at(&format!("{}/", url), later).0);
+        assert_eq!(
+            breaker.record_at(url, true, later),
+            Some((CircuitState::HalfOpen, CircuitState::Closed))
+        );
+        assert_eq!(breaker.state(url), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,208 @@
+use types::{Address, U256};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::system_contract::{
+    PriceManagerClient, PriceManagerExt, SysConfigClient, SysConfigExt,
+};
+use crate::client::Notice;
+use crate::crypto::pubkey_to_address;
+use crate::error::ToolError;
+use crate::protos::UnverifiedTransaction;
+use crate::rpctypes::{JsonRpcResponse, Quantity, ResponseValue};
+use crate::LowerHex;
+
+/// The fee a transaction costs at most on a chain in charge mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FeeEstimate {
+    /// Quota limit of the transaction
+    pub quota: u64,
+    /// Price of one quota
+    pub quota_price: U256,
+    /// `quota * quota_price`
+    pub max_fee: U256,
+}
+
+impl FeeEstimate {
+    /// Compute the max fee, saturate on overflow
+    pub fn new(quota: u64, quota_price: U256) -> Self {
+        let (max_fee, overflow) = quota_price.overflowing_mul(U256::from(quota));
+        FeeEstimate {
+            quota,
+            quota_price,
+            max_fee: if overflow { U256::max_value() } else { max_fee },
+        }
+    }
+}
+
+impl Client {
+    /// Whether the chain runs in charge mode, where transactions cost fees
+    pub fn is_charge_mode(&self) -> Result<bool, ToolError> {
+        let sys_config: SysConfigClient<Client> = SysConfigExt::create(self.clone());
+        let model = result_u256(SysConfigExt::get_economical_model(&sys_config, None)?)?;
+        Ok(model == U256::one())
+    }
+
+    /// Get the price of one quota
+    pub fn get_quota_price(&self) -> Result<U256, ToolError> {
+        let price_manager: PriceManagerClient<Client> = PriceManagerExt::create(self.clone());
+        result_u256(price_manager.price(None)?)
+    }
+
+    /// Estimate the max fee of a transaction with the quota,
+    /// `None` means the chain doesn't charge fees
+    pub fn estimate_fee(&self, quota: u64) -> Result<Option<FeeEstimate>, ToolError> {
+        Ok(self
+            .charge_price()?
+            .map(|quota_price| FeeEstimate::new(quota, quota_price)))
+    }
+
+    /// The price of one quota in charge mode, `None` means the chain doesn't charge fees
+    fn charge_price(&self) -> Result<Option<U256>, ToolError> {
+        if !self.is_charge_mode()? {
+            return Ok(None);
+        }
+        self.get_quota_price().map(Some)
+    }
+
+    /// Get the balance of the address at the latest height
+    pub fn get_current_balance(&self, address: &str) -> Result<U256, ToolError> {
+        result_u256(self.get_balance(address, "latest")?)
+    }
+
+    /// Before sending a batch, show the max fee of every transaction, compare it with the
+    /// max fee limit, and make sure every sender can afford the fees and the values of all
+    /// its transactions in the batch
+    ///
+    /// When the node can't tell the economical model, the quota price or a balance, the fee
+    /// isn't checked, that's notified and the transactions are still sent.
+    pub(crate) fn check_fee(
+        &self,
+        unverified_txs: &[UnverifiedTransaction],
+    ) -> Result<(), ToolError> {
+        if !self.fee_check() || unverified_txs.is_empty() {
+            return Ok(());
+        }
+        let quota_price = match self.charge_price() {
+            Ok(Some(quota_price)) => quota_price,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                self.notify(Notice::FeeUnchecked(&err));
+                return Ok(());
+            }
+        };
+
+        for unverified_tx in unverified_txs {
+            let estimate =
+                FeeEstimate::new(unverified_tx.get_transaction().get_quota(), quota_price);
+            self.notify(Notice::MaxFee(&estimate));
+            if let Some(max_fee) = self.max_fee() {
+                if estimate.max_fee > max_fee {
+                    return Err(ToolError::Customize(format!(
+                        "Max fee {} exceeds the limit {}",
+                        estimate.max_fee, max_fee
+                    )));
+                }
+            }
+        }
+
+        for (sender, required) in required_by_sender(unverified_txs, quota_price)? {
+            let sender = sender.completed_lower_hex_with_0x();
+            let balance = match self.get_current_balance(&sender) {
+                Ok(balance) => balance,
+                Err(err) => {
+                    self.notify(Notice::FeeUnchecked(&err));
+                    continue;
+                }
+            };
+            if balance < required {
+                return Err(ToolError::Customize(format!(
+                    "Insufficient balance of {}: {}, the fees and values need {}",
+                    sender, balance, required
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The max fees and the values of the transactions summed by sender, in the order of the batch
+fn required_by_sender(
+    unverified_txs: &[UnverifiedTransaction],
+    quota_price: U256,
+) -> Result<Vec<(Address, U256)>, ToolError> {
+    let mut required: Vec<(Address, U256)> = Vec::new();
+    for unverified_tx in unverified_txs {
+        let tx = unverified_tx.get_transaction();
+        // The transaction may be signed elsewhere, by another algorithm than the client key
+        let encryption = unverified_tx.encryption().ok_or_else(|| {
+            ToolError::Customize("Unknown algorithm of the transaction signature".to_string())
+        })?;
+        let sender = unverified_tx
+            .public_key(encryption)
+            .map(|pubkey| pubkey_to_address(&pubkey))
+            .map_err(ToolError::Customize)?;
+        let cost = FeeEstimate::new(tx.get_quota(), quota_price)
+            .max_fee
+            .saturating_add(U256::from(tx.get_value()));
+        match required.iter_mut().find(|(address, _)| *address == sender) {
+            Some((_, total)) => *total = total.saturating_add(cost),
+            None => required.push((sender, cost)),
+        }
+    }
+    Ok(required)
+}
+
+pub(crate) fn result_u256(response: JsonRpcResponse) -> Result<U256, ToolError> {
+    if let Some(err) = response.error() {
+        return Err(ToolError::Customize(err.message()));
+    }
+    match response.result() {
+        Some(ResponseValue::Singe(value)) => Quantity::from_params(&value).map(|value| value.0),
+        _ => Err(ToolError::Customize(
+            "Corresponding address does not respond".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{required_by_sender, FeeEstimate};
+    use crate::crypto::{pubkey_to_address, Encryption, KeyPair};
+    use crate::protos::{TransactionBuilder, TransactionVersion};
+    use types::U256;
+
+    #[test]
+    fn test_max_fee() {
+        let estimate = FeeEstimate::new(30_000, U256::from(1_000_000));
+        assert_eq!(estimate.max_fee, U256::from(30_000_000_000u64));
+
+        let estimate = FeeEstimate::new(2, U256::max_value());
+        assert_eq!(estimate.max_fee, U256::max_value());
+    }
+
+    #[test]
+    fn test_required_by_sender() {
+        let (alice, bob) = (
+            KeyPair::new(Encryption::Secp256k1),
+            KeyPair::new(Encryption::Sm2),
+        );
+        let transfer = |key_pair: &KeyPair, value: u64| {
+            TransactionBuilder::new(TransactionVersion::V2)
+                .set_quota(21_000)
+                .set_value(U256::from(value))
+                .build()
+                .unwrap()
+                .build_unverified(key_pair.privkey())
+        };
+        let batch = [transfer(&alice, 1), transfer(&bob, 2), transfer(&alice, 3)];
+
+        // The transactions of a sender in the batch add up
+        assert_eq!(
+            required_by_sender(&batch, U256::from(10)).unwrap(),
+            vec![
+                (pubkey_to_address(&alice.pubkey()), U256::from(420_004)),
+                (pubkey_to_address(&bob.pubkey()), U256::from(210_002)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,279 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{self, json, Value};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::remove_0x;
+use crate::error::ToolError;
+use crate::rpctypes::{JsonRpcResponse, Quantity};
+
+/// What a filter was installed with
+#[derive(Debug, Clone)]
+pub(crate) enum FilterKind {
+    /// Log filter
+    Logs {
+        topics: Option<Vec<String>>,
+        addresses: Option<Vec<String>>,
+        to: Option<String>,
+    },
+    /// Block filter
+    Blocks,
+}
+
+/// An installed filter, it's kept to reinstall the filter after the node expired it
+#[derive(Debug, Clone)]
+struct FilterState {
+    /// Id of the filter installed on the node now
+    current_id: String,
+    kind: FilterKind,
+    /// The first height the filter covers
+    start: u64,
+    /// (block number, log index) of the last delivered log
+    last_log: Option<(u64, u64)>,
+    /// Hash of the last delivered block
+    last_block: Option<String>,
+    /// Changes returned when renewing, they may be reported again by the new filter
+    replayed: HashSet<String>,
+}
+
+/// Filters installed by a client, keyed by the id of the first install,
+/// shared by all the clones of the client
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FilterRegistry {
+    filters: Arc<Mutex<HashMap<String, FilterState>>>,
+}
+
+impl Client {
+    /// The first height covered by a filter installed now with the `fromBlock`
+    pub(crate) fn filter_start(&self, from: Option<&str>) -> Result<u64, ToolError> {
+        match from.map(|from| u64::from_str_radix(remove_0x(from), 16)) {
+            Some(Ok(height)) => Ok(height),
+            _ => Ok(self.get_current_height()? + 1),
+        }
+    }
+
+    /// Remember the filter installed by the response
+    pub(crate) fn filter_installed(
+        &self,
+        response: &JsonRpcResponse,
+        kind: FilterKind,
+        start: u64,
+    ) {
+        if let Some(Value::String(id)) = response.result().map(|result| json!(result)) {
+            let state = FilterState {
+                current_id: id.clone(),
+                kind,
+                start,
+                last_log: None,
+                last_block: None,
+                replayed: HashSet::new(),
+            };
+            self.filters().filters.lock().unwrap().insert(id, state);
+        }
+    }
+
+    /// Forget the filter
+    pub(crate) fn filter_uninstalled(&self, id: &str) {
+        self.filters().filters.lock().unwrap().remove(id);
+    }
+
+    /// The id of the filter on the node, it changes every time the filter is renewed
+    pub(crate) fn installed_filter_id(&self, id: &str) -> String {
+        self.filters()
+            .filters
+            .lock()
+            .unwrap()
+            .get(id)
+            .map(|state| state.current_id.clone())
+            .unwrap_or_else(|| id.to_owned())
+    }
+
+    /// Track the delivered changes of the filter, a filter expired by the node is
+    /// reinstalled here and the changes missed in the meantime are returned instead
+    pub(crate) fn track_filter_changes(
+        &self,
+        id: &str,
+        response: JsonRpcResponse,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        let state = self.filters().filters.lock().unwrap().get(id).cloned();
+        let mut state = match state {
+            Some(state) => state,
+            None => return Ok(response),
+        };
+
+        let changes = match response.error() {
+            Some(ref err) if err.message().to_lowercase().contains("filter not found") => {
+                self.renew_filter(&mut state)?
+            }
+            Some(_) => return Ok(response),
+            None => {
+                let mut changes = match response.result().map(|result| json!(result)) {
+                    Some(Value::Array(changes)) => changes,
+                    _ => return Ok(response),
+                };
+                if !state.replayed.is_empty() {
+                    changes.retain(|change| !state.replayed.contains(&change_key(change)));
+                    state.replayed.clear();
+                }
+                changes
+            }
+        };
+
+        state.deliver(&changes);
+        self.filters()
+            .filters
+            .lock()
+            .unwrap()
+            .insert(id.to_owned(), state);
+        JsonRpcResponse::from_result(Value::Array(changes))
+    }
+
+    /// Reinstall the filter and collect what it missed since the last delivered change,
+    /// the new filter is tracked under the original id only
+    fn renew_filter(&self, state: &mut FilterState) -> Result<Vec<Value>, ToolError> {
+        let mut changes = Vec::new();
+        match state.kind {
+            FilterKind::Logs {
+                ref topics,
+                ref addresses,
+                ref to,
+            } => {
+                let topics = topics
+                    .as_ref()
+                    .map(|topics| topics.iter().map(String::as_str).collect());
+                let addresses = addresses
+                    .as_ref()
+                    .map(|addresses| addresses.iter().map(String::as_str).collect::<Vec<_>>());
+                state.current_id = filter_id(self.new_filter(
+                    topics.clone(),
+                    addresses.clone(),
+                    None,
+                    to.as_deref(),
+                )?)?;
+                self.filter_uninstalled(&state.current_id);
+
+                let from = state.last_log.map_or(state.start, |(height, _)| height);
+                let logs = result_value(self.get_logs(
+                    topics,
+                    addresses,
+                    Some(&format!("{:#x}", from)),
+                    to.as_deref(),
+                )?)?;
+                if let Value::Array(logs) = logs {
+                    changes.extend(logs.into_iter().filter(|log| {
+                        match (state.last_log, log_position(log)) {
+                            (Some(last), Some(position)) => position > last,
+                            _ => true,
+                        }
+                    }));
+                }
+            }
+            FilterKind::Blocks => {
+                state.current_id = filter_id(self.new_block_filter()?)?;
+                self.filter_uninstalled(&state.current_id);
+
+                let from = match state.last_block {
+                    Some(ref hash) => {
+                        let block = result_value(self.get_block_by_hash(hash, false)?)?;
+                        hex_number(&block["header"]["number"]).map_or(state.start, |h| h + 1)
+                    }
+                    None => state.start,
+                };
+                let current = self.get_current_height()?;
+                for height in from..=current {
+                    let block =
+                        result_value(self.get_block_by_number(&format!("{:#x}", height), false)?)?;
+                    changes.push(block["hash"].clone());
+                }
+            }
+        }
+        state.replayed = changes.iter().map(change_key).collect();
+        Ok(changes)
+    }
+}
+
+impl FilterState {
+    fn deliver(&mut self, changes: &[Value]) {
+        for change in changes {
+            match change {
+                Value::String(hash) => self.last_block = Some(hash.clone()),
+                log => {
+                    if let Some(position) = log_position(log) {
+                        self.last_log = self.last_log.max(Some(position));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Identify a change, the hash of a block or the position of a log
+fn change_key(change: &Value) -> String {
+    match change {
+        Value::String(hash) => hash.clone(),
+        log => format!(
+            "{}:{}",
+            log["blockNumber"].as_str().unwrap_or_default(),
+            log["logIndex"].as_str().unwrap_or_default()
+        ),
+    }
+}
+
+fn log_position(log: &Value) -> Option<(u64, u64)> {
+    Some((
+        hex_number(&log["blockNumber"])?,
+        hex_number(&log["logIndex"])?,
+    ))
+}
+
+fn hex_number(value: &Value) -> Option<u64> {
+    Quantity::from_json(value)?.as_u64().ok()
+}
+
+fn result_value(response: JsonRpcResponse) -> Result<Value, ToolError> {
+    if let Some(err) = response.error() {
+        return Err(ToolError::Customize(err.message()));
+    }
+    serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)
+}
+
+fn filter_id(response: JsonRpcResponse) -> Result<String, ToolError> {
+    match result_value(response)? {
+        Value::String(id) => Ok(id),
+        _ => Err(ToolError::Customize(
+            "Can't reinstall the filter".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{change_key, FilterKind, FilterState};
+    use serde_json::json;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_deliver() {
+        let mut state = FilterState {
+            current_id: "0x1".to_string(),
+            kind: FilterKind::Blocks,
+            start: 10,
+            last_log: None,
+            last_block: None,
+            replayed: HashSet::new(),
+        };
+        let logs = [
+            json!({"blockNumber": "0xb", "logIndex": "0x2"}),
+            json!({"blockNumber": "0xc", "logIndex": "0x0"}),
+            json!({"blockNumber": "0xb", "logIndex": "0x3"}),
+        ];
+        state.deliver(&logs);
+        assert_eq!(state.last_log, Some((12, 0)));
+        assert_eq!(change_key(&logs[0]), "0xb:0x2");
+
+        state.deliver(&[json!("0xabcd")]);
+        assert_eq!(state.last_block, Some("0xabcd".to_string()));
+        assert_eq!(change_key(&json!("0xabcd")), "0xabcd");
+    }
+}
@@ -0,0 +1,460 @@
+use std::sync::Mutex;
+
+use futures::{future, Future, Poll};
+use hyper::body::Payload;
+use hyper::client::HttpConnector;
+use hyper::{Body, HeaderMap, Request, Uri};
+use protobuf::{CodedInputStream, CodedOutputStream};
+use serde_json::{json, Value};
+use tokio::runtime::Runtime;
+
+use crate::client::basic::ClientExt;
+use crate::client::{remove_0x, TransactionOptions};
+use crate::error::ToolError;
+use crate::rpctypes::JsonRpcResponse;
+
+/// The service of the controller answering the chain queries
+const CONTROLLER_SERVICE: &str = "controller.RPCService";
+/// The service of the executor running the calls
+const EXECUTOR_SERVICE: &str = "executor.ExecutorService";
+
+/// A client speaking gRPC to the controller and the executor components directly,
+/// without the JSON-RPC gateway
+///
+/// Only the queries the component APIs have are supported, the others fail with
+/// `ToolError::Customize`. The responses are shaped like the JSON-RPC ones, so the
+/// code using `ClientExt` works with both transports. The components are reached by
+/// plain HTTP/2, as they usually listen on the loopback or a private network.
+pub struct GrpcClient {
+    controller: Uri,
+    executor: Option<Uri>,
+    client: hyper::Client<HttpConnector>,
+    runtime: Mutex<Runtime>,
+}
+
+impl GrpcClient {
+    /// Create a client of the controller at the url, such as `http://127.0.0.1:50004`
+    pub fn new(controller: &str) -> Result<Self, ToolError> {
+        Ok(GrpcClient {
+            controller: parse_uri(controller)?,
+            executor: None,
+            client: hyper::Client::builder().http2_only(true).build_http(),
+            runtime: Mutex::new(Runtime::new().map_err(ToolError::Stdio)?),
+        })
+    }
+
+    /// Set the url of the executor, needed by `call`
+    pub fn set_executor(mut self, executor: &str) -> Result<Self, ToolError> {
+        self.executor = Some(parse_uri(executor)?);
+        Ok(self)
+    }
+
+    /// Send the message to the method of the service and return the response message
+    fn unary(
+        &self,
+        base: &Uri,
+        service: &str,
+        method: &str,
+        message: Vec<u8>,
+    ) -> Result<Vec<u8>, ToolError> {
+        let url: Uri = format!(
+            "{}/{}/{}",
+            base.to_string().trim_end_matches('/'),
+            service,
+            method
+        )
+        .parse()
+        .map_err(|_| ToolError::Customize(format!("Invalid url: {}", base)))?;
+        let req: Request<Body> = Request::builder()
+            .uri(url)
+            .method("POST")
+            .header("content-type", "application/grpc")
+            .header("te", "trailers")
+            .body(Body::from(frame(&message)))
+            .unwrap();
+
+        let response = self
+            .client
+            .request(req)
+            .map_err(ToolError::Hyper)
+            .and_then(|response| {
+                let (parts, body) = response.into_parts();
+                read_body(body).map(move |(data, trailers)| (parts.headers, data, trailers))
+            });
+        let (headers, data, trailers) = self.runtime.lock().unwrap().block_on(response)?;
+        // A failing call may only have the headers, without the data and the trailers
+        check_status(trailers.as_ref().unwrap_or(&headers))?;
+        unframe(&data)
+    }
+
+    fn executor(&self) -> Result<&Uri, ToolError> {
+        self.executor.as_ref().ok_or_else(|| {
+            ToolError::Customize("The url of the executor is not set for the call".to_string())
+        })
+    }
+}
+
+fn parse_uri(url: &str) -> Result<Uri, ToolError> {
+    url.parse()
+        .map_err(|_| ToolError::Customize(format!("Invalid url: {}", url)))
+}
+
+/// The length-prefixed message of gRPC, not compressed
+fn frame(message: &[u8]) -> Vec<u8> {
+    let mut framed = Vec::with_capacity(message.len() + 5);
+    framed.push(0);
+    framed.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    framed.extend_from_slice(message);
+    framed
+}
+
+fn unframe(data: &[u8]) -> Result<Vec<u8>, ToolError> {
+    if data.len() < 5 {
+        return Err(ToolError::Customize("Empty gRPC response".to_string()));
+    }
+    if data[0] != 0 {
+        return Err(ToolError::Customize(
+            "Compressed gRPC responses are not supported".to_string(),
+        ));
+    }
+    let length = u32::from_be_bytes([data[1], data[2], data[3], data[4]]) as usize;
+    data.get(5..5 + length)
+        .map(ToOwned::to_owned)
+        .ok_or_else(|| ToolError::Customize("Truncated gRPC response".to_string()))
+}
+
+/// Read all the data of the body and then its trailers, where gRPC puts the status
+fn read_body(
+    mut body: Body,
+) -> impl Future<Item = (Vec<u8>, Option<HeaderMap>), Error = ToolError> {
+    let mut data = Vec::new();
+    future::poll_fn(
+        move || -> Poll<(Vec<u8>, Option<HeaderMap>), hyper::Error> {
+            while let Some(chunk) = futures::try_ready!(body.poll_data()) {
+                data.extend_from_slice(&chunk);
+            }
+            let trailers = futures::try_ready!(body.poll_trailers());
+            Ok((std::mem::take(&mut data), trailers).into())
+        },
+    )
+    .map_err(ToolError::Hyper)
+}
+
+fn check_status(headers: &HeaderMap) -> Result<(), ToolError> {
+    let status = headers
+        .get("grpc-status")
+        .and_then(|status| status.to_str().ok())
+        .unwrap_or("0");
+    if status == "0" {
+        return Ok(());
+    }
+    let message = headers
+        .get("grpc-message")
+        .and_then(|message| message.to_str().ok())
+        .unwrap_or_default();
+    Err(ToolError::Customize(format!(
+        "gRPC status {}: {}",
+        status, message
+    )))
+}
+
+/// Encode a message by writing its fields in order
+fn encode_message<F>(write: F) -> Result<Vec<u8>, ToolError>
+where
+    F: FnOnce(&mut CodedOutputStream) -> protobuf::ProtobufResult<()>,
+{
+    let mut bytes = Vec::new();
+    {
+        let mut stream = CodedOutputStream::vec(&mut bytes);
+        write(&mut stream).map_err(ToolError::Proto)?;
+        stream.flush().map_err(ToolError::Proto)?;
+    }
+    Ok(bytes)
+}
+
+/// A field of a decoded message, the unknown ones are skipped
+enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+/// Decode the varint and the length-delimited fields of a message
+fn decode_message(bytes: &[u8]) -> Result<Vec<(u32, Field)>, ToolError> {
+    use protobuf::wire_format::WireType;
+
+    let mut stream = CodedInputStream::from_bytes(bytes);
+    let mut fields = Vec::new();
+    while !stream.eof().map_err(ToolError::Proto)? {
+        let (number, wire_type) = stream.read_tag_unpack().map_err(ToolError::Proto)?;
+        match wire_type {
+            WireType::WireTypeVarint => fields.push((
+                number,
+                Field::Varint(stream.read_uint64().map_err(ToolError::Proto)?),
+            )),
+            WireType::WireTypeLengthDelimited => fields.push((
+                number,
+                Field::Bytes(stream.read_bytes().map_err(ToolError::Proto)?),
+            )),
+            other => stream.skip_field(other).map_err(ToolError::Proto)?,
+        }
+    }
+    Ok(fields)
+}
+
+/// The varint field of the number, 0 when absent as in proto3
+fn varint_field(fields: &[(u32, Field)], number: u32) -> u64 {
+    fields
+        .iter()
+        .find_map(|field| match field {
+            (found, Field::Varint(value)) if *found == number => Some(*value),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+/// The bytes field of the number, empty when absent as in proto3
+fn bytes_field(fields: &[(u32, Field)], number: u32) -> Vec<u8> {
+    fields
+        .iter()
+        .find_map(|field| match field {
+            (found, Field::Bytes(value)) if *found == number => Some(value.clone()),
+            _ => None,
+        })
+        .unwrap_or_default()
+}
+
+fn hex_bytes(hex: &str) -> Result<Vec<u8>, ToolError> {
+    hex::decode(remove_0x(hex)).map_err(ToolError::Decode)
+}
+
+fn unsupported(method: &str) -> Result<JsonRpcResponse, ToolError> {
+    Err(ToolError::Customize(format!(
+        "{} is not supported by the gRPC transport",
+        method
+    )))
+}
+
+fn response(result: Value) -> Result<JsonRpcResponse, ToolError> {
+    JsonRpcResponse::from_result(result)
+}
+
+impl ClientExt<JsonRpcResponse, ToolError> for GrpcClient {
+    fn get_peer_count(&self) -> Result<JsonRpcResponse, ToolError> {
+        // Empty -> PeerCount { uint64 peer_count = 1 }
+        let reply = self.unary(
+            &self.controller,
+            CONTROLLER_SERVICE,
+            "GetPeerCount",
+            Vec::new(),
+        )?;
+        let peer_count = varint_field(&decode_message(&reply)?, 1);
+        response(json!(format!("{:#x}", peer_count)))
+    }
+
+    fn get_peers_info(&self) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("peersInfo")
+    }
+
+    fn get_block_number(&self) -> Result<JsonRpcResponse, ToolError> {
+        // Flag { bool flag = 1 }, false for the latest block -> BlockNumber { uint64 block_number = 1 }
+        let request = encode_message(|stream| stream.write_bool(1, false))?;
+        let reply = self.unary(
+            &self.controller,
+            CONTROLLER_SERVICE,
+            "GetBlockNumber",
+            request,
+        )?;
+        let height = varint_field(&decode_message(&reply)?, 1);
+        response(json!(format!("{:#x}", height)))
+    }
+
+    fn send_raw_transaction(
+        &self,
+        _transaction_option: TransactionOptions,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("sendRawTransaction")
+    }
+
+    fn get_block_by_hash(
+        &self,
+        _hash: &str,
+        _transaction_info: bool,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getBlockByHash")
+    }
+
+    fn get_block_by_number(
+        &self,
+        _height: &str,
+        _transaction_info: bool,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getBlockByNumber")
+    }
+
+    fn get_transaction_receipt(&self, _hash: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getTransactionReceipt")
+    }
+
+    fn get_logs(
+        &self,
+        _topic: Option<Vec<&str>>,
+        _address: Option<Vec<&str>>,
+        _from: Option<&str>,
+        _to: Option<&str>,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getLogs")
+    }
+
+    fn call(
+        &self,
+        from: Option<&str>,
+        to: &str,
+        data: Option<&str>,
+        height: &str,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        // CallRequest { bytes from = 1; bytes to = 2; bytes method = 3; repeated bytes args = 4;
+        // uint64 height = 5 }, height 0 is the latest -> CallResponse { bytes value = 1 }
+        let height = match height {
+            "latest" | "pending" => 0,
+            height => u64::from_str_radix(remove_0x(height), 16).map_err(ToolError::Parse)?,
+        };
+        let (from, to, data) = (
+            hex_bytes(from.unwrap_or_default())?,
+            hex_bytes(to)?,
+            hex_bytes(data.unwrap_or_default())?,
+        );
+        let request = encode_message(|stream| {
+            stream.write_bytes(1, &from)?;
+            stream.write_bytes(2, &to)?;
+            stream.write_bytes(3, &data)?;
+            stream.write_uint64(5, height)
+        })?;
+        let reply = self.unary(self.executor()?, EXECUTOR_SERVICE, "Call", request)?;
+        let value = bytes_field(&decode_message(&reply)?, 1);
+        response(json!(format!("0x{}", hex::encode(value))))
+    }
+
+    fn get_transaction(&self, _hash: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getTransaction")
+    }
+
+    fn get_transaction_count(
+        &self,
+        _address: &str,
+        _height: &str,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getTransactionCount")
+    }
+
+    fn get_code(&self, _address: &str, _height: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getCode")
+    }
+
+    fn get_abi(&self, _address: &str, _height: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getAbi")
+    }
+
+    fn get_balance(&self, _address: &str, _height: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getBalance")
+    }
+
+    fn new_filter(
+        &self,
+        _topic: Option<Vec<&str>>,
+        _address: Option<Vec<&str>>,
+        _from: Option<&str>,
+        _to: Option<&str>,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("newFilter")
+    }
+
+    fn new_block_filter(&self) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("newBlockFilter")
+    }
+
+    fn uninstall_filter(&self, _filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("uninstallFilter")
+    }
+
+    fn get_filter_changes(&self, _filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getFilterChanges")
+    }
+
+    fn get_filter_logs(&self, _filter_id: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getFilterLogs")
+    }
+
+    fn get_transaction_proof(&self, _hash: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getTransactionProof")
+    }
+
+    fn get_metadata(&self, _height: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getMetaData")
+    }
+
+    fn get_block_header(&self, _height: &str) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getBlockHeader")
+    }
+
+    fn get_state_proof(
+        &self,
+        _address: &str,
+        _key: &str,
+        _height: &str,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getStateProof")
+    }
+
+    fn get_storage_at(
+        &self,
+        _address: &str,
+        _key: &str,
+        _height: &str,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("getStorageAt")
+    }
+
+    fn get_version(&self) -> Result<JsonRpcResponse, ToolError> {
+        // Empty -> SoftwareVersion { string version = 1 }
+        let reply = self.unary(
+            &self.controller,
+            CONTROLLER_SERVICE,
+            "GetVersion",
+            Vec::new(),
+        )?;
+        let version = bytes_field(&decode_message(&reply)?, 1);
+        response(json!({ "softwareVersion": String::from_utf8_lossy(&version) }))
+    }
+
+    fn estimate_quota(
+        &self,
+        _from: Option<&str>,
+        _to: &str,
+        _data: Option<&str>,
+        _height: &str,
+    ) -> Result<JsonRpcResponse, ToolError> {
+        unsupported("estimateQuota")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_message, encode_message, frame, unframe, varint_field};
+
+    #[test]
+    fn test_grpc_message() {
+        let message = encode_message(|stream| {
+            stream.write_uint64(1, 300)?;
+            stream.write_bytes(2, b"skipped")?;
+            stream.write_fixed32(3, 7)
+        })
+        .unwrap();
+        let framed = frame(&message);
+        assert_eq!(&framed[..5], &[0, 0, 0, 0, message.len() as u8]);
+        let fields = decode_message(&unframe(&framed).unwrap()).unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(varint_field(&fields, 1), 300);
+        assert_eq!(varint_field(&fields, 4), 0);
+        assert!(unframe(&framed[..4]).is_err());
+    }
+}
@@ -0,0 +1,791 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use hex;
+use serde_json::Value;
+use types::{Address, H256, U256};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::{remove_0x, ChainVersion};
+use crate::crypto::{pubkey_to_address, Encryption, Hashable, Signature};
+use crate::error::ToolError;
+use crate::rpctypes::{JsonRpcResponse, Quantity};
+use crate::LowerHex;
+
+/// Variant index of `Step::Precommit` in the BFT consensus messages
+const STEP_PRECOMMIT: u32 = 5;
+
+/// A block header whose hash is signed by a quorum of the validators
+#[derive(Debug, Clone, Serialize)]
+pub struct VerifiedHeader {
+    /// Block height
+    pub height: u64,
+    /// Block hash, the hash of the encoded header
+    pub hash: H256,
+    /// Hash of the previous block
+    pub prev_hash: H256,
+    /// Block timestamp in milliseconds
+    pub timestamp: u64,
+    /// Root of the state trie after the block
+    pub state_root: H256,
+    /// Root of the transactions of the block
+    pub transactions_root: H256,
+    /// Root of the receipts of the block
+    pub receipts_root: H256,
+}
+
+/// The BFT proof carried by a block, the precommits of the previous block
+#[derive(Debug, Clone)]
+pub struct BftProof {
+    /// Hash of the committed block
+    pub proposal: H256,
+    /// Height of the committed block
+    pub height: u64,
+    /// Round the block is committed in
+    pub round: u64,
+    /// The validators and their precommit signatures, in the order of the node
+    pub commits: Vec<(Address, Vec<u8>)>,
+}
+
+impl BftProof {
+    /// Decode the `proof` of a block header, it is `{"Bft": {...}}` or the inner object,
+    /// `None` when it isn't a BFT proof
+    pub fn from_json(proof: &Value) -> Option<Self> {
+        let proof = if proof["Bft"].is_object() {
+            &proof["Bft"]
+        } else {
+            proof
+        };
+        let number =
+            |name: &str| Quantity::from_json(&proof[name]).and_then(|number| number.as_u64().ok());
+        let mut commits = Vec::new();
+        for (sender, signature) in proof["commits"].as_object()? {
+            let sender = Address::from_str(remove_0x(sender)).ok()?;
+            let signature = hex::decode(remove_0x(signature.as_str()?)).ok()?;
+            commits.push((sender, signature));
+        }
+        Some(BftProof {
+            proposal: parse_h256(&proof["proposal"])?,
+            height: number("height")?,
+            round: number("round").unwrap_or_default(),
+            commits,
+        })
+    }
+
+    /// Whether the precommit of the sender is signed by it
+    pub fn is_signed_by(&self, sender: &Address, signature: &[u8], encryption: Encryption) -> bool {
+        let message = precommit_message(self.height, self.round, sender, &self.proposal);
+        match Signature::from(signature).recover(&message.crypt_hash(encryption)) {
+            Ok(pubkey) => pubkey_to_address(&pubkey) == *sender,
+            Err(_) => false,
+        }
+    }
+}
+
+/// Download block headers one by one and verify them against the validator set
+///
+/// Every header must hash to the block hash, link to the verified head, and be
+/// committed by more than 2/3 of the validators in the BFT proof of the next block.
+pub struct LightClient {
+    client: Client,
+    encryption: Encryption,
+    version: ChainVersion,
+    validators: Vec<Address>,
+    head: VerifiedHeader,
+}
+
+impl LightClient {
+    /// Start from a trusted block, the validators are read from the metadata at its height
+    ///
+    /// The block is trusted as is, pass its hash to make sure the node serves the expected one.
+    pub fn new(
+        client: Client,
+        encryption: Encryption,
+        height: u64,
+        trusted_hash: Option<H256>,
+    ) -> Result<Self, ToolError> {
+        let version = client.chain_version()?;
+        let mut light = LightClient {
+            client,
+            encryption,
+            version,
+            validators: Vec::new(),
+            head: VerifiedHeader {
+                height: 0,
+                hash: H256::default(),
+                prev_hash: H256::default(),
+                timestamp: 0,
+                state_root: H256::default(),
+                transactions_root: H256::default(),
+                receipts_root: H256::default(),
+            },
+        };
+        let (head, _) = light.fetch_header(height)?;
+        if let Some(hash) = trusted_hash {
+            if hash != head.hash {
+                return Err(ToolError::Customize(format!(
+                    "The block {} is {:#x}, not the trusted {:#x}",
+                    height, head.hash, hash
+                )));
+            }
+        }
+        let metadata = result_value(light.client.get_metadata(&format!("{:#x}", height))?)?;
+        light.validators = metadata["validators"]
+            .as_array()
+            .map(|validators| {
+                validators
+                    .iter()
+                    .filter_map(|address| address.as_str())
+                    .filter_map(|address| Address::from_str(remove_0x(address)).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        light.head = head;
+        Ok(light)
+    }
+
+    /// Replace the validator set used to check the proofs
+    pub fn set_validators(mut self, validators: Vec<Address>) -> Self {
+        self.validators = validators;
+        self
+    }
+
+    /// The validator set used to check the proofs
+    pub fn validators(&self) -> &[Address] {
+        &self.validators
+    }
+
+    /// The latest verified header
+    pub fn head(&self) -> &VerifiedHeader {
+        &self.head
+    }
+
+    /// Verify the block after the head, and make it the new head
+    pub fn verify_next(&mut self) -> Result<&VerifiedHeader, ToolError> {
+        let height = self.head.height + 1;
+        let (header, _) = self.fetch_header(height)?;
+        if header.prev_hash != self.head.hash {
+            return Err(ToolError::Customize(format!(
+                "Block {} doesn't link to the verified block {:#x}",
+                height, self.head.hash
+            )));
+        }
+
+        // The proof of a block is carried by the header of the next block
+        let (_, block) = self.fetch_header(height + 1)?;
+        self.check_proof(self.version.block_proof(&block["header"]["proof"]), &header)?;
+        self.head = header;
+        Ok(&self.head)
+    }
+
+    /// Verify the blocks up to the height
+    pub fn sync_to(&mut self, height: u64) -> Result<&VerifiedHeader, ToolError> {
+        while self.head.height < height {
+            self.verify_next()?;
+        }
+        Ok(&self.head)
+    }
+
+    /// Balance of the account at the head, proved against the state root of the head
+    pub fn verified_get_balance(&self, address: &Address) -> Result<U256, ToolError> {
+        let proof = self.state_proof(address, &H256::zero())?;
+        Ok(self
+            .verify_account(address, &proof)?
+            .map_or_else(U256::zero, |account| account.balance))
+    }
+
+    /// Storage value of the account at the head, proved against the state root of the head
+    pub fn verified_get_storage(&self, address: &Address, key: &H256) -> Result<H256, ToolError> {
+        let proof = self.state_proof(address, key)?;
+        let account = match self.verify_account(address, &proof)? {
+            Some(account) => account,
+            None => return Ok(H256::zero()),
+        };
+        let value = verify_trie_proof(
+            &account.storage_root,
+            &key.crypt_hash(self.encryption),
+            &proof.value_proof,
+            self.encryption,
+        )
+        .map_err(|err| ToolError::Customize(format!("Invalid storage proof: {}", err)))?;
+        match value {
+            Some(value) => {
+                let (_, value, _) = rlp_split(&value)
+                    .filter(|(is_list, value, rest)| {
+                        !is_list && value.len() <= 32 && rest.is_empty()
+                    })
+                    .ok_or_else(|| ToolError::Customize("Invalid storage value".to_string()))?;
+                let mut word = [0u8; 32];
+                word[32 - value.len()..].copy_from_slice(value);
+                Ok(H256::from(word))
+            }
+            None => Ok(H256::zero()),
+        }
+    }
+
+    /// Download the proof of the storage key of the account at the head
+    fn state_proof(&self, address: &Address, key: &H256) -> Result<StateProof, ToolError> {
+        let encoded = result_value(self.client.get_state_proof(
+            &address.completed_lower_hex_with_0x(),
+            &key.completed_lower_hex_with_0x(),
+            &format!("{:#x}", self.head.height),
+        )?)?;
+        let encoded = hex::decode(remove_0x(encoded.as_str().unwrap_or_default()))
+            .map_err(ToolError::Decode)?;
+        StateProof::decode(&encoded)
+            .filter(|proof| proof.address == *address && proof.key == *key)
+            .ok_or_else(|| ToolError::Customize("Invalid state proof".to_string()))
+    }
+
+    /// Check the account proof against the state root, None when the account doesn't exist
+    fn verify_account(
+        &self,
+        address: &Address,
+        proof: &StateProof,
+    ) -> Result<Option<Account>, ToolError> {
+        let invalid =
+            |reason: &str| ToolError::Customize(format!("Invalid account proof: {}", reason));
+        let account = verify_trie_proof(
+            &self.head.state_root,
+            &address.crypt_hash(self.encryption),
+            &proof.account_proof,
+            self.encryption,
+        )
+        .map_err(invalid)?;
+        match account {
+            Some(account) => {
+                let items = rlp_items(&account).ok_or_else(|| invalid("bad account encoding"))?;
+                if items.len() < 3 || items[1].len() > 32 || items[2].len() != 32 {
+                    return Err(invalid("bad account encoding"));
+                }
+                Ok(Some(Account {
+                    balance: U256::from_big_endian(items[1]),
+                    storage_root: H256::from(items[2]),
+                }))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Download the header and check that the block hash is the hash of it,
+    /// return the header and the block
+    fn fetch_header(&self, height: u64) -> Result<(VerifiedHeader, Value), ToolError> {
+        let height_hex = format!("{:#x}", height);
+        let encoded = result_value(self.client.get_block_header(&height_hex)?)?;
+        let encoded = hex::decode(remove_0x(encoded.as_str().unwrap_or_default()))
+            .map_err(ToolError::Decode)?;
+        let block = result_value(self.client.get_block_by_number(&height_hex, false)?)?;
+
+        let hash = encoded.crypt_hash(self.encryption);
+        if Some(hash) != parse_h256(&block["hash"]) {
+            return Err(ToolError::Customize(format!(
+                "The hash of block {} doesn't match its header",
+                height
+            )));
+        }
+
+        // The fields read from the block must be the ones at their places in the hashed header
+        let fields = HeaderFields::decode(&encoded)
+            .filter(|fields| fields.number == height)
+            .ok_or_else(|| {
+                ToolError::Customize(format!("Invalid header encoding of block {}", height))
+            })?;
+        let header = &block["header"];
+        for name in HeaderFields::ROOTS.iter() {
+            if parse_h256(&header[*name]) != fields.root(name) {
+                return Err(ToolError::Customize(format!(
+                    "The {} of block {} is not the one in its header",
+                    name, height
+                )));
+            }
+        }
+
+        let verified = VerifiedHeader {
+            height,
+            hash,
+            prev_hash: fields.prev_hash,
+            timestamp: fields.timestamp,
+            state_root: fields.state_root,
+            transactions_root: fields.transactions_root,
+            receipts_root: fields.receipts_root,
+        };
+        Ok((verified, block))
+    }
+
+    /// Check the BFT proof commits the header with more than 2/3 of the validators, every
+    /// validator counted once however its address is spelled
+    fn check_proof(&self, proof: &Value, header: &VerifiedHeader) -> Result<(), ToolError> {
+        let invalid = |reason: &str| {
+            Err(ToolError::Customize(format!(
+                "Invalid proof of block {}: {}",
+                header.height, reason
+            )))
+        };
+        let proof = match BftProof::from_json(proof) {
+            Some(proof) => proof,
+            None => return invalid("no valid commits"),
+        };
+        if proof.height != header.height {
+            return invalid("height mismatch");
+        }
+        if proof.proposal != header.hash {
+            return invalid("proposal mismatch");
+        }
+
+        let validators = self.validators.iter().collect::<HashSet<&Address>>();
+        let mut signers = HashSet::new();
+        for (sender, signature) in &proof.commits {
+            if !validators.contains(sender) {
+                return invalid(&format!("{:#x} is not a validator", sender));
+            }
+            if !signers.insert(sender) {
+                return invalid(&format!("duplicate commit of {:#x}", sender));
+            }
+            if !proof.is_signed_by(sender, signature, self.encryption) {
+                return invalid(&format!("bad signature of {:#x}", sender));
+            }
+        }
+        if signers.len() * 3 <= validators.len() * 2 {
+            return invalid(&format!(
+                "only {} of {} validators committed",
+                signers.len(),
+                validators.len()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The fields of an encoded header, read by their places in the RLP list of the header of
+/// CITA: `[prev hash, timestamp, number, transactions root, state root, receipts root, ...]`
+pub(crate) struct HeaderFields {
+    pub prev_hash: H256,
+    pub timestamp: u64,
+    pub number: u64,
+    pub transactions_root: H256,
+    pub state_root: H256,
+    pub receipts_root: H256,
+}
+
+impl HeaderFields {
+    /// The names of the hashes in the JSON of a header
+    pub const ROOTS: [&'static str; 4] =
+        ["prevHash", "stateRoot", "transactionsRoot", "receiptsRoot"];
+
+    /// Decode the header, None when it isn't a valid one
+    pub fn decode(encoded: &[u8]) -> Option<Self> {
+        let items = rlp_list(encoded)?;
+        if items.len() < 6 || items[..6].iter().any(|item| item.is_list) {
+            return None;
+        }
+        let hash = |index: usize| -> Option<H256> {
+            Some(items[index].payload)
+                .filter(|payload| payload.len() == 32)
+                .map(H256::from)
+        };
+        let number = |index: usize| -> Option<u64> {
+            Some(items[index].payload)
+                .filter(|payload| payload.len() <= 8)
+                .map(|payload| {
+                    payload
+                        .iter()
+                        .fold(0, |number, byte| (number << 8) | u64::from(*byte))
+                })
+        };
+        Some(HeaderFields {
+            prev_hash: hash(0)?,
+            timestamp: number(1)?,
+            number: number(2)?,
+            transactions_root: hash(3)?,
+            state_root: hash(4)?,
+            receipts_root: hash(5)?,
+        })
+    }
+
+    /// The hash of the name in `ROOTS`
+    pub fn root(&self, name: &str) -> Option<H256> {
+        match name {
+            "prevHash" => Some(self.prev_hash),
+            "stateRoot" => Some(self.state_root),
+            "transactionsRoot" => Some(self.transactions_root),
+            "receiptsRoot" => Some(self.receipts_root),
+            _ => None,
+        }
+    }
+}
+
+/// The precommit vote signed by a validator, bincode of
+/// `(height, round, Step::Precommit, sender, Some(proposal))`,
+/// where the hashes are serialized as 0x-prefixed hex strings
+fn precommit_message(height: u64, round: u64, sender: &Address, proposal: &H256) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(&height.to_le_bytes());
+    message.extend_from_slice(&round.to_le_bytes());
+    message.extend_from_slice(&STEP_PRECOMMIT.to_le_bytes());
+    let sender = sender.completed_lower_hex_with_0x();
+    message.extend_from_slice(&(sender.len() as u64).to_le_bytes());
+    message.extend_from_slice(sender.as_bytes());
+    message.push(1);
+    let proposal = proposal.completed_lower_hex_with_0x();
+    message.extend_from_slice(&(proposal.len() as u64).to_le_bytes());
+    message.extend_from_slice(proposal.as_bytes());
+    message
+}
+
+/// The proof of a storage key of an account, as returned by getStateProof
+pub struct StateProof {
+    /// Address of the account
+    pub address: Address,
+    /// Nodes of the state trie, from the root to the account
+    pub account_proof: Vec<Vec<u8>>,
+    /// The storage key
+    pub key: H256,
+    /// Nodes of the storage trie, from the root to the value
+    pub value_proof: Vec<Vec<u8>>,
+}
+
+impl StateProof {
+    /// Decode the RLP list of `[address, [node], key, [node]]`, None when it's malformed
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        let items = rlp_list(data)?;
+        if items.len() != 4 || items[0].payload.len() != 20 || items[2].payload.len() != 32 {
+            return None;
+        }
+        let nodes = |item: &RlpItem| -> Option<Vec<Vec<u8>>> {
+            if !item.is_list {
+                return None;
+            }
+            rlp_list(item.raw).map(|nodes| nodes.iter().map(|node| node.payload.to_vec()).collect())
+        };
+        Some(StateProof {
+            address: Address::from(items[0].payload),
+            account_proof: nodes(&items[1])?,
+            key: H256::from(items[2].payload),
+            value_proof: nodes(&items[3])?,
+        })
+    }
+}
+
+/// The fields of an account used by the verified queries, the state trie stores
+/// `[nonce, balance, storage root, ...]`
+struct Account {
+    balance: U256,
+    storage_root: H256,
+}
+
+/// Walk down the Merkle Patricia trie along the key with the proof nodes,
+/// return the value, or None when the proof shows the key is absent
+///
+/// An empty proof only proves the absence in the empty trie, the root of which is the hash of
+/// empty RLP.
+pub fn verify_trie_proof(
+    root: &H256,
+    key: &H256,
+    proof: &[Vec<u8>],
+    encryption: Encryption,
+) -> Result<Option<Vec<u8>>, &'static str> {
+    let nibbles: Vec<u8> = key
+        .iter()
+        .flat_map(|byte| vec![byte >> 4, byte & 0x0f])
+        .collect();
+    let mut path = &nibbles[..];
+    let mut proof = proof.iter();
+    let mut node: Vec<u8> = match proof.next() {
+        Some(node) if node.crypt_hash(encryption) == *root => node.clone(),
+        Some(_) => return Err("root mismatch"),
+        None if *root == [0x80u8].crypt_hash(encryption) => return Ok(None),
+        None => return Err("empty proof of a non-empty trie"),
+    };
+
+    loop {
+        let items = rlp_list(&node).ok_or("bad node encoding")?;
+        let next = match items.len() {
+            17 => {
+                if path.is_empty() {
+                    return Ok(Some(items[16].payload.to_vec()).filter(|value| !value.is_empty()));
+                }
+                let next = &items[usize::from(path[0])];
+                path = &path[1..];
+                next
+            }
+            2 => {
+                let (is_leaf, partial) = decode_path(items[0].payload).ok_or("bad node path")?;
+                if is_leaf {
+                    return Ok(Some(items[1].payload.to_vec()).filter(|_| partial[..] == path[..]));
+                }
+                if !path.starts_with(&partial) {
+                    return Ok(None);
+                }
+                path = &path[partial.len()..];
+                &items[1]
+            }
+            _ => return Err("bad node encoding"),
+        };
+
+        // Nodes shorter than a hash are embedded in their parents
+        node = if next.is_list {
+            next.raw.to_vec()
+        } else if next.payload.is_empty() {
+            return Ok(None);
+        } else if next.payload.len() == 32 {
+            match proof.next() {
+                Some(node) if node.crypt_hash(encryption)[..] == *next.payload => node.clone(),
+                Some(_) => return Err("node hash mismatch"),
+                None => return Err("incomplete proof"),
+            }
+        } else {
+            return Err("bad node reference");
+        };
+    }
+}
+
+/// Decode the hex-prefix encoded path of a leaf or extension node, return (is leaf, nibbles)
+fn decode_path(encoded: &[u8]) -> Option<(bool, Vec<u8>)> {
+    let flag = encoded.first()? >> 4;
+    if flag > 3 {
+        return None;
+    }
+    let mut nibbles = Vec::new();
+    if flag & 1 == 1 {
+        nibbles.push(encoded[0] & 0x0f);
+    }
+    for byte in &encoded[1..] {
+        nibbles.push(byte >> 4);
+        nibbles.push(byte & 0x0f);
+    }
+    Some((flag & 2 == 2, nibbles))
+}
+
+/// The payloads of the items of an RLP list, None when it isn't a valid list
+pub(crate) fn rlp_items(data: &[u8]) -> Option<Vec<&[u8]>> {
+    rlp_list(data).map(|items| items.iter().map(|item| item.payload).collect())
+}
+
+/// An item of an RLP list
+struct RlpItem<'a> {
+    is_list: bool,
+    payload: &'a [u8],
+    /// The encoding of the item, with the prefix
+    raw: &'a [u8],
+}
+
+/// The items of an RLP list, None when it isn't a valid list
+fn rlp_list(data: &[u8]) -> Option<Vec<RlpItem<'_>>> {
+    let (is_list, payload, rest) = rlp_split(data)?;
+    if !is_list || !rest.is_empty() {
+        return None;
+    }
+    let mut items = Vec::new();
+    let mut remaining = payload;
+    while !remaining.is_empty() {
+        let (is_list, payload, rest) = rlp_split(remaining)?;
+        items.push(RlpItem {
+            is_list,
+            payload,
+            raw: &remaining[..remaining.len() - rest.len()],
+        });
+        remaining = rest;
+    }
+    Some(items)
+}
+
+/// Split the first RLP item off, return (is list, payload, the rest)
+fn rlp_split(data: &[u8]) -> Option<(bool, &[u8], &[u8])> {
+    let prefix = *data.first()?;
+    let (is_list, offset, len) = match prefix {
+        0x00..=0x7f => return Some((false, &data[..1], &data[1..])),
+        0x80..=0xb7 => (false, 1, usize::from(prefix - 0x80)),
+        0xc0..=0xf7 => (true, 1, usize::from(prefix - 0xc0)),
+        _ => {
+            let (is_list, len_of_len) = if prefix < 0xc0 {
+                (false, usize::from(prefix - 0xb7))
+            } else {
+                (true, usize::from(prefix - 0xf7))
+            };
+            let len_bytes = data.get(1..=len_of_len)?;
+            if len_of_len > 8 {
+                return None;
+            }
+            let len = len_bytes
+                .iter()
+                .fold(0usize, |len, byte| (len << 8) | usize::from(*byte));
+            (is_list, 1 + len_of_len, len)
+        }
+    };
+    let end = offset.checked_add(len)?;
+    Some((is_list, data.get(offset..end)?, &data[end..]))
+}
+
+pub(crate) fn parse_h256(value: &Value) -> Option<H256> {
+    value
+        .as_str()
+        .and_then(|hash| H256::from_str(remove_0x(hash)).ok())
+}
+
+pub(crate) fn result_value(response: JsonRpcResponse) -> Result<Value, ToolError> {
+    if let Some(err) = response.error() {
+        return Err(ToolError::Customize(err.message()));
+    }
+    serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        precommit_message, rlp_items, verify_trie_proof, HeaderFields, LightClient, VerifiedHeader,
+    };
+    use crate::client::basic::Client;
+    use crate::client::ChainVersion;
+    use crate::crypto::{sign, Encryption, Hashable, KeyPair};
+    use crate::LowerHex;
+    use serde_json::{json, Map, Value};
+    use types::H256;
+
+    #[test]
+    fn test_rlp_items() {
+        // ["cat", "dog", [], 0x0f, 56 bytes]
+        let mut data = vec![0xf8, 0x44, 0x83, b'c', b'a', b't', 0x83, b'd', b'o', b'g'];
+        data.extend_from_slice(&[0xc0, 0x0f, 0xb8, 0x38]);
+        data.extend_from_slice(&[0xaa; 0x38]);
+        let items = rlp_items(&data).unwrap();
+        assert_eq!(items.len(), 5);
+        assert_eq!(items[0], b"cat");
+        assert_eq!(items[1], b"dog");
+        assert!(items[2].is_empty());
+        assert_eq!(items[3], &[0x0f]);
+        assert_eq!(items[4], &[0xaa; 0x38][..]);
+
+        assert!(rlp_items(&[0x83, b'c', b'a', b't']).is_none());
+        assert!(rlp_items(&[0xc4, 0x83, b'c']).is_none());
+    }
+
+    #[test]
+    fn test_header_fields() {
+        // [prev hash, timestamp, number 5, transactions root, state root, receipts root]
+        let hash = |byte: u8| {
+            let mut item = vec![0xa0];
+            item.extend_from_slice(&[byte; 32]);
+            item
+        };
+        let mut payload = hash(1);
+        payload.extend_from_slice(&[0x86, 0x01, 0x74, 0x87, 0x6e, 0x80, 0x00, 0x05]);
+        for byte in 2..=4 {
+            payload.extend_from_slice(&hash(byte));
+        }
+        let mut encoded = vec![0xf8, payload.len() as u8];
+        encoded.extend_from_slice(&payload);
+
+        let fields = HeaderFields::decode(&encoded).unwrap();
+        assert_eq!(fields.prev_hash, H256::from([1; 32]));
+        assert_eq!(fields.timestamp, 1_600_000_000_000);
+        assert_eq!(fields.number, 5);
+        assert_eq!(fields.root("transactionsRoot"), Some(H256::from([2; 32])));
+        assert_eq!(fields.root("stateRoot"), Some(H256::from([3; 32])));
+        assert_eq!(fields.root("receiptsRoot"), Some(H256::from([4; 32])));
+
+        // A hash with a byte less isn't one
+        encoded[1] -= 1;
+        encoded[2] -= 1;
+        encoded.remove(3);
+        assert!(HeaderFields::decode(&encoded).is_none());
+    }
+
+    #[test]
+    fn test_verify_trie_proof() {
+        // A trie of a single leaf: [hex-prefix of the whole key, "abc"]
+        let key = H256::from([0x12; 32]);
+        let mut leaf = vec![0xe6, 0xa1, 0x20];
+        leaf.extend_from_slice(&key);
+        leaf.extend_from_slice(&[0x83, b'a', b'b', b'c']);
+        let root = leaf.crypt_hash(Encryption::Secp256k1);
+        let proof = vec![leaf];
+
+        assert_eq!(
+            verify_trie_proof(&root, &key, &proof, Encryption::Secp256k1),
+            Ok(Some(b"abc".to_vec()))
+        );
+        assert_eq!(
+            verify_trie_proof(
+                &root,
+                &H256::from([0x13; 32]),
+                &proof,
+                Encryption::Secp256k1
+            ),
+            Ok(None)
+        );
+        assert!(verify_trie_proof(&H256::zero(), &key, &proof, Encryption::Secp256k1).is_err());
+    }
+
+    #[test]
+    fn test_verify_empty_proof() {
+        let key = H256::from([0x12; 32]);
+        let empty_root = [0x80u8].crypt_hash(Encryption::Secp256k1);
+        assert_eq!(
+            verify_trie_proof(&empty_root, &key, &[], Encryption::Secp256k1),
+            Ok(None)
+        );
+        // Any node could prove the absence of every account by no proof at all
+        let root = H256::from([0x34; 32]);
+        assert!(verify_trie_proof(&root, &key, &[], Encryption::Secp256k1).is_err());
+    }
+
+    #[test]
+    fn test_check_proof() {
+        let encryption = Encryption::Secp256k1;
+        let keys = (0..4)
+            .map(|_| KeyPair::new(encryption))
+            .collect::<Vec<KeyPair>>();
+        let header = VerifiedHeader {
+            height: 5,
+            hash: H256::from([0x56; 32]),
+            prev_hash: H256::zero(),
+            timestamp: 0,
+            state_root: H256::zero(),
+            transactions_root: H256::zero(),
+            receipts_root: H256::zero(),
+        };
+        let light = LightClient {
+            client: Client::new(),
+            encryption,
+            version: ChainVersion {
+                release: None,
+                protocol: 2,
+            },
+            validators: keys.iter().map(KeyPair::address).collect(),
+            head: header.clone(),
+        };
+        let commit = |key: &KeyPair| {
+            let message = precommit_message(5, 0, &key.address(), &header.hash);
+            let signature = sign(&key.privkey(), &message.crypt_hash(encryption));
+            Value::String(format!("0x{}", hex::encode(signature.to_vec())))
+        };
+        let proof = |commits: Map<String, Value>| {
+            json!({
+                "Bft": {
+                    "proposal": header.hash.completed_lower_hex_with_0x(),
+                    "height": "0x5",
+                    "commits": commits,
+                }
+            })
+        };
+
+        let mut commits = Map::new();
+        for key in &keys[..3] {
+            commits.insert(key.address().completed_lower_hex_with_0x(), commit(key));
+        }
+        assert!(light.check_proof(&proof(commits), &header).is_ok());
+
+        // Two spellings of one validator are one commit, 2 of 4 validators aren't a quorum
+        let mut commits = Map::new();
+        for key in &keys[..2] {
+            commits.insert(key.address().completed_lower_hex_with_0x(), commit(key));
+        }
+        let upper = format!(
+            "0x{}",
+            keys[0].address().completed_lower_hex().to_uppercase()
+        );
+        commits.insert(upper, commit(&keys[0]));
+        let err = light.check_proof(&proof(commits), &header).unwrap_err();
+        assert!(err.to_string().contains("duplicate commit"));
+    }
+}
@@ -0,0 +1,166 @@
+use crate::client::basic::Client;
+use crate::client::fee::result_u256;
+use crate::client::system_contract::{QuotaManageClient, QuotaManagementExt};
+use crate::client::Notice;
+use crate::error::ToolError;
+use crate::protos::{Transaction, UnverifiedTransaction};
+use crate::rpctypes::{ParamsValue, Quantity};
+
+/// The quota every transaction costs
+const TX_QUOTA: u64 = 21_000;
+/// The quota every contract creation costs
+const CREATE_QUOTA: u64 = 53_000;
+/// The quota of a zero byte of the data
+const ZERO_BYTE_QUOTA: u64 = 4;
+/// The quota of a non-zero byte of the data
+const NON_ZERO_BYTE_QUOTA: u64 = 68;
+
+/// The limits of the chain a transaction has to fit in, None when the node doesn't tell
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainLimits {
+    /// The max size of the data of a transaction, in bytes
+    pub max_data_size: Option<u64>,
+    /// The quota of a block, a transaction can't use more
+    pub block_quota_limit: Option<u64>,
+}
+
+impl ChainLimits {
+    /// Check the transaction against the limits, an error is what the node would reject
+    pub fn check(&self, tx: &Transaction) -> Result<(), ToolError> {
+        let size = tx.get_data().len() as u64;
+        if let Some(max_data_size) = self.max_data_size.filter(|max| size > *max) {
+            return Err(ToolError::Customize(format!(
+                "The data of the transaction has {} bytes, over the max data size {} of the chain",
+                size, max_data_size
+            )));
+        }
+        if let Some(limit) = self
+            .block_quota_limit
+            .filter(|limit| tx.get_quota() > *limit)
+        {
+            return Err(ToolError::Customize(format!(
+                "The quota {} of the transaction is over the block quota limit {} of the chain, \
+                 no block can pack it",
+                tx.get_quota(),
+                limit
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// The quota the transaction costs before any execution, by its kind and its data
+pub fn intrinsic_quota(tx: &Transaction) -> u64 {
+    let base = if tx.is_contract_creation() {
+        CREATE_QUOTA
+    } else {
+        TX_QUOTA
+    };
+    tx.get_data().iter().fold(base, |quota, byte| {
+        quota
+            + if *byte == 0 {
+                ZERO_BYTE_QUOTA
+            } else {
+                NON_ZERO_BYTE_QUOTA
+            }
+    })
+}
+
+impl Client {
+    /// Get the limits of the chain at the latest height
+    ///
+    /// `maxDataSize` and `blockQuotaLimit` are read from the metadata when the node has them,
+    /// the block quota limit is otherwise read from the quota manager.
+    pub fn get_chain_limits(&self) -> Result<ChainLimits, ToolError> {
+        let metadata = self.get_latest_metadata()?;
+        let limit = |key: &str| match metadata.get(key) {
+            Some(ParamsValue::Null) | None => Ok(None),
+            Some(value) => Quantity::from_params(value)?.as_u64().map(Some),
+        };
+        let max_data_size = limit("maxDataSize")?;
+        let block_quota_limit = match limit("blockQuotaLimit")? {
+            Some(limit) => Some(limit),
+            None => {
+                let quota_manager: QuotaManageClient<Client> =
+                    QuotaManagementExt::create(self.clone());
+                quota_manager
+                    .get_bql(None)
+                    .and_then(result_u256)
+                    .ok()
+                    .filter(|limit| !limit.is_zero())
+                    .map(|limit| limit.low_u64())
+            }
+        };
+        Ok(ChainLimits {
+            max_data_size,
+            block_quota_limit,
+        })
+    }
+
+    /// Before sending, check the transaction against the limits of the chain,
+    /// a quota below the intrinsic quota is notified and the transaction is still sent
+    pub(crate) fn check_limits(
+        &self,
+        unverified_tx: &UnverifiedTransaction,
+    ) -> Result<(), ToolError> {
+        if !self.limit_check() {
+            return Ok(());
+        }
+        let tx = unverified_tx.get_transaction();
+        let intrinsic = intrinsic_quota(tx);
+        if tx.get_quota() < intrinsic {
+            self.notify(Notice::QuotaTooLow {
+                quota: tx.get_quota(),
+                intrinsic,
+            });
+        }
+        self.get_chain_limits()?.check(tx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{intrinsic_quota, ChainLimits};
+    use crate::protos::{TransactionBuilder, TransactionVersion};
+
+    #[test]
+    fn test_chain_limits() {
+        let create = TransactionBuilder::new(TransactionVersion::V2)
+            .set_data(vec![0, 1, 2])
+            .set_quota(100_000)
+            .build()
+            .unwrap();
+        assert_eq!(intrinsic_quota(&create), 53_000 + 4 + 68 * 2);
+        let call = TransactionBuilder::new(TransactionVersion::V2)
+            .set_to(Some(
+                "ffffffffffffffffffffffffffffffffff020004".parse().unwrap(),
+            ))
+            .build()
+            .unwrap();
+        assert_eq!(intrinsic_quota(&call), 21_000);
+
+        assert!(ChainLimits::default().check(&create).is_ok());
+        let limits = ChainLimits {
+            max_data_size: Some(3),
+            block_quota_limit: Some(100_000),
+        };
+        assert!(limits.check(&create).is_ok());
+        assert!(ChainLimits {
+            max_data_size: Some(2),
+            ..limits
+        }
+        .check(&create)
+        .unwrap_err()
+        .to_string()
+        .contains("max data size 2"));
+        assert!(ChainLimits {
+            block_quota_limit: Some(99_999),
+            ..limits
+        }
+        .check(&create)
+        .unwrap_err()
+        .to_string()
+        .contains("block quota limit 99999"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs;
+
+use serde_json::{self, Value};
+
+use crate::abi::{decode_input, encode_input};
+use crate::client::basic::{call_params, Client};
+use crate::error::ToolError;
+
+/// A read call of multicall
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadCall {
+    /// Contract address
+    pub address: String,
+    /// Function name
+    pub function: String,
+    /// Function arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Path of the contract ABI file, the shared ABI is used when absent
+    #[serde(default)]
+    pub abi: Option<String>,
+}
+
+/// The decoded result of a read call
+#[derive(Debug, Clone, Serialize)]
+pub struct ReadCallResult {
+    /// Contract address
+    pub address: String,
+    /// Function name
+    pub function: String,
+    /// Function arguments
+    pub args: Vec<String>,
+    /// Decoded outputs, empty when the call failed
+    pub output: Vec<Value>,
+    /// Why the call failed
+    pub error: Option<String>,
+}
+
+impl ReadCallResult {
+    fn new(call: &ReadCall) -> Self {
+        ReadCallResult {
+            address: call.address.clone(),
+            function: call.function.clone(),
+            args: call.args.clone(),
+            output: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl Client {
+    /// Execute many read calls at once and decode all the results
+    ///
+    /// `abi` is the content of the ABI used by the calls without their own ABI file.
+    /// A failing call doesn't stop the others, its error is kept in the result.
+    pub fn multicall(
+        &self,
+        calls: &[ReadCall],
+        abi: Option<&str>,
+        height: &str,
+    ) -> Result<Vec<ReadCallResult>, ToolError> {
+        let mut results: Vec<ReadCallResult> = calls.iter().map(ReadCallResult::new).collect();
+        let mut prepared = Vec::new();
+
+        for (index, call) in calls.iter().enumerate() {
+            let call_abi = match call.abi {
+                Some(ref path) => fs::read_to_string(path).map_err(|e| e.to_string()),
+                None => abi
+                    .map(ToOwned::to_owned)
+                    .ok_or_else(|| "No input abi".to_string()),
+            };
+            let encoded = call_abi.and_then(|call_abi| {
+                encode_input(
+                    None,
+                    Some(&call_abi),
+                    &call.function,
+                    &call.args,
+                    true,
+                    false,
+                )
+                .map(|data| (call_abi, data))
+                .map_err(|e| e.to_string())
+            });
+            match encoded {
+                Ok((call_abi, data)) => prepared.push((index, call_abi, data)),
+                Err(err) => results[index].error = Some(err),
+            }
+        }
+
+        let params = prepared.iter().map(|(index, _, data)| {
+            call_params(
+                None,
+                &calls[*index].address,
+                Some(&format!("0x{}", data)),
+                height,
+            )
+        });
+        let responses = self.send_request(params.collect::<Vec<_>>().into_iter())?;
+
+        for ((index, call_abi, _), response) in prepared.iter().zip(responses) {
+            let result = &mut results[*index];
+            if let Some(err) = response.error() {
+                result.error = Some(err.revert_reason().unwrap_or_else(|| err.message()));
+                continue;
+            }
+            let data = match serde_json::to_value(response.result()) {
+                Ok(Value::String(data)) => data,
+                _ => {
+                    result.error = Some("Unexpected call result".to_string());
+                    continue;
+                }
+            };
+            match decode_input(None, Some(call_abi), &result.function, &data) {
+                Ok(output) => {
+                    result.output = output
+                        .iter()
+                        .filter_map(|value| serde_json::from_str(value).ok())
+                        .collect()
+                }
+                Err(err) => result.error = Some(err.to_string()),
+            }
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,133 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyper::Uri;
+
+/// Route the reads to the node of the lowest latency, or to the pinned node
+///
+/// The latencies are probed by `blockNumber`, again after the probe interval. The
+/// nodes which failed the last probe are skipped. The clones share the probes.
+#[derive(Debug, Clone)]
+pub struct NodeSelector {
+    nodes: Vec<Uri>,
+    interval: Duration,
+    pinned: Option<Uri>,
+    probes: Arc<Mutex<Probes>>,
+}
+
+#[derive(Debug, Default)]
+struct Probes {
+    latencies: Vec<Option<Duration>>,
+    probed: Option<Instant>,
+}
+
+impl NodeSelector {
+    /// Select among the nodes, probing them every interval
+    pub fn new(nodes: Vec<Uri>, interval: Duration) -> Self {
+        NodeSelector {
+            nodes,
+            interval,
+            pinned: None,
+            probes: Arc::new(Mutex::new(Probes::default())),
+        }
+    }
+
+    /// Send the reads to the node, without probing
+    pub fn pin(mut self, node: Option<Uri>) -> Self {
+        self.pinned = node;
+        self
+    }
+
+    /// The nodes to select among
+    pub fn nodes(&self) -> &[Uri] {
+        &self.nodes
+    }
+
+    /// The pinned node
+    pub fn pinned(&self) -> Option<&Uri> {
+        self.pinned.as_ref()
+    }
+
+    /// Whether the latencies should be probed before selecting
+    pub fn needs_probe(&self) -> bool {
+        self.needs_probe_at(Instant::now())
+    }
+
+    /// Keep the latencies of the nodes, in the order of the nodes, None if failed
+    pub fn update(&self, latencies: Vec<Option<Duration>>) {
+        let mut probes = self.probes.lock().unwrap();
+        probes.latencies = latencies;
+        probes.probed = Some(Instant::now());
+    }
+
+    /// The latencies of the last probe
+    pub fn latencies(&self) -> Vec<(Uri, Option<Duration>)> {
+        let probes = self.probes.lock().unwrap();
+        self.nodes
+            .iter()
+            .cloned()
+            .zip(
+                probes
+                    .latencies
+                    .iter()
+                    .cloned()
+                    .chain(std::iter::repeat(None)),
+            )
+            .collect()
+    }
+
+    /// The pinned node, or the fastest one, None if no node answered the last probe
+    pub fn select(&self) -> Option<Uri> {
+        if self.pinned.is_some() {
+            return self.pinned.clone();
+        }
+        self.nodes
+            .iter()
+            .zip(self.probes.lock().unwrap().latencies.iter())
+            .filter_map(|(node, latency)| latency.map(|latency| (latency, node)))
+            .min_by_key(|(latency, _)| *latency)
+            .map(|(_, node)| node.clone())
+    }
+
+    fn needs_probe_at(&self, now: Instant) -> bool {
+        if self.pinned.is_some() || self.nodes.is_empty() {
+            return false;
+        }
+        match self.probes.lock().unwrap().probed {
+            Some(probed) => now.saturating_duration_since(probed) >= self.interval,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::NodeSelector;
+
+    #[test]
+    fn test_select() {
+        let nodes = vec![
+            "http://127.0.0.1:1337".parse().unwrap(),
+            "http://127.0.0.1:1338".parse().unwrap(),
+            "http://127.0.0.1:1339".parse().unwrap(),
+        ];
+        let selector = NodeSelector::new(nodes.clone(), Duration::from_secs(60));
+        assert!(selector.needs_probe());
+        assert_eq!(selector.select(), None);
+
+        selector.update(vec![
+            Some(Duration::from_millis(30)),
+            None,
+            Some(Duration::from_millis(10)),
+        ]);
+        assert!(!selector.needs_probe());
+        assert!(selector.needs_probe_at(Instant::now() + Duration::from_secs(60)));
+        assert_eq!(selector.select(), Some(nodes[2].clone()));
+
+        let pinned = selector.pin(Some(nodes[1].clone()));
+        assert!(!pinned.needs_probe_at(Instant::now() + Duration::from_secs(60)));
+        assert_eq!(pinned.select(), Some(nodes[1].clone()));
+    }
+}
@@ -0,0 +1,140 @@
+use std::fmt;
+use std::sync::Arc;
+
+use types::U256;
+
+use crate::client::{ChainInfo, CircuitState, FeeEstimate, PendingTransaction};
+use crate::error::ToolError;
+use crate::rpctypes::JsonRpcParams;
+
+/// What the client reports while working, the library never prints by itself
+#[derive(Debug)]
+pub enum Notice<'a> {
+    /// A request about to be sent, only in debug mode
+    Request(&'a JsonRpcParams),
+    /// The max fee of a transaction about to be sent
+    MaxFee(&'a FeeEstimate),
+    /// The fee of the transactions about to be sent can't be checked, they're still sent
+    FeeUnchecked(&'a ToolError),
+    /// A transaction with the same content was already sent, under `DuplicatePolicy::Warn`
+    Duplicate(&'a PendingTransaction),
+    /// A transaction has the content of one before it in the batch about to be sent, under
+    /// `DuplicatePolicy::Warn`, by the content hash
+    DuplicateInBatch(&'a str),
+    /// The chain id signed in a transaction about to be sent isn't the one of the node
+    ChainMismatch {
+        /// The chain id in the transaction
+        signed: U256,
+        /// The chain id of the node for the version of the transaction
+        expected: U256,
+        /// The chain of the node
+        chain: &'a ChainInfo,
+    },
+    /// The quota of a transaction about to be sent is below what it costs before executing
+    QuotaTooLow {
+        /// The quota of the transaction
+        quota: u64,
+        /// The intrinsic quota of the transaction
+        intrinsic: u64,
+    },
+    /// The circuit breaker changed the state of the circuit of a node
+    Circuit {
+        /// Url of the node
+        url: &'a str,
+        /// State before
+        from: CircuitState,
+        /// State after
+        to: CircuitState,
+    },
+    /// The node only accepts an older name of a method, it's sent by the name from now on
+    Alias {
+        /// Url of the node
+        url: &'a str,
+        /// The current name of the method
+        method: &'a str,
+        /// The name the node accepts
+        alias: &'a str,
+    },
+    /// A signed transaction was sent, accepted by the node or not, for the audit of the
+    /// state changes
+    Sent {
+        /// Url of the node
+        url: &'a str,
+        /// The chain id signed in the transaction
+        chain_id: U256,
+        /// The transaction, its hash is empty if the node didn't accept it
+        transaction: &'a PendingTransaction,
+        /// Why the node didn't accept it, None if it did
+        error: Option<&'a str>,
+    },
+}
+
+impl<'a> fmt::Display for Notice<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Notice::Request(params) => write!(f, "<--{}", params),
+            Notice::MaxFee(estimate) => write!(
+                f,
+                "Max fee: {} (quota {} * quota price {})",
+                estimate.max_fee, estimate.quota, estimate.quota_price
+            ),
+            Notice::FeeUnchecked(err) => {
+                write!(f, "Warning: the fee isn't checked: {}", err)
+            }
+            Notice::Duplicate(sent) => write!(
+                f,
+                "Warning: a transaction with the same content was already sent: {}",
+                sent.hash
+            ),
+            Notice::DuplicateInBatch(content_hash) => write!(
+                f,
+                "Warning: the batch carries the same content twice: {}",
+                content_hash
+            ),
+            Notice::ChainMismatch {
+                signed,
+                expected,
+                chain,
+            } => write!(
+                f,
+                "Warning: the transaction is signed for the chain id {:#x}, \
+                 but the node is on {} of the chain id {:#x}, it may be replayed on another chain",
+                signed,
+                chain_label(&chain.chain_name),
+                expected
+            ),
+            Notice::QuotaTooLow { quota, intrinsic } => write!(
+                f,
+                "Warning: the quota {} is below the intrinsic quota {} of the transaction, \
+                 it will run out of quota",
+                quota, intrinsic
+            ),
+            Notice::Circuit { url, from, to } => {
+                write!(f, "Circuit of {}: {} -> {}", url, from, to)
+            }
+            Notice::Alias { url, method, alias } => {
+                write!(f, "{} only accepts `{}` as `{}`", url, method, alias)
+            }
+            Notice::Sent {
+                url,
+                transaction,
+                error,
+                ..
+            } => match error {
+                Some(error) => write!(f, "Failed to send to {}: {}", url, error),
+                None => write!(f, "Sent {} to {}", transaction.hash, url),
+            },
+        }
+    }
+}
+
+fn chain_label(name: &str) -> String {
+    if name.is_empty() {
+        "the chain".to_owned()
+    } else {
+        format!("`{}`", name)
+    }
+}
+
+/// Receive the notices of a client and all its clones
+pub type Notifier = Arc<dyn Fn(&Notice) + Send + Sync>;
@@ -0,0 +1,68 @@
+use std::sync::{Arc, Mutex};
+
+/// A transaction sent by the client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    /// Transaction hash returned by the node
+    pub hash: String,
+    /// Hash of the transaction content, see `Transaction::content_hash`
+    pub content_hash: String,
+    /// Sender address recovered from the signature, empty when it can't be recovered
+    pub sender: String,
+    /// Target address, empty when creating a contract
+    pub to: String,
+    /// The transaction is invalid after this height
+    pub valid_until_block: u64,
+}
+
+/// What to do when a transaction with the same content was already sent in this session
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum DuplicatePolicy {
+    /// Send it anyway
+    Allow,
+    /// Send it, but report `Notice::Duplicate`
+    #[default]
+    Warn,
+    /// Refuse to send it
+    Reject,
+}
+
+/// Transactions sent in this session, shared by all the clones of a client
+#[derive(Debug, Clone, Default)]
+pub struct PendingStore {
+    transactions: Arc<Mutex<Vec<PendingTransaction>>>,
+}
+
+impl PendingStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Record a sent transaction
+    pub fn insert(&self, transaction: PendingTransaction) {
+        self.transactions.lock().unwrap().push(transaction);
+    }
+
+    /// Find a sent transaction with the same content
+    pub fn find_by_content(&self, content_hash: &str) -> Option<PendingTransaction> {
+        self.transactions
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|tx| tx.content_hash == content_hash)
+            .cloned()
+    }
+
+    /// Forget a transaction, return it if it was recorded
+    pub fn remove(&self, hash: &str) -> Option<PendingTransaction> {
+        let mut transactions = self.transactions.lock().unwrap();
+        let index = transactions.iter().position(|tx| tx.hash == hash)?;
+        Some(transactions.remove(index))
+    }
+
+    /// All the recorded transactions, in sending order
+    pub fn list(&self) -> Vec<PendingTransaction> {
+        self.transactions.lock().unwrap().clone()
+    }
+}
@@ -0,0 +1,98 @@
+use serde_json::{json, Value};
+use types::Address;
+
+use crate::protos::Transaction;
+use crate::LowerHex;
+
+/// A signed transaction about to be sent, what the policies decide on
+#[derive(Debug, Clone, Copy)]
+pub struct OutgoingTransaction<'a> {
+    /// Url of the node
+    pub url: &'a str,
+    /// Sender recovered from the signature, None when it can't be recovered
+    pub sender: Option<Address>,
+    /// The transaction
+    pub transaction: &'a Transaction,
+}
+
+impl<'a> OutgoingTransaction<'a> {
+    /// The decoded transaction as JSON, `Transaction::to_value` with the url, the sender
+    /// and the function selector, the first 4 bytes of the data
+    pub fn to_value(&self) -> Value {
+        let mut value = self.transaction.to_value();
+        let data = self.transaction.get_data();
+        value["url"] = json!(self.url);
+        value["sender"] = json!(self
+            .sender
+            .map(|sender| sender.completed_lower_hex_with_0x()));
+        value["selector"] = json!(if data.len() >= 4 {
+            Some(format!("0x{}", hex::encode(&data[..4])))
+        } else {
+            None
+        });
+        value
+    }
+}
+
+/// A rule of the organization a transaction has to pass before it's sent, such as the allowed
+/// contracts, the max value or the business hours
+pub trait SendPolicy: Send + Sync {
+    /// Ok lets the transaction be sent, Err vetoes it with the reason
+    fn check(&self, transaction: &OutgoingTransaction) -> Result<(), String>;
+}
+
+impl<F> SendPolicy for F
+where
+    F: Fn(&OutgoingTransaction) -> Result<(), String> + Send + Sync,
+{
+    fn check(&self, transaction: &OutgoingTransaction) -> Result<(), String> {
+        self(transaction)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{OutgoingTransaction, SendPolicy};
+    use crate::protos::{TransactionBuilder, TransactionVersion};
+    use types::U256;
+
+    #[test]
+    fn test_send_policy() {
+        let max_value = |outgoing: &OutgoingTransaction| {
+            if outgoing.transaction.value_u256() > U256::from(100) {
+                Err("The value is over 100".to_owned())
+            } else {
+                Ok(())
+            }
+        };
+        let tx = |value: u64| {
+            TransactionBuilder::new(TransactionVersion::V2)
+                .set_to(Some(
+                    "ffffffffffffffffffffffffffffffffff020004".parse().unwrap(),
+                ))
+                .set_data(vec![0xa9, 0x05, 0x9c, 0xbb, 0x00])
+                .set_value(U256::from(value))
+                .build()
+                .unwrap()
+        };
+        let small = tx(1);
+        let outgoing = OutgoingTransaction {
+            url: "http://127.0.0.1:1337/",
+            sender: None,
+            transaction: &small,
+        };
+        assert!(max_value.check(&outgoing).is_ok());
+        assert_eq!(outgoing.to_value()["selector"], "0xa9059cbb");
+        assert_eq!(outgoing.to_value()["url"], "http://127.0.0.1:1337/");
+
+        let large = tx(101);
+        let outgoing = OutgoingTransaction {
+            transaction: &large,
+            ..outgoing
+        };
+        assert_eq!(
+            max_value.check(&outgoing),
+            Err("The value is over 100".to_owned())
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use std::collections::BTreeMap;
+use std::fs;
+
+use serde_json::{self, Value};
+
+use crate::abi::encode_input;
+use crate::client::basic::{Client, ClientExt};
+use crate::rpctypes::Quantity;
+
+/// A call of the quota report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaCall {
+    /// The name of the call in the report and the baseline, the function by default
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Contract address
+    pub address: String,
+    /// Function name
+    pub function: String,
+    /// Function arguments
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Path of the contract ABI file, the shared ABI is used when absent
+    #[serde(default)]
+    pub abi: Option<String>,
+    /// The sender of the call
+    #[serde(default)]
+    pub sender: Option<String>,
+}
+
+impl QuotaCall {
+    /// The name of the call in the report and the baseline
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.function)
+    }
+}
+
+/// How the quota of a call compares to the baseline
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuotaStatus {
+    /// Within the tolerance of the baseline
+    Ok,
+    /// Costs more than the tolerance allows
+    Regressed,
+    /// Costs less than the tolerance allows, the baseline may be updated
+    Improved,
+    /// Not in the baseline
+    New,
+    /// The estimation failed
+    Failed,
+}
+
+/// The quota used by a call, compared to the baseline
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaUsage {
+    /// The name of the call
+    pub name: String,
+    /// The estimated quota, None when the estimation failed
+    pub quota_used: Option<u64>,
+    /// The quota of the baseline
+    pub baseline: Option<u64>,
+    /// The change to the baseline, in percent
+    pub change: Option<f64>,
+    /// How the quota compares to the baseline
+    pub status: QuotaStatus,
+    /// Why the estimation failed
+    pub error: Option<String>,
+}
+
+impl Client {
+    /// Estimate the quota of every call at the latest state, without sending transactions
+    ///
+    /// `abi` is the content of the ABI used by the calls without their own ABI file.
+    /// A failing call doesn't stop the others, its error is kept in the result.
+    pub fn quota_used(
+        &self,
+        calls: &[QuotaCall],
+        abi: Option<&str>,
+    ) -> Vec<(String, Result<u64, String>)> {
+        calls
+            .iter()
+            .map(|call| (call.name().to_owned(), self.estimate_call(call, abi)))
+            .collect()
+    }
+
+    fn estimate_call(&self, call: &QuotaCall, abi: Option<&str>) -> Result<u64, String> {
+        let call_abi = match call.abi {
+            Some(ref path) => fs::read_to_string(path).map_err(|e| e.to_string())?,
+            None => abi
+                .map(ToOwned::to_owned)
+                .ok_or_else(|| "No input abi".to_string())?,
+        };
+        let data = encode_input(
+            None,
+            Some(&call_abi),
+            &call.function,
+            &call.args,
+            true,
+            false,
+        )
+        .map_err(|e| e.to_string())?;
+        let response = self
+            .estimate_quota(
+                call.sender.as_deref(),
+                &call.address,
+                Some(&format!("0x{}", data)),
+                "latest",
+            )
+            .map_err(|e| e.to_string())?;
+        if let Some(err) = response.error() {
+            return Err(err.revert_reason().unwrap_or_else(|| err.message()));
+        }
+        let quota = serde_json::to_value(response.result()).map_err(|e| e.to_string())?;
+        Quantity::from_json(&quota)
+            .ok_or_else(|| format!("Unexpected quota {}", quota))?
+            .as_u64()
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Compare the quota of the calls to the baseline, a change over `tolerance` percent is a
+/// regression or an improvement
+pub fn compare_quota(
+    used: &[(String, Result<u64, String>)],
+    baseline: &BTreeMap<String, u64>,
+    tolerance: f64,
+) -> Vec<QuotaUsage> {
+    used.iter()
+        .map(|(name, quota)| {
+            let expected = baseline.get(name).copied();
+            let (quota_used, error) = match quota {
+                Ok(quota) => (Some(*quota), None),
+                Err(err) => (None, Some(err.clone())),
+            };
+            let change = match (quota_used, expected) {
+                (Some(used), Some(expected)) if expected > 0 => {
+                    Some((used as f64 - expected as f64) * 100.0 / expected as f64)
+                }
+                (Some(used), Some(_)) => Some(if used > 0 { f64::INFINITY } else { 0.0 }),
+                _ => None,
+            };
+            let status = match (quota_used, change) {
+                (None, _) => QuotaStatus::Failed,
+                (Some(_), None) => QuotaStatus::New,
+                (Some(_), Some(change)) if change > tolerance => QuotaStatus::Regressed,
+                (Some(_), Some(change)) if change < -tolerance => QuotaStatus::Improved,
+                _ => QuotaStatus::Ok,
+            };
+            QuotaUsage {
+                name: name.clone(),
+                quota_used,
+                baseline: expected,
+                change,
+                status,
+                error,
+            }
+        })
+        .collect()
+}
+
+/// The baseline of the report, the measured quota of every call
+pub fn quota_baseline(used: &[(String, Result<u64, String>)]) -> Value {
+    let baseline = used
+        .iter()
+        .filter_map(|(name, quota)| quota.as_ref().ok().map(|quota| (name.clone(), *quota)))
+        .collect::<BTreeMap<String, u64>>();
+    serde_json::to_value(baseline).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{compare_quota, QuotaStatus};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_compare_quota() {
+        let mut baseline = BTreeMap::new();
+        baseline.insert("transfer".to_string(), 1000);
+        baseline.insert("approve".to_string(), 1000);
+        baseline.insert("mint".to_string(), 1000);
+        let used = vec![
+            ("transfer".to_string(), Ok(1020)),
+            ("approve".to_string(), Ok(1100)),
+            ("mint".to_string(), Ok(900)),
+            ("burn".to_string(), Ok(500)),
+            ("pause".to_string(), Err("reverted".to_string())),
+        ];
+        let report = compare_quota(&used, &baseline, 5.0);
+        assert_eq!(
+            report
+                .iter()
+                .map(|usage| usage.status)
+                .collect::<Vec<QuotaStatus>>(),
+            vec![
+                QuotaStatus::Ok,
+                QuotaStatus::Regressed,
+                QuotaStatus::Improved,
+                QuotaStatus::New,
+                QuotaStatus::Failed
+            ]
+        );
+        assert_eq!(report[1].change, Some(10.0));
+        assert_eq!(report[4].error.as_deref(), Some("reverted"));
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use hyper::Uri;
+
+use crate::error::ToolError;
+
+/// Limit the requests per second sent to every url, by a token bucket per url
+///
+/// The requests over the limit are delayed, not dropped. A bucket holds the tokens of
+/// one second, so a burst of that many requests is sent at once after an idle period.
+/// The clones share the buckets, so the cloned clients share the limits.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    default: Option<f64>,
+    limits: HashMap<String, f64>,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// Negative when requests are waiting for the tokens
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// Limit the requests to every url
+    pub fn new(requests_per_second: f64) -> Self {
+        RateLimiter {
+            default: Some(requests_per_second),
+            ..Default::default()
+        }
+    }
+
+    /// Limit the requests to the url, overriding the limit of every url
+    pub fn set_url_limit(mut self, url: &str, requests_per_second: f64) -> Self {
+        self.limits
+            .insert(normalize(url).to_owned(), requests_per_second);
+        self
+    }
+
+    /// Parse the limits, each is `RPS` for every url or `URL=RPS` for one url
+    pub fn from_limits<'a, T: IntoIterator<Item = &'a str>>(limits: T) -> Result<Self, ToolError> {
+        let mut limiter = RateLimiter::default();
+        for limit in limits {
+            let (url, rate) = match limit.rfind('=') {
+                Some(index) => (Some(&limit[..index]), &limit[index + 1..]),
+                None => (None, limit),
+            };
+            let rate = rate
+                .parse::<f64>()
+                .ok()
+                .filter(|rate| *rate > 0.0 && rate.is_finite())
+                .ok_or_else(|| ToolError::Customize(format!("Invalid rate limit: {}", limit)))?;
+            match url {
+                Some(url) => limiter = limiter.set_url_limit(url, rate),
+                None => limiter.default = Some(rate),
+            }
+        }
+        Ok(limiter)
+    }
+
+    /// The requests per second allowed to the url, None if unlimited
+    pub fn limit(&self, url: &Uri) -> Option<f64> {
+        self.limits
+            .get(normalize(&url.to_string()))
+            .cloned()
+            .or(self.default)
+    }
+
+    /// Take a token of the url, return when the request may be sent
+    pub fn reserve(&self, url: &Uri) -> Instant {
+        self.reserve_at(url, Instant::now())
+    }
+
+    fn reserve_at(&self, url: &Uri, now: Instant) -> Instant {
+        let rate = match self.limit(url) {
+            Some(rate) => rate,
+            None => return now,
+        };
+        let capacity = rate.max(1.0);
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets
+            .entry(normalize(&url.to_string()).to_owned())
+            .or_insert(Bucket {
+                tokens: capacity,
+                updated: now,
+            });
+
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        bucket.tokens = (bucket.tokens + elapsed.as_secs_f64() * rate).min(capacity);
+        bucket.updated = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            now
+        } else {
+            now + Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+/// The same url with or without the trailing slash
+pub(crate) fn normalize(url: &str) -> &str {
+    url.trim_end_matches('/')
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+
+    #[test]
+    fn test_reserve() {
+        let limiter = RateLimiter::from_limits(vec!["2", "http://127.0.0.1:1338=1"]).unwrap();
+        let node = "http://127.0.0.1:1337".parse().unwrap();
+        let other = "http://127.0.0.1:1338/".parse().unwrap();
+        let now = Instant::now();
+
+        // A burst of the tokens of one second, then one more every half second
+        assert_eq!(limiter.reserve_at(&node, now), now);
+        assert_eq!(limiter.reserve_at(&node, now), now);
+        assert_eq!(
+            limiter.reserve_at(&node, now),
+            now + Duration::from_millis(500)
+        );
+        assert_eq!(limiter.reserve_at(&node, now), now + Duration::from_secs(1));
+
+        // Every url has its own bucket
+        assert_eq!(limiter.limit(&other), Some(1.0));
+        assert_eq!(limiter.reserve_at(&other, now), now);
+        assert_eq!(
+            limiter.reserve_at(&other, now),
+            now + Duration::from_secs(1)
+        );
+
+        assert!(RateLimiter::from_limits(vec!["0"]).is_err());
+        assert!(RateLimiter::from_limits(vec!["http://127.0.0.1:1337=fast"]).is_err());
+    }
+}
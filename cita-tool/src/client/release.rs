@@ -0,0 +1,172 @@
+use futures::{Future, Stream};
+use hyper::{header, Body, Request, Uri};
+use serde_json::{self, Value};
+use sha2::{Digest, Sha256};
+
+use crate::client::basic::{create_client, Client};
+use crate::error::ToolError;
+
+/// The redirects followed, GitHub redirects the assets to their storage
+const MAX_REDIRECTS: usize = 5;
+
+/// A release of a GitHub repository
+#[derive(Debug, Clone, PartialEq)]
+pub struct GithubRelease {
+    /// The tag, such as `v20.2.2`
+    pub tag: String,
+    /// The names of the assets and their download urls
+    pub assets: Vec<(String, String)>,
+}
+
+impl GithubRelease {
+    /// The release of a response of the GitHub API
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let assets = value["assets"]
+            .as_array()?
+            .iter()
+            .filter_map(|asset| {
+                Some((
+                    asset["name"].as_str()?.to_owned(),
+                    asset["browser_download_url"].as_str()?.to_owned(),
+                ))
+            })
+            .collect();
+        Some(GithubRelease {
+            tag: value["tag_name"].as_str()?.to_owned(),
+            assets,
+        })
+    }
+
+    /// The download url of an asset
+    pub fn asset(&self, name: &str) -> Option<&str> {
+        self.assets
+            .iter()
+            .find(|(asset, _)| asset == name)
+            .map(|(_, url)| url.as_str())
+    }
+}
+
+/// The numbers of a version or a tag, `v20.2.2` is `[20, 2, 2]`, compared part by part
+pub fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect()
+}
+
+/// The sha256 of the data, in hex
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(Sha256::digest(data))
+}
+
+/// Check the data against its line in the output of `sha256sum`, `<sha256>  <name>`
+pub fn check_sha256(data: &[u8], checksums: &str, name: &str) -> Result<(), ToolError> {
+    let expected = checksums
+        .lines()
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            Some((parts.next()?, parts.next()?))
+        })
+        .find(|(_, file)| file.trim_start_matches('*') == name)
+        .map(|(sha256, _)| sha256.to_lowercase())
+        .ok_or_else(|| ToolError::Customize(format!("No checksum of {}", name)))?;
+    let actual = sha256_hex(data);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(ToolError::Customize(format!(
+            "The sha256 of {} is {}, not {}",
+            name, actual, expected
+        )))
+    }
+}
+
+impl Client {
+    /// A release of a GitHub repository, `owner/name`, the latest one without the tag,
+    /// `api` is `https://api.github.com` or the API of a GitHub Enterprise
+    pub fn github_release(
+        &self,
+        api: &str,
+        repo: &str,
+        tag: Option<&str>,
+    ) -> Result<GithubRelease, ToolError> {
+        let api = api.trim_end_matches('/');
+        let url = match tag {
+            Some(tag) => format!("{}/repos/{}/releases/tags/{}", api, repo, tag),
+            None => format!("{}/repos/{}/releases/latest", api, repo),
+        };
+        let value: Value =
+            serde_json::from_slice(&self.download(&url)?).map_err(ToolError::SerdeJson)?;
+        GithubRelease::from_json(&value)
+            .ok_or_else(|| ToolError::Customize(format!("No release in the response of {}", url)))
+    }
+
+    /// The content of the url, following the redirects
+    pub fn download(&self, url: &str) -> Result<Vec<u8>, ToolError> {
+        let mut url = url.to_owned();
+        for _ in 0..=MAX_REDIRECTS {
+            let uri: Uri = url
+                .parse()
+                .map_err(|err| ToolError::Customize(format!("{}: {}", url, err)))?;
+            let req = Request::get(uri)
+                .header(header::USER_AGENT, "cita-cli")
+                .body(Body::empty())
+                .unwrap();
+            let (status, location, body) = self.block_on(
+                create_client()
+                    .request(req)
+                    .and_then(|res| {
+                        let status = res.status();
+                        let location = res
+                            .headers()
+                            .get(header::LOCATION)
+                            .and_then(|location| location.to_str().ok())
+                            .map(str::to_owned);
+                        res.into_body()
+                            .concat2()
+                            .map(move |body| (status, location, body.to_vec()))
+                    })
+                    .map_err(ToolError::Hyper),
+            )?;
+            match location {
+                Some(location) if status.is_redirection() => url = location,
+                _ if status.is_success() => return Ok(body),
+                _ => return Err(ToolError::Customize(format!("GET {}: {}", url, status))),
+            }
+        }
+        Err(ToolError::Customize(format!(
+            "GET {}: more than {} redirects",
+            url, MAX_REDIRECTS
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_sha256, parse_version, sha256_hex, GithubRelease};
+    use serde_json::json;
+
+    #[test]
+    fn test_release() {
+        let release = GithubRelease::from_json(&json!({
+            "tag_name": "v20.2.3",
+            "assets": [{
+                "name": "cita-cli-x86_64-musl-tls-v20.2.3.tar.gz",
+                "browser_download_url": "https://github.com/citahub/cita-cli/releases/download/v20.2.3/cita-cli-x86_64-musl-tls-v20.2.3.tar.gz",
+            }],
+        }))
+        .unwrap();
+        assert!(release
+            .asset("cita-cli-x86_64-musl-tls-v20.2.3.tar.gz")
+            .is_some());
+        assert!(parse_version(&release.tag) > parse_version("20.2.2"));
+        assert!(parse_version("v20.10.0") > parse_version("v20.9.1"));
+
+        let sha256 = sha256_hex(b"cita-cli");
+        let checksums = format!("{}  cita-cli.tar.gz\n", sha256);
+        assert!(check_sha256(b"cita-cli", &checksums, "cita-cli.tar.gz").is_ok());
+        assert!(check_sha256(b"cita-cl1", &checksums, "cita-cli.tar.gz").is_err());
+        assert!(check_sha256(b"cita-cli", &checksums, "other.tar.gz").is_err());
+    }
+}
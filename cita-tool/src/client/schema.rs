@@ -0,0 +1,215 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_json::Value;
+
+/// The type of a field missing from some of the objects of an array
+const ABSENT: &str = "absent";
+
+/// The JSON shape of the values seen at a path: their types, and for the objects and the
+/// arrays the shapes of their fields and their items
+///
+/// The types are `null`, `bool`, `integer`, `number`, `hex`, `string`, `object` and `array`,
+/// a string of hex digits after `0x` is `hex`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Shape {
+    /// The types of the values
+    pub types: BTreeSet<String>,
+    /// The fields of the objects
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Shape>,
+    /// The items of the arrays, all of them merged
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub items: Option<Box<Shape>>,
+}
+
+impl Shape {
+    /// The shape of the value
+    pub fn of(value: &Value) -> Self {
+        let mut shape = Shape::default();
+        let kind = match value {
+            Value::Null => "null",
+            Value::Bool(_) => "bool",
+            Value::Number(number) if number.is_f64() => "number",
+            Value::Number(_) => "integer",
+            Value::String(text) if is_hex(text) => "hex",
+            Value::String(_) => "string",
+            Value::Array(items) => {
+                shape.items = items
+                    .iter()
+                    .map(Shape::of)
+                    .reduce(Shape::merge)
+                    .map(Box::new);
+                "array"
+            }
+            Value::Object(fields) => {
+                shape.fields = fields
+                    .iter()
+                    .map(|(name, value)| (name.clone(), Shape::of(value)))
+                    .collect();
+                "object"
+            }
+        };
+        shape.types.insert(kind.to_owned());
+        shape
+    }
+
+    /// The shape of the values of both, a field only one of the objects has may be absent
+    pub fn merge(mut self, other: Shape) -> Self {
+        let both_objects = self.types.contains("object") && other.types.contains("object");
+        self.types.extend(other.types);
+        let mut others = other.fields;
+        for (name, field) in self.fields.iter_mut() {
+            match others.remove(name) {
+                Some(other) => *field = field.clone().merge(other),
+                None if both_objects => {
+                    field.types.insert(ABSENT.to_owned());
+                }
+                None => {}
+            }
+        }
+        for (name, mut field) in others {
+            if both_objects {
+                field.types.insert(ABSENT.to_owned());
+            }
+            self.fields.insert(name, field);
+        }
+        self.items = match (self.items, other.items) {
+            (Some(items), Some(other)) => Some(Box::new(items.merge(*other))),
+            (items, other) => items.or(other),
+        };
+        self
+    }
+
+    /// The types joined by `|`
+    pub fn type_name(&self) -> String {
+        self.types
+            .iter()
+            .cloned()
+            .collect::<Vec<String>>()
+            .join("|")
+    }
+}
+
+/// How a path of the shape changed between two captures
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    /// The path is new
+    Added,
+    /// The path is gone
+    Removed,
+    /// The types of the path changed
+    Changed,
+}
+
+/// A change of the shape at a path
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaChange {
+    /// The path, `.` between the fields and `[]` for the items
+    pub path: String,
+    /// How it changed
+    pub kind: ChangeKind,
+    /// The types before
+    pub old: Option<String>,
+    /// The types after
+    pub new: Option<String>,
+}
+
+impl SchemaChange {
+    /// Whether the change may break the structs parsing the old shape, only
+    /// a new path is compatible
+    pub fn is_breaking(&self) -> bool {
+        self.kind != ChangeKind::Added
+    }
+}
+
+/// The changes from the old shape to the new one under the path
+pub fn diff_shapes(path: &str, old: &Shape, new: &Shape) -> Vec<SchemaChange> {
+    let mut changes = Vec::new();
+    if old.types != new.types {
+        changes.push(SchemaChange {
+            path: path.to_owned(),
+            kind: ChangeKind::Changed,
+            old: Some(old.type_name()),
+            new: Some(new.type_name()),
+        });
+    }
+    let names = old
+        .fields
+        .keys()
+        .chain(new.fields.keys())
+        .collect::<BTreeSet<&String>>();
+    for name in names {
+        let field_path = format!("{}.{}", path, name);
+        match (old.fields.get(name), new.fields.get(name)) {
+            (Some(old), Some(new)) => changes.extend(diff_shapes(&field_path, old, new)),
+            (old, new) => changes.push(SchemaChange {
+                path: field_path,
+                kind: if old.is_some() {
+                    ChangeKind::Removed
+                } else {
+                    ChangeKind::Added
+                },
+                old: old.map(Shape::type_name),
+                new: new.map(Shape::type_name),
+            }),
+        }
+    }
+    if let (Some(old), Some(new)) = (&old.items, &new.items) {
+        changes.extend(diff_shapes(&format!("{}[]", path), old, new));
+    }
+    changes
+}
+
+fn is_hex(text: &str) -> bool {
+    text.len() > 2 && text.starts_with("0x") && text[2..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff_shapes, ChangeKind, Shape};
+    use serde_json::json;
+
+    #[test]
+    fn test_schema_diff() {
+        let old = Shape::of(&json!({
+            "number": "0x10",
+            "proof": null,
+            "transactions": [{"hash": "0xab", "from": "0x01"}, {"hash": "0xcd"}],
+        }));
+        assert_eq!(old.fields["number"].type_name(), "hex");
+        let items = old.fields["transactions"].items.as_ref().unwrap();
+        assert_eq!(items.fields["from"].type_name(), "absent|hex");
+        assert_eq!(items.fields["hash"].type_name(), "hex");
+        let saved: Shape = serde_json::from_value(serde_json::to_value(&old).unwrap()).unwrap();
+        assert_eq!(saved, old);
+
+        let new = Shape::of(&json!({
+            "number": 16,
+            "proof": {"Bft": {}},
+            "transactions": [{"hash": "0xab", "from": "0x01", "index": "0x0"}],
+            "version": 2,
+        }));
+        let changes = diff_shapes("block", &old, &new);
+        let summary = changes
+            .iter()
+            .map(|change| (change.path.as_str(), change.kind.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                ("block.number", ChangeKind::Changed),
+                ("block.proof", ChangeKind::Changed),
+                ("block.proof.Bft", ChangeKind::Added),
+                ("block.transactions[].from", ChangeKind::Changed),
+                ("block.transactions[].index", ChangeKind::Added),
+                ("block.version", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(
+            changes.iter().filter(|change| change.is_breaking()).count(),
+            3
+        );
+        assert!(diff_shapes("block", &old, &old).is_empty());
+    }
+}
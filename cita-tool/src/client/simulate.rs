@@ -0,0 +1,64 @@
+use serde_json::{self, Value};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::{remove_0x, TransactionOptions};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+/// The outcome of a prospective transaction, called at the latest state
+#[derive(Debug, Clone, Serialize)]
+pub struct Simulation {
+    /// Whether the call succeeded
+    pub success: bool,
+    /// The sender of the call
+    pub sender: Option<String>,
+    /// The returned data, hex with 0x
+    pub output: Option<String>,
+    /// The revert reason, or the error of the call
+    pub error: Option<String>,
+}
+
+impl Client {
+    /// Call the transaction of the options at the latest state without sending it
+    ///
+    /// The sender defaults to the address of the private key. The call request of CITA
+    /// has no value, so the value of the options isn't simulated, and a contract
+    /// creation can't be called at all.
+    pub fn simulate(
+        &self,
+        options: &TransactionOptions,
+        sender: Option<&str>,
+    ) -> Result<Simulation, ToolError> {
+        let to = options.address();
+        if remove_0x(to).is_empty() {
+            return Err(ToolError::Customize(
+                "Can't simulate a contract creation".to_string(),
+            ));
+        }
+        let sender = sender.map(ToOwned::to_owned).or_else(|| {
+            self.transaction_signer()
+                .and_then(|signer| signer.address().ok())
+                .map(|address| address.completed_lower_hex_with_0x())
+        });
+        let data = format!("0x{}", remove_0x(options.code()));
+        let response = self.call(sender.as_deref(), to, Some(&data), "latest")?;
+
+        Ok(match response.error() {
+            Some(err) => Simulation {
+                success: false,
+                sender,
+                output: None,
+                error: Some(err.revert_reason().unwrap_or_else(|| err.message())),
+            },
+            None => Simulation {
+                success: true,
+                sender,
+                output: match serde_json::to_value(response.result()) {
+                    Ok(Value::String(output)) => Some(output),
+                    _ => None,
+                },
+                error: None,
+            },
+        })
+    }
+}
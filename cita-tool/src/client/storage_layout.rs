@@ -0,0 +1,483 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use serde_json::{self, json, Value};
+use types::{Address, H256, U256};
+
+#[cfg(feature = "http")]
+use crate::client::basic::{Client, ClientExt};
+use crate::client::{hex_to_u256, remove_0x};
+use crate::crypto::{Encryption, Hashable};
+use crate::error::ToolError;
+#[cfg(feature = "http")]
+use crate::LowerHex;
+
+/// Elements decoded at most from one dynamic array, and bytes at most from one string in slots
+const MAX_ELEMENTS: usize = 64;
+
+/// A state variable, or a member of a struct
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageEntry {
+    /// Name of the variable
+    pub label: String,
+    /// Offset in bytes inside the slot
+    pub offset: usize,
+    /// Slot of the variable, a decimal string
+    pub slot: String,
+    /// Type id in the types of the layout
+    #[serde(rename = "type")]
+    pub type_id: String,
+}
+
+/// A type of the storage layout
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageType {
+    /// `inplace`, `mapping`, `dynamic_array` or `bytes`
+    pub encoding: String,
+    /// Solidity name of the type
+    pub label: String,
+    /// Bytes taken by the type, a decimal string
+    #[serde(rename = "numberOfBytes")]
+    pub number_of_bytes: String,
+    /// Key type id of a mapping
+    #[serde(default)]
+    pub key: Option<String>,
+    /// Value type id of a mapping
+    #[serde(default)]
+    pub value: Option<String>,
+    /// Element type id of an array
+    #[serde(default)]
+    pub base: Option<String>,
+    /// Members of a struct
+    #[serde(default)]
+    pub members: Option<Vec<StorageEntry>>,
+}
+
+/// Storage layout of a contract, as `solc --storage-layout` outputs
+#[derive(Debug, Clone, Deserialize)]
+pub struct StorageLayout {
+    /// State variables
+    pub storage: Vec<StorageEntry>,
+    /// Types of the variables
+    #[serde(default)]
+    pub types: HashMap<String, StorageType>,
+}
+
+impl StorageLayout {
+    /// Parse the layout alone, or of the first contract in `solc --combined-json storage-layout`
+    pub fn from_json(content: &str) -> Result<Self, ToolError> {
+        let value: Value = serde_json::from_str(content).map_err(ToolError::SerdeJson)?;
+        let value = match value["contracts"].as_object() {
+            Some(contracts) => {
+                let layout = contracts
+                    .values()
+                    .next()
+                    .map(|contract| contract["storage-layout"].clone())
+                    .unwrap_or_default();
+                match layout {
+                    Value::String(layout) => {
+                        serde_json::from_str(&layout).map_err(ToolError::SerdeJson)?
+                    }
+                    layout => layout,
+                }
+            }
+            None => value,
+        };
+        serde_json::from_value(value).map_err(ToolError::SerdeJson)
+    }
+
+    /// Decode every state variable from the storage read by `read`
+    ///
+    /// The mappings are decoded for the keys given by their labels, the keys of nested
+    /// mappings are separated by commas, e.g. `allowed => ["0x..,0x.."]`.
+    pub fn decode<F>(
+        &self,
+        keys: &HashMap<String, Vec<String>>,
+        read: F,
+    ) -> Result<Vec<Value>, ToolError>
+    where
+        F: FnMut(&H256) -> Result<H256, ToolError>,
+    {
+        let mut decoder = Decoder {
+            types: self.types.clone(),
+            read,
+            cache: HashMap::new(),
+        };
+        self.storage
+            .iter()
+            .map(|entry| {
+                let slot = parse_slot(&entry.slot)?;
+                let value = decoder.variable(
+                    &entry.type_id,
+                    slot,
+                    entry.offset,
+                    keys.get(&entry.label)
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                )?;
+                Ok(json!({
+                    "name": entry.label,
+                    "type": decoder.label(&entry.type_id),
+                    "slot": entry.slot,
+                    "offset": entry.offset,
+                    "value": value,
+                }))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "http")]
+impl Client {
+    /// Read and decode the state variables of the contract at the height
+    pub fn storage_state(
+        &self,
+        layout: &StorageLayout,
+        address: &str,
+        keys: &HashMap<String, Vec<String>>,
+        height: &str,
+    ) -> Result<Vec<Value>, ToolError> {
+        layout.decode(keys, |slot| {
+            let response =
+                self.get_storage_at(address, &slot.completed_lower_hex_with_0x(), height)?;
+            if let Some(err) = response.error() {
+                return Err(ToolError::Customize(err.message()));
+            }
+            match serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)? {
+                Value::String(value) => H256::from_str(remove_0x(&value))
+                    .map_err(|e| ToolError::Customize(e.to_string())),
+                _ => Err(ToolError::Customize(
+                    "Unexpected getStorageAt result".to_string(),
+                )),
+            }
+        })
+    }
+}
+
+struct Decoder<F> {
+    types: HashMap<String, StorageType>,
+    read: F,
+    cache: HashMap<H256, H256>,
+}
+
+impl<F> Decoder<F>
+where
+    F: FnMut(&H256) -> Result<H256, ToolError>,
+{
+    fn label(&self, type_id: &str) -> String {
+        self.types
+            .get(type_id)
+            .map_or_else(|| type_id.to_owned(), |ty| ty.label.clone())
+    }
+
+    fn storage_type(&self, type_id: &str) -> Result<StorageType, ToolError> {
+        self.types
+            .get(type_id)
+            .cloned()
+            .ok_or_else(|| ToolError::Customize(format!("Unknown type {} in layout", type_id)))
+    }
+
+    fn slot(&mut self, slot: U256) -> Result<[u8; 32], ToolError> {
+        let key = to_h256(slot);
+        if let Some(value) = self.cache.get(&key) {
+            return Ok(value.0);
+        }
+        let value = (self.read)(&key)?;
+        self.cache.insert(key, value);
+        Ok(value.0)
+    }
+
+    fn variable(
+        &mut self,
+        type_id: &str,
+        slot: U256,
+        offset: usize,
+        keys: &[String],
+    ) -> Result<Value, ToolError> {
+        let ty = self.storage_type(type_id)?;
+        match ty.encoding.as_str() {
+            "mapping" => self.mapping(&ty, slot, keys),
+            "dynamic_array" => {
+                let length = U256::from(&self.slot(slot)?[..]);
+                let base = ty.base.clone().unwrap_or_default();
+                let start = U256::from(&keccak(&to_h256(slot).0)[..]);
+                let elements = self.array(&base, start, length_of(length))?;
+                Ok(json!({ "length": length.to_string(), "elements": elements }))
+            }
+            "bytes" => self.bytes(&ty, slot),
+            _ => {
+                if let Some(members) = ty.members {
+                    let mut result = serde_json::Map::new();
+                    for member in members {
+                        let value = self.variable(
+                            &member.type_id,
+                            slot + parse_slot(&member.slot)?,
+                            member.offset,
+                            &[],
+                        )?;
+                        result.insert(member.label, value);
+                    }
+                    Ok(Value::Object(result))
+                } else if let Some(base) = ty.base.clone() {
+                    let length = ty
+                        .label
+                        .rsplit('[')
+                        .next()
+                        .and_then(|length| length.trim_end_matches(']').parse().ok())
+                        .unwrap_or(0);
+                    Ok(Value::Array(self.array(&base, slot, length)?))
+                } else {
+                    let size = parse_size(&ty.number_of_bytes)?;
+                    let word = self.slot(slot)?;
+                    if offset + size > 32 {
+                        return Err(ToolError::Customize(format!(
+                            "Invalid offset {} of {}",
+                            offset, ty.label
+                        )));
+                    }
+                    Ok(format_value(
+                        &ty.label,
+                        &word[32 - offset - size..32 - offset],
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Elements of an array starting at the slot, packed when they are small
+    fn array(&mut self, base: &str, slot: U256, length: usize) -> Result<Vec<Value>, ToolError> {
+        let size = parse_size(&self.storage_type(base)?.number_of_bytes)?;
+        (0..length.min(MAX_ELEMENTS))
+            .map(|index| {
+                if size <= 16 {
+                    let per_slot = 32 / size;
+                    self.variable(
+                        base,
+                        slot + U256::from(index / per_slot),
+                        index % per_slot * size,
+                        &[],
+                    )
+                } else {
+                    let slots = size.div_ceil(32);
+                    self.variable(base, slot + U256::from(index * slots), 0, &[])
+                }
+            })
+            .collect()
+    }
+
+    fn mapping(
+        &mut self,
+        ty: &StorageType,
+        slot: U256,
+        keys: &[String],
+    ) -> Result<Value, ToolError> {
+        let (key_type, value_type) = match (ty.key.clone(), ty.value.clone()) {
+            (Some(key), Some(value)) => (key, value),
+            _ => {
+                return Err(ToolError::Customize(format!(
+                    "Missing key or value type of {}",
+                    ty.label
+                )))
+            }
+        };
+        let mut result = serde_json::Map::new();
+        for key in keys {
+            let mut levels = key.splitn(2, ',');
+            let first = levels.next().unwrap_or_default().trim();
+            let mut preimage = encode_key(&self.label(&key_type), first)?;
+            preimage.extend_from_slice(&to_h256(slot).0);
+            let value_slot = U256::from(&keccak(&preimage)[..]);
+            let nested: Vec<String> = levels.map(ToOwned::to_owned).collect();
+            let value = self.variable(&value_type, value_slot, 0, &nested)?;
+            match result.get_mut(first) {
+                Some(Value::Object(previous)) => {
+                    if let Value::Object(value) = value {
+                        previous.extend(value);
+                    }
+                }
+                _ => {
+                    result.insert(first.to_owned(), value);
+                }
+            }
+        }
+        Ok(Value::Object(result))
+    }
+
+    /// String and bytes, short ones are kept in the slot with the length
+    fn bytes(&mut self, ty: &StorageType, slot: U256) -> Result<Value, ToolError> {
+        let word = self.slot(slot)?;
+        let data = if word[31] & 1 == 0 {
+            let length = (word[31] / 2) as usize;
+            word[..length.min(31)].to_vec()
+        } else {
+            let length = length_of((U256::from(&word[..]) - U256::from(1)) / U256::from(2))
+                .min(MAX_ELEMENTS * 32);
+            let start = U256::from(&keccak(&to_h256(slot).0)[..]);
+            let mut data = Vec::with_capacity(length);
+            for index in 0..length.div_ceil(32) {
+                data.extend_from_slice(&self.slot(start + U256::from(index))?);
+            }
+            data.truncate(length);
+            data
+        };
+        if ty.label == "string" {
+            if let Ok(string) = String::from_utf8(data.clone()) {
+                return Ok(json!(string));
+            }
+        }
+        Ok(json!(format!("0x{}", hex::encode(data))))
+    }
+}
+
+fn keccak(data: &[u8]) -> [u8; 32] {
+    data.crypt_hash(Encryption::Secp256k1).0
+}
+
+fn to_h256(value: U256) -> H256 {
+    let mut bytes = [0u8; 32];
+    value.to_big_endian(&mut bytes);
+    H256::from(bytes)
+}
+
+fn length_of(length: U256) -> usize {
+    if length > U256::from(usize::MAX) {
+        usize::MAX
+    } else {
+        length.low_u64() as usize
+    }
+}
+
+fn parse_slot(slot: &str) -> Result<U256, ToolError> {
+    U256::from_dec_str(slot).map_err(|_| ToolError::Customize(format!("Invalid slot {}", slot)))
+}
+
+fn parse_size(size: &str) -> Result<usize, ToolError> {
+    size.parse()
+        .map_err(|_| ToolError::Customize(format!("Invalid numberOfBytes {}", size)))
+}
+
+fn parse_uint(value: &str) -> Result<U256, ToolError> {
+    let invalid = || ToolError::Customize(format!("Invalid integer key {}", value));
+    if let Some(hex) = value.strip_prefix("0x") {
+        hex_to_u256(hex).map_err(|_| invalid())
+    } else if let Some(negative) = value.strip_prefix('-') {
+        let value = U256::from_dec_str(negative).map_err(|_| invalid())?;
+        Ok((!value).overflowing_add(U256::from(1)).0)
+    } else {
+        U256::from_dec_str(value).map_err(|_| invalid())
+    }
+}
+
+/// The key of a mapping as it is hashed with the slot
+fn encode_key(label: &str, key: &str) -> Result<Vec<u8>, ToolError> {
+    if label == "string" {
+        Ok(key.as_bytes().to_vec())
+    } else if label == "bytes" {
+        hex::decode(remove_0x(key)).map_err(ToolError::Decode)
+    } else if label.starts_with("bytes") {
+        let mut bytes = hex::decode(remove_0x(key)).map_err(ToolError::Decode)?;
+        bytes.resize(32, 0);
+        Ok(bytes)
+    } else if label.starts_with("address") || label.starts_with("contract ") {
+        let address = Address::from_str(remove_0x(key))
+            .map_err(|_| ToolError::Customize(format!("Invalid address key {}", key)))?;
+        Ok(H256::from(address).to_vec())
+    } else if label == "bool" {
+        Ok(to_h256(U256::from((key == "true") as u8)).to_vec())
+    } else {
+        Ok(to_h256(parse_uint(key)?).to_vec())
+    }
+}
+
+/// Format a value kept inplace by its type
+fn format_value(label: &str, bytes: &[u8]) -> Value {
+    if label == "bool" {
+        json!(bytes.iter().any(|byte| *byte != 0))
+    } else if label.starts_with("address") || label.starts_with("contract ") {
+        json!(format!("0x{}", hex::encode(bytes)))
+    } else if label.starts_with("uint") || label.starts_with("enum ") {
+        json!(U256::from(bytes).to_string())
+    } else if label.starts_with("int") {
+        let value = U256::from(bytes);
+        if bytes[0] & 0x80 != 0 {
+            let modulus = if bytes.len() == 32 {
+                U256::zero()
+            } else {
+                U256::from(1) << (bytes.len() * 8)
+            };
+            json!(format!("-{}", modulus.overflowing_sub(value).0))
+        } else {
+            json!(value.to_string())
+        }
+    } else {
+        json!(format!("0x{}", hex::encode(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{keccak, to_h256, StorageLayout};
+    use types::{Address, H256, U256};
+
+    #[test]
+    fn test_decode_storage_layout() {
+        let layout = StorageLayout::from_json(
+            r#"{
+                "storage": [
+                    {"label": "owner", "offset": 0, "slot": "0", "type": "t_address"},
+                    {"label": "paused", "offset": 20, "slot": "0", "type": "t_bool"},
+                    {"label": "name", "offset": 0, "slot": "1", "type": "t_string_storage"},
+                    {"label": "balances", "offset": 0, "slot": "2",
+                     "type": "t_mapping(t_address,t_int256)"}
+                ],
+                "types": {
+                    "t_address": {"encoding": "inplace", "label": "address", "numberOfBytes": "20"},
+                    "t_bool": {"encoding": "inplace", "label": "bool", "numberOfBytes": "1"},
+                    "t_int256": {"encoding": "inplace", "label": "int256", "numberOfBytes": "32"},
+                    "t_string_storage": {"encoding": "bytes", "label": "string", "numberOfBytes": "32"},
+                    "t_mapping(t_address,t_int256)": {"encoding": "mapping", "label":
+                        "mapping(address => int256)", "key": "t_address", "value": "t_int256",
+                        "numberOfBytes": "32"}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let holder = [0x11u8; 20];
+        let mut preimage = H256::from(Address::from(&holder[..])).to_vec();
+        preimage.extend_from_slice(&to_h256(U256::from(2)).0);
+        let balance_slot = H256::from(keccak(&preimage));
+
+        let mut slot0 = [0u8; 32];
+        slot0[11] = 1;
+        slot0[12..].copy_from_slice(&[0xabu8; 20]);
+        let mut slot1 = [0u8; 32];
+        slot1[..3].copy_from_slice(b"cit");
+        slot1[31] = 6;
+        let mut storage = HashMap::new();
+        storage.insert(to_h256(U256::from(0)), H256::from(slot0));
+        storage.insert(to_h256(U256::from(1)), H256::from(slot1));
+        storage.insert(balance_slot, H256::from([0xffu8; 32]));
+
+        let mut keys = HashMap::new();
+        keys.insert(
+            "balances".to_string(),
+            vec![format!("0x{}", hex::encode(holder))],
+        );
+        let state = layout
+            .decode(&keys, |slot| {
+                Ok(storage.get(slot).cloned().unwrap_or_else(H256::zero))
+            })
+            .unwrap();
+
+        assert_eq!(state[0]["value"], format!("0x{}", "ab".repeat(20)));
+        assert_eq!(state[1]["value"], true);
+        assert_eq!(state[2]["value"], "cit");
+        assert_eq!(
+            state[3]["value"][format!("0x{}", hex::encode(holder))],
+            "-1"
+        );
+    }
+}
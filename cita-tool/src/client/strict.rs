@@ -0,0 +1,362 @@
+use serde_json::{Map, Value};
+
+use crate::client::MethodAliases;
+use crate::error::ToolError;
+
+/// The shape of a value in the responses of CITA
+#[derive(Debug, Clone, Copy)]
+pub enum Field {
+    /// Hex of a number without leading zeros, `0x0` for zero
+    Quantity,
+    /// Hex of bytes
+    Data,
+    /// Hex of 32 bytes
+    Hash,
+    /// Hex of 20 bytes
+    Address,
+    /// A json integer
+    Integer,
+    /// A json string
+    Text,
+    /// A json bool
+    Bool,
+    /// Never checked
+    Any,
+    /// The value or null
+    Nullable(&'static Field),
+    /// A field which may be missing
+    Optional(&'static Field),
+    /// Either of the shapes
+    Either(&'static Field, &'static Field),
+    /// An array of the shape
+    Array(&'static Field),
+    /// An object of exactly the fields, every one is mandatory unless `Optional`
+    Object(&'static [(&'static str, Field)]),
+}
+
+const LOG: Field = Field::Object(&[
+    ("address", Field::Address),
+    ("topics", Field::Array(&Field::Hash)),
+    ("data", Field::Data),
+    ("blockHash", Field::Hash),
+    ("blockNumber", Field::Quantity),
+    ("transactionHash", Field::Hash),
+    ("transactionIndex", Field::Quantity),
+    ("logIndex", Field::Quantity),
+    ("transactionLogIndex", Field::Quantity),
+]);
+
+const TRANSACTION: Field = Field::Object(&[
+    ("hash", Field::Hash),
+    ("content", Field::Data),
+    ("from", Field::Address),
+    ("blockNumber", Field::Quantity),
+    ("blockHash", Field::Hash),
+    ("index", Field::Quantity),
+]);
+
+const BLOCK: Field = Field::Nullable(&Field::Object(&[
+    ("version", Field::Integer),
+    ("hash", Field::Hash),
+    (
+        "header",
+        Field::Object(&[
+            ("timestamp", Field::Integer),
+            ("prevHash", Field::Hash),
+            ("number", Field::Quantity),
+            ("stateRoot", Field::Hash),
+            ("transactionsRoot", Field::Hash),
+            ("receiptsRoot", Field::Hash),
+            ("quotaUsed", Field::Quantity),
+            ("proof", Field::Nullable(&Field::Any)),
+            ("proposer", Field::Address),
+        ]),
+    ),
+    (
+        "body",
+        Field::Object(&[(
+            "transactions",
+            Field::Array(&Field::Either(&Field::Hash, &TRANSACTION)),
+        )]),
+    ),
+]));
+
+const RECEIPT: Field = Field::Nullable(&Field::Object(&[
+    ("transactionHash", Field::Hash),
+    ("transactionIndex", Field::Quantity),
+    ("blockHash", Field::Hash),
+    ("blockNumber", Field::Quantity),
+    ("cumulativeQuotaUsed", Field::Quantity),
+    ("quotaUsed", Field::Quantity),
+    ("contractAddress", Field::Nullable(&Field::Address)),
+    ("logs", Field::Array(&LOG)),
+    ("root", Field::Nullable(&Field::Data)),
+    ("logsBloom", Field::Data),
+    ("errorMessage", Field::Nullable(&Field::Text)),
+]));
+
+const META_DATA: Field = Field::Object(&[
+    ("chainId", Field::Integer),
+    ("chainIdV1", Field::Quantity),
+    ("chainName", Field::Text),
+    ("operator", Field::Text),
+    ("genesisTimestamp", Field::Integer),
+    ("validators", Field::Array(&Field::Address)),
+    ("blockInterval", Field::Integer),
+    ("tokenName", Field::Text),
+    ("tokenSymbol", Field::Text),
+    ("tokenAvatar", Field::Text),
+    ("version", Field::Integer),
+    ("economicalModel", Field::Integer),
+    ("website", Field::Text),
+]);
+
+/// The shape of the result of the method, None when it's not known
+pub fn result_field(method: &str) -> Option<Field> {
+    let method = MethodAliases::names(method)
+        .first()
+        .copied()
+        .unwrap_or(method);
+    let field = match method {
+        "blockNumber"
+        | "peerCount"
+        | "getTransactionCount"
+        | "getBalance"
+        | "estimateQuota"
+        | "newFilter"
+        | "newBlockFilter" => Field::Quantity,
+        "call"
+        | "getCode"
+        | "getAbi"
+        | "getTransactionProof"
+        | "getBlockHeader"
+        | "getStateProof" => Field::Data,
+        "getStorageAt" => Field::Hash,
+        "uninstallFilter" => Field::Bool,
+        "getBlockByNumber" | "getBlockByHash" => BLOCK,
+        "getTransaction" => Field::Nullable(&TRANSACTION),
+        "getTransactionReceipt" => RECEIPT,
+        "getLogs" | "getFilterLogs" => Field::Array(&LOG),
+        "getFilterChanges" => Field::Array(&Field::Either(&Field::Hash, &LOG)),
+        "getMetaData" => META_DATA,
+        "sendRawTransaction" => Field::Object(&[("hash", Field::Hash), ("status", Field::Text)]),
+        "peersInfo" => Field::Object(&[
+            ("amount", Field::Integer),
+            ("peers", Field::Any),
+            (
+                "errorMessage",
+                Field::Optional(&Field::Nullable(&Field::Text)),
+            ),
+        ]),
+        "getVersion" => Field::Object(&[("softwareVersion", Field::Text)]),
+        _ => return None,
+    };
+    Some(field)
+}
+
+/// What's wrong with the value at the path, empty when it has the shape
+pub fn check_field(path: &str, field: Field, value: &Value) -> Vec<String> {
+    let mut problems = Vec::new();
+    check(path, field, value, &mut problems);
+    problems
+}
+
+/// What's wrong with the raw jsonrpc response of the method, empty when it's
+/// a valid response of the version 2.0 with the result of the method
+pub fn check_response(method: &str, response: &Value) -> Vec<String> {
+    let object = match response.as_object() {
+        Some(object) => object,
+        None => return vec!["The response is not an object".to_string()],
+    };
+    let mut problems = Vec::new();
+    if object.get("jsonrpc") != Some(&Value::String("2.0".to_string())) {
+        problems.push("jsonrpc: not \"2.0\"".to_string());
+    }
+    if !object.get("id").is_some_and(Value::is_u64) {
+        problems.push("id: missing or not a number".to_string());
+    }
+    match (object.get("result"), object.get("error")) {
+        (Some(_), Some(_)) => problems.push("Both result and error are present".to_string()),
+        (None, None) => problems.push("Neither result nor error is present".to_string()),
+        (Some(result), None) => {
+            if let Some(field) = result_field(method) {
+                check("result", field, result, &mut problems);
+            }
+        }
+        (None, Some(error)) => check(
+            "error",
+            Field::Object(&[
+                ("code", Field::Integer),
+                ("message", Field::Text),
+                ("data", Field::Optional(&Field::Any)),
+            ]),
+            error,
+            &mut problems,
+        ),
+    }
+    unknown_fields(
+        "",
+        object,
+        &["jsonrpc", "id", "result", "error"],
+        &mut problems,
+    );
+    problems
+}
+
+/// Parse the raw response, fail on what strict mode refuses
+pub(crate) fn strict_response(method: &str, body: &[u8]) -> Result<(), ToolError> {
+    let response: Value = serde_json::from_slice(body).map_err(ToolError::SerdeJson)?;
+    let problems = check_response(method, &response);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(ToolError::Customize(format!(
+            "The response of {} is not strictly valid: {}",
+            method,
+            problems.join("; ")
+        )))
+    }
+}
+
+fn check(path: &str, field: Field, value: &Value, problems: &mut Vec<String>) {
+    let expected = |problems: &mut Vec<String>, what: &str| {
+        problems.push(format!("{}: expected {}, got {}", path, what, value));
+    };
+    match field {
+        Field::Quantity => match value.as_str().and_then(|hex| hex.strip_prefix("0x")) {
+            Some(digits) if is_lower_hex(digits) && !digits.is_empty() => {
+                if digits.len() > 1 && digits.starts_with('0') {
+                    problems.push(format!("{}: non-canonical quantity {}", path, value));
+                }
+            }
+            _ => expected(problems, "a hex quantity"),
+        },
+        Field::Data | Field::Hash | Field::Address => {
+            let length = match field {
+                Field::Hash => Some(64),
+                Field::Address => Some(40),
+                _ => None,
+            };
+            match value.as_str().and_then(|hex| hex.strip_prefix("0x")) {
+                Some(digits) if is_lower_hex(digits) && digits.len() % 2 == 0 => {
+                    if length.is_some_and(|length| length != digits.len()) {
+                        expected(problems, &format!("{} hex digits", length.unwrap()));
+                    }
+                }
+                Some(digits) if digits.chars().all(|c| c.is_ascii_hexdigit()) => {
+                    problems.push(format!("{}: non-canonical hex {}", path, value))
+                }
+                _ => expected(problems, "hex data"),
+            }
+        }
+        Field::Integer if !value.is_i64() && !value.is_u64() => expected(problems, "an integer"),
+        Field::Text if !value.is_string() => expected(problems, "a string"),
+        Field::Bool if !value.is_boolean() => expected(problems, "a bool"),
+        Field::Integer | Field::Text | Field::Bool | Field::Any => {}
+        Field::Nullable(field) | Field::Optional(field) => {
+            if !value.is_null() {
+                check(path, *field, value, problems);
+            }
+        }
+        Field::Either(left, right) => {
+            let mut left_problems = Vec::new();
+            check(path, *left, value, &mut left_problems);
+            if !left_problems.is_empty() {
+                check(path, *right, value, problems);
+            }
+        }
+        Field::Array(field) => match value.as_array() {
+            Some(values) => {
+                for (index, value) in values.iter().enumerate() {
+                    check(&format!("{}[{}]", path, index), *field, value, problems);
+                }
+            }
+            None => expected(problems, "an array"),
+        },
+        Field::Object(fields) => match value.as_object() {
+            Some(object) => {
+                for (name, field) in fields {
+                    let path = format!("{}.{}", path, name);
+                    match (object.get(*name), field) {
+                        (Some(value), _) => check(&path, *field, value, problems),
+                        (None, Field::Optional(_)) => {}
+                        (None, _) => problems.push(format!("{}: missing", path)),
+                    }
+                }
+                let names = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+                unknown_fields(path, object, &names, problems);
+            }
+            None => expected(problems, "an object"),
+        },
+    }
+}
+
+fn unknown_fields(
+    path: &str,
+    object: &Map<String, Value>,
+    known: &[&str],
+    problems: &mut Vec<String>,
+) {
+    for name in object.keys().filter(|name| !known.contains(&name.as_str())) {
+        if path.is_empty() {
+            problems.push(format!("{}: unknown field", name));
+        } else {
+            problems.push(format!("{}.{}: unknown field", path, name));
+        }
+    }
+}
+
+fn is_lower_hex(digits: &str) -> bool {
+    digits
+        .chars()
+        .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+#[cfg(test)]
+mod test {
+    use super::check_response;
+    use serde_json::json;
+
+    #[test]
+    fn test_check_response() {
+        let response = |result| json!({ "jsonrpc": "2.0", "id": 1, "result": result });
+        assert!(check_response("blockNumber", &response(json!("0x2a"))).is_empty());
+        assert!(check_response("blockNumber", &response(json!("0x0"))).is_empty());
+        assert_eq!(
+            check_response("cita_blockNumber", &response(json!("0x002a"))),
+            vec!["result: non-canonical quantity \"0x002a\""]
+        );
+        assert_eq!(
+            check_response("getCode", &response(json!("0xAB"))),
+            vec!["result: non-canonical hex \"0xAB\""]
+        );
+        // Not known methods only get the envelope checked
+        assert!(check_response("getCensoredAddrs", &response(json!([1]))).is_empty());
+
+        let log = json!({
+            "address": format!("0x{}", "1".repeat(40)),
+            "topics": [format!("0x{}", "2".repeat(64))],
+            "data": "0x",
+            "blockHash": format!("0x{}", "3".repeat(64)),
+            "blockNumber": "0x1",
+            "transactionHash": format!("0x{}", "4".repeat(64)),
+            "transactionIndex": "0x0",
+            "logIndex": "0x0",
+            "extra": true,
+        });
+        assert_eq!(
+            check_response("getLogs", &response(json!([log]))),
+            vec![
+                "result[0].transactionLogIndex: missing",
+                "result[0].extra: unknown field"
+            ]
+        );
+        assert_eq!(
+            check_response(
+                "blockNumber",
+                &json!({ "jsonrpc": "2.0", "id": 1, "error": { "code": -32601, "message": "" }, "x": 1 }),
+            ),
+            vec!["x: unknown field"]
+        );
+    }
+}
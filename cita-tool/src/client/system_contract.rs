@@ -39,7 +39,7 @@ where
 
     /// SendTx a contract method
     fn contract_send_tx(
-        &mut self,
+        &self,
         name: &str,
         values: &[&str],
         quota: Option<u64>,
@@ -139,7 +139,7 @@ where
 
     /// Create a new group
     fn new_group(
-        &mut self,
+        &self,
         origin: &str,
         name: &str,
         accounts: &str,
@@ -150,14 +150,14 @@ where
     }
 
     /// Delete the group
-    fn delete_group(&mut self, origin: &str, target: &str, quota: Option<u64>) -> Result<R, E> {
+    fn delete_group(&self, origin: &str, target: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(origin), remove_0x(target)];
         self.contract_send_tx("deleteGroup", &values, quota, None)
     }
 
     /// Update the group name
     fn update_group_name(
-        &mut self,
+        &self,
         origin: &str,
         target: &str,
         name: &str,
@@ -169,7 +169,7 @@ where
 
     /// Add accounts
     fn add_accounts(
-        &mut self,
+        &self,
         origin: &str,
         target: &str,
         accounts: &str,
@@ -181,7 +181,7 @@ where
 
     /// Delete accounts
     fn delete_accounts(
-        &mut self,
+        &self,
         origin: &str,
         target: &str,
         accounts: &str,
@@ -293,7 +293,7 @@ where
     /// param name: The name of role
     /// param permissions: The permissions of role
     /// return New role's address
-    fn new_role(&mut self, name: &str, permissions: &str, quota: Option<u64>) -> Result<R, E> {
+    fn new_role(&self, name: &str, permissions: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [name, permissions];
         self.contract_send_tx("newRole", &values, quota, None)
     }
@@ -302,7 +302,7 @@ where
     ///
     /// param role: The address of role
     /// return true if successed, otherwise false
-    fn delete_role(&mut self, role: &str, quota: Option<u64>) -> Result<R, E> {
+    fn delete_role(&self, role: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(role)];
         self.contract_send_tx("deleteRole", &values, quota, None)
     }
@@ -312,7 +312,7 @@ where
     /// param role: The address of role
     /// param name: The new name of role
     /// return true if successed, otherwise false
-    fn update_role_name(&mut self, role: &str, name: &str, quota: Option<u64>) -> Result<R, E> {
+    fn update_role_name(&self, role: &str, name: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(role), name];
         self.contract_send_tx("updateRoleName", &values, quota, None)
     }
@@ -322,12 +322,7 @@ where
     /// param role: The address of role
     /// param permissions: The permissions of role
     /// return true if successed, otherwise false
-    fn add_permissions(
-        &mut self,
-        role: &str,
-        permissions: &str,
-        quota: Option<u64>,
-    ) -> Result<R, E> {
+    fn add_permissions(&self, role: &str, permissions: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(role), permissions];
         self.contract_send_tx("addPermissions", &values, quota, None)
     }
@@ -338,7 +333,7 @@ where
     /// param permissions: The permissions of role
     /// return true if successed, otherwise false
     fn delete_permissions(
-        &mut self,
+        &self,
         role: &str,
         permissions: &str,
         quota: Option<u64>,
@@ -352,7 +347,7 @@ where
     /// param account: The account to be setted
     /// param role: The role to be setted
     /// return true if successed, otherwise false
-    fn set_role(&mut self, account: &str, role: &str, quota: Option<u64>) -> Result<R, E> {
+    fn set_role(&self, account: &str, role: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(account), remove_0x(role)];
         self.contract_send_tx("setRole", &values, quota, None)
     }
@@ -362,7 +357,7 @@ where
     /// param account: The account to be canceled
     /// param role: The role to be canceled
     /// return true if successed, otherwise false
-    fn cancel_role(&mut self, account: &str, role: &str, quota: Option<u64>) -> Result<R, E> {
+    fn cancel_role(&self, account: &str, role: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(account), remove_0x(role)];
         self.contract_send_tx("cancelRole", &values, quota, None)
     }
@@ -371,7 +366,7 @@ where
     ///
     /// param account: The account to be cleared
     /// return true if successed, otherwise false
-    fn clear_role(&mut self, account: &str, quota: Option<u64>) -> Result<R, E> {
+    fn clear_role(&self, account: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(account)];
         self.contract_send_tx("clearRole", &values, quota, None)
     }
@@ -383,7 +378,7 @@ where
     fn query_roles(&self, account: &str, height: Option<&str>) -> Result<R, E> {
         let values = [remove_0x(account)];
         let to = "0xffffffffffffffffffffffffffffffffff02000d";
-        self.contract_call_to_address("queryRoles", &values, &to, height)
+        self.contract_call_to_address("queryRoles", &values, to, height)
     }
 
     /// Query the accounts that have the role
@@ -393,7 +388,7 @@ where
     fn query_accounts(&self, role: &str, height: Option<&str>) -> Result<R, E> {
         let values = [remove_0x(role)];
         let to = "0xffffffffffffffffffffffffffffffffff02000d";
-        self.contract_call_to_address("queryAccounts", &values, &to, height)
+        self.contract_call_to_address("queryAccounts", &values, to, height)
     }
 }
 
@@ -563,7 +558,7 @@ where
     /// param funcs: The function signature of the resource
     /// return New permission's address
     fn new_permission(
-        &mut self,
+        &self,
         name: &str,
         contracts: &str,
         funcs: &str,
@@ -577,7 +572,7 @@ where
     ///
     /// param permission: The address of permission
     /// return true if successed, otherwise false
-    fn delete_permission(&mut self, permission: &str, quota: Option<u64>) -> Result<R, E> {
+    fn delete_permission(&self, permission: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(permission)];
         self.contract_send_tx("deletePermission", &values, quota, None)
     }
@@ -588,7 +583,7 @@ where
     /// param name: The new name
     /// return true if successed, otherwise false
     fn update_permission_name(
-        &mut self,
+        &self,
         permission: &str,
         name: &str,
         quota: Option<u64>,
@@ -604,7 +599,7 @@ where
     /// param funcs: The function signature of resource
     /// return true if successed, otherwise false
     fn add_resources(
-        &mut self,
+        &self,
         permission: &str,
         contracts: &str,
         funcs: &str,
@@ -621,7 +616,7 @@ where
     /// param funcs: The function signature of resource
     /// return true if successed, otherwise false
     fn delete_resources(
-        &mut self,
+        &self,
         permission: &str,
         contracts: &str,
         funcs: &str,
@@ -637,7 +632,7 @@ where
     /// param permission: The permission to be setted
     /// return true if success, otherwise false
     fn set_authorization(
-        &mut self,
+        &self,
         account_address: &str,
         permission: &str,
         quota: Option<u64>,
@@ -652,7 +647,7 @@ where
     /// param permissions: The multiple permissions to be setted
     /// return true if success, otherwise false
     fn set_authorizations(
-        &mut self,
+        &self,
         account_address: &str,
         permissions: &str,
         quota: Option<u64>,
@@ -667,7 +662,7 @@ where
     /// param permissions: The permission to be canceled
     /// return true if success, otherwise false
     fn cancel_authorization(
-        &mut self,
+        &self,
         account_address: &str,
         permission: &str,
         quota: Option<u64>,
@@ -682,7 +677,7 @@ where
     /// param permissions: The multiple permissions to be canceled
     /// return true if success, otherwise false
     fn cancel_authorizations(
-        &mut self,
+        &self,
         account_address: &str,
         permissions: &str,
         quota: Option<u64>,
@@ -695,7 +690,7 @@ where
     ///
     /// param account: The account to be cleared
     /// return true if success, otherwise false
-    fn clear_authorization(&mut self, account_address: &str, quota: Option<u64>) -> Result<R, E> {
+    fn clear_authorization(&self, account_address: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(account_address)];
         self.contract_send_tx("clearAuthorization", &values, quota, None)
     }
@@ -723,7 +718,7 @@ where
     fn create(client: T) -> Self;
 
     /// Downgrade consensus node to ordinary node
-    fn downgrade_consensus_node(&mut self, address: &str, quota: Option<u64>) -> Result<R, E> {
+    fn downgrade_consensus_node(&self, address: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(address)];
         self.contract_send_tx("deleteNode", &values, quota, None)
     }
@@ -740,7 +735,7 @@ where
     }
 
     /// Approve node upgrades to consensus nodes
-    fn approve_node(&mut self, address: &str, quota: Option<u64>) -> Result<R, E> {
+    fn approve_node(&self, address: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(address)];
         self.contract_send_tx("approveNode", &values, quota, None)
     }
@@ -751,7 +746,7 @@ where
     }
 
     /// Set node stake
-    fn set_stake(&mut self, address: &str, stake: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_stake(&self, address: &str, stake: U256, quota: Option<u64>) -> Result<R, E> {
         let stake = stake.completed_lower_hex();
         let values = [remove_0x(address), stake.as_str()];
         self.contract_send_tx("setStake", &values, quota, None)
@@ -811,21 +806,21 @@ where
     }
 
     /// Set block quota limit
-    fn set_bql(&mut self, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_bql(&self, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
         let quota_limit = quota_limit.completed_lower_hex();
         let values = [quota_limit.as_str()];
         self.contract_send_tx("setBQL", &values, quota, None)
     }
 
     /// Set default account quota limit
-    fn set_default_aql(&mut self, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_default_aql(&self, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
         let quota_limit = quota_limit.completed_lower_hex();
         let values = [quota_limit.as_str()];
         self.contract_send_tx("setDefaultAQL", &values, quota, None)
     }
 
     /// Set account quota upper limit of the specific account
-    fn set_aql(&mut self, address: &str, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_aql(&self, address: &str, quota_limit: U256, quota: Option<u64>) -> Result<R, E> {
         let quota_limit = quota_limit.completed_lower_hex();
         let values = [remove_0x(address), quota_limit.as_str()];
         self.contract_send_tx("setAQL", &values, quota, None)
@@ -865,7 +860,7 @@ where
     }
 
     /// Update admin account
-    fn add_admin(&mut self, address: &str, quota: Option<u64>) -> Result<R, E> {
+    fn add_admin(&self, address: &str, quota: Option<u64>) -> Result<R, E> {
         let values = [remove_0x(address)];
         self.contract_send_tx("update", &values, quota, None)
     }
@@ -893,7 +888,7 @@ where
     fn create(client: T) -> Self;
 
     /// Multi transactions send once
-    fn multi_transactions(&mut self, txs: Vec<&str>, quota: Option<u64>) -> Result<R, E> {
+    fn multi_transactions(&self, txs: Vec<&str>, quota: Option<u64>) -> Result<R, E> {
         let combined_txs = txs
             .into_iter()
             .fold(String::with_capacity(100), |mut a, b| {
@@ -980,25 +975,25 @@ where
     }
 
     /// Set chain name
-    fn set_chain_name(&mut self, chain_name: &str, quota: Option<u64>) -> Result<R, E> {
+    fn set_chain_name(&self, chain_name: &str, quota: Option<u64>) -> Result<R, E> {
         let value = [chain_name];
         self.contract_send_tx("setChainName", &value, quota, None)
     }
 
     /// Set operator
-    fn set_operator(&mut self, operator: &str, quota: Option<u64>) -> Result<R, E> {
+    fn set_operator(&self, operator: &str, quota: Option<u64>) -> Result<R, E> {
         let value = [operator];
         self.contract_send_tx("setOperator", &value, quota, None)
     }
 
     /// Set website
-    fn set_website(&mut self, website: &str, quota: Option<u64>) -> Result<R, E> {
+    fn set_website(&self, website: &str, quota: Option<u64>) -> Result<R, E> {
         let value = [website];
         self.contract_send_tx("setWebsite", &value, quota, None)
     }
 
     /// Set block interval
-    fn set_block_interval(&mut self, block_interval: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_block_interval(&self, block_interval: U256, quota: Option<u64>) -> Result<R, E> {
         let interval = block_interval.completed_lower_hex();
         let value = [interval.as_str()];
         self.contract_send_tx("setBlockInterval", &value, quota, None)
@@ -1032,7 +1027,7 @@ where
     }
 
     /// Set state
-    fn set_state(&mut self, state: bool, quota: Option<u64>) -> Result<R, E> {
+    fn set_state(&self, state: bool, quota: Option<u64>) -> Result<R, E> {
         let state = state.to_string();
         let value = [state.as_str()];
         self.contract_send_tx("setState", &value, quota, None)
@@ -1066,7 +1061,7 @@ where
     }
 
     /// Set quota price
-    fn set_price(&mut self, price: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_price(&self, price: U256, quota: Option<u64>) -> Result<R, E> {
         let price = price.completed_lower_hex();
         let value = [price.as_str()];
         self.contract_send_tx("setQuotaPrice", &value, quota, None)
@@ -1100,7 +1095,7 @@ where
     }
 
     /// Set version
-    fn set_version(&mut self, version: U256, quota: Option<u64>) -> Result<R, E> {
+    fn set_version(&self, version: U256, quota: Option<u64>) -> Result<R, E> {
         let version = version.completed_lower_hex();
         let value = [version.as_str()];
         self.contract_send_tx("setVersion", &value, quota, None)
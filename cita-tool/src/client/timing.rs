@@ -0,0 +1,123 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::client::LatencySummary;
+
+/// The upper bounds of the histogram buckets, in milliseconds, the last bucket has none
+const BUCKETS: [u64; 10] = [1, 2, 5, 10, 20, 50, 100, 200, 500, 1000];
+
+/// Record the round-trip time of every request the client posts
+///
+/// Every request of a batch and every retry is a round trip of its own, the delay of the
+/// rate limiter isn't part of it. The clones share the records, so the cloned clients
+/// are timed together.
+#[derive(Debug, Clone, Default)]
+pub struct RequestTimings {
+    /// The round-trip times, in milliseconds
+    latencies: Arc<Mutex<Vec<u64>>>,
+}
+
+impl RequestTimings {
+    /// No request recorded yet
+    pub fn new() -> Self {
+        RequestTimings::default()
+    }
+
+    /// A request was answered, or failed, after the duration
+    pub fn record(&self, elapsed: Duration) {
+        self.latencies
+            .lock()
+            .unwrap()
+            .push(elapsed.as_millis() as u64);
+    }
+
+    /// How many requests are recorded
+    pub fn count(&self) -> usize {
+        self.latencies.lock().unwrap().len()
+    }
+
+    /// The minimum, average, percentiles and maximum, None when there is no request
+    pub fn summary(&self) -> Option<LatencySummary> {
+        LatencySummary::from_latencies(&self.latencies.lock().unwrap())
+    }
+
+    /// The number of requests by the upper bound of their bucket, in milliseconds,
+    /// from the fastest bucket to the slowest one with requests, None is the bound of
+    /// the requests slower than a second
+    pub fn histogram(&self) -> Vec<(Option<u64>, usize)> {
+        let latencies = self.latencies.lock().unwrap();
+        let mut counts = vec![0; BUCKETS.len() + 1];
+        for latency in latencies.iter() {
+            let bucket = BUCKETS
+                .iter()
+                .position(|bound| latency <= bound)
+                .unwrap_or(BUCKETS.len());
+            counts[bucket] += 1;
+        }
+        let first = counts.iter().position(|count| *count > 0).unwrap_or(0);
+        let last = counts.iter().rposition(|count| *count > 0).unwrap_or(0);
+        counts
+            .into_iter()
+            .enumerate()
+            .skip(first)
+            .take(last + 1 - first)
+            .map(|(index, count)| (BUCKETS.get(index).copied(), count))
+            .collect()
+    }
+}
+
+impl fmt::Display for RequestTimings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let summary = match self.summary() {
+            Some(summary) => summary,
+            None => return write!(f, "No request sent"),
+        };
+        writeln!(
+            f,
+            "{} requests, min {}ms, avg {}ms, p95 {}ms, max {}ms",
+            self.count(),
+            summary.min,
+            summary.avg,
+            summary.p95,
+            summary.max
+        )?;
+        let histogram = self.histogram();
+        let widest = histogram.iter().map(|(_, count)| *count).max().unwrap_or(1);
+        for (index, (bound, count)) in histogram.iter().enumerate() {
+            let bound = match bound {
+                Some(bound) => format!("<= {}ms", bound),
+                None => format!("> {}ms", BUCKETS[BUCKETS.len() - 1]),
+            };
+            let bar = "#".repeat((count * 40).div_ceil(widest));
+            write!(f, "  {:>9} {:>6} {}", bound, count, bar)?;
+            if index + 1 < histogram.len() {
+                writeln!(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RequestTimings;
+    use std::time::Duration;
+
+    #[test]
+    fn test_request_timings() {
+        let timings = RequestTimings::new();
+        assert_eq!(timings.to_string(), "No request sent");
+        for millis in &[3, 4, 8, 15, 1500] {
+            timings.clone().record(Duration::from_millis(*millis));
+        }
+        assert_eq!(timings.count(), 5);
+        let summary = timings.summary().unwrap();
+        assert_eq!((summary.min, summary.max, summary.p95), (3, 1500, 1500));
+        let histogram = timings.histogram();
+        assert_eq!(histogram[0], (Some(5), 2));
+        assert_eq!(histogram[1], (Some(10), 1));
+        assert_eq!(histogram.last(), Some(&(None, 1)));
+        assert_eq!(histogram.len(), 9);
+    }
+}
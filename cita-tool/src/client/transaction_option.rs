@@ -1,4 +1,11 @@
+use hex::{decode, encode};
 use types::U256;
+use uuid::Uuid;
+
+use crate::client::remove_0x;
+use crate::error::ToolError;
+use crate::protos::{Transaction, TransactionVersion};
+use crate::LowerHex;
 
 /// Transaction parameter option
 #[derive(Clone, Copy, Debug)]
@@ -9,6 +16,7 @@ pub struct TransactionOptions<'a> {
     quota: Option<u64>,
     value: Option<U256>,
     version: Option<u32>,
+    nonce: Option<&'a str>,
 }
 
 impl<'a> TransactionOptions<'a> {
@@ -21,6 +29,7 @@ impl<'a> TransactionOptions<'a> {
             quota: None,
             value: None,
             version: None,
+            nonce: None,
         }
     }
 
@@ -93,6 +102,17 @@ impl<'a> TransactionOptions<'a> {
         self.version
     }
 
+    /// Set nonce, default is a random uuid
+    pub fn set_nonce(mut self, nonce: Option<&'a str>) -> Self {
+        self.nonce = nonce;
+        self
+    }
+
+    /// Get nonce
+    pub fn nonce(&self) -> Option<&str> {
+        self.nonce
+    }
+
     /// Restore initialization status
     pub fn clear(&mut self) {
         self.value = None;
@@ -100,7 +120,37 @@ impl<'a> TransactionOptions<'a> {
         self.current_height = None;
         self.address = "0x";
         self.code = "0x";
-        self.version = None
+        self.version = None;
+        self.nonce = None;
+    }
+
+    /// Build the transaction offline, the current height must be set
+    ///
+    /// The version defaults to 0, whose chain id must fit in `u32`.
+    pub fn build(&self, chain_id: U256) -> Result<Transaction, ToolError> {
+        let current_height = self
+            .current_height
+            .ok_or_else(|| ToolError::Customize("No height input".to_string()))?;
+
+        let mut tx = Transaction::new();
+        tx.set_data(decode(remove_0x(self.code)).map_err(ToolError::Decode)?);
+        tx.set_nonce(
+            self.nonce
+                .map(ToOwned::to_owned)
+                .unwrap_or_else(|| encode(Uuid::new_v4().as_bytes())),
+        );
+        tx.set_valid_until_block(current_height + 88);
+        tx.set_quota(self.quota.unwrap_or(10_000_000));
+        let value = self.value.unwrap_or_else(U256::zero).completed_lower_hex();
+        tx.set_value(decode(value).map_err(ToolError::Decode)?);
+
+        // Create a contract if the target address is empty
+        let version = TransactionVersion::new(self.version.unwrap_or(0))?;
+        version.set_to(&mut tx, self.address)?;
+        version.set_chain_id(&mut tx, chain_id)?;
+        tx.set_version(version.as_u32());
+
+        Ok(tx)
     }
 }
 
@@ -0,0 +1,105 @@
+use serde_json::{self, Value};
+
+use crate::client::alias::is_method_not_found;
+use crate::client::basic::{Client, ClientExt};
+use crate::client::remove_0x;
+use crate::error::ToolError;
+use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue, Quantity};
+
+const TXPOOL_STATUS: &str = "txpool_status";
+const TXPOOL_CONTENT: &str = "txpool_content";
+
+/// Where the pool was read from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PoolSource {
+    /// The pool RPCs of the node
+    Node,
+    /// The node has no pool RPC, the transactions sent in this session without a receipt
+    Local,
+}
+
+/// The pending transactions of the pool
+#[derive(Debug, Clone, Serialize)]
+pub struct TxPool {
+    /// Where the pool was read from
+    pub source: PoolSource,
+    /// How many transactions are pending, of the address when given
+    pub pending: u64,
+    /// The pending transactions, as the node or the pending store tells them
+    pub transactions: Vec<Value>,
+}
+
+impl Client {
+    /// txpool_status: The counts of the transactions in the pool, if the node has the RPC
+    pub fn get_txpool_status(&self) -> Result<JsonRpcResponse, ToolError> {
+        let params = JsonRpcParams::new()
+            .insert("method", ParamsValue::String(String::from(TXPOOL_STATUS)))
+            .insert("params", ParamsValue::List(Vec::new()));
+        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+    }
+
+    /// txpool_content: The transactions in the pool, of the address when given,
+    /// if the node has the RPC
+    pub fn get_txpool_content(&self, address: Option<&str>) -> Result<JsonRpcResponse, ToolError> {
+        let args = address
+            .map(|address| vec![ParamsValue::String(address.to_owned())])
+            .unwrap_or_default();
+        let params = JsonRpcParams::new()
+            .insert("method", ParamsValue::String(String::from(TXPOOL_CONTENT)))
+            .insert("params", ParamsValue::List(args));
+        Ok(self.send_request(vec![params].into_iter())?.pop().unwrap())
+    }
+
+    /// The pending transactions, of the address when given
+    ///
+    /// The pool RPCs of the node are used when it has them, otherwise the pool is emulated
+    /// by the transactions sent in this session, which have no receipt and aren't expired.
+    pub fn txpool(&self, address: Option<&str>) -> Result<TxPool, ToolError> {
+        let content = self.get_txpool_content(address)?;
+        if !is_method_not_found(&content) {
+            let transactions = match pool_result(content)? {
+                Value::Array(transactions) => transactions,
+                Value::Null => Vec::new(),
+                content => vec![content],
+            };
+            let status = pool_result(self.get_txpool_status()?)?;
+            let pending = match Quantity::from_json(&status["pending"]) {
+                Some(pending) if address.is_none() => pending.as_u64()?,
+                _ => transactions.len() as u64,
+            };
+            return Ok(TxPool {
+                source: PoolSource::Node,
+                pending,
+                transactions,
+            });
+        }
+
+        let height = self.get_current_height()?;
+        let mut transactions = Vec::new();
+        for sent in self.pending().list() {
+            let from_address = address.is_none_or(|address| {
+                remove_0x(&sent.sender).eq_ignore_ascii_case(remove_0x(address))
+            });
+            if !from_address || sent.valid_until_block < height {
+                continue;
+            }
+            let receipt = self.get_transaction_receipt(&sent.hash)?;
+            if receipt.error().is_none() && pool_result(receipt)?.is_null() {
+                transactions.push(serde_json::to_value(sent).map_err(ToolError::SerdeJson)?);
+            }
+        }
+        Ok(TxPool {
+            source: PoolSource::Local,
+            pending: transactions.len() as u64,
+            transactions,
+        })
+    }
+}
+
+fn pool_result(response: JsonRpcResponse) -> Result<Value, ToolError> {
+    if let Some(err) = response.error() {
+        return Err(ToolError::Customize(err.message()));
+    }
+    serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)
+}
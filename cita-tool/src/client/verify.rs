@@ -0,0 +1,189 @@
+use hex;
+use serde_json::Value;
+use types::H256;
+
+use crate::client::basic::{Client, ClientExt};
+use crate::client::light::{parse_h256, result_value, HeaderFields};
+use crate::client::remove_0x;
+use crate::crypto::{Encryption, Hashable};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+/// The result of checking a block against the hashes computed locally
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlockCheck {
+    /// Block height
+    pub height: u64,
+    /// Block hash returned by the node
+    pub hash: String,
+    /// Hash of the previous block in the header
+    pub prev_hash: String,
+    /// What doesn't match, empty when the block is consistent
+    pub mismatches: Vec<String>,
+}
+
+impl BlockCheck {
+    /// Whether every hash of the block matches
+    pub fn is_valid(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+impl Client {
+    /// Check the block hash is the hash of the encoded header, the roots of the header are
+    /// the hashed ones, the transaction hashes are the hashes of their contents, and the
+    /// transactions root is the merkle root of them
+    ///
+    /// It never trusts the node, a buggy or malicious gateway shows up in the mismatches.
+    pub fn verify_block(
+        &self,
+        height: u64,
+        encryption: Encryption,
+    ) -> Result<BlockCheck, ToolError> {
+        let height_hex = format!("{:#x}", height);
+        let block = result_value(self.get_block_by_number(&height_hex, true)?)?;
+        let encoded = result_value(self.get_block_header(&height_hex)?)?;
+        let encoded = hex::decode(remove_0x(encoded.as_str().unwrap_or_default()))
+            .map_err(ToolError::Decode)?;
+        let header = &block["header"];
+        let mut mismatches = Vec::new();
+
+        if Some(encoded.crypt_hash(encryption)) != parse_h256(&block["hash"]) {
+            mismatches.push("The block hash is not the hash of the header".to_string());
+        }
+        match HeaderFields::decode(&encoded) {
+            Some(fields) => {
+                for name in HeaderFields::ROOTS.iter() {
+                    if parse_h256(&header[*name]) != fields.root(name) {
+                        mismatches.push(format!("The {} is not the one in the header", name));
+                    }
+                }
+            }
+            None => mismatches.push("The header is not a valid encoded header".to_string()),
+        }
+
+        let transactions = block["body"]["transactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        let mut hashes = Vec::with_capacity(transactions.len());
+        for (index, transaction) in transactions.iter().enumerate() {
+            let (hash, content) = match transaction {
+                // A node may only return the hashes
+                Value::String(_) => (parse_h256(transaction), None),
+                _ => (
+                    parse_h256(&transaction["hash"]),
+                    transaction["content"].as_str(),
+                ),
+            };
+            let computed = match content {
+                Some(content) => {
+                    let content = hex::decode(remove_0x(content)).map_err(ToolError::Decode)?;
+                    Some(content.crypt_hash(encryption))
+                }
+                None => hash,
+            };
+            if computed.is_none() || computed != hash {
+                mismatches.push(format!(
+                    "The hash of transaction {} is not the hash of its content",
+                    index
+                ));
+            }
+            hashes.push(computed.unwrap_or_default());
+        }
+        let root = merkle_root(&hashes, encryption);
+        if Some(root) != parse_h256(&header["transactionsRoot"]) {
+            mismatches.push(format!(
+                "The transactionsRoot is not {}, the root of the transactions",
+                root.completed_lower_hex_with_0x()
+            ));
+        }
+
+        Ok(BlockCheck {
+            height,
+            hash: block["hash"].as_str().unwrap_or_default().to_owned(),
+            prev_hash: header["prevHash"].as_str().unwrap_or_default().to_owned(),
+            mismatches,
+        })
+    }
+
+    /// Verify the blocks of the range, and that every block links to the previous one
+    pub fn verify_blocks(
+        &self,
+        from: u64,
+        to: u64,
+        encryption: Encryption,
+    ) -> Result<Vec<BlockCheck>, ToolError> {
+        let mut checks: Vec<BlockCheck> = Vec::new();
+        for height in from..=to {
+            let mut check = self.verify_block(height, encryption)?;
+            if let Some(previous) = checks.last() {
+                if !previous.hash.eq_ignore_ascii_case(&check.prev_hash) {
+                    check.mismatches.push(format!(
+                        "The prevHash is not the hash of block {}",
+                        previous.height
+                    ));
+                }
+            }
+            checks.push(check);
+        }
+        Ok(checks)
+    }
+}
+
+/// The root of the complete binary merkle tree of the hashes, as the transactions root
+/// of CITA, the hash of empty RLP when there is no hash
+pub fn merkle_root(hashes: &[H256], encryption: Encryption) -> H256 {
+    let count = hashes.len();
+    match count {
+        0 => [0x80u8].crypt_hash(encryption),
+        1 => hashes[0],
+        _ => {
+            // The leaves are the last nodes, node `i` merges the nodes `2i + 1` and `2i + 2`
+            let mut nodes = vec![H256::zero(); 2 * count - 1];
+            nodes[count - 1..].copy_from_slice(hashes);
+            for index in (0..count - 1).rev() {
+                let mut data = [0u8; 64];
+                data[..32].copy_from_slice(&nodes[2 * index + 1]);
+                data[32..].copy_from_slice(&nodes[2 * index + 2]);
+                nodes[index] = data.crypt_hash(encryption);
+            }
+            nodes[0]
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::merkle_root;
+    use crate::crypto::{Encryption, Hashable};
+    use crate::LowerHex;
+    use types::H256;
+
+    #[test]
+    fn test_merkle_root() {
+        let encryption = Encryption::Secp256k1;
+        assert_eq!(
+            merkle_root(&[], encryption).completed_lower_hex(),
+            "56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421"
+        );
+        let leaves = (1..=3u8)
+            .map(|byte| [byte].crypt_hash(encryption))
+            .collect::<Vec<H256>>();
+        let merge = |left: &H256, right: &H256| {
+            let mut data = left.to_vec();
+            data.extend_from_slice(right);
+            data.crypt_hash(encryption)
+        };
+        assert_eq!(merkle_root(&leaves[..1], encryption), leaves[0]);
+        assert_eq!(
+            merkle_root(&leaves[..2], encryption),
+            merge(&leaves[0], &leaves[1])
+        );
+        assert_eq!(
+            merkle_root(&leaves, encryption),
+            merge(&merge(&leaves[1], &leaves[2]), &leaves[0])
+        );
+    }
+}
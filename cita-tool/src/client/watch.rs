@@ -0,0 +1,366 @@
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+
+use serde_json::{self, Value};
+
+use crate::client::basic::{Client, ClientExt};
+use crate::error::ToolError;
+
+/// Poll the logs of newly produced blocks
+///
+/// Every call of `poll` queries the block range between the last polled height
+/// and the current height, so no log will be returned twice.
+pub struct LogWatcher {
+    client: Client,
+    topics: Option<Vec<String>>,
+    addresses: Option<Vec<String>>,
+    next_height: Option<u64>,
+}
+
+impl LogWatcher {
+    /// Create a log watcher, start from the current height by default
+    pub fn new(client: Client) -> Self {
+        LogWatcher {
+            client,
+            topics: None,
+            addresses: None,
+            next_height: None,
+        }
+    }
+
+    /// Only watch the logs matching the topics
+    pub fn set_topics(mut self, topics: Option<Vec<String>>) -> Self {
+        self.topics = topics;
+        self
+    }
+
+    /// Only watch the logs emitted by the addresses
+    pub fn set_addresses(mut self, addresses: Option<Vec<String>>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    /// Start watching from the given height
+    pub fn set_from(mut self, height: Option<u64>) -> Self {
+        self.next_height = height;
+        self
+    }
+
+    /// The height of the first block that has not been polled yet
+    pub fn next_height(&self) -> Option<u64> {
+        self.next_height
+    }
+
+    /// Get the logs of the blocks produced since the last poll
+    pub fn poll(&mut self) -> Result<Vec<Value>, ToolError> {
+        let current = self.client.get_current_height()?;
+        let range = unpolled(self.next_height, current);
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let topics = self
+            .topics
+            .as_ref()
+            .map(|topics| topics.iter().map(String::as_str).collect());
+        let addresses = self
+            .addresses
+            .as_ref()
+            .map(|addresses| addresses.iter().map(String::as_str).collect());
+        let response = self.client.get_logs(
+            topics,
+            addresses,
+            Some(&format!("{:#x}", range.start())),
+            Some(&format!("{:#x}", range.end())),
+        )?;
+        if let Some(err) = response.error() {
+            return Err(ToolError::Customize(err.message()));
+        }
+
+        let logs = match serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)? {
+            Value::Array(logs) => logs,
+            Value::Null => Vec::new(),
+            other => {
+                return Err(ToolError::Customize(format!(
+                    "Unexpected getLogs result: {}",
+                    other
+                )));
+            }
+        };
+        self.next_height = Some(current + 1);
+        Ok(logs)
+    }
+}
+
+/// The heights a poll queries: from the first one not polled, or the current one at first,
+/// up to the current one
+fn unpolled(next_height: Option<u64>, current: u64) -> RangeInclusive<u64> {
+    next_height.unwrap_or(current)..=current
+}
+
+/// Event produced by the block watcher
+#[derive(Debug, Clone)]
+pub enum BlockEvent {
+    /// A block joined the chain, blocks replaced by a reorg are reported again
+    NewBlock {
+        /// Block height
+        height: u64,
+        /// Block hash
+        hash: String,
+        /// The whole block
+        block: Value,
+    },
+    /// The recently seen blocks starting from `height` were replaced
+    Reorg {
+        /// The lowest height whose block changed
+        height: u64,
+        /// How many seen blocks were replaced
+        depth: u64,
+        /// The fork point is below the window, the blocks under `height` may be replaced too
+        below_window: bool,
+    },
+}
+
+/// Poll new blocks and detect chain reorganization
+///
+/// The watcher keeps the hashes of the recent blocks, when the parent hash of
+/// a new block doesn't match the one it has seen, it walks back until the
+/// chain agrees again.
+pub struct BlockWatcher {
+    client: Client,
+    window: RecentBlocks,
+    next_height: Option<u64>,
+}
+
+impl BlockWatcher {
+    /// Create a block watcher, start from the current height and remember 64 blocks by default
+    pub fn new(client: Client) -> Self {
+        BlockWatcher {
+            client,
+            window: RecentBlocks::new(64),
+            next_height: None,
+        }
+    }
+
+    /// Start watching from the given height
+    pub fn set_from(mut self, height: Option<u64>) -> Self {
+        self.next_height = height;
+        self
+    }
+
+    /// Set how many recent block hashes are kept, which limits the detectable reorg depth
+    pub fn set_window_size(mut self, size: usize) -> Self {
+        self.window = RecentBlocks::new(size);
+        self
+    }
+
+    /// The height of the first block that has not been polled yet
+    pub fn next_height(&self) -> Option<u64> {
+        self.next_height
+    }
+
+    /// Get the blocks produced since the last poll
+    pub fn poll(&mut self) -> Result<Vec<BlockEvent>, ToolError> {
+        let current = self.client.get_current_height()?;
+        let mut events = Vec::new();
+
+        for height in unpolled(self.next_height, current) {
+            let client = &self.client;
+            events.extend(follow(&mut self.window, height, |height| {
+                fetch_block(client, height)
+            })?);
+            self.next_height = Some(height + 1);
+        }
+        Ok(events)
+    }
+}
+
+/// The hashes of the recent blocks by their heights, the oldest dropped past the size
+struct RecentBlocks {
+    blocks: VecDeque<(u64, String)>,
+    size: usize,
+}
+
+impl RecentBlocks {
+    fn new(size: usize) -> Self {
+        RecentBlocks {
+            blocks: VecDeque::new(),
+            size: size.max(1),
+        }
+    }
+
+    fn seen(&self, height: u64) -> Option<&str> {
+        self.blocks
+            .iter()
+            .find(|(h, _)| *h == height)
+            .map(|(_, hash)| hash.as_str())
+    }
+
+    fn remember(&mut self, height: u64, hash: String) {
+        match self.blocks.iter_mut().find(|(h, _)| *h == height) {
+            Some(entry) => entry.1 = hash,
+            None => {
+                self.blocks.push_back((height, hash));
+                if self.blocks.len() > self.size {
+                    self.blocks.pop_front();
+                }
+            }
+        }
+    }
+}
+
+/// The events of the block of the height joining the chain of the window
+///
+/// When its parent isn't the block seen, the canonical blocks are fetched again from the
+/// parent down to the first one whose parent is seen, or the lowest one of the window, and
+/// reported after a `Reorg`. `fetch` is the hash, the parent hash and the block of a height.
+fn follow<F>(
+    window: &mut RecentBlocks,
+    height: u64,
+    mut fetch: F,
+) -> Result<Vec<BlockEvent>, ToolError>
+where
+    F: FnMut(u64) -> Result<(String, String, Value), ToolError>,
+{
+    let mut events = Vec::new();
+    let (hash, prev_hash, block) = fetch(height)?;
+    let parent = height.checked_sub(1).and_then(|parent| window.seen(parent));
+    if parent.is_some() && parent != Some(prev_hash.as_str()) {
+        let mut replaced = Vec::new();
+        let mut fork = height - 1;
+        let below_window = loop {
+            let (hash, prev_hash, block) = fetch(fork)?;
+            replaced.push(BlockEvent::NewBlock {
+                height: fork,
+                hash: hash.clone(),
+                block,
+            });
+            window.remember(fork, hash);
+            match fork.checked_sub(1).map(|parent| window.seen(parent)) {
+                Some(Some(seen)) if seen != prev_hash => fork -= 1,
+                Some(Some(_)) | None => break false,
+                Some(None) => break true,
+            }
+        };
+        events.push(BlockEvent::Reorg {
+            height: fork,
+            depth: replaced.len() as u64,
+            below_window,
+        });
+        events.extend(replaced.into_iter().rev());
+    }
+    window.remember(height, hash.clone());
+    events.push(BlockEvent::NewBlock {
+        height,
+        hash,
+        block,
+    });
+    Ok(events)
+}
+
+fn fetch_block(client: &Client, height: u64) -> Result<(String, String, Value), ToolError> {
+    let response = client.get_block_by_number(&format!("{:#x}", height), false)?;
+    if let Some(err) = response.error() {
+        return Err(ToolError::Customize(err.message()));
+    }
+    let block = serde_json::to_value(response.result()).map_err(ToolError::SerdeJson)?;
+    match (block["hash"].as_str(), block["header"]["prevHash"].as_str()) {
+        (Some(hash), Some(prev_hash)) => Ok((hash.to_owned(), prev_hash.to_owned(), block)),
+        _ => Err(ToolError::Customize(format!(
+            "Block {} is not available",
+            height
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{follow, unpolled, BlockEvent, RecentBlocks};
+    use crate::error::ToolError;
+    use serde_json::Value;
+
+    /// The hash of the block of the height on the branch
+    fn hash(branch: char, height: u64) -> String {
+        format!("{}{}", branch, height)
+    }
+
+    /// Follow the blocks of the heights of the chain, the branch of a height is `branch`
+    fn follow_chain<B>(window: &mut RecentBlocks, heights: &[u64], branch: B) -> Vec<BlockEvent>
+    where
+        B: Fn(u64) -> char,
+    {
+        let mut events = Vec::new();
+        for height in heights {
+            let fetch = |height: u64| -> Result<(String, String, Value), ToolError> {
+                let parent = height.saturating_sub(1);
+                Ok((
+                    hash(branch(height), height),
+                    hash(branch(parent), parent),
+                    Value::Null,
+                ))
+            };
+            events.extend(follow(window, *height, fetch).unwrap());
+        }
+        events
+    }
+
+    fn summary(events: &[BlockEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                BlockEvent::NewBlock { hash, .. } => hash.clone(),
+                BlockEvent::Reorg {
+                    height,
+                    depth,
+                    below_window,
+                } => format!("reorg {} {} {}", height, depth, below_window),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unpolled() {
+        assert_eq!(unpolled(None, 7), 7..=7);
+        assert_eq!(unpolled(Some(3), 7), 3..=7);
+        assert!(unpolled(Some(8), 7).is_empty());
+    }
+
+    #[test]
+    fn test_reorg_within_window() {
+        let mut window = RecentBlocks::new(4);
+        follow_chain(&mut window, &[1, 2, 3, 4, 5], |_| 'a');
+        // The blocks from 4 are replaced by those of the branch b
+        let events = follow_chain(
+            &mut window,
+            &[6],
+            |height| if height < 4 { 'a' } else { 'b' },
+        );
+        assert_eq!(summary(&events), vec!["reorg 4 2 false", "b4", "b5", "b6"]);
+        assert_eq!(window.seen(5), Some("b5"));
+        assert_eq!(window.seen(3), Some("a3"));
+    }
+
+    #[test]
+    fn test_reorg_deeper_than_window() {
+        let mut window = RecentBlocks::new(3);
+        follow_chain(&mut window, &[1, 2, 3, 4, 5], |_| 'a');
+        // The window keeps 3 to 5, the fork at 2 is below it
+        let events = follow_chain(
+            &mut window,
+            &[6],
+            |height| if height < 2 { 'a' } else { 'b' },
+        );
+        assert_eq!(
+            summary(&events),
+            vec!["reorg 3 3 true", "b3", "b4", "b5", "b6"]
+        );
+    }
+
+    #[test]
+    fn test_no_reorg() {
+        let mut window = RecentBlocks::new(2);
+        let events = follow_chain(&mut window, &[7, 8, 9], |_| 'a');
+        assert_eq!(summary(&events), vec!["a7", "a8", "a9"]);
+        assert_eq!(window.seen(7), None);
+    }
+}
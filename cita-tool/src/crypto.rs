@@ -2,6 +2,12 @@ mod cita_ed25519;
 mod cita_secp256k1;
 mod cita_sm2;
 mod crypto_trait;
+mod keystore;
+#[cfg(feature = "ledger")]
+mod ledger;
+#[cfg(all(feature = "pkcs11", unix))]
+mod pkcs11;
+mod signer;
 
 use hex::encode;
 use std::fmt;
@@ -11,6 +17,14 @@ pub use self::cita_ed25519::{ed25519_sign, Ed25519KeyPair, Ed25519Signature};
 pub use self::cita_secp256k1::{secp256k1_sign, Secp256k1KeyPair, Secp256k1Signature};
 pub use self::cita_sm2::{sm2_sign, Sm2KeyPair, Sm2Signature};
 pub use self::crypto_trait::{CreateKey, Error, Hashable};
+pub use self::keystore::EncryptedData;
+#[cfg(feature = "ledger")]
+pub use self::ledger::{
+    ledger_devices, DerivationPath, LedgerDevice, LedgerSigner, DEFAULT_DERIVATION_PATH,
+};
+#[cfg(all(feature = "pkcs11", unix))]
+pub use self::pkcs11::{HsmKey, HsmSlot, Pkcs11Module, Pkcs11Signer};
+pub use self::signer::Signer;
 use crate::LowerHex;
 use types::{Address, H256, H512};
 
@@ -50,8 +64,43 @@ pub fn sign(privkey: &PrivateKey, message: &Message) -> Signature {
     }
 }
 
+/// Domain-separating prefix of signed messages, so that a message signature is never a valid
+/// transaction signature
+pub const MESSAGE_PREFIX: &str = "\x19CITA Signed Message:\n";
+
+/// Hash arbitrary data as a message: `hash(MESSAGE_PREFIX + len(data) + data)`
+pub fn hash_message(data: &[u8], encryption: Encryption) -> Message {
+    let mut content = format!("{}{}", MESSAGE_PREFIX, data.len()).into_bytes();
+    content.extend_from_slice(data);
+    content.crypt_hash(encryption)
+}
+
+/// Sign arbitrary data as a message
+pub fn sign_message(privkey: &PrivateKey, data: &[u8]) -> Signature {
+    let encryption = match privkey {
+        PrivateKey::Secp256k1(_) => Encryption::Secp256k1,
+        PrivateKey::Ed25519(_) => Encryption::Ed25519,
+        PrivateKey::Sm2(_) => Encryption::Sm2,
+        PrivateKey::Null => return Signature::Null,
+    };
+    sign(privkey, &hash_message(data, encryption))
+}
+
+/// Recover the signer address of a message signature
+pub fn recover_message(signature: &Signature, data: &[u8]) -> Result<Address, String> {
+    let encryption = match signature {
+        Signature::Secp256k1(_) => Encryption::Secp256k1,
+        Signature::Ed25519(_) => Encryption::Ed25519,
+        Signature::Sm2(_) => Encryption::Sm2,
+        Signature::Null => return Err("Invalid signature length".to_string()),
+    };
+    signature
+        .recover(&hash_message(data, encryption))
+        .map(|pubkey| pubkey_to_address(&pubkey))
+}
+
 /// Encryption enum
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum Encryption {
     /// Secp256k1
     Secp256k1,
@@ -129,9 +178,9 @@ impl PrivateKey {
 impl fmt::Debug for PrivateKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
-            PrivateKey::Secp256k1(private_key) => encode(private_key.to_vec()),
-            PrivateKey::Ed25519(private_key) => encode(private_key.to_vec()),
-            PrivateKey::Sm2(private_key) => encode(private_key.to_vec()),
+            PrivateKey::Secp256k1(private_key) => encode(private_key),
+            PrivateKey::Ed25519(private_key) => encode(private_key),
+            PrivateKey::Sm2(private_key) => encode(private_key),
             PrivateKey::Null => "".to_string(),
         };
         write!(f, "{}", msg)
@@ -141,9 +190,9 @@ impl fmt::Debug for PrivateKey {
 impl fmt::Display for PrivateKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
-            PrivateKey::Secp256k1(private_key) => encode(private_key.to_vec()),
-            PrivateKey::Ed25519(private_key) => encode(private_key.to_vec()),
-            PrivateKey::Sm2(private_key) => encode(private_key.to_vec()),
+            PrivateKey::Secp256k1(private_key) => encode(private_key),
+            PrivateKey::Ed25519(private_key) => encode(private_key),
+            PrivateKey::Sm2(private_key) => encode(private_key),
             PrivateKey::Null => "".to_string(),
         };
         write!(f, "{}", msg)
@@ -191,9 +240,9 @@ impl PubKey {
 impl fmt::Display for PubKey {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let msg = match *self {
-            PubKey::Secp256k1(pubkey) => encode(pubkey.to_vec()),
-            PubKey::Ed25519(pubkey) => encode(pubkey.to_vec()),
-            PubKey::Sm2(pubkey) => encode(pubkey.to_vec()),
+            PubKey::Secp256k1(pubkey) => encode(pubkey),
+            PubKey::Ed25519(pubkey) => encode(pubkey),
+            PubKey::Sm2(pubkey) => encode(pubkey),
             PubKey::Null => "".to_string(),
         };
         write!(f, "{}", msg)
@@ -342,13 +391,13 @@ impl Signature {
     pub fn verify_public(&self, pubkey: PubKey, message: &Message) -> Result<bool, String> {
         match (self, pubkey) {
             (Signature::Secp256k1(sig), PubKey::Secp256k1(pubkey)) => sig
-                .verify_public(&pubkey, &message)
+                .verify_public(&pubkey, message)
                 .map_err(|e| e.to_string()),
             (Signature::Ed25519(sig), PubKey::Ed25519(pubkey)) => sig
-                .verify_public(&pubkey, &message)
+                .verify_public(&pubkey, message)
                 .map_err(|e| e.to_string()),
             (Signature::Sm2(sig), PubKey::Sm2(pubkey)) => sig
-                .verify_public(&pubkey, &message)
+                .verify_public(&pubkey, message)
                 .map_err(|e| e.to_string()),
             (_, _) => Ok(false),
         }
@@ -358,9 +407,9 @@ impl Signature {
 impl fmt::Display for Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         match self {
-            Signature::Secp256k1(sig) => write!(f, "{}", encode(sig.0[..].to_vec())),
-            Signature::Ed25519(sig) => write!(f, "{}", encode(sig.0[..].to_vec())),
-            Signature::Sm2(sig) => write!(f, "{}", encode(sig.0[..].to_vec())),
+            Signature::Secp256k1(sig) => write!(f, "{}", encode(&sig.0[..])),
+            Signature::Ed25519(sig) => write!(f, "{}", encode(&sig.0[..])),
+            Signature::Sm2(sig) => write!(f, "{}", encode(&sig.0[..])),
             Signature::Null => write!(f, "null"),
         }
     }
@@ -368,7 +417,7 @@ impl fmt::Display for Signature {
 
 #[cfg(test)]
 mod test {
-    use super::{Encryption, KeyPair};
+    use super::{recover_message, sign_message, Encryption, KeyPair};
 
     #[test]
     fn secp256k1_generate_from_private_key() {
@@ -425,4 +474,20 @@ mod test {
             "5ae200f77d5c7df715f6ccb182fc5073dab1cfe9"
         );
     }
+
+    #[test]
+    fn test_sign_message() {
+        for encryption in [Encryption::Secp256k1, Encryption::Ed25519, Encryption::Sm2].iter() {
+            let key_pair = KeyPair::new(*encryption);
+            let signature = sign_message(&key_pair.privkey(), b"hello");
+            assert_eq!(
+                recover_message(&signature, b"hello").unwrap(),
+                key_pair.address()
+            );
+            assert_ne!(
+                recover_message(&signature, b"hello!").ok(),
+                Some(key_pair.address())
+            );
+        }
+    }
 }
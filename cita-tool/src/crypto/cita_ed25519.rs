@@ -22,9 +22,9 @@ pub struct Ed25519KeyPair {
 
 impl fmt::Display for Ed25519KeyPair {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        writeln!(f, "privkey:  {}", encode(self.privkey.0.to_vec()))?;
-        writeln!(f, "pubkey:  {}", encode(self.pubkey.0.to_vec()))?;
-        write!(f, "address:  {}", encode(self.address().0.to_vec()))
+        writeln!(f, "privkey:  {}", encode(self.privkey.0))?;
+        writeln!(f, "pubkey:  {}", encode(self.pubkey.0))?;
+        write!(f, "address:  {}", encode(self.address().0))
     }
 }
 
@@ -82,9 +82,10 @@ impl Ed25519Signature {
     pub fn recover(&self, message: &Message) -> Result<Ed25519PubKey, Error> {
         let sig = self.sig();
         let pubkey = self.pk();
-        EdPublicKey::from_bytes(&pubkey)
-            .unwrap()
-            .verify::<Sha512>(message, &EdSignature::from_bytes(&sig).unwrap())
+        let signature = EdSignature::from_bytes(sig).map_err(|_| Error::InvalidSignature)?;
+        EdPublicKey::from_bytes(pubkey)
+            .map_err(|_| Error::InvalidPubKey)?
+            .verify::<Sha512>(message, &signature)
             .map_err(|_| Error::InvalidSignature)
             .map(|_| Ed25519PubKey::from(pubkey))
     }
@@ -97,9 +98,10 @@ impl Ed25519Signature {
             return Err(Error::InvalidPubKey);
         }
 
-        EdPublicKey::from_bytes(&pubkey)
-            .unwrap()
-            .verify::<Sha512>(message.as_ref(), &EdSignature::from_bytes(&sig).unwrap())
+        let signature = EdSignature::from_bytes(sig).map_err(|_| Error::InvalidSignature)?;
+        EdPublicKey::from_bytes(pubkey)
+            .map_err(|_| Error::InvalidPubKey)?
+            .verify::<Sha512>(message.as_ref(), &signature)
             .map_err(|_| Error::InvalidSignature)
             .map(|_| true)
     }
@@ -135,14 +137,14 @@ impl<'a> From<&'a [u8]> for Ed25519Signature {
     fn from(slice: &'a [u8]) -> Ed25519Signature {
         assert_eq!(slice.len(), SIGNATURE_BYTES_LEN);
         let mut bytes = [0u8; 96];
-        bytes.copy_from_slice(&slice[..]);
+        bytes.copy_from_slice(slice);
         Ed25519Signature(bytes)
     }
 }
 
 impl fmt::Display for Ed25519Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{}", encode(self.0[..].to_vec()))
+        write!(f, "{}", encode(&self.0[..]))
     }
 }
 
@@ -173,4 +175,16 @@ mod tests {
         let sig = ed25519_sign(keypair.privkey(), &msg).unwrap();
         assert_eq!(keypair.pubkey(), &sig.recover(&msg).unwrap());
     }
+
+    #[test]
+    fn test_malformed_signature() {
+        let keypair = Ed25519KeyPair::gen_keypair();
+        let msg = Message::default();
+        let mut malformed = ed25519_sign(keypair.privkey(), &msg).unwrap().0;
+        malformed[63] = 0xff;
+        let malformed = Ed25519Signature(malformed);
+        // Errors, not panics
+        assert!(malformed.recover(&msg).is_err());
+        assert!(malformed.verify_public(keypair.pubkey(), &msg).is_err());
+    }
 }
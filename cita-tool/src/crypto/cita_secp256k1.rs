@@ -29,9 +29,9 @@ pub struct Secp256k1KeyPair {
 
 impl fmt::Display for Secp256k1KeyPair {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        writeln!(f, "privkey:  {}", encode(self.privkey.0.to_vec()))?;
-        writeln!(f, "pubkey:  {}", encode(self.pubkey.0.to_vec()))?;
-        write!(f, "address:  {}", encode(self.address().0.to_vec()))
+        writeln!(f, "privkey:  {}", encode(self.privkey.0))?;
+        writeln!(f, "pubkey:  {}", encode(self.pubkey.0))?;
+        write!(f, "address:  {}", encode(self.address().0))
     }
 }
 
@@ -216,9 +216,9 @@ impl Eq for Secp256k1Signature {}
 impl fmt::Debug for Secp256k1Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         f.debug_struct("Signature")
-            .field("r", &encode(self.0[0..32].to_vec()))
-            .field("s", &encode(self.0[32..64].to_vec()))
-            .field("v", &encode(self.0[64..65].to_vec()))
+            .field("r", &encode(&self.0[0..32]))
+            .field("s", &encode(&self.0[32..64]))
+            .field("v", &encode(&self.0[64..65]))
             .finish()
     }
 }
@@ -239,7 +239,7 @@ impl<'a> From<&'a [u8]> for Secp256k1Signature {
     fn from(slice: &'a [u8]) -> Secp256k1Signature {
         assert_eq!(slice.len(), SIGNATURE_BYTES_LEN);
         let mut bytes = [0u8; 65];
-        bytes.copy_from_slice(&slice[..]);
+        bytes.copy_from_slice(slice);
         Secp256k1Signature(bytes)
     }
 }
@@ -16,9 +16,9 @@ pub struct Sm2KeyPair {
 
 impl fmt::Display for Sm2KeyPair {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        writeln!(f, "privkey:  {}", encode(self.privkey.0.to_vec()))?;
-        writeln!(f, "pubkey:  {}", encode(self.pubkey.0.to_vec()))?;
-        write!(f, "address:  {}", encode(self.address().0.to_vec()))
+        writeln!(f, "privkey:  {}", encode(self.privkey.0))?;
+        writeln!(f, "pubkey:  {}", encode(self.pubkey.0))?;
+        write!(f, "address:  {}", encode(self.address().0))
     }
 }
 
@@ -88,7 +88,7 @@ impl Sm2Signature {
         ctx.load_pubkey(&pk_full[..])
             .map_err(|_| Error::RecoverError)
             .and_then(|pk| {
-                if ctx.verify(&message, &pk, &sig) {
+                if ctx.verify(message, &pk, &sig) {
                     Ok(Sm2Pubkey::from(self.pk()))
                 } else {
                     Err(Error::RecoverError)
@@ -107,7 +107,7 @@ impl Sm2Signature {
             pk_full[1..].copy_from_slice(self.pk());
             ctx.load_pubkey(&pk_full[..])
                 .map_err(|_| Error::RecoverError)
-                .map(|pk| ctx.verify(&message, &pk, &sig))
+                .map(|pk| ctx.verify(message, &pk, &sig))
         } else {
             Ok(false)
         }
@@ -121,7 +121,7 @@ pub fn sm2_sign(privkey: &Sm2Privkey, message: &Message) -> Result<Sm2Signature,
         .map_err(|_| Error::RecoverError)
         .map(|sk| {
             let pk = ctx.pk_from_sk(&sk);
-            let signature = ctx.sign(&message, &sk, &pk);
+            let signature = ctx.sign(message, &sk, &pk);
             let mut sig_bytes = [0u8; SIGNATURE_BYTES_LEN];
             let r_bytes = signature.get_r().to_bytes_be();
             let s_bytes = signature.get_s().to_bytes_be();
@@ -135,16 +135,16 @@ pub fn sm2_sign(privkey: &Sm2Privkey, message: &Message) -> Result<Sm2Signature,
 impl fmt::Debug for Sm2Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
         f.debug_struct("Signature")
-            .field("r", &encode(&self.r()))
-            .field("s", &encode(&self.s()))
-            .field("pk", &encode(&self.pk()))
+            .field("r", &encode(self.r()))
+            .field("s", &encode(self.s()))
+            .field("pk", &encode(self.pk()))
             .finish()
     }
 }
 
 impl fmt::Display for Sm2Signature {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(f, "{}", encode(self.0[..].to_vec()))
+        write!(f, "{}", encode(&self.0[..]))
     }
 }
 
@@ -164,7 +164,7 @@ impl<'a> From<&'a [u8]> for Sm2Signature {
     fn from(slice: &'a [u8]) -> Sm2Signature {
         assert_eq!(slice.len(), SIGNATURE_BYTES_LEN);
         let mut bytes = [0u8; SIGNATURE_BYTES_LEN];
-        bytes.copy_from_slice(&slice[..]);
+        bytes.copy_from_slice(slice);
         Sm2Signature(bytes)
     }
 }
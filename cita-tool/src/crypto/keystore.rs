@@ -0,0 +1,171 @@
+use hex::{decode, encode};
+use hmac::{Hmac, Mac};
+use libsm::sm4::{Cipher, Mode};
+use rand::{thread_rng, RngCore};
+use scrypt::{scrypt, ScryptParams};
+use sha2::Sha256;
+/// The scrypt cost of the new data, `N = 2^15`, 32 MiB, and the lowest one decrypted
+const MIN_LOG_N: u8 = 15;
+/// The highest scrypt cost decrypted, 128 MiB, so a forged file can't take all the memory
+const MAX_LOG_N: u8 = 17;
+/// The scrypt block size, `r`, the memory is `128 * r * N`
+const MIN_R: u32 = 8;
+const MAX_R: u32 = 8;
+/// The scrypt parallelization, `p`, every one more is another pass over the memory
+const MAX_P: u32 = 1;
+
+/// Data encrypted with a password
+///
+/// The key is derived from the password by scrypt (RFC 7914), the data is encrypted with sm4
+/// in CTR mode, and the iv and the ciphertext are authenticated by HMAC-SHA256.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EncryptedData {
+    /// Cipher of the data
+    pub cipher: String,
+    /// The key derivation, `scrypt`
+    pub kdf: String,
+    /// The scrypt cost, log2 of `N`
+    pub log_n: u8,
+    /// The scrypt block size
+    pub r: u32,
+    /// The scrypt parallelization
+    pub p: u32,
+    /// Hex of the salt of the key derivation
+    pub salt: String,
+    /// Hex of the initial counter
+    pub iv: String,
+    /// Hex of the encrypted data
+    pub ciphertext: String,
+    /// Hex of the HMAC-SHA256 of the iv and the ciphertext
+    pub mac: String,
+}
+
+impl EncryptedData {
+    /// Encrypt the data with the password
+    pub fn encrypt(data: &[u8], password: &str) -> Self {
+        let mut salt = [0u8; 32];
+        let mut iv = [0u8; 16];
+        thread_rng().fill_bytes(&mut salt);
+        thread_rng().fill_bytes(&mut iv);
+
+        let key = derive_key(password, &salt, MIN_LOG_N, MIN_R, 1)
+            .expect("The scrypt parameters of the new data are valid");
+        let ciphertext = Cipher::new(&key[..16], Mode::Ctr).encrypt(data, &iv);
+        EncryptedData {
+            cipher: "sm4-ctr".to_string(),
+            kdf: "scrypt".to_string(),
+            log_n: MIN_LOG_N,
+            r: MIN_R,
+            p: 1,
+            salt: encode(salt),
+            iv: encode(iv),
+            mac: encode(hmac_sha256(&key[16..], &iv, &ciphertext).result().code()),
+            ciphertext: encode(ciphertext),
+        }
+    }
+
+    /// Decrypt the data, fails on a wrong password
+    pub fn decrypt(&self, password: &str) -> Result<Vec<u8>, String> {
+        if self.cipher != "sm4-ctr" {
+            return Err(format!("Unsupported cipher: {}", self.cipher));
+        }
+        if self.kdf != "scrypt" {
+            return Err(format!("Unsupported key derivation: {}", self.kdf));
+        }
+        if !(MIN_LOG_N..=MAX_LOG_N).contains(&self.log_n)
+            || !(MIN_R..=MAX_R).contains(&self.r)
+            || !(1..=MAX_P).contains(&self.p)
+        {
+            return Err(format!(
+                "Unsupported scrypt parameters: log_n {}, r {}, p {}",
+                self.log_n, self.r, self.p
+            ));
+        }
+        let Fields {
+            salt,
+            iv,
+            ciphertext,
+            mac,
+        } = self.fields()?;
+        if salt.len() < 16 {
+            return Err("Invalid salt".to_string());
+        }
+
+        let key = derive_key(password, &salt, self.log_n, self.r, self.p)?;
+        hmac_sha256(&key[16..], &iv, &ciphertext)
+            .verify(&mac)
+            .map_err(|_| "Wrong password or corrupted data".to_string())?;
+        Ok(Cipher::new(&key[..16], Mode::Ctr).decrypt(&ciphertext, &iv))
+    }
+
+    /// The salt, the iv, the ciphertext and the MAC decoded
+    fn fields(&self) -> Result<Fields, String> {
+        let field = |value: &str| decode(value).map_err(|err| err.to_string());
+        let iv = field(&self.iv)?;
+        if iv.len() != 16 {
+            return Err("Invalid iv".to_string());
+        }
+        Ok(Fields {
+            salt: field(&self.salt)?,
+            iv,
+            ciphertext: field(&self.ciphertext)?,
+            mac: field(&self.mac)?,
+        })
+    }
+}
+
+/// The decoded fields of `EncryptedData`
+struct Fields {
+    salt: Vec<u8>,
+    iv: Vec<u8>,
+    ciphertext: Vec<u8>,
+    mac: Vec<u8>,
+}
+
+/// 48 bytes key, the first 16 bytes encrypt, the rest authenticates
+fn derive_key(password: &str, salt: &[u8], log_n: u8, r: u32, p: u32) -> Result<[u8; 48], String> {
+    let params = ScryptParams::new(log_n, r, p).map_err(|err| err.to_string())?;
+    let mut key = [0u8; 48];
+    scrypt(password.as_bytes(), salt, &params, &mut key).map_err(|err| err.to_string())?;
+    Ok(key)
+}
+
+/// HMAC-SHA256 (RFC 2104) of the iv and the ciphertext
+fn hmac_sha256(key: &[u8], iv: &[u8], ciphertext: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes a key of any length");
+    mac.input(iv);
+    mac.input(ciphertext);
+    mac
+}
+
+#[cfg(test)]
+mod test {
+    use super::EncryptedData;
+
+    #[test]
+    fn test_encrypt_data() {
+        let data = b"some secret keys".repeat(3);
+        let encrypted = EncryptedData::encrypt(&data, "password");
+        assert_ne!(encrypted.ciphertext, hex::encode(&data));
+        assert_eq!(encrypted.decrypt("password"), Ok(data));
+        assert!(encrypted.decrypt("wrong").is_err());
+
+        let mut weak = encrypted.clone();
+        weak.log_n = 10;
+        assert!(weak.decrypt("password").is_err());
+        // The work of a forged file is refused before the key derivation
+        for (log_n, r, p) in &[(18, 8, 1), (15, 16, 1), (15, 8, 2), (20, 32, 16)] {
+            let mut costly = encrypted.clone();
+            costly.log_n = *log_n;
+            costly.r = *r;
+            costly.p = *p;
+            assert!(costly
+                .decrypt("password")
+                .unwrap_err()
+                .starts_with("Unsupported scrypt parameters"));
+        }
+        let mut truncated = encrypted;
+        truncated.mac.truncate(8);
+        assert!(truncated.decrypt("password").is_err());
+    }
+}
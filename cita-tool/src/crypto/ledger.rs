@@ -0,0 +1,426 @@
+//! The Ledger: the APDUs of the CITA app framed into the 64 bytes reports of its HID interface,
+//! through the hidraw devices of Linux
+//!
+//! The public key of a path is got as by the Ethereum app, a transaction is signed as by the
+//! Zondax apps: the path first, then the encoded transaction in chunks, the last one marked.
+//!
+//! Experimental: no CITA app is published for the Ledger, these are the APDUs such an app is
+//! expected to take, no app installed by Ledger Live answers them.
+
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use types::H512;
+
+use crate::crypto::signer::{recoverable_signature, Signer};
+use crate::crypto::{Encryption, Hashable, Message, PubKey, Signature};
+use crate::error::ToolError;
+
+/// The path of the first account, of the coin type of Ethereum as the addresses are
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
+const HARDENED: u32 = 0x8000_0000;
+/// The most components of a path the app takes
+const MAX_DEPTH: usize = 10;
+
+const PACKET_SIZE: usize = 64;
+const CHANNEL: u16 = 0x0101;
+const TAG_APDU: u8 = 0x05;
+/// The most data of an APDU
+const CHUNK_SIZE: usize = 255;
+
+const CLA: u8 = 0xe0;
+const INS_GET_PUBLIC_KEY: u8 = 0x02;
+const INS_SIGN: u8 = 0x04;
+const P1_SILENT: u8 = 0x00;
+const P1_CONFIRM: u8 = 0x01;
+const P1_INIT: u8 = 0x00;
+const P1_ADD: u8 = 0x01;
+const P1_LAST: u8 = 0x02;
+
+const SW_OK: u16 = 0x9000;
+const SW_DENIED: u16 = 0x6985;
+const SW_LOCKED: [u16; 2] = [0x5515, 0x6b0c];
+const SW_NO_APP: [u16; 4] = [0x6d00, 0x6e00, 0x6e01, 0x6511];
+
+/// A BIP32 path, the hardened components are marked by `'`, such as `m/44'/60'/0'/0/0`
+#[derive(Debug, Clone, PartialEq)]
+pub struct DerivationPath(Vec<u32>);
+
+impl DerivationPath {
+    /// The path of the account next to this one, by the last component
+    pub fn next(&self) -> Self {
+        let mut path = self.0.clone();
+        if let Some(last) = path.last_mut() {
+            *last = last.wrapping_add(1);
+        }
+        DerivationPath(path)
+    }
+
+    /// The components in the APDU: their count and every one of them in big endian
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.0.len() as u8];
+        for index in &self.0 {
+            bytes.extend_from_slice(&index.to_be_bytes());
+        }
+        bytes
+    }
+}
+
+impl Default for DerivationPath {
+    fn default() -> Self {
+        DEFAULT_DERIVATION_PATH.parse().unwrap()
+    }
+}
+
+impl FromStr for DerivationPath {
+    type Err = String;
+
+    fn from_str(path: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid derivation path {}, such as {}",
+                path, DEFAULT_DERIVATION_PATH
+            )
+        };
+        let mut components = path.split('/');
+        if components.next() != Some("m") {
+            return Err(invalid());
+        }
+        let indexes = components
+            .map(|component| {
+                let (index, hardened) = match component
+                    .strip_suffix('\'')
+                    .or_else(|| component.strip_suffix('h'))
+                {
+                    Some(index) => (index, true),
+                    None => (component, false),
+                };
+                let index = index.parse::<u32>().map_err(|_| invalid())?;
+                match (index < HARDENED, hardened) {
+                    (false, _) => Err(invalid()),
+                    (true, true) => Ok(index | HARDENED),
+                    (true, false) => Ok(index),
+                }
+            })
+            .collect::<Result<Vec<u32>, String>>()?;
+        if indexes.is_empty() || indexes.len() > MAX_DEPTH {
+            return Err(invalid());
+        }
+        Ok(DerivationPath(indexes))
+    }
+}
+
+impl fmt::Display for DerivationPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "m")?;
+        for index in &self.0 {
+            if index & HARDENED == 0 {
+                write!(f, "/{}", index)?;
+            } else {
+                write!(f, "/{}'", index & !HARDENED)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The reports of the APDU
+fn frame(apdu: &[u8]) -> Vec<[u8; PACKET_SIZE]> {
+    let mut data = (apdu.len() as u16).to_be_bytes().to_vec();
+    data.extend_from_slice(apdu);
+    data.chunks(PACKET_SIZE - 5)
+        .enumerate()
+        .map(|(sequence, chunk)| {
+            let mut packet = [0u8; PACKET_SIZE];
+            packet[..2].copy_from_slice(&CHANNEL.to_be_bytes());
+            packet[2] = TAG_APDU;
+            packet[3..5].copy_from_slice(&(sequence as u16).to_be_bytes());
+            packet[5..5 + chunk.len()].copy_from_slice(chunk);
+            packet
+        })
+        .collect()
+}
+
+/// The response read from the reports, its status word is the last two bytes
+fn unframe<F>(mut read: F) -> Result<Vec<u8>, ToolError>
+where
+    F: FnMut() -> Result<[u8; PACKET_SIZE], ToolError>,
+{
+    let mut response = Vec::new();
+    let mut len = None;
+    let mut sequence = 0u16;
+    while len.is_none_or(|len| response.len() < len) {
+        let packet = read()?;
+        if packet[..2] != CHANNEL.to_be_bytes()
+            || packet[2] != TAG_APDU
+            || packet[3..5] != sequence.to_be_bytes()
+        {
+            return Err(ToolError::Customize(
+                "Unexpected report of the Ledger".to_string(),
+            ));
+        }
+        let data = if sequence == 0 {
+            len = Some(u16::from_be_bytes([packet[5], packet[6]]) as usize);
+            &packet[7..]
+        } else {
+            &packet[5..]
+        };
+        response.extend_from_slice(data);
+        sequence = sequence.wrapping_add(1);
+    }
+    response.truncate(len.unwrap_or_default());
+    if response.len() < 2 {
+        return Err(ToolError::Customize(
+            "No status word in the response of the Ledger".to_string(),
+        ));
+    }
+    Ok(response)
+}
+
+/// The data of the response, or the error of its status word
+fn check_status(mut response: Vec<u8>) -> Result<Vec<u8>, ToolError> {
+    let status = response.split_off(response.len() - 2);
+    let status = u16::from_be_bytes([status[0], status[1]]);
+    let reason = match status {
+        SW_OK => return Ok(response),
+        SW_DENIED => "rejected on the device".to_string(),
+        _ if SW_LOCKED.contains(&status) => "unlock the device".to_string(),
+        _ if SW_NO_APP.contains(&status) => {
+            "no CITA app is open, the Ledger support is experimental: the app isn't published"
+                .to_string()
+        }
+        _ => format!("status {:#06x}", status),
+    };
+    Err(ToolError::Customize(format!("Ledger: {}", reason)))
+}
+
+/// The hidraw devices of the APDU interfaces of the Ledgers plugged in
+#[cfg(target_os = "linux")]
+pub fn ledger_devices() -> Result<Vec<PathBuf>, ToolError> {
+    use std::fs;
+
+    /// The vendor id of Ledger, in the `HID_ID` of the uevent
+    const VENDOR: &str = ":00002C97:";
+    /// The APDU interface is of the usage page 0xffa0, the first item of its descriptor
+    const USAGE_PAGE: [u8; 3] = [0x06, 0xa0, 0xff];
+
+    let mut devices = fs::read_dir("/sys/class/hidraw")
+        .map_err(|err| ToolError::Customize(format!("Can't list the hidraw devices: {}", err)))?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            let device = entry.path().join("device");
+            let is_ledger = fs::read_to_string(device.join("uevent"))
+                .map(|uevent| {
+                    uevent
+                        .lines()
+                        .any(|line| line.starts_with("HID_ID=") && line.contains(VENDOR))
+                })
+                .unwrap_or(false);
+            is_ledger
+                && fs::read(device.join("report_descriptor"))
+                    .map(|descriptor| descriptor.starts_with(&USAGE_PAGE))
+                    .unwrap_or(false)
+        })
+        .map(|entry| Path::new("/dev").join(entry.file_name()))
+        .collect::<Vec<PathBuf>>();
+    devices.sort();
+    Ok(devices)
+}
+
+/// The hidraw devices of the APDU interfaces of the Ledgers plugged in
+#[cfg(not(target_os = "linux"))]
+pub fn ledger_devices() -> Result<Vec<PathBuf>, ToolError> {
+    Err(ToolError::Customize(
+        "The Ledger is only supported on Linux".to_string(),
+    ))
+}
+
+/// A Ledger plugged in, the APDUs are exchanged one at a time
+pub struct LedgerDevice {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl LedgerDevice {
+    /// Open the device, the first Ledger plugged in if none
+    pub fn open(path: Option<&Path>) -> Result<Self, ToolError> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => ledger_devices()?.into_iter().next().ok_or_else(|| {
+                ToolError::Customize("No Ledger is plugged in and unlocked".to_string())
+            })?,
+        };
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .map_err(|err| {
+                ToolError::Customize(format!(
+                    "Can't open the Ledger {}: {}, the udev rules of Ledger grant the access",
+                    path.display(),
+                    err
+                ))
+            })?;
+        Ok(LedgerDevice {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    /// The hidraw device
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Send the APDU and wait for its response, which may wait for the confirmation on the device
+    fn exchange(&self, ins: u8, p1: u8, data: &[u8]) -> Result<Vec<u8>, ToolError> {
+        let io_error = |err: std::io::Error| {
+            ToolError::Customize(format!("Ledger {}: {}", self.path.display(), err))
+        };
+        let mut apdu = vec![CLA, ins, p1, 0x00, data.len() as u8];
+        apdu.extend_from_slice(data);
+        let mut file = self.file.lock().unwrap();
+        for packet in frame(&apdu) {
+            // The report id 0 first, the device has only one
+            let mut report = vec![0u8];
+            report.extend_from_slice(&packet);
+            file.write_all(&report).map_err(io_error)?;
+        }
+        let response = unframe(|| {
+            let mut packet = [0u8; PACKET_SIZE];
+            file.read_exact(&mut packet).map_err(io_error)?;
+            Ok(packet)
+        })?;
+        check_status(response)
+    }
+
+    /// The public key of the path, shown on the device for the confirmation if `confirm`
+    pub fn pubkey(&self, path: &DerivationPath, confirm: bool) -> Result<H512, ToolError> {
+        let p1 = if confirm { P1_CONFIRM } else { P1_SILENT };
+        let response = self.exchange(INS_GET_PUBLIC_KEY, p1, &path.to_bytes())?;
+        // The length, 65, then the uncompressed point
+        match response.get(..66) {
+            Some([65, 0x04, point @ ..]) => Ok(H512::from(point)),
+            _ => Err(ToolError::Customize(
+                "Invalid public key of the Ledger".to_string(),
+            )),
+        }
+    }
+
+    /// Sign the encoded transaction by the key of the path after the confirmation on the device,
+    /// the signature is `v || r || s`
+    pub fn sign(&self, path: &DerivationPath, transaction: &[u8]) -> Result<Vec<u8>, ToolError> {
+        self.exchange(INS_SIGN, P1_INIT, &path.to_bytes())?;
+        let chunks = transaction.chunks(CHUNK_SIZE).collect::<Vec<&[u8]>>();
+        let mut response = Vec::new();
+        for (index, chunk) in chunks.iter().enumerate() {
+            let p1 = if index + 1 == chunks.len() {
+                P1_LAST
+            } else {
+                P1_ADD
+            };
+            response = self.exchange(INS_SIGN, p1, chunk)?;
+        }
+        if response.len() != 65 {
+            return Err(ToolError::Customize(format!(
+                "Invalid signature of the Ledger of {} bytes",
+                response.len()
+            )));
+        }
+        Ok(response)
+    }
+}
+
+/// The secp256k1 key of a path of the Ledger, every transaction is confirmed on the device
+pub struct LedgerSigner {
+    device: LedgerDevice,
+    path: DerivationPath,
+    pubkey: H512,
+}
+
+impl LedgerSigner {
+    /// The key of the path of the first Ledger plugged in
+    pub fn open(path: DerivationPath) -> Result<Self, ToolError> {
+        let device = LedgerDevice::open(None)?;
+        let pubkey = device.pubkey(&path, false)?;
+        Ok(LedgerSigner {
+            device,
+            path,
+            pubkey,
+        })
+    }
+
+    /// The derivation path of the key
+    pub fn derivation_path(&self) -> &DerivationPath {
+        &self.path
+    }
+}
+
+impl Signer for LedgerSigner {
+    fn encryption(&self) -> Encryption {
+        Encryption::Secp256k1
+    }
+
+    fn pubkey(&self) -> Result<PubKey, ToolError> {
+        Ok(PubKey::Secp256k1(self.pubkey))
+    }
+
+    fn sign(&self, _message: &Message) -> Result<Signature, ToolError> {
+        Err(ToolError::Customize(
+            "The Ledger only signs the transactions, it shows them for the confirmation"
+                .to_string(),
+        ))
+    }
+
+    fn sign_transaction(&self, transaction: &[u8]) -> Result<Signature, ToolError> {
+        let signature = self.device.sign(&self.path, transaction)?;
+        let message = transaction.crypt_hash(Encryption::Secp256k1);
+        recoverable_signature(&signature[1..], &message, &self.pubkey).map(Signature::Secp256k1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_status, frame, unframe, DerivationPath, PACKET_SIZE};
+
+    #[test]
+    fn test_ledger_apdu() {
+        let path: DerivationPath = "m/44'/60'/0'/0/0".parse().unwrap();
+        assert_eq!(path.to_string(), "m/44'/60'/0'/0/0");
+        assert_eq!(path.next().to_string(), "m/44'/60'/0'/0/1");
+        assert_eq!(path.to_bytes()[..9], [5, 0x80, 0, 0, 44, 0x80, 0, 0, 60]);
+        assert_eq!(
+            "m/44h/1".parse::<DerivationPath>().unwrap().to_string(),
+            "m/44'/1"
+        );
+        for invalid in &[
+            "",
+            "m",
+            "44'/60'",
+            "m/x",
+            "m/2147483648",
+            "m/1/2/3/4/5/6/7/8/9/10/11",
+        ] {
+            assert!(invalid.parse::<DerivationPath>().is_err(), "{}", invalid);
+        }
+
+        // A response over three reports: its length, the data and the status word
+        let mut apdu = (0..150u8).collect::<Vec<u8>>();
+        apdu.extend_from_slice(&[0x90, 0x00]);
+        let mut packets = frame(&apdu).into_iter();
+        assert_eq!(packets.len(), 3);
+        let response = unframe(|| Ok(packets.next().unwrap())).unwrap();
+        assert_eq!(response, apdu);
+        assert_eq!(check_status(response).unwrap().len(), 150);
+
+        let error = check_status(vec![0x69, 0x85]).unwrap_err().to_string();
+        assert!(error.contains("rejected"), "{}", error);
+        let error = unframe(|| Ok([0u8; PACKET_SIZE])).unwrap_err().to_string();
+        assert!(error.contains("Unexpected report"), "{}", error);
+    }
+}
@@ -0,0 +1,613 @@
+//! Keys kept in an HSM or a smartcard, driven by the PKCS#11 module of its vendor
+//!
+//! The module is loaded at runtime, only the secp256k1 keys of `CKM_ECDSA` are supported.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_uchar, c_ulong, c_void};
+use std::ptr;
+use std::sync::{Arc, Mutex};
+
+use types::H512;
+
+use crate::crypto::signer::{recoverable_signature, Signer};
+use crate::crypto::{Encryption, Message, PubKey, Signature};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+type CkRv = c_ulong;
+type CkSlotId = c_ulong;
+type CkSessionHandle = c_ulong;
+type CkObjectHandle = c_ulong;
+
+const CKR_OK: CkRv = 0;
+const CKR_CRYPTOKI_ALREADY_INITIALIZED: CkRv = 0x191;
+const CKR_USER_ALREADY_LOGGED_IN: CkRv = 0x100;
+const CKF_RW_SESSION: c_ulong = 0x2;
+const CKF_SERIAL_SESSION: c_ulong = 0x4;
+const CKU_USER: c_ulong = 1;
+const CKA_CLASS: c_ulong = 0x0;
+const CKA_LABEL: c_ulong = 0x3;
+const CKA_ID: c_ulong = 0x102;
+const CKA_KEY_TYPE: c_ulong = 0x100;
+const CKA_EC_PARAMS: c_ulong = 0x180;
+const CKA_EC_POINT: c_ulong = 0x181;
+const CKO_PUBLIC_KEY: c_ulong = 2;
+const CKO_PRIVATE_KEY: c_ulong = 3;
+const CKK_EC: c_ulong = 3;
+const CKM_ECDSA: c_ulong = 0x1041;
+
+/// The DER of the OID of secp256k1, the `CKA_EC_PARAMS` of its keys
+const SECP256K1_PARAMS: [u8; 7] = [0x06, 0x05, 0x2b, 0x81, 0x04, 0x00, 0x0a];
+
+#[repr(C)]
+#[allow(dead_code)]
+struct CkVersion {
+    major: c_uchar,
+    minor: c_uchar,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct CkTokenInfo {
+    label: [c_uchar; 32],
+    manufacturer_id: [c_uchar; 32],
+    model: [c_uchar; 16],
+    serial_number: [c_uchar; 16],
+    flags: c_ulong,
+    counts: [c_ulong; 10],
+    hardware_version: CkVersion,
+    firmware_version: CkVersion,
+    utc_time: [c_uchar; 16],
+}
+
+#[repr(C)]
+struct CkAttribute {
+    kind: c_ulong,
+    value: *mut c_void,
+    len: c_ulong,
+}
+
+#[repr(C)]
+struct CkMechanism {
+    mechanism: c_ulong,
+    parameter: *mut c_void,
+    len: c_ulong,
+}
+
+type Unused = Option<unsafe extern "C" fn()>;
+
+/// The head of `CK_FUNCTION_LIST`, up to `C_Sign`
+#[repr(C)]
+#[allow(dead_code)]
+struct FunctionList {
+    version: CkVersion,
+    initialize: unsafe extern "C" fn(*mut c_void) -> CkRv,
+    finalize: unsafe extern "C" fn(*mut c_void) -> CkRv,
+    get_info: Unused,
+    get_function_list: Unused,
+    get_slot_list: unsafe extern "C" fn(c_uchar, *mut CkSlotId, *mut c_ulong) -> CkRv,
+    get_slot_info: Unused,
+    get_token_info: unsafe extern "C" fn(CkSlotId, *mut CkTokenInfo) -> CkRv,
+    get_mechanism_list: Unused,
+    get_mechanism_info: Unused,
+    init_token: Unused,
+    init_pin: Unused,
+    set_pin: Unused,
+    open_session: unsafe extern "C" fn(
+        CkSlotId,
+        c_ulong,
+        *mut c_void,
+        *mut c_void,
+        *mut CkSessionHandle,
+    ) -> CkRv,
+    close_session: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    close_all_sessions: Unused,
+    get_session_info: Unused,
+    get_operation_state: Unused,
+    set_operation_state: Unused,
+    login: unsafe extern "C" fn(CkSessionHandle, c_ulong, *const c_uchar, c_ulong) -> CkRv,
+    logout: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    create_object: Unused,
+    copy_object: Unused,
+    destroy_object: Unused,
+    get_object_size: Unused,
+    get_attribute_value:
+        unsafe extern "C" fn(CkSessionHandle, CkObjectHandle, *mut CkAttribute, c_ulong) -> CkRv,
+    set_attribute_value: Unused,
+    find_objects_init: unsafe extern "C" fn(CkSessionHandle, *mut CkAttribute, c_ulong) -> CkRv,
+    find_objects:
+        unsafe extern "C" fn(CkSessionHandle, *mut CkObjectHandle, c_ulong, *mut c_ulong) -> CkRv,
+    find_objects_final: unsafe extern "C" fn(CkSessionHandle) -> CkRv,
+    encrypt_init: Unused,
+    encrypt: Unused,
+    encrypt_update: Unused,
+    encrypt_final: Unused,
+    decrypt_init: Unused,
+    decrypt: Unused,
+    decrypt_update: Unused,
+    decrypt_final: Unused,
+    digest_init: Unused,
+    digest: Unused,
+    digest_update: Unused,
+    digest_key: Unused,
+    digest_final: Unused,
+    sign_init: unsafe extern "C" fn(CkSessionHandle, *mut CkMechanism, CkObjectHandle) -> CkRv,
+    sign: unsafe extern "C" fn(
+        CkSessionHandle,
+        *const c_uchar,
+        c_ulong,
+        *mut c_uchar,
+        *mut c_ulong,
+    ) -> CkRv,
+}
+
+fn check(rv: CkRv, function: &str) -> Result<(), ToolError> {
+    if rv == CKR_OK {
+        return Ok(());
+    }
+    let reason = match rv {
+        0x3 => "the slot doesn't exist",
+        0x30 => "device error",
+        0x60 => "invalid key handle",
+        0x70 => "the mechanism isn't supported",
+        0xa0 => "incorrect pin",
+        0xa4 => "the pin is locked",
+        0xe0 => "no token in the slot",
+        0x101 => "not logged in",
+        _ => "",
+    };
+    Err(ToolError::Customize(format!(
+        "PKCS#11 {} failed with {:#x} {}",
+        function, rv, reason
+    )))
+}
+
+fn text(bytes: &[c_uchar]) -> String {
+    String::from_utf8_lossy(bytes).trim_end().to_owned()
+}
+
+/// A slot with a token
+#[derive(Debug, Clone, Serialize)]
+pub struct HsmSlot {
+    /// The slot id
+    pub id: u64,
+    /// The label of the token
+    pub label: String,
+    /// The manufacturer of the token
+    pub manufacturer: String,
+    /// The model of the token
+    pub model: String,
+    /// The serial number of the token
+    pub serial: String,
+}
+
+/// A public key of a token
+#[derive(Debug, Clone, Serialize)]
+pub struct HsmKey {
+    /// The slot id
+    pub slot: u64,
+    /// The label, the name the key is used by
+    pub label: String,
+    /// The `CKA_ID` in hex
+    pub id: String,
+    /// The address, None when it isn't a secp256k1 key
+    pub address: Option<String>,
+}
+
+/// A PKCS#11 module, initialized and loaded as long as it lives
+pub struct Pkcs11Module {
+    library: *mut c_void,
+    functions: *const FunctionList,
+    /// The module isn't initialized for concurrent calls, they are made one by one
+    lock: Mutex<()>,
+}
+
+// The calls are serialized by the lock
+unsafe impl Send for Pkcs11Module {}
+unsafe impl Sync for Pkcs11Module {}
+
+impl Pkcs11Module {
+    /// Load and initialize the module, e.g. `/usr/lib/softhsm/libsofthsm2.so`
+    pub fn open(path: &str) -> Result<Self, ToolError> {
+        let name = CString::new(path).map_err(|err| ToolError::Customize(err.to_string()))?;
+        unsafe {
+            let library = libc::dlopen(name.as_ptr(), libc::RTLD_NOW | libc::RTLD_LOCAL);
+            if library.is_null() {
+                let err = CStr::from_ptr(libc::dlerror() as *const c_char);
+                return Err(ToolError::Customize(format!(
+                    "Can't load the PKCS#11 module {}: {}",
+                    path,
+                    err.to_string_lossy()
+                )));
+            }
+            let symbol = libc::dlsym(library, b"C_GetFunctionList\0".as_ptr() as *const c_char);
+            if symbol.is_null() {
+                libc::dlclose(library);
+                return Err(ToolError::Customize(format!(
+                    "{} isn't a PKCS#11 module, it has no C_GetFunctionList",
+                    path
+                )));
+            }
+            let get_function_list: unsafe extern "C" fn(*mut *const FunctionList) -> CkRv =
+                std::mem::transmute(symbol);
+            let mut functions = ptr::null();
+            let rv = get_function_list(&mut functions);
+            if rv != CKR_OK || functions.is_null() {
+                libc::dlclose(library);
+                check(rv, "C_GetFunctionList")?;
+                return Err(ToolError::Customize(format!(
+                    "{} has no PKCS#11 functions",
+                    path
+                )));
+            }
+            let module = Pkcs11Module {
+                library,
+                functions,
+                lock: Mutex::new(()),
+            };
+            match ((*functions).initialize)(ptr::null_mut()) {
+                CKR_CRYPTOKI_ALREADY_INITIALIZED => {}
+                rv => check(rv, "C_Initialize")?,
+            }
+            Ok(module)
+        }
+    }
+
+    fn functions(&self) -> &FunctionList {
+        unsafe { &*self.functions }
+    }
+
+    /// The slots with a token
+    // `CK_ULONG` is `u32` on the 32-bit targets
+    #[allow(clippy::useless_conversion)]
+    pub fn slots(&self) -> Result<Vec<HsmSlot>, ToolError> {
+        let _lock = self.lock.lock().unwrap();
+        let functions = self.functions();
+        let mut count: c_ulong = 0;
+        unsafe {
+            check(
+                (functions.get_slot_list)(1, ptr::null_mut(), &mut count),
+                "C_GetSlotList",
+            )?;
+            let mut ids = vec![0; count as usize];
+            check(
+                (functions.get_slot_list)(1, ids.as_mut_ptr(), &mut count),
+                "C_GetSlotList",
+            )?;
+            ids.truncate(count as usize);
+            ids.into_iter()
+                .map(|id| {
+                    let mut info: CkTokenInfo = std::mem::zeroed();
+                    check((functions.get_token_info)(id, &mut info), "C_GetTokenInfo")?;
+                    Ok(HsmSlot {
+                        id: u64::from(id),
+                        label: text(&info.label),
+                        manufacturer: text(&info.manufacturer_id),
+                        model: text(&info.model),
+                        serial: text(&info.serial_number),
+                    })
+                })
+                .collect()
+        }
+    }
+
+    /// The public keys of the token in the slot, logged in by the pin if any
+    pub fn keys(&self, slot: u64, pin: Option<&str>) -> Result<Vec<HsmKey>, ToolError> {
+        let session = Session::open(self, slot, pin)?;
+        let _lock = self.lock.lock().unwrap();
+        session
+            .find(CKO_PUBLIC_KEY, None)?
+            .into_iter()
+            .map(|handle| {
+                let label = session.attribute(handle, CKA_LABEL)?;
+                let id = session.attribute(handle, CKA_ID)?;
+                Ok(HsmKey {
+                    slot,
+                    label: String::from_utf8_lossy(&label).into_owned(),
+                    id: hex::encode(id),
+                    address: session.secp256k1_pubkey(handle).ok().map(|pubkey| {
+                        crate::crypto::pubkey_to_address(&PubKey::Secp256k1(pubkey))
+                            .completed_lower_hex_with_0x()
+                    }),
+                })
+            })
+            .collect()
+    }
+}
+
+impl Drop for Pkcs11Module {
+    fn drop(&mut self) {
+        unsafe {
+            if !self.functions.is_null() {
+                ((*self.functions).finalize)(ptr::null_mut());
+            }
+            libc::dlclose(self.library);
+        }
+    }
+}
+
+/// A session of a token, logged out and closed when dropped
+struct Session {
+    module: *const Pkcs11Module,
+    handle: CkSessionHandle,
+    logged_in: bool,
+}
+
+impl Session {
+    fn open(module: &Pkcs11Module, slot: u64, pin: Option<&str>) -> Result<Self, ToolError> {
+        let _lock = module.lock.lock().unwrap();
+        let functions = module.functions();
+        let mut handle = 0;
+        unsafe {
+            check(
+                (functions.open_session)(
+                    slot as CkSlotId,
+                    CKF_SERIAL_SESSION | CKF_RW_SESSION,
+                    ptr::null_mut(),
+                    ptr::null_mut(),
+                    &mut handle,
+                ),
+                "C_OpenSession",
+            )?;
+        }
+        let mut session = Session {
+            module,
+            handle,
+            logged_in: false,
+        };
+        if let Some(pin) = pin {
+            let rv =
+                unsafe { (functions.login)(handle, CKU_USER, pin.as_ptr(), pin.len() as c_ulong) };
+            if rv != CKR_USER_ALREADY_LOGGED_IN {
+                check(rv, "C_Login")?;
+            }
+            session.logged_in = true;
+        }
+        Ok(session)
+    }
+
+    fn functions(&self) -> &FunctionList {
+        unsafe { (*self.module).functions() }
+    }
+
+    /// The objects of the class, and of the label if any
+    fn find(&self, class: c_ulong, label: Option<&str>) -> Result<Vec<CkObjectHandle>, ToolError> {
+        let functions = self.functions();
+        let mut class = class;
+        let mut key_type = CKK_EC;
+        let mut template = vec![
+            CkAttribute {
+                kind: CKA_CLASS,
+                value: &mut class as *mut c_ulong as *mut c_void,
+                len: std::mem::size_of::<c_ulong>() as c_ulong,
+            },
+            CkAttribute {
+                kind: CKA_KEY_TYPE,
+                value: &mut key_type as *mut c_ulong as *mut c_void,
+                len: std::mem::size_of::<c_ulong>() as c_ulong,
+            },
+        ];
+        if let Some(label) = label {
+            template.push(CkAttribute {
+                kind: CKA_LABEL,
+                value: label.as_ptr() as *mut c_void,
+                len: label.len() as c_ulong,
+            });
+        }
+        let mut objects = Vec::new();
+        unsafe {
+            check(
+                (functions.find_objects_init)(
+                    self.handle,
+                    template.as_mut_ptr(),
+                    template.len() as c_ulong,
+                ),
+                "C_FindObjectsInit",
+            )?;
+            let mut found = [0; 16];
+            loop {
+                let mut count = 0;
+                let rv = (functions.find_objects)(
+                    self.handle,
+                    found.as_mut_ptr(),
+                    found.len() as c_ulong,
+                    &mut count,
+                );
+                if rv != CKR_OK || count == 0 {
+                    (functions.find_objects_final)(self.handle);
+                    check(rv, "C_FindObjects")?;
+                    break;
+                }
+                objects.extend_from_slice(&found[..count as usize]);
+            }
+        }
+        Ok(objects)
+    }
+
+    fn attribute(&self, object: CkObjectHandle, kind: c_ulong) -> Result<Vec<u8>, ToolError> {
+        let functions = self.functions();
+        let mut attribute = CkAttribute {
+            kind,
+            value: ptr::null_mut(),
+            len: 0,
+        };
+        unsafe {
+            check(
+                (functions.get_attribute_value)(self.handle, object, &mut attribute, 1),
+                "C_GetAttributeValue",
+            )?;
+            let mut value = vec![0u8; attribute.len as usize];
+            attribute.value = value.as_mut_ptr() as *mut c_void;
+            check(
+                (functions.get_attribute_value)(self.handle, object, &mut attribute, 1),
+                "C_GetAttributeValue",
+            )?;
+            value.truncate(attribute.len as usize);
+            Ok(value)
+        }
+    }
+
+    fn secp256k1_pubkey(&self, object: CkObjectHandle) -> Result<H512, ToolError> {
+        if self.attribute(object, CKA_EC_PARAMS)? != SECP256K1_PARAMS {
+            return Err(ToolError::Customize(
+                "The key isn't a secp256k1 key".to_string(),
+            ));
+        }
+        parse_ec_point(&self.attribute(object, CKA_EC_POINT)?)
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        let functions = self.functions();
+        unsafe {
+            if self.logged_in {
+                (functions.logout)(self.handle);
+            }
+            (functions.close_session)(self.handle);
+        }
+    }
+}
+
+/// The key of the point, the `CKA_EC_POINT` is an uncompressed point, DER wrapped or not
+fn parse_ec_point(point: &[u8]) -> Result<H512, ToolError> {
+    let point = match point {
+        [0x04, 0x41, rest @ ..] if rest.len() == 65 => rest,
+        _ => point,
+    };
+    match point {
+        [0x04, key @ ..] if key.len() == 64 => Ok(H512::from(key)),
+        _ => Err(ToolError::Customize(format!(
+            "Invalid EC point 0x{}",
+            hex::encode(point)
+        ))),
+    }
+}
+
+/// A secp256k1 key of a token, it signs by `CKM_ECDSA` in a session logged in by the pin
+pub struct Pkcs11Signer {
+    // Closed before the module is dropped
+    session: Mutex<Session>,
+    module: Arc<Pkcs11Module>,
+    key: CkObjectHandle,
+    pubkey: H512,
+}
+
+// The session is only used under its lock and the one of the module
+unsafe impl Send for Pkcs11Signer {}
+unsafe impl Sync for Pkcs11Signer {}
+
+impl Pkcs11Signer {
+    /// Log in the token of the slot and find the private key and the public key of the label
+    pub fn open(
+        module: Arc<Pkcs11Module>,
+        slot: u64,
+        pin: &str,
+        label: &str,
+    ) -> Result<Self, ToolError> {
+        let session = Session::open(&module, slot, Some(pin))?;
+        let (key, pubkey) = {
+            let _lock = module.lock.lock().unwrap();
+            let not_found = |class| {
+                ToolError::Customize(format!(
+                    "No EC {} key of label {} in slot {}",
+                    class, label, slot
+                ))
+            };
+            let key = *session
+                .find(CKO_PRIVATE_KEY, Some(label))?
+                .first()
+                .ok_or_else(|| not_found("private"))?;
+            let public = *session
+                .find(CKO_PUBLIC_KEY, Some(label))?
+                .first()
+                .ok_or_else(|| not_found("public"))?;
+            (key, session.secp256k1_pubkey(public)?)
+        };
+        Ok(Pkcs11Signer {
+            session: Mutex::new(session),
+            module,
+            key,
+            pubkey,
+        })
+    }
+}
+
+impl Signer for Pkcs11Signer {
+    fn encryption(&self) -> Encryption {
+        Encryption::Secp256k1
+    }
+
+    fn pubkey(&self) -> Result<PubKey, ToolError> {
+        Ok(PubKey::Secp256k1(self.pubkey))
+    }
+
+    fn sign(&self, message: &Message) -> Result<Signature, ToolError> {
+        let session = self.session.lock().unwrap();
+        let _lock = self.module.lock.lock().unwrap();
+        let functions = self.module.functions();
+        let mut mechanism = CkMechanism {
+            mechanism: CKM_ECDSA,
+            parameter: ptr::null_mut(),
+            len: 0,
+        };
+        let mut signature = [0u8; 72];
+        let mut len = signature.len() as c_ulong;
+        unsafe {
+            check(
+                (functions.sign_init)(session.handle, &mut mechanism, self.key),
+                "C_SignInit",
+            )?;
+            check(
+                (functions.sign)(
+                    session.handle,
+                    message.0.as_ptr(),
+                    message.0.len() as c_ulong,
+                    signature.as_mut_ptr(),
+                    &mut len,
+                ),
+                "C_Sign",
+            )?;
+        }
+        recoverable_signature(&signature[..len as usize], message, &self.pubkey)
+            .map(Signature::Secp256k1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_ec_point;
+    use crate::crypto::signer::{recoverable_signature, SECP256K1_N};
+    use crate::crypto::{sign, Encryption, KeyPair, PubKey, Signature};
+    use types::{H256, U256};
+
+    #[test]
+    fn test_ecdsa_of_hsm() {
+        let key_pair = KeyPair::new(Encryption::Secp256k1);
+        let pubkey = match key_pair.pubkey() {
+            PubKey::Secp256k1(pubkey) => pubkey,
+            _ => unreachable!(),
+        };
+        let mut point = vec![0x04, 0x41, 0x04];
+        point.extend_from_slice(&pubkey.0);
+        assert_eq!(parse_ec_point(&point).unwrap(), pubkey);
+        assert_eq!(parse_ec_point(&point[2..]).unwrap(), pubkey);
+        assert!(parse_ec_point(&point[3..]).is_err());
+
+        let message = H256::from(7);
+        let expected = match sign(&key_pair.privkey(), &message) {
+            Signature::Secp256k1(signature) => signature,
+            _ => unreachable!(),
+        };
+        // An HSM gives `r || s` and may give the high s
+        let n = SECP256K1_N.parse::<U256>().unwrap();
+        let high_s = H256::from(n - U256::from(expected.s()));
+        for s in &[H256::from(expected.s()), high_s] {
+            let mut raw = expected.r().to_vec();
+            raw.extend_from_slice(&s.0);
+            let signature = recoverable_signature(&raw, &message, &pubkey).unwrap();
+            assert_eq!(signature.0.to_vec(), expected.0.to_vec());
+        }
+        assert!(recoverable_signature(&[0; 64], &message, &pubkey).is_err());
+    }
+}
@@ -0,0 +1,96 @@
+use types::Address;
+
+use crate::crypto::{
+    pubkey_to_address, sign, Encryption, Hashable, KeyPair, Message, PrivateKey, PubKey, Signature,
+};
+use crate::error::ToolError;
+
+/// The order of secp256k1
+#[cfg(any(all(feature = "pkcs11", unix), feature = "ledger"))]
+pub(crate) const SECP256K1_N: &str =
+    "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+/// What signs the transactions: a private key in memory, or a key kept by a device
+/// which never leaves it
+pub trait Signer: Send + Sync {
+    /// The algorithm of the key
+    fn encryption(&self) -> Encryption;
+
+    /// The public key
+    fn pubkey(&self) -> Result<PubKey, ToolError>;
+
+    /// Sign the hash, the signature must be recoverable by the public key
+    fn sign(&self, message: &Message) -> Result<Signature, ToolError>;
+
+    /// Sign the encoded transaction, a device showing the transaction for the confirmation
+    /// signs it instead of its hash
+    fn sign_transaction(&self, transaction: &[u8]) -> Result<Signature, ToolError> {
+        self.sign(&transaction.crypt_hash(self.encryption()))
+    }
+
+    /// The address of the key
+    fn address(&self) -> Result<Address, ToolError> {
+        Ok(pubkey_to_address(&self.pubkey()?))
+    }
+}
+
+impl Signer for PrivateKey {
+    fn encryption(&self) -> Encryption {
+        match self {
+            PrivateKey::Ed25519(_) => Encryption::Ed25519,
+            PrivateKey::Sm2(_) => Encryption::Sm2,
+            _ => Encryption::Secp256k1,
+        }
+    }
+
+    fn pubkey(&self) -> Result<PubKey, ToolError> {
+        match self {
+            PrivateKey::Null => Err(ToolError::Customize("No private key".to_string())),
+            _ => Ok(KeyPair::from_privkey(*self).pubkey()),
+        }
+    }
+
+    fn sign(&self, message: &Message) -> Result<Signature, ToolError> {
+        match self {
+            PrivateKey::Null => Err(ToolError::Customize("No private key".to_string())),
+            _ => Ok(sign(self, message)),
+        }
+    }
+}
+
+/// The recoverable signature of the `r || s` of a device, the low s and the v of which
+/// recovers the public key
+#[cfg(any(all(feature = "pkcs11", unix), feature = "ledger"))]
+pub(crate) fn recoverable_signature(
+    signature: &[u8],
+    message: &Message,
+    pubkey: &types::H512,
+) -> Result<crate::crypto::Secp256k1Signature, ToolError> {
+    use crate::crypto::Secp256k1Signature;
+    use types::{H256, U256};
+
+    if signature.len() != 64 {
+        return Err(ToolError::Customize(format!(
+            "Invalid ECDSA signature of {} bytes",
+            signature.len()
+        )));
+    }
+    let r = H256::from(&signature[..32]);
+    let mut s = U256::from(&signature[32..]);
+    let n = SECP256K1_N.parse::<U256>().unwrap();
+    if s > n / 2 {
+        s = n - s;
+    }
+    let s = H256::from(s);
+    (0..2)
+        .map(|v| Secp256k1Signature::from_rsv(&r, &s, v))
+        .find(|signature| {
+            Signature::Secp256k1(Secp256k1Signature(signature.0))
+                .recover(message)
+                .map(|recovered| recovered.to_vec() == pubkey.to_vec())
+                .unwrap_or(false)
+        })
+        .ok_or_else(|| {
+            ToolError::Customize("The signature of the device isn't of its public key".to_string())
+        })
+}
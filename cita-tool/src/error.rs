@@ -1,5 +1,6 @@
 use failure::Fail;
 use hex::FromHexError;
+#[cfg(feature = "http")]
 use hyper;
 use protobuf::error::ProtobufError;
 use serde_json;
@@ -15,6 +16,7 @@ pub enum ToolError {
     #[fail(display = "Serde_json error: {}", _0)]
     SerdeJson(serde_json::error::Error),
     /// Hyper error
+    #[cfg(feature = "http")]
     #[fail(display = "Hyper error: {}", _0)]
     Hyper(hyper::Error),
     /// ABI error
@@ -29,6 +31,9 @@ pub enum ToolError {
     /// Parse error
     #[fail(display = "Parse int error: {}", _0)]
     Parse(ParseIntError),
+    /// The call reverted, with the decoded reason
+    #[fail(display = "Execution reverted: {}", _0)]
+    Reverted(String),
     /// Customize error
     #[fail(display = "Customize error: {}", _0)]
     Customize(String),
@@ -1,4 +1,22 @@
 //! A easy-use CITA command line tool
+//!
+//! This crate is the library behind `cita-cli`, it has no terminal or command line
+//! dependencies and never prints, so it can be embedded by other services:
+//!
+//! - [`client::basic::Client`](client/basic/struct.Client.html): the jsonrpc client,
+//!   with [`ClientExt`](client/basic/trait.ClientExt.html) for the rpc methods and
+//!   [`Notice`](client/enum.Notice.html)s reported to an optional notifier
+//! - [`TransactionOptions`](client/struct.TransactionOptions.html): build transactions
+//!   with `Client::generate_transaction`
+//! - [`client::system_contract`](client/system_contract/index.html): system contract clients
+//! - `encode_input`, `decode_input`, `decode_logs` and
+//!   [`SelectorTable`](struct.SelectorTable.html): contract ABI
+//! - [`KeyPair`](enum.KeyPair.html), [`PrivateKey`](enum.PrivateKey.html) and
+//!   [`EncryptedData`](struct.EncryptedData.html): keys and the keystore
+//! - [`MultisigProposal`](struct.MultisigProposal.html): offline multisig signatures
+//!
+//! The items re-exported here follow semver, breaking changes only come with a new
+//! minor version while the crate is `0.x`.
 
 #![deny(warnings)]
 #![deny(missing_docs)]
@@ -8,30 +26,65 @@ extern crate serde_derive;
 
 /// Ethabi
 mod abi;
+/// Address checksum encoding and validation
+pub mod address;
 /// The Jsonrpc Client
 pub mod client;
 /// Encryption algorithm library
 pub mod crypto;
 /// Error of cita tool
+// The impls of the `Fail` derive are in a const block
+#[allow(non_local_definitions)]
 pub mod error;
+/// Multisig proposals and signature shares
+mod multisig;
 /// Transaction protobuf code
 pub mod protos;
 /// Request and Response type
 pub mod rpctypes;
+/// Function selector table and calldata decoding
+mod selector;
+/// Transaction encoding test vectors
+mod test_vector;
 
-pub use crate::abi::{decode_input, decode_logs, decode_params, encode_input, encode_params};
-pub use crate::client::{parse_url, remove_0x, TransactionOptions};
+pub use crate::abi::{
+    decode_input, decode_log_by_topic, decode_logs, decode_named_output, decode_params,
+    decode_revert_reason, encode_input, encode_json_input, encode_params,
+};
+pub use crate::address::{
+    is_mixed_case, parse_checksum_address, parse_hex_address, to_checksum_address,
+};
+#[cfg(feature = "http")]
+pub use crate::client::parse_url;
+pub use crate::client::{hex_to_u256, remove_0x, TransactionOptions};
 pub use crate::crypto::{
     ed25519_sign, Ed25519KeyPair, Ed25519PrivKey, Ed25519PubKey, Ed25519Signature,
 };
 pub use crate::crypto::{
-    pubkey_to_address, secp256k1_sign, sign, sm2_sign, CreateKey, Encryption, Hashable, KeyPair,
-    Message, PrivateKey, PubKey, Secp256k1KeyPair, Secp256k1PrivKey, Secp256k1PubKey, Signature,
-    Sm2KeyPair, Sm2Privkey, Sm2Pubkey, Sm2Signature,
+    hash_message, pubkey_to_address, recover_message, secp256k1_sign, sign, sign_message, sm2_sign,
+    CreateKey, EncryptedData, Encryption, Hashable, KeyPair, Message, PrivateKey, PubKey,
+    Secp256k1KeyPair, Secp256k1PrivKey, Secp256k1PubKey, Signature, Signer, Sm2KeyPair, Sm2Privkey,
+    Sm2Pubkey, Sm2Signature,
+};
+#[cfg(feature = "ledger")]
+pub use crate::crypto::{
+    ledger_devices, DerivationPath, LedgerDevice, LedgerSigner, DEFAULT_DERIVATION_PATH,
 };
+#[cfg(all(feature = "pkcs11", unix))]
+pub use crate::crypto::{HsmKey, HsmSlot, Pkcs11Module, Pkcs11Signer};
 pub use crate::error::ToolError;
-pub use crate::protos::{Crypto, SignedTransaction, Transaction, UnverifiedTransaction};
-pub use crate::rpctypes::{JsonRpcParams, JsonRpcResponse, ParamsValue, ResponseValue};
+pub use crate::multisig::{
+    CombinedSignatures, MultisigProposal, SignatureShare, MULTISIG_EXECUTE, MULTISIG_FORMAT,
+};
+pub use crate::protos::{
+    Crypto, SignedTransaction, Transaction, TransactionBuilder, TransactionVersion,
+    UnverifiedTransaction,
+};
+pub use crate::rpctypes::{
+    JsonRpcParams, JsonRpcResponse, Params, ParamsValue, Quantity, ResponseValue,
+};
+pub use crate::selector::SelectorTable;
+pub use crate::test_vector::{TestVector, TEST_VECTORS};
 pub use hex::{decode, encode};
 pub use protobuf::Message as ProtoMessage;
 pub use types::{Address, H128, H160, H256, H264, H32, H512, H520, H64};
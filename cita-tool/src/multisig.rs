@@ -0,0 +1,288 @@
+use std::collections::BTreeMap;
+
+use hex::{decode as hex_decode, encode};
+use types::U256;
+
+use crate::abi::encode_params;
+use crate::client::{hex_to_u256, remove_0x};
+use crate::crypto::{
+    pubkey_to_address, sign, Encryption, Hashable, Message, PrivateKey, Signature,
+};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+/// The format of the multisig proposal and share files of this crate
+pub const MULTISIG_FORMAT: &str = "cita-multisig/1";
+
+/// The wallet function the combined signatures are submitted to by default
+pub const MULTISIG_EXECUTE: &str = "execute(address,uint256,bytes,bytes)";
+
+/// An unsigned call of a multisig wallet contract, passed to the owners to cosign
+///
+/// The owners sign `digest`, the keccak256 of
+/// `abi.encode(wallet, to, value, keccak256(data), nonce, chainId)` without a message
+/// prefix, so the wallet verifies the signatures by `ecrecover` of the same encoding.
+/// The nonce is the wallet's own, it keeps a proposal from being executed twice.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MultisigProposal {
+    /// Always `MULTISIG_FORMAT`
+    pub format: String,
+    /// The multisig wallet contract
+    pub wallet: String,
+    /// The destination of the call made by the wallet
+    pub to: String,
+    /// Hex of the value sent by the wallet
+    pub value: String,
+    /// Hex of the calldata the wallet sends
+    pub data: String,
+    /// Hex of the wallet nonce
+    pub nonce: String,
+    /// Hex of the chain id
+    pub chain_id: String,
+    /// Signatures the wallet requires, checked when combining if present
+    #[serde(default)]
+    pub threshold: Option<usize>,
+    /// The hash signed by the owners
+    pub digest: String,
+}
+
+/// The signature of one owner over the digest of a proposal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignatureShare {
+    /// Always `MULTISIG_FORMAT`
+    pub format: String,
+    /// The digest of the proposal
+    pub digest: String,
+    /// The address of the owner
+    pub signer: String,
+    /// Hex of the signature
+    pub signature: String,
+}
+
+/// The proposal with the signatures of enough owners, ready to submit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CombinedSignatures {
+    /// The signers, in ascending order
+    pub signers: Vec<String>,
+    /// The signatures concatenated in the order of the signers
+    pub signatures: String,
+    /// The calldata of the wallet function
+    pub data: String,
+}
+
+impl MultisigProposal {
+    /// Build a proposal and its digest
+    pub fn new(
+        wallet: &str,
+        to: &str,
+        value: U256,
+        data: &str,
+        nonce: U256,
+        chain_id: U256,
+        threshold: Option<usize>,
+    ) -> Result<Self, ToolError> {
+        let mut proposal = MultisigProposal {
+            format: MULTISIG_FORMAT.to_owned(),
+            wallet: format!("0x{}", remove_0x(wallet).to_lowercase()),
+            to: format!("0x{}", remove_0x(to).to_lowercase()),
+            value: value.lower_hex_with_0x(),
+            data: format!("0x{}", remove_0x(data)),
+            nonce: nonce.lower_hex_with_0x(),
+            chain_id: chain_id.lower_hex_with_0x(),
+            threshold,
+            digest: String::new(),
+        };
+        proposal.digest = proposal.compute_digest()?.lower_hex_with_0x();
+        Ok(proposal)
+    }
+
+    /// The digest of the fields, the stored one is not trusted
+    pub fn compute_digest(&self) -> Result<Message, ToolError> {
+        if self.format != MULTISIG_FORMAT {
+            return Err(ToolError::Customize(format!(
+                "Unknown multisig format {}, expected {}",
+                self.format, MULTISIG_FORMAT
+            )));
+        }
+        let data = hex_decode(remove_0x(&self.data)).map_err(ToolError::Decode)?;
+        let uint = |hex: &str| hex_to_u256(hex).map(|value| value.to_string());
+        let types = [
+            "address", "address", "uint256", "bytes32", "uint256", "uint256",
+        ]
+        .iter()
+        .map(|kind| kind.to_string())
+        .collect::<Vec<_>>();
+        let values = [
+            remove_0x(&self.wallet).to_owned(),
+            remove_0x(&self.to).to_owned(),
+            uint(&self.value)?,
+            data.crypt_hash(Encryption::Secp256k1).lower_hex(),
+            uint(&self.nonce)?,
+            uint(&self.chain_id)?,
+        ];
+        let encoded = encode_params(&types, &values, true)?;
+        let encoded = hex_decode(encoded).map_err(ToolError::Decode)?;
+        Ok(encoded.crypt_hash(Encryption::Secp256k1))
+    }
+
+    /// Fail if the stored digest doesn't match the fields, so a tampered proposal
+    /// is not signed
+    pub fn verify(&self) -> Result<Message, ToolError> {
+        let digest = self.compute_digest()?;
+        if remove_0x(&self.digest).to_lowercase() != digest.lower_hex() {
+            return Err(ToolError::Customize(format!(
+                "The digest {} doesn't match the proposal, expected {}",
+                self.digest,
+                digest.lower_hex_with_0x()
+            )));
+        }
+        Ok(digest)
+    }
+
+    /// Sign the digest with the private key of an owner
+    pub fn cosign(&self, private_key: &PrivateKey) -> Result<SignatureShare, ToolError> {
+        let digest = self.verify()?;
+        let signature = sign(private_key, &digest);
+        let signer = signature
+            .recover(&digest)
+            .map(|pubkey| pubkey_to_address(&pubkey))
+            .map_err(ToolError::Customize)?;
+        Ok(SignatureShare {
+            format: MULTISIG_FORMAT.to_owned(),
+            digest: digest.lower_hex_with_0x(),
+            signer: signer.completed_lower_hex_with_0x(),
+            signature: format!("0x{}", signature),
+        })
+    }
+
+    /// Check the shares against the digest, drop the duplicates, and encode the call
+    /// of the wallet function, `function(to, value, data, signatures)`
+    pub fn combine(
+        &self,
+        shares: &[SignatureShare],
+        function: &str,
+    ) -> Result<CombinedSignatures, ToolError> {
+        let digest = self.verify()?;
+        let mut signatures = BTreeMap::new();
+        for share in shares {
+            if remove_0x(&share.digest).to_lowercase() != digest.lower_hex() {
+                return Err(ToolError::Customize(format!(
+                    "The share of {} signs another proposal {}",
+                    share.signer, share.digest
+                )));
+            }
+            let bytes = hex_decode(remove_0x(&share.signature)).map_err(ToolError::Decode)?;
+            let signer = Signature::from(&bytes)
+                .recover(&digest)
+                .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+                .map_err(ToolError::Customize)?;
+            if signer != share.signer.to_lowercase() {
+                return Err(ToolError::Customize(format!(
+                    "The share of {} is signed by {}",
+                    share.signer, signer
+                )));
+            }
+            signatures.insert(signer, encode(bytes));
+        }
+        if let Some(threshold) = self.threshold {
+            if signatures.len() < threshold {
+                return Err(ToolError::Customize(format!(
+                    "{} of the {} required signatures",
+                    signatures.len(),
+                    threshold
+                )));
+            }
+        }
+
+        let signature_bytes = signatures.values().cloned().collect::<String>();
+        let (name, types) = match (function.find('('), function.rfind(')')) {
+            (Some(open), Some(close)) if open < close => (
+                &function[..open],
+                function[open + 1..close]
+                    .split(',')
+                    .map(|kind| kind.trim().to_owned())
+                    .collect::<Vec<_>>(),
+            ),
+            _ => {
+                return Err(ToolError::Abi(format!(
+                    "Invalid function signature: {}",
+                    function
+                )));
+            }
+        };
+        let values = [
+            remove_0x(&self.to).to_owned(),
+            hex_to_u256(&self.value)?.to_string(),
+            remove_0x(&self.data).to_owned(),
+            signature_bytes.clone(),
+        ];
+        if types.len() != values.len() {
+            return Err(ToolError::Abi(format!(
+                "{} doesn't take (address,uint256,bytes,bytes)",
+                name
+            )));
+        }
+        let signature = format!("{}({})", name, types.join(","));
+        let selector = signature
+            .as_bytes()
+            .crypt_hash(Encryption::Secp256k1)
+            .lower_hex();
+        Ok(CombinedSignatures {
+            signers: signatures.keys().cloned().collect(),
+            signatures: format!("0x{}", signature_bytes),
+            data: format!(
+                "0x{}{}",
+                &selector[..8],
+                encode_params(&types, &values, true)?
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MultisigProposal, MULTISIG_EXECUTE};
+    use crate::crypto::{Encryption, KeyPair};
+    use types::U256;
+
+    #[test]
+    fn test_multisig_cosign_combine() {
+        let proposal = MultisigProposal::new(
+            "0xffffffffffffffffffffffffffffffffff020000",
+            "0x0000000000000000000000000000000000000001",
+            U256::from(10),
+            "0x12345678",
+            U256::from(3),
+            U256::from(1),
+            Some(2),
+        )
+        .unwrap();
+        let owners = [
+            KeyPair::new(Encryption::Secp256k1),
+            KeyPair::new(Encryption::Secp256k1),
+        ];
+        let shares = owners
+            .iter()
+            .map(|owner| proposal.cosign(&owner.privkey()).unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(proposal.combine(&shares[..1], MULTISIG_EXECUTE).is_err());
+        // The duplicates count once
+        let duplicated = vec![shares[0].clone(), shares[0].clone()];
+        assert!(proposal.combine(&duplicated, MULTISIG_EXECUTE).is_err());
+
+        let combined = proposal.combine(&shares, MULTISIG_EXECUTE).unwrap();
+        assert_eq!(combined.signers.len(), 2);
+        assert!(combined.signers[0] < combined.signers[1]);
+        assert_eq!(combined.signatures.len(), 2 + 65 * 2 * 2);
+        assert!(combined.data.starts_with("0x"));
+
+        let mut tampered = proposal.clone();
+        tampered.value = "0x64".to_owned();
+        assert!(tampered.cosign(&owners[0].privkey()).is_err());
+        let mut forged = shares[1].clone();
+        forged.signer = shares[0].signer.clone();
+        assert!(proposal.combine(&[forged], MULTISIG_EXECUTE).is_err());
+    }
+}
@@ -1,11 +1,52 @@
 #![allow(bare_trait_objects)]
+// The code generated from `proto/blockchain.proto` is checked in, the `regenerate-protos`
+// feature generates it again at build time, without `protoc`. The lints of the pinned
+// toolchain newer than the generator are allowed on it.
+#[cfg(not(feature = "regenerate-protos"))]
+#[allow(
+    renamed_and_removed_lints,
+    static_mut_refs,
+    unused_parens,
+    mismatched_lifetime_syntaxes
+)]
 pub mod blockchain;
+/// Generated at build time from `proto/blockchain.proto`
+#[cfg(feature = "regenerate-protos")]
+#[allow(
+    clippy::all,
+    dead_code,
+    missing_docs,
+    non_camel_case_types,
+    non_snake_case
+)]
+#[allow(
+    non_upper_case_globals,
+    trivial_casts,
+    unsafe_code,
+    unused_imports,
+    unused_results
+)]
+#[allow(
+    renamed_and_removed_lints,
+    static_mut_refs,
+    unused_parens,
+    mismatched_lifetime_syntaxes
+)]
+pub mod blockchain {
+    include!(concat!(env!("OUT_DIR"), "/blockchain.rs"));
+}
+/// Build transactions and show them without knowing the fields of the versions
+mod builder;
+/// The transaction formats of the protocol versions
+pub mod version;
 
 pub use self::blockchain::{Crypto, SignedTransaction, Transaction, UnverifiedTransaction};
+pub use self::builder::TransactionBuilder;
+pub use self::version::TransactionVersion;
 use crate::client::remove_0x;
 use crate::crypto::PubKey;
 use crate::crypto::{
-    pubkey_to_address, sign, Encryption, Hashable, KeyPair, PrivateKey, Signature,
+    pubkey_to_address, sign, Encryption, Hashable, KeyPair, PrivateKey, Signature, Signer,
 };
 use crate::LowerHex;
 use hex;
@@ -18,6 +59,11 @@ use types::{Address, H256, U256};
 use crate::error::ToolError;
 use std::str::FromStr;
 
+/// Order of the secp256k1 curve
+const SECP256K1_ORDER: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+/// Order of the sm2 curve
+const SM2_ORDER: &str = "fffffffeffffffffffffffffffffffff7203df6b21c6052b53bbf40939d54123";
+
 impl UnverifiedTransaction {
     /// UnverifiedTransaction as JSON Value
     pub fn to_json(&self, encryption: Encryption) -> Result<Value, String> {
@@ -62,6 +108,114 @@ impl UnverifiedTransaction {
             _ => Err("Mismatched encryption algorithm".to_string()),
         }
     }
+
+    /// The encryption of the signature, told by its length
+    pub fn encryption(&self) -> Option<Encryption> {
+        match self.get_signature().len() {
+            65 => Some(Encryption::Secp256k1),
+            96 => Some(Encryption::Ed25519),
+            128 => Some(Encryption::Sm2),
+            _ => None,
+        }
+    }
+
+    /// The sender recovered from the signature, the encryption is told by its length
+    pub fn sender(&self) -> Option<Address> {
+        self.public_key(self.encryption()?)
+            .ok()
+            .map(|pub_key| pubkey_to_address(&pub_key))
+    }
+
+    /// Recover the signer and check the signature for invalid recovery ids, out of range
+    /// values and malleability, used to audit transactions signed by third parties
+    pub fn inspect_signature(&self, encryption: Encryption) -> Result<Value, String> {
+        let bytes: Vec<u8> = self
+            .get_transaction()
+            .write_to_bytes()
+            .map_err(|e| e.to_string())?;
+        let hash = bytes.crypt_hash(encryption);
+        let signature = self.get_signature();
+        let mut issues = Vec::new();
+
+        if self.get_crypto() != Crypto::DEFAULT {
+            issues.push(format!("Unsupported crypto {:?}", self.get_crypto()));
+        }
+        let algorithm = match signature.len() {
+            65 => Some(Encryption::Secp256k1),
+            96 => Some(Encryption::Ed25519),
+            128 => Some(Encryption::Sm2),
+            _ => None,
+        };
+        match algorithm {
+            Some(algorithm) if algorithm != encryption => issues.push(format!(
+                "The signature is {} but the transaction is hashed with {}",
+                algorithm, encryption
+            )),
+            None => issues.push(format!("Unexpected signature length {}", signature.len())),
+            _ => {}
+        }
+
+        let mut components = json!({});
+        let order = match algorithm {
+            Some(Encryption::Secp256k1) => Some(SECP256K1_ORDER),
+            Some(Encryption::Sm2) => Some(SM2_ORDER),
+            _ => None,
+        };
+        if let Some(order) = order {
+            let order = U256::from_str(order).unwrap();
+            let r = U256::from(&signature[0..32]);
+            let s = U256::from(&signature[32..64]);
+            if r.is_zero() || r >= order {
+                issues.push("r is out of range".to_string());
+            }
+            if s.is_zero() || s >= order {
+                issues.push("s is out of range".to_string());
+            }
+            components = json!({
+                "r": r.completed_lower_hex_with_0x(),
+                "s": s.completed_lower_hex_with_0x(),
+            });
+            if algorithm == Some(Encryption::Secp256k1) {
+                let v = signature[64];
+                components["v"] = json!(v);
+                if s > order / 2 {
+                    issues.push(
+                        "s is in the upper half of the curve order, the signature is malleable"
+                            .to_string(),
+                    );
+                }
+                match v {
+                    0 | 1 => {}
+                    27 | 28 => issues.push(format!(
+                        "Invalid recovery id {}, it is offset by 27, expect 0 or 1",
+                        v
+                    )),
+                    _ => issues.push(format!("Invalid recovery id {}, expect 0 or 1", v)),
+                }
+            }
+        }
+
+        let (pub_key, sender) = match Signature::from(signature).recover(&hash) {
+            Ok(pub_key) => (
+                Value::String(format!("0x{}", pub_key)),
+                json!(pubkey_to_address(&pub_key)),
+            ),
+            Err(err) => {
+                issues.push(format!("Recovery failed: {}", err));
+                (Value::Null, Value::Null)
+            }
+        };
+
+        Ok(json!({
+            "algorithm": algorithm.map(|algorithm| algorithm.to_string()),
+            "message_hash": hash,
+            "signature": components,
+            "pub_key": pub_key,
+            "sender": sender,
+            "valid": issues.is_empty(),
+            "issues": issues,
+        }))
+    }
 }
 
 impl FromStr for UnverifiedTransaction {
@@ -75,6 +229,17 @@ impl FromStr for UnverifiedTransaction {
 }
 
 impl Transaction {
+    /// Hash of the transaction content, the nonce and valid_until_block are excluded,
+    /// so sending the same operation again gets the same hash
+    pub fn content_hash(&self) -> H256 {
+        let mut tx = self.clone();
+        tx.clear_nonce();
+        tx.clear_valid_until_block();
+        tx.write_to_bytes()
+            .unwrap()
+            .crypt_hash(Encryption::Secp256k1)
+    }
+
     /// Sign data
     pub fn sign(&self, sk: PrivateKey) -> SignedTransaction {
         let key_pair = KeyPair::from_privkey(sk);
@@ -85,7 +250,7 @@ impl Transaction {
         // Build SignedTransaction
         let mut signed_tx = SignedTransaction::new();
         signed_tx.set_signer(pubkey.to_vec());
-        let bytes: Vec<u8> = (&unverified_tx).write_to_bytes().unwrap();
+        let bytes: Vec<u8> = unverified_tx.write_to_bytes().unwrap();
 
         let hash = match sk {
             PrivateKey::Secp256k1(_) => bytes.crypt_hash(Encryption::Secp256k1),
@@ -117,6 +282,20 @@ impl Transaction {
         unverified_tx.set_crypto(Crypto::DEFAULT);
         unverified_tx
     }
+
+    /// Build unverified transaction signed by the signer, such as the key of an HSM
+    pub fn build_unverified_by(
+        &self,
+        signer: &dyn Signer,
+    ) -> Result<UnverifiedTransaction, ToolError> {
+        let bytes: Vec<u8> = self.write_to_bytes().map_err(ToolError::Proto)?;
+        let signature = signer.sign_transaction(&bytes)?;
+        let mut unverified_tx = UnverifiedTransaction::new();
+        unverified_tx.set_transaction(self.clone());
+        unverified_tx.set_signature(signature.to_vec());
+        unverified_tx.set_crypto(Crypto::DEFAULT);
+        Ok(unverified_tx)
+    }
 }
 
 #[cfg(test)]
@@ -130,4 +309,60 @@ mod test {
         let tx: UnverifiedTransaction = parse_from_bytes(&content).unwrap();
         assert_eq!("abce", hex::encode(&tx.transaction.get_ref().data));
     }
+
+    #[test]
+    fn test_content_hash() {
+        let mut tx = Transaction::new();
+        tx.set_to("ffffffffffffffffffffffffffffffffff010000".to_string());
+        tx.set_data(vec![0xab, 0xce]);
+        tx.set_nonce("1".to_string());
+        tx.set_valid_until_block(88);
+
+        let mut resend = tx.clone();
+        resend.set_nonce("2".to_string());
+        resend.set_valid_until_block(100);
+        assert_eq!(tx.content_hash(), resend.content_hash());
+
+        resend.set_data(vec![0xab]);
+        assert_ne!(tx.content_hash(), resend.content_hash());
+    }
+
+    #[test]
+    fn test_inspect_signature() {
+        let key_pair = KeyPair::new(Encryption::Secp256k1);
+        let mut tx = Transaction::new();
+        tx.set_data(vec![0xab, 0xce]);
+        let mut unverified = tx.build_unverified(key_pair.privkey());
+
+        let report = unverified.inspect_signature(Encryption::Secp256k1).unwrap();
+        assert_eq!(report["valid"], json!(true));
+        assert_eq!(report["sender"], json!(key_pair.address()));
+        assert_eq!(unverified.sender(), Some(key_pair.address()));
+
+        // (r, n - s) with the flipped recovery id is another valid signature
+        let mut signature = unverified.get_signature().to_vec();
+        let order = U256::from_str(SECP256K1_ORDER).unwrap();
+        let s: [u8; 32] = (order - U256::from(&signature[32..64])).into();
+        signature[32..64].copy_from_slice(&s);
+        signature[64] ^= 1;
+        unverified.set_signature(signature);
+
+        let report = unverified.inspect_signature(Encryption::Secp256k1).unwrap();
+        assert_eq!(report["valid"], json!(false));
+        assert_eq!(report["sender"], json!(key_pair.address()));
+        assert_eq!(report["issues"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_signature_encryption() {
+        let mut tx = Transaction::new();
+        tx.set_data(vec![0xab, 0xce]);
+        for encryption in &[Encryption::Secp256k1, Encryption::Ed25519, Encryption::Sm2] {
+            let key_pair = KeyPair::new(*encryption);
+            let unverified = tx.build_unverified(key_pair.privkey());
+            assert_eq!(unverified.encryption(), Some(*encryption));
+            assert_eq!(unverified.sender(), Some(key_pair.address()));
+        }
+        assert_eq!(UnverifiedTransaction::new().encryption(), None);
+    }
 }
@@ -0,0 +1,255 @@
+use std::fmt;
+
+use protobuf::Message;
+use serde_json::{json, Value};
+use types::{Address, U256};
+use uuid::Uuid;
+
+use crate::error::ToolError;
+use crate::protos::{SignedTransaction, Transaction, TransactionVersion, UnverifiedTransaction};
+use crate::LowerHex;
+
+/// Build a transaction of any version from typed values
+///
+/// The version decides which fields carry the target address and the chain id,
+/// no target address creates a contract.
+/// Example:
+/// ```rust
+/// extern crate cita_tool;
+///
+/// use cita_tool::{TransactionBuilder, TransactionVersion, U256};
+///
+/// let tx = TransactionBuilder::new(TransactionVersion::V2)
+///     .set_to(Some("ffffffffffffffffffffffffffffffffff020004".parse().unwrap()))
+///     .set_chain_id(U256::from(1))
+///     .set_value(U256::from(100))
+///     .set_valid_until_block(188)
+///     .build()
+///     .unwrap();
+/// assert!(!tx.is_contract_creation());
+/// assert_eq!(tx.get_to(), "");
+/// assert_eq!(tx.value_u256(), U256::from(100));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TransactionBuilder {
+    version: TransactionVersion,
+    to: Option<Address>,
+    data: Vec<u8>,
+    value: U256,
+    quota: u64,
+    nonce: Option<String>,
+    valid_until_block: u64,
+    chain_id: U256,
+}
+
+impl TransactionBuilder {
+    /// A contract creation of the version, without data, of quota 10_000_000
+    pub fn new(version: TransactionVersion) -> Self {
+        TransactionBuilder {
+            version,
+            to: None,
+            data: Vec::new(),
+            value: U256::zero(),
+            quota: 10_000_000,
+            nonce: None,
+            valid_until_block: 0,
+            chain_id: U256::zero(),
+        }
+    }
+
+    /// Set the target address, None creates a contract
+    pub fn set_to(mut self, to: Option<Address>) -> Self {
+        self.to = to;
+        self
+    }
+
+    /// Set the data, the code of a contract creation
+    pub fn set_data(mut self, data: Vec<u8>) -> Self {
+        self.data = data;
+        self
+    }
+
+    /// Set the value to transfer
+    pub fn set_value(mut self, value: U256) -> Self {
+        self.value = value;
+        self
+    }
+
+    /// Set the quota
+    pub fn set_quota(mut self, quota: u64) -> Self {
+        self.quota = quota;
+        self
+    }
+
+    /// Set the nonce, a random one by default
+    pub fn set_nonce(mut self, nonce: &str) -> Self {
+        self.nonce = Some(nonce.to_owned());
+        self
+    }
+
+    /// Set the last height the transaction may be packed at
+    pub fn set_valid_until_block(mut self, height: u64) -> Self {
+        self.valid_until_block = height;
+        self
+    }
+
+    /// Set the chain id, it must fit in `u32` for version 0
+    pub fn set_chain_id(mut self, chain_id: U256) -> Self {
+        self.chain_id = chain_id;
+        self
+    }
+
+    /// The transaction, the version must be enabled
+    pub fn build(&self) -> Result<Transaction, ToolError> {
+        let version = TransactionVersion::new(self.version.as_u32())?;
+        let mut tx = Transaction::new();
+        let to = self
+            .to
+            .map(|to| to.completed_lower_hex())
+            .unwrap_or_default();
+        version.set_to(&mut tx, &to)?;
+        version.set_chain_id(&mut tx, self.chain_id)?;
+        tx.set_version(version.as_u32());
+        tx.set_data(self.data.clone());
+        tx.set_value(hex::decode(self.value.completed_lower_hex()).map_err(ToolError::Decode)?);
+        tx.set_quota(self.quota);
+        tx.set_nonce(
+            self.nonce
+                .clone()
+                .unwrap_or_else(|| hex::encode(Uuid::new_v4().as_bytes())),
+        );
+        tx.set_valid_until_block(self.valid_until_block);
+        Ok(tx)
+    }
+}
+
+impl Transaction {
+    /// The target address by the version, None for a contract creation
+    pub fn to_address(&self) -> Option<Address> {
+        let to = TransactionVersion::of(self)
+            .map(|version| version.to(self))
+            .unwrap_or_default();
+        hex::decode(&to)
+            .ok()
+            .filter(|to| to.len() == 20)
+            .map(|to| Address::from(to.as_slice()))
+    }
+
+    /// Whether the transaction creates a contract, it has no target address
+    pub fn is_contract_creation(&self) -> bool {
+        self.to_address().is_none()
+    }
+
+    /// The value to transfer
+    pub fn value_u256(&self) -> U256 {
+        U256::from(self.get_value())
+    }
+
+    /// The fields as JSON, the address and the chain id are read by the version
+    pub fn to_value(&self) -> Value {
+        let version = TransactionVersion::of(self);
+        json!({
+            "to": self.to_address().map(|to| to.completed_lower_hex_with_0x()),
+            "nonce": self.get_nonce(),
+            "quota": self.get_quota(),
+            "valid_until_block": self.get_valid_until_block(),
+            "data": format!("0x{}", hex::encode(self.get_data())),
+            "value": self.value_u256().completed_lower_hex_with_0x(),
+            "chain_id": version
+                .ok()
+                .map(|version| version.chain_id(self).completed_lower_hex_with_0x()),
+            "version": self.get_version(),
+        })
+    }
+}
+
+impl fmt::Display for Transaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+impl UnverifiedTransaction {
+    /// The hex string sent by `sendRawTransaction`
+    pub fn to_hex(&self) -> Result<String, ToolError> {
+        self.write_to_bytes()
+            .map(|bytes| format!("0x{}", hex::encode(bytes)))
+            .map_err(ToolError::Proto)
+    }
+}
+
+impl fmt::Display for UnverifiedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = json!({
+            "transaction": self.get_transaction().to_value(),
+            "signature": format!("0x{}", hex::encode(self.get_signature())),
+            "sender": self.sender().map(|sender| sender.completed_lower_hex_with_0x()),
+            "crypto": format!("{:?}", self.get_crypto()),
+        });
+        write!(f, "{}", value)
+    }
+}
+
+impl fmt::Display for SignedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = json!({
+            "tx_hash": format!("0x{}", hex::encode(self.get_tx_hash())),
+            "signer": format!("0x{}", hex::encode(self.get_signer())),
+            "transaction_with_sig": serde_json::from_str::<Value>(
+                &self.get_transaction_with_sig().to_string()
+            )
+            .unwrap_or_default(),
+        });
+        write!(f, "{}", value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TransactionBuilder;
+    use crate::crypto::{Encryption, KeyPair};
+    use crate::protos::{TransactionVersion, UnverifiedTransaction};
+    use crate::LowerHex;
+    use std::str::FromStr;
+    use types::{Address, U256};
+
+    #[test]
+    fn test_transaction_builder() {
+        let to: Address = "ffffffffffffffffffffffffffffffffff020004".parse().unwrap();
+        for version in TransactionVersion::ALL.iter() {
+            let builder = TransactionBuilder::new(*version)
+                .set_chain_id(U256::from(7))
+                .set_data(vec![0xab])
+                .set_nonce("1");
+            let create = builder.build().unwrap();
+            assert!(create.is_contract_creation());
+            assert_eq!(create.to_value()["to"], serde_json::Value::Null);
+
+            let call = builder.set_to(Some(to)).build().unwrap();
+            assert_eq!(call.to_address(), Some(to));
+            assert_eq!(
+                TransactionVersion::of(&call).unwrap().chain_id(&call),
+                U256::from(7)
+            );
+            assert_eq!(
+                call.to_value()["chain_id"],
+                "0x".to_string() + &"0".repeat(63) + "7"
+            );
+        }
+        assert!(TransactionBuilder::new(TransactionVersion::V0)
+            .set_chain_id(U256::from(u64::MAX))
+            .build()
+            .is_err());
+
+        let key_pair = KeyPair::new(Encryption::Secp256k1);
+        let unverified = TransactionBuilder::new(TransactionVersion::V2)
+            .build()
+            .unwrap()
+            .build_unverified(key_pair.privkey());
+        let parsed = UnverifiedTransaction::from_str(&unverified.to_hex().unwrap()).unwrap();
+        assert_eq!(parsed.sender(), Some(key_pair.address()));
+        assert!(parsed
+            .to_string()
+            .contains(&key_pair.address().completed_lower_hex()));
+    }
+}
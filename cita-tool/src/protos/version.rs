@@ -0,0 +1,167 @@
+use hex::decode;
+use types::U256;
+
+use crate::address::check_address;
+use crate::client::remove_0x;
+use crate::error::ToolError;
+use crate::protos::Transaction;
+use crate::LowerHex;
+
+/// The transaction formats of the CITA protocol versions
+///
+/// All the versions share the `Transaction` message of `proto/blockchain.proto`,
+/// they differ in which fields carry the target address and the chain id.
+/// Building a version can be disabled by its `tx-v*` feature,
+/// parsing the transactions of any version is always supported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransactionVersion {
+    /// `to` in hex string and `chain_id` in u32
+    V0,
+    /// `to_v1` in bytes and `chain_id_v1` in 32 bytes big endian
+    V1,
+    /// The fields of V1
+    V2,
+}
+
+impl TransactionVersion {
+    /// All the known versions
+    pub const ALL: [TransactionVersion; 3] = [
+        TransactionVersion::V0,
+        TransactionVersion::V1,
+        TransactionVersion::V2,
+    ];
+
+    /// The version to build transactions with, it must be enabled
+    pub fn new(version: u32) -> Result<Self, ToolError> {
+        let version = Self::from_u32(version)?;
+        if version.is_enabled() {
+            Ok(version)
+        } else {
+            Err(ToolError::Customize(format!(
+                "Transaction version {0} is disabled, rebuild cita-tool with the `tx-v{0}` feature",
+                version.as_u32()
+            )))
+        }
+    }
+
+    /// The version of the number, whether enabled or not
+    pub fn from_u32(version: u32) -> Result<Self, ToolError> {
+        Self::ALL
+            .iter()
+            .find(|known| known.as_u32() == version)
+            .cloned()
+            .ok_or_else(|| ToolError::Customize("Invalid version".to_string()))
+    }
+
+    /// The version of a parsed transaction
+    pub fn of(tx: &Transaction) -> Result<Self, ToolError> {
+        Self::from_u32(tx.get_version())
+    }
+
+    /// The versions enabled by the features
+    pub fn enabled() -> Vec<TransactionVersion> {
+        Self::ALL
+            .iter()
+            .filter(|version| version.is_enabled())
+            .cloned()
+            .collect()
+    }
+
+    /// The number in the `version` field
+    pub fn as_u32(self) -> u32 {
+        match self {
+            TransactionVersion::V0 => 0,
+            TransactionVersion::V1 => 1,
+            TransactionVersion::V2 => 2,
+        }
+    }
+
+    /// Whether building transactions of the version is built in
+    pub fn is_enabled(self) -> bool {
+        match self {
+            TransactionVersion::V0 => cfg!(feature = "tx-v0"),
+            TransactionVersion::V1 => cfg!(feature = "tx-v1"),
+            TransactionVersion::V2 => cfg!(feature = "tx-v2"),
+        }
+    }
+
+    /// Set the target address, an empty address creates a contract
+    pub fn set_to(self, tx: &mut Transaction, address: &str) -> Result<(), ToolError> {
+        if !remove_0x(address).is_empty() {
+            check_address("address", address)?;
+        }
+        match self {
+            TransactionVersion::V0 => tx.set_to(remove_0x(address).to_string()),
+            _ => tx.set_to_v1(decode(remove_0x(address)).map_err(ToolError::Decode)?),
+        }
+        Ok(())
+    }
+
+    /// The target address in hex without `0x`, empty for a contract creation
+    pub fn to(self, tx: &Transaction) -> String {
+        match self {
+            TransactionVersion::V0 => remove_0x(tx.get_to()).to_string(),
+            _ => hex::encode(tx.get_to_v1()),
+        }
+    }
+
+    /// Set the chain id, the chain id of version 0 must fit in `u32`
+    pub fn set_chain_id(self, tx: &mut Transaction, chain_id: U256) -> Result<(), ToolError> {
+        match self {
+            TransactionVersion::V0 => {
+                if chain_id > U256::from(u32::MAX) {
+                    return Err(ToolError::Customize(
+                        "The chain id of version 0 must be u32".to_string(),
+                    ));
+                }
+                tx.set_chain_id(chain_id.low_u32());
+            }
+            _ => tx.set_chain_id_v1(
+                decode(chain_id.completed_lower_hex()).map_err(ToolError::Decode)?,
+            ),
+        }
+        Ok(())
+    }
+
+    /// The chain id signed in the transaction
+    pub fn chain_id(self, tx: &Transaction) -> U256 {
+        match self {
+            TransactionVersion::V0 => U256::from(tx.get_chain_id()),
+            _ => U256::from(tx.get_chain_id_v1()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TransactionVersion;
+    use crate::protos::Transaction;
+    use types::U256;
+
+    #[test]
+    fn test_version_fields() {
+        let address = "0xffffffffffffffffffffffffffffffffff020004";
+        for version in TransactionVersion::ALL.iter() {
+            let mut tx = Transaction::new();
+            version.set_to(&mut tx, address).unwrap();
+            version.set_chain_id(&mut tx, U256::from(7)).unwrap();
+            tx.set_version(version.as_u32());
+
+            let parsed = TransactionVersion::of(&tx).unwrap();
+            assert_eq!(parsed, *version);
+            assert_eq!(parsed.to(&tx), &address[2..]);
+            assert_eq!(parsed.chain_id(&tx), U256::from(7));
+            assert_eq!(tx.get_to().is_empty(), *version != TransactionVersion::V0);
+        }
+        assert!(TransactionVersion::V0
+            .set_chain_id(&mut Transaction::new(), U256::from(u64::MAX))
+            .is_err());
+        assert!(TransactionVersion::from_u32(3).is_err());
+        assert!(TransactionVersion::V0
+            .set_to(&mut Transaction::new(), "0x1234")
+            .is_err());
+        assert!(TransactionVersion::V2
+            .set_to(&mut Transaction::new(), &address[..41])
+            .is_err());
+    }
+}
@@ -1,6 +1,13 @@
-use std::{collections::HashMap, convert::Into, default::Default, fmt};
+use std::{collections::HashMap, convert::Into, default::Default, fmt, str::FromStr};
 
-use serde_json::{self, json};
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{self, json, Map, Number, Value};
+use types::U256;
+
+use crate::abi::decode_revert_reason;
+use crate::client::hex_to_u256;
+use crate::error::ToolError;
 
 /// JsonRpc params
 #[derive(Serialize, Deserialize, Clone)]
@@ -30,6 +37,48 @@ impl JsonRpcParams {
     pub fn get<T: Into<String>>(&self, key: T) -> Option<&ParamsValue> {
         self.extra.get(&key.into())
     }
+
+    /// A request of the method with the params
+    ///
+    /// Example:
+    /// ```rust
+    /// extern crate cita_tool;
+    /// #[macro_use]
+    /// extern crate serde_json;
+    ///
+    /// use cita_tool::{JsonRpcParams, Params};
+    ///
+    /// # fn main() {
+    /// let params = Params::from_json(json!([{ "fromBlock": "0x0", "topics": [null, ["0x01"]] }]));
+    /// let request = JsonRpcParams::request("getLogs", params.unwrap());
+    /// assert_eq!(request.method(), Some("getLogs"));
+    /// assert_eq!(request.get_json("params").unwrap()[0]["topics"][1][0], json!("0x01"));
+    /// # }
+    /// ```
+    pub fn request(method: &str, params: Params) -> Self {
+        JsonRpcParams::new()
+            .insert("method", ParamsValue::from(method))
+            .insert("params", params.into())
+    }
+
+    /// The method of the request
+    pub fn method(&self) -> Option<&str> {
+        match self.get("method") {
+            Some(ParamsValue::String(method)) => Some(method),
+            _ => None,
+        }
+    }
+
+    /// The params of the request, None if they're absent or neither a list nor a map
+    pub fn params(&self) -> Option<Params> {
+        self.get_json("params")
+            .and_then(|params| Params::from_json(params).ok())
+    }
+
+    /// Get params as json
+    pub fn get_json<T: Into<String>>(&self, key: T) -> Option<Value> {
+        self.get(key).map(Value::from)
+    }
 }
 
 impl Default for JsonRpcParams {
@@ -63,6 +112,8 @@ pub enum ParamsValue {
     String(String),
     /// Singe int parameter
     Int(u64),
+    /// The other numbers, negative or with a fraction
+    Number(Number),
     /// Multiple parameters
     List(Vec<ParamsValue>),
     /// Map of values
@@ -85,6 +136,129 @@ impl fmt::Display for ParamsValue {
     }
 }
 
+impl From<Value> for ParamsValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => ParamsValue::Null,
+            Value::Bool(value) => ParamsValue::Bool(value),
+            Value::Number(number) => match number.as_u64() {
+                Some(value) => ParamsValue::Int(value),
+                None => ParamsValue::Number(number),
+            },
+            Value::String(value) => ParamsValue::String(value),
+            Value::Array(values) => {
+                ParamsValue::List(values.into_iter().map(ParamsValue::from).collect())
+            }
+            Value::Object(map) => ParamsValue::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, ParamsValue::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl<'a> From<&'a ParamsValue> for Value {
+    fn from(value: &'a ParamsValue) -> Self {
+        match value {
+            ParamsValue::Null => Value::Null,
+            ParamsValue::Bool(value) => Value::Bool(*value),
+            ParamsValue::Int(value) => Value::from(*value),
+            ParamsValue::Number(number) => Value::Number(number.clone()),
+            ParamsValue::String(value) => Value::String(value.clone()),
+            ParamsValue::List(values) => Value::Array(values.iter().map(Value::from).collect()),
+            ParamsValue::Map(map) => Value::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<ParamsValue> for Value {
+    fn from(value: ParamsValue) -> Self {
+        Value::from(&value)
+    }
+}
+
+impl<'a> From<&'a str> for ParamsValue {
+    fn from(value: &'a str) -> Self {
+        ParamsValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for ParamsValue {
+    fn from(value: String) -> Self {
+        ParamsValue::String(value)
+    }
+}
+
+impl From<u64> for ParamsValue {
+    fn from(value: u64) -> Self {
+        ParamsValue::Int(value)
+    }
+}
+
+impl From<bool> for ParamsValue {
+    fn from(value: bool) -> Self {
+        ParamsValue::Bool(value)
+    }
+}
+
+impl<T: Into<ParamsValue>> From<Vec<T>> for ParamsValue {
+    fn from(values: Vec<T>) -> Self {
+        ParamsValue::List(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<ParamsValue>> From<Option<T>> for ParamsValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(ParamsValue::Null, Into::into)
+    }
+}
+
+/// The `params` of a jsonrpc request, by position or by name
+#[derive(Debug, Clone, PartialEq)]
+pub enum Params {
+    /// No params, sent as an empty list
+    None,
+    /// By position, a list
+    Positional(Vec<Value>),
+    /// By name, a map
+    Named(Map<String, Value>),
+}
+
+impl Params {
+    /// The params of a json list, a map or null
+    pub fn from_json(value: Value) -> Result<Self, ToolError> {
+        match value {
+            Value::Null => Ok(Params::None),
+            Value::Array(values) => Ok(Params::Positional(values)),
+            Value::Object(map) => Ok(Params::Named(map)),
+            value => Err(ToolError::Customize(format!(
+                "The params must be a list or a map, got {}",
+                value
+            ))),
+        }
+    }
+
+    /// The params as json
+    pub fn to_json(&self) -> Value {
+        match self {
+            Params::None => Value::Array(Vec::new()),
+            Params::Positional(values) => Value::Array(values.clone()),
+            Params::Named(map) => Value::Object(map.clone()),
+        }
+    }
+}
+
+impl From<Params> for ParamsValue {
+    fn from(params: Params) -> Self {
+        ParamsValue::from(params.to_json())
+    }
+}
+
 /// The value of response result or error
 #[derive(Clone, Deserialize, Serialize)]
 #[serde(untagged)]
@@ -133,6 +307,16 @@ impl JsonRpcResponse {
     pub fn is_ok(&self) -> bool {
         self.result.is_some()
     }
+
+    /// Build a successful response locally, such as merging the results of many requests
+    pub(crate) fn from_result(result: Value) -> Result<Self, ToolError> {
+        Ok(JsonRpcResponse {
+            jsonrpc: "2.0".to_string(),
+            result: Some(serde_json::from_value(result).map_err(ToolError::SerdeJson)?),
+            error: None,
+            id: 0,
+        })
+    }
 }
 
 impl fmt::Debug for JsonRpcResponse {
@@ -167,6 +351,29 @@ impl ErrorResponse {
     pub fn code(&self) -> i64 {
         self.code
     }
+
+    /// The decoded reason of a reverted call, from the revert payload in the data,
+    /// or at the end of the message
+    pub fn revert_reason(&self) -> Option<String> {
+        if let Some(ParamsValue::String(ref data)) = self.data {
+            if let Some(reason) = decode_revert_reason(data) {
+                return Some(reason);
+            }
+        }
+        self.message
+            .split(|c: char| c.is_whitespace() || c == ':')
+            .rev()
+            .find(|word| word.starts_with("0x"))
+            .and_then(decode_revert_reason)
+    }
+
+    /// The error of the reverted call with its reason, or the message
+    pub fn into_error(self) -> ToolError {
+        match self.revert_reason() {
+            Some(reason) => ToolError::Reverted(reason),
+            None => ToolError::Customize(self.message),
+        }
+    }
 }
 
 impl fmt::Debug for ErrorResponse {
@@ -180,3 +387,198 @@ impl fmt::Display for ErrorResponse {
         write!(f, "{}", json!(self))
     }
 }
+
+/// A number of a response, nodes send it as a hex string, a decimal string or an integer
+///
+/// Strings with `0x` are hex, the other strings are decimal. It is serialized
+/// as a hex string, the form of the jsonrpc quantities.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Quantity(pub U256);
+
+impl Quantity {
+    /// The quantity of a json value, `None` if it is absent or not a number
+    pub fn from_json(value: &Value) -> Option<Self> {
+        serde_json::from_value(value.clone()).ok()
+    }
+
+    /// The quantity of a params value
+    pub fn from_params(value: &ParamsValue) -> Result<Self, ToolError> {
+        match value {
+            ParamsValue::Int(value) => Ok(Quantity::from(*value)),
+            ParamsValue::String(value) => value.parse(),
+            other => Err(ToolError::Customize(format!("{} is not a number", other))),
+        }
+    }
+
+    /// The value in u64, an error if it is larger
+    pub fn as_u64(self) -> Result<u64, ToolError> {
+        if self.0 > U256::from(u64::MAX) {
+            Err(ToolError::Customize(format!(
+                "{} is larger than u64",
+                self.0
+            )))
+        } else {
+            Ok(self.0.low_u64())
+        }
+    }
+
+    /// The value in u32, an error if it is larger
+    pub fn as_u32(self) -> Result<u32, ToolError> {
+        if self.0 > U256::from(u32::MAX) {
+            Err(ToolError::Customize(format!(
+                "{} is larger than u32",
+                self.0
+            )))
+        } else {
+            Ok(self.0.low_u32())
+        }
+    }
+}
+
+impl From<u64> for Quantity {
+    fn from(value: u64) -> Self {
+        Quantity(U256::from(value))
+    }
+}
+
+impl FromStr for Quantity {
+    type Err = ToolError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.starts_with("0x") || value.starts_with("0X") {
+            hex_to_u256(value).map(Quantity)
+        } else {
+            U256::from_dec_str(value)
+                .map(Quantity)
+                .map_err(|_| ToolError::Customize(format!("Invalid quantity {:?}", value)))
+        }
+    }
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl Serialize for Quantity {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Quantity {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct QuantityVisitor;
+
+        impl<'de> Visitor<'de> for QuantityVisitor {
+            type Value = Quantity;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(
+                    f,
+                    "a hex string, a decimal string or a non-negative integer"
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Quantity, E> {
+                Ok(Quantity::from(value))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Quantity, E> {
+                if value < 0 {
+                    Err(E::invalid_value(de::Unexpected::Signed(value), &self))
+                } else {
+                    Ok(Quantity::from(value as u64))
+                }
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Quantity, E> {
+                if value < 0.0 || value.fract() != 0.0 || value >= u64::MAX as f64 {
+                    Err(E::invalid_value(de::Unexpected::Float(value), &self))
+                } else {
+                    Ok(Quantity::from(value as u64))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Quantity, E> {
+                value.parse().map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_any(QuantityVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{JsonRpcParams, Params, ParamsValue, Quantity};
+    use serde_json::{json, Value};
+    use types::U256;
+
+    #[test]
+    fn test_quantity_forms() {
+        for value in &[
+            json!("0x1a"),
+            json!("26"),
+            json!(26),
+            json!(26.0),
+            json!("0X1A"),
+        ] {
+            assert_eq!(Quantity::from_json(value), Some(Quantity::from(26)));
+        }
+        assert_eq!(Quantity::from_json(&json!("0x")), Some(Quantity::default()));
+        for value in &[json!(-1), json!(1.5), json!("1a"), json!(null), json!([])] {
+            assert_eq!(Quantity::from_json(value), None);
+        }
+        assert_eq!(
+            Quantity::from_params(&ParamsValue::String("0x10".to_string())).unwrap(),
+            Quantity::from(16)
+        );
+        let large = Quantity(U256::from(u64::MAX) + U256::from(1));
+        assert!(large.as_u64().is_err());
+        assert_eq!(json!(Quantity::from(255)), json!("0xff"));
+    }
+
+    #[test]
+    fn test_params_json() {
+        let nested = json!({
+            "fromBlock": "0x0",
+            "topics": [null, ["0x01", "0x02"]],
+            "offset": -1,
+            "ratio": 0.5,
+            "limit": 10,
+            "strict": true,
+        });
+        let value = ParamsValue::from(nested.clone());
+        assert_eq!(Value::from(&value), nested);
+        assert_eq!(
+            serde_json::from_value::<ParamsValue>(nested.clone())
+                .map(Value::from)
+                .unwrap(),
+            nested
+        );
+
+        let request =
+            JsonRpcParams::request("getLogs", Params::from_json(json!([nested])).unwrap());
+        assert_eq!(request.method(), Some("getLogs"));
+        assert_eq!(
+            request.params(),
+            Some(Params::Positional(vec![nested.clone()]))
+        );
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["params"][0]["offset"],
+            json!(-1)
+        );
+        assert_eq!(
+            JsonRpcParams::request("peerCount", Params::None).get_json("params"),
+            Some(json!([]))
+        );
+        assert!(Params::from_json(json!("0x1")).is_err());
+        assert_eq!(
+            Value::from(ParamsValue::from(vec![Some("0x01"), None])),
+            json!(["0x01", null])
+        );
+    }
+}
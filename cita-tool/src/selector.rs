@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use ethabi::param_type::{ParamType, Reader};
+use ethabi::token::Token;
+use ethabi::{decode, Contract};
+use hex::{decode as hex_decode, encode as hex_encode};
+use serde_json::{json, Value};
+
+use crate::client::remove_0x;
+use crate::crypto::{Encryption, Hashable};
+use crate::error::ToolError;
+
+/// ABI of the system contracts, bundled into the selector table
+const SYSTEM_ABIS: [&str; 16] = [
+    include_str!("../contract_abi/Admin.abi"),
+    include_str!("../contract_abi/Authorization.abi"),
+    include_str!("../contract_abi/BatchTx.abi"),
+    include_str!("../contract_abi/ChainManager.abi"),
+    include_str!("../contract_abi/EmergencyBrake.abi"),
+    include_str!("../contract_abi/Group.abi"),
+    include_str!("../contract_abi/GroupManagement.abi"),
+    include_str!("../contract_abi/NodeManager.abi"),
+    include_str!("../contract_abi/Permission.abi"),
+    include_str!("../contract_abi/PermissionManagement.abi"),
+    include_str!("../contract_abi/PriceManager.abi"),
+    include_str!("../contract_abi/QuotaManager.abi"),
+    include_str!("../contract_abi/Role.abi"),
+    include_str!("../contract_abi/RoleManagement.abi"),
+    include_str!("../contract_abi/SysConfig.abi"),
+    include_str!("../contract_abi/VersionManager.abi"),
+];
+
+/// Signatures of the common token and proxy functions
+const COMMON_SIGNATURES: [&str; 18] = [
+    "totalSupply()",
+    "balanceOf(address)",
+    "transfer(address,uint256)",
+    "transferFrom(address,address,uint256)",
+    "approve(address,uint256)",
+    "allowance(address,address)",
+    "mint(address,uint256)",
+    "burn(uint256)",
+    "ownerOf(uint256)",
+    "safeTransferFrom(address,address,uint256)",
+    "safeTransferFrom(address,address,uint256,bytes)",
+    "setApprovalForAll(address,bool)",
+    "owner()",
+    "transferOwnership(address)",
+    "renounceOwnership()",
+    "implementation()",
+    "upgradeTo(address)",
+    "upgradeToAndCall(address,bytes)",
+];
+
+/// Function signatures indexed by their 4 bytes selectors
+#[derive(Debug, Clone, Default)]
+pub struct SelectorTable {
+    signatures: HashMap<[u8; 4], Vec<(String, Vec<ParamType>)>>,
+}
+
+impl SelectorTable {
+    /// The table of the system contracts and the common functions
+    pub fn bundled() -> Self {
+        let mut table = SelectorTable::default();
+        for abi in SYSTEM_ABIS.iter() {
+            table
+                .add_abi(abi)
+                .expect("The bundled ABI of system contracts are valid");
+        }
+        for signature in COMMON_SIGNATURES.iter() {
+            table
+                .add_signature(signature)
+                .expect("The common signatures are valid");
+        }
+        table
+    }
+
+    /// Add the functions of an ABI
+    pub fn add_abi(&mut self, abi: &str) -> Result<(), ToolError> {
+        let contract =
+            Contract::load(abi.as_bytes()).map_err(|e| ToolError::Abi(format!("{}", e)))?;
+        for function in contract.functions() {
+            let params = function
+                .inputs
+                .iter()
+                .map(|param| param.kind.clone())
+                .collect();
+            self.insert(&function.name, params);
+        }
+        Ok(())
+    }
+
+    /// Add a signature such as `transfer(address,uint256)`
+    pub fn add_signature(&mut self, signature: &str) -> Result<(), ToolError> {
+        let invalid = || ToolError::Abi(format!("Invalid signature: {}", signature));
+        let start = signature.find('(').ok_or_else(invalid)?;
+        if !signature.ends_with(')') {
+            return Err(invalid());
+        }
+        let types = &signature[start + 1..signature.len() - 1];
+        let params = if types.is_empty() {
+            Vec::new()
+        } else {
+            types
+                .split(',')
+                .map(|param| Reader::read(param.trim()))
+                .collect::<Result<_, _>>()
+                .map_err(|e| ToolError::Abi(format!("{}", e)))?
+        };
+        self.insert(signature[..start].trim(), params);
+        Ok(())
+    }
+
+    /// The signatures of the selector, more than one on collisions
+    pub fn lookup(&self, selector: &[u8; 4]) -> Vec<String> {
+        self.signatures
+            .get(selector)
+            .map(|functions| {
+                functions
+                    .iter()
+                    .map(|(name, params)| signature(name, params))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Split the calldata into the selector and the arguments, decode the arguments by the
+    /// first matched signature, or into 32 bytes words if no signature matches
+    pub fn decode(&self, data: &str) -> Result<Value, ToolError> {
+        let data = hex_decode(remove_0x(data)).map_err(ToolError::Decode)?;
+        if data.len() < 4 {
+            return Err(ToolError::Customize(
+                "Calldata is shorter than a selector".to_string(),
+            ));
+        }
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(&data[..4]);
+        let arguments = &data[4..];
+
+        let functions = self.signatures.get(&selector).cloned().unwrap_or_default();
+        for (name, params) in functions {
+            if let Ok(tokens) = decode(&params, arguments) {
+                return Ok(json!({
+                    "selector": format!("0x{}", hex_encode(selector)),
+                    "signature": signature(&name, &params),
+                    "arguments": params
+                        .iter()
+                        .zip(tokens)
+                        .map(|(param, token)| json!({
+                            "type": format!("{}", param),
+                            "value": token_value(token),
+                        }))
+                        .collect::<Vec<_>>(),
+                }));
+            }
+        }
+
+        Ok(json!({
+            "selector": format!("0x{}", hex_encode(selector)),
+            "signature": Value::Null,
+            "words": arguments
+                .chunks(32)
+                .map(|word| format!("0x{}", hex_encode(word)))
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    fn insert(&mut self, name: &str, params: Vec<ParamType>) {
+        let mut selector = [0u8; 4];
+        selector.copy_from_slice(
+            &signature(name, &params)
+                .as_bytes()
+                .crypt_hash(Encryption::Secp256k1)
+                .0[..4],
+        );
+        let functions = self.signatures.entry(selector).or_default();
+        if !functions.iter().any(|(n, p)| n == name && *p == params) {
+            functions.push((name.to_owned(), params));
+        }
+    }
+}
+
+fn signature(name: &str, params: &[ParamType]) -> String {
+    let types = params
+        .iter()
+        .map(|param| format!("{}", param))
+        .collect::<Vec<_>>();
+    format!("{}({})", name, types.join(","))
+}
+
+fn token_value(token: Token) -> Value {
+    match token {
+        Token::Bool(b) => json!(b),
+        Token::String(s) => json!(s),
+        Token::Address(address) => json!(format!("0x{:x}", address)),
+        Token::Bytes(bytes) | Token::FixedBytes(bytes) => {
+            json!(format!("0x{}", hex_encode(bytes)))
+        }
+        Token::Uint(uint) => json!(uint.to_string()),
+        Token::Int(int) => {
+            if int.bit(255) {
+                json!(format!("-{}", (!int).overflowing_add(1.into()).0))
+            } else {
+                json!(int.to_string())
+            }
+        }
+        Token::Array(tokens) | Token::FixedArray(tokens) => {
+            Value::Array(tokens.into_iter().map(token_value).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SelectorTable;
+    use crate::crypto::{Encryption, Hashable};
+
+    #[test]
+    fn test_decode_calldata() {
+        let mut table = SelectorTable::bundled();
+        assert_eq!(
+            table.lookup(&[0xa9, 0x05, 0x9c, 0xbb]),
+            vec!["transfer(address,uint256)".to_string()]
+        );
+
+        let data = "0xa9059cbb\
+                    000000000000000000000000ffffffffffffffffffffffffffffffffff020001\
+                    00000000000000000000000000000000000000000000000000000000000003e8";
+        let decoded = table.decode(data).unwrap();
+        assert_eq!(decoded["signature"], "transfer(address,uint256)");
+        assert_eq!(
+            decoded["arguments"][0]["value"],
+            "0xffffffffffffffffffffffffffffffffff020001"
+        );
+        assert_eq!(decoded["arguments"][1]["value"], "1000");
+
+        let unknown = "0x12345678\
+                       000000000000000000000000000000000000000000000000000000000000002a";
+        let decoded = table.decode(unknown).unwrap();
+        assert!(decoded["signature"].is_null());
+        assert_eq!(decoded["words"].as_array().unwrap().len(), 1);
+
+        table.add_signature("answer(uint8)").unwrap();
+        let selector = "answer(uint8)".as_bytes().crypt_hash(Encryption::Secp256k1);
+        let data = format!("0x{}{}", hex::encode(&selector.0[..4]), &unknown[10..]);
+        assert_eq!(table.decode(&data).unwrap()["arguments"][0]["value"], "42");
+    }
+}
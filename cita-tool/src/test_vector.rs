@@ -0,0 +1,143 @@
+use std::str::FromStr;
+
+use hex::encode;
+use protobuf::Message as ProtoMessage;
+use types::U256;
+
+use crate::client::{remove_0x, TransactionOptions};
+use crate::crypto::{pubkey_to_address, Encryption, Hashable, PrivateKey};
+use crate::error::ToolError;
+use crate::LowerHex;
+
+/// The bundled test vectors, regenerate them with `cita-cli tx test-vector`
+pub const TEST_VECTORS: &str = include_str!("../test_vectors.json");
+
+/// A transaction built from fixed inputs, and the encoding it must produce
+///
+/// Sm2 signatures are randomized, so the `unverified` output of sm2 vectors is absent,
+/// their signatures are checked by recovering the sender instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestVector {
+    /// Name of the case
+    pub name: String,
+    /// Algorithm of the private key
+    pub encryption: String,
+    /// Hex of the private key
+    pub private_key: String,
+    /// Target address, empty to create a contract
+    pub to: String,
+    /// Hex of the data
+    pub data: String,
+    /// Hex of the value
+    pub value: String,
+    /// Quota limit
+    pub quota: u64,
+    /// The transaction is valid until 88 blocks after it
+    pub current_height: u64,
+    /// Hex of the chain id
+    pub chain_id: String,
+    /// Transaction version
+    pub version: u32,
+    /// Transaction nonce
+    pub nonce: String,
+    /// Output: hex of the protobuf `Transaction`
+    #[serde(default)]
+    pub transaction: String,
+    /// Output: hash of the `Transaction`, which is signed
+    #[serde(default)]
+    pub hash: String,
+    /// Output: address of the private key
+    #[serde(default)]
+    pub sender: String,
+    /// Output: hex of the protobuf `UnverifiedTransaction`
+    #[serde(default)]
+    pub unverified: Option<String>,
+}
+
+impl TestVector {
+    /// The bundled test vectors
+    pub fn bundled() -> Vec<TestVector> {
+        serde_json::from_str(TEST_VECTORS).expect("The bundled test vectors are valid")
+    }
+
+    /// Same inputs, with the outputs computed by this build
+    pub fn regenerate(&self) -> Result<TestVector, ToolError> {
+        let encryption = Encryption::from_str(&self.encryption).map_err(ToolError::Customize)?;
+        let private_key = PrivateKey::from_str(remove_0x(&self.private_key), encryption)
+            .map_err(ToolError::Customize)?;
+        let value = U256::from_str(remove_0x(&self.value))
+            .map_err(|e| ToolError::Customize(e.to_string()))?;
+        let chain_id = U256::from_str(remove_0x(&self.chain_id))
+            .map_err(|e| ToolError::Customize(e.to_string()))?;
+
+        let tx = TransactionOptions::new()
+            .set_code(&self.data)
+            .set_address(&self.to)
+            .set_current_height(Some(self.current_height))
+            .set_quota(Some(self.quota))
+            .set_value(Some(value))
+            .set_version(Some(self.version))
+            .set_nonce(Some(&self.nonce))
+            .build(chain_id)?;
+        let bytes = tx.write_to_bytes().map_err(ToolError::Proto)?;
+        let unverified = tx.build_unverified(private_key);
+        let sender = unverified
+            .public_key(encryption)
+            .map(|pubkey| pubkey_to_address(&pubkey).completed_lower_hex_with_0x())
+            .map_err(ToolError::Customize)?;
+
+        let mut vector = self.clone();
+        vector.hash = bytes.crypt_hash(encryption).completed_lower_hex_with_0x();
+        vector.transaction = format!("0x{}", encode(bytes));
+        vector.sender = sender;
+        vector.unverified = match encryption {
+            Encryption::Sm2 => None,
+            _ => Some(format!(
+                "0x{}",
+                encode(unverified.write_to_bytes().map_err(ToolError::Proto)?)
+            )),
+        };
+        Ok(vector)
+    }
+
+    /// Regenerate and compare the outputs, return the names of the differing ones
+    pub fn check(&self) -> Result<Vec<&'static str>, ToolError> {
+        let expected = self.regenerate()?;
+        let mut differences = Vec::new();
+        if expected.transaction != self.transaction {
+            differences.push("transaction");
+        }
+        if expected.hash != self.hash {
+            differences.push("hash");
+        }
+        if expected.sender != self.sender {
+            differences.push("sender");
+        }
+        if expected.unverified != self.unverified {
+            differences.push("unverified");
+        }
+        Ok(differences)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TestVector;
+    use crate::protos::TransactionVersion;
+
+    #[test]
+    fn test_bundled_vectors() {
+        for vector in TestVector::bundled()
+            .into_iter()
+            .filter(|vector| TransactionVersion::new(vector.version).is_ok())
+        {
+            assert_eq!(
+                vector.check().unwrap(),
+                Vec::<&str>::new(),
+                "test vector {} changed",
+                vector.name
+            );
+        }
+    }
+}
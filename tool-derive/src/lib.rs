@@ -42,13 +42,13 @@ pub fn contract(input: TokenStream) -> TokenStream {
             }
         }
     }
-    if path == "" {
+    if path.is_empty() {
         panic!("path must set");
     }
-    if address == "" {
+    if address.is_empty() {
         panic!("contract address must set");
     }
-    if trait_name == "" {
+    if trait_name.is_empty() {
         panic!("trait name must set");
     }
     // struct name
@@ -113,7 +113,7 @@ pub fn contract(input: TokenStream) -> TokenStream {
                 }
 
                 fn contract_send_tx(
-                    &mut self,
+                    &self,
                     name: &str,
                     values: &[&str],
                     quota: Option<u64>,